@@ -105,14 +105,30 @@ export default defineConfig({
           items: [
             { label: 'init', slug: 'commands/init' },
             { label: 'search', slug: 'commands/search' },
+            { label: 'check', slug: 'commands/check' },
+            { label: 'hook', slug: 'commands/hook' },
             { label: 'rename', slug: 'commands/rename' },
             { label: 'replace', slug: 'commands/replace' },
             { label: 'plan', slug: 'commands/plan' },
+            { label: 'plan-diff', slug: 'commands/plan-diff' },
+            { label: 'plan-merge', slug: 'commands/plan-merge' },
+            { label: 'replan', slug: 'commands/replan' },
+            { label: 'validate-plan', slug: 'commands/validate-plan' },
+            { label: 'preview-file', slug: 'commands/preview-file' },
+            { label: 'explain', slug: 'commands/explain' },
             { label: 'apply', slug: 'commands/apply' },
+            { label: 'keygen', slug: 'commands/keygen' },
             { label: 'undo', slug: 'commands/undo' },
             { label: 'redo', slug: 'commands/redo' },
             { label: 'status', slug: 'commands/status' },
             { label: 'history', slug: 'commands/history' },
+            { label: 'report', slug: 'commands/report' },
+            { label: 'gc', slug: 'commands/gc' },
+            { label: 'doctor', slug: 'commands/doctor' },
+            { label: 'serve', slug: 'commands/serve' },
+            { label: 'completions', slug: 'commands/completions' },
+            { label: 'config', slug: 'commands/config' },
+            { label: 'export-types', slug: 'commands/export-types' },
           ],
         },
         {
@@ -127,6 +143,8 @@ export default defineConfig({
           label: 'Reference',
           items: [
             { label: 'Configuration', slug: 'reference/configuration' },
+            { label: 'Exit Codes', slug: 'reference/exit-codes' },
+            { label: 'Porcelain Output', slug: 'reference/porcelain' },
             { label: 'Platform Support', slug: 'reference/platform-support' },
           ],
         },
@@ -0,0 +1,58 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+// Embed build metadata for `renamify version --output json`. Packagers can
+// override the git commit with RENAMIFY_GIT_COMMIT and pin the build date with
+// SOURCE_DATE_EPOCH for reproducible builds.
+fn main() {
+    println!("cargo:rerun-if-env-changed=RENAMIFY_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    for git_file in ["../.git/HEAD", "../.git/refs/heads"] {
+        if Path::new(git_file).exists() {
+            println!("cargo:rerun-if-changed={git_file}");
+        }
+    }
+
+    let git_commit = env::var("RENAMIFY_GIT_COMMIT")
+        .ok()
+        .or_else(|| command_output("git", &["rev-parse", "--short=12", "HEAD"]))
+        .unwrap_or_default();
+    println!("cargo:rustc-env=RENAMIFY_GIT_COMMIT={git_commit}");
+
+    let build_date = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(chrono::Utc::now);
+    println!(
+        "cargo:rustc-env=RENAMIFY_BUILD_DATE={}",
+        build_date.format("%Y-%m-%d")
+    );
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_default();
+    println!("cargo:rustc-env=RENAMIFY_RUSTC_VERSION={rustc_version}");
+
+    println!(
+        "cargo:rustc-env=RENAMIFY_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .map(|feature| feature.to_lowercase().replace('_', "-"))
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=RENAMIFY_FEATURES={}", features.join(","));
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let trimmed = stdout.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
@@ -1,28 +1,113 @@
 use anyhow::Result;
-use renamify_core::{apply_operation, OutputFormatter};
+use renamify_core::vcs::CommitOptions;
+use renamify_core::{
+    apply_bundle_operation, apply_check_operation, apply_operation, BackupStrategy, Durability,
+    OutputFormatter, PlanTrust, PorcelainFormatter, StageMode, TransactionScope,
+};
+use std::path::Path;
+use std::process;
 
-use crate::OutputFormat;
+use crate::cli::PorcelainOutputFormat;
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_apply(
     plan_id: Option<String>,
-    commit: bool,
+    trust: &PlanTrust,
+    bundle: Option<&Path>,
+    commit: Option<CommitOptions>,
+    stage: StageMode,
     force: bool,
-    output: OutputFormat,
+    strict: bool,
+    report_stale_paths: bool,
+    transaction_scope: TransactionScope,
+    durability: Durability,
+    backup: BackupStrategy,
+    output: PorcelainOutputFormat,
     quiet: bool,
+    working_dir: Option<&Path>,
 ) -> Result<()> {
-    let result = apply_operation(None, plan_id.as_deref(), commit, force, None)?;
+    let result = match bundle {
+        Some(bundle) => apply_bundle_operation(
+            bundle,
+            commit.as_ref(),
+            stage,
+            force,
+            durability,
+            backup,
+            working_dir,
+        )?,
+        None => apply_operation(
+            None,
+            plan_id.as_deref(),
+            trust,
+            commit.as_ref(),
+            stage,
+            force,
+            strict,
+            report_stale_paths,
+            transaction_scope,
+            durability,
+            backup,
+            working_dir,
+            None, // observer
+        )?,
+    };
 
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        PorcelainOutputFormat::Json => {
             print!("{}", result.format_json());
         },
-        OutputFormat::Summary => {
+        PorcelainOutputFormat::Porcelain => {
+            print!("{}", result.format_porcelain());
+        },
+        PorcelainOutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_apply_check(
+    plan_id: Option<String>,
+    trust: &PlanTrust,
+    strict: bool,
+    show_tree: bool,
+    use_color: bool,
+    output: PorcelainOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = apply_check_operation(
+        plan_id.as_deref(),
+        trust,
+        strict,
+        show_tree,
+        use_color && output == PorcelainOutputFormat::Summary,
+        working_dir,
+    )?;
+
+    match output {
+        PorcelainOutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        PorcelainOutputFormat::Porcelain => {
+            print!("{}", result.format_porcelain());
+        },
+        PorcelainOutputFormat::Summary => {
             if !quiet {
                 print!("{}", result.format_summary());
             }
         },
     }
 
+    if !result.is_clean() {
+        process::exit(1);
+    }
+
     Ok(())
 }
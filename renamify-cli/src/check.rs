@@ -0,0 +1,69 @@
+use anyhow::Result;
+use renamify_core::{check_operation, CheckResult, OutputFormatter, WalkOptions};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::cli::SearchOutputFormat;
+
+pub fn handle_check(
+    paths: Vec<PathBuf>,
+    forbid: Vec<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    unrestricted: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    output: SearchOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let forbidden = forbidden(forbid, working_dir);
+    let result = check_operation(
+        &forbidden,
+        paths,
+        include,
+        exclude,
+        unrestricted,
+        include_submodules,
+        walk,
+        working_dir,
+    )?;
+
+    report(&result, output, quiet);
+    Ok(())
+}
+
+/// The identifiers given with --forbid, or else the [check] section of the
+/// config file
+pub fn forbidden(forbid: Vec<String>, working_dir: Option<&Path>) -> Vec<String> {
+    if forbid.is_empty() {
+        working_dir
+            .map_or_else(renamify_core::Config::load, renamify_core::Config::load_in)
+            .unwrap_or_default()
+            .check
+            .forbid
+    } else {
+        forbid
+    }
+}
+
+/// Print `result` and exit with code 1 if it found anything
+pub fn report(result: &CheckResult, output: SearchOutputFormat, quiet: bool) {
+    match output {
+        SearchOutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        SearchOutputFormat::Github => {
+            print!("{}", result.format_github());
+        },
+        SearchOutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    if !result.passed() {
+        process::exit(1);
+    }
+}
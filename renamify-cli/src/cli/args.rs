@@ -1,20 +1,34 @@
 use clap::{Args, Parser, Subcommand};
-use std::path::PathBuf;
-
-use super::types::{OutputFormat, PreviewArg, SearchPreviewArg, StyleArg};
+use renamify_core::filters::FilterSet;
+use renamify_core::operations::plan::ExpectedCount;
+use renamify_core::suppress::FileLines;
+use renamify_core::vcs::CommitOptions;
+use renamify_core::{BackupStrategy, CoercionMode, Config, Durability};
+use std::path::{Path, PathBuf};
+
+use super::types::{
+    AcronymRenderingArg, AtomicArg, CaseMappingArg, CompletionKind, GroupByArg, MappingFormat,
+    MatchAnchorArg, OutputFormat, PlanOutputFormat, PorcelainOutputFormat, PreviewArg,
+    PreviewFileOutput, SearchOutputFormat, SearchPreviewArg, StyleArg, TransactionScopeArg,
+};
 
 /// Smart search & replace for code and files with case-aware transformations
 #[derive(Parser, Debug)]
 #[command(name = "renamify")]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    /// Only missing with --type-list, which runs on its own
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 
     /// Disable colored output
     #[arg(long, global = true, env = "NO_COLOR")]
     pub no_color: bool,
 
+    /// Print long previews directly instead of through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
     /// Reduce the level of "smart" filtering. Can be repeated up to 3 times.
     /// -u: Don't respect .gitignore files
     /// -uu: Don't respect any ignore files (.gitignore, .ignore, .rgignore, .rnignore), include hidden files
@@ -22,6 +36,63 @@ pub struct Cli {
     #[arg(short = 'u', long = "unrestricted", global = true, action = clap::ArgAction::Count, verbatim_doc_comment)]
     pub unrestricted: u8,
 
+    /// Also scan submodules and other nested repositories (linked worktrees are always skipped)
+    #[arg(long, global = true)]
+    pub include_submodules: bool,
+
+    /// Descend at most this many directories below each search path
+    #[arg(long, global = true, value_name = "NUM")]
+    pub max_depth: Option<usize>,
+
+    /// Don't cross file system boundaries, such as into NFS mounts
+    #[arg(long, global = true)]
+    pub one_file_system: bool,
+
+    /// Follow symbolic links to files and directories
+    #[arg(short = 'L', long, global = true)]
+    pub follow: bool,
+
+    /// Don't search the contents of files larger than this (e.g. 512K, 5M, 1G;
+    /// 0 for no limit). Their names are still searched, and they are listed
+    /// in the output [default: 10M]
+    #[arg(long, global = true, value_name = "SIZE", value_parser = super::types::parse_filesize)]
+    pub max_filesize: Option<u64>,
+
+    /// Skip files git tracks when a .gitignore rule matches them. They are
+    /// searched and renamed like any other tracked file by default
+    #[arg(long, global = true)]
+    pub no_tracked_ignored: bool,
+
+    /// Only search files of this type, e.g. rust, py or js (repeatable; see
+    /// --type-list)
+    #[arg(short = 't', long = "type", global = true, value_name = "TYPE", value_delimiter = ',', value_parser = super::types::parse_file_type)]
+    pub types: Vec<String>,
+
+    /// Don't search files of this type (repeatable)
+    #[arg(short = 'T', long = "type-not", global = true, value_name = "TYPE", value_delimiter = ',', value_parser = super::types::parse_file_type)]
+    pub types_not: Vec<String>,
+
+    /// Show the file types --type and --type-not accept, then exit
+    #[arg(long, exclusive = true)]
+    pub type_list: bool,
+
+    /// When apply, rename and replace sync edited files to disk: file (each
+    /// file as it is written), batch (groups of files) or end (everything
+    /// once at the end) [default: file, or config]
+    #[arg(long, global = true, value_name = "WHEN")]
+    pub durability: Option<Durability>,
+
+    /// What apply, rename and replace keep so that undo can restore edited
+    /// files: copy (a reverse patch per file), git-stash (the original content
+    /// in the git object store) or none [default: copy, or config]
+    #[arg(long, global = true, value_name = "STRATEGY")]
+    pub backup: Option<BackupStrategy>,
+
+    /// Keep no backups; same as --backup none. Undo can then only reverse
+    /// renames, so restore content edits with git
+    #[arg(long, global = true, conflicts_with = "backup")]
+    pub no_backup: bool,
+
     /// Run as if started in <path> instead of the current working directory
     #[arg(short = 'C', global = true, value_name = "PATH")]
     pub directory: Option<PathBuf>,
@@ -34,6 +105,18 @@ pub struct Cli {
     #[arg(long, global = true, conflicts_with = "auto_init")]
     pub no_auto_init: bool,
 
+    /// Don't lock or write anything in .renamify: no history, backups or saved
+    /// plan unless --plan-out points elsewhere. Commands that change files
+    /// refuse to run. Used automatically, with a warning, when .renamify is
+    /// read-only and the command only reads
+    #[arg(long, global = true, conflicts_with = "auto_init")]
+    pub no_workspace: bool,
+
+    /// Search every file again instead of skipping unchanged files that the
+    /// last plan for the same search found nothing in (.renamify/cache)
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
     /// Assume yes for all prompts
     #[arg(short = 'y', long = "yes", global = true, env = "RENAMIFY_YES")]
     pub yes: bool,
@@ -103,6 +186,11 @@ pub struct RenameFileArgs {
     /// Don't rename files or directories (equivalent to --no-rename-files --no-rename-dirs)
     #[arg(long = "no-rename-paths")]
     pub no_rename_paths: bool,
+
+    /// Allow renames to a path that differs only by case from another path
+    /// (breaks checkouts on case-insensitive filesystems such as Windows and macOS)
+    #[arg(long)]
+    pub allow_case_collisions: bool,
 }
 
 /// Common acronym arguments shared across commands
@@ -123,34 +211,117 @@ pub struct AcronymArgs {
     /// Use only these acronyms (replaces default list)
     #[arg(long, value_delimiter = ',', conflicts_with_all = ["include_acronyms", "exclude_acronyms"])]
     pub only_acronyms: Vec<String>,
+
+    /// How acronyms are written in camelCase and PascalCase replacements (overrides config)
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub acronym_rendering: Option<AcronymRenderingArg>,
+
+    /// How letters change case in variants and replacements (overrides config)
+    #[arg(long, value_enum, value_name = "MAPPING")]
+    pub case_mapping: Option<CaseMappingArg>,
 }
 
-/// Atomic identifier arguments
+/// Arguments for committing the applied changes
 #[derive(Args, Debug, Clone)]
-pub struct AtomicArgs {
-    /// Treat both search and replace terms as atomic identifiers (no word boundaries). E.g. GitHub becomes github in snake_case, not git_hub
-    #[arg(long, conflicts_with_all = ["atomic_search", "atomic_replace"])]
-    pub atomic_identifiers: bool,
-
-    /// Treat search term as atomic identifier (GitHub → github, not git_hub)
+pub struct CommitArgs {
+    /// Commit the changes with the repository's version control system (git, jj or hg).
+    /// The message comes from `commit.template` in config
     #[arg(long)]
-    pub atomic_search: bool,
+    pub commit: bool,
 
-    /// Treat replace term as atomic identifier (GitHub → github, not git_hub)
-    #[arg(long)]
-    pub atomic_replace: bool,
+    /// Add a Signed-off-by trailer to the commit (git only)
+    #[arg(long, requires = "commit")]
+    pub signoff: bool,
+
+    /// Skip pre-commit and commit-msg hooks (git only)
+    #[arg(long, requires = "commit")]
+    pub no_verify: bool,
+}
+
+impl CommitArgs {
+    /// The commit options when `--commit` was given, with the message template
+    /// from config
+    pub fn options(&self, config: &Config) -> Option<CommitOptions> {
+        self.commit.then(|| CommitOptions {
+            template: config.commit.template.clone(),
+            signoff: self.signoff,
+            no_verify: self.no_verify,
+        })
+    }
+}
+
+/// Arguments for saving and reusing named filter sets
+#[derive(Args, Debug, Clone)]
+pub struct SavedFilterArgs {
+    /// Add the filters saved under this name in .renamify/filters/ to the ones given here
+    #[arg(long, value_name = "NAME")]
+    pub filters: Option<String>,
+
+    /// Save the include, exclude and match exclusion filters of this run under this name
+    #[arg(long, value_name = "NAME")]
+    pub save_filters: Option<String>,
+}
+
+impl SavedFilterArgs {
+    /// Run `command` with the command line filters added to the saved set,
+    /// then save the filters it ran with if it succeeded
+    pub fn run(
+        &self,
+        working_dir: Option<&Path>,
+        cli_filters: FilterSet,
+        command: impl FnOnce(FilterSet) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let renamify_dir = &working_dir.map_or_else(
+            || Path::new(".renamify").to_path_buf(),
+            |dir| dir.join(".renamify"),
+        );
+        let filters = match &self.filters {
+            Some(name) => FilterSet::load(renamify_dir, name)?.merged(cli_filters),
+            None => cli_filters,
+        };
+        command(filters.clone())?;
+        if let Some(name) = &self.save_filters {
+            let path = filters.save(renamify_dir, name)?;
+            eprintln!("Saved filters '{name}' to {}", path.display());
+        }
+        Ok(())
+    }
+}
 
-    /// Override config: allow word boundary detection for both terms
-    #[arg(long, conflicts_with = "atomic_identifiers")]
-    pub no_atomic_identifiers: bool,
+/// Atomic identifier arguments
+#[derive(Args, Debug, Clone)]
+pub struct AtomicArgs {
+    /// Treat terms as atomic identifiers, with no word boundaries (GitHub → github, not git_hub). A bare --atomic means both
+    #[arg(
+        long,
+        value_enum,
+        value_name = "TERMS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "both"
+    )]
+    pub atomic: Option<AtomicArg>,
 
-    /// Override config for search: allow word boundaries
-    #[arg(long, conflicts_with = "atomic_search")]
-    pub no_atomic_search: bool,
+    /// Ignore the atomic identifiers listed in config
+    #[arg(long)]
+    pub no_atomic: bool,
+}
 
-    /// Override config for replace: allow word boundaries
-    #[arg(long, conflicts_with = "atomic_replace")]
-    pub no_atomic_replace: bool,
+impl AtomicArgs {
+    /// Combine the flags with the `atomic` identifiers from config
+    pub fn to_config(&self, config_atomics: Vec<String>) -> renamify_core::atomic::AtomicConfig {
+        let config_atomics = if self.no_atomic {
+            Vec::new()
+        } else {
+            config_atomics
+        };
+        renamify_core::atomic::AtomicConfig::from_flags_and_config(
+            self.atomic == Some(AtomicArg::Both),
+            self.atomic == Some(AtomicArg::Search),
+            self.atomic == Some(AtomicArg::Replace),
+            config_atomics,
+        )
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -172,6 +343,11 @@ pub enum Commands {
         /// Configure global excludes file if it doesn't exist
         #[arg(long, requires = "global")]
         configure_global: bool,
+
+        /// Find dependency and build output directories (node_modules, dist,
+        /// target, coverage, ...) and offer to add them to .rnignore
+        #[arg(long, conflicts_with = "check")]
+        suggest_ignores: bool,
     },
 
     /// Search for identifiers without creating a plan
@@ -183,6 +359,11 @@ pub enum Commands {
         #[arg(help = "Search paths (files or directories)")]
         paths: Vec<PathBuf>,
 
+        /// Show what each match and path would become if renamed to this
+        /// identifier, without creating a plan
+        #[arg(long, value_name = "NEW")]
+        replace: Option<String>,
+
         /// Include glob patterns
         #[arg(long, value_delimiter = ',')]
         include: Vec<String>,
@@ -199,6 +380,14 @@ pub enum Commands {
         #[arg(long, default_value_t = true)]
         rename_dirs: bool,
 
+        /// Only search file and directory names; file contents are never read
+        #[arg(long)]
+        paths_only: bool,
+
+        /// Only search file contents; the tree is never walked for file or directory names
+        #[arg(long, conflicts_with = "paths_only")]
+        contents_only: bool,
+
         #[command(flatten)]
         styles: StyleArgs,
 
@@ -206,6 +395,49 @@ pub enum Commands {
         #[arg(long)]
         exclude_matching_lines: Option<String>,
 
+        /// Exclude lines of one file, as PATH:N or PATH:N-M (may be repeated)
+        #[arg(long, value_name = "PATH:LINES")]
+        exclude_lines_in_file: Vec<FileLines>,
+
+        #[command(flatten)]
+        saved_filters: SavedFilterArgs,
+
+        /// Skip matches inside string literals in code files (identifiers are still renamed)
+        #[arg(long, conflicts_with = "only_strings")]
+        skip_strings: bool,
+
+        /// Only change string literals and comments in code files, leaving identifiers and paths alone
+        #[arg(long)]
+        only_strings: bool,
+
+        /// Only match where the search term leads, ends or makes up the whole identifier
+        #[arg(long, value_enum, default_value = "any", value_name = "ANCHOR")]
+        match_anchor: MatchAnchorArg,
+
+        /// In YAML, JSON and TOML files, only change keys
+        #[arg(long, conflicts_with = "only_values")]
+        only_keys: bool,
+
+        /// In YAML, JSON and TOML files, only change values
+        #[arg(long)]
+        only_values: bool,
+
+        /// In YAML, JSON and TOML files, only change matches at or under these dotted paths (e.g. spec.template.metadata.name)
+        #[arg(long, value_delimiter = ',', value_name = "PATH")]
+        yaml_path: Vec<String>,
+
+        /// Apply a named profile from the config, or a built-in one such as k8s
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// List the matches that filters left out, with the reason for each
+        #[arg(long)]
+        show_skipped: bool,
+
+        /// Also list near-miss spellings within this edit distance, e.g. old_nmae for old_name
+        #[arg(long, value_name = "DISTANCE", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=3))]
+        fuzzy: u8,
+
         /// Preview output format (defaults from config if not specified)
         #[arg(long, value_enum)]
         preview: Option<SearchPreviewArg>,
@@ -214,18 +446,79 @@ pub enum Commands {
         #[arg(long)]
         fixed_table_width: bool,
 
+        /// Group the table or matches preview by file or by variant
+        #[arg(long, value_enum, default_value = "file")]
+        group_by: GroupByArg,
+
+        /// Show only the first N matches of each file in the diff or matches
+        /// preview; totals still cover every match
+        #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        preview_sample: Option<usize>,
+
         #[command(flatten)]
         acronyms: AcronymArgs,
 
-        /// Output format for machine consumption
+        #[command(flatten)]
+        atomic: AtomicArgs,
+
+        /// Output format for machine consumption (github prints GitHub Actions annotations)
         #[arg(long, value_enum, default_value = "summary")]
-        output: OutputFormat,
+        output: SearchOutputFormat,
+
+        /// Print the number of matches in each file as path:count
+        #[arg(short = 'c', long, group = "listing", conflicts_with = "output")]
+        count: bool,
+
+        /// Print only the paths of files with at least one match
+        #[arg(short = 'l', long, group = "listing", conflicts_with = "output")]
+        files_with_matches: bool,
+
+        /// Print only the paths of searched files without a match
+        #[arg(long, group = "listing", conflicts_with = "output")]
+        files_without_match: bool,
+
+        /// Print each match as a JSON object on its own line, followed by a summary
+        #[arg(long, group = "listing", conflicts_with = "output")]
+        json: bool,
 
         /// Suppress all output (alias for --preview none)
         #[arg(long)]
         quiet: bool,
     },
 
+    /// Fail if forbidden identifiers appear in any case variant (for CI policy checks)
+    Check {
+        /// Paths to check (files or directories). Defaults to current directory
+        #[arg(help = "Paths to check (files or directories)")]
+        paths: Vec<PathBuf>,
+
+        /// Forbidden identifier (repeatable; defaults to [check] forbid in config)
+        #[arg(long, value_delimiter = ',')]
+        forbid: Vec<String>,
+
+        /// Include glob patterns
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Exclude glob patterns
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Output format for machine consumption (github prints GitHub Actions annotations)
+        #[arg(long, value_enum, default_value = "summary")]
+        output: SearchOutputFormat,
+
+        /// Suppress all output (exit code only)
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Run as a git hook, e.g. `renamify hook pre-commit` in .git/hooks/pre-commit
+    Hook {
+        #[command(subcommand)]
+        command: HookCommand,
+    },
+
     /// Plan and apply a renaming in one step (with confirmation)
     Rename {
         /// Old identifier to replace
@@ -244,6 +537,14 @@ pub enum Commands {
         #[command(flatten)]
         rename_files: RenameFileArgs,
 
+        /// Only rename files and directories; file contents are never read or changed
+        #[arg(long, conflicts_with = "no_rename_paths")]
+        paths_only: bool,
+
+        /// Only change file contents; the tree is never walked for file or directory renames
+        #[arg(long, conflicts_with = "paths_only")]
+        contents_only: bool,
+
         #[command(flatten)]
         styles: StyleArgs,
 
@@ -255,18 +556,80 @@ pub enum Commands {
         #[arg(long)]
         exclude_matching_lines: Option<String>,
 
+        /// Exclude lines of one file, as PATH:N or PATH:N-M (may be repeated)
+        #[arg(long, value_name = "PATH:LINES")]
+        exclude_lines_in_file: Vec<FileLines>,
+
+        #[command(flatten)]
+        saved_filters: SavedFilterArgs,
+
+        /// Skip matches inside string literals in code files (identifiers are still renamed)
+        #[arg(long, conflicts_with = "only_strings")]
+        skip_strings: bool,
+
+        /// Only change string literals and comments in code files, leaving identifiers and paths alone
+        #[arg(long)]
+        only_strings: bool,
+
+        /// Only match where the search term leads, ends or makes up the whole identifier
+        #[arg(long, value_enum, default_value = "any", value_name = "ANCHOR")]
+        match_anchor: MatchAnchorArg,
+
+        /// Allow a search term that is a single short or common word, such as `id` or `data`
+        #[arg(long)]
+        allow_short_search: bool,
+
+        /// How replacements are restyled to fit their surroundings: auto, off or force:<style>
+        #[arg(long, value_name = "MODE", default_value = "auto")]
+        coerce: CoercionMode,
+
+        /// In YAML, JSON and TOML files, only change keys
+        #[arg(long, conflicts_with = "only_values")]
+        only_keys: bool,
+
+        /// In YAML, JSON and TOML files, only change values
+        #[arg(long)]
+        only_values: bool,
+
+        /// In YAML, JSON and TOML files, only change matches at or under these dotted paths (e.g. spec.template.metadata.name)
+        #[arg(long, value_delimiter = ',', value_name = "PATH")]
+        yaml_path: Vec<String>,
+
+        /// Apply a named profile from the config, or a built-in one such as k8s
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Exchange the two terms: OLD becomes NEW and existing NEW becomes OLD
+        #[arg(long)]
+        swap: bool,
+
         /// Show preview before confirmation prompt
         #[arg(long, value_enum)]
         preview: Option<PreviewArg>,
 
-        /// Commit changes to git after applying
-        #[arg(long)]
-        commit: bool,
+        #[command(flatten)]
+        commit: CommitArgs,
 
-        /// Acknowledge large changes (>500 files or >100 renames)
+        /// Acknowledge large changes (more files or renames than the large-change thresholds)
         #[arg(long)]
         large: bool,
 
+        /// Number of changed files above which --large is required [default: 500, or config]
+        #[arg(long, value_name = "N")]
+        large_files_threshold: Option<usize>,
+
+        /// Number of renames above which --large is required [default: 100, or config]
+        #[arg(long, value_name = "N")]
+        large_renames_threshold: Option<usize>,
+
+        /// Apply without prompting only if the plan has N matches, give or take TOLERANCE
+        #[arg(long, value_name = "N[:TOLERANCE]")]
+        expect_matches: Option<ExpectedCount>,
+
+        /// Apply without prompting only if the plan has N renames, give or take TOLERANCE
+        #[arg(long, value_name = "N[:TOLERANCE]")]
+        expect_renames: Option<ExpectedCount>,
+
         /// Force apply even with conflicts
         #[arg(long)]
         force_with_conflicts: bool,
@@ -328,18 +691,33 @@ pub enum Commands {
         #[arg(long)]
         exclude_matching_lines: Option<String>,
 
+        /// Exclude lines of one file, as PATH:N or PATH:N-M (may be repeated)
+        #[arg(long, value_name = "PATH:LINES")]
+        exclude_lines_in_file: Vec<FileLines>,
+
+        /// How replacements are restyled to fit their surroundings: off, auto or force:<style>
+        #[arg(long, value_name = "MODE", default_value = "off")]
+        coerce: CoercionMode,
+
         /// Show preview before confirmation prompt
         #[arg(long, value_enum)]
         preview: Option<PreviewArg>,
 
-        /// Commit changes to git after applying
-        #[arg(long)]
-        commit: bool,
+        #[command(flatten)]
+        commit: CommitArgs,
 
-        /// Acknowledge large changes (>500 files or >100 renames)
+        /// Acknowledge large changes (more files or renames than the large-change thresholds)
         #[arg(long)]
         large: bool,
 
+        /// Number of changed files above which --large is required [default: 500, or config]
+        #[arg(long, value_name = "N")]
+        large_files_threshold: Option<usize>,
+
+        /// Number of renames above which --large is required [default: 100, or config]
+        #[arg(long, value_name = "N")]
+        large_renames_threshold: Option<usize>,
+
         /// Force apply even with conflicts
         #[arg(long)]
         force_with_conflicts: bool,
@@ -377,12 +755,31 @@ pub enum Commands {
         #[arg(help = "Search paths (files or directories)")]
         paths: Vec<PathBuf>,
 
+        /// Plan the content piped on stdin instead of the files on disk, as if it
+        /// were the file at --stdin-path
+        #[arg(long, requires = "stdin_path", conflicts_with_all = ["paths", "swap", "split_by_codeowners", "paths_only"])]
+        stdin: bool,
+
+        /// Path the content on stdin stands for, relative to the working
+        /// directory. Hunks name it and its extension decides how the content
+        /// is parsed; it doesn't have to exist
+        #[arg(long, value_name = "PATH", requires = "stdin")]
+        stdin_path: Option<PathBuf>,
+
         #[command(flatten)]
         filter: FilterArgs,
 
         #[command(flatten)]
         rename_files: RenameFileArgs,
 
+        /// Only rename files and directories; file contents are never read or changed
+        #[arg(long, conflicts_with = "no_rename_paths")]
+        paths_only: bool,
+
+        /// Only change file contents; the tree is never walked for file or directory renames
+        #[arg(long, conflicts_with = "paths_only")]
+        contents_only: bool,
+
         #[command(flatten)]
         styles: StyleArgs,
 
@@ -394,6 +791,62 @@ pub enum Commands {
         #[arg(long)]
         exclude_matching_lines: Option<String>,
 
+        /// Exclude lines of one file, as PATH:N or PATH:N-M (may be repeated)
+        #[arg(long, value_name = "PATH:LINES")]
+        exclude_lines_in_file: Vec<FileLines>,
+
+        #[command(flatten)]
+        saved_filters: SavedFilterArgs,
+
+        /// Skip matches inside string literals in code files (identifiers are still renamed)
+        #[arg(long, conflicts_with = "only_strings")]
+        skip_strings: bool,
+
+        /// Only change string literals and comments in code files, leaving identifiers and paths alone
+        #[arg(long)]
+        only_strings: bool,
+
+        /// Only match where the search term leads, ends or makes up the whole identifier
+        #[arg(long, value_enum, default_value = "any", value_name = "ANCHOR")]
+        match_anchor: MatchAnchorArg,
+
+        /// Allow a search term that is a single short or common word, such as `id` or `data`
+        #[arg(long)]
+        allow_short_search: bool,
+
+        /// How replacements are restyled to fit their surroundings: auto, off or force:<style>
+        #[arg(long, value_name = "MODE", default_value = "auto")]
+        coerce: CoercionMode,
+
+        /// In YAML, JSON and TOML files, only change keys
+        #[arg(long, conflicts_with = "only_values")]
+        only_keys: bool,
+
+        /// In YAML, JSON and TOML files, only change values
+        #[arg(long)]
+        only_values: bool,
+
+        /// In YAML, JSON and TOML files, only change matches at or under these dotted paths (e.g. spec.template.metadata.name)
+        #[arg(long, value_delimiter = ',', value_name = "PATH")]
+        yaml_path: Vec<String>,
+
+        /// Apply a named profile from the config, or a built-in one such as k8s
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// List the matches that filters left out, with the reason for each
+        #[arg(long)]
+        show_skipped: bool,
+
+        /// Exchange the two terms: OLD becomes NEW and existing NEW becomes OLD
+        #[arg(long)]
+        swap: bool,
+
+        /// Also find near-miss spellings within this edit distance, e.g. old_nmae for
+        /// old_name. They are only added to the plan once confirmed (or with --yes).
+        #[arg(long, value_name = "DISTANCE", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=3))]
+        fuzzy: u8,
+
         /// Preview output format (defaults from config if not specified)
         #[arg(long, value_enum)]
         preview: Option<PreviewArg>,
@@ -402,6 +855,15 @@ pub enum Commands {
         #[arg(long)]
         fixed_table_width: bool,
 
+        /// Group the table or matches preview by file or by variant
+        #[arg(long, value_enum, default_value = "file")]
+        group_by: GroupByArg,
+
+        /// Show only the first N matches of each file in the diff or matches
+        /// preview; totals still cover every match
+        #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        preview_sample: Option<usize>,
+
         /// Output path for the plan
         #[arg(long, default_value = ".renamify/plan.json")]
         plan_out: PathBuf,
@@ -410,37 +872,336 @@ pub enum Commands {
         #[arg(long)]
         dry_run: bool,
 
+        /// Also write one plan per set of owners in CODEOWNERS into this directory,
+        /// so each team can review and apply its part separately
+        #[arg(long, value_name = "DIR")]
+        split_by_codeowners: Option<PathBuf>,
+
+        /// Sign the written plan with a secret key made by `renamify keygen`
+        #[arg(long, value_name = "KEY", conflicts_with = "dry_run")]
+        sign: Option<PathBuf>,
+
+        /// Fail if the plan contains more than N matches
+        #[arg(long, value_name = "N")]
+        max_matches: Option<usize>,
+
+        /// Fail if more than N files would have their contents changed
+        #[arg(long, value_name = "N")]
+        max_files: Option<usize>,
+
+        /// Fail if the plan contains no matches and no renames
+        #[arg(long)]
+        fail_if_zero: bool,
+
+        /// Fail if any file could not be read, instead of listing it after the
+        /// preview
+        #[arg(long)]
+        strict: bool,
+
         #[command(flatten)]
         acronyms: AcronymArgs,
 
         #[command(flatten)]
         atomic: AtomicArgs,
 
+        /// Output format (porcelain is a stable, line-oriented format for scripts;
+        /// workspace-edit is an LSP WorkspaceEdit for editor extensions)
+        #[arg(long, value_enum, default_value = "summary")]
+        output: PlanOutputFormat,
+
+        /// Suppress all output (alias for --preview none)
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Compare two plans and report added, removed, and changed hunks and renames
+    PlanDiff {
+        /// Original plan file
+        old_plan: PathBuf,
+
+        /// Plan file to compare against
+        new_plan: PathBuf,
+
         /// Output format for machine consumption
         #[arg(long, value_enum, default_value = "summary")]
         output: OutputFormat,
 
-        /// Suppress all output (alias for --preview none)
+        /// Suppress all output
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Check that a plan still applies cleanly: schema, paths, hunk positions and rename conflicts
+    ValidatePlan {
+        /// Plan file to check
+        #[arg(default_value = ".renamify/plan.json")]
+        plan: PathBuf,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+
+        /// Suppress all output and rely on the exit code
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Summarize how renamify has been used in this repository, from its local
+    /// history
+    Report {
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+
+    /// Remove what crashed or interrupted runs left behind: backups, plans and
+    /// logs no history entry refers to, stale locks and temporary files
+    Gc {
+        /// List what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+
+    /// Check the environment: git, file system case sensitivity, long paths on
+    /// Windows, a writable .renamify, the locale and color support
+    Doctor {
+        /// Output format for machine consumption (include it in bug reports)
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+
+    /// Run a JSON-RPC service for GUIs and editors, answering plan, preview,
+    /// apply and undo requests until shutdown
+    Serve {
+        /// Read requests from stdin and write responses to stdout, one JSON
+        /// object per line
+        #[arg(long, required = true)]
+        stdio: bool,
+    },
+
+    /// Generate a key pair for signing plans with `plan --sign`
+    Keygen {
+        /// Where to write the secret key; the public key is written next to it
+        /// with `.pub` appended
+        path: PathBuf,
+
+        /// Replace existing key files
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Explain how an identifier is tokenized and why it does or doesn't match
+    Explain {
+        /// Search term, as given to plan or search
+        search: String,
+
+        /// Identifier to explain, e.g. SomeOldNameFactory
+        identifier: String,
+
+        /// Replacement to show the renamed identifier for
+        #[arg(long)]
+        replace: Option<String>,
+
+        /// Case styles to exclude from the default set
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            conflicts_with = "only_styles"
+        )]
+        exclude_styles: Vec<StyleArg>,
+
+        /// Additional case styles to include
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            conflicts_with = "only_styles"
+        )]
+        include_styles: Vec<StyleArg>,
+
+        /// Use only these case styles (overrides defaults)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        only_styles: Vec<StyleArg>,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+
+    /// Preview the renaming of a single buffer, e.g. an unsaved file in an editor
+    ///
+    /// Reads the buffer from stdin (or from --file without --stdin) and prints
+    /// the transformed content or a diff. Nothing is written and no lock is taken.
+    PreviewFile {
+        /// Old identifier to replace
+        #[arg(long)]
+        search: String,
+
+        /// New identifier to replace with
+        #[arg(long)]
+        replace: String,
+
+        /// Path of the buffer, used for file-type handling and in diffs
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        /// Read the buffer from stdin even when --file is given
+        #[arg(long)]
+        stdin: bool,
+
+        #[command(flatten)]
+        styles: StyleArgs,
+
+        /// Specific matches to exclude (e.g., compound words to ignore)
+        #[arg(long, value_delimiter = ',')]
+        exclude_match: Vec<String>,
+
+        /// Exclude matches on lines matching this regex pattern
+        #[arg(long)]
+        exclude_matching_lines: Option<String>,
+
+        /// Skip matches inside string literals in code files (identifiers are still renamed)
+        #[arg(long, conflicts_with = "only_strings")]
+        skip_strings: bool,
+
+        /// Only change string literals and comments in code files, leaving identifiers alone
+        #[arg(long)]
+        only_strings: bool,
+
+        /// How replacements are restyled to fit their surroundings: auto, off or force:<style>
+        #[arg(long, value_name = "MODE", default_value = "auto")]
+        coerce: CoercionMode,
+
+        /// Print the transformed content, a diff, or JSON
+        #[arg(long, value_enum, default_value = "content")]
+        output: PreviewFileOutput,
+    },
+
+    /// Merge several plans into one, rejecting overlapping or conflicting edits
+    PlanMerge {
+        /// Plan files to merge (at least two)
+        #[arg(required = true, num_args = 2..)]
+        plans: Vec<PathBuf>,
+
+        /// Where to write the merged plan
+        #[arg(short = 'o', long = "out", default_value = ".renamify/plan.json")]
+        out: PathBuf,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+
+        /// Suppress all output
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Regenerate a plan against the current tree with its recorded options
+    Replan {
+        /// Plan ID, path or URL to regenerate (optional - defaults to .renamify/plan.json)
+        plan: Option<String>,
+
+        /// Paths to search (files or directories), as for `plan`
+        #[arg(long = "path", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Where to write the new plan
+        #[arg(long, default_value = ".renamify/plan.json")]
+        plan_out: PathBuf,
+
+        /// Compare with the current tree without writing a new plan
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+
+        /// Suppress all output
         #[arg(long)]
         quiet: bool,
     },
 
     /// Apply a renaming plan
     Apply {
-        /// Plan ID or path to apply (optional - defaults to .renamify/plan.json)
+        /// Plan ID, path or URL to apply (optional - defaults to .renamify/plan.json)
         id: Option<String>,
 
-        /// Commit changes to git
+        /// Plan path or URL to apply (https://, or s3:// and gs:// when built
+        /// with the cloud-storage feature)
+        #[arg(long, value_name = "PATH_OR_URL", conflicts_with = "id")]
+        plan: Option<String>,
+
+        /// Check the plan's SHA-256 digest before applying it
+        #[arg(long, value_name = "sha256:HEX", conflicts_with = "bundle")]
+        checksum: Option<String>,
+
+        /// Only accept plans signed by this public key (hex, or a .pub file made
+        /// by `renamify keygen`); may be given more than once
+        #[arg(long, value_name = "KEY", conflicts_with = "bundle")]
+        trusted_key: Vec<String>,
+
+        /// Refuse plans that are not signed by a trusted key
+        #[arg(long, requires = "trusted_key")]
+        require_signed: bool,
+
+        /// Apply a bundle written by `history export --bundle` instead of a plan
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["id", "plan"])]
+        bundle: Option<PathBuf>,
+
+        #[command(flatten)]
+        commit: CommitArgs,
+
+        /// Stage the changes in git after writing them
+        #[arg(long, conflicts_with = "stage_only")]
+        stage: bool,
+
+        /// Write the changes to the git index only, leaving the working tree and
+        /// any local edits as they are
         #[arg(long)]
-        commit: bool,
+        stage_only: bool,
 
         /// Force apply even with conflicts
         #[arg(long)]
         force_with_conflicts: bool,
 
-        /// Output format for machine consumption
+        /// Refuse a plan that could not read some files, whose matches would be
+        /// left unchanged
+        #[arg(long, conflicts_with = "bundle")]
+        strict: bool,
+
+        /// After applying, list ignored build metadata (compile_commands.json,
+        /// .idea/, .vscode/ settings, Makefiles in build directories) that still
+        /// names renamed paths
+        #[arg(long, conflicts_with = "bundle")]
+        report_stale_paths: bool,
+
+        /// Check that the plan would apply cleanly and say what it would do,
+        /// without changing anything
+        #[arg(
+            long,
+            conflicts_with_all = ["bundle", "stage", "stage_only", "force_with_conflicts", "report_stale_paths"]
+        )]
+        check: bool,
+
+        /// With --check, show the touched paths as they will be laid out after
+        /// the renames
+        #[arg(long, requires = "check")]
+        show_tree: bool,
+
+        /// Apply the whole plan at once, or each directory as its own transaction
+        /// with separate backups and history entries
+        #[arg(long, value_enum, default_value = "plan")]
+        transaction_scope: TransactionScopeArg,
+
+        /// Output format (porcelain is a stable, line-oriented format for scripts)
         #[arg(long, value_enum, default_value = "summary")]
-        output: OutputFormat,
+        output: PorcelainOutputFormat,
 
         /// Suppress all output (alias for --preview none)
         #[arg(long)]
@@ -452,9 +1213,14 @@ pub enum Commands {
         /// History ID to undo (use 'latest' for the most recent non-revert entry)
         id: String,
 
-        /// Output format for machine consumption
+        /// Undo even when files changed since the apply, overwriting the newer
+        /// edits (without it, undo lists those files and changes nothing)
+        #[arg(long)]
+        force_undo: bool,
+
+        /// Output format (porcelain is a stable, line-oriented format for scripts)
         #[arg(long, value_enum, default_value = "summary")]
-        output: OutputFormat,
+        output: PorcelainOutputFormat,
 
         /// Suppress all output (alias for --preview none)
         #[arg(long)]
@@ -466,9 +1232,9 @@ pub enum Commands {
         /// History ID to redo (use 'latest' for the most recent reverted entry)
         id: String,
 
-        /// Output format for machine consumption
+        /// Output format (porcelain is a stable, line-oriented format for scripts)
         #[arg(long, value_enum, default_value = "summary")]
-        output: OutputFormat,
+        output: PorcelainOutputFormat,
 
         /// Suppress all output (alias for --preview none)
         #[arg(long)]
@@ -477,9 +1243,9 @@ pub enum Commands {
 
     /// Show renaming status
     Status {
-        /// Output format for machine consumption
+        /// Output format (porcelain is a stable, line-oriented format for scripts)
         #[arg(long, value_enum, default_value = "summary")]
-        output: OutputFormat,
+        output: PorcelainOutputFormat,
 
         /// Suppress all output (alias for --preview none)
         #[arg(long)]
@@ -488,13 +1254,16 @@ pub enum Commands {
 
     /// Show renaming history
     History {
+        #[command(subcommand)]
+        command: Option<HistoryCommand>,
+
         /// Limit number of entries
         #[arg(long)]
         limit: Option<usize>,
 
-        /// Output format for machine consumption
+        /// Output format (porcelain is a stable, line-oriented format for scripts)
         #[arg(long, value_enum, default_value = "summary")]
-        output: OutputFormat,
+        output: PorcelainOutputFormat,
 
         /// Suppress all output (alias for --preview none)
         #[arg(long)]
@@ -508,6 +1277,57 @@ pub enum Commands {
         output: OutputFormat,
     },
 
+    /// Generate shell completions
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+
+        /// Write the completion file into this directory instead of stdout
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+
+    /// Inspect the effective configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Export TypeScript types and JSON schemas as a versioned `renamify-types` package
+    ExportTypes {
+        /// Directory to write the package into
+        #[arg(long, value_name = "DIR")]
+        out: PathBuf,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+
+        /// Suppress all output
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Generate man pages for renamify and all subcommands
+    #[command(hide = true)]
+    Man {
+        /// Directory to write the man pages into
+        #[arg(long, value_name = "DIR")]
+        out_dir: PathBuf,
+    },
+
+    /// Print the full help for every subcommand
+    HelpAll,
+
+    /// Print dynamic completion candidates (used by the generated shell scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Kind of value to complete
+        #[arg(value_enum)]
+        kind: CompletionKind,
+    },
+
     #[command(hide = true)]
     TestLock {
         /// Delay in milliseconds before releasing lock
@@ -515,3 +1335,114 @@ pub enum Commands {
         delay: u64,
     },
 }
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective configuration (defaults < user config < repo config < environment)
+    Show {
+        /// Show where each value came from
+        #[arg(long)]
+        origin: bool,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+
+    /// Print one config value, e.g. `renamify config get defaults.preview_format`
+    Get {
+        /// Dotted config key
+        key: String,
+
+        /// Read the user config file instead of the effective configuration
+        #[arg(long, conflicts_with = "repo")]
+        global: bool,
+
+        /// Read the repo config file (.renamify/config.toml) instead of the effective configuration
+        #[arg(long)]
+        repo: bool,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+
+    /// Set one config value, e.g. `renamify config set defaults.preview_format table`
+    Set {
+        /// Dotted config key
+        key: String,
+
+        /// New value, parsed as TOML (bare words are treated as strings)
+        value: String,
+
+        /// Write the user config file
+        #[arg(long, conflicts_with = "repo")]
+        global: bool,
+
+        /// Write the repo config file (.renamify/config.toml) [default]
+        #[arg(long)]
+        repo: bool,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HookCommand {
+    /// Fail the commit when a staged file contains or is named after a
+    /// forbidden identifier. Only staged files are read, from the git index
+    PreCommit {
+        /// Forbidden identifier (repeatable; defaults to [check] forbid in config)
+        #[arg(long, value_delimiter = ',')]
+        forbid: Vec<String>,
+
+        /// Include glob patterns, relative to the repository root
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Exclude glob patterns, relative to the repository root
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: SearchOutputFormat,
+
+        /// Suppress all output (exit code only)
+        #[arg(long)]
+        quiet: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommand {
+    /// Export an applied rename so a teammate can apply it with `apply --bundle`
+    Export {
+        /// History ID to export (use 'latest' for the most recent non-revert entry)
+        id: String,
+
+        /// File to write the bundle to, e.g. `rename.renamify-bundle`
+        #[arg(long, value_name = "FILE")]
+        bundle: PathBuf,
+
+        /// Output format for machine consumption
+        #[arg(long, value_enum, default_value = "summary")]
+        output: OutputFormat,
+
+        /// Suppress all output
+        #[arg(long)]
+        quiet: bool,
+    },
+
+    /// Print what each identifier variant and path became in an applied rename
+    Mapping {
+        /// History ID (use 'latest' for the most recent non-revert entry)
+        id: String,
+
+        /// Format of the mapping
+        #[arg(long, value_enum, default_value = "json")]
+        format: MappingFormat,
+    },
+}
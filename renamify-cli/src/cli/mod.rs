@@ -1,5 +1,8 @@
 pub mod args;
 pub mod types;
 
-pub use args::{Cli, Commands};
-pub use types::{OutputFormat, PreviewArg};
+pub use args::{Cli, Commands, ConfigCommand, HistoryCommand, HookCommand};
+pub use types::{
+    CompletionKind, MappingFormat, OutputFormat, PlanOutputFormat, PorcelainOutputFormat,
+    PreviewArg, PreviewFileOutput, SearchOutputFormat,
+};
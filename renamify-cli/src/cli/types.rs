@@ -1,5 +1,7 @@
 use clap::ValueEnum;
-use renamify_core::{Preview, Style};
+use renamify_core::acronym::AcronymRendering;
+use renamify_core::case_model::CaseMapping;
+use renamify_core::{GroupBy, MatchAnchor, Preview, Style, TransactionScope};
 
 #[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
 pub enum StyleArg {
@@ -99,12 +101,108 @@ impl From<PreviewArg> for Preview {
     }
 }
 
+/// How acronyms are written in camelCase and PascalCase
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum AcronymRenderingArg {
+    /// Acronyms typed in uppercase stay uppercase (getAPIClient)
+    PreserveUpper,
+    /// Acronyms are capitalized like words (getApiClient)
+    Pascalize,
+    /// Acronyms are lowercase (getapiClient)
+    Lower,
+}
+
+impl From<AcronymRenderingArg> for AcronymRendering {
+    fn from(arg: AcronymRenderingArg) -> Self {
+        match arg {
+            AcronymRenderingArg::PreserveUpper => Self::PreserveUpper,
+            AcronymRenderingArg::Pascalize => Self::Pascalize,
+            AcronymRenderingArg::Lower => Self::Lower,
+        }
+    }
+}
+
+/// How letters change case in variants and replacements
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum CaseMappingArg {
+    /// Unicode's default mappings (İ lowercases to i̇)
+    Unicode,
+    /// Only ASCII letters change case
+    Ascii,
+    /// Turkish dotted and dotless i (İ and i, I and ı)
+    Turkish,
+}
+
+impl From<CaseMappingArg> for CaseMapping {
+    fn from(arg: CaseMappingArg) -> Self {
+        match arg {
+            CaseMappingArg::Unicode => Self::Unicode,
+            CaseMappingArg::Ascii => Self::Ascii,
+            CaseMappingArg::Turkish => Self::Turkish,
+        }
+    }
+}
+
+/// Which terms `--atomic` treats as atomic identifiers
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum AtomicArg {
+    Search,
+    Replace,
+    Both,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
 pub enum OutputFormat {
     Summary,
     Json,
 }
 
+/// Output formats for commands that also offer the stable porcelain format
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum PorcelainOutputFormat {
+    Summary,
+    Json,
+    /// Stable tab-separated records that will not change between versions
+    Porcelain,
+}
+
+/// Output formats for `plan`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum PlanOutputFormat {
+    Summary,
+    Json,
+    /// Stable tab-separated records that will not change between versions
+    Porcelain,
+    /// An LSP `WorkspaceEdit` for editors to apply themselves
+    WorkspaceEdit,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum SearchOutputFormat {
+    Summary,
+    Json,
+    /// GitHub Actions workflow commands (one annotation per match)
+    Github,
+}
+
+/// Formats for `history mapping`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum MappingFormat {
+    Json,
+    /// One `kind,old,new,occurrences` row per identifier and path
+    Csv,
+}
+
+/// Output formats for `preview-file`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum PreviewFileOutput {
+    /// The transformed buffer
+    Content,
+    /// A unified diff against the original buffer
+    Diff,
+    Json,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
 pub enum SearchPreviewArg {
     Table,
@@ -123,3 +221,93 @@ impl From<SearchPreviewArg> for Preview {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum GroupByArg {
+    /// One section per file
+    File,
+    /// One section per variant, e.g. `OldName` apart from `old_name`
+    Variant,
+}
+
+impl From<GroupByArg> for GroupBy {
+    fn from(arg: GroupByArg) -> Self {
+        match arg {
+            GroupByArg::File => Self::File,
+            GroupByArg::Variant => Self::Variant,
+        }
+    }
+}
+
+/// Values that the shell completion scripts ask for at completion time
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum CompletionKind {
+    /// History IDs accepted by undo and redo
+    HistoryIds,
+    /// Style names accepted by the style filter flags
+    Styles,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum TransactionScopeArg {
+    /// Apply the whole plan as one transaction
+    Plan,
+    /// Apply each directory as its own transaction
+    Dir,
+}
+
+impl From<TransactionScopeArg> for TransactionScope {
+    fn from(arg: TransactionScopeArg) -> Self {
+        match arg {
+            TransactionScopeArg::Plan => Self::Plan,
+            TransactionScopeArg::Dir => Self::Dir,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum MatchAnchorArg {
+    /// Match the search term anywhere in an identifier
+    Any,
+    /// Only match when the search term leads the identifier (`old_config`, `OldConfig`)
+    Prefix,
+    /// Only match when the search term ends the identifier (`threshold_old`)
+    Suffix,
+    /// Only match when the search term is the whole identifier
+    Whole,
+}
+
+impl From<MatchAnchorArg> for MatchAnchor {
+    fn from(arg: MatchAnchorArg) -> Self {
+        match arg {
+            MatchAnchorArg::Any => Self::Any,
+            MatchAnchorArg::Prefix => Self::Prefix,
+            MatchAnchorArg::Suffix => Self::Suffix,
+            MatchAnchorArg::Whole => Self::Whole,
+        }
+    }
+}
+
+/// Check a `--type` or `--type-not` value names a built-in file type
+pub fn parse_file_type(value: &str) -> Result<String, String> {
+    renamify_core::language::find_file_type(value)
+        .map(|file_type| file_type.name.to_string())
+        .ok_or_else(|| format!("unknown file type '{value}' (see --type-list)"))
+}
+
+/// Parse a `--max-filesize` value: a number of bytes with an optional K, M or
+/// G suffix (powers of 1024), as ripgrep accepts
+pub fn parse_filesize(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1024),
+        Some('M') => (&value[..value.len() - 1], 1024 * 1024),
+        Some('G') => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|size| size.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{value}' (expected e.g. 512K, 5M or 1G)"))
+}
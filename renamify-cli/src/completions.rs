@@ -0,0 +1,263 @@
+use anyhow::Result;
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::{Generator, Shell};
+use renamify_core::history_operation;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::cli::types::StyleArg;
+use crate::cli::{Cli, CompletionKind};
+
+pub fn handle_completions(shell: Shell, out_dir: Option<&Path>) -> Result<()> {
+    let mut cmd = Cli::command();
+
+    match out_dir {
+        Some(dir) => generate_completions(shell, &mut cmd, "renamify", dir),
+        None => {
+            let mut stdout = io::stdout();
+            write_completions(shell, &mut cmd, "renamify", &mut stdout)?;
+            Ok(())
+        },
+    }
+}
+
+/// Print completion candidates, one per line. Candidates may carry a
+/// tab-separated description that fish and zsh display next to the value.
+pub fn handle_complete(kind: CompletionKind, working_dir: Option<&Path>) -> Result<()> {
+    let mut stdout = io::stdout();
+    for candidate in completion_candidates(kind, working_dir) {
+        writeln!(stdout, "{}", candidate)?;
+    }
+    Ok(())
+}
+
+fn completion_candidates(kind: CompletionKind, working_dir: Option<&Path>) -> Vec<String> {
+    match kind {
+        CompletionKind::HistoryIds => {
+            // Completion must never fail loudly, so a missing or unreadable
+            // history simply yields no candidates
+            let Ok(history) = history_operation(None, working_dir) else {
+                return Vec::new();
+            };
+            history
+                .entries
+                .into_iter()
+                .map(|entry| format!("{}\t{} -> {}", entry.id, entry.search, entry.replace))
+                .collect()
+        },
+        CompletionKind::Styles => StyleArg::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .map(|value| value.get_name().to_string())
+            .collect(),
+    }
+}
+
+// Generate shell completions
+pub fn generate_completions(
+    shell: Shell,
+    cmd: &mut clap::Command,
+    name: &str,
+    out_dir: &Path,
+) -> Result<()> {
+    use std::fs;
+
+    fs::create_dir_all(out_dir)?;
+    let path = out_dir.join(shell.file_name(name));
+    let mut file = fs::File::create(&path)?;
+    write_completions(shell, cmd, name, &mut file)?;
+    println!("Generated completion file: {}", path.display());
+    Ok(())
+}
+
+/// Write the static clap completions followed by the hooks that ask
+/// `renamify __complete` for history IDs and style names at completion time.
+fn write_completions(
+    shell: Shell,
+    cmd: &mut clap::Command,
+    name: &str,
+    out: &mut dyn Write,
+) -> Result<()> {
+    clap_complete::generate(shell, cmd, name, out);
+
+    if let Some(hook) = dynamic_hook(shell) {
+        out.write_all(hook.replace("@NAME@", name).as_bytes())?;
+    }
+    Ok(())
+}
+
+fn dynamic_hook(shell: Shell) -> Option<&'static str> {
+    match shell {
+        Shell::Bash => Some(BASH_HOOK),
+        Shell::Zsh => Some(ZSH_HOOK),
+        Shell::Fish => Some(FISH_HOOK),
+        _ => None,
+    }
+}
+
+const BASH_HOOK: &str = r#"
+# Dynamic completion for history IDs and comma-separated style lists
+_@NAME@_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ ${COMP_CWORD} -eq 2 && ( "${COMP_WORDS[1]}" == "undo" || "${COMP_WORDS[1]}" == "redo" ) ]]; then
+        local ids
+        ids="latest $(@NAME@ __complete history-ids 2>/dev/null | cut -f1)"
+        COMPREPLY=( $(compgen -W "${ids}" -- "${cur}") )
+        return 0
+    fi
+    case "${prev}" in
+        --exclude-styles|--include-styles|--only-styles)
+            local head="" tail="${cur}"
+            if [[ "${cur}" == *,* ]]; then
+                head="${cur%,*},"
+                tail="${cur##*,}"
+            fi
+            COMPREPLY=( $(compgen -P "${head}" -W "$(@NAME@ __complete styles 2>/dev/null)" -- "${tail}") )
+            compopt -o nospace 2>/dev/null
+            return 0
+            ;;
+    esac
+    _@NAME@ "$@"
+}
+
+complete -F _@NAME@_dynamic -o nosort -o bashdefault -o default @NAME@
+"#;
+
+const ZSH_HOOK: &str = r#"
+# Dynamic completion for history IDs (effective when this file is sourced)
+_@NAME@_dynamic() {
+    if (( CURRENT == 3 )) && [[ "$words[2]" == (undo|redo) ]]; then
+        local -a ids
+        ids=("latest:most recent entry" ${(f)"$(@NAME@ __complete history-ids 2>/dev/null | sed 's/:/\\:/g; s/\t/:/')"})
+        _describe -t history-ids 'history id' ids
+        return
+    fi
+    _@NAME@ "$@"
+}
+
+if [ "$funcstack[1]" != "_@NAME@" ]; then
+    compdef _@NAME@_dynamic @NAME@
+fi
+"#;
+
+const FISH_HOOK: &str = r#"
+# Dynamic completion for history IDs
+complete -c @NAME@ -n "__fish_seen_subcommand_from undo redo" -f -a "latest\t'Most recent entry'"
+complete -c @NAME@ -n "__fish_seen_subcommand_from undo redo" -f -a "(@NAME@ __complete history-ids 2>/dev/null)"
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generate_completions_bash() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut cmd = Cli::command();
+
+        let result = generate_completions(Shell::Bash, &mut cmd, "renamify", temp_dir.path());
+
+        assert!(result.is_ok());
+
+        // Check that the completion file was created
+        let completion_file = temp_dir.path().join("renamify.bash");
+        assert!(completion_file.exists());
+
+        // Read and verify the content has bash completion markers
+        let content = std::fs::read_to_string(completion_file).unwrap();
+        assert!(content.contains("complete"));
+        assert!(content.contains("renamify"));
+        assert!(content.contains("renamify __complete history-ids"));
+        assert!(content.contains("complete -F _renamify_dynamic"));
+    }
+
+    #[test]
+    fn test_generate_completions_zsh() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut cmd = Cli::command();
+
+        let result = generate_completions(Shell::Zsh, &mut cmd, "renamify", temp_dir.path());
+
+        assert!(result.is_ok());
+
+        // Check that the completion file was created
+        let completion_file = temp_dir.path().join("_renamify");
+        assert!(completion_file.exists());
+
+        // Read and verify the content has zsh completion markers
+        let content = std::fs::read_to_string(completion_file).unwrap();
+        assert!(content.contains("#compdef"));
+        assert!(content.contains("renamify"));
+        assert!(content.contains("compdef _renamify_dynamic renamify"));
+    }
+
+    #[test]
+    fn test_generate_completions_fish() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut cmd = Cli::command();
+
+        let result = generate_completions(Shell::Fish, &mut cmd, "renamify", temp_dir.path());
+
+        assert!(result.is_ok());
+
+        // Check that the completion file was created
+        let completion_file = temp_dir.path().join("renamify.fish");
+        assert!(completion_file.exists());
+
+        // Read and verify the content has fish completion markers
+        let content = std::fs::read_to_string(completion_file).unwrap();
+        assert!(content.contains("complete"));
+        assert!(content.contains("-c renamify"));
+        assert!(content.contains("(renamify __complete history-ids 2>/dev/null)"));
+    }
+
+    #[test]
+    fn test_generate_completions_powershell_has_no_dynamic_hook() {
+        let mut cmd = Cli::command();
+        let mut out = Vec::new();
+
+        write_completions(Shell::PowerShell, &mut cmd, "renamify", &mut out).unwrap();
+
+        let content = String::from_utf8(out).unwrap();
+        assert!(content.contains("renamify"));
+        assert!(!content.contains("__complete history-ids"));
+    }
+
+    #[test]
+    fn test_generate_completions_creates_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested_path = temp_dir.path().join("nested").join("dir");
+        let mut cmd = Cli::command();
+
+        // Directory doesn't exist yet
+        assert!(!nested_path.exists());
+
+        let result = generate_completions(Shell::Bash, &mut cmd, "renamify", &nested_path);
+
+        assert!(result.is_ok());
+
+        // Directory was created
+        assert!(nested_path.exists());
+
+        // File was created in the directory
+        let completion_file = nested_path.join("renamify.bash");
+        assert!(completion_file.exists());
+    }
+
+    #[test]
+    fn test_style_candidates() {
+        let styles = completion_candidates(CompletionKind::Styles, None);
+        assert!(styles.contains(&"snake".to_string()));
+        assert!(styles.contains(&"screaming-snake".to_string()));
+        assert!(styles.contains(&"space-separated".to_string()));
+    }
+
+    #[test]
+    fn test_history_id_candidates_without_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let ids = completion_candidates(CompletionKind::HistoryIds, Some(temp_dir.path()));
+        assert!(ids.is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use renamify_core::{
+    config_get_operation, config_set_operation, config_show_operation, ConfigScope, OutputFormatter,
+};
+
+use crate::OutputFormat;
+use std::path::Path;
+
+pub fn handle_config_show(
+    origin: bool,
+    output: OutputFormat,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = config_show_operation(origin, working_dir)?;
+    print_result(&result, output);
+    Ok(())
+}
+
+pub fn handle_config_get(
+    key: &str,
+    global: bool,
+    repo: bool,
+    output: OutputFormat,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let scope = if global {
+        Some(ConfigScope::User)
+    } else if repo {
+        Some(ConfigScope::Repo)
+    } else {
+        None
+    };
+
+    let result = config_get_operation(key, scope, working_dir)?;
+    print_result(&result, output);
+    Ok(())
+}
+
+pub fn handle_config_set(
+    key: &str,
+    value: &str,
+    global: bool,
+    output: OutputFormat,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let scope = if global {
+        ConfigScope::User
+    } else {
+        ConfigScope::Repo
+    };
+
+    let result = config_set_operation(key, value, scope, working_dir)?;
+    print_result(&result, output);
+    Ok(())
+}
+
+fn print_result(result: &dyn OutputFormatter, output: OutputFormat) {
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            print!("{}", result.format_summary());
+        },
+    }
+}
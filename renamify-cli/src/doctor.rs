@@ -0,0 +1,30 @@
+use anyhow::Result;
+use renamify_core::{doctor_operation, OutputFormatter};
+use std::path::Path;
+use std::process;
+
+use crate::OutputFormat;
+
+pub fn handle_doctor(
+    no_color: bool,
+    output: OutputFormat,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = doctor_operation(no_color, working_dir);
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            print!("{}", result.format_summary());
+        },
+    }
+
+    if !result.passed() {
+        process::exit(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use renamify_core::acronym::AcronymRendering;
+use renamify_core::case_model::CaseMapping;
+use renamify_core::{explain_operation, OutputFormatter, Style};
+
+use crate::cli::types::StyleArg;
+use crate::OutputFormat;
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn handle_explain(
+    search: &str,
+    identifier: &str,
+    replace: Option<&str>,
+    exclude_styles: Vec<StyleArg>,
+    include_styles: Vec<StyleArg>,
+    only_styles: Vec<StyleArg>,
+    acronym_rendering: AcronymRendering,
+    case_mapping: CaseMapping,
+    output: OutputFormat,
+) -> Result<()> {
+    let expand = |styles: Vec<StyleArg>| -> Vec<Style> {
+        styles
+            .into_iter()
+            .flat_map(|s| s.expand())
+            .map(Into::into)
+            .collect()
+    };
+
+    let result = explain_operation(
+        search,
+        identifier,
+        replace,
+        &expand(exclude_styles),
+        &expand(include_styles),
+        &expand(only_styles),
+        acronym_rendering,
+        case_mapping,
+    )?;
+
+    match output {
+        OutputFormat::Json => print!("{}", result.format_json()),
+        OutputFormat::Summary => print!("{}", result.format_summary()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use renamify_core::{export_types_operation, OutputFormatter};
+use std::path::Path;
+
+use crate::OutputFormat;
+
+pub fn handle_export_types(
+    out: &Path,
+    output: OutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = export_types_operation(out, working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    Ok(())
+}
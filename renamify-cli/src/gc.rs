@@ -0,0 +1,21 @@
+use anyhow::Result;
+use renamify_core::{gc_operation, OutputFormatter};
+
+use crate::OutputFormat;
+use std::path::Path;
+
+pub fn handle_gc(dry_run: bool, output: OutputFormat, working_dir: Option<&Path>) -> Result<()> {
+    let result = gc_operation(dry_run, working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            print!("{}", result.format_summary());
+        },
+    }
+
+    Ok(())
+}
@@ -1,16 +1,49 @@
 use anyhow::Result;
-use renamify_core::{history_operation, OutputFormatter};
+use renamify_core::{
+    history_export_operation, history_mapping_operation, history_operation, OutputFormatter,
+    PorcelainFormatter,
+};
+use std::path::Path;
 
-use crate::OutputFormat;
+use crate::cli::{MappingFormat, OutputFormat, PorcelainOutputFormat};
 
-pub fn handle_history(limit: Option<usize>, output: OutputFormat, quiet: bool) -> Result<()> {
-    let result = history_operation(limit, None)?;
+pub fn handle_history(
+    limit: Option<usize>,
+    output: PorcelainOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = history_operation(limit, working_dir)?;
 
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        PorcelainOutputFormat::Json => {
             print!("{}", result.format_json());
         },
+        PorcelainOutputFormat::Porcelain => {
+            print!("{}", result.format_porcelain());
+        },
+        PorcelainOutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn handle_history_export(
+    id: &str,
+    bundle: &Path,
+    output: OutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = history_export_operation(id, bundle, working_dir)?;
+
+    match output {
+        OutputFormat::Json => print!("{}", result.format_json()),
         OutputFormat::Summary => {
             if !quiet {
                 print!("{}", result.format_summary());
@@ -20,3 +53,18 @@ pub fn handle_history(limit: Option<usize>, output: OutputFormat, quiet: bool) -
 
     Ok(())
 }
+
+pub fn handle_history_mapping(
+    id: &str,
+    format: MappingFormat,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let mapping = history_mapping_operation(id, working_dir)?;
+
+    match format {
+        MappingFormat::Json => println!("{}", serde_json::to_string_pretty(&mapping)?),
+        MappingFormat::Csv => print!("{}", mapping.to_csv()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use renamify_core::check_staged_operation;
+use std::path::Path;
+
+use crate::check::{forbidden, report};
+use crate::cli::SearchOutputFormat;
+
+/// Check the staged files for forbidden identifiers before a commit
+pub fn handle_pre_commit(
+    forbid: Vec<String>,
+    include: &[String],
+    exclude: &[String],
+    output: SearchOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let forbidden = forbidden(forbid, working_dir);
+    let result = check_staged_operation(&forbidden, include, exclude, working_dir)?;
+    report(&result, output, quiet);
+    Ok(())
+}
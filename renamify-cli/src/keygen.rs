@@ -0,0 +1,19 @@
+use anyhow::Result;
+use renamify_core::signing::{generate_key, public_key_path};
+use std::path::Path;
+
+pub fn handle_keygen(path: &Path, force: bool) -> Result<()> {
+    let public_key = generate_key(path, force)?;
+    println!("Wrote secret key to {}", path.display());
+    println!("Wrote public key to {}", public_key_path(path).display());
+    println!("Public key: {public_key}");
+    println!();
+    println!(
+        "Sign plans with: renamify plan ... --sign {}",
+        path.display()
+    );
+    println!(
+        "Apply only signed plans with: renamify apply --require-signed --trusted-key {public_key}"
+    );
+    Ok(())
+}
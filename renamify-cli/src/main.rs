@@ -1,28 +1,57 @@
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
-use renamify_core::{Config, OutputFormatter, Preview, VersionResult};
+use clap::{CommandFactory, Parser};
+use renamify_core::filters::FilterSet;
+use renamify_core::operations::plan::{CountExpectations, LargeChangeThresholds, PlanGuards};
+use renamify_core::vcs::Vcs;
+use renamify_core::{
+    BackupStrategy, BuildInfo, Config, ErrorResult, LiteralMode, OutputFormatter, PlanTrust,
+    PorcelainFormatter, Preview, StageMode, StructuredFilter, StructuredTarget, VersionResult,
+};
 use std::io::{self, BufRead, IsTerminal};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 mod apply;
+mod check;
 mod cli;
+mod completions;
+mod config;
+mod doctor;
+mod explain;
+mod export_types;
+mod gc;
 mod history;
+mod hook;
+mod keygen;
+mod man;
 mod plan;
+mod plan_diff;
+mod plan_merge;
+mod preview_file;
 mod redo;
 mod rename;
 mod replace;
+mod replan;
+mod report;
+mod search;
+mod serve;
 mod status;
+mod suggest_ignores;
+mod type_list;
 mod undo;
+mod validate_plan;
 
 #[cfg(test)]
 mod test_lock_signals;
 
 // Import from our new cli module
-use cli::{Cli, Commands, OutputFormat, PreviewArg};
+use cli::{
+    Cli, Commands, ConfigCommand, HistoryCommand, HookCommand, MappingFormat, OutputFormat,
+    PlanOutputFormat, PorcelainOutputFormat, PreviewArg, PreviewFileOutput, SearchOutputFormat,
+};
 
 fn main() {
     // Set up signal handler for graceful shutdown (both SIGINT and SIGTERM)
@@ -31,6 +60,10 @@ fn main() {
     // Handle SIGINT (Ctrl-C)
     let interrupted_clone = Arc::clone(&interrupted);
     ctrlc::set_handler(move || {
+        // The pager handles Ctrl+C itself
+        if renamify_core::interrupt::pager_active() {
+            return;
+        }
         if renamify_core::interrupt::confirmation_prompt_active() {
             eprintln!("\nOperation cancelled by user.");
             process::exit(130);
@@ -53,118 +86,270 @@ fn main() {
     }
 
     let cli = Cli::parse();
+    if cli.type_list {
+        if let Err(e) = type_list::handle_type_list() {
+            eprintln!("Error: {e:#}");
+            process::exit(1);
+        }
+        return;
+    }
+    let Some(command) = cli.command else {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "'renamify' requires a subcommand but one was not provided",
+            )
+            .exit();
+    };
     let use_color = !cli.no_color && io::stdout().is_terminal();
-
-    // Handle -C directory flag
-    if let Some(ref dir) = cli.directory {
-        std::env::set_current_dir(dir)
-            .with_context(|| format!("Failed to change to directory: {}", dir.display()))
-            .unwrap_or_else(|e| {
-                eprintln!("Error: {e:#}");
+    renamify_core::pager::set_enabled(!cli.no_pager);
+
+    // -C runs the command in another directory. The process's current
+    // directory is never changed: commands are given the directory instead,
+    // and resolve relative paths against it.
+    let working_dir = cli.directory.as_deref().map(|dir| {
+        dir.canonicalize()
+            .ok()
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(|| {
+                eprintln!("Error: Not a directory: {}", dir.display());
                 process::exit(2);
-            });
+            })
+    });
+    let working_dir = working_dir.as_deref();
+    let walk = renamify_core::WalkOptions {
+        max_depth: cli.max_depth,
+        one_file_system: cli.one_file_system,
+        follow_links: cli.follow,
+        max_filesize: match cli.max_filesize {
+            Some(0) => None,
+            Some(limit) => Some(limit),
+            None => Some(renamify_core::DEFAULT_MAX_FILESIZE),
+        },
+        include_tracked: !cli.no_tracked_ignored,
+        types: cli.types.clone(),
+        types_not: cli.types_not.clone(),
+    };
+    let resolve =
+        |path: &Path| working_dir.map_or_else(|| path.to_path_buf(), |dir| dir.join(path));
+
+    // Commands that only read can run without a writable .renamify
+    if cli.no_workspace
+        || (runs_without_workspace(&command)
+            && !renamify_core::workspace::is_writable(&workspace_dir(working_dir)))
+    {
+        if !cli.no_workspace {
+            eprintln!(
+                "Warning: .renamify is not writable; continuing without history, locking or a saved plan (pass --no-workspace to skip this check)"
+            );
+        }
+        renamify_core::workspace::set_disabled(true);
     }
+    renamify_core::scan_cache::set_disabled(cli.no_cache);
 
     // Check if we need to auto-init before running commands that create .renamify/
     let needs_renamify_dir = matches!(
-        cli.command,
+        command,
         Commands::Plan { .. }
             | Commands::Apply { .. }
             | Commands::Rename { .. }
             | Commands::Replace { .. }
             | Commands::Search { .. }
+            | Commands::PlanMerge { .. }
+            | Commands::Replan { dry_run: false, .. }
     );
 
-    if needs_renamify_dir && !cli.no_auto_init {
-        if let Err(e) = check_and_auto_init(&cli.auto_init, cli.yes) {
+    if needs_renamify_dir && !cli.no_auto_init && !renamify_core::workspace::is_disabled() {
+        if let Err(e) = check_and_auto_init(working_dir, &cli.auto_init, cli.yes) {
             eprintln!("Error during auto-initialization: {e:#}");
             process::exit(2);
         }
     }
 
     // Load config to get defaults
-    let config = Config::load().unwrap_or_default();
+    let config = working_dir
+        .map_or_else(Config::load, Config::load_in)
+        .unwrap_or_default();
+    let durability = cli.durability.unwrap_or(config.defaults.durability);
+    let backup = if cli.no_backup {
+        BackupStrategy::None
+    } else {
+        cli.backup.unwrap_or(config.defaults.backup)
+    };
+
+    // Errors are reported as JSON on stdout when the command asked for JSON output
+    let json_output = wants_json_output(&command);
+    let porcelain_output = wants_porcelain_output(&command);
 
-    let result = match cli.command {
+    let result = match command {
         Commands::Plan {
             search,
             replace,
             paths,
+            stdin: _,
+            stdin_path,
             filter,
             rename_files,
+            paths_only,
+            contents_only,
             styles,
             exclude_match,
             exclude_matching_lines,
+            exclude_lines_in_file,
+            saved_filters,
+            skip_strings,
+            only_strings,
+            match_anchor,
+            allow_short_search,
+            coerce,
+            only_keys,
+            only_values,
+            yaml_path,
+            profile,
+            show_skipped,
+            swap,
+            fuzzy,
             preview,
             fixed_table_width,
+            group_by,
+            preview_sample,
             plan_out,
             dry_run,
+            split_by_codeowners,
+            sign,
+            max_matches,
+            max_files,
+            fail_if_zero,
+            strict,
             acronyms,
             atomic,
             output,
             quiet,
         } => {
-            // Use preview format from CLI arg or config default (unless JSON output)
-            let format = if output == OutputFormat::Json {
-                None // No preview for JSON output
+            // Use preview format from CLI arg or config default (unless machine-readable output)
+            let format = if output != PlanOutputFormat::Summary {
+                None // No preview for machine-readable output
             } else {
                 Some(preview.map(std::convert::Into::into).unwrap_or_else(|| {
                     Preview::from_str(&config.defaults.preview_format).unwrap_or(Preview::Diff)
                 }))
             };
 
-            plan::handle_plan(
-                &search,
-                &replace,
-                paths,
-                filter.include,
-                filter.exclude,
-                filter.respect_gitignore,
-                cli.unrestricted,
-                !rename_files.no_rename_files && !rename_files.no_rename_paths,
-                !rename_files.no_rename_dirs && !rename_files.no_rename_paths,
-                styles.exclude_styles,
-                styles.include_styles,
-                styles.only_styles,
+            let cli_filters = FilterSet {
+                include: filter.include,
+                exclude: filter.exclude,
                 exclude_match,
                 exclude_matching_lines,
-                format,
-                fixed_table_width,
-                plan_out,
-                dry_run,
-                use_color,
-                acronyms.no_acronyms,
-                acronyms.include_acronyms,
-                acronyms.exclude_acronyms,
-                acronyms.only_acronyms,
-                atomic,
-                output,
-                quiet,
-                styles.ignore_ambiguous,
-                !styles.no_plural_variants,
-                false, // regex flag - not used in Plan command
-            )
+            };
+            saved_filters.run(working_dir, cli_filters, |filters| {
+                plan::handle_plan(
+                    &search,
+                    &replace,
+                    paths,
+                    stdin_path.as_deref(),
+                    filters.include,
+                    filters.exclude,
+                    filter.respect_gitignore,
+                    cli.unrestricted,
+                    cli.include_submodules,
+                    walk,
+                    paths_only,
+                    contents_only,
+                    !rename_files.no_rename_files && !rename_files.no_rename_paths,
+                    !rename_files.no_rename_dirs && !rename_files.no_rename_paths,
+                    rename_files.allow_case_collisions,
+                    styles.exclude_styles,
+                    styles.include_styles,
+                    styles.only_styles,
+                    filters.exclude_match,
+                    filters.exclude_matching_lines,
+                    exclude_lines_in_file,
+                    literal_mode(skip_strings, only_strings),
+                    match_anchor.into(),
+                    allow_short_search,
+                    coerce,
+                    structured_filter(only_keys, only_values, yaml_path),
+                    profile.as_deref(),
+                    show_skipped,
+                    swap,
+                    fuzzy,
+                    cli.yes,
+                    format,
+                    fixed_table_width,
+                    group_by.into(),
+                    preview_sample,
+                    plan_out,
+                    dry_run,
+                    split_by_codeowners.as_deref(),
+                    sign.as_deref(),
+                    use_color,
+                    acronyms.no_acronyms,
+                    acronyms.include_acronyms,
+                    acronyms.exclude_acronyms,
+                    acronyms.only_acronyms,
+                    acronyms.acronym_rendering.map(Into::into),
+                    acronyms.case_mapping.map(Into::into),
+                    atomic,
+                    output,
+                    quiet,
+                    styles.ignore_ambiguous,
+                    !styles.no_plural_variants,
+                    false, // regex flag - not used in Plan command
+                    PlanGuards {
+                        max_matches,
+                        max_files,
+                        fail_if_zero,
+                        strict,
+                    },
+                    working_dir,
+                )
+            })
         },
 
         Commands::Search {
             term,
             paths,
+            replace,
             include,
             exclude,
             rename_files,
             rename_dirs,
+            paths_only,
+            contents_only,
             styles,
             exclude_matching_lines,
+            exclude_lines_in_file,
+            saved_filters,
+            skip_strings,
+            only_strings,
+            match_anchor,
+            only_keys,
+            only_values,
+            yaml_path,
+            profile,
+            show_skipped,
+            fuzzy,
             preview,
             fixed_table_width,
+            group_by,
+            preview_sample,
             acronyms,
+            atomic,
             output,
+            count,
+            files_with_matches,
+            files_without_match,
+            json,
             quiet,
         } => {
-            // Use preview format from CLI arg or default to matches for search (unless JSON output)
-            let format = if output == OutputFormat::Json {
-                None // No preview for JSON output
-            } else {
+            let listing = search::SearchListing::from_flags(
+                count,
+                files_with_matches,
+                files_without_match,
+                json,
+            );
+            // Use preview format from CLI arg or default to matches for search (unless machine-readable output)
+            let format = if output == SearchOutputFormat::Summary && listing.is_none() {
                 Some(preview.map(std::convert::Into::into).unwrap_or_else(|| {
                     // For search, default to matches instead of diff
                     let config_format = Preview::from_str(&config.defaults.preview_format)
@@ -175,79 +360,356 @@ fn main() {
                         config_format
                     }
                 }))
+            } else {
+                None // No preview for machine-readable output
             };
 
-            // Call plan handler with empty replacement string and dry_run=true
-            plan::handle_plan(
-                &term,
-                "", // Empty replacement string for search
-                paths,
+            let cli_filters = FilterSet {
                 include,
                 exclude,
-                true, // respect_gitignore (use default true for search)
-                cli.unrestricted,
-                rename_files,
-                rename_dirs,
-                styles.exclude_styles,
-                styles.include_styles,
-                styles.only_styles,
-                vec![], // exclude_match not needed for search
+                exclude_match: vec![],
                 exclude_matching_lines,
-                format,
-                fixed_table_width,
-                PathBuf::from(".renamify/plan.json"),
-                true, // Always dry-run for search
-                use_color,
-                acronyms.no_acronyms,
-                acronyms.include_acronyms,
-                acronyms.exclude_acronyms,
-                acronyms.only_acronyms,
-                cli::args::AtomicArgs {
-                    atomic_identifiers: false,
-                    atomic_search: false,
-                    atomic_replace: false,
-                    no_atomic_identifiers: false,
-                    no_atomic_search: false,
-                    no_atomic_replace: false,
-                },
-                output,
-                quiet,
-                styles.ignore_ambiguous,
-                !styles.no_plural_variants,
-                false, // regex flag - not used in Search command
-            )
+            };
+            saved_filters.run(working_dir, cli_filters, |filters| {
+                search::handle_search(
+                    &term,
+                    replace.as_deref(),
+                    paths,
+                    filters.include,
+                    filters.exclude,
+                    cli.unrestricted,
+                    cli.include_submodules,
+                    walk,
+                    paths_only,
+                    contents_only,
+                    rename_files,
+                    rename_dirs,
+                    styles.exclude_styles,
+                    styles.include_styles,
+                    styles.only_styles,
+                    filters.exclude_match,
+                    filters.exclude_matching_lines,
+                    exclude_lines_in_file,
+                    literal_mode(skip_strings, only_strings),
+                    match_anchor.into(),
+                    structured_filter(only_keys, only_values, yaml_path),
+                    profile.as_deref(),
+                    show_skipped,
+                    fuzzy,
+                    format,
+                    fixed_table_width,
+                    group_by.into(),
+                    preview_sample,
+                    use_color,
+                    acronyms.no_acronyms,
+                    acronyms.include_acronyms,
+                    acronyms.exclude_acronyms,
+                    acronyms.only_acronyms,
+                    acronyms.acronym_rendering.map(Into::into),
+                    acronyms.case_mapping.map(Into::into),
+                    output,
+                    listing,
+                    quiet,
+                    styles.ignore_ambiguous,
+                    !styles.no_plural_variants,
+                    &atomic,
+                    working_dir,
+                )
+            })
         },
 
+        Commands::Check {
+            paths,
+            forbid,
+            include,
+            exclude,
+            output,
+            quiet,
+        } => check::handle_check(
+            paths,
+            forbid,
+            include,
+            exclude,
+            cli.unrestricted,
+            cli.include_submodules,
+            walk,
+            output,
+            quiet,
+            working_dir,
+        ),
+
+        Commands::Hook {
+            command:
+                HookCommand::PreCommit {
+                    forbid,
+                    include,
+                    exclude,
+                    output,
+                    quiet,
+                },
+        } => hook::handle_pre_commit(forbid, &include, &exclude, output, quiet, working_dir),
+
+        Commands::Apply {
+            id,
+            plan,
+            checksum,
+            trusted_key,
+            require_signed,
+            strict,
+            check: true,
+            show_tree,
+            output,
+            quiet,
+            ..
+        } => apply::handle_apply_check(
+            plan.or(id),
+            &PlanTrust {
+                checksum,
+                trusted_keys: trusted_key,
+                require_signed,
+            },
+            strict,
+            show_tree,
+            use_color,
+            output,
+            quiet,
+            working_dir,
+        ),
+
         Commands::Apply {
             id,
+            plan,
+            checksum,
+            trusted_key,
+            require_signed,
+            bundle,
             commit,
+            stage,
+            stage_only,
             force_with_conflicts,
+            strict,
+            report_stale_paths,
+            transaction_scope,
+            output,
+            quiet,
+            ..
+        } => apply::handle_apply(
+            plan.or(id),
+            &PlanTrust {
+                checksum,
+                trusted_keys: trusted_key,
+                require_signed,
+            },
+            bundle.as_deref(),
+            commit.options(&config),
+            if stage_only {
+                StageMode::IndexOnly
+            } else if stage {
+                StageMode::Staged
+            } else {
+                StageMode::Unstaged
+            },
+            force_with_conflicts,
+            strict,
+            report_stale_paths,
+            transaction_scope.into(),
+            durability,
+            backup,
+            output,
+            quiet,
+            working_dir,
+        ),
+
+        Commands::PlanDiff {
+            old_plan,
+            new_plan,
+            output,
+            quiet,
+        } => plan_diff::handle_plan_diff(&old_plan, &new_plan, output, quiet, working_dir),
+
+        Commands::ValidatePlan {
+            plan,
+            output,
+            quiet,
+        } => validate_plan::handle_validate_plan(&plan, output, quiet, working_dir),
+
+        Commands::Explain {
+            search,
+            identifier,
+            replace,
+            exclude_styles,
+            include_styles,
+            only_styles,
+            output,
+        } => explain::handle_explain(
+            &search,
+            &identifier,
+            replace.as_deref(),
+            exclude_styles,
+            include_styles,
+            only_styles,
+            config.acronym_rendering,
+            config.case_mapping,
+            output,
+        ),
+
+        Commands::PreviewFile {
+            search,
+            replace,
+            file,
+            stdin,
+            styles,
+            exclude_match,
+            exclude_matching_lines,
+            skip_strings,
+            only_strings,
+            coerce,
+            output,
+        } => preview_file::handle_preview_file(
+            &search,
+            &replace,
+            file.as_deref(),
+            stdin,
+            styles,
+            exclude_match,
+            exclude_matching_lines,
+            literal_mode(skip_strings, only_strings),
+            coerce,
+            config.acronym_rendering,
+            config.case_mapping,
+            output,
+            use_color,
+            working_dir,
+        ),
+
+        Commands::PlanMerge {
+            plans,
+            out,
             output,
             quiet,
-        } => apply::handle_apply(id, commit, force_with_conflicts, output, quiet),
+        } => plan_merge::handle_plan_merge(&plans, &out, output, quiet, working_dir),
 
-        Commands::Undo { id, output, quiet } => undo::handle_undo(&id, output, quiet),
+        Commands::Replan {
+            plan,
+            paths,
+            plan_out,
+            dry_run,
+            output,
+            quiet,
+        } => replan::handle_replan(
+            plan.as_deref(),
+            paths,
+            &plan_out,
+            dry_run,
+            output,
+            quiet,
+            working_dir,
+        ),
 
-        Commands::Redo { id, output, quiet } => redo::handle_redo(&id, output, quiet),
+        Commands::Undo {
+            id,
+            force_undo,
+            output,
+            quiet,
+        } => undo::handle_undo(&id, force_undo, output, quiet, working_dir),
+
+        Commands::Redo { id, output, quiet } => redo::handle_redo(&id, output, quiet, working_dir),
+
+        Commands::Status { output, quiet } => status::handle_status(output, quiet, working_dir),
 
-        Commands::Status { output, quiet } => status::handle_status(output, quiet),
+        Commands::History {
+            command:
+                Some(HistoryCommand::Export {
+                    id,
+                    bundle,
+                    output,
+                    quiet,
+                }),
+            ..
+        } => history::handle_history_export(&id, &bundle, output, quiet, working_dir),
+
+        Commands::History {
+            command: Some(HistoryCommand::Mapping { id, format }),
+            ..
+        } => history::handle_history_mapping(&id, format, working_dir),
 
         Commands::History {
+            command: None,
             limit,
             output,
             quiet,
-        } => history::handle_history(limit, output, quiet),
+        } => history::handle_history(limit, output, quiet, working_dir),
 
         Commands::Init {
             local,
             global,
             check,
             configure_global,
-        } => handle_init(local, global, check, configure_global),
+            suggest_ignores,
+        } => handle_init(
+            working_dir,
+            local,
+            global,
+            check,
+            configure_global,
+            suggest_ignores,
+            cli.yes,
+        ),
 
         Commands::Version { output } => handle_version(output),
 
-        Commands::TestLock { delay } => handle_test_lock(delay, Arc::clone(&interrupted)),
+        Commands::Keygen { path, force } => keygen::handle_keygen(&resolve(&path), force),
+
+        Commands::Report { output } => report::handle_report(output, working_dir),
+
+        Commands::Gc { dry_run, output } => gc::handle_gc(dry_run, output, working_dir),
+
+        Commands::Doctor { output } => doctor::handle_doctor(cli.no_color, output, working_dir),
+
+        Commands::Serve { .. } => serve::handle_serve(durability, backup, working_dir),
+
+        Commands::Completions { shell, out_dir } => {
+            completions::handle_completions(shell, out_dir.as_deref().map(resolve).as_deref())
+        },
+
+        Commands::Config {
+            command: ConfigCommand::Show { origin, output },
+        } => config::handle_config_show(origin, output, working_dir),
+
+        Commands::Config {
+            command:
+                ConfigCommand::Get {
+                    key,
+                    global,
+                    repo,
+                    output,
+                },
+        } => config::handle_config_get(&key, global, repo, output, working_dir),
+
+        Commands::Config {
+            command:
+                ConfigCommand::Set {
+                    key,
+                    value,
+                    global,
+                    repo: _,
+                    output,
+                },
+        } => config::handle_config_set(&key, &value, global, output, working_dir),
+
+        Commands::ExportTypes { out, output, quiet } => {
+            export_types::handle_export_types(&out, output, quiet, working_dir)
+        },
+
+        Commands::Man { out_dir } => man::handle_man(&resolve(&out_dir)),
+
+        Commands::HelpAll => man::handle_help_all(),
+
+        Commands::Complete { kind } => completions::handle_complete(kind, working_dir),
+
+        Commands::TestLock { delay } => {
+            handle_test_lock(working_dir, delay, Arc::clone(&interrupted))
+        },
 
         Commands::Rename {
             search,
@@ -255,12 +717,30 @@ fn main() {
             paths,
             filter,
             rename_files,
+            paths_only,
+            contents_only,
             styles,
             exclude_match,
             exclude_matching_lines,
+            exclude_lines_in_file,
+            saved_filters,
+            skip_strings,
+            only_strings,
+            match_anchor,
+            allow_short_search,
+            coerce,
+            only_keys,
+            only_values,
+            yaml_path,
+            profile,
+            swap,
             preview,
             commit,
             large,
+            large_files_threshold,
+            large_renames_threshold,
+            expect_matches,
+            expect_renames,
             force_with_conflicts,
             confirm_collisions,
             rename_root,
@@ -274,40 +754,75 @@ fn main() {
             // Use preview format from CLI arg or config default
             let format = preview.or_else(|| PreviewArg::from_str(&config.defaults.preview_format));
 
-            rename::handle_rename(
-                &search,
-                &replace,
-                paths,
-                filter.include,
-                filter.exclude,
-                cli.unrestricted,
-                !rename_files.no_rename_files && !rename_files.no_rename_paths,
-                !rename_files.no_rename_dirs && !rename_files.no_rename_paths,
-                styles.exclude_styles,
-                styles.include_styles,
-                styles.only_styles,
-                !styles.no_plural_variants,
-                styles.ignore_ambiguous,
+            let cli_filters = FilterSet {
+                include: filter.include,
+                exclude: filter.exclude,
                 exclude_match,
                 exclude_matching_lines,
-                format,
-                commit,
-                large,
-                force_with_conflicts,
-                confirm_collisions,
-                rename_root,
-                no_rename_root,
-                dry_run,
-                acronyms.no_acronyms,
-                acronyms.include_acronyms,
-                acronyms.exclude_acronyms,
-                acronyms.only_acronyms,
-                atomic,
-                cli.yes,
-                use_color,
-                output,
-                quiet,
-            )
+            };
+            saved_filters.run(working_dir, cli_filters, |filters| {
+                rename::handle_rename(
+                    &search,
+                    &replace,
+                    paths,
+                    filters.include,
+                    filters.exclude,
+                    cli.unrestricted,
+                    cli.include_submodules,
+                    walk,
+                    paths_only,
+                    contents_only,
+                    !rename_files.no_rename_files && !rename_files.no_rename_paths,
+                    !rename_files.no_rename_dirs && !rename_files.no_rename_paths,
+                    rename_files.allow_case_collisions,
+                    styles.exclude_styles,
+                    styles.include_styles,
+                    styles.only_styles,
+                    !styles.no_plural_variants,
+                    styles.ignore_ambiguous,
+                    filters.exclude_match,
+                    filters.exclude_matching_lines,
+                    exclude_lines_in_file,
+                    literal_mode(skip_strings, only_strings),
+                    match_anchor.into(),
+                    allow_short_search,
+                    coerce,
+                    structured_filter(only_keys, only_values, yaml_path),
+                    profile.as_deref(),
+                    swap,
+                    format,
+                    commit.options(&config),
+                    large,
+                    LargeChangeThresholds::from_flags_and_config(
+                        large_files_threshold,
+                        large_renames_threshold,
+                        &config.defaults,
+                    ),
+                    CountExpectations {
+                        matches: expect_matches,
+                        renames: expect_renames,
+                    },
+                    force_with_conflicts,
+                    durability,
+                    backup,
+                    confirm_collisions,
+                    rename_root,
+                    no_rename_root,
+                    dry_run,
+                    acronyms.no_acronyms,
+                    acronyms.include_acronyms,
+                    acronyms.exclude_acronyms,
+                    acronyms.only_acronyms,
+                    acronyms.acronym_rendering.map(Into::into),
+                    acronyms.case_mapping.map(Into::into),
+                    atomic,
+                    cli.yes,
+                    use_color,
+                    output,
+                    quiet,
+                    working_dir,
+                )
+            })
         },
 
         Commands::Replace {
@@ -318,9 +833,13 @@ fn main() {
             filter,
             rename_files,
             exclude_matching_lines,
+            exclude_lines_in_file,
+            coerce,
             preview,
             commit,
             large,
+            large_files_threshold,
+            large_renames_threshold,
             force_with_conflicts,
             no_plural_variants,
             dry_run,
@@ -339,19 +858,32 @@ fn main() {
                 filter.include,
                 filter.exclude,
                 cli.unrestricted,
+                cli.include_submodules,
+                walk,
                 !rename_files.no_rename_files && !rename_files.no_rename_paths,
                 !rename_files.no_rename_dirs && !rename_files.no_rename_paths,
+                rename_files.allow_case_collisions,
                 exclude_matching_lines,
+                exclude_lines_in_file,
+                coerce,
                 format,
-                commit,
+                commit.options(&config),
                 large,
+                LargeChangeThresholds::from_flags_and_config(
+                    large_files_threshold,
+                    large_renames_threshold,
+                    &config.defaults,
+                ),
                 force_with_conflicts,
+                durability,
+                backup,
                 !no_plural_variants,
                 dry_run,
                 yes || cli.yes,
                 use_color,
                 output,
                 quiet,
+                working_dir,
             )
         },
     };
@@ -366,35 +898,154 @@ fn main() {
     match result {
         Ok(()) => std::process::exit(0),
         Err(e) => {
-            eprintln!("Error: {e:#}");
+            let error = ErrorResult::from_error(&e);
+            if json_output {
+                println!("{}", error.format_json());
+            } else if porcelain_output {
+                print!("{}", error.format_porcelain());
+            }
+            eprintln!("{}", error.format_summary());
 
-            // Determine exit code based on error type
-            let exit_code = if e.to_string().contains("conflict") {
-                1 // Conflicts
-            } else if e.to_string().contains("invalid") || e.to_string().contains("not found") {
-                2 // Invalid input
-            } else {
-                3 // Internal error
-            };
+            std::process::exit(error.exit_code());
+        },
+    }
+}
 
-            std::process::exit(exit_code);
+fn wants_json_output(command: &Commands) -> bool {
+    match command {
+        Commands::Search { output, json, .. } => *output == SearchOutputFormat::Json || *json,
+        Commands::Check { output, .. }
+        | Commands::Hook {
+            command: HookCommand::PreCommit { output, .. },
+        } => *output == SearchOutputFormat::Json,
+        Commands::Plan { output, .. } => {
+            matches!(
+                output,
+                PlanOutputFormat::Json | PlanOutputFormat::WorkspaceEdit
+            )
         },
+        Commands::Apply { output, .. }
+        | Commands::Undo { output, .. }
+        | Commands::Redo { output, .. }
+        | Commands::Status { output, .. }
+        | Commands::History {
+            command: None,
+            output,
+            ..
+        } => *output == PorcelainOutputFormat::Json,
+        Commands::History {
+            command: Some(HistoryCommand::Export { output, .. }),
+            ..
+        } => *output == OutputFormat::Json,
+        Commands::History {
+            command: Some(HistoryCommand::Mapping { format, .. }),
+            ..
+        } => *format == MappingFormat::Json,
+        Commands::PreviewFile { output, .. } => *output == PreviewFileOutput::Json,
+        Commands::Rename { output, .. }
+        | Commands::Replace { output, .. }
+        | Commands::PlanDiff { output, .. }
+        | Commands::ValidatePlan { output, .. }
+        | Commands::Report { output }
+        | Commands::Gc { output, .. }
+        | Commands::Doctor { output }
+        | Commands::Explain { output, .. }
+        | Commands::PlanMerge { output, .. }
+        | Commands::Replan { output, .. }
+        | Commands::ExportTypes { output, .. }
+        | Commands::Version { output }
+        | Commands::Config {
+            command:
+                ConfigCommand::Show { output, .. }
+                | ConfigCommand::Get { output, .. }
+                | ConfigCommand::Set { output, .. },
+        } => *output == OutputFormat::Json,
+        Commands::Init { .. }
+        | Commands::Keygen { .. }
+        | Commands::Serve { .. }
+        | Commands::Completions { .. }
+        | Commands::Man { .. }
+        | Commands::HelpAll
+        | Commands::Complete { .. }
+        | Commands::TestLock { .. } => false,
+    }
+}
+
+/// Whether the command can run with the workspace disabled, when .renamify
+/// is read-only
+const fn runs_without_workspace(command: &Commands) -> bool {
+    match command {
+        Commands::Plan { .. }
+        | Commands::Search { .. }
+        | Commands::Check { .. }
+        | Commands::Hook { .. } => true,
+        Commands::Rename { dry_run, .. } | Commands::Replace { dry_run, .. } => *dry_run,
+        _ => false,
     }
 }
 
-fn is_renamify_ignored() -> Result<bool> {
+/// Map the string literal flags to the scanner's literal mode
+const fn literal_mode(skip_strings: bool, only_strings: bool) -> LiteralMode {
+    if skip_strings {
+        LiteralMode::SkipStrings
+    } else if only_strings {
+        LiteralMode::OnlyStrings
+    } else {
+        LiteralMode::All
+    }
+}
+
+/// Map the key, value and path flags to the scanner's structured file filter
+fn structured_filter(
+    only_keys: bool,
+    only_values: bool,
+    yaml_path: Vec<String>,
+) -> Option<StructuredFilter> {
+    let target = if only_keys {
+        Some(StructuredTarget::Keys)
+    } else if only_values {
+        Some(StructuredTarget::Values)
+    } else {
+        None
+    };
+    (target.is_some() || !yaml_path.is_empty()).then_some(StructuredFilter {
+        target,
+        paths: yaml_path,
+    })
+}
+
+fn wants_porcelain_output(command: &Commands) -> bool {
+    match command {
+        Commands::Plan { output, .. } => *output == PlanOutputFormat::Porcelain,
+        Commands::Apply { output, .. }
+        | Commands::Undo { output, .. }
+        | Commands::Redo { output, .. }
+        | Commands::Status { output, .. }
+        | Commands::History {
+            command: None,
+            output,
+            ..
+        } => *output == PorcelainOutputFormat::Porcelain,
+        _ => false,
+    }
+}
+
+fn is_renamify_ignored(working_dir: Option<&Path>) -> Result<bool> {
     // Check if .renamify is already ignored in any ignore file
 
-    // 1. Check .gitignore
-    if let Ok(content) = std::fs::read_to_string(".gitignore") {
+    // 1. Check .gitignore, or the repository's own ignore file (.hgignore)
+    let vcs = current_vcs(working_dir);
+    let ignore_file = vcs
+        .as_ref()
+        .map_or_else(|| ignore_file(working_dir), |vcs| vcs.ignore_file());
+    if let Ok(content) = std::fs::read_to_string(ignore_file) {
         if is_pattern_in_content(&content) {
             return Ok(true);
         }
     }
 
-    // 2. Check .git/info/exclude (if in git repo)
-    if let Ok(git_dir) = find_git_dir() {
-        let exclude_path = git_dir.join("info").join("exclude");
+    // 2. Check the local exclude file, like .git/info/exclude (if in a repo)
+    if let Some(Ok(exclude_path)) = vcs.as_ref().map(|vcs| vcs.local_exclude_file()) {
         if let Ok(content) = std::fs::read_to_string(exclude_path) {
             if is_pattern_in_content(&content) {
                 return Ok(true);
@@ -427,18 +1078,27 @@ fn is_pattern_in_content(content: &str) -> bool {
         })
 }
 
-fn check_and_auto_init(auto_init: &Option<String>, yes: bool) -> Result<()> {
+fn check_and_auto_init(
+    working_dir: Option<&Path>,
+    auto_init: &Option<String>,
+    yes: bool,
+) -> Result<()> {
     // If .renamify is already ignored, nothing to do
-    if is_renamify_ignored()? {
+    if is_renamify_ignored(working_dir)? {
         return Ok(());
     }
 
-    // Check if .renamify is tracked in git
-    if is_in_git_repo()? && is_file_tracked(".renamify")? {
-        eprintln!("\n⚠ Error: .renamify directory is already tracked by git.");
-        eprintln!("  Please run: git rm -r --cached .renamify");
-        eprintln!("  Then run your command again.");
-        process::exit(1);
+    // Check if .renamify is tracked by the repository
+    if let Some(vcs) = current_vcs(working_dir) {
+        if vcs.is_tracked(&workspace_dir(working_dir)).unwrap_or(false) {
+            eprintln!(
+                "\n⚠ Error: .renamify directory is already tracked by {}.",
+                vcs.name()
+            );
+            eprintln!("  Please run: {}", vcs.untrack_command(".renamify"));
+            eprintln!("  Then run your command again.");
+            process::exit(1);
+        }
     }
 
     // Determine init mode
@@ -466,9 +1126,9 @@ fn check_and_auto_init(auto_init: &Option<String>, yes: bool) -> Result<()> {
 
     // Perform the initialization
     match mode {
-        InitMode::Repo => do_init(false, false, false)?,
-        InitMode::Local => do_init(true, false, false)?,
-        InitMode::Global => do_init(false, true, false)?,
+        InitMode::Repo => do_init(working_dir, false, false, false)?,
+        InitMode::Local => do_init(working_dir, true, false, false)?,
+        InitMode::Global => do_init(working_dir, false, true, false)?,
         InitMode::Skip => return Ok(()),
     }
 
@@ -514,9 +1174,14 @@ fn prompt_for_init_with_input<R: io::Read>(reader: &mut R) -> Result<InitMode> {
     }
 }
 
-fn do_init(local: bool, global: bool, _configure_global: bool) -> Result<()> {
+fn do_init(
+    working_dir: Option<&Path>,
+    local: bool,
+    global: bool,
+    _configure_global: bool,
+) -> Result<()> {
     // This is the core init logic
-    const RENAMIFY_PATTERN: &str = ".renamify/";
+    let vcs = current_vcs(working_dir);
 
     // Determine which file to modify
     let target_path = if global {
@@ -524,11 +1189,18 @@ fn do_init(local: bool, global: bool, _configure_global: bool) -> Result<()> {
         get_global_excludes_path()?
     } else if local {
         // Use .git/info/exclude
-        let git_dir = find_git_dir()?;
-        git_dir.join("info").join("exclude")
+        vcs.as_ref()
+            .ok_or_else(|| anyhow!("Not in a git repository"))?
+            .local_exclude_file()?
     } else {
-        // Default: .gitignore in current directory
-        PathBuf::from(".gitignore")
+        // Default: .gitignore in the working directory (.hgignore at the top of an hg repo)
+        vcs.as_ref()
+            .map_or_else(|| ignore_file(working_dir), |vcs| vcs.ignore_file())
+    };
+    // Exclude files are always in gitignore syntax
+    let lines = match &vcs {
+        Some(vcs) if !global && !local => vcs.ignore_lines(),
+        _ => &[".renamify/"],
     };
 
     // Check if pattern already exists in file
@@ -564,8 +1236,10 @@ fn do_init(local: bool, global: bool, _configure_global: bool) -> Result<()> {
         content.push('\n'); // Blank line before our section
     }
     content.push_str("# Renamify workspace\n");
-    content.push_str(RENAMIFY_PATTERN);
-    content.push('\n');
+    for line in lines {
+        content.push_str(line);
+        content.push('\n');
+    }
 
     // Write atomically
     use std::io::Write;
@@ -631,10 +1305,18 @@ fn configure_global_excludes() -> Result<()> {
     Ok(())
 }
 
-fn handle_init(local: bool, global: bool, check: bool, configure_global: bool) -> Result<()> {
+fn handle_init(
+    working_dir: Option<&Path>,
+    local: bool,
+    global: bool,
+    check: bool,
+    configure_global: bool,
+    suggest_ignores: bool,
+    yes: bool,
+) -> Result<()> {
     // Check mode: just verify if .renamify is ignored
     if check {
-        if is_renamify_ignored()? {
+        if is_renamify_ignored(working_dir)? {
             eprintln!(".renamify is properly ignored");
             return Ok(());
         }
@@ -648,33 +1330,27 @@ fn handle_init(local: bool, global: bool, check: bool, configure_global: bool) -
     }
 
     // Use the common init logic
-    do_init(local, global, configure_global)?;
-
-    // Check if .renamify is tracked by git (only if not using --global)
-    if !global && is_in_git_repo()? && is_file_tracked(".renamify")? {
-        eprintln!("\n⚠ Warning: .renamify directory is already tracked by git.");
-        eprintln!("  You may want to run: git rm -r --cached .renamify");
+    do_init(working_dir, local, global, configure_global)?;
+
+    // Check if .renamify is tracked by the repository (only if not using --global)
+    if let Some(vcs) = current_vcs(working_dir).filter(|_| !global) {
+        if vcs.is_tracked(&workspace_dir(working_dir)).unwrap_or(false) {
+            eprintln!(
+                "\n⚠ Warning: .renamify directory is already tracked by {}.",
+                vcs.name()
+            );
+            eprintln!(
+                "  You may want to run: {}",
+                vcs.untrack_command(".renamify")
+            );
+        }
     }
 
-    Ok(())
-}
-
-fn find_git_dir() -> Result<PathBuf> {
-    let output = std::process::Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .output()
-        .context("Failed to run git rev-parse")?;
-
-    if !output.status.success() {
-        return Err(anyhow!("Not in a git repository"));
+    if suggest_ignores {
+        suggest_ignores::handle_suggest_ignores(root_dir(working_dir), yes)?;
     }
 
-    let git_dir = String::from_utf8(output.stdout)
-        .context("Invalid UTF-8 in git output")?
-        .trim()
-        .to_string();
-
-    Ok(PathBuf::from(git_dir))
+    Ok(())
 }
 
 fn get_global_excludes_path() -> Result<PathBuf> {
@@ -709,44 +1385,59 @@ fn get_global_excludes_path() -> Result<PathBuf> {
     }
 }
 
-fn is_in_git_repo() -> Result<bool> {
-    let output = std::process::Command::new("git")
-        .args(["rev-parse", "--is-inside-work-tree"])
-        .output()
-        .context("Failed to run git rev-parse")?;
+/// The directory commands run in: the `-C` directory, or the current directory
+fn root_dir(working_dir: Option<&Path>) -> &Path {
+    working_dir.unwrap_or_else(|| Path::new("."))
+}
 
-    Ok(output.status.success())
+/// The directory output shows paths relative to: the `-C` directory, or the
+/// current directory
+fn display_dir(working_dir: Option<&Path>) -> Option<PathBuf> {
+    working_dir
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok())
 }
 
-fn is_file_tracked(path: &str) -> Result<bool> {
-    let output = std::process::Command::new("git")
-        .args(["ls-files", "--error-unmatch", path])
-        .output()
-        .context("Failed to run git ls-files")?;
+/// The `.renamify` directory of the working directory, as an absolute path
+fn workspace_dir(working_dir: Option<&Path>) -> PathBuf {
+    let root = root_dir(working_dir);
+    root.canonicalize()
+        .unwrap_or_else(|_| root.to_path_buf())
+        .join(".renamify")
+}
 
-    Ok(output.status.success())
+/// The git, jj or hg repository the working directory is in
+fn current_vcs(working_dir: Option<&Path>) -> Option<Box<dyn Vcs>> {
+    renamify_core::vcs::detect(root_dir(working_dir))
 }
 
-// Generate shell completions
-pub fn generate_completions<G: clap_complete::Generator>(
-    gen: G,
-    cmd: &mut clap::Command,
-    name: &str,
-    out_dir: &std::path::Path,
-) -> Result<()> {
-    use clap_complete::generate_to;
-    use std::fs;
+/// The `.gitignore` of the working directory, for when it isn't in a repository
+fn ignore_file(working_dir: Option<&Path>) -> PathBuf {
+    working_dir.map_or_else(|| PathBuf::from(".gitignore"), |dir| dir.join(".gitignore"))
+}
 
-    fs::create_dir_all(out_dir)?;
-    let path = generate_to(gen, cmd, name, out_dir)?;
-    println!("Generated completion file: {}", path.display());
-    Ok(())
+/// Build metadata embedded by build.rs (empty values mean unknown)
+fn build_info() -> BuildInfo {
+    let known = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
+    BuildInfo {
+        git_commit: known(env!("RENAMIFY_GIT_COMMIT")),
+        build_date: known(env!("RENAMIFY_BUILD_DATE")),
+        rustc_version: known(env!("RENAMIFY_RUSTC_VERSION")),
+        target: known(env!("RENAMIFY_TARGET")),
+        features: env!("RENAMIFY_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_string)
+            .collect(),
+    }
 }
 
 fn handle_version(output: OutputFormat) -> Result<()> {
     let version_result = VersionResult {
         name: "renamify".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        build: Some(build_info()),
     };
 
     let formatted = match output {
@@ -758,13 +1449,16 @@ fn handle_version(output: OutputFormat) -> Result<()> {
     Ok(())
 }
 
-fn handle_test_lock(delay: u64, interrupted: Arc<AtomicBool>) -> Result<()> {
+fn handle_test_lock(
+    working_dir: Option<&Path>,
+    delay: u64,
+    interrupted: Arc<AtomicBool>,
+) -> Result<()> {
     use renamify_core::LockFile;
     use std::thread;
     use std::time::Duration;
 
-    let current_dir = std::env::current_dir().expect("Failed to get current directory");
-    let renamify_dir = current_dir.join(".renamify");
+    let renamify_dir = root_dir(working_dir).join(".renamify");
 
     // Ensure .renamify directory exists
     if !renamify_dir.exists() {
@@ -799,90 +1493,6 @@ fn handle_test_lock(delay: u64, interrupted: Arc<AtomicBool>) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap_complete::Shell;
-    use tempfile::TempDir;
-
-    #[test]
-    fn test_generate_completions_bash() {
-        use clap::CommandFactory;
-        let temp_dir = TempDir::new().unwrap();
-        let mut cmd = <Cli as CommandFactory>::command();
-
-        let result = generate_completions(Shell::Bash, &mut cmd, "renamify", temp_dir.path());
-
-        assert!(result.is_ok());
-
-        // Check that the completion file was created
-        let completion_file = temp_dir.path().join("renamify.bash");
-        assert!(completion_file.exists());
-
-        // Read and verify the content has bash completion markers
-        let content = std::fs::read_to_string(completion_file).unwrap();
-        assert!(content.contains("complete"));
-        assert!(content.contains("renamify"));
-    }
-
-    #[test]
-    fn test_generate_completions_zsh() {
-        use clap::CommandFactory;
-        let temp_dir = TempDir::new().unwrap();
-        let mut cmd = <Cli as CommandFactory>::command();
-
-        let result = generate_completions(Shell::Zsh, &mut cmd, "renamify", temp_dir.path());
-
-        assert!(result.is_ok());
-
-        // Check that the completion file was created
-        let completion_file = temp_dir.path().join("_renamify");
-        assert!(completion_file.exists());
-
-        // Read and verify the content has zsh completion markers
-        let content = std::fs::read_to_string(completion_file).unwrap();
-        assert!(content.contains("#compdef"));
-        assert!(content.contains("renamify"));
-    }
-
-    #[test]
-    fn test_generate_completions_fish() {
-        use clap::CommandFactory;
-        let temp_dir = TempDir::new().unwrap();
-        let mut cmd = <Cli as CommandFactory>::command();
-
-        let result = generate_completions(Shell::Fish, &mut cmd, "renamify", temp_dir.path());
-
-        assert!(result.is_ok());
-
-        // Check that the completion file was created
-        let completion_file = temp_dir.path().join("renamify.fish");
-        assert!(completion_file.exists());
-
-        // Read and verify the content has fish completion markers
-        let content = std::fs::read_to_string(completion_file).unwrap();
-        assert!(content.contains("complete"));
-        assert!(content.contains("-c renamify"));
-    }
-
-    #[test]
-    fn test_generate_completions_creates_directory() {
-        use clap::CommandFactory;
-        let temp_dir = TempDir::new().unwrap();
-        let nested_path = temp_dir.path().join("nested").join("dir");
-        let mut cmd = <Cli as CommandFactory>::command();
-
-        // Directory doesn't exist yet
-        assert!(!nested_path.exists());
-
-        let result = generate_completions(Shell::Bash, &mut cmd, "renamify", &nested_path);
-
-        assert!(result.is_ok());
-
-        // Directory was created
-        assert!(nested_path.exists());
-
-        // File was created in the directory
-        let completion_file = nested_path.join("renamify.bash");
-        assert!(completion_file.exists());
-    }
 
     #[test]
     fn test_prompt_for_init_repo() {
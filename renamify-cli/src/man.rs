@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::cli::Cli;
+
+/// Write `renamify.1` plus one page per visible subcommand (`renamify-plan.1`, ...)
+pub fn handle_man(out_dir: &Path) -> Result<()> {
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create directory: {}", out_dir.display()))?;
+    clap_mangen::generate_to(Cli::command(), out_dir)
+        .with_context(|| format!("Failed to write man pages to {}", out_dir.display()))?;
+    println!("Generated man pages in: {}", out_dir.display());
+    Ok(())
+}
+
+pub fn handle_help_all() -> Result<()> {
+    let mut stdout = io::stdout();
+    write_help_all(&mut stdout)
+}
+
+/// Print the long help of the top-level command followed by every visible
+/// subcommand, so all flags are documented in one place
+fn write_help_all(out: &mut dyn Write) -> Result<()> {
+    let mut cmd = Cli::command().disable_help_subcommand(true);
+    cmd.build();
+
+    writeln!(out, "{}", cmd.render_long_help())?;
+
+    for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        let mut sub = sub.clone().bin_name(format!("renamify {}", sub.get_name()));
+        writeln!(out, "\n{}", "=".repeat(80))?;
+        writeln!(out, "renamify {}", sub.get_name())?;
+        writeln!(out, "{}\n", "=".repeat(80))?;
+        writeln!(out, "{}", sub.render_long_help())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_handle_man_writes_subcommand_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let out_dir = temp_dir.path().join("man1");
+
+        handle_man(&out_dir).unwrap();
+
+        assert!(out_dir.join("renamify.1").exists());
+        assert!(out_dir.join("renamify-plan.1").exists());
+        assert!(out_dir.join("renamify-undo.1").exists());
+        // Hidden subcommands are internal and get no page
+        assert!(!out_dir.join("renamify-test-lock.1").exists());
+        assert!(!out_dir.join("renamify-man.1").exists());
+    }
+
+    #[test]
+    fn test_help_all_covers_every_subcommand() {
+        let mut out = Vec::new();
+        write_help_all(&mut out).unwrap();
+        let help = String::from_utf8(out).unwrap();
+
+        assert!(help.contains("renamify plan"));
+        assert!(help.contains("--max-matches"));
+        assert!(help.contains("renamify check"));
+        assert!(help.contains("--forbid"));
+        assert!(!help.contains("test-lock"));
+    }
+}
@@ -1,53 +1,102 @@
-use anyhow::Result;
-use renamify_core::{plan_operation, OutputFormatter, Style};
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use renamify_core::acronym::AcronymRendering;
+use renamify_core::case_model::CaseMapping;
+use renamify_core::operations::plan::{accept_near_misses, accept_suspicious_renames, PlanGuards};
+use renamify_core::signing::sign_plan_file;
+use renamify_core::suppress::FileLines;
+use renamify_core::{
+    plan_operation, plan_split_operation, CoercionMode, LiteralMode, MatchAnchor, OutputFormatter,
+    PorcelainFormatter, RenamifyError, StructuredFilter, Style, WalkOptions,
+};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 
-use crate::cli::{args::AtomicArgs, types::StyleArg, OutputFormat};
-use renamify_core::Preview;
+use crate::cli::{args::AtomicArgs, types::StyleArg, PlanOutputFormat};
+use renamify_core::{GroupBy, Preview};
 
 #[allow(clippy::too_many_arguments)]
 pub fn handle_plan(
     search: &str,
     replace: &str,
     paths: Vec<PathBuf>,
+    stdin_path: Option<&Path>,
     include: Vec<String>,
     exclude: Vec<String>,
     respect_gitignore: bool,
     unrestricted: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    paths_only: bool,
+    contents_only: bool,
     rename_files: bool,
     rename_dirs: bool,
+    allow_case_collisions: bool,
     exclude_styles: Vec<StyleArg>,
     include_styles: Vec<StyleArg>,
     only_styles: Vec<StyleArg>,
     exclude_match: Vec<String>,
     exclude_matching_lines: Option<String>,
+    exclude_lines_in_file: Vec<FileLines>,
+    literal_mode: LiteralMode,
+    match_anchor: MatchAnchor,
+    allow_short_search: bool,
+    coerce: CoercionMode,
+    structured: Option<StructuredFilter>,
+    profile: Option<&str>,
+    show_skipped: bool,
+    swap: bool,
+    fuzzy: u8,
+    yes: bool,
     preview: Option<Preview>,
     fixed_table_width: bool,
+    group_by: GroupBy,
+    preview_sample: Option<usize>,
     plan_out: PathBuf,
     dry_run: bool,
+    split_by_codeowners: Option<&Path>,
+    sign: Option<&Path>,
     use_color: bool,
     no_acronyms: bool,
     include_acronyms: Vec<String>,
     exclude_acronyms: Vec<String>,
     only_acronyms: Vec<String>,
+    acronym_rendering: Option<AcronymRendering>,
+    case_mapping: Option<CaseMapping>,
     atomic: AtomicArgs,
-    output: OutputFormat,
+    output: PlanOutputFormat,
     quiet: bool,
     ignore_ambiguous: bool,
     enable_plural_variants: bool,
     _regex: bool, // TODO: Implement regex mode
+    guards: PlanGuards,
+    working_dir: Option<&Path>,
 ) -> Result<()> {
     // Error if both preview and JSON output are specified
-    if preview.is_some() && preview != Some(Preview::None) && output == OutputFormat::Json {
-        return Err(anyhow::anyhow!(
+    if preview.is_some() && preview != Some(Preview::None) && output == PlanOutputFormat::Json {
+        return Err(RenamifyError::InvalidInput(
             "Cannot use both --preview and --output json. Use --output json for machine-readable output."
-        ));
+                .to_string(),
+        )
+        .into());
     }
+
     // Validate that --fixed-table-width is only used with table preview
     if fixed_table_width && preview.is_some() && preview != Some(Preview::Table) {
-        return Err(anyhow::anyhow!(
-            "--fixed-table-width can only be used with --preview table"
-        ));
+        return Err(RenamifyError::InvalidInput(
+            "--fixed-table-width can only be used with --preview table".to_string(),
+        )
+        .into());
+    }
+
+    if group_by == GroupBy::Variant
+        && preview.is_some()
+        && !matches!(preview, Some(Preview::Table | Preview::Matches))
+    {
+        return Err(RenamifyError::InvalidInput(
+            "--group-by variant can only be used with --preview table or --preview matches"
+                .to_string(),
+        )
+        .into());
     }
 
     // Expand any shorthand styles (e.g., space-separated) before conversion
@@ -68,41 +117,55 @@ pub fn handle_plan(
         .collect();
 
     // Load config to get atomic identifiers
-    let config = renamify_core::Config::load().unwrap_or_default();
+    let config = working_dir
+        .map_or_else(renamify_core::Config::load, renamify_core::Config::load_in)
+        .unwrap_or_default();
+    let profile = profile.map(|name| config.profile(name)).transpose()?;
+
+    // A single short or common word matches inside countless unrelated identifiers
+    if let Some(reason) = config.short_search.check(search) {
+        if !allow_short_search {
+            return Err(RenamifyError::InvalidInput(format!(
+                "{reason}, so it is likely to match far more than intended. Narrow it with --match-anchor, or pass --allow-short-search to plan it anyway"
+            ))
+            .into());
+        }
+    }
 
     // Build atomic config from CLI args and config file
-    let atomic_config = renamify_core::atomic::AtomicConfig::from_flags_and_config(
-        atomic.atomic_identifiers,
-        atomic.atomic_search || (atomic.atomic_identifiers && !atomic.no_atomic_search),
-        atomic.atomic_replace || (atomic.atomic_identifiers && !atomic.no_atomic_replace),
-        config.atomic,
-    );
+    let atomic_config = atomic.to_config(config.atomic);
 
     // Handle quiet mode - overrides preview to none unless output is json
-    let effective_preview = if quiet && output != OutputFormat::Json {
+    let effective_preview = if quiet && output == PlanOutputFormat::Summary {
         None
     } else {
         preview
     };
 
-    // For JSON output, don't generate preview
-    let preview_format = if output == OutputFormat::Json {
+    // For machine-readable output, don't generate preview
+    let preview_format = if output != PlanOutputFormat::Summary {
         None
     } else {
-        effective_preview.map(|p| match p {
-            Preview::Table => "table".to_string(),
-            Preview::Diff => "diff".to_string(),
-            Preview::Matches => "matches".to_string(),
-            Preview::Summary => "summary".to_string(),
-            Preview::None => "none".to_string(),
-        })
+        effective_preview.map(|p| p.name().to_string())
     };
 
+    // Tooling pipes in content that isn't on disk yet, such as a staged blob
+    let stdin_content = stdin_path
+        .map(|_| -> Result<Vec<u8>> {
+            let mut content = Vec::new();
+            io::stdin()
+                .read_to_end(&mut content)
+                .context("Failed to read the content from stdin")?;
+            Ok(content)
+        })
+        .transpose()?;
+
     // Call the core operation
-    let (result, preview_content) = plan_operation(
+    let (mut result, preview_content) = plan_operation(
         search,
         replace,
         paths,
+        stdin_path.zip(stdin_content.as_deref()),
         include,
         exclude,
         respect_gitignore,
@@ -114,37 +177,169 @@ pub fn handle_plan(
         &only_styles,
         exclude_match,
         exclude_matching_lines,
-        Some(plan_out),
+        exclude_lines_in_file,
+        Some(plan_out.clone()),
         preview_format.as_ref(),
         dry_run,
         fixed_table_width,
+        group_by,
+        preview_sample,
         use_color,
         no_acronyms,
         include_acronyms,
         exclude_acronyms,
         only_acronyms,
+        acronym_rendering,
+        case_mapping,
         enable_plural_variants,
         ignore_ambiguous,
-        None, // working_dir
+        allow_case_collisions,
+        literal_mode,
+        match_anchor,
+        coerce,
+        structured,
+        profile.as_ref(),
+        show_skipped,
+        swap,
+        fuzzy,
+        include_submodules,
+        walk,
+        paths_only,
+        contents_only,
+        working_dir,
         Some(&atomic_config),
+        Some(&guards),
+        None, // observer
     )?;
 
+    // Plan files are written relative to the working directory
+    let resolve =
+        |path: &Path| working_dir.map_or_else(|| path.to_path_buf(), |dir| dir.join(path));
+
+    // Near misses and suspicious renames need a yes from the user, so the
+    // preview comes first
+    let (near_misses, suspicious) = result.plan.as_ref().map_or((0, 0), |plan| {
+        (plan.near_misses.len(), plan.suspicious_renames.len())
+    });
+    let interactive = output == PlanOutputFormat::Summary
+        && !quiet
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal();
+    let confirm_near_misses = |result: &mut renamify_core::PlanResult| -> Result<()> {
+        if dry_run {
+            return Ok(());
+        }
+        if near_misses > 0 {
+            if yes
+                || (interactive
+                    && prompt(&format!("Add the {near_misses} near misses to the plan?"))?)
+            {
+                let added = accept_near_misses(result, &resolve(&plan_out))?;
+                eprintln!("Added {added} near misses to the plan");
+            } else if output != PlanOutputFormat::Json {
+                eprintln!(
+                    "Left {near_misses} near misses out of the plan (pass --yes to include them)"
+                );
+            }
+        }
+        if suspicious > 0 {
+            let question = format!(
+                "Add the {suspicious} renames into ignored or protected paths to the plan?"
+            );
+            if yes || (interactive && prompt(&question)?) {
+                let added = accept_suspicious_renames(result, &resolve(&plan_out))?;
+                eprintln!("Added {added} renames into ignored or protected paths to the plan");
+            } else if output != PlanOutputFormat::Json {
+                eprintln!(
+                    "Left {suspicious} renames into ignored or protected paths out of the plan (pass --yes to include them)"
+                );
+            }
+        }
+        Ok(())
+    };
+    // Split once the near misses are settled, so the split plans match the plan
+    let split_plan = |result: &mut renamify_core::PlanResult| -> Result<()> {
+        if let (Some(out_dir), Some(plan)) = (split_by_codeowners, &result.plan) {
+            result.owner_plans = plan_split_operation(plan, out_dir, working_dir)?;
+        }
+        Ok(())
+    };
+    // Sign last, once the plan files are written for good
+    let sign_plans = |result: &renamify_core::PlanResult| -> Result<()> {
+        let Some(key) = sign else {
+            return Ok(());
+        };
+        sign_plan_file(&resolve(&plan_out), key)?;
+        for owner_plan in &result.owner_plans {
+            sign_plan_file(&resolve(&owner_plan.plan_path), key)?;
+        }
+        Ok(())
+    };
+
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        PlanOutputFormat::Json => {
+            confirm_near_misses(&mut result)?;
+            split_plan(&mut result)?;
+            sign_plans(&result)?;
             print!("{}", result.format_json());
         },
-        OutputFormat::Summary => {
+        PlanOutputFormat::Porcelain => {
+            confirm_near_misses(&mut result)?;
+            split_plan(&mut result)?;
+            sign_plans(&result)?;
+            print!("{}", result.format_porcelain());
+        },
+        PlanOutputFormat::WorkspaceEdit => {
+            confirm_near_misses(&mut result)?;
+            split_plan(&mut result)?;
+            sign_plans(&result)?;
+            let base = match working_dir {
+                Some(dir) => dir.to_path_buf(),
+                None => std::env::current_dir()?,
+            };
+            let edit = result.plan.as_ref().map_or_else(
+                || {
+                    Ok(renamify_core::lsp::WorkspaceEdit {
+                        document_changes: Vec::new(),
+                    })
+                },
+                |plan| renamify_core::lsp::workspace_edit(plan, &base),
+            )?;
+            println!("{}", serde_json::to_string_pretty(&edit)?);
+        },
+        PlanOutputFormat::Summary => {
             if !quiet {
                 // Print preview content if available
                 if let Some(preview) = preview_content {
-                    println!("{}", preview);
+                    renamify_core::pager::page(&preview);
                 }
+            }
+            confirm_near_misses(&mut result)?;
+            split_plan(&mut result)?;
+            sign_plans(&result)?;
+            if !quiet {
                 // Print summary
                 print!("{}", result.format_summary());
             }
         },
     }
+    if output != PlanOutputFormat::Json {
+        for warning in &result.warnings {
+            eprintln!("Warning: {warning}");
+        }
+    }
 
     Ok(())
 }
+
+fn prompt(question: &str) -> Result<bool> {
+    let _guard = renamify_core::interrupt::ConfirmationPromptGuard::activate();
+    print!("{question} [y/N]: ");
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+use renamify_core::{plan_diff_operation, OutputFormatter};
+use std::path::Path;
+
+use crate::OutputFormat;
+
+pub fn handle_plan_diff(
+    old_plan: &Path,
+    new_plan: &Path,
+    output: OutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = plan_diff_operation(old_plan, new_plan, working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    Ok(())
+}
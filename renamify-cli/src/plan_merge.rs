@@ -0,0 +1,29 @@
+use anyhow::Result;
+use renamify_core::{plan_merge_operation, OutputFormatter};
+use std::path::{Path, PathBuf};
+
+use crate::OutputFormat;
+
+pub fn handle_plan_merge(
+    plans: &[PathBuf],
+    out: &Path,
+    output: OutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = plan_merge_operation(plans, out, working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use renamify_core::acronym::AcronymRendering;
+use renamify_core::case_model::CaseMapping;
+use renamify_core::{preview_file_operation, CoercionMode, LiteralMode, OutputFormatter, Style};
+use std::io::Read;
+use std::path::Path;
+
+use crate::cli::{args::StyleArgs, PreviewFileOutput};
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::needless_pass_by_value)]
+pub fn handle_preview_file(
+    search: &str,
+    replace: &str,
+    file: Option<&Path>,
+    stdin: bool,
+    styles: StyleArgs,
+    exclude_match: Vec<String>,
+    exclude_matching_lines: Option<String>,
+    literal_mode: LiteralMode,
+    coerce: CoercionMode,
+    acronym_rendering: AcronymRendering,
+    case_mapping: CaseMapping,
+    output: PreviewFileOutput,
+    use_color: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    // Editors pipe unsaved buffers, so stdin wins unless only --file is given
+    let content = match file {
+        Some(path) if !stdin => std::fs::read_to_string(
+            working_dir.map_or_else(|| path.to_path_buf(), |dir| dir.join(path)),
+        )
+        .with_context(|| format!("Failed to read {}", path.display()))?,
+        _ => {
+            let mut buffer = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buffer)
+                .context("Failed to read the buffer from stdin")?;
+            buffer
+        },
+    };
+
+    let expand = |styles: Vec<crate::cli::types::StyleArg>| -> Vec<Style> {
+        styles
+            .into_iter()
+            .flat_map(|s| s.expand())
+            .map(Into::into)
+            .collect()
+    };
+
+    let (result, diff) = preview_file_operation(
+        search,
+        replace,
+        file,
+        &content,
+        &expand(styles.exclude_styles),
+        &expand(styles.include_styles),
+        &expand(styles.only_styles),
+        !styles.no_plural_variants,
+        styles.ignore_ambiguous,
+        exclude_match,
+        exclude_matching_lines,
+        literal_mode,
+        coerce,
+        acronym_rendering,
+        case_mapping,
+        output == PreviewFileOutput::Diff,
+        use_color,
+    )?;
+
+    match output {
+        PreviewFileOutput::Json => print!("{}", result.format_json()),
+        PreviewFileOutput::Diff => print!("{}", diff.unwrap_or_default()),
+        PreviewFileOutput::Content => print!("{}", result.format_summary()),
+    }
+
+    Ok(())
+}
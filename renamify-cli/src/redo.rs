@@ -1,17 +1,26 @@
 use anyhow::Result;
-use renamify_core::{redo_operation, OutputFormatter};
+use renamify_core::{redo_operation, OutputFormatter, PorcelainFormatter};
 
-use crate::OutputFormat;
+use crate::cli::PorcelainOutputFormat;
+use std::path::Path;
 
-pub fn handle_redo(id: &str, output: OutputFormat, quiet: bool) -> Result<()> {
-    let result = redo_operation(id, None)?;
+pub fn handle_redo(
+    id: &str,
+    output: PorcelainOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = redo_operation(id, working_dir)?;
 
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        PorcelainOutputFormat::Json => {
             print!("{}", result.format_json());
         },
-        OutputFormat::Summary => {
+        PorcelainOutputFormat::Porcelain => {
+            print!("{}", result.format_porcelain());
+        },
+        PorcelainOutputFormat::Summary => {
             if !quiet {
                 print!("{}", result.format_summary());
             }
@@ -1,6 +1,14 @@
 use anyhow::Result;
-use renamify_core::{rename_operation, OutputFormatter, Style};
-use std::path::PathBuf;
+use renamify_core::acronym::AcronymRendering;
+use renamify_core::case_model::CaseMapping;
+use renamify_core::operations::plan::{CountExpectations, LargeChangeThresholds};
+use renamify_core::suppress::FileLines;
+use renamify_core::vcs::CommitOptions;
+use renamify_core::{
+    rename_operation, BackupStrategy, CoercionMode, Durability, LiteralMode, MatchAnchor,
+    OutputFormatter, RenamifyError, StructuredFilter, Style, WalkOptions,
+};
+use std::path::{Path, PathBuf};
 
 use crate::cli::{args::AtomicArgs, types::StyleArg, OutputFormat, PreviewArg};
 
@@ -12,8 +20,13 @@ pub fn handle_rename(
     include: Vec<String>,
     exclude: Vec<String>,
     unrestricted: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    paths_only: bool,
+    contents_only: bool,
     rename_files: bool,
     rename_dirs: bool,
+    allow_case_collisions: bool,
     exclude_styles: Vec<StyleArg>,
     include_styles: Vec<StyleArg>,
     only_styles: Vec<StyleArg>,
@@ -21,10 +34,22 @@ pub fn handle_rename(
     ignore_ambiguous: bool,
     exclude_match: Vec<String>,
     exclude_matching_lines: Option<String>,
+    exclude_lines_in_file: Vec<FileLines>,
+    literal_mode: LiteralMode,
+    match_anchor: MatchAnchor,
+    allow_short_search: bool,
+    coerce: CoercionMode,
+    structured: Option<StructuredFilter>,
+    profile: Option<&str>,
+    swap: bool,
     preview: Option<PreviewArg>,
-    commit: bool,
+    commit: Option<CommitOptions>,
     large: bool,
+    large_thresholds: LargeChangeThresholds,
+    expect: CountExpectations,
     force_with_conflicts: bool,
+    durability: Durability,
+    backup: BackupStrategy,
     _confirm_collisions: bool, // TODO: implement collision detection
     rename_root: bool,
     no_rename_root: bool,
@@ -33,11 +58,14 @@ pub fn handle_rename(
     include_acronyms: Vec<String>,
     exclude_acronyms: Vec<String>,
     only_acronyms: Vec<String>,
+    acronym_rendering: Option<AcronymRendering>,
+    case_mapping: Option<CaseMapping>,
     atomic: AtomicArgs,
     auto_approve: bool,
     use_color: bool,
     output: OutputFormat,
     quiet: bool,
+    working_dir: Option<&Path>,
 ) -> Result<()> {
     // Expand any shorthand styles (e.g., space-separated) before conversion
     let exclude_styles: Vec<Style> = exclude_styles
@@ -57,15 +85,23 @@ pub fn handle_rename(
         .collect();
 
     // Load config to get atomic identifiers
-    let config = renamify_core::Config::load().unwrap_or_default();
+    let config = working_dir
+        .map_or_else(renamify_core::Config::load, renamify_core::Config::load_in)
+        .unwrap_or_default();
+    let profile = profile.map(|name| config.profile(name)).transpose()?;
+
+    // A single short or common word matches inside countless unrelated identifiers
+    if let Some(reason) = config.short_search.check(search) {
+        if !allow_short_search {
+            return Err(RenamifyError::InvalidInput(format!(
+                "{reason}, so it is likely to match far more than intended. Narrow it with --match-anchor, or pass --allow-short-search to rename it anyway"
+            ))
+            .into());
+        }
+    }
 
     // Build atomic config from CLI args and config file
-    let atomic_config = renamify_core::atomic::AtomicConfig::from_flags_and_config(
-        atomic.atomic_identifiers,
-        atomic.atomic_search || (atomic.atomic_identifiers && !atomic.no_atomic_search),
-        atomic.atomic_replace || (atomic.atomic_identifiers && !atomic.no_atomic_replace),
-        config.atomic,
-    );
+    let atomic_config = atomic.to_config(config.atomic);
 
     // Handle quiet mode - overrides preview to none unless output is json
     let effective_preview = if quiet && output != OutputFormat::Json {
@@ -95,6 +131,10 @@ pub fn handle_rename(
         &include,
         &exclude,
         unrestricted,
+        include_submodules,
+        walk,
+        paths_only,
+        contents_only,
         rename_files,
         rename_dirs,
         &exclude_styles,
@@ -102,12 +142,24 @@ pub fn handle_rename(
         &only_styles,
         enable_plural_variants,
         ignore_ambiguous,
+        allow_case_collisions,
+        literal_mode,
+        match_anchor,
+        coerce,
+        structured,
+        profile.as_ref(),
+        swap,
         &exclude_match,
         exclude_matching_lines.as_ref(),
+        &exclude_lines_in_file,
         preview_format.as_ref(),
-        commit,
+        commit.as_ref(),
         large,
+        &large_thresholds,
+        &expect,
         force_with_conflicts,
+        durability,
+        backup,
         rename_root,
         no_rename_root,
         dry_run,
@@ -115,6 +167,9 @@ pub fn handle_rename(
         &include_acronyms,
         &exclude_acronyms,
         &only_acronyms,
+        acronym_rendering,
+        case_mapping,
+        working_dir,
         Some(&atomic_config),
         auto_approve,
         use_color,
@@ -129,13 +184,18 @@ pub fn handle_rename(
             if !quiet {
                 // Print preview content if available
                 if let Some(preview) = preview_content {
-                    println!("{}", preview);
+                    renamify_core::pager::page(&preview);
                 }
                 // Print summary
                 print!("{}", result.format_summary());
             }
         },
     }
+    if output != OutputFormat::Json {
+        for warning in result.plan.iter().flat_map(|plan| &plan.warnings) {
+            eprintln!("Warning: {warning}");
+        }
+    }
 
     Ok(())
 }
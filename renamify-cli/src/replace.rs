@@ -1,8 +1,14 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 use regex::Regex;
-use renamify_core::{apply_plan, create_simple_plan, Plan, PlanOptions, Preview};
+use renamify_core::operations::plan::LargeChangeThresholds;
+use renamify_core::suppress::FileLines;
+use renamify_core::vcs::{CommitOptions, Vcs};
+use renamify_core::{
+    apply_plan, create_simple_plan, BackupStrategy, CoercionMode, Durability, Plan, PlanOptions,
+    Preview, RenamifyError, WalkOptions,
+};
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cli::{OutputFormat, PreviewArg};
 
@@ -15,24 +21,42 @@ pub fn handle_replace(
     include: Vec<String>,
     exclude: Vec<String>,
     unrestricted: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
     rename_files: bool,
     rename_dirs: bool,
+    allow_case_collisions: bool,
     exclude_matching_lines: Option<String>,
+    exclude_lines_in_file: Vec<FileLines>,
+    coerce: CoercionMode,
     preview: Option<PreviewArg>,
-    commit: bool,
+    commit: Option<CommitOptions>,
     large: bool,
+    large_thresholds: LargeChangeThresholds,
     force_with_conflicts: bool,
+    durability: Durability,
+    backup: BackupStrategy,
     enable_plural_variants: bool,
     dry_run: bool,
     yes: bool,
     use_color: bool,
     output: OutputFormat,
     quiet: bool,
+    working_dir: Option<&Path>,
 ) -> Result<()> {
+    // Relative paths are relative to the working directory
+    let root = working_dir.map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    let paths: Vec<PathBuf> = match working_dir {
+        Some(dir) if paths.is_empty() => vec![dir.to_path_buf()],
+        Some(dir) => paths.iter().map(|path| dir.join(path)).collect(),
+        None => paths,
+    };
+
     // Create plan options for regex/literal replacement
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines,
+        exclude_lines_in_file,
         no_acronyms: true, // Disable acronym detection for replace
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -47,9 +71,23 @@ pub fn handle_replace(
         rename_dirs,
         rename_root: false,
         plan_out: PathBuf::from(".renamify/plan.json"),
-        coerce_separators: renamify_core::scanner::CoercionMode::Off,
+        coerce_separators: coerce,
         atomic_config: None, // Replace doesn't use atomic mode
         enable_plural_variants,
+        allow_case_collisions,
+        literal_mode: renamify_core::LiteralMode::All, // Replace matches raw text everywhere
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: working_dir.map(Path::to_path_buf),
+        walk,
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(), // Replace never changes case
     };
 
     // Create the plan using simple regex/literal replacement
@@ -58,7 +96,9 @@ pub fn handle_replace(
         create_simple_plan(pattern, replacement, paths, &options, false)?
     } else {
         // Regex replacement - validate the pattern first
-        Regex::new(pattern).with_context(|| format!("Invalid regex pattern: {}", pattern))?;
+        Regex::new(pattern).map_err(|e| {
+            RenamifyError::InvalidInput(format!("Invalid regex pattern: {}: {}", pattern, e))
+        })?;
 
         // Create plan with regex replacement
         create_simple_plan(pattern, replacement, paths, &options, true)?
@@ -74,13 +114,13 @@ pub fn handle_replace(
             .len();
         let total_renames = plan.paths.len();
 
-        if total_files > 500 || total_renames > 100 {
+        if large_thresholds.is_exceeded(total_files, total_renames) {
             eprintln!(
                 "Warning: This will affect {} files and rename {} files/directories.",
                 total_files, total_renames
             );
             eprintln!("Use --large to acknowledge large changes or --yes to skip this check.");
-            return Err(anyhow!("Large change requires --large flag"));
+            large_thresholds.check(total_files, total_renames)?;
         }
     }
 
@@ -112,8 +152,18 @@ pub fn handle_replace(
     if !quiet {
         let preview_format = preview.map(|p| p.into()).unwrap_or(Preview::Summary);
 
-        let output = renamify_core::render_plan(&plan, preview_format, Some(use_color));
-        print!("{}", output);
+        let output = renamify_core::render_plan_in(
+            &plan,
+            preview_format,
+            Some(use_color),
+            false,
+            renamify_core::GroupBy::File,
+            None,
+            working_dir,
+        );
+        if !output.is_empty() {
+            renamify_core::pager::page(output.strip_suffix('\n').unwrap_or(&output));
+        }
     }
 
     // If dry-run, we're done
@@ -135,27 +185,39 @@ pub fn handle_replace(
         }
     }
 
+    renamify_core::workspace::require("replace")?;
+
     // Ensure .renamify directory exists before applying
     // (it may not exist in fresh repos even after auto-init adds it to .gitignore)
-    let renamify_dir = PathBuf::from(".renamify");
+    let renamify_dir = root.join(".renamify");
     if !renamify_dir.exists() {
         std::fs::create_dir_all(&renamify_dir).context("Failed to create .renamify directory")?;
     }
 
+    // Check the commit can be made before changing any files
+    let pending_commit = commit
+        .as_ref()
+        .map(|options| prepare_commit(&plan, options, &root))
+        .transpose()?;
+
     // Apply the plan
     let apply_options = renamify_core::ApplyOptions {
-        create_backups: true,
-        backup_dir: PathBuf::from(".renamify/backups"),
+        backup_dir: renamify_dir.join("backups"),
         commit: false,
         force: force_with_conflicts,
         skip_symlinks: false,
         log_file: None,
+        commit_options: CommitOptions::default(),
+        stage: false,
+        working_dir: working_dir.map(Path::to_path_buf),
+        durability,
+        backup,
     };
     apply_plan(&mut plan.clone(), &apply_options)?;
 
     // Commit if requested
-    if commit {
-        commit_changes(&plan)?;
+    if let (Some((vcs, message)), Some(options)) = (pending_commit, commit.as_ref()) {
+        commit_changes(&plan, vcs.as_ref(), &message, options)?;
     }
 
     if !quiet {
@@ -165,56 +227,38 @@ pub fn handle_replace(
     Ok(())
 }
 
-fn commit_changes(plan: &Plan) -> Result<()> {
-    use std::process::Command;
-
-    // Check if we're in a git repository
-    let status = Command::new("git")
-        .args(["status", "--porcelain"])
-        .output()
-        .context("Failed to check git status")?;
-
-    if !status.status.success() {
-        return Err(anyhow!("Not in a git repository"));
-    }
+/// Find the repository and render the commit message
+fn prepare_commit(
+    plan: &Plan,
+    options: &CommitOptions,
+    root: &Path,
+) -> Result<(Box<dyn Vcs>, String)> {
+    let Some(vcs) = renamify_core::vcs::detect(root) else {
+        return Err(
+            RenamifyError::InvalidInput("Not in a git, jj or hg repository".to_string()).into(),
+        );
+    };
+    vcs.check_commit_options(options)?;
+    let message = options.message(plan, "Replace '{search}' with '{replace}'")?;
+    Ok((vcs, message))
+}
 
-    // Add all changed files
-    let files_to_add: Vec<String> = plan
+fn commit_changes(
+    plan: &Plan,
+    vcs: &dyn Vcs,
+    message: &str,
+    options: &CommitOptions,
+) -> Result<()> {
+    // Commit only the changed files
+    let files_to_commit: Vec<PathBuf> = plan
         .matches
         .iter()
-        .map(|m| m.file.to_string_lossy().to_string())
-        .chain(
-            plan.paths
-                .iter()
-                .map(|r| r.new_path.to_string_lossy().to_string()),
-        )
-        .collect::<std::collections::HashSet<_>>()
+        .map(|m| m.file.clone())
+        .chain(plan.paths.iter().map(|r| r.new_path.clone()))
+        .collect::<std::collections::BTreeSet<_>>()
         .into_iter()
         .collect();
 
-    if !files_to_add.is_empty() {
-        let status = Command::new("git")
-            .arg("add")
-            .args(&files_to_add)
-            .status()
-            .context("Failed to add files to git")?;
-
-        if !status.success() {
-            return Err(anyhow!("Failed to add files to git"));
-        }
-    }
-
-    // Create commit message
-    let message = format!("Replace '{}' with '{}'", plan.search, plan.replace);
-
-    let status = Command::new("git")
-        .args(["commit", "-m", &message])
-        .status()
-        .context("Failed to create git commit")?;
-
-    if !status.success() {
-        return Err(anyhow!("Failed to create git commit"));
-    }
-
-    Ok(())
+    vcs.commit_paths(&files_to_commit, message, options)
+        .context("Failed to create commit")
 }
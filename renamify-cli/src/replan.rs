@@ -0,0 +1,31 @@
+use anyhow::Result;
+use renamify_core::{replan_operation, OutputFormatter};
+use std::path::{Path, PathBuf};
+
+use crate::OutputFormat;
+
+pub fn handle_replan(
+    plan: Option<&str>,
+    paths: Vec<PathBuf>,
+    plan_out: &Path,
+    dry_run: bool,
+    output: OutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = replan_operation(plan, paths, Some(plan_out), dry_run, working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use renamify_core::{report_operation, OutputFormatter};
+
+use crate::OutputFormat;
+use std::path::Path;
+
+pub fn handle_report(output: OutputFormat, working_dir: Option<&Path>) -> Result<()> {
+    let result = report_operation(working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            print!("{}", result.format_summary());
+        },
+    }
+
+    Ok(())
+}
@@ -1,36 +1,123 @@
 use anyhow::Result;
-use renamify_core::{plan_operation, OutputFormatter, Style};
-use std::path::PathBuf;
+use renamify_core::acronym::AcronymRendering;
+use renamify_core::case_model::CaseMapping;
+use renamify_core::suppress::FileLines;
+use renamify_core::{
+    plan_operation, CoercionMode, LiteralMode, MatchAnchor, OperationObserver, OutputFormatter,
+    RenamifyError, StructuredFilter, Style, WalkOptions,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-use crate::{OutputFormat, StyleArg};
-use renamify_core::Preview;
+use crate::cli::{args::AtomicArgs, types::StyleArg, SearchOutputFormat};
+use renamify_core::{GroupBy, Preview};
+
+/// A ripgrep-style listing printed instead of the usual search output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchListing {
+    /// `--count`: matches per file as path:count
+    Count,
+    /// `--files-with-matches`: paths of files with a match
+    FilesWithMatches,
+    /// `--files-without-match`: paths of searched files without a match
+    FilesWithoutMatch,
+    /// `--json`: one JSON object per match
+    JsonEvents,
+}
+
+impl SearchListing {
+    /// The listing picked by the search flags, which clap keeps exclusive
+    pub fn from_flags(
+        count: bool,
+        files_with_matches: bool,
+        files_without_match: bool,
+        json: bool,
+    ) -> Option<Self> {
+        [
+            (count, Self::Count),
+            (files_with_matches, Self::FilesWithMatches),
+            (files_without_match, Self::FilesWithoutMatch),
+            (json, Self::JsonEvents),
+        ]
+        .into_iter()
+        .find_map(|(set, listing)| set.then_some(listing))
+    }
+}
+
+/// Collects the path of every file whose contents were searched
+#[derive(Default)]
+struct ScannedFiles(Mutex<Vec<PathBuf>>);
+
+impl OperationObserver for ScannedFiles {
+    fn on_file_scanned(&self, path: &Path, _scanned: usize, _total: usize) {
+        self.0.lock().unwrap().push(path.to_path_buf());
+    }
+}
 
 #[allow(clippy::too_many_arguments)]
 pub fn handle_search(
     term: &str,
+    replace: Option<&str>,
     paths: Vec<PathBuf>,
     include: Vec<String>,
     exclude: Vec<String>,
-    respect_gitignore: bool,
     unrestricted: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    paths_only: bool,
+    contents_only: bool,
     rename_files: bool,
     rename_dirs: bool,
     exclude_styles: Vec<StyleArg>,
     include_styles: Vec<StyleArg>,
     only_styles: Vec<StyleArg>,
+    exclude_match: Vec<String>,
     exclude_matching_lines: Option<String>,
+    exclude_lines_in_file: Vec<FileLines>,
+    literal_mode: LiteralMode,
+    match_anchor: MatchAnchor,
+    structured: Option<StructuredFilter>,
+    profile: Option<&str>,
+    show_skipped: bool,
+    fuzzy: u8,
     preview: Option<Preview>,
     fixed_table_width: bool,
+    group_by: GroupBy,
+    preview_sample: Option<usize>,
     use_color: bool,
     no_acronyms: bool,
     include_acronyms: Vec<String>,
     exclude_acronyms: Vec<String>,
     only_acronyms: Vec<String>,
-    output: OutputFormat,
+    acronym_rendering: Option<AcronymRendering>,
+    case_mapping: Option<CaseMapping>,
+    output: SearchOutputFormat,
+    listing: Option<SearchListing>,
     quiet: bool,
     ignore_ambiguous: bool,
     enable_plural_variants: bool,
+    atomic: &AtomicArgs,
+    working_dir: Option<&Path>,
 ) -> Result<()> {
+    // Validate that --fixed-table-width is only used with table preview
+    if fixed_table_width && preview.is_some() && preview != Some(Preview::Table) {
+        return Err(RenamifyError::InvalidInput(
+            "--fixed-table-width can only be used with --preview table".to_string(),
+        )
+        .into());
+    }
+
+    if group_by == GroupBy::Variant
+        && preview.is_some()
+        && !matches!(preview, Some(Preview::Table | Preview::Matches))
+    {
+        return Err(RenamifyError::InvalidInput(
+            "--group-by variant can only be used with --preview table or --preview matches"
+                .to_string(),
+        )
+        .into());
+    }
+
     // Expand any shorthand styles (e.g., space-separated) before conversion
     let exclude_styles: Vec<Style> = exclude_styles
         .into_iter()
@@ -48,71 +135,126 @@ pub fn handle_search(
         .map(Into::into)
         .collect();
 
-    // Handle quiet mode - overrides preview to none unless output is json
-    let effective_preview = if quiet && output != OutputFormat::Json {
-        None
-    } else {
-        preview
-    };
+    // Load config to get atomic identifiers
+    let config = working_dir
+        .map_or_else(renamify_core::Config::load, renamify_core::Config::load_in)
+        .unwrap_or_default();
+    let profile = profile.map(|name| config.profile(name)).transpose()?;
+    let atomic_config = atomic.to_config(config.atomic);
 
-    // For JSON output, don't generate preview
-    let preview_format = if output == OutputFormat::Json {
-        None
+    // Handle quiet mode - overrides preview to none
+    let effective_preview = if quiet { None } else { preview };
+
+    // Only the summary output renders a preview
+    let preview_format = if output == SearchOutputFormat::Summary && listing.is_none() {
+        effective_preview.map(|p| p.name().to_string())
     } else {
-        effective_preview.map(|p| match p {
-            Preview::Table => "table".to_string(),
-            Preview::Diff => "diff".to_string(),
-            Preview::Matches => "matches".to_string(),
-            Preview::Summary => "summary".to_string(),
-            Preview::None => "none".to_string(),
-        })
+        None
     };
 
-    // Call the core operation with search mode (empty replace string)
-    let (result, preview_content) = plan_operation(
+    // Only --files-without-match needs the files that were searched
+    let scanned_files = ScannedFiles::default();
+    let observer: Option<&dyn OperationObserver> =
+        (listing == Some(SearchListing::FilesWithoutMatch)).then_some(&scanned_files);
+
+    // Without --replace, the core operation runs in search mode with an empty replacement
+    let (mut result, preview_content) = plan_operation(
         term,
-        "", // Empty replacement for search
+        replace.unwrap_or_default(),
         paths,
+        None,
         include,
         exclude,
-        respect_gitignore,
+        true, // respect_gitignore (use default true for search)
         unrestricted,
         rename_files,
         rename_dirs,
         &exclude_styles,
         &include_styles,
         &only_styles,
-        vec![], // exclude_match not used for search
+        exclude_match,
         exclude_matching_lines,
+        exclude_lines_in_file,
         None, // No plan output for search
         preview_format.as_ref(),
         true, // Always dry-run for search
         fixed_table_width,
+        group_by,
+        preview_sample,
         use_color,
         no_acronyms,
         include_acronyms,
         exclude_acronyms,
         only_acronyms,
+        acronym_rendering,
+        case_mapping,
         enable_plural_variants,
         ignore_ambiguous,
-        None, // working_dir
-        None, // atomic_config
+        false,
+        literal_mode,
+        match_anchor,
+        CoercionMode::Auto,
+        structured,
+        profile.as_ref(),
+        show_skipped,
+        false, // swap
+        fuzzy,
+        include_submodules,
+        walk,
+        paths_only,
+        contents_only,
+        working_dir,
+        Some(&atomic_config),
+        None, // guards
+        observer,
     )?;
+    result.search_only = true;
+
+    let base_dir = crate::display_dir(working_dir);
+    let base_dir = base_dir.as_deref();
+    if let Some(listing) = listing {
+        let listed = match listing {
+            SearchListing::Count => result.format_count(base_dir),
+            SearchListing::FilesWithMatches => result.format_files_with_matches(base_dir),
+            SearchListing::FilesWithoutMatch => {
+                let scanned = scanned_files.0.into_inner().unwrap();
+                result.format_files_without_match(&scanned, base_dir)
+            },
+            SearchListing::JsonEvents => result.format_json_events(base_dir),
+        };
+        if !quiet {
+            print!("{listed}");
+        }
+        if listing != SearchListing::JsonEvents {
+            for warning in &result.warnings {
+                eprintln!("Warning: {warning}");
+            }
+        }
+        return Ok(());
+    }
 
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        SearchOutputFormat::Json => {
             print!("{}", result.format_json());
-        }
-        OutputFormat::Summary => {
+        },
+        SearchOutputFormat::Github => {
+            print!("{}", result.format_github(base_dir));
+        },
+        SearchOutputFormat::Summary => {
             if !quiet {
                 // Print preview content if available
                 if let Some(preview) = preview_content {
-                    println!("{}", preview);
+                    renamify_core::pager::page(&preview);
                 }
                 // Print summary
                 print!("{}", result.format_summary());
             }
+        },
+    }
+    if output != SearchOutputFormat::Json {
+        for warning in &result.warnings {
+            eprintln!("Warning: {warning}");
         }
     }
 
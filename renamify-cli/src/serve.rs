@@ -0,0 +1,18 @@
+use anyhow::Result;
+use renamify_core::{serve_operation, BackupStrategy, Durability};
+use std::io;
+use std::path::Path;
+
+pub fn handle_serve(
+    durability: Durability,
+    backup: BackupStrategy,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    serve_operation(
+        io::stdin().lock(),
+        io::stdout(),
+        durability,
+        backup,
+        working_dir,
+    )
+}
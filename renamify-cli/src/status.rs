@@ -1,17 +1,25 @@
 use anyhow::Result;
-use renamify_core::{status_operation, OutputFormatter};
+use renamify_core::{status_operation, OutputFormatter, PorcelainFormatter};
 
-use crate::OutputFormat;
+use crate::cli::PorcelainOutputFormat;
+use std::path::Path;
 
-pub fn handle_status(output: OutputFormat, quiet: bool) -> Result<()> {
-    let result = status_operation(None)?;
+pub fn handle_status(
+    output: PorcelainOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = status_operation(working_dir)?;
 
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        PorcelainOutputFormat::Json => {
             print!("{}", result.format_json());
         },
-        OutputFormat::Summary => {
+        PorcelainOutputFormat::Porcelain => {
+            print!("{}", result.format_porcelain());
+        },
+        PorcelainOutputFormat::Summary => {
             if !quiet {
                 print!("{}", result.format_summary());
             }
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use renamify_core::noisy_dirs::{add_to_rnignore, suggest_ignores, IgnoreSuggestion};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+/// List noisy directories that scans would read and add the chosen ones to
+/// `.rnignore`: all of them with `--yes`, or as picked at a prompt
+pub fn handle_suggest_ignores(root: &Path, yes: bool) -> Result<()> {
+    let suggestions = suggest_ignores(root);
+    if suggestions.is_empty() {
+        eprintln!("No noisy directories found");
+        return Ok(());
+    }
+
+    eprintln!("\nThese directories are usually dependencies or build output:");
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        eprintln!("  {}. {}", index + 1, describe(root, suggestion));
+    }
+
+    let chosen: Vec<String> = if yes {
+        suggestions.iter().map(|s| s.pattern.clone()).collect()
+    } else if io::stdin().is_terminal() {
+        let picked = prompt_for_choice(&mut io::stdin(), suggestions.len())?;
+        picked
+            .into_iter()
+            .map(|index| suggestions[index].pattern.clone())
+            .collect()
+    } else {
+        eprintln!("Run with --yes to add them all to .rnignore");
+        return Ok(());
+    };
+    if chosen.is_empty() {
+        return Ok(());
+    }
+
+    let added = add_to_rnignore(root, &chosen)?;
+    if !added.is_empty() {
+        eprintln!("Added to .rnignore: {}", added.join(" "));
+    }
+    Ok(())
+}
+
+fn describe(root: &Path, suggestion: &IgnoreSuggestion) -> String {
+    let first = suggestion.directories[0]
+        .strip_prefix(root)
+        .unwrap_or(&suggestion.directories[0])
+        .display();
+    let others = suggestion.directories.len() - 1;
+    let location = if others == 0 {
+        format!("{first}")
+    } else {
+        format!("{first} and {others} more")
+    };
+    format!(
+        "{} ({location}, {} files)",
+        suggestion.pattern, suggestion.files
+    )
+}
+
+/// Ask which suggestions to add, returning their indexes
+fn prompt_for_choice<R: io::Read>(reader: &mut R, count: usize) -> Result<Vec<usize>> {
+    let _guard = renamify_core::interrupt::ConfirmationPromptGuard::activate();
+    loop {
+        eprint!("Add to .rnignore? [A]ll, numbers (e.g. 1,3) or [n]one: ");
+        io::stderr().flush()?;
+
+        let mut input = String::new();
+        if io::BufReader::new(&mut *reader).read_line(&mut input)? == 0 {
+            return Ok(Vec::new());
+        }
+        match parse_choice(&input, count) {
+            Some(picked) => return Ok(picked),
+            None => eprintln!("Invalid choice. Enter A, n, or numbers from 1 to {count}."),
+        }
+    }
+}
+
+fn parse_choice(input: &str, count: usize) -> Option<Vec<usize>> {
+    match input.trim().to_lowercase().as_str() {
+        "" | "a" | "all" | "y" | "yes" => Some((0..count).collect()),
+        "n" | "no" | "none" => Some(Vec::new()),
+        numbers => numbers
+            .split([',', ' '])
+            .filter(|n| !n.is_empty())
+            .map(|n| {
+                n.parse::<usize>()
+                    .ok()
+                    .filter(|n| (1..=count).contains(n))
+                    .map(|n| n - 1)
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("\n", 3), Some(vec![0, 1, 2]));
+        assert_eq!(parse_choice("A\n", 3), Some(vec![0, 1, 2]));
+        assert_eq!(parse_choice("n\n", 3), Some(vec![]));
+        assert_eq!(parse_choice("1,3\n", 3), Some(vec![0, 2]));
+        assert_eq!(parse_choice("2 3", 3), Some(vec![1, 2]));
+        assert_eq!(parse_choice("4", 3), None);
+        assert_eq!(parse_choice("x", 3), None);
+    }
+}
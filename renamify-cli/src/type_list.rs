@@ -0,0 +1,13 @@
+use renamify_core::language::FILE_TYPES;
+use std::io::{self, Write};
+
+/// Print each file type `--type` accepts with its globs, as `rg --type-list`
+/// does
+pub fn handle_type_list() -> anyhow::Result<()> {
+    let mut stdout = io::stdout();
+    for file_type in FILE_TYPES {
+        let globs: Vec<String> = file_type.globs().collect();
+        writeln!(stdout, "{}: {}", file_type.name, globs.join(", "))?;
+    }
+    Ok(())
+}
@@ -1,17 +1,27 @@
 use anyhow::Result;
-use renamify_core::{undo_operation, OutputFormatter};
+use renamify_core::{undo_operation, OutputFormatter, PorcelainFormatter};
 
-use crate::OutputFormat;
+use crate::cli::PorcelainOutputFormat;
+use std::path::Path;
 
-pub fn handle_undo(id: &str, output: OutputFormat, quiet: bool) -> Result<()> {
-    let result = undo_operation(id, None)?;
+pub fn handle_undo(
+    id: &str,
+    force_undo: bool,
+    output: PorcelainOutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = undo_operation(id, force_undo, working_dir)?;
 
     // Handle output based on format
     match output {
-        OutputFormat::Json => {
+        PorcelainOutputFormat::Json => {
             print!("{}", result.format_json());
         },
-        OutputFormat::Summary => {
+        PorcelainOutputFormat::Porcelain => {
+            print!("{}", result.format_porcelain());
+        },
+        PorcelainOutputFormat::Summary => {
             if !quiet {
                 print!("{}", result.format_summary());
             }
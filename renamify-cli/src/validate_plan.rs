@@ -0,0 +1,33 @@
+use anyhow::Result;
+use renamify_core::{validate_plan_operation, OutputFormatter};
+use std::path::Path;
+use std::process;
+
+use crate::OutputFormat;
+
+pub fn handle_validate_plan(
+    plan: &Path,
+    output: OutputFormat,
+    quiet: bool,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let result = validate_plan_operation(plan, working_dir)?;
+
+    // Handle output based on format
+    match output {
+        OutputFormat::Json => {
+            print!("{}", result.format_json());
+        },
+        OutputFormat::Summary => {
+            if !quiet {
+                print!("{}", result.format_summary());
+            }
+        },
+    }
+
+    if !result.is_valid() {
+        process::exit(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use predicates::prelude::*;
+use serde_json::Value;
+
+#[test]
+fn test_check_command_passes_when_clean() {
+    let temp = TempDir::new().unwrap();
+    temp.child("src/lib.rs")
+        .write_str("fn new_name() {}")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp.path())
+        .args(["check", "--forbid", "old_name"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No forbidden identifiers found"));
+}
+
+#[test]
+fn test_check_command_fails_on_any_case_variant() {
+    let temp = TempDir::new().unwrap();
+    temp.child("src/lib.rs")
+        .write_str("let x = OldName::new();\nlet legacyApi = 1;\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp.path())
+        .args([
+            "check",
+            "--forbid",
+            "old_name",
+            "--forbid",
+            "legacy_api",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["success"], false);
+    let violations = json["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0]["term"], "old_name");
+    assert_eq!(violations[0]["content"], "OldName");
+    assert_eq!(violations[0]["file"], "src/lib.rs");
+    assert_eq!(violations[0]["line"], 1);
+    assert_eq!(violations[1]["term"], "legacy_api");
+    assert_eq!(violations[1]["content"], "legacyApi");
+}
+
+#[test]
+fn test_check_command_uses_config_forbid_list() {
+    let temp = TempDir::new().unwrap();
+    temp.child(".renamify/config.toml")
+        .write_str("[check]\nforbid = [\"old_name\"]\n")
+        .unwrap();
+    temp.child("old_name.txt")
+        .write_str("nothing here")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp.path())
+        .args(["check", "--output", "github"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "::error file=old_name.txt,title=renamify check::",
+        ));
+}
+
+#[test]
+fn test_check_command_requires_forbidden_identifiers() {
+    let temp = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("check")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("No forbidden identifiers given"));
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_hook_pre_commit_checks_staged_content_only() {
+    let temp = TempDir::new().unwrap();
+    git(temp.path(), &["init", "-q"]);
+    temp.child("src/lib.rs").write_str("fn ok() {}\n").unwrap();
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-q", "-m", "init"]);
+
+    // Unstaged files are never read
+    temp.child("notes.txt").write_str("old_name\n").unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp.path())
+        .args(["hook", "pre-commit", "--forbid", "old_name"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(0 files scanned)"));
+
+    // The staged version is checked, not the working tree
+    temp.child("src/lib.rs")
+        .write_str("fn ok() {}\nfn oldName() {}\n")
+        .unwrap();
+    temp.child("src/old_name/mod.rs").write_str("\n").unwrap();
+    git(temp.path(), &["add", "src"]);
+    temp.child("src/lib.rs").write_str("fn ok() {}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp.path())
+        .args([
+            "hook",
+            "pre-commit",
+            "--forbid",
+            "old_name",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["summary"]["files_scanned"], 2);
+    let violations: Vec<(&str, &str)> = json["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| (v["file"].as_str().unwrap(), v["content"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        violations,
+        vec![("src/lib.rs", "oldName"), ("src/old_name", "old_name")]
+    );
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp.path())
+        .args([
+            "hook",
+            "pre-commit",
+            "--forbid",
+            "old_name",
+            "--exclude",
+            "src/**",
+        ])
+        .assert()
+        .success();
+}
@@ -2,7 +2,7 @@ use assert_cmd::Command;
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use predicates::prelude::*;
-use renamify_core::{plan_operation, Style};
+use renamify_core::{plan_operation, CoercionMode, LiteralMode, MatchAnchor, Style};
 use std::path::PathBuf;
 
 /// Helper function to create a cross-platform path string for testing
@@ -46,488 +46,4607 @@ fn test_version_subcommand() {
 #[test]
 fn test_version_subcommand_json() {
     let mut cmd = Command::cargo_bin("renamify").unwrap();
-    cmd.args(["version", "--output", "json"])
+    let output = cmd
+        .args(["version", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["name"], "renamify");
+    assert!(regex::Regex::new(r"^\d+\.\d+\.\d+")
+        .unwrap()
+        .is_match(json["version"].as_str().unwrap()));
+    assert!(json["build"]["target"]
+        .as_str()
+        .is_some_and(|t| !t.is_empty()));
+    assert!(json["build"]["rustc_version"]
+        .as_str()
+        .is_some_and(|v| v.starts_with("rustc")));
+    assert!(json["build"]["features"].is_array());
+}
+
+#[test]
+fn test_plan_diff_command() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("fn old_name() {}\nstruct OldName;\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--plan-out", "a.json"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--only-styles",
+            "snake",
+            "--no-rename-files",
+            "--plan-out",
+            "b.json",
+        ])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan-diff", "a.json", "a.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Plans are identical"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["plan-diff", "a.json", "b.json", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["operation"], "plan-diff");
+    assert_eq!(json["identical"], false);
+    assert_eq!(json["summary"]["matches_removed"], 1);
+    assert_eq!(json["summary"]["matches_unchanged"], 1);
+    assert_eq!(json["summary"]["renames_removed"], 1);
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan-diff", "a.json", "missing.json"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("missing.json"));
+}
+
+#[test]
+fn test_validate_plan_command() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["validate-plan"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "is valid (1 matches, 1 renames checked)",
+        ));
+
+    // The file changed after planning, and the rename target now exists
+    temp_dir
+        .child("old_name.rs")
+        .write_str("fn renamed() {}\n")
+        .unwrap();
+    temp_dir.child("new_name.rs").write_str("").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["validate-plan", ".renamify/plan.json", "--output", "json"])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["operation"], "validate-plan");
+    assert_eq!(json["valid"], false);
+    assert_eq!(json["issues"][0]["kind"], "stale_match");
+    assert_eq!(json["issues"][0]["line"], 1);
+    assert_eq!(json["issues"][1]["kind"], "rename_conflict");
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["validate-plan", "missing.json"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("missing.json"));
+}
+
+#[test]
+fn test_plan_merge_command() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("a.rs")
+        .write_str("fn old_name() {}\nfn other_name() {}\n")
+        .unwrap();
+
+    for (search, out) in [("old_name", "one.json"), ("other_name", "two.json")] {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args([
+                "plan",
+                search,
+                "new_name",
+                "--include",
+                "a.rs",
+                "--plan-out",
+                out,
+            ])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan-merge", "one.json", "two.json", "one.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Merged 3 plans"))
+        .stdout(predicate::str::contains("2 matches in 1 files"))
+        .stdout(predicate::str::contains("Skipped 1 duplicate matches"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("apply")
+        .assert()
+        .success();
+    temp_dir
+        .child("a.rs")
+        .assert("fn new_name() {}\nfn new_name() {}\n");
+
+    // Overlapping edits with different replacements cannot be merged
+    temp_dir.child("b.rs").write_str("old_name\n").unwrap();
+    for (replace, out) in [("new_name", "three.json"), ("newer_name", "four.json")] {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_name",
+                replace,
+                "--include",
+                "b.rs",
+                "--plan-out",
+                out,
+            ])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan-merge", "three.json", "four.json", "-o", "merged.json"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("conflicting edit"));
+    assert!(!temp_dir.path().join("merged.json").exists());
+}
+
+#[test]
+fn test_replan_command() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("a.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+    temp_dir.child("b.md").write_str("old_name\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--include", "*.rs"])
+        .assert()
+        .success();
+
+    // The match moves down a line and a new one appears; b.md stays out
+    temp_dir
+        .child("a.rs")
+        .write_str("// header\nfn old_name() {}\nfn old_name_two() {}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["replan", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved: 1 matches"))
+        .stdout(predicate::str::contains("(was line 1, column 4)"))
+        .stdout(predicate::str::contains("+1 -0"))
+        .stdout(predicate::str::contains("Dry run: no plan written"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("replan")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Plan written to .renamify/plan.json",
+        ));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("apply")
+        .assert()
+        .success();
+    temp_dir
+        .child("a.rs")
+        .assert("// header\nfn new_name() {}\nfn new_name_two() {}\n");
+    temp_dir.child("b.md").assert("old_name\n");
+}
+
+#[test]
+fn test_porcelain_output() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("a.rs").write_str("old_name\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--output", "porcelain"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("plan_id\t"));
+    assert!(lines.contains(&"total_matches\t1"));
+    // Nothing but records, so no preview
+    assert!(lines.iter().all(|line| line.contains('\t')));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["apply", "--output", "porcelain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("replacements\t1\n"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["history", "--output", "porcelain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("entry\t"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["undo", "missing", "--output", "porcelain"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::starts_with("error\tnot_found\t2\t"));
+}
+
+#[test]
+fn test_rename_dry_run_json_includes_checks_and_history_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("// old_name\n")
+        .unwrap();
+    temp_dir
+        .child("new_name.rs")
+        .write_str("// taken\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "rename",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["dry_run"], true);
+    let checks = &json["safety_checks"];
+    assert_eq!(checks["large_change"]["exceeded"], false);
+    assert_eq!(checks["large_change"]["max_files"], 500);
+    assert_eq!(checks["collisions"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        checks["collisions"][0]["reason"],
+        "destination already exists"
+    );
+    assert_eq!(checks["passed"], false);
+
+    let entry = &json["history_entry"];
+    assert_eq!(entry["id"], json["plan_id"]);
+    assert_eq!(entry["affected_files"].as_object().unwrap().len(), 2);
+    assert_eq!(entry["renames"].as_array().unwrap().len(), 1);
+
+    // Nothing was applied or recorded
+    temp_dir
+        .child("old_name.rs")
+        .assert(predicate::path::exists());
+    temp_dir
+        .child("lib.rs")
+        .assert(predicate::str::contains("old_name"));
+    temp_dir
+        .child(".renamify/history.json")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn test_rename_warnings_only_in_human_output() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name.bin")
+        .write_binary(b"\x00\x01\x02")
+        .unwrap();
+
+    let rename = |output: &str| {
+        Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args([
+                "rename",
+                "old_name",
+                "new_name",
+                "--dry-run",
+                "--output",
+                output,
+            ])
+            .assert()
+            .success()
+    };
+
+    // The warning is in the JSON, and stderr stays clean
+    let assert = rename("json").stderr(predicate::str::contains("Warning:").not());
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert!(json["plan"]["warnings"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("old_name.bin is binary"));
+
+    rename("summary").stderr(predicate::str::contains(
+        "Warning: old_name.bin is binary, so it is renamed but its contents are not searched",
+    ));
+}
+
+#[test]
+fn test_rename_expect_counts() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("a.rs").write_str("old_name\n").unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("old_name\n")
+        .unwrap();
+
+    // Out of tolerance: nothing is applied and the exit code is distinct
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "rename",
+            "old_name",
+            "new_name",
+            "--expect-matches",
+            "5:2",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .code(7)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["error"]["kind"], "unexpected_count");
+    assert_eq!(json["error"]["guard"]["name"], "expect_matches");
+    assert_eq!(
+        json["error"]["guard"]["thresholds"],
+        serde_json::json!([{ "name": "matches", "found": 2, "min": 3, "max": 7 }])
+    );
+    temp_dir
+        .child("a.rs")
+        .assert(predicate::str::contains("old_name"));
+
+    // Within tolerance: applied without a prompt, even without a terminal
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "rename",
+            "old_name",
+            "new_name",
+            "--expect-matches",
+            "3:1",
+            "--expect-renames",
+            "1",
+        ])
+        .assert()
+        .success();
+    temp_dir
+        .child("a.rs")
+        .assert(predicate::str::contains("new_name"));
+    temp_dir
+        .child("new_name.rs")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn test_large_change_thresholds() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("a.rs").write_str("old_name\n").unwrap();
+    temp_dir.child("b.rs").write_str("old_name\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "-y",
+            "rename",
+            "old_name",
+            "new_name",
+            "--large-files-threshold",
+            "1",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .code(4)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["error"]["guard"]["name"], "large_change");
+    assert_eq!(
+        json["error"]["guard"]["thresholds"],
+        serde_json::json!([{ "name": "files", "found": 2, "max": 1 }])
+    );
+    temp_dir
+        .child("a.rs")
+        .assert(predicate::str::contains("old_name"));
+
+    // Thresholds can also come from the config file
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("[defaults]\nlarge_files_threshold = 1\n")
+        .unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["replace", "old_name", "new_name", "--output", "json"])
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains("\"name\":\"large_change\""));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "-y",
+            "rename",
+            "old_name",
+            "new_name",
+            "--large-files-threshold",
+            "2",
+        ])
+        .assert()
+        .success();
+    temp_dir.child("a.rs").assert("new_name\n");
+}
+
+#[test]
+fn test_config_show_origin() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.child("xdg");
+    config_home
+        .child("renamify/config.toml")
+        .write_str("atomic = [\"GitHub\"]\n[defaults]\npreview_format = \"table\"\n")
+        .unwrap();
+    let repo = temp_dir.child("repo");
+    repo.child(".renamify/config.toml")
+        .write_str("[defaults]\npreview_format = \"summary\"\nunrestricted_level = 1\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(repo.path())
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("RENAMIFY_UNRESTRICTED_LEVEL", "2")
+        .args(["config", "show", "--origin", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["config"]["defaults"]["preview_format"], "summary");
+    assert_eq!(json["config"]["defaults"]["unrestricted_level"], 2);
+    assert_eq!(json["config"]["atomic"], serde_json::json!(["GitHub"]));
+    assert_eq!(json["origins"]["defaults.preview_format"]["origin"], "repo");
+    assert_eq!(json["origins"]["atomic"]["origin"], "user");
+    assert_eq!(
+        json["origins"]["defaults.unrestricted_level"]["source"],
+        "RENAMIFY_UNRESTRICTED_LEVEL"
+    );
+    assert_eq!(
+        json["origins"]["defaults.rename_files"]["origin"],
+        "default"
+    );
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(repo.path())
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .args(["config", "show", "--origin"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "defaults.preview_format = \"summary\"",
+        ))
+        .stdout(predicate::str::contains("# user ("));
+
+    // Without --origin the effective config is printed as TOML
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(repo.path())
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[defaults]"))
+        .stdout(predicate::str::contains("# ").not());
+}
+
+#[test]
+fn test_config_get_and_set() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.child("xdg");
+    let repo = temp_dir.child("repo");
+    repo.create_dir_all().unwrap();
+    repo.child(".renamify/config.toml")
+        .write_str("# Team defaults\n[defaults]\npreview_format = \"summary\" # keep\n")
+        .unwrap();
+
+    let renamify = || {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(repo.path())
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env_remove("RENAMIFY_PREVIEW_FORMAT");
+        cmd
+    };
+
+    renamify()
+        .args(["config", "set", "defaults.preview_format", "table"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Set defaults.preview_format = \"table\"",
+        ));
+    let repo_config = std::fs::read_to_string(repo.child(".renamify/config.toml").path()).unwrap();
+    assert!(repo_config.contains("# Team defaults"));
+    assert!(repo_config.contains("preview_format = \"table\" # keep"));
+
+    renamify()
+        .args([
+            "config",
+            "set",
+            "--global",
+            "defaults.unrestricted_level",
+            "2",
+        ])
+        .assert()
+        .success();
+    config_home
+        .child("renamify/config.toml")
+        .assert(predicate::str::contains("unrestricted_level = 2"));
+
+    renamify()
+        .args(["config", "get", "defaults.preview_format"])
+        .assert()
+        .success()
+        .stdout("table\n");
+
+    let output = renamify()
+        .args([
+            "config",
+            "get",
+            "defaults.unrestricted_level",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["value"], 2);
+    assert_eq!(json["origin"]["origin"], "user");
+
+    // Scoped reads only look at that file
+    renamify()
+        .args(["config", "get", "--repo", "defaults.unrestricted_level"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not set"));
+
+    renamify()
+        .args(["config", "set", "defaults.no_such_key", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown config key"));
+
+    renamify()
+        .args(["config", "set", "defaults.unrestricted_level", "lots"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid value"));
+
+    renamify()
+        .args(["config", "get", "--global", "--repo", "atomic"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_plan_json_reports_scan_stats() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("main.rs").write_str("old_name\n").unwrap();
+    temp_dir
+        .child("logo.png")
+        .write_binary(&[0, 1, 2, 3, 0, 0])
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["summary"]["files_scanned"], 2);
+    assert_eq!(json["summary"]["bytes_scanned"], 15);
+    assert_eq!(json["summary"]["files_skipped"]["binary"], 1);
+    for phase in ["walk_ms", "read_ms", "match_ms", "rename_plan_ms"] {
+        assert!(json["summary"]["timings"][phase].is_number());
+    }
+    assert_eq!(json["plan"]["stats"]["bytes_scanned"], 15);
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--preview",
+            "summary",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Scanned: 2 files, 15 B (skipped: 1 binary)",
+        ))
+        .stdout(predicate::str::contains("Timing: walk "));
+}
+
+#[test]
+fn test_skip_strings() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("src/app.js")
+        .write_str(
+            "// Render the widget\nfunction renderWidget(widget) {\n  alert(\"Your widget is ready\");\n  return `${widget.name}: widget`;\n}\n",
+        )
+        .unwrap();
+    temp_dir
+        .child("README.md")
+        .write_str("The \"widget\" docs\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "widget",
+            "gadget",
+            "--skip-strings",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let mut lines: Vec<(String, u64)> = json["plan"]["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hunk| {
+            (
+                PathBuf::from(hunk["file"].as_str().unwrap())
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+                hunk["line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    lines.sort();
+    lines.dedup();
+
+    // Identifiers, interpolated code and comments are kept; string text is
+    // skipped. Markdown has no string syntax, so its match is kept.
+    assert_eq!(
+        lines,
+        vec![
+            ("README.md".to_string(), 1),
+            ("app.js".to_string(), 1),
+            ("app.js".to_string(), 2),
+            ("app.js".to_string(), 4),
+        ]
+    );
+    let line_four: Vec<&str> = json["plan"]["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|hunk| hunk["line"] == 4)
+        .map(|hunk| hunk["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(line_four, vec!["widget"]);
+}
+
+#[test]
+fn test_only_strings() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("src/widget.js")
+        .write_str(
+            "// Render the widget\nfunction renderWidget(widget) {\n  alert(\"Your widget is ready\");\n  return `${widget.name}: widget`;\n}\n",
+        )
+        .unwrap();
+    temp_dir
+        .child("README.md")
+        .write_str("The widget docs\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "widget",
+            "gadget",
+            "--only-strings",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let mut hunks: Vec<(String, u64, String)> = json["plan"]["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hunk| {
+            (
+                PathBuf::from(hunk["file"].as_str().unwrap())
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+                hunk["line"].as_u64().unwrap(),
+                hunk["content"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    hunks.sort();
+
+    // Comments, string text and docs are kept; identifiers and interpolated
+    // code are skipped, and the file itself is not renamed
+    assert_eq!(
+        hunks,
+        vec![
+            ("README.md".to_string(), 1, "widget".to_string()),
+            ("widget.js".to_string(), 1, "widget".to_string()),
+            ("widget.js".to_string(), 3, "widget".to_string()),
+            ("widget.js".to_string(), 4, "widget".to_string()),
+        ]
+    );
+    assert!(json["plan"]["paths"].as_array().unwrap().is_empty());
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "widget",
+            "gadget",
+            "--only-strings",
+            "--skip-strings",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_group_by_variant_preview() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("a.rs")
+        .write_str("let old_name = OldName::new();\nconst OLD_NAME: u8 = 1;\n")
+        .unwrap();
+    temp_dir
+        .child("b.rs")
+        .write_str("struct OldName;\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--preview",
+            "matches",
+            "--group-by",
+            "variant",
+            "--dry-run",
+            "--no-color",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+
+    // Most frequent variant first, each with its own section
+    let pascal = stdout
+        .find("OldName (pascal): 2 matches in 2 files")
+        .unwrap();
+    let screaming = stdout
+        .find("OLD_NAME (screaming-snake): 1 matches in 1 files")
+        .unwrap();
+    assert!(pascal < screaming);
+    assert!(stdout.contains("renamify plan old_name new_name --exclude-match old_name"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "search",
+            "old_name",
+            "--preview",
+            "table",
+            "--group-by",
+            "variant",
+            "--no-color",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Variant"))
+        .stdout(predicate::str::contains("screaming-snake"))
+        .stdout(predicate::str::contains(
+            "renamify plan old_name <NEW> --exclude-match",
+        ));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--preview",
+            "diff",
+            "--group-by",
+            "variant",
+            "--dry-run",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--group-by variant can only be used with --preview table",
+        ));
+}
+
+#[test]
+fn test_coerce_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("config.rs")
+        .write_str("use old_name_core::Engine;\nlet old_name = 1;\n")
+        .unwrap();
+
+    let plan_json = |args: &[&str]| -> serde_json::Value {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_name",
+                "new_name",
+                "--dry-run",
+                "--output",
+                "json",
+            ])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&output).unwrap()
+    };
+    let replacements = |json: &serde_json::Value| -> Vec<String> {
+        json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| hunk["replace"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let json = plan_json(&[]);
+    assert_eq!(json["plan"]["coercion"], "Auto");
+    assert_eq!(replacements(&json), vec!["new_name_core", "new_name"]);
+
+    let json = plan_json(&["--coerce", "force:kebab"]);
+    assert_eq!(json["plan"]["coercion"]["Force"], "Kebab");
+    assert_eq!(replacements(&json), vec!["new-name-core", "new-name"]);
+
+    // Replace leaves its replacement alone unless asked
+    temp_dir
+        .child("Makefile")
+        .write_str("build-oldtool:\n")
+        .unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "replace",
+            "oldtool",
+            "new_tool",
+            "--no-regex",
+            "--dry-run",
+            "--preview",
+            "diff",
+        ])
+        .args(["--include", "Makefile"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+build-new_tool:"));
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "replace",
+            "oldtool",
+            "new_tool",
+            "--no-regex",
+            "--dry-run",
+            "--preview",
+            "diff",
+        ])
+        .args(["--include", "Makefile", "--coerce", "auto"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+build-new-tool:"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--coerce", "force:wavy"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown style 'wavy'"));
+}
+
+#[test]
+fn test_structured_keys_values_and_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("deploy.yaml")
+        .write_str(concat!(
+            "# old_app deployment\n",
+            "metadata:\n",
+            "  name: old_app\n",
+            "  labels:\n",
+            "    old_app: \"true\"\n",
+            "spec:\n",
+            "  template:\n",
+            "    metadata:\n",
+            "      name: old_app\n",
+        ))
+        .unwrap();
+    temp_dir
+        .child("Cargo.toml")
+        .write_str("[dependencies]\nold_app = \"1\"\n")
+        .unwrap();
+    temp_dir.child("main.rs").write_str("old_app();\n").unwrap();
+
+    let matched_lines = |args: &[&str]| -> Vec<(String, u64)> {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_app",
+                "new_app",
+                "--dry-run",
+                "--output",
+                "json",
+            ])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let mut lines: Vec<(String, u64)> = json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| {
+                (
+                    PathBuf::from(hunk["file"].as_str().unwrap())
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                    hunk["line"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        lines.sort();
+        lines
+    };
+    let at = |file: &str, line: u64| (file.to_string(), line);
+
+    // Comments are neither keys nor values; other files keep their matches
+    assert_eq!(
+        matched_lines(&["--only-values"]),
+        vec![at("deploy.yaml", 3), at("deploy.yaml", 9), at("main.rs", 1)]
+    );
+    assert_eq!(
+        matched_lines(&["--only-keys"]),
+        vec![at("Cargo.toml", 2), at("deploy.yaml", 5), at("main.rs", 1)]
+    );
+    assert_eq!(
+        matched_lines(&["--yaml-path", "spec.template.metadata.name,dependencies"]),
+        vec![at("Cargo.toml", 2), at("deploy.yaml", 9), at("main.rs", 1)]
+    );
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_app", "new_app", "--only-keys", "--only-values"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_k8s_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("deploy.yaml")
+        .write_str(concat!(
+            "metadata:\n",
+            "  labels:\n",
+            "    app: old-app\n",
+            "    helm.sh/chart: old-app-1.2.0\n",
+            "spec:\n",
+            "  containers:\n",
+            "    - name: old_app\n",
+            "      env: [{name: OLD_APP_MODE, value: oldApp}]\n",
+        ))
+        .unwrap();
+    temp_dir
+        .child("service.yaml")
+        .write_str("spec:\n  selector:\n    app: old-app\n")
+        .unwrap();
+
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old-app",
+            "new-app",
+            "--profile",
+            "k8s",
+            "--exclude",
+            "service.yaml",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let lines: Vec<u64> = json["plan"]["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hunk| hunk["line"].as_u64().unwrap())
+        .collect();
+    assert_eq!(lines, vec![3, 7]);
+    assert_eq!(
+        json["warnings"],
+        serde_json::json!([{
+            "kind": "label",
+            "severity": "warning",
+            "message": "Label 'app: old-app' is renamed in deploy.yaml:3 but not in service.yaml:3"
+        }])
+    );
+
+    // A profile from the config replaces the built-in one
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("[profiles.k8s]\nonly_styles = [\"snake\"]\n")
+        .unwrap();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old-app",
+            "new-app",
+            "--profile",
+            "k8s",
+            "--dry-run",
+        ])
+        .args(["--preview", "summary"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Edits: 1 files, 1 replacements"))
+        .stderr(predicate::str::contains("Warning").not());
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old-app",
+            "new-app",
+            "--profile",
+            "helm",
+            "--dry-run",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown profile 'helm'. Available profiles: k8s, sql",
+        ));
+}
+
+#[test]
+fn test_sql_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("001_orders.sql")
+        .write_str(concat!(
+            "CREATE TABLE \"OrderItem\" (id int);\n",
+            "SELECT * FROM Order_Item ORDER BY id; -- order report\n",
+            "INSERT INTO ORDER_ITEM VALUES (1);\n",
+        ))
+        .unwrap();
+    temp_dir
+        .child("order_item.rb")
+        .write_str("ORDER_ITEM_TABLE = \"order_item\"\n")
+        .unwrap();
+
+    let replacements = |search: &str, replace: &str| -> Vec<(u64, String, String)> {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["plan", search, replace, "--profile", "sql"])
+            .args(["--include", "*.sql", "--dry-run", "--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| {
+                (
+                    hunk["line"].as_u64().unwrap(),
+                    hunk["content"].as_str().unwrap().to_string(),
+                    hunk["replace"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+    let hunk =
+        |line: u64, content: &str, replace: &str| (line, content.to_string(), replace.to_string());
+
+    let mut found = replacements("order_item", "line_item");
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            hunk(1, "OrderItem", "LineItem"),
+            hunk(2, "Order_Item", "Line_Item"),
+            hunk(3, "ORDER_ITEM", "LINE_ITEM"),
+        ]
+    );
+
+    // The ORDER keyword is left alone, but not the word in a comment
+    let found = replacements("order", "purchase");
+    assert!(found.contains(&hunk(2, "order", "purchase")));
+    assert!(found.iter().all(|(_, content, _)| content != "ORDER"));
+}
+
+#[test]
+fn test_show_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("let old_name = 1; // TODO old_name\nstruct OldName;\nfn old_name() {}\n")
+        .unwrap();
+    temp_dir
+        .child("logo.bin")
+        .write_binary(b"old_name\x00\x01\x02")
+        .unwrap();
+
+    let plan = |extra: &[&str]| -> serde_json::Value {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_name",
+                "new_name",
+                "--dry-run",
+                "--output",
+                "json",
+            ])
+            .args([
+                "--exclude-match",
+                "OldName",
+                "--exclude-matching-lines",
+                "TODO",
+            ])
+            .args(extra)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&output).unwrap()
+    };
+
+    assert!(plan(&[])["plan"].get("skipped").is_none());
+
+    let json = plan(&["--show-skipped"]);
+    assert_eq!(json["plan"]["matches"].as_array().unwrap().len(), 1);
+    let skipped: Vec<(String, Option<u64>, String, String)> = json["plan"]["skipped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                PathBuf::from(entry["file"].as_str().unwrap())
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+                entry["line"].as_u64(),
+                entry["content"].as_str().unwrap().to_string(),
+                entry["reason"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    let entry = |file: &str, line: Option<u64>, content: &str, reason: &str| {
+        (
+            file.to_string(),
+            line,
+            content.to_string(),
+            reason.to_string(),
+        )
+    };
+    assert_eq!(
+        skipped,
+        vec![
+            entry("lib.rs", Some(1), "old_name", "exclude_matching_lines"),
+            entry("lib.rs", Some(1), "old_name", "exclude_matching_lines"),
+            entry("lib.rs", Some(2), "OldName", "exclude_match"),
+            entry("logo.bin", None, "old_name", "binary"),
+        ]
+    );
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "search",
+            "old_name",
+            "--exclude-matching-lines",
+            "TODO",
+            "--show-skipped",
+        ])
+        .args(["--preview", "matches"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped Matches (3):"))
+        .stdout(predicate::str::contains(
+            "lib.rs:1 old_name (line matches --exclude-matching-lines)",
+        ))
+        .stdout(predicate::str::contains("logo.bin old_name (binary file)"));
+}
+
+#[test]
+fn test_plan_swap() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = "use old_name::OldName;\nuse new_name::NewName;\n";
+    temp_dir.child("lib.rs").write_str(source).unwrap();
+    temp_dir
+        .child("old_name/mod.rs")
+        .write_str("pub struct OldName;\n")
+        .unwrap();
+    temp_dir
+        .child("new_name/mod.rs")
+        .write_str("pub struct NewName;\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--swap",
+            "--preview",
+            "none",
+        ])
+        .assert()
+        .success();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("apply")
+        .assert()
+        .success();
+
+    temp_dir
+        .child("lib.rs")
+        .assert("use new_name::NewName;\nuse old_name::OldName;\n");
+    temp_dir
+        .child("new_name/mod.rs")
+        .assert("pub struct NewName;\n");
+    temp_dir
+        .child("old_name/mod.rs")
+        .assert("pub struct OldName;\n");
+    let history = std::fs::read_to_string(temp_dir.path().join(".renamify/history.json")).unwrap();
+    assert!(!history.contains(".renamify.tmp"));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["undo", "latest"])
+        .assert()
+        .success();
+    temp_dir.child("lib.rs").assert(source);
+    temp_dir
+        .child("old_name/mod.rs")
+        .assert("pub struct OldName;\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "old_name", "--swap", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--swap needs two different terms"));
+}
+
+#[test]
+fn test_plan_fuzzy_near_misses() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("fn old_name() {}\nfn get_old_nmae() {}\n")
+        .unwrap();
+
+    // Without a terminal or --yes, near misses stay out of the plan
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--fuzzy",
+            "1",
+            "--preview",
+            "matches",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Near Misses (1, not in the plan until confirmed):",
+        ))
+        .stdout(predicate::str::contains(
+            "lib.rs:2 get_old_nmae -> get_new_name",
+        ))
+        .stderr(predicate::str::contains(
+            "Left 1 near misses out of the plan",
+        ));
+    let plan: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(temp_dir.path().join(".renamify/plan.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(plan["matches"].as_array().unwrap().len(), 1);
+    assert_eq!(plan["near_misses"][0]["content"], "get_old_nmae");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--fuzzy", "1", "--yes"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Added 1 near misses to the plan"));
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("apply")
+        .assert()
+        .success();
+    temp_dir
+        .child("lib.rs")
+        .assert("fn new_name() {}\nfn get_new_name() {}\n");
+}
+
+#[test]
+fn test_plan_holds_back_renames_into_protected_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_build/mod.rs")
+        .write_str("// old_build\n")
+        .unwrap();
+    temp_dir
+        .child("src/old_build.rs")
+        .write_str("mod old_build;\n")
+        .unwrap();
+
+    // Without a terminal or --yes, old_build/ -> build/ stays out of the plan
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_build", "build", "--preview", "summary"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "old_build -> build (matches the protected pattern `build`)",
+        ))
+        .stderr(predicate::str::contains(
+            "Left 1 renames into ignored or protected paths out of the plan",
+        ));
+    let plan: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(temp_dir.path().join(".renamify/plan.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(plan["paths"].as_array().unwrap().len(), 1);
+    assert_eq!(plan["suspicious_renames"][0]["rename"]["kind"], "dir");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_build", "build", "--yes"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Added 1 renames into ignored or protected paths to the plan",
+        ));
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("apply")
+        .assert()
+        .success();
+    temp_dir.child("build/mod.rs").assert("// build\n");
+    temp_dir.child("src/build.rs").assert("mod build;\n");
+}
+
+#[test]
+fn test_plan_includes_tracked_files_matched_by_gitignore() {
+    let temp_dir = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    temp_dir.child(".gitignore").write_str("dist/\n").unwrap();
+    temp_dir
+        .child("dist/old_name.js")
+        .write_str("old_name();\n")
+        .unwrap();
+    temp_dir
+        .child("dist/untracked.js")
+        .write_str("old_name();\n")
+        .unwrap();
+    git(&["add", "-f", "dist/old_name.js"]);
+
+    let plan = |extra: &[&str]| -> serde_json::Value {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_name",
+                "new_name",
+                "--dry-run",
+                "--output",
+                "json",
+            ])
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let tracked = plan(&[]);
+    let matches = tracked["plan"]["matches"].as_array().unwrap();
+    assert_eq!(matches.len(), 1);
+    assert!(matches[0]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("dist/old_name.js"));
+    assert_eq!(tracked["plan"]["paths"].as_array().unwrap().len(), 1);
+
+    let skipped = plan(&["--no-tracked-ignored"]);
+    assert_eq!(skipped["plan"]["matches"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_history_mapping() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("use old_name::OldName;\n")
+        .unwrap();
+    temp_dir
+        .child("old_name/mod.rs")
+        .write_str("pub struct OldName;\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["rename", "old_name", "new_name", "--yes"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["history", "mapping", "latest", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(
+            "kind,old,new,occurrences\n\
+             identifier,OldName,NewName,2\n\
+             identifier,old_name,new_name,1\n\
+             path,old_name,new_name,\n",
+        );
+
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["history", "mapping", "latest"])
+        .output()
+        .unwrap();
+    let mapping: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(mapping["search"], "old_name");
+    assert_eq!(mapping["paths"][0]["new"], "new_name");
+}
+
+#[test]
+fn test_history_export_bundle() {
+    let exporter = TempDir::new().unwrap();
+    let teammate = TempDir::new().unwrap();
+    let bundles = TempDir::new().unwrap();
+    let bundle = bundles.path().join("rename.renamify-bundle");
+    for dir in [&exporter, &teammate] {
+        dir.child("lib.rs")
+            .write_str("use old_name::OldName;\n")
+            .unwrap();
+        dir.child("old_name/mod.rs")
+            .write_str("pub struct OldName;\n")
+            .unwrap();
+    }
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(exporter.path())
+        .args(["plan", "old_name", "new_name", "--preview", "none"])
+        .assert()
+        .success();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(exporter.path())
+        .arg("apply")
+        .assert()
+        .success();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(exporter.path())
+        .args(["history", "export", "latest", "--bundle"])
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 replacements across 2 files, 1 renames",
+        ));
+
+    // The teammate has edited lib.rs since, so the bundle has drifted
+    teammate
+        .child("lib.rs")
+        .write_str("// notes\nuse old_name::OldName;\n")
+        .unwrap();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(teammate.path())
+        .args(["apply", "--bundle"])
+        .arg(&bundle)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Files have changed since the bundle was exported: lib.rs",
+        ));
+    teammate
+        .child("old_name/mod.rs")
+        .assert("pub struct OldName;\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(teammate.path())
+        .args(["apply", "--force-with-conflicts", "--bundle"])
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved edits in lib.rs"));
+    teammate
+        .child("lib.rs")
+        .assert("// notes\nuse new_name::NewName;\n");
+    teammate
+        .child("new_name/mod.rs")
+        .assert("pub struct NewName;\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(teammate.path())
+        .args(["undo", "latest"])
+        .assert()
+        .success();
+    teammate
+        .child("lib.rs")
+        .assert("// notes\nuse old_name::OldName;\n");
+}
+
+#[test]
+fn test_history_export_bundle_without_backup_copies() {
+    for backup in ["git-stash", "none"] {
+        let exporter = TempDir::new().unwrap();
+        let teammate = TempDir::new().unwrap();
+        let bundle = teammate.path().join("rename.renamify-bundle");
+        for dir in [&exporter, &teammate] {
+            dir.child("old_name/mod.rs")
+                .write_str("pub struct OldName;\n")
+                .unwrap();
+        }
+        std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(exporter.path())
+            .status()
+            .unwrap();
+
+        Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(exporter.path())
+            .args(["rename", "old_name", "new_name", "-y", "--backup", backup])
+            .assert()
+            .success();
+        let export = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(exporter.path())
+            .args(["history", "export", "latest", "--bundle"])
+            .arg(&bundle)
+            .assert();
+
+        if backup == "none" {
+            export
+                .failure()
+                .stderr(predicate::str::contains("applied with --backup none"));
+            continue;
+        }
+
+        // The original content comes from the git blobs
+        export.success();
+        Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(teammate.path())
+            .args(["apply", "--bundle"])
+            .arg(&bundle)
+            .assert()
+            .success();
+        teammate
+            .child("new_name/mod.rs")
+            .assert("pub struct NewName;\n");
+    }
+}
+
+#[test]
+fn test_submodules_and_worktrees() {
+    let temp_dir = TempDir::new().unwrap();
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let init = |dir: &std::path::Path| {
+        git(dir, &["init", "-q"]);
+        git(dir, &["config", "user.name", "Test User"]);
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "commit.gpgsign", "false"]);
+    };
+    init(temp_dir.path());
+    temp_dir.child("lib.rs").write_str("old_name();\n").unwrap();
+    temp_dir
+        .child("vendor/dep/dep.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+    let dep = temp_dir.path().join("vendor/dep");
+    init(&dep);
+    git(&dep, &["add", "-A"]);
+    git(&dep, &["commit", "-qm", "Initial commit"]);
+    // A linked worktree checked out inside the tree
+    temp_dir
+        .child("feature/.git")
+        .write_str("gitdir: /work/repo/.git/worktrees/feature\n")
+        .unwrap();
+    temp_dir
+        .child("feature/lib.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "rename",
+            "old_name",
+            "new_name",
+            "--yes",
+            "--preview",
+            "none",
+        ])
+        .assert()
+        .success();
+    temp_dir.child("lib.rs").assert("new_name();\n");
+    temp_dir.child("vendor/dep/dep.rs").assert("old_name();\n");
+    temp_dir.child("feature/lib.rs").assert("old_name();\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["rename", "new_name", "newer_name", "--include-submodules"])
+        .args(["--commit", "--yes", "--preview", "none"])
+        .assert()
+        .success();
+    temp_dir.child("lib.rs").assert("newer_name();\n");
+    temp_dir.child("feature/lib.rs").assert("old_name();\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["rename", "old_name", "new_name", "--include-submodules"])
+        .args(["--commit", "--yes", "--preview", "none"])
+        .assert()
+        .success();
+    temp_dir.child("vendor/dep/dep.rs").assert("new_name();\n");
+    temp_dir.child("feature/lib.rs").assert("old_name();\n");
+    assert!(git(&dep, &["log", "--format=%s"]).contains("renamify: rename old_name -> new_name"));
+    assert!(git(temp_dir.path(), &["status", "--porcelain"]).is_empty());
+}
+
+#[test]
+fn test_commit_template_signoff_and_no_verify() {
+    let temp_dir = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.name", "Test User"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "commit.gpgsign", "false"]);
+    temp_dir
+        .child("src/old_name.rs")
+        .write_str("old_name();\nold_name();\n")
+        .unwrap();
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("[commit]\ntemplate = \"refactor: {search} -> {replace} ({matches} in {files} files, {{{renames}}} renames)\"\n")
+        .unwrap();
+    temp_dir
+        .child(".gitignore")
+        .write_str(".renamify/\n")
+        .unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "Initial commit"]);
+    temp_dir
+        .child(".git/hooks/pre-commit")
+        .write_str("#!/bin/sh\nexit 1\n")
+        .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let hook = temp_dir.path().join(".git/hooks/pre-commit");
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["rename", "old_name", "new_name", "--signoff"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--commit"));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["rename", "old_name", "new_name", "--commit", "--signoff"])
+        .args(["--no-verify", "--yes", "--preview", "none"])
+        .assert()
+        .success();
+    temp_dir
+        .child("src/new_name.rs")
+        .assert(predicate::path::exists());
+    let message = git(&["log", "-1", "--format=%B"]);
+    assert!(
+        message.starts_with("refactor: old_name -> new_name (2 in 1 files, {1} renames)"),
+        "{message}"
+    );
+    assert!(message.contains("Signed-off-by: Test User <test@example.com>"));
+
+    // A bad template fails before any file changes
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("[commit]\ntemplate = \"rename {serach}\"\n")
+        .unwrap();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["rename", "new_name", "newer_name", "--commit"])
+        .args(["--no-verify", "--yes", "--preview", "none"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown placeholder {serach}"));
+    temp_dir
+        .child("src/new_name.rs")
+        .assert("new_name();\nnew_name();\n");
+}
+
+#[test]
+fn test_apply_stage_and_stage_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let renamify = |args: &[&str]| {
+        Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(args)
+            .assert()
+            .success();
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.name", "Test User"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "commit.gpgsign", "false"]);
+    temp_dir
+        .child(".gitignore")
+        .write_str(".renamify/\n")
+        .unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("mod old_name;\n// notes\n")
+        .unwrap();
+    temp_dir
+        .child("src/old_name.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "Initial commit"]);
+
+    // --stage writes the working tree and stages everything it changed
+    renamify(&["plan", "old_name", "new_name", "--output", "json"]);
+    renamify(&["apply", "--stage"]);
+    temp_dir.child("lib.rs").assert("mod new_name;\n// notes\n");
+    assert!(git(&["diff", "--name-only"]).is_empty());
+    assert_eq!(
+        git(&["diff", "--cached", "--name-status"]),
+        "M\tlib.rs\nA\tsrc/new_name.rs\nD\tsrc/old_name.rs\n"
+    );
+    git(&["commit", "-qm", "Rename old_name"]);
+
+    // --stage-only leaves local edits in the working tree alone
+    temp_dir
+        .child("lib.rs")
+        .write_str("// wip\nmod new_name;\n// notes\n")
+        .unwrap();
+    renamify(&["plan", "new_name", "newer_name", "--output", "json"]);
+    renamify(&["apply", "--stage-only", "--commit"]);
+    temp_dir
+        .child("lib.rs")
+        .assert("// wip\nmod new_name;\n// notes\n");
+    temp_dir
+        .child("src/new_name.rs")
+        .assert("fn new_name() {}\n");
+    assert_eq!(git(&["show", "HEAD:lib.rs"]), "mod newer_name;\n// notes\n");
+    assert_eq!(
+        git(&["show", "HEAD:src/newer_name.rs"]),
+        "fn newer_name() {}\n"
+    );
+    assert_eq!(
+        git(&["ls-tree", "-r", "--name-only", "HEAD"]),
+        ".gitignore\nlib.rs\nsrc/newer_name.rs\n"
+    );
+
+    // The working tree still has the old name on a line that differs from
+    // the index, so the edit can't be placed there
+    renamify(&["plan", "new_name", "newest_name", "--output", "json"]);
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["apply", "--stage-only"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "lib.rs:2 has unstaged changes on the line to edit",
+        ));
+}
+
+#[test]
+fn test_sparse_checkout_report() {
+    let temp_dir = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.name", "Test User"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "commit.gpgsign", "false"]);
+    temp_dir
+        .child("app/main.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+    temp_dir
+        .child("services/billing.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+    temp_dir
+        .child("services/old_name/mod.rs")
+        .write_str("mod x;\n")
+        .unwrap();
+    temp_dir
+        .child("services/other.rs")
+        .write_str("unrelated();\n")
+        .unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "Initial commit"]);
+    git(&["sparse-checkout", "set", "app"]);
+    temp_dir
+        .child("services")
+        .assert(predicate::path::missing());
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--preview",
+            "none",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: 2 files outside the sparse checkout likely need this rename too",
+        ))
+        .stdout(predicate::str::contains(
+            "Not checked out: 3 tracked files (sparse checkout)",
+        ))
+        .stdout(predicate::str::contains("    services/billing.rs"))
+        .stdout(predicate::str::contains("    services/old_name/mod.rs"))
+        .stdout(predicate::str::contains("services/other.rs").not());
+
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["sparse_checkout"]["missing_files"], 3);
+    assert_eq!(json["sparse_checkout"]["contents_searched"], true);
+    assert_eq!(
+        json["sparse_checkout"]["likely_matches"],
+        serde_json::json!(["services/billing.rs", "services/old_name/mod.rs"])
+    );
+}
+
+#[test]
+fn test_plan_split_by_codeowners() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child(".github/CODEOWNERS")
+        .write_str("*.rs @org/core\n/billing/ @org/billing @alice\n")
+        .unwrap();
+    temp_dir
+        .child("src/main.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+    temp_dir
+        .child("billing/invoice.rs")
+        .write_str("old_name(); old_name();\n")
+        .unwrap();
+    temp_dir
+        .child("billing/old_name.txt")
+        .write_str("unrelated\n")
+        .unwrap();
+    temp_dir
+        .child("README.txt")
+        .write_str("old_name\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--preview", "none"])
+        .args(["--split-by-codeowners", "owners"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Split by CODEOWNERS into 3 plans:"))
+        .stdout(predicate::str::contains(
+            "  @org/billing @alice: owners/org-billing+alice.json (1 files, 2 replacements, 1 renames)",
+        ))
+        .stdout(predicate::str::contains(
+            "  @org/core: owners/org-core.json (1 files, 1 replacements, 0 renames)",
+        ))
+        .stdout(predicate::str::contains(
+            "  (unowned): owners/unowned.json (1 files, 1 replacements, 0 renames)",
+        ));
+    temp_dir
+        .child(".renamify/plan.json")
+        .assert(predicate::path::exists());
+
+    // Each owner's plan applies on its own
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["apply", "owners/org-billing+alice.json"])
+        .assert()
+        .success();
+    temp_dir
+        .child("billing/invoice.rs")
+        .assert("new_name(); new_name();\n");
+    temp_dir
+        .child("billing/new_name.txt")
+        .assert(predicate::path::exists());
+    temp_dir.child("src/main.rs").assert("old_name();\n");
+    temp_dir.child("README.txt").assert("old_name\n");
+
+    std::fs::remove_file(temp_dir.path().join(".github/CODEOWNERS")).unwrap();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--preview",
+            "none",
+        ])
+        .args(["--split-by-codeowners", "owners"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No CODEOWNERS file"));
+}
+
+#[test]
+fn test_preview_is_not_paged_when_piped() {
+    let temp_dir = TempDir::new().unwrap();
+    let content: String = (0..200).map(|i| format!("old_name_{i}();\n")).collect();
+    temp_dir.child("lib.rs").write_str(&content).unwrap();
+
+    for args in [
+        &["search", "old_name"][..],
+        &["search", "old_name", "--no-pager"],
+    ] {
+        Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .env("PAGER", "echo paged")
+            .env("LINES", "10")
+            .args(args)
+            .args(["--preview", "matches"])
+            .assert()
+            .success()
+            .stdout(predicate::function(|out: &str| out.lines().count() > 10))
+            .stdout(predicate::str::contains("paged").not());
+    }
+}
+
+#[test]
+fn test_preview_sample() {
+    let temp_dir = TempDir::new().unwrap();
+    let content: String = (0..10).map(|i| format!("old_name_{i}();\n")).collect();
+    temp_dir.child("lib.rs").write_str(&content).unwrap();
+    temp_dir
+        .child("short.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .args(["--preview", "diff", "--preview-sample", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+new_name_2();"))
+        .stdout(predicate::str::contains("new_name_3").not())
+        .stdout(predicate::str::contains(
+            "... 7 more matches in this file not shown",
+        ))
+        .stdout(predicate::str::contains(
+            "Showing 4 of 11 matches (up to 3 per file). Totals: 11 matches in 2 files",
+        ));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["search", "old_name", "--preview", "matches"])
+        .args(["--preview-sample", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old_name_1"))
+        .stdout(predicate::str::contains("old_name_2").not())
+        .stdout(predicate::str::contains("8 more"));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["search", "old_name", "--preview-sample", "0"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_paths_only() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("assets/old_name.png")
+        .write_str("old_name")
+        .unwrap();
+    temp_dir
+        .child("old_name_fixtures/data.json")
+        .write_str("{}")
+        .unwrap();
+    temp_dir
+        .child("src/lib.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--paths-only", "--dry-run"])
+        .args(["--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"total_matches\":0"))
+        .stdout(predicate::str::contains("\"renames\":2"));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["-y", "rename", "old_name", "new_name", "--paths-only"])
+        .assert()
+        .success();
+    temp_dir.child("assets/new_name.png").assert("old_name");
+    temp_dir.child("new_name_fixtures/data.json").assert("{}");
+    temp_dir.child("src/lib.rs").assert("old_name();\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--paths-only",
+            "--no-rename-paths",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_contents_only() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--contents-only",
+            "--dry-run",
+        ])
+        .args(["--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"total_matches\":1"))
+        .stdout(predicate::str::contains("\"renames\":0"));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["-y", "rename", "old_name", "new_name", "--contents-only"])
+        .assert()
+        .success();
+    temp_dir.child("old_name.rs").assert("new_name();\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["search", "new_name", "--contents-only", "--paths-only"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_negated_include_and_exclude_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    for file in [
+        "src/lib.rs",
+        "src/generated/api.rs",
+        "tests/unit.rs",
+        "tests/fixtures/data.rs",
+    ] {
+        temp_dir.child(file).write_str("old_name\n").unwrap();
+    }
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["search", "old_name", "--preview", "matches"])
+        .args(["--include", "src/**,tests/**,!src/generated/**"])
+        .args(["--exclude", "tests/**,!tests/fixtures/**"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("src/lib.rs"))
+        .stdout(predicate::str::contains("tests/fixtures/data.rs"))
+        .stdout(predicate::str::contains("generated").not())
+        .stdout(predicate::str::contains("unit.rs").not());
+}
+
+#[test]
+fn test_save_and_reuse_filters() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("src/lib.rs")
+        .write_str("old_name();\nold_name_legacy();\n")
+        .unwrap();
+    temp_dir
+        .child("vendor/dep.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .args([
+            "--exclude",
+            "vendor/**",
+            "--exclude-match",
+            "old_name_legacy",
+        ])
+        .args(["--save-filters", "legacy", "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"total_matches\":1"))
+        .stderr(predicate::str::contains("Saved filters 'legacy'"));
+    temp_dir
+        .child(".renamify/filters/legacy.toml")
+        .assert(predicate::str::contains("old_name_legacy"));
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "-y",
+            "rename",
+            "old_name",
+            "new_name",
+            "--filters",
+            "legacy",
+        ])
+        .assert()
+        .success();
+    temp_dir
+        .child("src/lib.rs")
+        .assert("new_name();\nold_name_legacy();\n");
+    temp_dir.child("vendor/dep.rs").assert("old_name();\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["search", "old_name", "--filters", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No filter set named 'missing'"))
+        .stderr(predicate::str::contains("saved: legacy"));
+}
+
+#[test]
+fn test_empty_plan_diagnosis() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child(".gitignore").write_str("build/\n").unwrap();
+    temp_dir
+        .child("build/out.rs")
+        .write_str("old_name();\n")
+        .unwrap();
+    temp_dir
+        .child("src/lib.rs")
+        .write_str("OldName::new();\n")
+        .unwrap();
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .args(["--only-styles", "snake", "--preview", "none"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No matches or renames found:"))
+        .stdout(predicate::str::contains(
+            "Hidden by ignore files or in nested repositories: 1",
+        ))
+        .stdout(predicate::str::contains(
+            "Matches in case styles not searched: 1",
+        ));
+}
+
+#[test]
+fn test_explain() {
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .args([
+            "explain",
+            "old_name",
+            "SomeOldNameFactory",
+            "--replace",
+            "new_name",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Tokens:     Some | Old | Name | Factory",
+        ))
+        .stdout(predicate::str::contains("Segments:   1..3 (Old Name)"))
+        .stdout(predicate::str::contains(
+            "compound match: SomeOldNameFactory -> SomeNewNameFactory",
+        ));
+
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .args([
+            "explain",
+            "old_name",
+            "SomeOldName",
+            "--only-styles",
+            "snake",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["operation"], "explain");
+    assert_eq!(json["explanation"]["match"], "none");
+    assert_eq!(json["explanation"]["style"], "Pascal");
+    assert_eq!(
+        json["explanation"]["reason"],
+        "'SomeOldName' is pascal case, which is not one of the styles in use"
+    );
+}
+
+#[test]
+fn test_preview_file_from_stdin() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("struct OldName;\n")
+        .unwrap();
+    let buffer = "fn old_name() -> OldName { OldName }\n";
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "preview-file",
+            "--search",
+            "old_name",
+            "--replace",
+            "new_name",
+        ])
+        .write_stdin(buffer)
+        .assert()
+        .success()
+        .stdout("fn new_name() -> NewName { NewName }\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "preview-file",
+            "--search",
+            "old_name",
+            "--replace",
+            "new_name",
+            "--file",
+            "lib.rs",
+            "--stdin",
+            "--output",
+            "diff",
+        ])
+        .write_stdin(buffer)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- lib.rs"))
+        .stdout(predicate::str::contains(
+            "+fn new_name() -> NewName { NewName }",
+        ));
+
+    // Without --stdin the file on disk is read, and left unchanged
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "preview-file",
+            "--search",
+            "old_name",
+            "--replace",
+            "new_name",
+            "--file",
+            "lib.rs",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["operation"], "preview-file");
+    assert_eq!(json["content"], "struct NewName;\n");
+    temp_dir.child("lib.rs").assert("struct OldName;\n");
+    assert!(!temp_dir.path().join(".renamify").exists());
+}
+
+#[test]
+fn test_export_types_command() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["export-types", "--out", "types", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["operation"], "export-types");
+    assert!(json["types"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("Plan")));
+
+    temp_dir
+        .child("types/Plan.d.ts")
+        .assert(predicate::str::contains("export type Plan"));
+    temp_dir
+        .child("types/schema/HistoryEntry.schema.json")
+        .assert(predicate::str::contains("\"title\": \"HistoryEntry\""));
+    temp_dir
+        .child("types/package.json")
+        .assert(predicate::str::contains("\"renamify-types\""));
+}
+
+#[test]
+fn test_plan_stdin_with_virtual_path() {
+    let temp_dir = TempDir::new().unwrap();
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    // On disk, but only the piped content is planned
+    temp_dir
+        .child("src/lib.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--stdin",
+            "--stdin-path",
+            "src/staged.rs",
+            "--output",
+            "json",
+        ])
+        .write_stdin("use crate::old_name;\nOldName::new();\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let matches = json["plan"]["matches"].as_array().unwrap();
+    assert_eq!(matches.len(), 2);
+    let staged = temp_dir.path().join("src/staged.rs");
+    assert!(matches
+        .iter()
+        .all(|hunk| PathBuf::from(hunk["file"].as_str().unwrap()) == staged));
+    assert_eq!(matches[1]["replace"], "NewName");
+    assert_eq!(json["plan"]["paths"].as_array().unwrap().len(), 0);
+
+    // The virtual path is required, and paths to walk make no sense with it
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--stdin", "--dry-run"])
+        .write_stdin("old_name\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--stdin-path <PATH>"));
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "src",
+            "--stdin",
+            "--stdin-path",
+            "a.rs",
+        ])
+        .write_stdin("old_name\n")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_plan_rejects_case_collisions() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("old_name\n")
+        .unwrap();
+    temp_dir.child("New_Name.rs").write_str("other\n").unwrap();
+    // Both files can only coexist on a case-sensitive filesystem
+    if !temp_dir.child("new_name.rs").path().exists() {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["plan", "old_name", "new_name", "--dry-run"])
+            .assert()
+            .code(1)
+            .stderr(predicate::str::contains("CaseCollision"))
+            .stderr(predicate::str::contains("New_Name.rs"));
+
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_name",
+                "new_name",
+                "--dry-run",
+                "--allow-case-collisions",
+            ])
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn test_plan_command_missing_args() {
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.arg("plan")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("required arguments"));
+}
+
+#[test]
+fn test_plan_command_basic() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("test.rs");
+    test_file
+        .write_str("fn old_name() { let old_name = 42; }")
+        .unwrap();
+
+    let (_result, preview) = plan_operation(
+        "old_name",
+        "new_name",
+        vec![PathBuf::from(".")],              // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        true,                                  // rename_files
+        true,                                  // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        Some(&"table".to_string()),            // preview_format
+        true,                                  // dry_run
+        true,                                  // fixed_table_width - for consistent test output
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_dir.path()),                 // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
+    )
+    .unwrap();
+
+    // Verify the preview contains the table content
+    let preview_content = preview.unwrap();
+    assert!(
+        preview_content.contains("test.rs"),
+        "Preview doesn't contain 'test.rs'. Preview content:\n{}",
+        preview_content
+    );
+}
+
+#[test]
+fn test_plan_command_with_styles() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("test.rs");
+    test_file
+        .write_str("fn oldName() { let old_name = 42; }")
+        .unwrap();
+
+    // Test excluding styles (exclude kebab and pascal, keeping snake and camel)
+    let (_result, preview) = plan_operation(
+        "old-name",
+        "new-name",
+        vec![PathBuf::from(".")],                              // paths
+        None,                                                  // buffer
+        vec![],                                                // include
+        vec![],                                                // exclude
+        true,                                                  // respect_gitignore
+        0,                                                     // unrestricted_level
+        true,                                                  // rename_files
+        true,                                                  // rename_dirs
+        &[Style::Kebab, Style::Pascal, Style::ScreamingSnake], // exclude_styles
+        &[],                                                   // include_styles
+        &[],                                                   // only_styles
+        vec![],                                                // exclude_match
+        None,                                                  // exclude_matching_lines
+        vec![],                                                // exclude_lines_in_file
+        None,                                                  // plan_out
+        Some(&"table".to_string()),                            // preview_format
+        true,                                                  // dry_run
+        true,                                  // fixed_table_width - for consistent test output
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_dir.path()),                 // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
+    )
+    .unwrap();
+
+    // Verify the preview contains the table content
+    let preview_content = preview.unwrap();
+    assert!(
+        preview_content.contains("test.rs"),
+        "Preview doesn't contain 'test.rs'. Preview content:\n{}",
+        preview_content
+    );
+    assert!(
+        preview_content.contains("old_name") || preview_content.contains("oldName"),
+        "Preview doesn't contain 'old_name' or 'oldName'. Preview content:\n{}",
+        preview_content
+    );
+
+    // Test including additional styles
+    let (_result2, preview2) = plan_operation(
+        "old-name",
+        "new-name",
+        vec![PathBuf::from(".")],              // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        true,                                  // rename_files
+        true,                                  // rename_dirs
+        &[],                                   // exclude_styles
+        &[Style::Title, Style::Train],         // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        Some(&"table".to_string()),            // preview_format
+        true,                                  // dry_run
+        true,                                  // fixed_table_width - for consistent test output
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_dir.path()),                 // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
+    )
+    .unwrap();
+
+    let preview2_content = preview2.unwrap();
+    assert!(
+        preview2_content.contains("test.rs"),
+        "Preview2 doesn't contain 'test.rs'. Preview content:\n{}",
+        preview2_content
+    );
+}
+
+#[test]
+fn test_plan_command_plural_variant_toggle() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("sample.ts");
+    test_file
+        .write_str(
+            "type DeployRequest = {}\n\
+type DeployRequestList = DeployRequest[];\n\
+export const load = (): Promise<DeployRequestList> => Promise.resolve([]);\n",
+        )
+        .unwrap();
+
+    let (enabled_result, _) = plan_operation(
+        "DeployRequests",
+        "DeployApprovalRequests",
+        vec![PathBuf::from(".")],              // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        true,                                  // rename_files
+        true,                                  // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        None,                                  // preview_format
+        true,                                  // dry_run
+        true,                                  // fixed_table_width
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_dir.path()),                 // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
+    )
+    .unwrap();
+
+    let plan_enabled = enabled_result
+        .plan
+        .expect("plan result should include plan");
+    assert!(plan_enabled.matches.iter().any(|m| m
+        .line_after
+        .as_deref()
+        .is_some_and(|line| line.contains("DeployApprovalRequestList"))));
+
+    let (disabled_result, _) = plan_operation(
+        "DeployRequests",
+        "DeployApprovalRequests",
+        vec![PathBuf::from(".")],
+        None,
+        vec![],
+        vec![],
+        true,
+        0,
+        true,
+        true,
+        &[],
+        &[],
+        &[],
+        vec![],
+        None,
+        vec![],
+        None,
+        None,
+        true,
+        true,
+        renamify_core::GroupBy::File,
+        None, // preview_sample
+        false,
+        false,
+        vec![],
+        vec![],
+        vec![],
+        None,  // acronym_rendering
+        None,  // case_mapping
+        false, // enable_plural_variants
+        false,
+        false,
+        LiteralMode::All,
+        MatchAnchor::Any,
+        CoercionMode::Auto,
+        None,
+        None,
+        false,
+        false,
+        0,
+        false,
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_dir.path()),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let plan_disabled = disabled_result
+        .plan
+        .expect("plan result should include plan");
+    assert!(plan_disabled.matches.iter().all(|m| m
+        .line_after
+        .as_deref()
+        .is_none_or(|line| !line.contains("DeployApprovalRequestList"))));
+}
+
+#[test]
+fn test_plan_command_with_includes() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("src").create_dir_all().unwrap();
+    temp_dir.child("tests").create_dir_all().unwrap();
+    temp_dir
+        .child("src/main.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+    temp_dir
+        .child("tests/test.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--include",
+            "src/**/*.rs",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("src/main.rs"))
+        .stdout(predicate::str::contains("tests/test.rs").not());
+}
+
+#[test]
+fn test_plan_command_with_excludes() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("src").create_dir_all().unwrap();
+    temp_dir.child("tests").create_dir_all().unwrap();
+    temp_dir
+        .child("src/main.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+    temp_dir
+        .child("tests/test.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--exclude",
+            "tests/**",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("src/main.rs"))
+        .stdout(predicate::str::contains("tests/test.rs").not());
+}
+
+#[test]
+fn test_plan_command_json_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("test.rs");
+    test_file.write_str("fn old_name() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--output",
+            "json",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("{"))
+        .stdout(predicate::str::contains("\"search\""))
+        .stdout(predicate::str::contains("\"replace\""));
+}
+
+#[test]
+fn test_plan_command_workspace_edit_format() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--output",
+            "workspace-edit",
+            "--dry-run",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let edit: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let changes = edit["documentChanges"].as_array().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert!(changes[0]["textDocument"]["uri"]
+        .as_str()
+        .unwrap()
+        .ends_with("/old_name.rs"));
+    let text_edit = &changes[0]["edits"][0];
+    assert_eq!(text_edit["range"]["start"]["line"], 0);
+    assert_eq!(text_edit["range"]["start"]["character"], 3);
+    assert_eq!(text_edit["range"]["end"]["character"], 11);
+    assert_eq!(text_edit["newText"], "new_name");
+    assert_eq!(changes[1]["kind"], "rename");
+    assert!(changes[1]["newUri"]
+        .as_str()
+        .unwrap()
+        .ends_with("/new_name.rs"));
+}
+
+#[test]
+fn test_plan_command_guards() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("test.rs")
+        .write_str("fn old_name() { old_name(); }")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--max-matches", "1"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "2 matches exceeds --max-matches 1",
+        ));
+
+    // A tripped guard must not leave a plan behind
+    assert!(!temp_dir.path().join(".renamify/plan.json").exists());
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "missing_name", "new_name", "--fail-if-zero"])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains("--fail-if-zero"))
+        .stderr(predicate::str::contains("Files searched: "));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--max-matches",
+            "2",
+            "--max-files",
+            "1",
+            "--fail-if-zero",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_exit_codes_and_json_error_kind() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("test.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+
+    // Missing plan: not_found maps to exit code 2
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["apply", "--output", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["success"], false);
+    assert_eq!(json["error"]["kind"], "not_found");
+    assert_eq!(json["error"]["exit_code"], 2);
+
+    // Invalid regex: invalid_input maps to exit code 2
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--dry-run",
+            "--exclude-matching-lines",
+            "(",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid regex pattern"));
+
+    // An empty plan under --fail-if-zero has its own exit code and kind
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "missing_name",
+            "new_name",
+            "--fail-if-zero",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(6));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["error"]["kind"], "no_matches");
+    assert_eq!(json["error"]["guard"]["name"], "fail_if_zero");
+    assert!(json["error"]["empty_plan"]["files_searched"].is_number());
+}
+
+#[test]
+fn test_plan_command_diff_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("test.rs");
+    test_file.write_str("fn old_name() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--preview",
+            "diff",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("---"))
+        .stdout(predicate::str::contains("+++"))
+        .stdout(predicate::str::contains("-fn old_name() {}"))
+        .stdout(predicate::str::contains("+fn new_name() {}"));
+}
+
+#[test]
+fn test_plan_command_table_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("test.rs");
+    test_file.write_str("fn old_name() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--preview",
+            "table",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("File"))
+        .stdout(predicate::str::contains("Kind"))
+        .stdout(predicate::str::contains("Matches"))
+        .stdout(predicate::str::contains("TOTALS"));
+}
+
+#[test]
+fn test_no_color_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.child("test.rs");
+    test_file.write_str("fn old_name() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["--no-color", "plan", "old_name", "new_name", "--dry-run"])
+        .assert()
+        .success();
+    // Just verify it doesn't crash with no-color flag
+}
+
+#[test]
+fn test_rename_files_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("old_name.txt").write_str("test").unwrap();
+    temp_dir
+        .child("test.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old_name.txt"))
+        .stdout(predicate::str::contains("new_name.txt"));
+}
+
+#[test]
+fn test_rename_dirs_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("old_name").create_dir_all().unwrap();
+    temp_dir
+        .child("old_name/test.rs")
+        .write_str("fn test() {}")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old_name"))
+        .stdout(predicate::str::contains("new_name"));
+}
+
+#[test]
+fn test_no_rename_files_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("old_name.txt").write_str("test").unwrap();
+    temp_dir
+        .child("test.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "--no-rename-files",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("test.rs"))
+        .stdout(predicate::str::contains("old_name.txt").not());
+}
+
+#[test]
+fn test_apply_command_directory_transactions() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("old_name/old_name.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+    temp_dir
+        .child("other/lib.rs")
+        .write_str("use old_name;")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--output", "json"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["apply", "--transaction-scope", "dir", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let plan_id = json["plan_id"].as_str().unwrap();
+    // old_name/ (content + file rename), other/ (content), and the root (dir rename)
+    assert_eq!(json["transactions"].as_array().unwrap().len(), 3);
+
+    temp_dir
+        .child("new_name/new_name.rs")
+        .assert("fn new_name() {}");
+    temp_dir.child("other/lib.rs").assert("use new_name;");
+
+    for n in (1..=3).rev() {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["undo", &format!("{plan_id}-{n}")])
+            .assert()
+            .success();
+    }
+
+    temp_dir
+        .child("old_name/old_name.rs")
+        .assert("fn old_name() {}");
+    temp_dir.child("other/lib.rs").assert("use old_name;");
+}
+
+#[test]
+fn test_apply_reports_stale_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("src/old_name/main.c")
+        .write_str("int main() {}\n")
+        .unwrap();
+    temp_dir.child(".gitignore").write_str("build/\n").unwrap();
+    temp_dir
+        .child("build/compile_commands.json")
+        .write_str("[{\"file\": \"src/old_name/main.c\"}]\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--output", "json"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["apply", "--report-stale-paths", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let stale = json["stale_paths"].as_array().unwrap();
+    assert_eq!(stale.len(), 1);
+    assert!(stale[0]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("build/compile_commands.json"));
+    assert_eq!(stale[0]["line"], 1);
+    assert_eq!(stale[0]["old_path"], "src/old_name");
+    assert_eq!(stale[0]["new_path"], "src/new_name");
+    temp_dir
+        .child("src/new_name/main.c")
+        .assert("int main() {}\n");
+    // Reported, not rewritten
+    temp_dir
+        .child("build/compile_commands.json")
+        .assert("[{\"file\": \"src/old_name/main.c\"}]\n");
+}
+
+#[test]
+fn test_apply_command_missing_plan() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["apply", "nonexistent.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read plan file"));
+}
+
+#[test]
+fn test_apply_command_with_plan() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Create a test file with content to replace
+    temp_dir
+        .child("test.rs")
+        .write_str("fn old_name() {}")
+        .unwrap();
+
+    // First create a plan
+    let renamify_dir = temp_dir.child(".renamify");
+    renamify_dir.create_dir_all().unwrap();
+
+    // Create a minimal valid plan
+    let plan_json = r#"{
+        "id": "test123",
+        "created_at": "2024-01-01T00:00:00Z",
+        "search": "old_name",
+        "replace": "new_name",
+        "styles": [],
+        "includes": [],
+        "excludes": [],
+        "matches": [],
+        "paths": [],
+        "stats": {
+            "files_scanned": 1,
+            "total_matches": 0,
+            "matches_by_variant": {},
+            "files_with_matches": 0
+        },
+        "version": "1.0.0"
+    }"#;
+
+    let plan_path = renamify_dir.child("plan.json");
+    plan_path.write_str(plan_json).unwrap();
+
+    // Apply the plan (uses default plan.json)
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("apply")
         .assert()
         .success()
-        .stdout(
-            predicate::str::is_match(r#"\{"name":"renamify","version":"\d+\.\d+\.\d+"\}"#).unwrap(),
-        );
+        .stdout(predicate::str::contains("Applied"));
 }
 
 #[test]
-fn test_plan_command_missing_args() {
-    let mut cmd = Command::cargo_bin("renamify").unwrap();
-    cmd.arg("plan")
+fn test_apply_plan_from_url() {
+    use sha2::{Digest, Sha256};
+    use std::io::{BufRead, BufReader, Write};
+
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("lib.rs").write_str("old_name();\n").unwrap();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--plan-out", "shared.json"])
+        .assert()
+        .success();
+    let plan = std::fs::read(temp_dir.path().join("shared.json")).unwrap();
+    let checksum = format!("sha256:{:x}", Sha256::digest(&plan));
+
+    // Serves the plan to each request
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/plan.json", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut line = String::new();
+            let mut reader = BufReader::new(&stream);
+            while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                line.clear();
+            }
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                plan.len()
+            );
+            let _ = stream.write_all(header.as_bytes());
+            let _ = stream.write_all(&plan);
+        }
+    });
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["apply", "--plan", &url])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("without --checksum"));
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "apply",
+            "--plan",
+            &url,
+            "--checksum",
+            &format!("sha256:{}", "0".repeat(64)),
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Checksum mismatch"));
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("lib.rs")).unwrap(),
+        "old_name();\n"
+    );
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["apply", "--plan", &url, "--checksum", &checksum])
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("lib.rs")).unwrap(),
+        "new_name();\n"
+    );
+}
+
+#[test]
+fn test_signed_plans() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("lib.rs").write_str("old_name();\n").unwrap();
+    let renamify = || {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path());
+        cmd
+    };
+    renamify()
+        .args(["keygen", "keys/ci.key"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Public key: "));
+    renamify()
+        .args(["keygen", "keys/other.key"])
+        .assert()
+        .success();
+
+    // Unsigned plans are refused once a signature is required
+    renamify()
+        .args(["plan", "old_name", "new_name", "lib.rs"])
+        .assert()
+        .success();
+    renamify()
+        .args([
+            "apply",
+            "--require-signed",
+            "--trusted-key",
+            "keys/ci.key.pub",
+        ])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("is not signed"));
+
+    renamify()
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "lib.rs",
+            "--sign",
+            "keys/ci.key",
+        ])
+        .assert()
+        .success();
+    let plan_path = temp_dir.path().join(".renamify/plan.json");
+    let signed = std::fs::read_to_string(&plan_path).unwrap();
+    assert!(signed.contains("\"signature\""));
+
+    renamify()
+        .args(["apply", "--trusted-key", "keys/other.key.pub"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("not a trusted key"));
+    std::fs::write(&plan_path, signed.replace("new_name", "bad_name")).unwrap();
+    renamify()
+        .arg("apply")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("changed after it was signed"));
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("lib.rs")).unwrap(),
+        "old_name();\n"
+    );
+
+    std::fs::write(&plan_path, signed).unwrap();
+    renamify()
+        .args([
+            "apply",
+            "--require-signed",
+            "--trusted-key",
+            "keys/ci.key.pub",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("lib.rs")).unwrap(),
+        "new_name();\n"
+    );
+}
+
+#[test]
+fn test_report_command() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("lib.rs").write_str("old_name();\n").unwrap();
+    let renamify = || {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path());
+        cmd
+    };
+    renamify()
+        .arg("report")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No renamify history"));
+
+    renamify()
+        .args(["rename", "old_name", "new_name", "lib.rs", "--yes"])
+        .assert()
+        .success();
+    renamify().args(["undo", "latest"]).assert().success();
+    renamify()
+        .arg("report")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Applies: 1  Undos: 1 (100% of applies)",
+        ));
+
+    let output = renamify()
+        .args(["report", "--output", "json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["report"]["entries"], 2);
+    assert_eq!(json["report"]["average_matches_per_plan"], 1.0);
+    assert_eq!(json["report"]["by_month"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_no_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child("lib.rs").write_str("old_name();\n").unwrap();
+    let renamify = || {
+        let mut cmd = Command::cargo_bin("renamify").unwrap();
+        cmd.current_dir(temp_dir.path()).arg("--no-workspace");
+        cmd
+    };
+
+    renamify()
+        .args(["plan", "old_name", "new_name"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("The plan was not saved"));
+    renamify()
+        .args(["search", "old_name"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"));
+    renamify()
+        .args(["rename", "old_name", "new_name", "--dry-run"])
+        .assert()
+        .success();
+    assert!(!temp_dir.path().join(".renamify").exists());
+
+    renamify()
+        .args(["plan", "old_name", "new_name", "--plan-out", "plan.json"])
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("plan.json").exists());
+
+    // Changing files needs the history and backups
+    renamify()
+        .args(["apply", "plan.json"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "apply needs a writable .renamify directory",
+        ));
+    renamify()
+        .args(["rename", "old_name", "new_name", "--yes"])
+        .assert()
+        .code(2);
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("lib.rs")).unwrap(),
+        "old_name();\n"
+    );
+
+    // A .renamify that can't be written to turns the workspace off for commands
+    // that only read
+    temp_dir.child(".renamify").write_str("").unwrap();
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "lib.rs"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: .renamify is not writable",
+        ));
+}
+
+#[test]
+fn test_atomic_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("doc_spring();\ndocspring();\n")
+        .unwrap();
+    let matched = |args: &[&str]| -> Vec<(String, String)> {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .arg("plan")
+            .args(args)
+            .args(["--dry-run", "--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| {
+                (
+                    hunk["content"].as_str().unwrap().to_string(),
+                    hunk["replace"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+    let pair = |content: &str, replace: &str| (content.to_string(), replace.to_string());
+
+    assert_eq!(
+        matched(&["DocSpring", "FormAPI", "lib.rs"]),
+        [pair("doc_spring", "form_api")]
+    );
+    // A bare --atomic doesn't take the search term as its value
+    assert_eq!(
+        matched(&["--atomic", "DocSpring", "FormAPI", "lib.rs"]),
+        [pair("docspring", "formapi")]
+    );
+    assert_eq!(
+        matched(&["DocSpring", "FormAPI", "lib.rs", "--atomic=search"]),
+        [pair("docspring", "form_api")]
+    );
+
+    // Atomic identifiers from config apply until --no-atomic
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("atomic = [\"DocSpring\"]\n")
+        .unwrap();
+    assert_eq!(
+        matched(&["DocSpring", "FormAPI", "lib.rs"]),
+        [pair("docspring", "form_api")]
+    );
+    assert_eq!(
+        matched(&["DocSpring", "FormAPI", "lib.rs", "--no-atomic"]),
+        [pair("doc_spring", "form_api")]
+    );
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["plan", "DocSpring", "FormAPI", "--atomic=all"])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("required arguments"));
+        .stderr(predicate::str::contains("invalid value 'all'"));
 }
 
 #[test]
-fn test_plan_command_basic() {
+fn test_acronym_rendering() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("test.rs");
-    test_file
-        .write_str("fn old_name() { let old_name = 42; }")
+    temp_dir
+        .child("lib.rs")
+        .write_str("let client = oldClient;\nfetchOldClient();\n")
         .unwrap();
+    let replacements = |args: &[&str]| -> Vec<String> {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["plan", "OldClient", "APIClient", "lib.rs"])
+            .args(args)
+            .args(["--dry-run", "--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let mut replacements: Vec<String> = json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| hunk["replace"].as_str().unwrap().to_string())
+            .collect();
+        replacements.sort();
+        replacements
+    };
+
+    // Exact and compound matches render the acronym the same way
+    assert_eq!(replacements(&[]), ["apiClient", "fetchAPIClient"]);
+    assert_eq!(
+        replacements(&["--acronym-rendering", "pascalize"]),
+        ["apiClient", "fetchApiClient"]
+    );
 
-    let (_result, preview) = plan_operation(
-        "old_name",
-        "new_name",
-        vec![PathBuf::from(".")],   // paths
-        vec![],                     // include
-        vec![],                     // exclude
-        true,                       // respect_gitignore
-        0,                          // unrestricted_level
-        true,                       // rename_files
-        true,                       // rename_dirs
-        &[],                        // exclude_styles
-        &[],                        // include_styles
-        &[],                        // only_styles
-        vec![],                     // exclude_match
-        None,                       // exclude_matching_lines
-        None,                       // plan_out
-        Some(&"table".to_string()), // preview_format
-        true,                       // dry_run
-        true,                       // fixed_table_width - for consistent test output
-        false,                      // use_color
-        false,                      // no_acronyms
-        vec![],                     // include_acronyms
-        vec![],                     // exclude_acronyms
-        vec![],                     // only_acronyms
-        true,                       // enable_plural_variants
-        false,                      // ignore_ambiguous
-        Some(temp_dir.path()),      // working_dir
-        None,                       // atomic_config
-    )
-    .unwrap();
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("acronym_rendering = \"lower\"\n")
+        .unwrap();
+    assert_eq!(replacements(&[]), ["apiClient", "fetchapiClient"]);
+    assert_eq!(
+        replacements(&["--acronym-rendering", "preserve-upper"]),
+        ["apiClient", "fetchAPIClient"]
+    );
+}
 
-    // Verify the preview contains the table content
-    let preview_content = preview.unwrap();
-    assert!(
-        preview_content.contains("test.rs"),
-        "Preview doesn't contain 'test.rs'. Preview content:\n{}",
-        preview_content
+#[test]
+fn test_case_mapping() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("OldName.rs")
+        .write_str("let old_name = OldName::new(OLD_NAME);\n")
+        .unwrap();
+    let plan = |args: &[&str]| -> (Vec<String>, Vec<String>) {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["plan", "old_name", "ilk_isim"])
+            .args(args)
+            .args(["--dry-run", "--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let mut replacements: Vec<String> = json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| hunk["replace"].as_str().unwrap().to_string())
+            .collect();
+        replacements.sort();
+        let paths = json["plan"]["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rename| rename["new_path"].as_str().unwrap().to_string())
+            .collect();
+        (replacements, paths)
+    };
+
+    let (replacements, paths) = plan(&[]);
+    assert_eq!(replacements, ["ILK_ISIM", "IlkIsim", "ilk_isim"]);
+    assert!(paths[0].ends_with("IlkIsim.rs"));
+
+    // Turkish keeps the dot on the capital İ in identifiers and file names
+    let (replacements, paths) = plan(&["--case-mapping", "turkish"]);
+    assert_eq!(replacements, ["ilk_isim", "İLK_İSİM", "İlkİsim"]);
+    assert!(paths[0].ends_with("İlkİsim.rs"));
+
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("case_mapping = \"turkish\"\n")
+        .unwrap();
+    assert_eq!(plan(&[]).0, ["ilk_isim", "İLK_İSİM", "İlkİsim"]);
+    assert_eq!(
+        plan(&["--case-mapping", "unicode"]).0,
+        ["ILK_ISIM", "IlkIsim", "ilk_isim"]
     );
 }
 
 #[test]
-fn test_plan_command_with_styles() {
+fn test_match_anchor() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("test.rs");
-    test_file
-        .write_str("fn oldName() { let old_name = 42; }")
+    temp_dir
+        .child("lib.rs")
+        .write_str("let old_config = OldConfig::load();\nlet threshold_old = 3;\n")
         .unwrap();
+    let matched = |anchor: &str| -> Vec<String> {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["search", "old", "lib.rs", "--match-anchor", anchor])
+            .args(["--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let mut matched: Vec<String> = json["plan"]["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hunk| hunk["content"].as_str().unwrap().to_string())
+            .collect();
+        matched.sort();
+        matched
+    };
+
+    assert_eq!(matched("prefix"), ["Old", "old"]);
+    assert_eq!(matched("suffix"), ["old"]);
+    assert_eq!(matched("whole"), Vec::<String>::new());
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["search", "old", "--match-anchor", "middle"])
+        .assert()
+        .failure();
+}
 
-    // Test excluding styles (exclude kebab and pascal, keeping snake and camel)
-    let (_result, preview) = plan_operation(
-        "old-name",
-        "new-name",
-        vec![PathBuf::from(".")],                              // paths
-        vec![],                                                // include
-        vec![],                                                // exclude
-        true,                                                  // respect_gitignore
-        0,                                                     // unrestricted_level
-        true,                                                  // rename_files
-        true,                                                  // rename_dirs
-        &[Style::Kebab, Style::Pascal, Style::ScreamingSnake], // exclude_styles
-        &[],                                                   // include_styles
-        &[],                                                   // only_styles
-        vec![],                                                // exclude_match
-        None,                                                  // exclude_matching_lines
-        None,                                                  // plan_out
-        Some(&"table".to_string()),                            // preview_format
-        true,                                                  // dry_run
-        true,                  // fixed_table_width - for consistent test output
-        false,                 // use_color
-        false,                 // no_acronyms
-        vec![],                // include_acronyms
-        vec![],                // exclude_acronyms
-        vec![],                // only_acronyms
-        true,                  // enable_plural_variants
-        false,                 // ignore_ambiguous
-        Some(temp_dir.path()), // working_dir
-        None,                  // atomic_config
-    )
-    .unwrap();
+#[test]
+fn test_short_search_guard() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("lib.rs")
+        .write_str("let id = widget_id(data);\n")
+        .unwrap();
+    let renamify = || {
+        let mut command = Command::cargo_bin("renamify").unwrap();
+        command.current_dir(temp_dir.path());
+        command
+    };
+
+    renamify()
+        .args(["plan", "id", "key", "lib.rs", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--allow-short-search"));
+    renamify()
+        .args(["plan", "data", "input", "lib.rs", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("stopword"));
+    renamify()
+        .args(["plan", "id", "key", "lib.rs", "--dry-run"])
+        .arg("--allow-short-search")
+        .assert()
+        .success();
+    renamify()
+        .args(["plan", "widget_id", "gadget_id", "lib.rs", "--dry-run"])
+        .assert()
+        .success();
+
+    // Search only warns
+    renamify()
+        .args(["search", "id", "lib.rs"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Warning: 'id' is a single"));
+
+    temp_dir
+        .child(".renamify/config.toml")
+        .write_str("[short_search]\nmin_length = 2\nstopwords = [\"widget\"]\n")
+        .unwrap();
+    renamify()
+        .args(["plan", "id", "key", "lib.rs", "--dry-run"])
+        .assert()
+        .success();
+    renamify()
+        .args(["plan", "widget", "gadget", "lib.rs", "--dry-run"])
+        .assert()
+        .code(2);
+}
 
-    // Verify the preview contains the table content
-    let preview_content = preview.unwrap();
-    assert!(
-        preview_content.contains("test.rs"),
-        "Preview doesn't contain 'test.rs'. Preview content:\n{}",
-        preview_content
-    );
-    assert!(
-        preview_content.contains("old_name") || preview_content.contains("oldName"),
-        "Preview doesn't contain 'old_name' or 'oldName'. Preview content:\n{}",
-        preview_content
-    );
+#[test]
+fn test_directory_flag_runs_in_another_directory() {
+    let outer = TempDir::new().unwrap();
+    let repo = outer.child("repo");
+    repo.child("src/lib.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+    let renamify = || {
+        let mut command = Command::cargo_bin("renamify").unwrap();
+        command
+            .current_dir(outer.path())
+            .args(["-C", "repo", "--no-auto-init"]);
+        command
+    };
+
+    renamify()
+        .args([
+            "plan",
+            "old_name",
+            "new_name",
+            "src",
+            "--preview",
+            "summary",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("src/lib.rs: 1 matches"));
+    repo.child(".renamify/plan.json")
+        .assert(predicate::path::exists());
+    outer.child(".renamify").assert(predicate::path::missing());
 
-    // Test including additional styles
-    let (_result2, preview2) = plan_operation(
-        "old-name",
-        "new-name",
-        vec![PathBuf::from(".")],      // paths
-        vec![],                        // include
-        vec![],                        // exclude
-        true,                          // respect_gitignore
-        0,                             // unrestricted_level
-        true,                          // rename_files
-        true,                          // rename_dirs
-        &[],                           // exclude_styles
-        &[Style::Title, Style::Train], // include_styles
-        &[],                           // only_styles
-        vec![],                        // exclude_match
-        None,                          // exclude_matching_lines
-        None,                          // plan_out
-        Some(&"table".to_string()),    // preview_format
-        true,                          // dry_run
-        true,                          // fixed_table_width - for consistent test output
-        false,                         // use_color
-        false,                         // no_acronyms
-        vec![],                        // include_acronyms
-        vec![],                        // exclude_acronyms
-        vec![],                        // only_acronyms
-        true,                          // enable_plural_variants
-        false,                         // ignore_ambiguous
-        Some(temp_dir.path()),         // working_dir
-        None,                          // atomic_config
-    )
-    .unwrap();
+    renamify().args(["apply", "--quiet"]).assert().success();
+    repo.child("src/lib.rs").assert("fn new_name() {}\n");
 
-    let preview2_content = preview2.unwrap();
-    assert!(
-        preview2_content.contains("test.rs"),
-        "Preview2 doesn't contain 'test.rs'. Preview content:\n{}",
-        preview2_content
-    );
+    renamify()
+        .args(["history", "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"old_name\""));
+    renamify()
+        .args(["undo", "latest", "--quiet"])
+        .assert()
+        .success();
+    repo.child("src/lib.rs").assert("fn old_name() {}\n");
+
+    Command::cargo_bin("renamify")
+        .unwrap()
+        .args(["-C", "missing", "status"])
+        .current_dir(outer.path())
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Not a directory"));
 }
 
 #[test]
-fn test_plan_command_plural_variant_toggle() {
+fn test_search_listing_flags() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("sample.ts");
-    test_file
-        .write_str(
-            "type DeployRequest = {}\n\
-type DeployRequestList = DeployRequest[];\n\
-export const load = (): Promise<DeployRequestList> => Promise.resolve([]);\n",
-        )
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    temp_dir
+        .child("a.rs")
+        .write_str("fn old_name() {}\nOldName::new();\n")
         .unwrap();
+    temp_dir.child("b.rs").write_str("let x = 1;\n").unwrap();
 
-    let (enabled_result, _) = plan_operation(
-        "DeployRequests",
-        "DeployApprovalRequests",
-        vec![PathBuf::from(".")], // paths
-        vec![],                   // include
-        vec![],                   // exclude
-        true,                     // respect_gitignore
-        0,                        // unrestricted_level
-        true,                     // rename_files
-        true,                     // rename_dirs
-        &[],                      // exclude_styles
-        &[],                      // include_styles
-        &[],                      // only_styles
-        vec![],                   // exclude_match
-        None,                     // exclude_matching_lines
-        None,                     // plan_out
-        None,                     // preview_format
-        true,                     // dry_run
-        true,                     // fixed_table_width
-        false,                    // use_color
-        false,                    // no_acronyms
-        vec![],                   // include_acronyms
-        vec![],                   // exclude_acronyms
-        vec![],                   // only_acronyms
-        true,                     // enable_plural_variants
-        false,                    // ignore_ambiguous
-        Some(temp_dir.path()),    // working_dir
-        None,                     // atomic_config
-    )
-    .unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "--count"])
+        .assert()
+        .success()
+        .stdout("a.rs:2\n");
 
-    let plan_enabled = enabled_result
-        .plan
-        .expect("plan result should include plan");
-    assert!(plan_enabled.matches.iter().any(|m| m
-        .line_after
-        .as_deref()
-        .is_some_and(|line| line.contains("DeployApprovalRequestList"))));
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "-l"])
+        .assert()
+        .success()
+        .stdout("a.rs\n");
 
-    let (disabled_result, _) = plan_operation(
-        "DeployRequests",
-        "DeployApprovalRequests",
-        vec![PathBuf::from(".")],
-        vec![],
-        vec![],
-        true,
-        0,
-        true,
-        true,
-        &[],
-        &[],
-        &[],
-        vec![],
-        None,
-        None,
-        None,
-        true,
-        true,
-        false,
-        false,
-        vec![],
-        vec![],
-        vec![],
-        false, // enable_plural_variants
-        false,
-        Some(temp_dir.path()),
-        None,
-    )
-    .unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "--files-without-match"])
+        .assert()
+        .success()
+        .stdout("b.rs\n");
 
-    let plan_disabled = disabled_result
-        .plan
-        .expect("plan result should include plan");
-    assert!(plan_disabled.matches.iter().all(|m| m
-        .line_after
-        .as_deref()
-        .is_none_or(|line| !line.contains("DeployApprovalRequestList"))));
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["search", "old_name", "--json"])
+        .output()
+        .unwrap();
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[1]["type"], "match");
+    assert_eq!(events[1]["variant"], "OldName");
+    assert_eq!(events[1]["line"], 2);
+    assert_eq!(events[2]["type"], "summary");
+    assert_eq!(events[2]["total_matches"], 2);
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "--count", "-l"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
 }
 
+#[cfg(unix)]
 #[test]
-fn test_plan_command_with_includes() {
+fn test_walk_depth_and_symlinks() {
     let temp_dir = TempDir::new().unwrap();
-    temp_dir.child("src").create_dir_all().unwrap();
-    temp_dir.child("tests").create_dir_all().unwrap();
-    temp_dir
-        .child("src/main.rs")
-        .write_str("fn old_name() {}")
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
         .unwrap();
+    temp_dir.child("top.txt").write_str("old_name\n").unwrap();
     temp_dir
-        .child("tests/test.rs")
-        .write_str("fn old_name() {}")
+        .child("a/b/deep.txt")
+        .write_str("old_name\n")
         .unwrap();
+    let outside = TempDir::new().unwrap();
+    outside.child("linked.txt").write_str("old_name\n").unwrap();
+    std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("ext")).unwrap();
+    std::os::unix::fs::symlink("top.txt", temp_dir.path().join("alias.txt")).unwrap();
 
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args([
-            "plan",
-            "old_name",
-            "new_name",
-            "--include",
-            "src/**/*.rs",
-            "--dry-run",
-        ])
+        .args(["search", "old_name", "-l"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("src/main.rs"))
-        .stdout(predicate::str::contains("tests/test.rs").not());
+        .stdout("a/b/deep.txt\ntop.txt\n");
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "-l", "--max-depth", "1"])
+        .assert()
+        .success()
+        .stdout("top.txt\n");
+
+    // Followed links are edited through to their targets and stay links
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["rename", "old_name", "new_name", "--follow", "--yes"])
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(outside.path().join("linked.txt")).unwrap(),
+        "new_name\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("top.txt")).unwrap(),
+        "new_name\n"
+    );
+    assert!(std::fs::symlink_metadata(temp_dir.path().join("alias.txt"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
 }
 
 #[test]
-fn test_plan_command_with_excludes() {
+fn test_max_filesize_lists_large_files() {
     let temp_dir = TempDir::new().unwrap();
-    temp_dir.child("src").create_dir_all().unwrap();
-    temp_dir.child("tests").create_dir_all().unwrap();
-    temp_dir
-        .child("src/main.rs")
-        .write_str("fn old_name() {}")
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
         .unwrap();
+    temp_dir.child("small.txt").write_str("old_name\n").unwrap();
     temp_dir
-        .child("tests/test.rs")
-        .write_str("fn old_name() {}")
+        .child("old_name.log")
+        .write_str(&"old_name\n".repeat(1000))
         .unwrap();
 
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args([
-            "plan",
-            "old_name",
-            "new_name",
-            "--exclude",
-            "tests/**",
-            "--dry-run",
-        ])
+        .args(["search", "old_name", "--max-filesize", "1K"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("src/main.rs"))
-        .stdout(predicate::str::contains("tests/test.rs").not());
+        .stdout(predicate::str::contains(
+            "Large Files Not Searched (1, raise --max-filesize to search them):",
+        ))
+        .stdout(predicate::str::contains(
+            "old_name.log (8.8 KB, name matches)",
+        ))
+        .stdout(predicate::str::contains(
+            "Not searched: 1 files over --max-filesize",
+        ));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "--max-filesize", "0", "--count"])
+        .assert()
+        .success()
+        .stdout("old_name.log:1000\nsmall.txt:1\n");
 }
 
 #[test]
-fn test_plan_command_json_format() {
+fn test_type_filters() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("test.rs");
-    test_file.write_str("fn old_name() {}").unwrap();
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    temp_dir
+        .child("src/lib.rs")
+        .write_str("old_name\n")
+        .unwrap();
+    temp_dir
+        .child("web/app.ts")
+        .write_str("old_name\n")
+        .unwrap();
+    temp_dir.child("README.md").write_str("old_name\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "--type", "rust,ts", "--count"])
+        .assert()
+        .success()
+        .stdout("src/lib.rs:1\nweb/app.ts:1\n");
 
+    // --type-not combines with --include
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
         .args([
-            "plan",
+            "search",
             "old_name",
-            "new_name",
-            "--output",
-            "json",
-            "--dry-run",
+            "-T",
+            "md",
+            "--include",
+            "src/**",
+            "--count",
         ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("{"))
-        .stdout(predicate::str::contains("\"search\""))
-        .stdout(predicate::str::contains("\"replace\""));
+        .stdout("src/lib.rs:1\n");
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["search", "old_name", "--type", "rs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown file type 'rs' (see --type-list)",
+        ));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.arg("--type-list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust: *.rs\n"))
+        .stdout(predicate::str::contains("ts: *.ts, *.tsx, *.mts, *.cts\n"));
 }
 
 #[test]
-fn test_plan_command_diff_format() {
+fn test_init_suggest_ignores() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("test.rs");
-    test_file.write_str("fn old_name() {}").unwrap();
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    temp_dir
+        .child("node_modules/left-pad/index.js")
+        .write_str("")
+        .unwrap();
+    temp_dir.child("dist/app.js").write_str("").unwrap();
+    temp_dir.child("src/main.rs").write_str("").unwrap();
 
+    // Without a terminal the suggestions are only listed
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args([
-            "plan",
-            "old_name",
-            "new_name",
-            "--preview",
-            "diff",
-            "--dry-run",
-        ])
+        .args(["init", "--suggest-ignores"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "1. node_modules/ (node_modules, 1 files)",
+        ))
+        .stderr(predicate::str::contains("2. dist/ (dist, 1 files)"));
+    assert!(!temp_dir.child(".rnignore").exists());
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["init", "--suggest-ignores", "--yes"])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("---"))
-        .stdout(predicate::str::contains("+++"))
-        .stdout(predicate::str::contains("-fn old_name() {}"))
-        .stdout(predicate::str::contains("+fn new_name() {}"));
+        .success();
+    temp_dir
+        .child(".rnignore")
+        .assert(predicate::str::diff("node_modules/\ndist/\n"));
 }
 
 #[test]
-fn test_plan_command_table_format() {
+fn test_serve_stdio() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("test.rs");
-    test_file.write_str("fn old_name() {}").unwrap();
-
-    let mut cmd = Command::cargo_bin("renamify").unwrap();
-    cmd.current_dir(temp_dir.path())
-        .args([
-            "plan",
-            "old_name",
-            "new_name",
-            "--preview",
-            "table",
-            "--dry-run",
-        ])
+    temp_dir.child("a.rs").write_str("old_name\n").unwrap();
+
+    let requests = [
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize"}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "plan", "params": {"search": "old_name", "replace": "new_name"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "apply"}"#,
+        r#"{"jsonrpc": "2.0", "id": 4, "method": "shutdown"}"#,
+    ];
+    let output = Command::cargo_bin("renamify")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["serve", "--stdio"])
+        .write_stdin(requests.join("\n"))
         .assert()
         .success()
-        .stdout(predicate::str::contains("File"))
-        .stdout(predicate::str::contains("Kind"))
-        .stdout(predicate::str::contains("Matches"))
-        .stdout(predicate::str::contains("TOTALS"));
+        .get_output()
+        .stdout
+        .clone();
+    let responses: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["result"]["protocol_version"], 1);
+    assert_eq!(responses[1]["result"]["total_matches"], 1);
+    assert_eq!(responses[2]["result"]["replacements"], 1);
+    temp_dir.child("a.rs").assert("new_name\n");
 }
 
 #[test]
-fn test_no_color_flag() {
+fn test_gc_removes_orphaned_backups() {
     let temp_dir = TempDir::new().unwrap();
-    let test_file = temp_dir.child("test.rs");
-    test_file.write_str("fn old_name() {}").unwrap();
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    temp_dir
+        .child("main.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
 
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args(["--no-color", "plan", "old_name", "new_name", "--dry-run"])
+        .args(["rename", "old_name", "new_name", "--yes"])
         .assert()
         .success();
-    // Just verify it doesn't crash with no-color flag
-}
-
-#[test]
-fn test_rename_files_flag() {
-    let temp_dir = TempDir::new().unwrap();
-    temp_dir.child("old_name.txt").write_str("test").unwrap();
     temp_dir
-        .child("test.rs")
-        .write_str("fn old_name() {}")
+        .child(".renamify/backups/crashed/main.rs")
+        .write_str("fn old_name() {}\n")
         .unwrap();
 
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .args(["gc", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("old_name.txt"))
-        .stdout(predicate::str::contains("new_name.txt"));
-}
-
-#[test]
-fn test_rename_dirs_flag() {
-    let temp_dir = TempDir::new().unwrap();
-    temp_dir.child("old_name").create_dir_all().unwrap();
+        .stdout(predicate::str::contains("Would remove 1 orphaned files"))
+        .stdout(predicate::str::contains(
+            "backup     .renamify/backups/crashed",
+        ));
     temp_dir
-        .child("old_name/test.rs")
-        .write_str("fn test() {}")
-        .unwrap();
+        .child(".renamify/backups/crashed")
+        .assert(predicate::path::exists());
 
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args(["plan", "old_name", "new_name", "--dry-run"])
+        .args(["gc"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("old_name"))
-        .stdout(predicate::str::contains("new_name"));
+        .stdout(predicate::str::contains("Removed 1 orphaned files"));
+    temp_dir
+        .child(".renamify/backups/crashed")
+        .assert(predicate::path::missing());
+
+    // The applied rename can still be undone
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["undo", "latest"])
+        .assert()
+        .success();
+    temp_dir
+        .child("main.rs")
+        .assert(predicate::str::diff("fn old_name() {}\n"));
 }
 
 #[test]
-fn test_no_rename_files_flag() {
+fn test_plan_skips_unchanged_files_from_cache() {
     let temp_dir = TempDir::new().unwrap();
-    temp_dir.child("old_name.txt").write_str("test").unwrap();
-    temp_dir
-        .child("test.rs")
-        .write_str("fn old_name() {}")
+    temp_dir.child("a.rs").write_str("old_name\n").unwrap();
+    temp_dir.child("b.rs").write_str("other_thing\n").unwrap();
+    // Files modified moments before they're read aren't cached
+    let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    for name in ["a.rs", "b.rs"] {
+        std::fs::File::options()
+            .write(true)
+            .open(temp_dir.path().join(name))
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+    }
+
+    let total_matches = |extra: &[&str]| {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args([
+                "plan",
+                "old_name",
+                "new_name",
+                "--dry-run",
+                "--output",
+                "porcelain",
+            ])
+            .args(extra)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .find_map(|line| line.strip_prefix("total_matches\t").map(str::to_string))
+            .unwrap()
+    };
+    assert_eq!(total_matches(&[]), "1");
+    assert_eq!(
+        std::fs::read_dir(temp_dir.path().join(".renamify/cache"))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    // Same size and modification time, so the cached result for b.rs stands
+    // until --no-cache searches it again
+    let b = temp_dir.path().join("b.rs");
+    std::fs::write(&b, "old_name_xy\n").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&b)
+        .unwrap()
+        .set_modified(an_hour_ago)
         .unwrap();
+    assert_eq!(total_matches(&[]), "1");
+    assert_eq!(total_matches(&["--no-cache"]), "2");
 
-    let mut cmd = Command::cargo_bin("renamify").unwrap();
-    cmd.current_dir(temp_dir.path())
-        .args([
-            "plan",
-            "old_name",
-            "new_name",
-            "--no-rename-files",
-            "--dry-run",
-        ])
-        .assert()
-        .success()
-        .stdout(predicate::str::contains("test.rs"))
-        .stdout(predicate::str::contains("old_name.txt").not());
+    // A real edit moves the modification time
+    std::fs::write(&b, "old_name\n").unwrap();
+    assert_eq!(total_matches(&[]), "2");
 }
 
 #[test]
-fn test_apply_command_missing_plan() {
+fn test_doctor_command() {
     let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp_dir.path())
+        .args(["doctor", "--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["operation"], "doctor");
+    assert_eq!(json["success"], true);
+    let workspace = json["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == "workspace")
+        .unwrap();
+    assert_eq!(workspace["status"], "pass");
+    // Nothing is created while checking
+    assert!(!temp_dir.path().join(".renamify").exists());
+
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .args(["apply", "nonexistent.json"])
+        .arg("doctor")
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("Failed to read plan file"));
+        .success()
+        .stdout(predicate::str::contains(
+            "✓ workspace: .renamify is writable",
+        ));
 }
 
 #[test]
-fn test_apply_command_with_plan() {
+fn test_apply_strict_refuses_plan_with_unreadable_files() {
     let temp_dir = TempDir::new().unwrap();
-
-    // Create a test file with content to replace
     temp_dir
         .child("test.rs")
         .write_str("fn old_name() {}")
         .unwrap();
-
-    // First create a plan
     let renamify_dir = temp_dir.child(".renamify");
     renamify_dir.create_dir_all().unwrap();
 
-    // Create a minimal valid plan
+    // A plan whose scan could not read secret.rs
     let plan_json = r#"{
-        "id": "test123",
+        "id": "strict1",
         "created_at": "2024-01-01T00:00:00Z",
         "search": "old_name",
         "replace": "new_name",
@@ -540,21 +4659,96 @@ fn test_apply_command_with_plan() {
             "files_scanned": 1,
             "total_matches": 0,
             "matches_by_variant": {},
-            "files_with_matches": 0
+            "files_with_matches": 0,
+            "files_skipped": {
+                "binary": 0,
+                "excluded": 0,
+                "unreadable": 1,
+                "unreadable_files": [
+                    { "path": "secret.rs", "error": "Permission denied (os error 13)" }
+                ]
+            }
         },
         "version": "1.0.0"
     }"#;
+    renamify_dir
+        .child("plan.json")
+        .write_str(plan_json)
+        .unwrap();
 
-    let plan_path = renamify_dir.child("plan.json");
-    plan_path.write_str(plan_json).unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["apply", "--strict"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "1 file could not be read (--strict):\n  secret.rs: Permission denied",
+        ));
+    assert!(renamify_dir.child("plan.json").exists());
 
-    // Apply the plan (uses default plan.json)
     let mut cmd = Command::cargo_bin("renamify").unwrap();
     cmd.current_dir(temp_dir.path())
-        .arg("apply")
+        .args(["apply"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Applied"));
+        .stdout(predicate::str::contains(
+            "Warning: 1 file could not be read when planning",
+        ))
+        .stdout(predicate::str::contains(
+            "  secret.rs (Permission denied (os error 13))",
+        ));
+}
+
+#[test]
+fn test_apply_check_show_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("src/main.rs")
+        .write_str("use old_name;\n")
+        .unwrap();
+    temp_dir
+        .child("src/old_name/old_name.rs")
+        .write_str("fn old_name() {}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name", "--preview", "none"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["apply", "--check", "--show-tree"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "would apply cleanly (nothing was changed)",
+        ))
+        .stdout(predicate::str::contains(concat!(
+            ".\n",
+            "└── src/\n",
+            "    ├── main.rs (1 edit)\n",
+            "    └── old_name/ → new_name/\n",
+            "        └── old_name.rs → new_name.rs (1 edit)\n",
+        )));
+    // Nothing was applied, and the plan is still there to apply
+    temp_dir
+        .child("src/old_name/old_name.rs")
+        .assert("fn old_name() {}\n");
+    assert!(temp_dir.child(".renamify/plan.json").exists());
+
+    // A file changed since planning is reported, with exit code 1
+    temp_dir
+        .child("src/main.rs")
+        .write_str("use something_else;\n")
+        .unwrap();
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["apply", "--check", "--output", "json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""kind":"stale_match""#));
 }
 
 #[test]
@@ -762,6 +4956,54 @@ fn test_status_command_with_pending_plan() {
         .stdout(predicate::str::contains("test-plan-123"));
 }
 
+#[test]
+fn test_status_reports_plan_drift() {
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir
+        .child("main.rs")
+        .write_str("fn old_name() {}\nold_name();\nlet x = old_name;\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["plan", "old_name", "new_name"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Hunks: 3 applicable, 0 drifted, 0 already applied",
+        ));
+
+    // One hunk applied by hand, one edited away
+    temp_dir
+        .child("main.rs")
+        .write_str("fn new_name() {}\nold_name();\nlet x = other;\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Hunks: 1 applicable, 1 drifted, 1 already applied",
+        ))
+        .stdout(predicate::str::contains("  main.rs"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["status", "--output", "porcelain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pending_hunks\t1\t1\t1\n"))
+        .stdout(predicate::str::contains("drifted\tmain.rs\n"));
+}
+
 #[test]
 fn test_history_command_empty() {
     let temp_dir = TempDir::new().unwrap();
@@ -999,6 +5241,56 @@ fn test_init_command_not_in_git_repo() {
         .stderr(predicates::str::contains("Not in a git repository"));
 }
 
+#[test]
+fn test_init_command_in_hg_and_jj_repos() {
+    // Mercurial only reads the .hgignore at the top of the repository
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child(".hg").create_dir_all().unwrap();
+    temp_dir.child("src").create_dir_all().unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path().join("src"))
+        .arg("init")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(".hgignore"));
+    temp_dir
+        .child(".hgignore")
+        .assert("# Renamify workspace\nsyntax: glob\n.renamify\n");
+    temp_dir
+        .child("src/.gitignore")
+        .assert(predicate::path::missing());
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["init", "--check"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["init", "--local"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "hg repositories have no local exclude file",
+        ));
+
+    // jj reads .gitignore, and git's exclude file when colocated
+    let temp_dir = TempDir::new().unwrap();
+    temp_dir.child(".jj").create_dir_all().unwrap();
+    temp_dir.child(".git").create_dir_all().unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args(["init", "--local"])
+        .assert()
+        .success();
+    temp_dir
+        .child(".git/info/exclude")
+        .assert("# Renamify workspace\n.renamify/\n");
+}
+
 #[test]
 fn test_init_command_with_variations() {
     // Test that it detects existing patterns with variations
@@ -1819,3 +6111,33 @@ fn test_undo_with_multiple_files() {
     let txt_content = std::fs::read_to_string(temp_dir.path().join("old_func.txt")).unwrap();
     assert_eq!(txt_content, "Documentation for old_func");
 }
+
+#[test]
+fn test_completions_command() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("completions")
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_renamify_dynamic"));
+
+    // Without any history there are no IDs to offer, but the command still succeeds
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("__complete")
+        .arg("history-ids")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("__complete")
+        .arg("styles")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("screaming-snake"));
+}
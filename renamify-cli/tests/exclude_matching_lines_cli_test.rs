@@ -235,3 +235,42 @@ fn test_exclude_matching_lines_help_text() {
         .stdout(predicate::str::contains("--exclude-matching-lines"))
         .stdout(predicate::str::contains("regex"));
 }
+
+#[test]
+fn test_exclude_lines_in_file_and_inline_ignores() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("a.rs"),
+        "fn old_tool() {}\nlet a = old_tool(); // renamify:ignore-line\nlet b = old_tool();\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_tool",
+            "new_tool",
+            "--exclude-lines-in-file",
+            "a.rs:3",
+            "--dry-run",
+            "--output",
+            "json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"total_matches\":1"));
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .args([
+            "plan",
+            "old_tool",
+            "new_tool",
+            "--exclude-lines-in-file",
+            "a.rs",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("expected PATH:N or PATH:N-M"));
+}
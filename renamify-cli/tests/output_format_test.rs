@@ -123,7 +123,7 @@ fn test_search_output_json() {
     assert!(json.get("plan_id").is_some());
     assert!(json.get("search").is_some());
     assert_eq!(json["search"], "search_term");
-    assert_eq!(json["replace"], "");
+    assert!(json["replace"].is_null());
 }
 
 #[test]
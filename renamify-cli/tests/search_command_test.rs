@@ -60,6 +60,52 @@ fn test_search_command_json_output() {
         .stdout(predicates::str::contains("\"total_matches\""));
 }
 
+#[test]
+fn test_search_command_json_replace() {
+    let temp = TempDir::new().unwrap();
+    temp.child("hello_world.txt")
+        .write_str("hello_world HelloWorld")
+        .unwrap();
+    let search = |args: &[&str]| -> serde_json::Value {
+        let output = Command::cargo_bin("renamify")
+            .unwrap()
+            .current_dir(temp.path())
+            .args(["search", "hello_world", "--output", "json"])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&output).unwrap()
+    };
+
+    // Without --replace, the replacement is null and matches and paths
+    // leave theirs out rather than leaving it empty
+    let json = search(&[]);
+    assert_eq!(json["operation"], "search");
+    assert!(json["replace"].is_null());
+    let matches = json["plan"]["matches"].as_array().unwrap();
+    assert_eq!(matches[1]["variant"], "HelloWorld");
+    assert_eq!(matches[1]["style"], "Pascal");
+    assert!(matches[1].get("replace").is_none());
+    assert!(matches[1].get("line_after").is_none());
+    assert!(json["plan"]["paths"][0].get("new_path").is_none());
+
+    // With --replace, each match says what a rename would write
+    let json = search(&["--replace", "goodbye_world"]);
+    assert_eq!(json["operation"], "search");
+    assert_eq!(json["replace"], "goodbye_world");
+    let matches = json["plan"]["matches"].as_array().unwrap();
+    assert_eq!(matches[0]["replace"], "goodbye_world");
+    assert_eq!(matches[1]["replace"], "GoodbyeWorld");
+    assert!(json["plan"]["paths"][0]["new_path"]
+        .as_str()
+        .unwrap()
+        .ends_with("goodbye_world.txt"));
+    assert!(!temp.child(".renamify/plan.json").exists());
+}
+
 #[test]
 fn test_search_command_with_includes() {
     let temp = TempDir::new().unwrap();
@@ -320,3 +366,34 @@ fn test_search_command_case_variations() {
         .success()
         .stdout(predicates::str::contains("6 matches"));
 }
+
+#[test]
+fn test_search_command_github_output() {
+    let temp = TempDir::new().unwrap();
+
+    temp.child("src/lib.rs")
+        .write_str("fn main() {\n    let oldName = old_name();\n}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("renamify").unwrap();
+    let output = cmd
+        .current_dir(temp.path())
+        .args(["search", "old_name", "--output", "github"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines.len(),
+        2,
+        "Expected one annotation per match: {stdout}"
+    );
+    assert!(lines
+        .iter()
+        .all(|l| l.starts_with("::warning file=src/lib.rs,line=2,")));
+    assert!(stdout.contains("col=9,endColumn=16"));
+    assert!(stdout.contains("::Found 'old_name' (variant of 'old_name')"));
+    assert!(!stdout.contains("Search results"));
+}
@@ -222,37 +222,59 @@ The Rename-Tool-Based-Solution is working.
     std::env::set_current_dir(temp_dir.path()).unwrap();
 
     // Apply rename using the core rename operation directly
-    use renamify_core::rename_operation;
+    use renamify_core::operations::plan::{CountExpectations, LargeChangeThresholds};
+    use renamify_core::{rename_operation, CoercionMode, LiteralMode, MatchAnchor};
+    let large_thresholds = LargeChangeThresholds::default();
+    let expect = CountExpectations::default();
     rename_operation(
-        "rename_tool",
-        "smart_search_and_replace",
-        vec![], // paths (empty = current dir)
-        &[],    // include
-        &[],    // exclude
-        0,      // unrestricted_level
-        true,   // rename_files
-        true,   // rename_dirs
-        &[],    // exclude_styles
-        &[],    // include_styles
-        &[],    // only_styles
-        true,   // enable_plural_variants
-        false,  // ignore_ambiguous
-        &[],    // exclude_match
-        None,   // exclude_matching_lines
-        None,   // preview_format
-        false,  // commit
-        false,  // large
-        false,  // force_with_conflicts
-        false,  // rename_root
-        false,  // no_rename_root
-        false,  // dry_run
-        false,  // no_acronyms
-        &[],    // include_acronyms
-        &[],    // exclude_acronyms
-        &[],    // only_acronyms
-        None,   // atomic_config
-        true,   // auto_approve
-        true,   // use_color
+        "rename_tool",                            // search
+        "smart_search_and_replace",               // replace
+        vec![],                                   // paths (empty = current dir)
+        &[],                                      // include
+        &[],                                      // exclude
+        0,                                        // unrestricted_level
+        false,                                    // include_submodules
+        renamify_core::WalkOptions::default(),    // walk
+        false,                                    // paths_only
+        false,                                    // contents_only
+        true,                                     // rename_files
+        true,                                     // rename_dirs
+        &[],                                      // exclude_styles
+        &[],                                      // include_styles
+        &[],                                      // only_styles
+        true,                                     // enable_plural_variants
+        false,                                    // ignore_ambiguous
+        false,                                    // allow_case_collisions
+        LiteralMode::All,                         // literal_mode
+        MatchAnchor::Any,                         // match_anchor
+        CoercionMode::Auto,                       // coerce_separators
+        None,                                     // structured
+        None,                                     // profile
+        false,                                    // swap
+        &[],                                      // exclude_match
+        None,                                     // exclude_matching_lines
+        &[],                                      // exclude_lines_in_file
+        None,                                     // preview_format
+        None,                                     // commit
+        false,                                    // large
+        &large_thresholds,                        // large_thresholds
+        &expect,                                  // expect
+        false,                                    // force_with_conflicts
+        renamify_core::Durability::default(),     // durability
+        renamify_core::BackupStrategy::default(), // backup
+        false,                                    // rename_root
+        false,                                    // no_rename_root
+        false,                                    // dry_run
+        false,                                    // no_acronyms
+        &[],                                      // include_acronyms
+        &[],                                      // exclude_acronyms
+        &[],                                      // only_acronyms
+        None,                                     // acronym_rendering
+        None,                                     // case_mapping
+        None,                                     // working_dir
+        None,                                     // atomic_config
+        true,                                     // auto_approve
+        true,                                     // use_color
     )
     .unwrap();
 
@@ -263,7 +285,7 @@ The Rename-Tool-Based-Solution is working.
 
     // Undo the changes using the core undo operation directly
     use renamify_core::undo_operation;
-    undo_operation("latest", None).unwrap();
+    undo_operation("latest", false, None).unwrap();
 
     // Restore original directory
     std::env::set_current_dir(original_dir).unwrap();
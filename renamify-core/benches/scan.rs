@@ -0,0 +1,89 @@
+//! Scan benchmarks on a generated tree.
+//!
+//! `scan/shared_walk` plans content edits and renames from one walk of the
+//! tree. `scan/separate_walks` does the same work with a second walk for the
+//! renames, as scans did before rename planning reused the content walk, and
+//! `scan/contents_only` skips rename planning altogether. The cost of planning
+//! renames with a walk of their own is `plan_renames/own_walk`.
+//!
+//! Run with `cargo bench -p renamify-core --bench scan`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use renamify_core::case_model::generate_variant_map;
+use renamify_core::rename::plan_renames_with_search;
+use renamify_core::{scan_repository, PlanOptions};
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use tempfile::TempDir;
+
+const DIRS: usize = 200;
+const FILES_PER_DIR: usize = 50;
+
+/// A tree of `DIRS` directories of `FILES_PER_DIR` one-line files each, with
+/// the search term in a tenth of the file names and in every fifth file
+fn generate_tree(root: &Path) {
+    for dir in 0..DIRS {
+        let dir_path = root.join(format!("module_{dir}"));
+        fs::create_dir_all(&dir_path).unwrap();
+        for file in 0..FILES_PER_DIR {
+            let name = if file % 10 == 0 {
+                format!("old_name_{file}.rs")
+            } else {
+                format!("file_{file}.rs")
+            };
+            let content = if file % 5 == 0 {
+                "fn old_name() -> OldName { OldName::default() }\n"
+            } else {
+                "fn unrelated() -> Value { Value::default() }\n"
+            };
+            fs::write(dir_path.join(name), content).unwrap();
+        }
+    }
+}
+
+fn scan_benchmarks(c: &mut Criterion) {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    generate_tree(&root);
+
+    let options = PlanOptions::default();
+    let contents_only = PlanOptions {
+        contents_only: true,
+        ..PlanOptions::default()
+    };
+    let mapping = generate_variant_map("old_name", "new_name", None);
+
+    let mut group = c.benchmark_group("scan");
+    group.sample_size(30);
+    group.bench_function("shared_walk", |b| {
+        b.iter(|| black_box(scan_repository(&root, "old_name", "new_name", &options).unwrap()));
+    });
+    group.bench_function("separate_walks", |b| {
+        b.iter(|| {
+            black_box(scan_repository(&root, "old_name", "new_name", &contents_only).unwrap());
+            black_box(
+                plan_renames_with_search(&root, &mapping, &options, "old_name", "new_name")
+                    .unwrap(),
+            )
+        });
+    });
+    group.bench_function("contents_only", |b| {
+        b.iter(|| {
+            black_box(scan_repository(&root, "old_name", "new_name", &contents_only).unwrap())
+        });
+    });
+    group.finish();
+
+    c.bench_function("plan_renames/own_walk", |b| {
+        b.iter(|| {
+            black_box(
+                plan_renames_with_search(&root, &mapping, &options, "old_name", "new_name")
+                    .unwrap(),
+            )
+        });
+    });
+}
+
+criterion_group!(benches, scan_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use renamify_core::case_model::{detect_style, parse_to_tokens, to_style, Style, Token, TokenModel};
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+
+    // Any input: no panics, and no empty tokens
+    let model = parse_to_tokens(&input);
+    assert!(model.tokens.iter().all(|token| !token.text.is_empty()));
+    let _ = detect_style(&input);
+    for style in Style::all_styles() {
+        let _ = to_style(&model, style);
+    }
+
+    // The words of the input, as in tests/case_conversion_props.rs: lowercase,
+    // starting with two letters
+    let words: Vec<Token> = input
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.len() >= 2 && word.bytes().take(2).all(|b| b.is_ascii_alphabetic()))
+        .take(8)
+        .map(|word| Token::new(word.to_ascii_lowercase()))
+        .collect();
+    if words.is_empty() {
+        return;
+    }
+    let expected: Vec<&str> = words.iter().map(|token| token.text.as_str()).collect();
+    let model = TokenModel::new(words.clone());
+
+    for style in Style::all_styles() {
+        let written = to_style(&model, style);
+        let parsed = parse_to_tokens(&written);
+        // Converting what was written in a style to it again changes nothing
+        assert_eq!(to_style(&parsed, style), written, "{expected:?} as {style:?}");
+        // Styles with separators or case changes keep every word
+        if !matches!(style, Style::LowerFlat | Style::UpperFlat) {
+            let found: Vec<String> = parsed.tokens.iter().map(|t| t.text.to_lowercase()).collect();
+            assert_eq!(found, expected, "{written:?}");
+        }
+    }
+});
@@ -1,5 +1,9 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
+use ts_rs::TS;
 
 /// Default acronyms commonly used in development
 pub const DEFAULT_ACRONYMS: &[&str] = &[
@@ -18,6 +22,44 @@ pub fn get_default_acronym_set() -> &'static AcronymSet {
     DEFAULT_ACRONYM_SET.get_or_init(AcronymSet::default)
 }
 
+/// How known acronyms are written where `camelCase` and `PascalCase` start a new
+/// word. The first word of a `camelCase` identifier is always lowercase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "kebab-case")]
+pub enum AcronymRendering {
+    /// Acronyms typed in uppercase stay uppercase: `getAPIClient`
+    #[default]
+    PreserveUpper,
+    /// Acronyms are capitalized like any other word: `getApiClient`
+    Pascalize,
+    /// Acronyms are written in lowercase: `getapiClient`
+    Lower,
+}
+
+thread_local! {
+    static RENDERING: Cell<AcronymRendering> = const { Cell::new(AcronymRendering::PreserveUpper) };
+}
+
+/// Run `f` with `rendering` deciding how acronyms are written in `camelCase`
+/// and `PascalCase`. Only the current thread is affected, and the previous
+/// rendering is restored afterwards.
+pub fn with_rendering<R>(rendering: AcronymRendering, f: impl FnOnce() -> R) -> R {
+    struct Restore(AcronymRendering);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            RENDERING.set(self.0);
+        }
+    }
+
+    let _restore = Restore(RENDERING.replace(rendering));
+    f()
+}
+
+pub fn rendering() -> AcronymRendering {
+    RENDERING.get()
+}
+
 /// Trie node for efficient acronym matching
 #[derive(Debug, Clone, Default)]
 struct TrieNode {
@@ -48,7 +48,7 @@ impl CrossFileContextAnalyzer {
         // Check cache first
         if let Ok(cache) = CONTEXT_CACHE.lock() {
             if let Some(cached) = cache.get(&cache_key) {
-                if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+                if crate::debug::flags().ambiguity {
                     eprintln!(
                         "DEBUG CrossFileContextAnalyzer: Cache hit for {}",
                         cache_key
@@ -100,7 +100,7 @@ impl CrossFileContextAnalyzer {
             self.find_files_with_extension(project_root, include_hidden, file_extension)
         {
             for path in entries {
-                if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+                if crate::debug::flags().ambiguity {
                     eprintln!(
                         "DEBUG CrossFileContextAnalyzer: Checking file: {}",
                         path.display()
@@ -130,7 +130,7 @@ impl CrossFileContextAnalyzer {
     ) -> Result<Vec<PathBuf>, std::io::Error> {
         let mut files = Vec::new();
         self.find_files_recursive(root, include_hidden, extension, &mut files, 0)?;
-        if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+        if crate::debug::flags().ambiguity {
             eprintln!(
                 "DEBUG CrossFileContextAnalyzer: Found {} files with extension {}",
                 files.len(),
@@ -153,7 +153,7 @@ impl CrossFileContextAnalyzer {
             return Ok(());
         }
 
-        if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+        if crate::debug::flags().ambiguity {
             eprintln!(
                 "DEBUG CrossFileContextAnalyzer: Searching for files with extension {} in {}. Depth: {}, files.len(): {}",
                 extension, dir.display(), depth, files.len());
@@ -170,7 +170,7 @@ impl CrossFileContextAnalyzer {
                 || dir_name == "dist"
                 || dir_name == "vendor"
             {
-                if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+                if crate::debug::flags().ambiguity {
                     eprintln!("DEBUG CrossFileContextAnalyzer: Skipping dir");
                 }
                 return Ok(());
@@ -181,7 +181,7 @@ impl CrossFileContextAnalyzer {
             let entry = entry?;
             let path = entry.path();
 
-            if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+            if crate::debug::flags().ambiguity {
                 eprintln!("DEBUG CrossFileContextAnalyzer: entry: {}", path.display());
             }
 
@@ -300,7 +300,7 @@ mod tests {
         writeln!(f2, "const productName = 'Widget';").unwrap();
         f2.sync_all().ok();
 
-        if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+        if crate::debug::flags().ambiguity {
             eprintln!(
                 "DEBUG CrossFileContextAnalyzer: Created test files: {}, {}",
                 file1.display(),
@@ -1,64 +1,78 @@
 use crate::case_model::Style;
-use std::path::Path;
+use crate::language::Language;
 
 use super::languages;
+use super::languages::literals::LiteralSyntax;
 
 /// Language-specific heuristics for resolving case style ambiguity
 pub struct LanguageHeuristics;
 
 impl LanguageHeuristics {
-    /// Apply language-specific heuristics based on file extension and context
+    /// Apply language-specific heuristics based on the file's language and
+    /// context
     pub fn suggest_style(
-        file_path: &Path,
+        language: Language,
         preceding_context: &str,
         possible_styles: &[Style],
     ) -> Option<Style> {
-        let extension = file_path.extension()?.to_str()?;
         let context = preceding_context.trim();
 
-        if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+        if crate::debug::flags().ambiguity {
             eprintln!("DEBUG LanguageHeuristics: called suggest_style");
-            eprintln!("  file_path: {}", file_path.display());
-            eprintln!("  extension: {}", extension);
+            eprintln!("  language: {:?}", language);
             eprintln!("  preceding_context: {:?}", preceding_context);
             eprintln!("  context (trimmed): {:?}", context);
             eprintln!("  possible_styles: {:?}", possible_styles);
         }
 
-        match extension {
+        match language {
             // Programming languages
-            "rb" | "rake" | "gemspec" => languages::ruby::suggest_style(context, possible_styles),
-            "py" | "pyw" | "pyi" => languages::python::suggest_style(context, possible_styles),
-            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => {
-                languages::javascript::suggest_style(context, possible_styles)
-            },
-            "go" => languages::go::suggest_style(context, possible_styles),
-            "rs" => languages::rust::suggest_style(context, possible_styles),
-            "java" | "kt" | "kts" => languages::java::suggest_style(context, possible_styles),
-            "c" | "cpp" | "cc" | "cxx" | "h" | "hpp" | "hxx" => {
-                languages::c_cpp::suggest_style(context, possible_styles)
-            },
+            Language::Ruby => languages::ruby::suggest_style(context, possible_styles),
+            Language::Python => languages::python::suggest_style(context, possible_styles),
+            Language::JavaScript => languages::javascript::suggest_style(context, possible_styles),
+            Language::Go => languages::go::suggest_style(context, possible_styles),
+            Language::Rust => languages::rust::suggest_style(context, possible_styles),
+            Language::Java => languages::java::suggest_style(context, possible_styles),
+            Language::C => languages::c_cpp::suggest_style(context, possible_styles),
 
             // Web technologies
-            "css" | "scss" | "sass" | "less" | "styl" => {
-                languages::css::suggest_style(context, possible_styles)
-            },
-            "html" | "htm" | "xml" | "svg" | "vue" => {
-                languages::html::suggest_style(context, possible_styles)
-            },
+            Language::Css => languages::css::suggest_style(context, possible_styles),
+            Language::Html => languages::html::suggest_style(context, possible_styles),
 
             // Shell and scripting
-            "sh" | "bash" | "zsh" | "fish" | "ksh" => {
-                languages::shell::suggest_style(context, possible_styles)
-            },
+            Language::Shell => languages::shell::suggest_style(context, possible_styles),
 
             // Configuration files
-            "yml" | "yaml" => languages::yaml::suggest_style(context, possible_styles),
-            "json" | "jsonc" | "json5" | "toml" | "ini" | "cfg" | "conf" | "env" => {
+            Language::Yaml => languages::yaml::suggest_style(context, possible_styles),
+            Language::Json | Language::Toml | Language::Ini => {
                 languages::config::suggest_style(context, possible_styles)
             },
 
-            _ => None,
+            Language::Sql => None,
+        }
+    }
+
+    /// String literal and comment delimiters for code in `language`.
+    ///
+    /// Markup and config files return None: their strings are usually keys and
+    /// values rather than user-facing text.
+    pub const fn literal_syntax(language: Language) -> Option<&'static LiteralSyntax> {
+        match language {
+            Language::Ruby => Some(&languages::ruby::LITERAL_SYNTAX),
+            Language::Python => Some(&languages::python::LITERAL_SYNTAX),
+            Language::JavaScript => Some(&languages::javascript::LITERAL_SYNTAX),
+            Language::Go => Some(&languages::go::LITERAL_SYNTAX),
+            Language::Rust => Some(&languages::rust::LITERAL_SYNTAX),
+            Language::Java => Some(&languages::java::LITERAL_SYNTAX),
+            Language::C => Some(&languages::c_cpp::LITERAL_SYNTAX),
+            Language::Css => Some(&languages::css::LITERAL_SYNTAX),
+            Language::Shell => Some(&languages::shell::LITERAL_SYNTAX),
+            Language::Html
+            | Language::Yaml
+            | Language::Json
+            | Language::Toml
+            | Language::Ini
+            | Language::Sql => None,
         }
     }
 }
@@ -66,14 +80,20 @@ impl LanguageHeuristics {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+
+    fn suggest_style(path: &Path, context: &str, possible_styles: &[Style]) -> Option<Style> {
+        Language::from_path(path).and_then(|language| {
+            LanguageHeuristics::suggest_style(language, context, possible_styles)
+        })
+    }
 
     #[test]
     fn test_ruby_class_heuristic() {
         let path = PathBuf::from("test.rb");
         let possible_styles = vec![Style::Pascal, Style::Camel, Style::Snake];
 
-        let result = LanguageHeuristics::suggest_style(&path, "class ", &possible_styles);
+        let result = suggest_style(&path, "class ", &possible_styles);
         assert_eq!(result, Some(Style::Pascal));
     }
 
@@ -82,7 +102,7 @@ mod tests {
         let path = PathBuf::from("test.rb");
         let possible_styles = vec![Style::Snake, Style::Camel, Style::Kebab];
 
-        let result = LanguageHeuristics::suggest_style(&path, "def ", &possible_styles);
+        let result = suggest_style(&path, "def ", &possible_styles);
         assert_eq!(result, Some(Style::Snake));
     }
 
@@ -91,7 +111,7 @@ mod tests {
         let path = PathBuf::from("test.py");
         let possible_styles = vec![Style::Pascal, Style::Camel];
 
-        let result = LanguageHeuristics::suggest_style(&path, "class ", &possible_styles);
+        let result = suggest_style(&path, "class ", &possible_styles);
         assert_eq!(result, Some(Style::Pascal));
     }
 
@@ -100,7 +120,7 @@ mod tests {
         let path = PathBuf::from("test.js");
         let possible_styles = vec![Style::Camel, Style::Snake];
 
-        let result = LanguageHeuristics::suggest_style(&path, "function ", &possible_styles);
+        let result = suggest_style(&path, "function ", &possible_styles);
         assert_eq!(result, Some(Style::Camel));
     }
 
@@ -109,7 +129,7 @@ mod tests {
         let path = PathBuf::from("test.py");
         let possible_styles = vec![Style::Kebab]; // Python doesn't use kebab
 
-        let result = LanguageHeuristics::suggest_style(&path, "def ", &possible_styles);
+        let result = suggest_style(&path, "def ", &possible_styles);
         assert_eq!(result, None);
     }
 
@@ -118,7 +138,7 @@ mod tests {
         let path = PathBuf::from("test.xyz");
         let possible_styles = vec![Style::Snake, Style::Camel];
 
-        let result = LanguageHeuristics::suggest_style(&path, "function ", &possible_styles);
+        let result = suggest_style(&path, "function ", &possible_styles);
         assert_eq!(result, None);
     }
 
@@ -128,7 +148,7 @@ mod tests {
         let possible_styles = vec![Style::ScreamingSnake, Style::Snake];
 
         // Note: "export " gets trimmed to "export" in suggest_style
-        let result = LanguageHeuristics::suggest_style(&path, "export ", &possible_styles);
+        let result = suggest_style(&path, "export ", &possible_styles);
         assert_eq!(result, Some(Style::ScreamingSnake));
     }
 
@@ -137,7 +157,7 @@ mod tests {
         let path = PathBuf::from("config.yml");
         let possible_styles = vec![Style::Snake, Style::Camel];
 
-        let result = LanguageHeuristics::suggest_style(&path, "key:", &possible_styles);
+        let result = suggest_style(&path, "key:", &possible_styles);
         assert_eq!(result, Some(Style::Snake));
     }
 }
@@ -1,5 +1,15 @@
+use super::literals::{LiteralSyntax, StringSyntax};
 use crate::case_model::Style;
 
+/// C/C++ string literal and comment delimiters
+pub const LITERAL_SYNTAX: LiteralSyntax = LiteralSyntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[StringSyntax::new("\"", "\""), StringSyntax::new("'", "'")],
+    comment_needs_space: false,
+    char_literals: false,
+};
+
 /// C/C++ language-specific heuristics for resolving case style ambiguity
 pub fn suggest_style(context: &str, possible_styles: &[Style]) -> Option<Style> {
     // Check typedef FIRST (more specific)
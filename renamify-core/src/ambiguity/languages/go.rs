@@ -1,5 +1,19 @@
+use super::literals::{LiteralSyntax, StringSyntax};
 use crate::case_model::Style;
 
+/// Go string literal and comment delimiters
+pub const LITERAL_SYNTAX: LiteralSyntax = LiteralSyntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[
+        StringSyntax::new("\"", "\""),
+        StringSyntax::new("'", "'"),
+        StringSyntax::new("`", "`").raw().multiline(),
+    ],
+    comment_needs_space: false,
+    char_literals: false,
+};
+
 /// Go language-specific heuristics for resolving case style ambiguity
 pub fn suggest_style(context: &str, possible_styles: &[Style]) -> Option<Style> {
     // Check if this is likely an exported identifier (starts with uppercase)
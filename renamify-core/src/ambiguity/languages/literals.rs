@@ -0,0 +1,356 @@
+//! Lightweight lexing of string literals and comments.
+//!
+//! This is a heuristic scanner, not a parser: it knows each language's quote,
+//! comment and interpolation delimiters and walks the file once, recording the
+//! byte ranges that are inside string literals or comments. Interpolated code
+//! such as `${name}` in a JavaScript template literal is left as code.
+
+use crate::ambiguity::language_heuristics::LanguageHeuristics;
+use crate::language::Language;
+use std::path::Path;
+
+/// Where a byte offset sits in a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    String,
+    Comment,
+}
+
+/// Quote, comment and interpolation delimiters of a language
+#[derive(Debug)]
+pub struct LiteralSyntax {
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+    pub strings: &'static [StringSyntax],
+    /// Line comments only start at the beginning of a line or after whitespace
+    /// (shell `#`, which also appears in `$#` and `${#var}`)
+    pub comment_needs_space: bool,
+    /// `'` starts a character literal only when it closes one character later,
+    /// otherwise it is a lifetime or label (Rust)
+    pub char_literals: bool,
+}
+
+/// One kind of string literal
+#[derive(Debug)]
+pub struct StringSyntax {
+    pub open: &'static str,
+    pub close: &'static str,
+    /// A backslash escapes the next character
+    pub escapes: bool,
+    /// The literal may span several lines
+    pub multiline: bool,
+    pub interpolation: Option<Interpolation>,
+}
+
+/// Code embedded in a string literal, such as `${name}` or `#{name}`
+#[derive(Debug)]
+pub struct Interpolation {
+    pub open: &'static str,
+    /// Arbitrary nested code with balanced braces (`${a + b}`); otherwise only
+    /// simple placeholders such as Rust's `{name}` or `{name:?}` count
+    pub nested: bool,
+}
+
+impl StringSyntax {
+    #[must_use]
+    pub const fn new(open: &'static str, close: &'static str) -> Self {
+        Self {
+            open,
+            close,
+            escapes: true,
+            multiline: false,
+            interpolation: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn raw(mut self) -> Self {
+        self.escapes = false;
+        self
+    }
+
+    #[must_use]
+    pub const fn multiline(mut self) -> Self {
+        self.multiline = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn interpolated(mut self, open: &'static str, nested: bool) -> Self {
+        self.interpolation = Some(Interpolation { open, nested });
+        self
+    }
+}
+
+/// Byte ranges of a file that are inside string literals or comments
+#[derive(Debug, Default)]
+pub struct LiteralRegions {
+    regions: Vec<(usize, usize, RegionKind)>,
+}
+
+impl LiteralRegions {
+    /// Scan `content` if `path` holds code in a language with known string
+    /// and comment syntax
+    pub fn scan_file(path: &Path, content: &[u8]) -> Option<Self> {
+        let language = Language::detect(path, std::str::from_utf8(content).ok())?;
+        LanguageHeuristics::literal_syntax(language).map(|syntax| Self::scan(content, syntax))
+    }
+
+    pub fn scan(content: &[u8], syntax: &LiteralSyntax) -> Self {
+        let mut regions = Vec::new();
+        let mut pos = 0;
+
+        while pos < content.len() {
+            if let Some(end) = line_comment_end(content, pos, syntax) {
+                regions.push((pos, end, RegionKind::Comment));
+                pos = end;
+            } else if let Some((_, close)) = syntax
+                .block_comments
+                .iter()
+                .find(|(open, _)| content[pos..].starts_with(open.as_bytes()))
+            {
+                let end = find(content, pos + 1, close.as_bytes())
+                    .map_or(content.len(), |found| found + close.len());
+                regions.push((pos, end, RegionKind::Comment));
+                pos = end;
+            } else if syntax.char_literals && content[pos] == b'\'' {
+                pos = char_literal_end(content, pos).unwrap_or(pos + 1);
+            } else if let Some(string) = string_at(content, pos, syntax) {
+                pos = scan_string(content, pos, string, &mut regions);
+            } else {
+                pos += 1;
+            }
+        }
+
+        Self { regions }
+    }
+
+    /// The region containing `offset`, or None if it is in code
+    pub fn kind_at(&self, offset: usize) -> Option<RegionKind> {
+        let idx = self
+            .regions
+            .partition_point(|(start, _, _)| *start <= offset);
+        let (start, end, kind) = *self.regions.get(idx.checked_sub(1)?)?;
+        (start <= offset && offset < end).then_some(kind)
+    }
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+fn find(content: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    content
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|found| from + found)
+}
+
+fn line_comment_end(content: &[u8], pos: usize, syntax: &LiteralSyntax) -> Option<usize> {
+    let starts = syntax
+        .line_comments
+        .iter()
+        .any(|prefix| content[pos..].starts_with(prefix.as_bytes()));
+    if !starts || (syntax.comment_needs_space && pos > 0 && !content[pos - 1].is_ascii_whitespace())
+    {
+        return None;
+    }
+    Some(find(content, pos, b"\n").unwrap_or(content.len()))
+}
+
+/// The longest string opener at `pos`. Prefixed openers such as `f"` or `r#"`
+/// only count at the start of a token.
+fn string_at<'a>(
+    content: &[u8],
+    pos: usize,
+    syntax: &'a LiteralSyntax,
+) -> Option<&'a StringSyntax> {
+    syntax
+        .strings
+        .iter()
+        .filter(|string| content[pos..].starts_with(string.open.as_bytes()))
+        .filter(|string| {
+            !is_ident_byte(string.open.as_bytes()[0])
+                || pos == 0
+                || !is_ident_byte(content[pos - 1])
+        })
+        .max_by_key(|string| string.open.len())
+}
+
+/// End of a character literal such as `'a'` or `'\n'`, or None for a lifetime
+fn char_literal_end(content: &[u8], pos: usize) -> Option<usize> {
+    let rest = content.get(pos + 1..)?;
+    let body = if rest.first() == Some(&b'\\') {
+        rest.iter().skip(2).position(|&byte| byte == b'\'')? + 2
+    } else {
+        // Width of the UTF-8 character from its first byte
+        let len = match *rest.first()? {
+            byte if byte < 0x80 => 1,
+            byte if byte >= 0xF0 => 4,
+            byte if byte >= 0xE0 => 3,
+            _ => 2,
+        };
+        if rest.get(len) != Some(&b'\'') {
+            return None;
+        }
+        len
+    };
+    Some(pos + 1 + body + 1)
+}
+
+/// Scan a string literal starting at `pos`, recording its text (but not its
+/// interpolated code), and return the offset just past it
+fn scan_string(
+    content: &[u8],
+    pos: usize,
+    string: &StringSyntax,
+    regions: &mut Vec<(usize, usize, RegionKind)>,
+) -> usize {
+    let mut segment_start = pos;
+    let mut i = pos + string.open.len();
+
+    while i < content.len() {
+        if string.escapes && content[i] == b'\\' {
+            i += 2;
+            continue;
+        }
+        if content[i..].starts_with(string.close.as_bytes()) {
+            i += string.close.len();
+            break;
+        }
+        if !string.multiline && content[i] == b'\n' {
+            break;
+        }
+        if let Some(interpolation) = &string.interpolation {
+            if content[i..].starts_with(interpolation.open.as_bytes()) {
+                if let Some(end) = interpolation_end(content, i, interpolation) {
+                    regions.push((segment_start, i, RegionKind::String));
+                    segment_start = end;
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    let end = i.min(content.len());
+    if end > segment_start {
+        regions.push((segment_start, end, RegionKind::String));
+    }
+    end
+}
+
+fn interpolation_end(content: &[u8], pos: usize, interpolation: &Interpolation) -> Option<usize> {
+    let body_start = pos + interpolation.open.len();
+    if interpolation.nested {
+        let mut depth = 1;
+        for (offset, &byte) in content.get(body_start..)?.iter().enumerate() {
+            match byte {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(body_start + offset + 1);
+                    }
+                },
+                _ => {},
+            }
+        }
+        None
+    } else {
+        // Simple placeholders never contain quotes, braces or newlines
+        let body = content.get(body_start..)?;
+        let len = body
+            .iter()
+            .position(|&byte| matches!(byte, b'}' | b'{' | b'"' | b'\n'))?;
+        (body[len] == b'}' && body.first().is_some_and(|&byte| is_ident_byte(byte)))
+            .then_some(body_start + len + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ambiguity::languages::{javascript, python, rust, shell};
+
+    fn kinds(source: &str, syntax: &LiteralSyntax, word: &str) -> Vec<Option<RegionKind>> {
+        let regions = LiteralRegions::scan(source.as_bytes(), syntax);
+        source
+            .match_indices(word)
+            .map(|(offset, _)| regions.kind_at(offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_strings_comments_and_format_args() {
+        let source = concat!(
+            "fn user_name<'a>(x: &'a str) -> char {\n",
+            "    // user_name helper\n",
+            "    println!(\"user_name is {user_name}\");\n",
+            "    let raw = r#\"say \"user_name\"\"#;\n",
+            "    let quote = '\"';\n",
+            "    user_name\n",
+            "}\n",
+        );
+        assert_eq!(
+            kinds(source, &rust::LITERAL_SYNTAX, "user_name"),
+            vec![
+                None,
+                Some(RegionKind::Comment),
+                Some(RegionKind::String),
+                None,
+                Some(RegionKind::String),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_javascript_template_interpolation() {
+        let source = "const msg = `Hello ${userName}, userName`; // userName\nuserName('x');";
+        assert_eq!(
+            kinds(source, &javascript::LITERAL_SYNTAX, "userName"),
+            vec![
+                None,
+                Some(RegionKind::String),
+                Some(RegionKind::Comment),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_docstrings_and_f_strings() {
+        let source = "def user_name():\n    \"\"\"Return user_name.\"\"\"\n    return f\"{user_name} user_name\"  # user_name\n";
+        assert_eq!(
+            kinds(source, &python::LITERAL_SYNTAX, "user_name"),
+            vec![
+                None,
+                Some(RegionKind::String),
+                None,
+                Some(RegionKind::String),
+                Some(RegionKind::Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_hash_needs_space() {
+        let source = "echo ${#user_name} \"user_name\" # user_name\n";
+        assert_eq!(
+            kinds(source, &shell::LITERAL_SYNTAX, "user_name"),
+            vec![None, Some(RegionKind::String), Some(RegionKind::Comment)]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_string_ends_at_newline() {
+        let source = "let s = \"user_name\nuser_name";
+        assert_eq!(
+            kinds(source, &javascript::LITERAL_SYNTAX, "user_name"),
+            vec![Some(RegionKind::String), None]
+        );
+    }
+}
@@ -5,6 +5,7 @@ pub mod go;
 pub mod html;
 pub mod java;
 pub mod javascript;
+pub mod literals;
 pub mod python;
 pub mod ruby;
 pub mod rust;
@@ -1,5 +1,28 @@
+use super::literals::{LiteralSyntax, StringSyntax};
 use crate::case_model::Style;
 
+/// Python string literal and comment delimiters
+pub const LITERAL_SYNTAX: LiteralSyntax = LiteralSyntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    strings: &[
+        StringSyntax::new("\"", "\""),
+        StringSyntax::new("'", "'"),
+        StringSyntax::new("\"\"\"", "\"\"\"").multiline(),
+        StringSyntax::new("'''", "'''").multiline(),
+        StringSyntax::new("f\"", "\"").interpolated("{", true),
+        StringSyntax::new("f'", "'").interpolated("{", true),
+        StringSyntax::new("f\"\"\"", "\"\"\"")
+            .multiline()
+            .interpolated("{", true),
+        StringSyntax::new("f'''", "'''")
+            .multiline()
+            .interpolated("{", true),
+    ],
+    comment_needs_space: false,
+    char_literals: false,
+};
+
 /// Python language-specific heuristics for resolving case style ambiguity
 pub fn suggest_style(context: &str, possible_styles: &[Style]) -> Option<Style> {
     if context.ends_with("class") {
@@ -1,5 +1,23 @@
+use super::literals::{LiteralSyntax, StringSyntax};
 use crate::case_model::Style;
 
+/// Ruby string literal and comment delimiters
+pub const LITERAL_SYNTAX: LiteralSyntax = LiteralSyntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    strings: &[
+        StringSyntax::new("\"", "\"")
+            .multiline()
+            .interpolated("#{", true),
+        StringSyntax::new("'", "'").multiline(),
+        StringSyntax::new("`", "`")
+            .multiline()
+            .interpolated("#{", true),
+    ],
+    comment_needs_space: false,
+    char_literals: false,
+};
+
 /// Ruby language-specific heuristics for resolving case style ambiguity
 pub fn suggest_style(context: &str, possible_styles: &[Style]) -> Option<Style> {
     if context.ends_with("class") || context.ends_with("module") {
@@ -1,5 +1,24 @@
+use super::literals::{LiteralSyntax, StringSyntax};
 use crate::case_model::Style;
 
+/// Rust string literal and comment delimiters
+pub const LITERAL_SYNTAX: LiteralSyntax = LiteralSyntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[
+        StringSyntax::new("\"", "\"")
+            .multiline()
+            .interpolated("{", false),
+        StringSyntax::new("r\"", "\"").raw().multiline(),
+        StringSyntax::new("r#\"", "\"#").raw().multiline(),
+        StringSyntax::new("r##\"", "\"##").raw().multiline(),
+        StringSyntax::new("br\"", "\"").raw().multiline(),
+        StringSyntax::new("br#\"", "\"#").raw().multiline(),
+    ],
+    comment_needs_space: false,
+    char_literals: true,
+};
+
 /// Rust language-specific heuristics for resolving case style ambiguity
 pub fn suggest_style(context: &str, possible_styles: &[Style]) -> Option<Style> {
     if context.ends_with("struct")
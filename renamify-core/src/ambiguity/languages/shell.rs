@@ -1,5 +1,20 @@
+use super::literals::{LiteralSyntax, StringSyntax};
 use crate::case_model::Style;
 
+/// Shell string literal and comment delimiters
+pub const LITERAL_SYNTAX: LiteralSyntax = LiteralSyntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    strings: &[
+        StringSyntax::new("\"", "\"")
+            .multiline()
+            .interpolated("${", true),
+        StringSyntax::new("'", "'").raw().multiline(),
+    ],
+    comment_needs_space: true,
+    char_literals: false,
+};
+
 /// Shell script language-specific heuristics for resolving case style ambiguity
 pub fn suggest_style(context: &str, possible_styles: &[Style]) -> Option<Style> {
     if context.ends_with("export") || context.contains("export ") {
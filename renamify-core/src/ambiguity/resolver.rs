@@ -4,6 +4,7 @@ use super::{
 };
 use crate::case_constraints::filter_compatible_styles;
 use crate::case_model::{detect_style, parse_to_tokens, to_style, Style};
+use crate::language::Language;
 
 /// Context for resolving ambiguity
 #[derive(Debug, Clone, Default)]
@@ -78,7 +79,7 @@ impl AmbiguityResolver {
         context: &AmbiguityContext,
         replacement_possible_styles: Option<&[Style]>,
     ) -> ResolvedStyle {
-        if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+        if crate::debug::flags().ambiguity {
             eprintln!(
                 "=== Resolving ambiguity for '{}' -> '{}' ===",
                 matched_text, replacement_text
@@ -90,7 +91,7 @@ impl AmbiguityResolver {
         // First check if it's even ambiguous
         if !crate::ambiguity::is_ambiguous(matched_text, &Style::all_styles()) {
             if let Some(style) = detect_style(matched_text) {
-                if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+                if crate::debug::flags().ambiguity {
                     eprintln!("  -> Not ambiguous, detected style: {:?}", style);
                 }
                 return ResolvedStyle {
@@ -119,7 +120,7 @@ impl AmbiguityResolver {
             );
         }
 
-        if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+        if crate::debug::flags().ambiguity {
             eprintln!(
                 "  Possible styles (before constraints): {:?}",
                 possible_styles
@@ -134,7 +135,7 @@ impl AmbiguityResolver {
         if let Some(resolved) =
             Self::try_language_heuristics(matched_text, context, &constrained_styles)
         {
-            if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+            if crate::debug::flags().ambiguity {
                 eprintln!("  -> Resolved by language heuristics: {:?}", resolved.style);
             }
             return resolved;
@@ -142,7 +143,7 @@ impl AmbiguityResolver {
 
         // Level 2: File context analysis
         if let Some(resolved) = self.try_file_context(matched_text, context, &constrained_styles) {
-            if std::env::var("RENAMIFY_DEBUG_AMBIGUITY").is_ok() {
+            if crate::debug::flags().ambiguity {
                 eprintln!("  -> Resolved by file context: {:?}", resolved.style);
             }
             return resolved;
@@ -180,8 +181,8 @@ impl AmbiguityResolver {
             ""
         };
 
-        if let Some(style) =
-            LanguageHeuristics::suggest_style(file_path, preceding, possible_styles)
+        let language = Language::detect(file_path, context.file_content.as_deref())?;
+        if let Some(style) = LanguageHeuristics::suggest_style(language, preceding, possible_styles)
         {
             return Some(ResolvedStyle {
                 style,
@@ -1,43 +1,131 @@
-use crate::history::{create_history_entry, History};
-use crate::scanner::Plan;
+use crate::backup::{BackupStrategy, GitBlobs};
+use crate::content_cache::ContentCache;
+use crate::error::RenamifyError;
+use crate::history::{create_history_entry, History, HistoryEntry};
+use crate::mapping::RenameMapping;
+use crate::observer::{check_cancelled, OperationObserver};
+use crate::scanner::{Plan, Stats};
+use crate::vcs::{CommitOptions, Vcs};
 use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Write as FmtWrite;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use ts_rs::TS;
 
 /// Options for applying a renaming plan
 #[derive(Debug, Clone)]
 pub struct ApplyOptions {
-    /// Create backups before applying changes
-    pub create_backups: bool,
+    /// What to keep so that undo can restore edited files
+    pub backup: BackupStrategy,
     /// Path to backup directory
     pub backup_dir: PathBuf,
     /// Commit changes to git after successful apply
     pub commit: bool,
+    /// Message template and flags for the commit
+    pub commit_options: CommitOptions,
+    /// Stage the changes in git after applying
+    pub stage: bool,
     /// Force apply even with conflicts
     pub force: bool,
     /// Skip symlinks
     pub skip_symlinks: bool,
     /// Log file path
     pub log_file: Option<PathBuf>,
+    /// Directory the repository is found from for staging and committing,
+    /// instead of the current directory
+    pub working_dir: Option<PathBuf>,
+    /// When edited files are synced to disk
+    pub durability: Durability,
 }
 
 impl Default for ApplyOptions {
     fn default() -> Self {
         Self {
-            create_backups: true,
+            backup: BackupStrategy::default(),
             backup_dir: PathBuf::from(".renamify/backups"),
             commit: false,
+            commit_options: CommitOptions::default(),
+            stage: false,
             force: false,
             skip_symlinks: true,
             log_file: Some(PathBuf::from(".renamify/apply.log")),
+            working_dir: None,
+            durability: Durability::default(),
         }
     }
 }
 
+impl ApplyOptions {
+    /// The working directory, or the current directory if none was given
+    pub(crate) fn working_dir(&self) -> Result<PathBuf> {
+        match &self.working_dir {
+            Some(working_dir) => Ok(working_dir.clone()),
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+}
+
+/// How much of a plan is applied as a single all-or-nothing unit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransactionScope {
+    /// The whole plan is one transaction
+    #[default]
+    Plan,
+    /// Each directory is its own transaction with its own backups, log and history entry
+    Dir,
+}
+
+/// When apply syncs edited files to disk, trading crash safety for speed
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema, TS,
+)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum Durability {
+    /// Sync every file and its directory as soon as it is written
+    #[default]
+    File,
+    /// Write files in batches, syncing each batch together before renaming it
+    /// into place, and each touched directory once per batch
+    Batch,
+    /// Sync nothing until every file is written, then sync them all at once
+    End,
+}
+
+impl std::fmt::Display for Durability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::File => "file",
+            Self::Batch => "batch",
+            Self::End => "end",
+        })
+    }
+}
+
+impl std::str::FromStr for Durability {
+    type Err = String;
+
+    /// Parse `file`, `batch` or `end` as accepted by `--durability`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(Self::File),
+            "batch" => Ok(Self::Batch),
+            "end" => Ok(Self::End),
+            _ => Err(format!(
+                "Invalid durability '{s}', expected file, batch or end"
+            )),
+        }
+    }
+}
+
+/// Number of files written before a `Durability::Batch` batch is synced and
+/// renamed into place
+const DURABILITY_BATCH_SIZE: usize = 256;
+
 /// Tracks the state of an apply operation
 pub struct ApplyState {
     content_edits_applied: Vec<PathBuf>,
@@ -290,7 +378,7 @@ fn split_preserving_newlines(s: &str) -> Vec<&str> {
 
 /// Replace diffy's generic patch headers with actual file paths
 /// Converts absolute paths to relative paths for better portability
-fn replace_patch_headers(patch_str: &str, from_path: &Path, to_path: &Path) -> String {
+pub(crate) fn replace_patch_headers(patch_str: &str, from_path: &Path, to_path: &Path) -> String {
     let mut result = String::new();
     let lines = split_preserving_newlines(patch_str);
 
@@ -378,19 +466,12 @@ pub fn calculate_checksum(path: &Path) -> Result<String> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
-/// Apply content edits to a file atomically without creating backup
-fn apply_content_edits_with_content(
+/// Apply replacements to file content in memory, validating each one against the original
+pub(crate) fn apply_replacements(
     path: &Path,
     original_content: &str,
     replacements: &[(String, String, usize, usize)],
-    state: &mut ApplyState,
-) -> Result<()> {
-    state.log(&format!(
-        "Applying {} edits to {}",
-        replacements.len(),
-        path.display()
-    ))?;
-
+) -> Result<String> {
     // Apply replacements (in reverse order to maintain positions)
     let mut modified = original_content.to_string();
 
@@ -398,58 +479,184 @@ fn apply_content_edits_with_content(
         // Validate the replacement matches expected content
         let actual = &original_content[*start..*end];
         if actual != before {
-            return Err(anyhow!(
+            return Err(RenamifyError::Conflict(format!(
                 "Content mismatch in {}: expected '{}', found '{}'",
                 path.display(),
                 before,
                 actual
-            ));
+            ))
+            .into());
         }
 
         // Apply the replacement
         modified.replace_range(*start..*end, after);
     }
 
-    // Write to temporary file in the same directory (for atomicity)
-    let temp_path = path.with_extension(format!("{}.renamify.tmp", std::process::id()));
+    Ok(modified)
+}
 
-    // Get original file permissions before writing
-    let original_metadata = fs::metadata(path)
-        .with_context(|| format!("Failed to get metadata for {}", path.display()))?;
-    let original_permissions = original_metadata.permissions();
+/// Writes edited files to temp files and renames them into place, syncing
+/// them to disk as often as the `Durability` asks
+struct ContentWriter {
+    durability: Durability,
+    /// Temp files written but not yet renamed over their targets, with their targets
+    pending: Vec<(PathBuf, PathBuf)>,
+    /// Files renamed into place but not yet synced, for `Durability::End`
+    unsynced: Vec<PathBuf>,
+}
 
-    {
-        let mut temp_file = File::create(&temp_path)
-            .with_context(|| format!("Failed to create temp file {}", temp_path.display()))?;
-        temp_file.write_all(modified.as_bytes())?;
-        temp_file.sync_all()?; // fsync
+impl ContentWriter {
+    const fn new(durability: Durability) -> Self {
+        Self {
+            durability,
+            pending: Vec::new(),
+            unsynced: Vec::new(),
+        }
     }
 
-    // Set the same permissions on the temp file before renaming
-    fs::set_permissions(&temp_path, original_permissions)?;
+    /// Apply the edits to a file. Depending on the durability the new content
+    /// may only be in a temp file until `flush` or `finish`.
+    fn write(
+        &mut self,
+        path: &Path,
+        original_content: &str,
+        replacements: &[(String, String, usize, usize)],
+        state: &mut ApplyState,
+    ) -> Result<()> {
+        state.log(&format!(
+            "Applying {} edits to {}",
+            replacements.len(),
+            path.display()
+        ))?;
 
-    // Atomic rename
-    fs::rename(&temp_path, path)
-        .with_context(|| format!("Failed to atomically replace {}", path.display()))?;
+        let modified = apply_replacements(path, original_content, replacements)?;
 
-    // Sync parent directory on Unix
-    #[cfg(unix)]
-    {
-        if let Some(parent) = path.parent() {
-            if parent.as_os_str().is_empty() {
-                // For files in the current directory, sync "."
-                let dir = File::open(".")?;
-                dir.sync_all()?;
-            } else {
-                let dir = File::open(parent)?;
-                dir.sync_all()?;
+        // A file reached through a followed symlink is edited in place of its
+        // target, so the link itself survives the atomic rename below
+        let path = if fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) {
+            fs::canonicalize(path)
+                .with_context(|| format!("Failed to resolve symlink {}", path.display()))?
+        } else {
+            path.to_path_buf()
+        };
+
+        // Write to temporary file in the same directory (for atomicity)
+        let temp_path = path.with_extension(format!("{}.renamify.tmp", std::process::id()));
+
+        // Get original file permissions before writing
+        let original_metadata = fs::metadata(&path)
+            .with_context(|| format!("Failed to get metadata for {}", path.display()))?;
+        let original_permissions = original_metadata.permissions();
+
+        {
+            let mut temp_file = File::create(&temp_path)
+                .with_context(|| format!("Failed to create temp file {}", temp_path.display()))?;
+            temp_file.write_all(modified.as_bytes())?;
+            if self.durability == Durability::File {
+                temp_file.sync_all()?; // fsync
             }
         }
+
+        // Set the same permissions on the temp file before renaming
+        fs::set_permissions(&temp_path, original_permissions)?;
+
+        match self.durability {
+            Durability::File => {
+                replace_with_temp(&temp_path, &path, state)?;
+                sync_dirs([path.as_path()])?;
+            },
+            Durability::Batch => {
+                self.pending.push((temp_path, path));
+                if self.pending.len() >= DURABILITY_BATCH_SIZE {
+                    self.flush(state)?;
+                }
+            },
+            Durability::End => {
+                replace_with_temp(&temp_path, &path, state)?;
+                self.unsynced.push(path);
+            },
+        }
+        Ok(())
+    }
+
+    /// Sync the pending batch and rename it into place
+    fn flush(&mut self, state: &mut ApplyState) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        state.log(&format!("Syncing a batch of {} files", self.pending.len()))?;
+        sync_files(self.pending.iter().map(|(temp, _)| temp.as_path()))?;
+        let batch = std::mem::take(&mut self.pending);
+        for (i, (temp, path)) in batch.iter().enumerate() {
+            if let Err(e) = replace_with_temp(temp, path, state) {
+                // Leave nothing of the rest of the batch behind
+                self.pending = batch[i..].to_vec();
+                return Err(e);
+            }
+        }
+        sync_dirs(batch.iter().map(|(_, path)| path.as_path()))
+    }
+
+    /// Put every written file in place and on disk
+    fn finish(&mut self, state: &mut ApplyState) -> Result<()> {
+        self.flush(state)?;
+        if !self.unsynced.is_empty() {
+            state.log(&format!("Syncing {} edited files", self.unsynced.len()))?;
+            let unsynced = std::mem::take(&mut self.unsynced);
+            sync_files(unsynced.iter().map(PathBuf::as_path))?;
+            sync_dirs(unsynced.iter().map(PathBuf::as_path))?;
+        }
+        Ok(())
     }
 
+    /// Remove temp files that were never renamed into place, after a failure
+    fn discard(&mut self) {
+        for (temp, _) in self.pending.drain(..) {
+            let _ = fs::remove_file(temp);
+        }
+    }
+}
+
+/// Atomically rename a temp file over the file it replaces
+fn replace_with_temp(temp_path: &Path, path: &Path, state: &mut ApplyState) -> Result<()> {
+    fs::rename(temp_path, path)
+        .with_context(|| format!("Failed to atomically replace {}", path.display()))?;
     state.content_edits_applied.push(path.to_path_buf());
     state.log(&format!("Successfully applied edits to {}", path.display()))?;
+    Ok(())
+}
 
+/// Sync files to disk, in parallel so slow network filesystems overlap the waits
+fn sync_files<'a>(paths: impl Iterator<Item = &'a Path>) -> Result<()> {
+    use rayon::prelude::*;
+    let paths: Vec<&Path> = paths.collect();
+    paths.par_iter().try_for_each(|path| {
+        File::open(path)
+            .and_then(|file| file.sync_all())
+            .with_context(|| format!("Failed to sync {}", path.display()))
+    })
+}
+
+/// Sync the parent directory of each file once, so the renames are on disk
+#[cfg(unix)]
+fn sync_dirs<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Result<()> {
+    let parents: std::collections::BTreeSet<&Path> = paths
+        .into_iter()
+        .map(|path| match path.parent() {
+            // For files in the current directory, sync "."
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        })
+        .collect();
+    for parent in parents {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn sync_dirs<'a>(_paths: impl IntoIterator<Item = &'a Path>) -> Result<()> {
     Ok(())
 }
 
@@ -514,6 +721,205 @@ fn perform_rename(from: &Path, to: &Path, _is_dir: bool, state: &mut ApplyState)
     Ok(())
 }
 
+/// Sort renames for applying: directories first, then files (deepest to
+/// shallowest), so parent directories are renamed before their contents.
+///
+/// Directories are ordered so that each is renamed after any directory it, or
+/// its destination, is inside, and shallowest first otherwise. Renames onto
+/// each other's sources, such as swaps, are left to staging. An order is
+/// impossible when a directory would end up inside itself, e.g. `a → a/b`, or
+/// when renames wait on each other, e.g. `a → b` while `b → a/b`; that is a
+/// conflict, found before anything is changed.
+pub(crate) fn order_renames(
+    paths: &[crate::scanner::Rename],
+) -> Result<Vec<crate::scanner::Rename>, RenamifyError> {
+    use crate::scanner::RenameKind;
+
+    let (dirs, mut files): (Vec<_>, Vec<_>) = paths
+        .iter()
+        .cloned()
+        .partition(|rename| rename.kind == RenameKind::Dir);
+    // For files: deepest first
+    files.sort_by_key(|rename| std::cmp::Reverse(rename.path.components().count()));
+
+    let inside = |path: &Path, dir: &Path| path.starts_with(dir) && path != dir;
+    let destination_inside = |rename: &crate::scanner::Rename, dir: &Path| {
+        rename
+            .new_path
+            .parent()
+            .is_some_and(|parent| parent.starts_with(dir))
+    };
+
+    if let Some(rename) = dirs
+        .iter()
+        .find(|rename| destination_inside(rename, &rename.path))
+    {
+        return Err(RenamifyError::Conflict(format!(
+            "Can't rename {} to {}: a directory can't be moved inside itself",
+            rename.path.display(),
+            rename.new_path.display()
+        )));
+    }
+
+    // `after[i]` are the directories that can only be renamed once `i` has
+    // been, and `follows[i]` also those renamed onto its source, which staging
+    // handles unless they are part of a cycle with one of `after`
+    let count = dirs.len();
+    let mut after = vec![Vec::new(); count];
+    let mut follows = vec![Vec::new(); count];
+    for (i, first) in dirs.iter().enumerate() {
+        for (j, second) in dirs.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            if inside(&second.path, &first.path) || destination_inside(second, &first.path) {
+                after[i].push(j);
+                follows[i].push(j);
+            } else if second.new_path == first.path {
+                follows[i].push(j);
+            }
+        }
+    }
+
+    // `reach[i][j]` is whether `j` has to wait for `i`, directly or not
+    let reach: Vec<Vec<bool>> = (0..count)
+        .map(|start| {
+            let mut seen = vec![false; count];
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                for &next in &follows[node] {
+                    if !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            seen
+        })
+        .collect();
+    for (i, dependents) in after.iter().enumerate() {
+        if dependents.iter().any(|&j| reach[j][i]) {
+            let cycle = (0..count)
+                .filter(|&k| k == i || (reach[i][k] && reach[k][i]))
+                .map(|k| {
+                    format!(
+                        "  {} -> {}",
+                        dirs[k].path.display(),
+                        dirs[k].new_path.display()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Err(RenamifyError::Conflict(format!(
+                "Directory renames can't be ordered, as each has to wait for another:\n{cycle}"
+            )));
+        }
+    }
+
+    // Kahn's algorithm, taking the shallowest ready directory first
+    let mut waiting = vec![0; count];
+    for &j in after.iter().flatten() {
+        waiting[j] += 1;
+    }
+    let mut ready: BTreeSet<(usize, usize)> = (0..count)
+        .filter(|&i| waiting[i] == 0)
+        .map(|i| (dirs[i].path.components().count(), i))
+        .collect();
+    let mut ordered = Vec::with_capacity(paths.len());
+    while let Some((_, i)) = ready.pop_first() {
+        ordered.push(dirs[i].clone());
+        for &j in &after[i] {
+            waiting[j] -= 1;
+            if waiting[j] == 0 {
+                ready.insert((dirs[j].path.components().count(), j));
+            }
+        }
+    }
+    ordered.extend(files);
+    Ok(ordered)
+}
+
+/// Where `path` ended up after `renames`, given as original source and final
+/// destination pairs. The deepest rename containing it decides.
+pub(crate) fn renamed_location(path: &Path, renames: &[(PathBuf, PathBuf)]) -> PathBuf {
+    renames
+        .iter()
+        .filter_map(|(from, to)| Some((from, to, path.strip_prefix(from).ok()?)))
+        .max_by_key(|(from, _, _)| from.components().count())
+        .map_or_else(
+            || path.to_path_buf(),
+            |(_, to, relative)| {
+                if relative.as_os_str().is_empty() {
+                    to.clone()
+                } else {
+                    to.join(relative)
+                }
+            },
+        )
+}
+
+/// Rebase a rename's source and destination onto the directories already renamed
+pub(crate) fn adjust_rename(
+    rename: &crate::scanner::Rename,
+    previous_renames: &[(PathBuf, PathBuf)],
+) -> (PathBuf, PathBuf) {
+    let mut adjusted_from = rename.path.clone();
+    let mut adjusted_to = rename.new_path.clone();
+
+    // Only paths strictly inside a renamed directory move with it. A destination
+    // equal to an earlier source, as in a swap, stays where it is.
+    let inside = |path: &Path, dir: &Path| {
+        path.strip_prefix(dir)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+    };
+    for (prev_from, prev_to) in previous_renames {
+        // This rename's source is inside a directory that was already renamed
+        if let Some(relative) = inside(&rename.path, prev_from) {
+            adjusted_from = prev_to.join(relative);
+        }
+        // This rename's destination is inside a directory that was already renamed
+        if let Some(relative) = inside(&rename.new_path, prev_from) {
+            adjusted_to = prev_to.join(relative);
+        }
+    }
+
+    (adjusted_from, adjusted_to)
+}
+
+/// A rename parked under a temporary name until its destination is free
+struct StagedRename {
+    /// Original source path
+    from: PathBuf,
+    temp: PathBuf,
+    /// Destination, adjusted for directories already renamed
+    to: PathBuf,
+    /// Destination as planned, which is the source of another rename
+    destination: PathBuf,
+    is_dir: bool,
+}
+
+/// Temporary name for a rename whose destination is still occupied
+pub(crate) fn staging_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{name}.{}.renamify.tmp", std::process::id()))
+}
+
+/// The renames performed so far as the rest of the plan should see them: moves
+/// to a temporary name are left out, and moves from one are recorded from the
+/// original path instead. `staged` maps temporary names to original paths.
+fn collapse_staged(
+    performed: &[(PathBuf, PathBuf)],
+    staged: &HashMap<PathBuf, PathBuf>,
+) -> Vec<(PathBuf, PathBuf)> {
+    performed
+        .iter()
+        .filter(|(_, to)| !staged.contains_key(to))
+        .map(|(from, to)| (staged.get(from).unwrap_or(from).clone(), to.clone()))
+        .collect()
+}
+
 /// Rollback all applied changes
 fn rollback(state: &mut ApplyState) -> Result<()> {
     state.log("Starting rollback due to error")?;
@@ -552,37 +958,123 @@ fn rollback(state: &mut ApplyState) -> Result<()> {
     Ok(())
 }
 
+/// Undo a cancelled apply: revert its renames, then put back the original
+/// contents of the files it edited
+fn rollback_cancelled(state: &mut ApplyState, edited: &[(PathBuf, String)]) -> Result<()> {
+    state.log("Apply cancelled, rolling back")?;
+    rollback(state)?;
+    for (path, content) in edited {
+        fs::write(path, content)
+            .with_context(|| format!("Failed to restore {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The error for files to edit that could not be read, each given as
+/// `path: error`
+fn unreadable_error(unreadable: &[String]) -> anyhow::Error {
+    let count = match unreadable.len() {
+        1 => "1 file".to_string(),
+        count => format!("{count} files"),
+    };
+    anyhow!(
+        "{count} in the plan could not be read, so nothing was changed:\n  {}",
+        unreadable.join("\n  ")
+    )
+}
+
+/// Commit message used with `--commit` when `commit.template` isn't set
+pub const DEFAULT_COMMIT_TEMPLATE: &str = "renamify: rename {search} -> {replace} (#{plan_id})";
+
 /// Apply a renaming plan
-#[allow(clippy::too_many_lines)]
 pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
+    apply_plan_observed(plan, options, None)
+}
+
+/// Apply a renaming plan, reporting each applied hunk to `observer`. If it asks
+/// to cancel, the changes made so far are rolled back and the apply fails with
+/// `RenamifyError::Cancelled`.
+pub fn apply_plan_observed(
+    plan: &mut Plan,
+    options: &ApplyOptions,
+    observer: Option<&dyn OperationObserver>,
+) -> Result<()> {
+    apply_plan_cached(plan, options, observer, None)
+}
+
+/// Apply a renaming plan, taking the contents of files from `cache` where they
+/// are unchanged since the scan that filled it instead of reading them again
+#[allow(clippy::too_many_lines)]
+pub fn apply_plan_cached(
+    plan: &mut Plan,
+    options: &ApplyOptions,
+    observer: Option<&dyn OperationObserver>,
+    cache: Option<&ContentCache>,
+) -> Result<()> {
     let mut state = ApplyState::new(options.log_file.clone())?;
 
     state.log(&format!("Starting apply for plan {}", plan.id))?;
     state.log(&format!("Options: {:?}", options))?;
 
+    // Ordered up front, so renames that can't be ordered fail before anything
+    // changes instead of partway through
+    let renames = order_renames(&plan.paths)?;
+
+    // Prepared before any renames move the files, and so any bad template or
+    // flag fails before anything changes
+    let pending_commit = options
+        .commit
+        .then(|| PendingCommit::prepare(plan, options))
+        .transpose()?;
+
     // Note: Backup system uses diffy patches, not file backups
 
-    // STEP 1: Store original content BEFORE any changes for diff generation
+    // STEP 1: Store original content BEFORE any changes for diff generation,
+    // and check every file to edit can be read, so an unreadable file fails the
+    // apply before anything changes instead of halfway through
     let mut original_contents: HashMap<PathBuf, String> = HashMap::new();
-    if options.create_backups {
-        let mut files_with_content_changes: HashSet<&Path> = HashSet::new();
-        for hunk in &plan.matches {
-            files_with_content_changes.insert(&hunk.file);
-        }
-
-        for file_path in files_with_content_changes {
-            if let Ok(content) = fs::read_to_string(file_path) {
-                original_contents.insert(file_path.to_path_buf(), content);
-            }
+    let files_with_content_changes: BTreeSet<&Path> = plan
+        .matches
+        .iter()
+        .map(|hunk| hunk.file.as_path())
+        .collect();
+    let mut unreadable = Vec::new();
+    for file_path in &files_with_content_changes {
+        let file_path = *file_path;
+        let read = if options.backup == BackupStrategy::Copy {
+            cache
+                .and_then(|cache| cache.take(file_path))
+                .map_or_else(|| fs::read_to_string(file_path), Ok)
+                .map(|content| {
+                    original_contents.insert(file_path.to_path_buf(), content);
+                })
+        } else {
+            File::open(file_path).map(drop)
+        };
+        if let Err(e) = read {
+            unreadable.push(format!("{}: {e}", file_path.display()));
         }
     }
+    if !unreadable.is_empty() {
+        state.log(&format!("Unreadable files: {}", unreadable.join(", ")))?;
+        return Err(unreadable_error(&unreadable));
+    }
+    if options.backup == BackupStrategy::GitStash {
+        state.log("Storing original contents in git")?;
+        let paths: Vec<PathBuf> = files_with_content_changes
+            .iter()
+            .map(|path| path.to_path_buf())
+            .collect();
+        GitBlobs::store(&paths, &options.working_dir()?, &plan.id)?
+            .write(&history_backups_path(plan, options))?;
+    }
 
     // STEP 2: Apply content edits FIRST (before any renames)
     // Group content edits by file
     let mut edits_by_file: BTreeMap<PathBuf, Vec<_>> = BTreeMap::new();
 
     // Debug Train-Case patterns in plan
-    if std::env::var("RENAMIFY_DEBUG_TRAIN_CASE").is_ok() {
+    if crate::debug::flags().train_case {
         eprintln!("\n=== Plan matches for Train-Case patterns ===");
         for hunk in &plan.matches {
             if hunk.content.contains('-')
@@ -597,95 +1089,130 @@ pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
     }
 
     for hunk in &plan.matches {
-        edits_by_file.entry(hunk.file.clone()).or_default().push((
-            hunk.content.clone(),
-            hunk.replace.clone(),
-            hunk.start,
-            hunk.end,
-        ));
+        edits_by_file
+            .entry(hunk.file.clone())
+            .or_default()
+            .push(hunk);
     }
 
     // Apply content edits to files at their ORIGINAL locations (before renames)
-    for (path, edits) in edits_by_file {
-        // Read the file content
-        let file_content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read {}", path.display()))?;
+    let total_hunks = plan.matches.len();
+    let mut hunks_applied = 0;
+    // Original contents of edited files, restored if the observer cancels
+    let mut edited: Vec<(PathBuf, String)> = Vec::new();
+    let mut writer = ContentWriter::new(options.durability);
+    for (path, hunks) in edits_by_file {
+        if let Err(e) = check_cancelled(observer, "Apply") {
+            writer.discard();
+            rollback_cancelled(&mut state, &edited)?;
+            return Err(e.into());
+        }
 
-        if let Err(e) = apply_content_edits_with_content(&path, &file_content, &edits, &mut state) {
+        let edits: Vec<_> = hunks
+            .iter()
+            .map(|hunk| {
+                (
+                    hunk.content.clone(),
+                    hunk.replace.clone(),
+                    hunk.start,
+                    hunk.end,
+                )
+            })
+            .collect();
+
+        // Read the file content, unless it was read for the backups already
+        let file_content = match original_contents.get(&path) {
+            Some(content) => content.clone(),
+            None => match cache.and_then(|cache| cache.take(&path)) {
+                Some(content) => content,
+                None => match fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        state.log(&format!("Error reading {}: {}", path.display(), e))?;
+                        writer.discard();
+                        rollback(&mut state)?;
+                        return Err(
+                            anyhow!(e).context(format!("Failed to read {}", path.display()))
+                        );
+                    },
+                },
+            },
+        };
+
+        if let Err(e) = writer.write(&path, &file_content, &edits, &mut state) {
             state.log(&format!(
                 "Error applying edits to {}: {}",
                 path.display(),
                 e
             ))?;
 
+            writer.discard();
             rollback(&mut state)?;
 
             return Err(e);
         }
-    }
 
-    // STEP 3: Apply renames AFTER content edits
-    // Sort renames: directories first (shallowest to deepest), then files (deepest to shallowest)
-    // This ensures parent directories are renamed before their contents
-    let mut renames = plan.paths.clone();
-    renames.sort_by(|a, b| {
-        use crate::scanner::RenameKind;
-        match (&a.kind, &b.kind) {
-            (RenameKind::Dir, RenameKind::File) => std::cmp::Ordering::Less,
-            (RenameKind::File, RenameKind::Dir) => std::cmp::Ordering::Greater,
-            (RenameKind::Dir, RenameKind::Dir) => {
-                // For directories: shallowest first
-                a.path
-                    .components()
-                    .count()
-                    .cmp(&b.path.components().count())
-            },
-            (RenameKind::File, RenameKind::File) => {
-                // For files: deepest first
-                b.path
-                    .components()
-                    .count()
-                    .cmp(&a.path.components().count())
-            },
+        if let Some(observer) = observer {
+            edited.push((path, file_content));
+            for hunk in hunks {
+                hunks_applied += 1;
+                observer.on_hunk_applied(hunk, hunks_applied, total_hunks);
+            }
         }
-    });
+    }
 
+    if let Err(e) = writer.finish(&mut state) {
+        state.log(&format!("Error finishing content edits: {}", e))?;
+        writer.discard();
+        rollback(&mut state)?;
+        return Err(e);
+    }
+
+    // STEP 3: Apply renames AFTER content edits. A rename onto the source of a
+    // rename still to come, such as either half of a swap, moves to a temporary
+    // name first and reaches its destination once that source has moved away.
+    let mut pending_sources: HashSet<PathBuf> =
+        renames.iter().map(|rename| rename.path.clone()).collect();
+    let mut waiting: Vec<StagedRename> = Vec::new();
+    let mut staged: HashMap<PathBuf, PathBuf> = HashMap::new();
     for rename in renames {
+        if let Err(e) = check_cancelled(observer, "Apply") {
+            rollback_cancelled(&mut state, &edited)?;
+            return Err(e.into());
+        }
+
         let is_dir = rename.kind == crate::scanner::RenameKind::Dir;
+        pending_sources.remove(&rename.path);
 
         // Check if this rename's source path has been affected by a previous directory rename
         // This handles the case where a file inside a renamed directory also needs to be renamed
-        let mut adjusted_from = rename.path.clone();
-        let mut adjusted_to = rename.new_path.clone();
-
-        // Clone to avoid borrow checker issues
-        let previous_renames = state.renames_performed.clone();
-
-        for (prev_from, prev_to) in &previous_renames {
-            // Check if the source path needs adjustment
-            if let Ok(relative) = rename.path.strip_prefix(prev_from) {
-                // This rename's source is inside a directory that was already renamed
-                adjusted_from = prev_to.join(relative);
-                state.log(&format!(
-                    "Adjusted rename source: {} -> {} (due to parent directory rename)",
-                    rename.path.display(),
-                    adjusted_from.display()
-                ))?;
-            }
-
-            // Check if the destination path needs adjustment
-            if let Ok(relative) = rename.new_path.strip_prefix(prev_from) {
-                // This rename's destination is inside a directory that was already renamed
-                adjusted_to = prev_to.join(relative);
-                state.log(&format!(
-                    "Adjusted rename destination: {} -> {} (due to parent directory rename)",
-                    rename.new_path.display(),
-                    adjusted_to.display()
-                ))?;
-            }
+        let (adjusted_from, adjusted_to) = if staged.is_empty() {
+            adjust_rename(&rename, &state.renames_performed)
+        } else {
+            adjust_rename(&rename, &collapse_staged(&state.renames_performed, &staged))
+        };
+        if adjusted_from != rename.path {
+            state.log(&format!(
+                "Adjusted rename source: {} -> {} (due to parent directory rename)",
+                rename.path.display(),
+                adjusted_from.display()
+            ))?;
+        }
+        if adjusted_to != rename.new_path {
+            state.log(&format!(
+                "Adjusted rename destination: {} -> {} (due to parent directory rename)",
+                rename.new_path.display(),
+                adjusted_to.display()
+            ))?;
         }
 
-        if let Err(e) = perform_rename(&adjusted_from, &adjusted_to, is_dir, &mut state) {
+        let destination_taken = pending_sources.contains(&rename.new_path);
+        let target = if destination_taken {
+            staging_path(&adjusted_from)
+        } else {
+            adjusted_to.clone()
+        };
+        if let Err(e) = perform_rename(&adjusted_from, &target, is_dir, &mut state) {
             state.log(&format!("Error performing rename: {}", e))?;
 
             rollback(&mut state)?;
@@ -693,10 +1220,19 @@ pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
             return Err(e);
         }
 
-        // Override the recorded rename to use the ORIGINAL paths for tracking
-        // The perform_rename function records adjusted_from -> adjusted_to
-        // But we need original_from -> final_to for proper tracking
-        if adjusted_from != rename.path || adjusted_to != rename.new_path {
+        if destination_taken {
+            staged.insert(target.clone(), rename.path.clone());
+            waiting.push(StagedRename {
+                from: rename.path.clone(),
+                temp: target,
+                to: adjusted_to,
+                destination: rename.new_path.clone(),
+                is_dir,
+            });
+        } else if adjusted_from != rename.path || adjusted_to != rename.new_path {
+            // Override the recorded rename to use the ORIGINAL paths for tracking
+            // The perform_rename function records adjusted_from -> adjusted_to
+            // But we need original_from -> final_to for proper tracking
             // Remove the last entry (which has adjusted paths)
             state.renames_performed.pop();
             // Add the correct entry with original from path and adjusted to path
@@ -704,10 +1240,44 @@ pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
                 .renames_performed
                 .push((rename.path.clone(), adjusted_to.clone()));
         }
+
+        // Finish the staged renames that were waiting for this source to move
+        let (ready, still_waiting): (Vec<_>, Vec<_>) = waiting
+            .into_iter()
+            .partition(|staged_rename| staged_rename.destination == rename.path);
+        waiting = still_waiting;
+        for staged_rename in ready {
+            if let Err(e) = perform_rename(
+                &staged_rename.temp,
+                &staged_rename.to,
+                staged_rename.is_dir,
+                &mut state,
+            ) {
+                state.log(&format!("Error finishing staged rename: {}", e))?;
+
+                rollback(&mut state)?;
+
+                return Err(e);
+            }
+        }
+    }
+    if let Some(staged_rename) = waiting.first() {
+        let message = format!(
+            "Staged rename of {} never reached {}",
+            staged_rename.from.display(),
+            staged_rename.to.display()
+        );
+        state.log(&message)?;
+        rollback(&mut state)?;
+        return Err(anyhow!(message));
+    }
+    // Hide the temporary names from the backups and history
+    if !staged.is_empty() {
+        state.renames_performed = collapse_staged(&state.renames_performed, &staged);
     }
 
     // STEP 4: Generate comprehensive patch after all changes are complete
-    if options.create_backups {
+    if options.backup == BackupStrategy::Copy {
         state.log("Creating comprehensive patch backup")?;
 
         // Generate individual reverse patch files
@@ -721,45 +1291,32 @@ pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
         state.log("Comprehensive patch created successfully")?;
     }
 
+    if options.stage {
+        state.log("Staging changes")?;
+        crate::stage::stage_applied(
+            &options.working_dir()?,
+            &state.content_edits_applied,
+            &state.renames_performed,
+        )?;
+    }
+
     // Commit to git if requested
-    if options.commit {
+    if let Some(pending_commit) = &pending_commit {
         state.log("Creating git commit")?;
-
-        let output = std::process::Command::new("git")
-            .args(["add", "-A"])
-            .output()?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to stage changes: {}", error));
-        }
-
-        let commit_message = format!(
-            "renamify: rename {} -> {} (#{}))",
-            plan.search, plan.replace, plan.id
-        );
-
-        let output = std::process::Command::new("git")
-            .args(["commit", "-m", &commit_message])
-            .output()?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to commit changes: {}", error));
-        }
-
-        state.log(&format!("Created git commit: {}", commit_message))?;
+        pending_commit.commit(&options.commit_options)?;
+        state.log(&format!("Created git commit: {}", pending_commit.message))?;
     }
 
     // Record in history
     state.log("Recording in history")?;
 
-    // Calculate checksums for all affected files
+    // Calculate checksums for all affected files, where the renames left them
     let mut affected_files = HashMap::new();
     for path in &state.content_edits_applied {
+        let path = renamed_location(path, &state.renames_performed);
         if path.exists() {
-            let checksum = calculate_checksum(path)?;
-            affected_files.insert(path.clone(), checksum);
+            let checksum = calculate_checksum(&path)?;
+            affected_files.insert(path, checksum);
         }
     }
 
@@ -771,20 +1328,11 @@ pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
         }
     }
 
-    // Pass the correct backup path to history entry
-    // If backup_dir already includes the plan_id, use it as-is
-    // Otherwise, append the plan_id
-    let backups_path = if options.backup_dir.ends_with(&plan.id) {
-        options.backup_dir.clone()
-    } else {
-        options.backup_dir.join(&plan.id)
-    };
-
     let history_entry = create_history_entry(
         plan,
         affected_files,
         state.renames_performed.clone(),
-        backups_path,
+        history_backups_path(plan, options),
         None, // Not a revert
         None, // Not a redo
     );
@@ -816,10 +1364,249 @@ pub fn apply_plan(plan: &mut Plan, options: &ApplyOptions) -> Result<()> {
     fs::write(&plan_path, plan_json)?;
     state.log(&format!("Stored plan at {}", plan_path.display()))?;
 
+    // Keep what each name became for audits
+    let root = match renamify_dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let root = root.canonicalize().unwrap_or(root);
+    RenameMapping::from_plan(plan, &state.renames_performed, &root).write(renamify_dir)?;
+
     state.log("Apply completed successfully")?;
     Ok(())
 }
 
+/// Stage everything and create a git commit for an applied plan
+/// Backup path recorded in history for a plan.
+/// If `backup_dir` already includes the plan id it is used as-is, otherwise the id is appended.
+fn history_backups_path(plan: &Plan, options: &ApplyOptions) -> PathBuf {
+    if options.backup_dir.ends_with(&plan.id) {
+        options.backup_dir.clone()
+    } else {
+        options.backup_dir.join(&plan.id)
+    }
+}
+
+/// Build the history entry that `apply_plan` would record, without touching the filesystem.
+///
+/// Content edits are applied in memory so the checksums match what apply will
+/// store; only `created_at` differs from the real entry.
+pub fn preview_history_entry(plan: &Plan, options: &ApplyOptions) -> Result<HistoryEntry> {
+    let mut edits_by_file: BTreeMap<&Path, Vec<_>> = BTreeMap::new();
+    for hunk in &plan.matches {
+        edits_by_file.entry(&hunk.file).or_default().push((
+            hunk.content.clone(),
+            hunk.replace.clone(),
+            hunk.start,
+            hunk.end,
+        ));
+    }
+
+    let mut edited_checksums = HashMap::new();
+    for (path, edits) in edits_by_file {
+        let file_content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let modified = apply_replacements(path, &file_content, &edits)?;
+        edited_checksums.insert(
+            path.to_path_buf(),
+            format!("{:x}", Sha256::digest(modified.as_bytes())),
+        );
+    }
+
+    let mut renames_performed: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for rename in order_renames(&plan.paths)? {
+        let (_, adjusted_to) = adjust_rename(&rename, &renames_performed);
+        renames_performed.push((rename.path, adjusted_to));
+    }
+
+    // Edited files that are moved by a rename are recorded at their new location
+    let mut affected_files = HashMap::new();
+    for (path, checksum) in &edited_checksums {
+        affected_files.insert(renamed_location(path, &renames_performed), checksum.clone());
+    }
+    for rename in plan
+        .paths
+        .iter()
+        .filter(|rename| rename.kind == crate::scanner::RenameKind::File)
+    {
+        let Some((_, to)) = renames_performed
+            .iter()
+            .find(|(from, _)| *from == rename.path)
+        else {
+            continue;
+        };
+        let checksum = match edited_checksums.get(&rename.path) {
+            Some(checksum) => checksum.clone(),
+            None => calculate_checksum(&rename.path)?,
+        };
+        affected_files.insert(to.clone(), checksum);
+    }
+
+    Ok(create_history_entry(
+        plan,
+        affected_files,
+        renames_performed,
+        history_backups_path(plan, options),
+        None,
+        None,
+    ))
+}
+
+/// A commit to make once a plan is applied
+struct PendingCommit {
+    vcs: Box<dyn Vcs>,
+    message: String,
+    /// Submodules and other nested repositories with changes in the plan,
+    /// which are committed in their own repository
+    submodules: Vec<PathBuf>,
+}
+
+impl PendingCommit {
+    fn prepare(plan: &Plan, options: &ApplyOptions) -> Result<Self> {
+        let working_dir = options.working_dir()?;
+        let vcs = crate::vcs::detect(&working_dir)
+            .ok_or_else(|| anyhow!("Not in a git, jj or hg repository"))?;
+        vcs.check_commit_options(&options.commit_options)?;
+        let message = options
+            .commit_options
+            .message(plan, DEFAULT_COMMIT_TEMPLATE)?;
+        let submodules = crate::submodule::nested_repos(
+            &working_dir,
+            plan.matches
+                .iter()
+                .map(|hunk| hunk.file.as_path())
+                .chain(plan.paths.iter().map(|rename| rename.path.as_path())),
+        );
+        Ok(Self {
+            vcs,
+            message,
+            submodules,
+        })
+    }
+
+    fn commit(&self, options: &CommitOptions) -> Result<()> {
+        // Submodules first, so the parent commit records their new commits
+        for submodule in &self.submodules {
+            crate::vcs::Git::new(submodule)
+                .commit_all(&self.message, options)
+                .with_context(|| format!("In submodule {}", submodule.display()))?;
+        }
+        self.vcs.commit_all(&self.message, options)
+    }
+}
+
+/// Apply a plan as a sequence of per-directory transactions.
+///
+/// The content edits and renames of each directory become a sub-plan that is
+/// applied with its own backup set, log and history entry. Directories are
+/// processed deepest first, so a directory is renamed only after everything
+/// inside it is done. A failure rolls back the failing directory only; the
+/// directories applied before it stay applied and can be undone one by one.
+///
+/// Returns the IDs of the transactions, in the order they were applied.
+pub fn apply_plan_by_directory(
+    plan: &Plan,
+    options: &ApplyOptions,
+    observer: Option<&dyn OperationObserver>,
+) -> Result<Vec<String>> {
+    let pending_commit = options
+        .commit
+        .then(|| PendingCommit::prepare(plan, options))
+        .transpose()?;
+    let parent_dir = |path: &Path| path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+    let empty_plan = Plan {
+        matches: Vec::new(),
+        paths: Vec::new(),
+        ..plan.clone()
+    };
+
+    let mut groups: BTreeMap<PathBuf, Plan> = BTreeMap::new();
+    for hunk in &plan.matches {
+        groups
+            .entry(parent_dir(&hunk.file))
+            .or_insert_with(|| empty_plan.clone())
+            .matches
+            .push(hunk.clone());
+    }
+    for rename in &plan.paths {
+        groups
+            .entry(parent_dir(&rename.path))
+            .or_insert_with(|| empty_plan.clone())
+            .paths
+            .push(rename.clone());
+    }
+
+    // Each transaction checks its own files too, but by then the ones before
+    // it have been applied
+    let unreadable: Vec<String> = plan
+        .matches
+        .iter()
+        .map(|hunk| hunk.file.as_path())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|path| {
+            File::open(path)
+                .err()
+                .map(|e| format!("{}: {e}", path.display()))
+        })
+        .collect();
+    if !unreadable.is_empty() {
+        return Err(unreadable_error(&unreadable));
+    }
+    order_renames(&plan.paths)?;
+
+    let mut ordered: Vec<(PathBuf, Plan)> = groups.into_iter().collect();
+    ordered.sort_by(|(a, _), (b, _)| {
+        b.components()
+            .count()
+            .cmp(&a.components().count())
+            .then_with(|| a.cmp(b))
+    });
+
+    let total = ordered.len();
+    let mut applied = Vec::with_capacity(total);
+    for (index, (dir, mut sub_plan)) in ordered.into_iter().enumerate() {
+        sub_plan.id = format!("{}-{}", plan.id, index + 1);
+        sub_plan.stats = Stats::from_matches(plan.stats.files_scanned, &sub_plan.matches);
+
+        let sub_options = ApplyOptions {
+            commit: false,
+            log_file: options
+                .log_file
+                .as_ref()
+                .map(|log| log.with_file_name(format!("{}.log", sub_plan.id))),
+            ..options.clone()
+        };
+
+        apply_plan_observed(&mut sub_plan, &sub_options, observer).with_context(|| {
+            format!(
+                "Transaction {} for {} failed after {} of {} directories were applied{}",
+                sub_plan.id,
+                dir.display(),
+                applied.len(),
+                total,
+                if applied.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " (undo them with 'renamify undo <id>' for {}-1 to {}-{})",
+                        plan.id,
+                        plan.id,
+                        applied.len()
+                    )
+                }
+            )
+        })?;
+        applied.push(sub_plan.id);
+    }
+
+    if let Some(pending_commit) = &pending_commit {
+        pending_commit.commit(&options.commit_options)?;
+    }
+
+    Ok(applied)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::too_many_lines)]
@@ -861,6 +1648,10 @@ mod tests {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             }],
             paths: vec![],
             stats: Stats {
@@ -868,14 +1659,21 @@ mod tests {
                 total_matches: 1,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 1,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
         let options = ApplyOptions {
             backup_dir: temp_dir.path().join(".renamify/backups"),
-            create_backups: true,
+            backup: BackupStrategy::Copy,
             ..Default::default()
         };
 
@@ -938,7 +1736,8 @@ mod tests {
 
         // Apply edits with pre-read content
         let original_content = fs::read_to_string(&test_file).unwrap();
-        apply_content_edits_with_content(&test_file, &original_content, &replacements, &mut state)
+        ContentWriter::new(Durability::File)
+            .write(&test_file, &original_content, &replacements, &mut state)
             .unwrap();
 
         // Check state was updated
@@ -957,6 +1756,60 @@ mod tests {
         assert_eq!(content, "fn new_name() {}");
     }
 
+    #[test]
+    fn test_content_writer_durability() {
+        for durability in [Durability::Batch, Durability::End] {
+            let temp_dir = TempDir::new().unwrap();
+            let files: Vec<PathBuf> = (0..DURABILITY_BATCH_SIZE + 3)
+                .map(|i| temp_dir.path().join(format!("file{i}.rs")))
+                .collect();
+            for file in &files {
+                fs::write(file, "fn old_name() {}").unwrap();
+            }
+            let replacements = vec![("old_name".to_string(), "new_name".to_string(), 3, 11)];
+
+            let mut state = ApplyState::new(None).unwrap();
+            let mut writer = ContentWriter::new(durability);
+            for file in &files {
+                writer
+                    .write(file, "fn old_name() {}", &replacements, &mut state)
+                    .unwrap();
+            }
+            writer.finish(&mut state).unwrap();
+
+            assert_eq!(state.content_edits_applied.len(), files.len());
+            for file in &files {
+                assert_eq!(fs::read_to_string(file).unwrap(), "fn new_name() {}");
+            }
+            assert_eq!(
+                fs::read_dir(temp_dir.path()).unwrap().count(),
+                files.len(),
+                "no temp files are left behind with {durability}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_content_writer_discards_unfinished_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file.rs");
+        fs::write(&file, "fn old_name() {}").unwrap();
+        let replacements = vec![("old_name".to_string(), "new_name".to_string(), 3, 11)];
+
+        let mut state = ApplyState::new(None).unwrap();
+        let mut writer = ContentWriter::new(Durability::Batch);
+        writer
+            .write(&file, "fn old_name() {}", &replacements, &mut state)
+            .unwrap();
+        writer.discard();
+
+        assert!(state.content_edits_applied.is_empty());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "fn old_name() {}");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+        assert_eq!("END".parse::<Durability>(), Ok(Durability::End));
+        assert!("sometimes".parse::<Durability>().is_err());
+    }
+
     #[test]
     #[serial]
     fn test_apply_plan_populates_affected_files() {
@@ -989,6 +1842,10 @@ mod tests {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             }],
             paths: vec![],
             stats: Stats {
@@ -996,14 +1853,21 @@ mod tests {
                 total_matches: 1,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 1,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
         let options = ApplyOptions {
             backup_dir: temp_dir.path().join(".renamify/backups"),
-            create_backups: true,
+            backup: BackupStrategy::Copy,
             ..Default::default()
         };
 
@@ -1026,6 +1890,287 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_preview_history_entry_matches_apply() {
+        let temp_dir = TempDir::new().unwrap();
+        let edited = temp_dir.path().join("lib.rs");
+        let renamed = temp_dir.path().join("old.rs");
+        let renamed_to = temp_dir.path().join("new.rs");
+        fs::write(&edited, "fn old() {}").unwrap();
+        fs::write(&renamed, "fn old() {}").unwrap();
+
+        let hunk = |file: &Path| MatchHunk {
+            file: file.to_path_buf(),
+            line: 1,
+            byte_offset: 3,
+            char_offset: 3,
+            variant: "old".to_string(),
+            content: "old".to_string(),
+            replace: "new".to_string(),
+            start: 3,
+            end: 6,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        };
+        let mut plan = Plan {
+            id: "test_preview".to_string(),
+            created_at: "2024-01-01".to_string(),
+            search: "old".to_string(),
+            replace: "new".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![hunk(&edited), hunk(&renamed)],
+            paths: vec![crate::scanner::Rename {
+                path: renamed.clone(),
+                new_path: renamed_to.clone(),
+                kind: crate::scanner::RenameKind::File,
+                coercion_applied: None,
+            }],
+            stats: Stats {
+                files_scanned: 2,
+                total_matches: 2,
+                matches_by_variant: HashMap::new(),
+                files_with_matches: 2,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        let options = ApplyOptions {
+            backup_dir: temp_dir.path().join(".renamify/backups"),
+            log_file: None,
+            ..Default::default()
+        };
+
+        let preview = preview_history_entry(&plan, &options).unwrap();
+        // Previewing must not touch the files
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "fn old() {}");
+        assert!(renamed.exists());
+
+        apply_plan(&mut plan, &options).unwrap();
+        let history = History::load(temp_dir.path().join(".renamify").as_path()).unwrap();
+        let entry = history.list_entries(None)[0];
+
+        assert_eq!(preview.id, entry.id);
+        assert_eq!(preview.affected_files, entry.affected_files);
+        assert_eq!(preview.renames, entry.renames);
+        assert_eq!(preview.backups_path, entry.backups_path);
+        assert_eq!(preview.affected_files.len(), 2);
+        assert!(preview.affected_files.contains_key(&renamed_to));
+    }
+
+    #[test]
+    fn test_apply_swaps_paths_through_temporary_names() {
+        use crate::scanner::{Rename, RenameKind};
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("old_dir")).unwrap();
+        fs::create_dir_all(root.join("new_dir")).unwrap();
+        fs::write(root.join("old_dir/mod.rs"), "old").unwrap();
+        fs::write(root.join("new_dir/mod.rs"), "new").unwrap();
+        fs::write(root.join("old.rs"), "old").unwrap();
+        fs::write(root.join("new.rs"), "new").unwrap();
+
+        let rename = |from: &str, to: &str, kind: RenameKind| Rename {
+            path: root.join(from),
+            new_path: root.join(to),
+            kind,
+            coercion_applied: None,
+        };
+        let mut plan = Plan {
+            id: "test_swap".to_string(),
+            created_at: "2024-01-01".to_string(),
+            search: "old, new".to_string(),
+            replace: "new, old".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![
+                rename("old_dir", "new_dir", RenameKind::Dir),
+                rename("new_dir", "old_dir", RenameKind::Dir),
+                rename("old.rs", "new.rs", RenameKind::File),
+                rename("new.rs", "old.rs", RenameKind::File),
+            ],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        let options = ApplyOptions {
+            backup_dir: root.join(".renamify/backups"),
+            log_file: None,
+            ..Default::default()
+        };
+
+        apply_plan(&mut plan, &options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join("new_dir/mod.rs")).unwrap(),
+            "old"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("old_dir/mod.rs")).unwrap(),
+            "new"
+        );
+        assert_eq!(fs::read_to_string(root.join("new.rs")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(root.join("old.rs")).unwrap(), "new");
+
+        // History records the swap itself, not the temporary names
+        let history = History::load(root.join(".renamify").as_path()).unwrap();
+        let mut renames = history.list_entries(None)[0].renames.clone();
+        renames.sort();
+        let mut expected: Vec<(PathBuf, PathBuf)> = plan
+            .paths
+            .iter()
+            .map(|rename| (rename.path.clone(), rename.new_path.clone()))
+            .collect();
+        expected.sort();
+        assert_eq!(renames, expected);
+    }
+
+    #[test]
+    fn test_order_renames() {
+        use crate::scanner::{Rename, RenameKind};
+
+        let rename = |from: &str, to: &str, kind: RenameKind| Rename {
+            path: PathBuf::from(from),
+            new_path: PathBuf::from(to),
+            kind,
+            coercion_applied: None,
+        };
+        let order = |renames: &[Rename]| -> Vec<String> {
+            order_renames(renames)
+                .unwrap()
+                .iter()
+                .map(|rename| rename.path.display().to_string())
+                .collect()
+        };
+
+        // Files after directories, deepest first; directories shallowest first
+        assert_eq!(
+            order(&[
+                rename("a/b.rs", "a/c.rs", RenameKind::File),
+                rename("a/b/c", "a/b/d", RenameKind::Dir),
+                rename("a/b/c/d.rs", "a/b/c/e.rs", RenameKind::File),
+                rename("a/b", "a/c", RenameKind::Dir),
+            ]),
+            ["a/b", "a/b/c", "a/b/c/d.rs", "a/b.rs"]
+        );
+        // A directory moving into a deeper one waits for it to be renamed
+        assert_eq!(
+            order(&[
+                rename("x", "lib/y/x", RenameKind::Dir),
+                rename("lib/y", "lib/z", RenameKind::Dir),
+            ]),
+            ["lib/y", "x"]
+        );
+        // Swaps are left to staging
+        assert_eq!(
+            order(&[
+                rename("a", "b", RenameKind::Dir),
+                rename("b", "a", RenameKind::Dir),
+            ]),
+            ["a", "b"]
+        );
+
+        let conflict = |renames: &[Rename]| match order_renames(renames) {
+            Err(RenamifyError::Conflict(message)) => message,
+            other => panic!("expected a conflict, got {other:?}"),
+        };
+        let message = conflict(&[
+            rename("a", "b", RenameKind::Dir),
+            rename("b", "a/b", RenameKind::Dir),
+        ]);
+        assert!(message.contains("a -> b\n  b -> a/b"), "{message}");
+        assert!(conflict(&[rename("a", "a/b", RenameKind::Dir)]).contains("inside itself"));
+        assert!(conflict(&[
+            rename("a", "b/a", RenameKind::Dir),
+            rename("b", "a/b", RenameKind::Dir),
+        ])
+        .contains("can't be ordered"));
+    }
+
+    #[test]
+    fn test_apply_rejects_renames_that_cant_be_ordered() {
+        use crate::scanner::{Rename, RenameKind};
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("a/a.rs"), "a").unwrap();
+
+        let mut plan = Plan {
+            id: "test_cycle".to_string(),
+            created_at: "2024-01-01".to_string(),
+            search: "a".to_string(),
+            replace: "b".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![
+                Rename {
+                    path: root.join("a"),
+                    new_path: root.join("b"),
+                    kind: RenameKind::Dir,
+                    coercion_applied: None,
+                },
+                Rename {
+                    path: root.join("b"),
+                    new_path: root.join("a/b"),
+                    kind: RenameKind::Dir,
+                    coercion_applied: None,
+                },
+            ],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        let options = ApplyOptions {
+            backup_dir: root.join(".renamify/backups"),
+            log_file: None,
+            ..Default::default()
+        };
+
+        let err = apply_plan(&mut plan, &options).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RenamifyError>(),
+            Some(RenamifyError::Conflict(_))
+        ));
+        assert!(root.join("a/a.rs").exists());
+        assert!(root.join("b").is_dir());
+        assert!(!root.join("a/b").exists());
+    }
+
     #[test]
     fn test_checksum_calculation() {
         let temp_dir = TempDir::new().unwrap();
@@ -1191,4 +2336,151 @@ mod tests {
             assert_eq!(result, expected);
         }
     }
+
+    #[test]
+    #[serial]
+    fn test_apply_plan_by_directory_keeps_earlier_directories_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a").join("one.rs");
+        let second = temp_dir.path().join("b").join("two.rs");
+        fs::create_dir_all(first.parent().unwrap()).unwrap();
+        fs::create_dir_all(second.parent().unwrap()).unwrap();
+        fs::write(&first, "old_name").unwrap();
+        fs::write(&second, "changed!").unwrap();
+
+        let hunk = |file: &Path| MatchHunk {
+            file: file.to_path_buf(),
+            line: 1,
+            byte_offset: 0,
+            char_offset: 0,
+            variant: "old_name".to_string(),
+            content: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            start: 0,
+            end: 8,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        };
+        let matches = vec![hunk(&first), hunk(&second)];
+        let plan = Plan {
+            id: "dir_tx".to_string(),
+            created_at: "2024-01-01".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            stats: Stats::from_matches(2, &matches),
+            matches,
+            paths: vec![],
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+
+        let options = ApplyOptions {
+            backup_dir: temp_dir.path().join(".renamify/backups"),
+            log_file: None,
+            ..Default::default()
+        };
+
+        let err = apply_plan_by_directory(&plan, &options, None).unwrap_err();
+
+        // The first directory was applied and recorded on its own
+        assert_eq!(fs::read_to_string(&first).unwrap(), "new_name");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "changed!");
+        assert!(format!("{err:#}").contains("after 1 of 2 directories were applied"));
+        assert_eq!(
+            crate::error::error_kind(&err),
+            crate::error::ErrorKind::Conflict
+        );
+
+        let history = History::load(&temp_dir.path().join(".renamify")).unwrap();
+        assert!(history.find_entry("dir_tx-1").is_some());
+        assert!(history.find_entry("dir_tx-2").is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_apply_plan_fails_before_changes_on_unreadable_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a").join("one.rs");
+        let missing = temp_dir.path().join("b").join("two.rs");
+        fs::create_dir_all(first.parent().unwrap()).unwrap();
+        fs::write(&first, "old_name").unwrap();
+
+        let hunk = |file: &Path| MatchHunk {
+            file: file.to_path_buf(),
+            line: 1,
+            byte_offset: 0,
+            char_offset: 0,
+            variant: "old_name".to_string(),
+            content: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            start: 0,
+            end: 8,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        };
+        let matches = vec![hunk(&first), hunk(&missing)];
+        let mut plan = Plan {
+            id: "unreadable".to_string(),
+            created_at: "2024-01-01".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            stats: Stats::from_matches(2, &matches),
+            matches,
+            paths: vec![],
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+
+        for backup in [BackupStrategy::Copy, BackupStrategy::None] {
+            let options = ApplyOptions {
+                backup,
+                backup_dir: temp_dir.path().join(".renamify/backups"),
+                log_file: None,
+                ..Default::default()
+            };
+            let err = apply_plan(&mut plan, &options).unwrap_err();
+            assert!(err
+                .to_string()
+                .starts_with("1 file in the plan could not be read, so nothing was changed:"));
+            assert!(err.to_string().contains("two.rs"));
+            assert_eq!(fs::read_to_string(&first).unwrap(), "old_name");
+
+            assert!(apply_plan_by_directory(&plan, &options, None).is_err());
+            assert_eq!(fs::read_to_string(&first).unwrap(), "old_name");
+        }
+    }
 }
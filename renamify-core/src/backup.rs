@@ -0,0 +1,226 @@
+//! What an apply keeps so that undo can restore the files it edited.
+//!
+//! - `copy`, the default, stores a reverse patch of every edited file under
+//!   `.renamify/backups/<id>/reverse_patches/`.
+//! - `git-stash` stores each edited file's original content in the git object
+//!   store and records only the blob ids, in `.renamify/backups/<id>/blobs.json`.
+//!   Nothing has to be read or diffed, which is much faster on huge repos. A
+//!   commit holding the blobs under `refs/renamify/<id>` keeps `git gc` from
+//!   pruning them until the apply is undone or its backup is collected.
+//! - `none` keeps nothing and relies on git. Undo still reverses renames, but
+//!   can't restore content edits.
+//!
+//! Undo tells which one was used from what is in the backup directory.
+
+use crate::error::RenamifyError;
+use crate::vcs::{Git, Vcs};
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// What apply keeps so that undo can restore edited files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupStrategy {
+    /// A reverse patch of every edited file
+    #[default]
+    Copy,
+    /// The blob id of every edited file's original content, stored in git
+    GitStash,
+    /// No backups; content edits can only be undone with git
+    None,
+}
+
+impl std::fmt::Display for BackupStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Copy => "copy",
+            Self::GitStash => "git-stash",
+            Self::None => "none",
+        })
+    }
+}
+
+impl std::str::FromStr for BackupStrategy {
+    type Err = String;
+
+    /// Parse `copy`, `git-stash` or `none` as accepted by `--backup`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "copy" => Ok(Self::Copy),
+            "git-stash" => Ok(Self::GitStash),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "Invalid backup strategy '{s}', expected copy, git-stash or none"
+            )),
+        }
+    }
+}
+
+/// The original content of each edited file, as blobs in the git object store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitBlobs {
+    /// The repository holding the blobs
+    pub repository: PathBuf,
+    /// The ref that keeps the blobs from being pruned
+    pub reference: String,
+    /// Each edited file, at its path before the apply, with its blob id
+    pub blobs: BTreeMap<PathBuf, String>,
+}
+
+impl GitBlobs {
+    const FILE_NAME: &'static str = "blobs.json";
+
+    /// Store the current content of `paths` in the git repository containing
+    /// `working_dir`, kept from pruning by a ref named after apply `id`
+    pub fn store(paths: &[PathBuf], working_dir: &Path, id: &str) -> Result<Self> {
+        let git = Git::containing(working_dir).map_err(|_| {
+            RenamifyError::InvalidInput(
+                "--backup git-stash needs a git repository; use --backup copy instead".to_string(),
+            )
+        })?;
+        let ids = git.write_blobs(paths)?;
+        let reference = format!("refs/renamify/{id}");
+        git.keep_blobs(&reference, &ids, &format!("renamify backup of {id}"))?;
+        Ok(Self {
+            repository: git.root().to_path_buf(),
+            reference,
+            blobs: paths.iter().cloned().zip(ids).collect(),
+        })
+    }
+
+    /// Record the blob ids in `backup_dir`
+    pub fn write(&self, backup_dir: &Path) -> Result<()> {
+        fs::create_dir_all(backup_dir)?;
+        let path = backup_dir.join(Self::FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The blob ids recorded in `backup_dir`, if the apply used `git-stash`
+    pub fn read(backup_dir: &Path) -> Result<Option<Self>> {
+        let path = backup_dir.join(Self::FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Some(serde_json::from_str(&content).with_context(|| {
+            format!("Invalid backup record {}", path.display())
+        })?))
+    }
+
+    /// The original content of `path`, if it was recorded
+    pub fn original(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        self.blobs
+            .get(path)
+            .map(|id| Git::new(&self.repository).read_blob(id))
+            .transpose()
+    }
+
+    /// Fail, naming the files, when any blob is gone from the object store
+    pub fn check_available(&self) -> Result<()> {
+        let ids: Vec<&str> = self.blobs.values().map(String::as_str).collect();
+        let missing = Git::new(&self.repository).missing_objects(&ids)?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let files: Vec<String> = self
+            .blobs
+            .iter()
+            .filter(|(_, id)| missing.contains(id))
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        Err(RenamifyError::NotFound(format!(
+            "The original content of {} is missing from the git object store, so it can't be restored. \
+             Was {} deleted before git gc ran?",
+            files.join(", "),
+            self.reference
+        ))
+        .into())
+    }
+
+    /// Delete the ref keeping the blobs, once they are no longer needed
+    pub fn release(&self) -> Result<()> {
+        Git::new(&self.repository).delete_ref(&self.reference)
+    }
+
+    /// Write each recorded file's original content back to its original path,
+    /// keeping the permissions of the file it replaces. Files deleted since
+    /// the apply stay deleted.
+    pub fn restore(&self) -> Result<()> {
+        let git = Git::new(&self.repository);
+        for (path, id) in &self.blobs {
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            let content = git.read_blob(id)?;
+            fs::write(path, content)
+                .with_context(|| format!("Failed to restore {}", path.display()))?;
+            fs::set_permissions(path, metadata.permissions())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_backup_strategy() {
+        assert_eq!("git-stash".parse(), Ok(BackupStrategy::GitStash));
+        assert_eq!("None".parse(), Ok(BackupStrategy::None));
+        assert!("zip".parse::<BackupStrategy>().is_err());
+        assert_eq!(BackupStrategy::GitStash.to_string(), "git-stash");
+    }
+
+    #[test]
+    fn test_git_blobs_restore_original_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&root)
+            .status()
+            .unwrap();
+        let file = root.join("a.rs");
+        fs::write(&file, "old_name\n").unwrap();
+
+        let blobs = GitBlobs::store(std::slice::from_ref(&file), &root, "abc").unwrap();
+        blobs.write(&root.join("backup")).unwrap();
+        fs::write(&file, "new_name\n").unwrap();
+
+        // The ref keeps the blobs through an aggressive gc
+        let gc = |root: &Path| {
+            Command::new("git")
+                .args(["gc", "-q", "--prune=now"])
+                .current_dir(root)
+                .status()
+                .unwrap()
+        };
+        gc(&root);
+        let blobs = GitBlobs::read(&root.join("backup")).unwrap().unwrap();
+        assert_eq!(blobs.reference, "refs/renamify/abc");
+        blobs.check_available().unwrap();
+        assert_eq!(blobs.original(&file).unwrap().unwrap(), b"old_name\n");
+        blobs.restore().unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "old_name\n");
+
+        // Without it they are pruned, and that is reported by file
+        blobs.release().unwrap();
+        gc(&root);
+        let error = blobs.check_available().unwrap_err().to_string();
+        assert!(error.contains("a.rs is missing"), "{error}");
+
+        let outside = TempDir::new().unwrap();
+        assert!(GitBlobs::store(&[], outside.path(), "abc").is_err());
+    }
+}
@@ -0,0 +1,466 @@
+//! Shareable rename bundles, written by `history export --bundle` and applied
+//! with `apply --bundle`.
+//!
+//! A bundle carries an applied plan with paths relative to the project root,
+//! along with each edited file's checksum and patch from before the rename, so
+//! a teammate can apply exactly the same edits without re-planning. Files that
+//! no longer match their checksum have drifted; their patches decide whether
+//! the edits can still be placed.
+
+use crate::apply::{apply_replacements, renamed_location, replace_patch_headers};
+use crate::backup::GitBlobs;
+use crate::error::RenamifyError;
+use crate::history::HistoryEntry;
+use crate::scanner::{MatchHunk, Plan};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Newest bundle format this version of renamify reads and writes
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    /// Renamify version that exported the bundle
+    pub renamify_version: String,
+    pub exported_at: String,
+    /// History ID of the exported rename, reused when the bundle is applied
+    pub history_id: String,
+    pub search: String,
+    pub replace: String,
+    /// The applied plan, with paths relative to the project root
+    pub plan: Plan,
+    /// Every file with content edits, in path order
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Path relative to the project root, before any renames
+    pub path: PathBuf,
+    /// SHA-256 of the file's content before the rename
+    pub checksum: String,
+    /// Unified diff from the content before the rename to the content after it
+    pub patch: String,
+}
+
+impl Bundle {
+    /// Package an applied rename. `plan` is the plan stored for `entry` and
+    /// `root` the canonical project root. Every edited file must still be as the
+    /// rename left it, since its reverse patch, or with `--backup git-stash` its
+    /// blob, recovers the original content.
+    pub fn export(entry: &HistoryEntry, plan: &Plan, root: &Path) -> Result<Self> {
+        let relative = |path: &Path| -> Result<PathBuf> {
+            path.strip_prefix(root).map(Path::to_path_buf).map_err(|_| {
+                RenamifyError::InvalidInput(format!(
+                    "{} is outside {}",
+                    path.display(),
+                    root.display()
+                ))
+                .into()
+            })
+        };
+
+        let mut hunks_by_file: BTreeMap<&Path, &MatchHunk> = BTreeMap::new();
+        for hunk in &plan.matches {
+            hunks_by_file.entry(&hunk.file).or_insert(hunk);
+        }
+
+        let reverse_patches = entry.backups_path.join("reverse_patches");
+        let blobs = GitBlobs::read(&entry.backups_path)?;
+        if let Some(blobs) = &blobs {
+            blobs.check_available()?;
+        }
+        let mut files = Vec::new();
+        for (path, hunk) in hunks_by_file {
+            let current_path = hunk
+                .renamed_file
+                .clone()
+                .unwrap_or_else(|| renamed_location(path, &entry.renames));
+            let current_path = current_path.as_path();
+            let current = fs::read_to_string(current_path)
+                .with_context(|| format!("Failed to read {}", current_path.display()))?;
+            let unchanged = entry
+                .affected_files
+                .get(current_path)
+                .is_none_or(|checksum| *checksum == checksum_of(&current));
+            if !unchanged {
+                return Err(RenamifyError::Conflict(format!(
+                    "{} has changed since {} was applied; bundles can only be exported while the rename is in place",
+                    current_path.display(),
+                    entry.id
+                ))
+                .into());
+            }
+
+            let original = if let Some(hash) = &hunk.patch_hash {
+                let patch_path = reverse_patches.join(format!("{hash}.patch"));
+                let reverse = fs::read_to_string(&patch_path)
+                    .with_context(|| format!("Failed to read {}", patch_path.display()))?;
+                apply_patch(&current, &reverse)
+                    .with_context(|| format!("Failed to recover the original {}", path.display()))?
+            } else if let Some(blobs) = &blobs {
+                let content = blobs.original(path)?.ok_or_else(|| {
+                    RenamifyError::NotFound(format!(
+                        "No original content recorded for {} in {}",
+                        path.display(),
+                        entry.id
+                    ))
+                })?;
+                String::from_utf8(content).with_context(|| {
+                    format!("The original {} is not valid UTF-8", path.display())
+                })?
+            } else {
+                return Err(RenamifyError::InvalidInput(format!(
+                    "{} was applied with --backup none, which keeps no original content to export; \
+                     only renames applied with --backup copy or git-stash can be exported as bundles",
+                    entry.id
+                ))
+                .into());
+            };
+
+            let from = relative(path)?;
+            let to = relative(current_path)?;
+            let patch = diffy::create_patch(&original, &current).to_string();
+            files.push(BundleFile {
+                checksum: checksum_of(&original),
+                patch: replace_patch_headers(&patch, &from, &to),
+                path: from,
+            });
+        }
+
+        let mut plan = plan.clone();
+        for hunk in &mut plan.matches {
+            hunk.file = relative(&hunk.file)?;
+            hunk.original_file = None;
+            hunk.renamed_file = None;
+            hunk.patch_hash = None;
+        }
+        for rename in &mut plan.paths {
+            rename.path = relative(&rename.path)?;
+            rename.new_path = relative(&rename.new_path)?;
+        }
+        plan.created_directories = None;
+
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            renamify_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: chrono::Local::now().to_rfc3339(),
+            history_id: entry.id.clone(),
+            search: entry.search.clone(),
+            replace: entry.replace.clone(),
+            plan,
+            files,
+        })
+    }
+
+    /// Read a bundle file, refusing formats newer than this renamify
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read bundle {}", path.display()))?;
+        let bundle: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse bundle {}", path.display()))?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(RenamifyError::InvalidInput(format!(
+                "Bundle {} uses format version {}, but this renamify reads up to version {}",
+                path.display(),
+                bundle.version,
+                BUNDLE_VERSION
+            ))
+            .into());
+        }
+        bundle.check_paths(path)?;
+        Ok(bundle)
+    }
+
+    /// Refuse a bundle whose paths could reach outside the checkout it is
+    /// applied to
+    fn check_paths(&self, source: &Path) -> Result<()> {
+        let hunk_paths = self.plan.matches.iter().flat_map(|hunk| {
+            [
+                Some(&hunk.file),
+                hunk.original_file.as_ref(),
+                hunk.renamed_file.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+        });
+        let rename_paths = self
+            .plan
+            .paths
+            .iter()
+            .flat_map(|rename| [&rename.path, &rename.new_path]);
+        let mut paths = self
+            .files
+            .iter()
+            .map(|file| &file.path)
+            .chain(hunk_paths)
+            .chain(rename_paths)
+            .chain(self.plan.created_directories.iter().flatten());
+        match paths.find(|path| !is_inside_root(path)) {
+            Some(path) => Err(RenamifyError::InvalidInput(format!(
+                "Bundle {} has path {}, which is not relative to the project root",
+                source.display(),
+                path.display()
+            ))
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).with_context(|| format!("Failed to write bundle {}", path.display()))
+    }
+
+    /// Files under `root` that differ from the exporter's checkout: edited files
+    /// whose content has changed and renamed paths that are missing
+    pub fn drift(&self, root: &Path) -> Vec<PathBuf> {
+        let mut drifted: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|file| {
+                fs::read_to_string(root.join(&file.path))
+                    .map_or(true, |content| checksum_of(&content) != file.checksum)
+            })
+            .map(|file| file.path.clone())
+            .collect();
+        for rename in &self.plan.paths {
+            if !root.join(&rename.path).exists() && !drifted.contains(&rename.path) {
+                drifted.push(rename.path.clone());
+            }
+        }
+        drifted
+    }
+
+    /// The bundle's plan with paths under `root`. The hunks of `drifted` files
+    /// are moved to where their lines are now, which only succeeds if the file's
+    /// patch still applies and the moved hunks make the same edits.
+    pub fn plan_for(&self, root: &Path, drifted: &[PathBuf]) -> Result<Plan> {
+        let mut plan = self.plan.clone();
+        for file in self
+            .files
+            .iter()
+            .filter(|file| drifted.contains(&file.path))
+        {
+            let path = root.join(&file.path);
+            let cannot_apply = |reason: &str| {
+                RenamifyError::Conflict(format!(
+                    "{} has changed since the bundle was exported and its edits cannot be applied: {reason}",
+                    file.path.display()
+                ))
+            };
+            let content = fs::read_to_string(&path).map_err(|_| cannot_apply("file not found"))?;
+            let patched = apply_patch(&content, &file.patch)
+                .map_err(|_| cannot_apply("patch does not apply"))?;
+
+            let mut hunks: Vec<&mut MatchHunk> = plan
+                .matches
+                .iter_mut()
+                .filter(|hunk| hunk.file == file.path)
+                .collect();
+            for hunk in &mut hunks {
+                reanchor(hunk, &content).ok_or_else(|| cannot_apply("edited lines have moved"))?;
+            }
+            hunks.sort_by_key(|hunk| hunk.start);
+            let edits: Vec<(String, String, usize, usize)> = hunks
+                .iter()
+                .map(|hunk| {
+                    (
+                        hunk.content.clone(),
+                        hunk.replace.clone(),
+                        hunk.start,
+                        hunk.end,
+                    )
+                })
+                .collect();
+            let edited = apply_replacements(&path, &content, &edits)
+                .map_err(|_| cannot_apply("edited lines have moved"))?;
+            if edited != patched {
+                return Err(cannot_apply("edits no longer match the patch").into());
+            }
+        }
+        for rename in &self.plan.paths {
+            if drifted.contains(&rename.path) && !root.join(&rename.path).exists() {
+                return Err(RenamifyError::Conflict(format!(
+                    "{} has been removed since the bundle was exported",
+                    rename.path.display()
+                ))
+                .into());
+            }
+        }
+
+        for hunk in &mut plan.matches {
+            hunk.file = root.join(&hunk.file);
+        }
+        for rename in &mut plan.paths {
+            rename.path = root.join(&rename.path);
+            rename.new_path = root.join(&rename.new_path);
+        }
+        Ok(plan)
+    }
+}
+
+/// Whether the relative `path` stays inside whatever root it is joined to:
+/// not absolute and without `..`
+pub(crate) fn is_inside_root(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// Move `hunk` to the line in `content` that matches its original line,
+/// nearest to where it was
+fn reanchor(hunk: &mut MatchHunk, content: &str) -> Option<()> {
+    let line_before = hunk.line_before.as_deref()?.trim_end_matches(['\r', '\n']);
+    let original_line = usize::try_from(hunk.line).ok()?.checked_sub(1)?;
+    let line_starts = std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1));
+    let (index, line_start) = line_starts
+        .enumerate()
+        .filter(|(_, start)| content[*start..].lines().next() == Some(line_before))
+        .min_by_key(|(index, _)| index.abs_diff(original_line))?;
+
+    let start = line_start + hunk.byte_offset as usize;
+    hunk.end = start + (hunk.end - hunk.start);
+    hunk.start = start;
+    hunk.line = u64::try_from(index + 1).ok()?;
+    Some(())
+}
+
+fn checksum_of(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Apply a unified diff to `content` in memory
+fn apply_patch(content: &str, patch: &str) -> Result<String> {
+    // diffy expects LF line endings, and patches are written with CRLF on Windows
+    #[cfg(windows)]
+    let (content, patch) = (content.replace("\r\n", "\n"), patch.replace("\r\n", "\n"));
+    #[cfg(windows)]
+    let (content, patch) = (content.as_str(), patch.as_str());
+
+    let patch = diffy::Patch::from_str(patch).map_err(|e| anyhow!("Failed to parse patch: {e}"))?;
+    let result =
+        diffy::apply(content, &patch).map_err(|e| anyhow!("Failed to apply patch: {e}"))?;
+
+    #[cfg(windows)]
+    let result = result.replace('\n', "\r\n");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{CoercionMode, Stats};
+    use tempfile::TempDir;
+
+    fn hunk(file: &str, line: u64, column: u32, start: usize, line_before: &str) -> MatchHunk {
+        MatchHunk {
+            start,
+            end: start + 8,
+            line_before: Some(line_before.to_string()),
+            ..MatchHunk::for_test(file, line, column, "old_name", "new_name")
+        }
+    }
+
+    fn bundle(original: &str, renamed: &str, hunks: Vec<MatchHunk>) -> Bundle {
+        Bundle {
+            version: BUNDLE_VERSION,
+            renamify_version: "0.0.0".to_string(),
+            exported_at: "0".to_string(),
+            history_id: "abc".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            plan: Plan {
+                id: "abc".to_string(),
+                created_at: "0".to_string(),
+                search: "old_name".to_string(),
+                replace: "new_name".to_string(),
+                styles: vec![],
+                includes: vec![],
+                excludes: vec![],
+                matches: hunks,
+                paths: vec![],
+                stats: Stats::default(),
+                version: "1.0.0".to_string(),
+                coercion: CoercionMode::Auto,
+                skipped: vec![],
+                near_misses: vec![],
+                suspicious_renames: vec![],
+                warnings: vec![],
+                options: None,
+                created_directories: None,
+            },
+            files: vec![BundleFile {
+                path: PathBuf::from("a.rs"),
+                checksum: checksum_of(original),
+                patch: diffy::create_patch(original, renamed).to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_drifted_file_is_reanchored() {
+        let original = "fn old_name() {}\n\nfn main() {\n    old_name();\n}\n";
+        let renamed = "fn new_name() {}\n\nfn main() {\n    new_name();\n}\n";
+        let bundle = bundle(
+            original,
+            renamed,
+            vec![
+                hunk("a.rs", 1, 3, 3, "fn old_name() {}"),
+                hunk("a.rs", 4, 4, 34, "    old_name();\n"),
+            ],
+        );
+
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.rs"), original).unwrap();
+        assert!(bundle.drift(dir.path()).is_empty());
+
+        let drifted_content = format!("// header\n{original}");
+        fs::write(dir.path().join("a.rs"), &drifted_content).unwrap();
+        let drifted = bundle.drift(dir.path());
+        assert_eq!(drifted, vec![PathBuf::from("a.rs")]);
+
+        let plan = bundle.plan_for(dir.path(), &drifted).unwrap();
+        let starts: Vec<(u64, usize)> = plan.matches.iter().map(|h| (h.line, h.start)).collect();
+        assert_eq!(starts, vec![(2, 13), (5, 44)]);
+        assert_eq!(plan.matches[0].file, dir.path().join("a.rs"));
+
+        fs::write(dir.path().join("a.rs"), "fn other() {}\n").unwrap();
+        let err = bundle.plan_for(dir.path(), &drifted).unwrap_err();
+        assert!(err.to_string().contains("patch does not apply"), "{err}");
+    }
+
+    #[test]
+    fn test_read_refuses_paths_outside_the_root() {
+        assert!(is_inside_root(Path::new("src/a.rs")));
+        assert!(is_inside_root(Path::new("./a.rs")));
+        assert!(!is_inside_root(Path::new("../a.rs")));
+        assert!(!is_inside_root(Path::new("src/../../a.rs")));
+        assert!(!is_inside_root(Path::new("/etc/passwd")));
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rename.bundle.json");
+        let mut bundle = bundle("old_name\n", "new_name\n", vec![]);
+        bundle.write(&path).unwrap();
+        assert!(Bundle::read(&path).is_ok());
+
+        for unsafe_path in ["../outside.rs", "/tmp/outside.rs"] {
+            bundle.files[0].path = PathBuf::from(unsafe_path);
+            bundle.write(&path).unwrap();
+            let err = Bundle::read(&path).unwrap_err();
+            assert!(
+                err.to_string().contains("not relative to the project root"),
+                "{err}"
+            );
+        }
+
+        bundle.files[0].path = PathBuf::from("a.rs");
+        bundle.plan.matches = vec![hunk("../../a.rs", 1, 0, 0, "old_name")];
+        bundle.write(&path).unwrap();
+        assert!(Bundle::read(&path).is_err());
+    }
+}
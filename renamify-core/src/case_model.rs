@@ -1,9 +1,12 @@
+use crate::acronym::AcronymRendering;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::BTreeMap;
 use std::hash::Hash;
 use ts_rs::TS;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub enum Style {
     Snake,          // snake_case
@@ -59,6 +62,146 @@ impl Style {
             Self::UpperSentence,
         ]
     }
+
+    /// The style's name as accepted by `--only-styles` and friends
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Snake => "snake",
+            Self::Kebab => "kebab",
+            Self::Camel => "camel",
+            Self::Pascal => "pascal",
+            Self::ScreamingSnake => "screaming-snake",
+            Self::Title => "title",
+            Self::Train => "train",
+            Self::ScreamingTrain => "screaming-train",
+            Self::Dot => "dot",
+            Self::LowerFlat => "lower-flat",
+            Self::UpperFlat => "upper-flat",
+            Self::Sentence => "sentence",
+            Self::LowerSentence => "lower-sentence",
+            Self::UpperSentence => "upper-sentence",
+        }
+    }
+}
+
+impl std::str::FromStr for Style {
+    type Err = String;
+
+    /// Parse a style name such as `kebab` or `screaming-snake`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase().replace('_', "-");
+        Self::all_styles()
+            .into_iter()
+            .find(|style| style.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::all_styles().into_iter().map(Self::name).collect();
+                format!(
+                    "Unknown style '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How letters change case when identifiers are written in a style
+///
+/// The default follows Unicode's locale-independent mappings, under which the
+/// Turkish `İ` lowercases to `i̇` (`i` and a combining dot above).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaseMapping {
+    /// Unicode's default mappings: `İ` lowercases to `i̇`, `i` uppercases to `I`
+    #[default]
+    Unicode,
+    /// Only ASCII letters change case, other letters are kept as written
+    Ascii,
+    /// Turkish and Azerbaijani dotted and dotless i: `İ` and `i`, `I` and `ı`
+    Turkish,
+}
+
+impl CaseMapping {
+    /// `s` in lowercase
+    pub fn lower(self, s: &str) -> String {
+        match self {
+            Self::Unicode => s.to_lowercase(),
+            Self::Ascii => s.to_ascii_lowercase(),
+            Self::Turkish => {
+                let mut result = String::with_capacity(s.len());
+                let mut chars = s.chars().peekable();
+                while let Some(c) = chars.next() {
+                    match c {
+                        // `I` with a combining dot above is a decomposed `İ`
+                        'I' if chars.next_if_eq(&'\u{307}').is_some() => result.push('i'),
+                        'I' => result.push('ı'),
+                        'İ' => result.push('i'),
+                        _ => result.extend(c.to_lowercase()),
+                    }
+                }
+                result
+            },
+        }
+    }
+
+    /// `s` in uppercase
+    pub fn upper(self, s: &str) -> String {
+        match self {
+            Self::Unicode => s.to_uppercase(),
+            Self::Ascii => s.to_ascii_uppercase(),
+            Self::Turkish => {
+                let mut result = String::with_capacity(s.len());
+                let mut chars = s.chars().peekable();
+                while let Some(c) = chars.next() {
+                    match c {
+                        'i' => {
+                            // `i̇` as left by Unicode lowercasing keeps a single dot
+                            chars.next_if_eq(&'\u{307}');
+                            result.push('İ');
+                        },
+                        'ı' => result.push('I'),
+                        _ => result.extend(c.to_uppercase()),
+                    }
+                }
+                result
+            },
+        }
+    }
+
+    /// `s` with its first letter in uppercase and the rest in lowercase
+    pub fn capitalize(self, s: &str) -> String {
+        let Some(first) = s.chars().next() else {
+            return String::new();
+        };
+        let mut split = first.len_utf8();
+        if s[split..].starts_with('\u{307}') {
+            split += '\u{307}'.len_utf8();
+        }
+        self.upper(&s[..split]) + &self.lower(&s[split..])
+    }
+}
+
+thread_local! {
+    static CASE_MAPPING: Cell<CaseMapping> = const { Cell::new(CaseMapping::Unicode) };
+}
+
+/// Run `f` with `mapping` deciding how letters change case in generated
+/// variants and replacements. Only the current thread is affected, and the
+/// previous mapping is restored afterwards.
+pub fn with_case_mapping<R>(mapping: CaseMapping, f: impl FnOnce() -> R) -> R {
+    struct Restore(CaseMapping);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CASE_MAPPING.set(self.0);
+        }
+    }
+
+    let _restore = Restore(CASE_MAPPING.replace(mapping));
+    f()
+}
+
+pub fn case_mapping() -> CaseMapping {
+    CASE_MAPPING.get()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -251,7 +394,7 @@ pub fn parse_to_tokens_with_acronyms(
     let bytes = s.as_bytes();
     let mut current = Vec::new();
 
-    let debug = std::env::var("DEBUG_TOKENIZE").is_ok();
+    let debug = crate::debug::flags().tokenize;
     if debug {
         eprintln!("=== Tokenizing: '{}' ===", s);
     }
@@ -445,8 +588,13 @@ pub fn parse_to_tokens_with_acronyms(
                     }
                 }
                 // 3. digit to uppercase letter (e.g., "arm64Arch" -> "arm64", "Arch")
-                //    BUT don't split if the digit is part of a known acronym like "2FA"
-                else if prev.is_ascii_digit() && b.is_ascii_uppercase() {
+                //    BUT don't split if the digit is part of a known acronym like "2FA",
+                //    or in all-caps text, where "3D_MODEL" is "3d_model" uppercased
+                else if prev.is_ascii_digit()
+                    && b.is_ascii_uppercase()
+                    && (current.iter().any(u8::is_ascii_lowercase)
+                        || bytes.get(i + 1).is_some_and(u8::is_ascii_lowercase))
+                {
                     // Check if we're in the middle of a known acronym
                     // Look back to find where digits started
                     let mut digit_start = current.len();
@@ -503,67 +651,59 @@ pub fn parse_to_tokens_with_acronyms(
 }
 
 pub fn to_style(model: &TokenModel, style: Style) -> String {
+    to_style_with_rendering(model, style, crate::acronym::rendering())
+}
+
+/// Like [`to_style`], with known acronyms in `camelCase` and `PascalCase` written
+/// by `rendering` rather than the configured policy
+pub fn to_style_with_rendering(
+    model: &TokenModel,
+    style: Style,
+    rendering: AcronymRendering,
+) -> String {
     if model.tokens.is_empty() {
         return String::new();
     }
 
+    let mapping = case_mapping();
     match style {
         Style::Snake => model
             .tokens
             .iter()
-            .map(|t| t.text.to_lowercase())
+            .map(|t| mapping.lower(&t.text))
             .collect::<Vec<_>>()
             .join("_"),
 
         Style::Kebab => model
             .tokens
             .iter()
-            .map(|t| t.text.to_lowercase())
+            .map(|t| mapping.lower(&t.text))
             .collect::<Vec<_>>()
             .join("-"),
 
         Style::Camel => {
-            let acronym_set = crate::acronym::get_default_acronym_set();
             let mut result = String::new();
             for (i, token) in model.tokens.iter().enumerate() {
                 if i == 0 {
-                    result.push_str(&token.text.to_lowercase());
+                    result.push_str(&mapping.lower(&token.text));
                 } else {
-                    // If the token is an all-uppercase known acronym, preserve it
-                    if token.text.chars().all(|c| c.is_ascii_uppercase())
-                        && acronym_set.is_acronym(&token.text)
-                    {
-                        result.push_str(&token.text);
-                    } else {
-                        result.push_str(&capitalize_first(&token.text));
-                    }
+                    result.push_str(&capitalize_word(&token.text, i, rendering));
                 }
             }
             result
         },
 
-        Style::Pascal => {
-            let acronym_set = crate::acronym::get_default_acronym_set();
-            model
-                .tokens
-                .iter()
-                .map(|t| {
-                    // If the token is an all-uppercase known acronym, preserve it
-                    if t.text.chars().all(|c| c.is_ascii_uppercase())
-                        && acronym_set.is_acronym(&t.text)
-                    {
-                        t.text.clone()
-                    } else {
-                        capitalize_first(&t.text)
-                    }
-                })
-                .collect::<String>()
-        },
+        Style::Pascal => model
+            .tokens
+            .iter()
+            .enumerate()
+            .map(|(i, t)| capitalize_word(&t.text, i, rendering))
+            .collect::<String>(),
 
         Style::ScreamingSnake => model
             .tokens
             .iter()
-            .map(|t| t.text.to_uppercase())
+            .map(|t| mapping.upper(&t.text))
             .collect::<Vec<_>>()
             .join("_"),
 
@@ -596,27 +736,27 @@ pub fn to_style(model: &TokenModel, style: Style) -> String {
         Style::ScreamingTrain => model
             .tokens
             .iter()
-            .map(|t| t.text.to_uppercase())
+            .map(|t| mapping.upper(&t.text))
             .collect::<Vec<_>>()
             .join("-"),
 
         Style::Dot => model
             .tokens
             .iter()
-            .map(|t| t.text.to_lowercase())
+            .map(|t| mapping.lower(&t.text))
             .collect::<Vec<_>>()
             .join("."),
 
         Style::LowerFlat => model
             .tokens
             .iter()
-            .map(|t| t.text.to_lowercase())
+            .map(|t| mapping.lower(&t.text))
             .collect::<String>(),
 
         Style::UpperFlat => model
             .tokens
             .iter()
-            .map(|t| t.text.to_uppercase())
+            .map(|t| mapping.upper(&t.text))
             .collect::<String>(),
 
         Style::Sentence => {
@@ -628,7 +768,7 @@ pub fn to_style(model: &TokenModel, style: Style) -> String {
                 if i == 0 {
                     result.push(capitalize_first(&token.text));
                 } else {
-                    result.push(token.text.to_lowercase());
+                    result.push(mapping.lower(&token.text));
                 }
             }
             result.join(" ")
@@ -637,33 +777,50 @@ pub fn to_style(model: &TokenModel, style: Style) -> String {
         Style::LowerSentence => model
             .tokens
             .iter()
-            .map(|t| t.text.to_lowercase())
+            .map(|t| mapping.lower(&t.text))
             .collect::<Vec<_>>()
             .join(" "),
 
         Style::UpperSentence => model
             .tokens
             .iter()
-            .map(|t| t.text.to_uppercase())
+            .map(|t| mapping.upper(&t.text))
             .collect::<Vec<_>>()
             .join(" "),
     }
 }
 
-fn capitalize_first(s: &str) -> String {
-    if s.is_empty() {
-        return String::new();
+/// Write the `index`th word of a `camelCase` or `PascalCase` identifier, where a
+/// new word starts with an uppercase letter
+pub(crate) fn capitalize_word(word: &str, index: usize, rendering: AcronymRendering) -> String {
+    let acronym_set = crate::acronym::get_default_acronym_set();
+    match rendering {
+        AcronymRendering::PreserveUpper => {
+            if word.chars().all(|c| c.is_ascii_uppercase()) && acronym_set.is_acronym(word) {
+                word.to_string()
+            } else {
+                capitalize_first(word)
+            }
+        },
+        AcronymRendering::Lower if index > 0 && acronym_set.is_acronym(&word.to_uppercase()) => {
+            case_mapping().lower(word)
+        },
+        AcronymRendering::Pascalize | AcronymRendering::Lower => {
+            if acronym_set.is_acronym(&word.to_uppercase()) {
+                case_mapping().capitalize(word)
+            } else {
+                capitalize_first(word)
+            }
+        },
     }
+}
 
+fn capitalize_first(s: &str) -> String {
     if s.bytes().all(|b| b.is_ascii_uppercase()) && s.len() <= 2 {
         return s.to_string();
     }
 
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
-    }
+    case_mapping().capitalize(s)
 }
 
 pub fn generate_variant_map(
@@ -757,7 +914,7 @@ fn generate_variant_map_internal(
 
     let mut map = BTreeMap::new();
 
-    if std::env::var("RENAMIFY_DEBUG_VARIANTS").is_ok() {
+    if crate::debug::flags().variants {
         eprintln!(
             "DEBUG VARIANTS: Generating variants for '{}' -> '{}'",
             search, replace
@@ -780,7 +937,7 @@ fn generate_variant_map_internal(
                 to_style(replace_model, *style)
             };
 
-            if std::env::var("RENAMIFY_DEBUG_VARIANTS").is_ok() {
+            if crate::debug::flags().variants {
                 eprintln!(
                     "DEBUG VARIANTS: Style {:?} -> '{}' => '{}'",
                     style, search_variant, replace_variant
@@ -804,7 +961,7 @@ fn generate_variant_map_internal(
     // Removed automatic case variants - they were causing incorrect matches
     // All variants should come from the explicit style system only
 
-    if std::env::var("RENAMIFY_DEBUG_VARIANTS").is_ok() {
+    if crate::debug::flags().variants {
         eprintln!(
             "DEBUG VARIANTS: Final variant map has {} entries",
             map.len()
@@ -924,6 +1081,19 @@ mod tests {
         assert_eq!(tokens.tokens[1].text, "2FA");
     }
 
+    #[test]
+    fn test_parse_digits_in_all_caps() {
+        // Written from "3d_model", so it should read back the same way
+        let tokens = parse_to_tokens("MODEL_3D_VIEW");
+        let texts: Vec<&str> = tokens.tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["MODEL", "3D", "VIEW"]);
+
+        // Mixed case still splits before the uppercase letter
+        let tokens = parse_to_tokens("arm64Arch");
+        let texts: Vec<&str> = tokens.tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["arm64", "Arch"]);
+    }
+
     #[test]
     fn test_detect_snake_case() {
         assert_eq!(detect_style("hello_world"), Some(Style::Snake));
@@ -1680,4 +1850,66 @@ mod tests {
         // DocSpring should not have word-separated variants
         assert!(!map.contains_key("doc_spring"));
     }
+
+    #[test]
+    fn test_to_style_acronym_rendering() {
+        let model = parse_to_tokens("get_API_client");
+        let render = |style, rendering| to_style_with_rendering(&model, style, rendering);
+
+        assert_eq!(
+            render(Style::Camel, AcronymRendering::PreserveUpper),
+            "getAPIClient"
+        );
+        assert_eq!(
+            render(Style::Camel, AcronymRendering::Pascalize),
+            "getApiClient"
+        );
+        assert_eq!(
+            render(Style::Camel, AcronymRendering::Lower),
+            "getapiClient"
+        );
+        assert_eq!(
+            render(Style::Pascal, AcronymRendering::Pascalize),
+            "GetApiClient"
+        );
+
+        // Two-letter acronyms and leading acronyms follow the policy too
+        let model = parse_to_tokens("ID_token");
+        assert_eq!(
+            to_style_with_rendering(&model, Style::Pascal, AcronymRendering::Pascalize),
+            "IdToken"
+        );
+        assert_eq!(
+            to_style_with_rendering(&model, Style::Pascal, AcronymRendering::Lower),
+            "IdToken"
+        );
+        assert_eq!(
+            to_style_with_rendering(&model, Style::Camel, AcronymRendering::PreserveUpper),
+            "idToken"
+        );
+    }
+
+    #[test]
+    fn test_case_mapping() {
+        // Unicode lowercases İ to i and a combining dot above
+        assert_eq!(CaseMapping::Unicode.lower("İSTANBUL"), "i\u{307}stanbul");
+        assert_eq!(CaseMapping::Unicode.upper("ırmak"), "IRMAK");
+
+        // ASCII only changes ASCII letters
+        assert_eq!(CaseMapping::Ascii.lower("İSTANBUL_Ünİ"), "İstanbul_Ünİ");
+        assert_eq!(CaseMapping::Ascii.upper("istanbul_ünı"), "ISTANBUL_üNı");
+
+        // Turkish pairs dotted and dotless i
+        let turkish = CaseMapping::Turkish;
+        assert_eq!(turkish.lower("İSTANBUL"), "istanbul");
+        assert_eq!(turkish.lower("IRMAK"), "ırmak");
+        assert_eq!(turkish.upper("istanbul"), "İSTANBUL");
+        assert_eq!(turkish.upper("ırmak"), "IRMAK");
+        assert_eq!(turkish.capitalize("istanbul"), "İstanbul");
+
+        // Decomposed dotted i converts to a single dotted letter
+        assert_eq!(turkish.lower("I\u{307}STANBUL"), "istanbul");
+        assert_eq!(turkish.upper("i\u{307}stanbul"), "İSTANBUL");
+        assert_eq!(turkish.capitalize("i\u{307}stanbul"), "İstanbul");
+    }
 }
@@ -0,0 +1,151 @@
+//! CODEOWNERS parsing, used to split a plan by the team that owns each file.
+//!
+//! Patterns follow GitHub's rules: a pattern with a leading or inner `/` is
+//! anchored to the repository root, other patterns match at any depth, and a
+//! pattern that matches a directory owns everything below it, except that
+//! `dir/*` only owns the files directly inside `dir`. The last matching rule
+//! wins, and a rule without owners leaves its paths unowned.
+
+use crate::error::RenamifyError;
+use anyhow::Result;
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::{Path, PathBuf};
+
+/// Where GitHub looks for CODEOWNERS, in the order it looks
+pub const CODEOWNERS_LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug)]
+struct Rule {
+    matcher: GlobMatcher,
+    /// Matches directories only, and so only owns paths below them
+    dir_only: bool,
+    /// `dir/*`, which doesn't own paths nested deeper
+    children_only: bool,
+    owners: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Load the first CODEOWNERS file GitHub would use in `root`
+    pub fn find(root: &Path) -> Result<(PathBuf, Self)> {
+        let path = CODEOWNERS_LOCATIONS
+            .iter()
+            .map(|location| root.join(location))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                RenamifyError::NotFound(format!(
+                    "No CODEOWNERS file in {} (looked for {})",
+                    root.display(),
+                    CODEOWNERS_LOCATIONS.join(", ")
+                ))
+            })?;
+        let content = std::fs::read_to_string(&path)?;
+        let owners = Self::parse(&content).map_err(|e| {
+            RenamifyError::InvalidInput(format!("Invalid CODEOWNERS {}: {e}", path.display()))
+        })?;
+        Ok((path, owners))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners = fields
+                .take_while(|field| !field.starts_with('#'))
+                .map(ToString::to_string)
+                .collect();
+            let rule = Rule::new(pattern.replace("\\#", "#").as_str(), owners)
+                .map_err(|e| format!("line {}: {e}", index + 1))?;
+            rules.push(rule);
+        }
+        Ok(Self { rules })
+    }
+
+    /// The owners of `path`, relative to the repository root; empty when
+    /// unowned
+    pub fn owners(&self, path: &Path, is_dir: bool) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.owns(path, is_dir))
+            .map_or(&[], |rule| rule.owners.as_slice())
+    }
+}
+
+impl Rule {
+    fn new(pattern: &str, owners: Vec<String>) -> Result<Self, globset::Error> {
+        let dir_only = pattern.ends_with('/');
+        let children_only = pattern.ends_with("/*");
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.contains('/');
+        let glob = match trimmed.strip_prefix('/') {
+            Some(rest) => rest.to_string(),
+            None if anchored => trimmed.to_string(),
+            None => format!("**/{trimmed}"),
+        };
+        let matcher = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()?
+            .compile_matcher();
+        Ok(Self {
+            matcher,
+            dir_only,
+            children_only,
+            owners,
+        })
+    }
+
+    fn owns(&self, path: &Path, is_dir: bool) -> bool {
+        if (is_dir || !self.dir_only) && self.matcher.is_match(path) {
+            return true;
+        }
+        !self.children_only
+            && path
+                .ancestors()
+                .skip(1)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .any(|dir| self.matcher.is_match(dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owners() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n\
+             *       @org/core\n\
+             /docs/  @org/docs # Documentation\n\
+             *.md    @org/writers\n\
+             src/billing @org/billing @alice\n\
+             assets/* @org/design\n\
+             generated/\n",
+        )
+        .unwrap();
+        let owners_of = |path: &str| owners.owners(Path::new(path), false).join(" ");
+
+        assert_eq!(owners_of("src/main.rs"), "@org/core");
+        assert_eq!(owners_of("docs/guide/intro.txt"), "@org/docs");
+        assert_eq!(owners_of("docs/README.md"), "@org/writers");
+        assert_eq!(owners_of("src/billing/invoice.rs"), "@org/billing @alice");
+        assert_eq!(owners_of("lib/src/billing/invoice.rs"), "@org/core");
+        assert_eq!(owners_of("assets/logo.svg"), "@org/design");
+        assert_eq!(owners_of("assets/icons/logo.svg"), "@org/core");
+        assert_eq!(owners_of("generated/api.rs"), "");
+        assert_eq!(owners.owners(Path::new("docs"), true), ["@org/docs"]);
+        assert_eq!(owners.owners(Path::new("docs"), false), ["@org/core"]);
+    }
+}
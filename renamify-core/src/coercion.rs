@@ -1,10 +1,13 @@
 use crate::acronym::get_default_acronym_set;
+use crate::case_model::case_mapping;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 /// Detected style of an identifier or path segment
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export, rename = "CoercionStyle")]
+#[schemars(rename = "CoercionStyle")]
 pub enum Style {
     /// `snake_case`
     Snake,
@@ -38,6 +41,54 @@ pub enum Style {
     Mixed,
 }
 
+const STYLE_NAMES: [(Style, &str); 14] = [
+    (Style::Snake, "snake"),
+    (Style::Kebab, "kebab"),
+    (Style::Camel, "camel"),
+    (Style::Pascal, "pascal"),
+    (Style::ScreamingSnake, "screaming-snake"),
+    (Style::Title, "title"),
+    (Style::Train, "train"),
+    (Style::ScreamingTrain, "screaming-train"),
+    (Style::Dot, "dot"),
+    (Style::LowerFlat, "lower-flat"),
+    (Style::UpperFlat, "upper-flat"),
+    (Style::Sentence, "sentence"),
+    (Style::LowerSentence, "lower-sentence"),
+    (Style::UpperSentence, "upper-sentence"),
+];
+
+impl Style {
+    /// Name of the style as written on the command line, e.g. `screaming-snake`
+    pub fn name(self) -> &'static str {
+        STYLE_NAMES
+            .iter()
+            .find(|(style, _)| *style == self)
+            .map_or("mixed", |(_, name)| name)
+    }
+}
+
+impl std::str::FromStr for Style {
+    type Err = String;
+
+    /// Parse a concrete style name; `mixed` is not a style that can be produced
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase().replace('_', "-");
+        STYLE_NAMES
+            .iter()
+            .find(|(_, candidate)| *candidate == name)
+            .map(|(style, _)| *style)
+            .ok_or_else(|| {
+                let names: Vec<&str> = STYLE_NAMES.iter().map(|(_, name)| *name).collect();
+                format!(
+                    "Unknown style '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
 /// Token representing a word in an identifier
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Token {
@@ -334,7 +385,7 @@ pub fn tokenize(s: &str) -> Vec<Token> {
                     let is_acronym =
                         current_word.chars().all(char::is_uppercase) && current_word.len() > 1;
                     tokens.push(Token {
-                        word: current_word.to_lowercase(),
+                        word: case_mapping().lower(&current_word),
                         is_acronym,
                     });
                     current_word.clear();
@@ -349,7 +400,7 @@ pub fn tokenize(s: &str) -> Vec<Token> {
                     // Transition from lowercase to uppercase - new word
                     if !current_word.is_empty() {
                         tokens.push(Token {
-                            word: current_word.to_lowercase(),
+                            word: case_mapping().lower(&current_word),
                             is_acronym: false,
                         });
                         current_word.clear();
@@ -372,7 +423,7 @@ pub fn tokenize(s: &str) -> Vec<Token> {
                     let last_upper = current_word.pop().unwrap();
                     if !current_word.is_empty() {
                         tokens.push(Token {
-                            word: current_word.to_lowercase(),
+                            word: case_mapping().lower(&current_word),
                             is_acronym: true,
                         });
                     }
@@ -396,7 +447,7 @@ pub fn tokenize(s: &str) -> Vec<Token> {
                     let is_acronym =
                         current_word.chars().all(char::is_uppercase) && current_word.len() > 1;
                     tokens.push(Token {
-                        word: current_word.to_lowercase(),
+                        word: case_mapping().lower(&current_word),
                         is_acronym,
                     });
                     current_word.clear();
@@ -412,7 +463,7 @@ pub fn tokenize(s: &str) -> Vec<Token> {
     if !current_word.is_empty() {
         let is_acronym = current_word.chars().all(char::is_uppercase) && current_word.len() > 1;
         tokens.push(Token {
-            word: current_word.to_lowercase(),
+            word: case_mapping().lower(&current_word),
             is_acronym,
         });
     }
@@ -426,6 +477,7 @@ pub fn render_tokens(tokens: &[Token], style: Style) -> String {
         return String::new();
     }
 
+    let mapping = case_mapping();
     match style {
         Style::Snake => tokens
             .iter()
@@ -454,7 +506,7 @@ pub fn render_tokens(tokens: &[Token], style: Style) -> String {
             .collect::<String>(),
         Style::ScreamingSnake => tokens
             .iter()
-            .map(|t| t.word.to_uppercase())
+            .map(|t| mapping.upper(&t.word))
             .collect::<Vec<_>>()
             .join("_"),
         Style::Title => tokens
@@ -469,7 +521,7 @@ pub fn render_tokens(tokens: &[Token], style: Style) -> String {
             .join("-"),
         Style::ScreamingTrain => tokens
             .iter()
-            .map(|t| t.word.to_uppercase())
+            .map(|t| mapping.upper(&t.word))
             .collect::<Vec<_>>()
             .join("-"),
         Style::Dot => tokens
@@ -480,7 +532,7 @@ pub fn render_tokens(tokens: &[Token], style: Style) -> String {
         Style::LowerFlat => tokens.iter().map(|t| t.word.clone()).collect::<String>(),
         Style::UpperFlat => tokens
             .iter()
-            .map(|t| t.word.to_uppercase())
+            .map(|t| mapping.upper(&t.word))
             .collect::<String>(),
         Style::Sentence => {
             let mut result = String::new();
@@ -503,7 +555,7 @@ pub fn render_tokens(tokens: &[Token], style: Style) -> String {
             .join(" "),
         Style::UpperSentence => tokens
             .iter()
-            .map(|t| t.word.to_uppercase())
+            .map(|t| mapping.upper(&t.word))
             .collect::<Vec<_>>()
             .join(" "),
         Style::Mixed => {
@@ -675,10 +727,12 @@ fn replace_case_insensitive(text: &str, pattern: &str, replacement: &str) -> Str
 }
 
 fn capitalize(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
+    match s.chars().next() {
         None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        Some(first) => {
+            let (first, rest) = s.split_at(first.len_utf8());
+            case_mapping().upper(first) + rest
+        },
     }
 }
 
@@ -1,5 +1,12 @@
-use crate::case_model::{parse_to_tokens, to_style, Style, Token, TokenModel};
+use crate::case_model::{
+    capitalize_word, case_mapping, detect_style, parse_to_tokens, to_style, Style, Token,
+    TokenModel,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::ops::Range;
+use ts_rs::TS;
 
 /// Extract special prefixes (like __, _) from an identifier
 /// Returns (prefix, `identifier_without_prefix`)
@@ -57,7 +64,7 @@ pub fn find_compound_variants(
     let (prefix, identifier_without_prefix) = extract_prefix(identifier);
 
     // Debug: print styles array being passed
-    if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+    if crate::debug::flags().compound {
         println!("find_compound_variants called with styles: {:?}", styles);
         if !prefix.is_empty() {
             println!("  Extracted prefix: '{}' from '{}'", prefix, identifier);
@@ -70,7 +77,7 @@ pub fn find_compound_variants(
     let new_tokens = parse_to_tokens(new_pattern);
 
     // Debug: Print token info
-    if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+    if crate::debug::flags().compound {
         println!(
             "identifier_tokens for '{}': {:?}",
             identifier_without_prefix, identifier_tokens
@@ -138,7 +145,7 @@ pub fn find_compound_variants(
 
     // Guard against empty pattern_len or empty replacement_tokens
     if pattern_len == 0 || replacement_tokens.is_empty() {
-        if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+        if crate::debug::flags().compound {
             println!(
                 "Returning early: pattern_len={}, replacement_tokens.len()={}",
                 pattern_len,
@@ -237,19 +244,15 @@ pub fn find_compound_variants(
                 match style {
                     Style::Pascal | Style::Camel => {
                         // For PascalCase and camelCase, join into a single token
+                        let rendering = crate::acronym::rendering();
                         let mut result = String::new();
                         for (i, token) in new_tokens.tokens.iter().enumerate() {
                             let cased =
                                 if style == Style::Pascal || (style == Style::Camel && i > 0) {
-                                    // Capitalize first letter
-                                    let mut text = token.text.clone();
-                                    if let Some(first_char) = text.chars().next() {
-                                        text = first_char.to_uppercase().to_string() + &text[1..];
-                                    }
-                                    text
+                                    capitalize_word(&token.text, pos + i, rendering)
                                 } else {
                                     // camelCase first token is lowercase
-                                    token.text.to_lowercase()
+                                    case_mapping().lower(&token.text)
                                 };
                             result.push_str(&cased);
                         }
@@ -260,7 +263,7 @@ pub fn find_compound_variants(
                         // For Train case, generate the Train-case replacement and split it
                         let new_model = TokenModel::new(new_tokens.tokens.clone());
                         let train_replacement = to_style(&new_model, Style::Train);
-                        if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+                        if crate::debug::flags().compound {
                             println!("    Train case replacement: '{}'", train_replacement);
                         }
                         // Split on hyphens to get individual tokens
@@ -268,7 +271,7 @@ pub fn find_compound_variants(
                             .split('-')
                             .map(|s| Token::new(s.to_string()))
                             .collect();
-                        if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+                        if crate::debug::flags().compound {
                             println!("    Train case tokens: {:?}", tokens);
                         }
                         tokens
@@ -280,9 +283,9 @@ pub fn find_compound_variants(
                             .iter()
                             .map(|token| {
                                 let text = if style == Style::ScreamingSnake {
-                                    token.text.to_uppercase()
+                                    case_mapping().upper(&token.text)
                                 } else {
-                                    token.text.to_lowercase()
+                                    case_mapping().lower(&token.text)
                                 };
                                 Token::new(text)
                             })
@@ -293,7 +296,7 @@ pub fn find_compound_variants(
                         new_tokens
                             .tokens
                             .iter()
-                            .map(|token| Token::new(token.text.to_lowercase()))
+                            .map(|token| Token::new(case_mapping().lower(&token.text)))
                             .collect()
                     },
                     _ => {
@@ -317,8 +320,8 @@ pub fn find_compound_variants(
                         // Original token started with uppercase, preserve it
                         let mut adjusted_token = Token::new(new_token.text.clone());
                         if let Some(first_char) = adjusted_token.text.chars().next() {
-                            adjusted_token.text =
-                                first_char.to_uppercase().to_string() + &adjusted_token.text[1..];
+                            let (first, rest) = adjusted_token.text.split_at(first_char.len_utf8());
+                            adjusted_token.text = case_mapping().upper(first) + rest;
                         }
                         new_tokens_adjusted.push(adjusted_token);
                     } else {
@@ -345,7 +348,7 @@ pub fn find_compound_variants(
     // If we made any replacements, create the compound match
     if replacements_made > 0 {
         // Debug: print compound matching attempt
-        if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+        if crate::debug::flags().compound {
             println!(
                 "Found {} replacements in '{}' -> trying to create compound match",
                 replacements_made, identifier
@@ -355,7 +358,7 @@ pub fn find_compound_variants(
         // Detect the style of the original identifier (without prefix)
         let detected_style = crate::case_model::detect_style(identifier_without_prefix);
 
-        if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+        if crate::debug::flags().compound {
             println!(
                 "  detect_style('{}') returned: {:?}",
                 identifier_without_prefix, detected_style
@@ -387,7 +390,7 @@ pub fn find_compound_variants(
 
         if let Some(style) = inferred_style {
             // Debug: print style detection
-            if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+            if crate::debug::flags().compound {
                 println!(
                     "  Detected style: {:?} for '{}'",
                     style, identifier_without_prefix
@@ -494,7 +497,7 @@ pub fn find_compound_variants(
                 });
             } else {
                 // Debug: style not in target styles
-                if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+                if crate::debug::flags().compound {
                     println!(
                         "  Style {:?} not in target styles for '{}', skipping",
                         style, identifier_without_prefix
@@ -506,7 +509,7 @@ pub fn find_compound_variants(
             }
         } else {
             // Debug: no style detected
-            if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
+            if crate::debug::flags().compound {
                 println!(
                     "  No style detected for '{}', skipping",
                     identifier_without_prefix
@@ -538,6 +541,114 @@ pub fn generate_compound_variants(
     BTreeMap::new()
 }
 
+/// How an identifier was matched, if at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplainedMatch {
+    /// The identifier is the search term written in one of the styles
+    Exact,
+    /// The search term is part of a larger identifier
+    Compound,
+    /// The identifier is left alone
+    None,
+}
+
+/// Why an identifier does or doesn't match the search term
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct Explanation {
+    pub identifier: String,
+    /// Leading underscores set aside before tokenizing
+    pub prefix: String,
+    /// Tokens of the identifier without its prefix
+    pub tokens: Vec<String>,
+    pub search_tokens: Vec<String>,
+    /// Case style of the identifier without its prefix, if it has one
+    pub style: Option<Style>,
+    /// Token ranges of the identifier that spell out the search term
+    pub segments: Vec<Range<usize>>,
+    #[serde(rename = "match")]
+    pub matched: ExplainedMatch,
+    /// The renamed identifier, when it matches and a replacement was given
+    pub replacement: Option<String>,
+    pub reason: String,
+}
+
+/// Explain how `identifier` is tokenized, where the search term appears in it
+/// and what it would be renamed to. An empty `replace` explains a search.
+///
+/// This only covers the case model; filters such as `--exclude-match` or
+/// `--skip-strings` are not taken into account.
+pub fn explain(identifier: &str, search: &str, replace: &str, styles: &[Style]) -> Explanation {
+    let (prefix, rest) = extract_prefix(identifier);
+    let tokens = parse_to_tokens(rest).tokens;
+    let search_model = parse_to_tokens(search);
+    let replace_model = parse_to_tokens(replace);
+    let style = detect_style(rest);
+
+    let width = search_model.tokens.len();
+    let segments: Vec<Range<usize>> = if width == 0 || width > tokens.len() {
+        Vec::new()
+    } else {
+        (0..=tokens.len() - width)
+            .filter(|&start| tokens_match(&tokens[start..start + width], &search_model.tokens))
+            .map(|start| start..start + width)
+            .collect()
+    };
+
+    let texts =
+        |tokens: &[Token]| -> Vec<String> { tokens.iter().map(|t| t.text.clone()).collect() };
+    let mut explanation = Explanation {
+        identifier: identifier.to_string(),
+        prefix: prefix.to_string(),
+        tokens: texts(&tokens),
+        search_tokens: texts(&search_model.tokens),
+        style,
+        segments,
+        matched: ExplainedMatch::None,
+        replacement: None,
+        reason: String::new(),
+    };
+
+    if let Some(exact) = styles
+        .iter()
+        .find(|&&s| to_style(&search_model, s) == identifier)
+    {
+        explanation.matched = ExplainedMatch::Exact;
+        explanation.replacement = (!replace.is_empty()).then(|| to_style(&replace_model, *exact));
+        explanation.reason = format!("'{identifier}' is the {} form of '{search}'", exact.name());
+        return explanation;
+    }
+
+    // Searches have no replacement, so probe with the search term itself
+    let probe = if replace.is_empty() { search } else { replace };
+    if let Some(compound) = find_compound_variants(identifier, search, probe, styles).first() {
+        explanation.matched = ExplainedMatch::Compound;
+        explanation.replacement = (!replace.is_empty()).then(|| compound.replacement.clone());
+        explanation.reason = format!(
+            "'{search}' is part of the {} identifier '{identifier}'",
+            compound.style.name()
+        );
+        return explanation;
+    }
+
+    explanation.reason = if explanation.segments.is_empty() {
+        format!(
+            "the tokens of '{search}' ({}) do not appear next to each other in '{identifier}'",
+            explanation.search_tokens.join(", ")
+        )
+    } else if let Some(style) = style.filter(|s| !styles.contains(s)) {
+        format!(
+            "'{rest}' is {} case, which is not one of the styles in use",
+            style.name()
+        )
+    } else {
+        format!("'{rest}' mixes case styles, so there is no style to write the replacement in")
+    };
+    explanation
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -608,4 +719,35 @@ mod tests {
         assert_eq!(matches[0].full_identifier, "oldNameAndOldName");
         assert_eq!(matches[0].replacement, "newNameAndNewName");
     }
+
+    #[test]
+    fn test_explain() {
+        let styles = Style::default_styles();
+
+        let compound = explain("SomeOldNameFactory", "old_name", "new_name", &styles);
+        assert_eq!(compound.tokens, vec!["Some", "Old", "Name", "Factory"]);
+        assert_eq!(compound.segments, vec![1..3]);
+        assert_eq!(compound.matched, ExplainedMatch::Compound);
+        assert_eq!(compound.replacement.as_deref(), Some("SomeNewNameFactory"));
+
+        let exact = explain("OLD_NAME", "old_name", "", &styles);
+        assert_eq!(exact.matched, ExplainedMatch::Exact);
+        assert_eq!(exact.replacement, None);
+        assert_eq!(
+            exact.reason,
+            "'OLD_NAME' is the screaming-snake form of 'old_name'"
+        );
+
+        let apart = explain("OldFooName", "old_name", "new_name", &styles);
+        assert_eq!(apart.matched, ExplainedMatch::None);
+        assert!(apart.segments.is_empty());
+
+        let excluded = explain("SomeOldName", "old_name", "new_name", &[Style::Snake]);
+        assert_eq!(excluded.matched, ExplainedMatch::None);
+        assert_eq!(excluded.segments, vec![1..3]);
+        assert_eq!(
+            excluded.reason,
+            "'SomeOldName' is pascal case, which is not one of the styles in use"
+        );
+    }
 }
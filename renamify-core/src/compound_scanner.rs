@@ -1,7 +1,7 @@
 use crate::case_model::Style;
 use crate::compound_matcher::find_compound_variants;
-use crate::pattern::{build_pattern, is_boundary, Match};
-use crate::scanner::{CoercionMode, MatchHunk, VariantMap};
+use crate::pattern::{build_pattern, is_boundary, LineTracker, Match};
+use crate::scanner::{BoundaryClass, CoercionMode, MatchHunk, MatchKind, VariantMap};
 use bstr::ByteSlice;
 use regex::bytes::Regex;
 use std::collections::BTreeSet;
@@ -41,7 +41,7 @@ impl IdentifierExtractor {
         for m in self.regex.find_iter(content) {
             let identifier = String::from_utf8_lossy(m.as_bytes()).to_string();
 
-            if std::env::var("RENAMIFY_DEBUG_IDENTIFIERS").is_ok() {
+            if crate::debug::flags().identifiers {
                 println!(
                     "Found identifier: '{}' at {}-{}",
                     identifier,
@@ -110,7 +110,11 @@ fn byte_offset_to_char_offset(text: &str, byte_offset: usize) -> usize {
     char_offset
 }
 
-/// Enhanced matching that finds both exact and compound matches
+/// Enhanced matching that finds both exact and compound matches.
+///
+/// `candidate_lines` are the lines a cheap literal prefilter found any variant
+/// or search token on. Identifiers are only extracted from those lines, since
+/// a compound match has to contain one of them.
 pub fn find_enhanced_matches(
     content: &[u8],
     file: &str,
@@ -120,6 +124,7 @@ pub fn find_enhanced_matches(
     styles: &[Style],
     identifier_extractor: &IdentifierExtractor,
     additional_lines: Option<&BTreeSet<usize>>,
+    candidate_lines: Option<&BTreeSet<usize>>,
 ) -> Vec<Match> {
     let mut all_matches = Vec::new();
     let mut processed_ranges = Vec::new(); // Track (start, end) ranges that were exactly matched
@@ -137,38 +142,25 @@ pub fn find_enhanced_matches(
     if !skip_exact_match {
         let variants: Vec<String> = variant_map.keys().cloned().collect();
         if let Ok(pattern) = build_pattern(&variants) {
-            for m in pattern.regex.find_iter(content) {
-                if !is_boundary(content, m.start(), m.end()) {
+            let mut lines = LineTracker::new(content);
+            for m in pattern.find_iter(content) {
+                if !is_boundary(content, m.start, m.end) {
                     continue;
                 }
 
-                let match_text = m.as_bytes();
-                let variant = pattern
-                    .identify_variant(match_text)
-                    .unwrap_or_default()
-                    .to_string();
-
-                #[allow(clippy::naive_bytecount)]
-                let line_number = content[..m.start()].iter().filter(|&&b| b == b'\n').count() + 1;
-
-                let line_start = content[..m.start()]
-                    .iter()
-                    .rposition(|&b| b == b'\n')
-                    .map_or(0, |p| p + 1);
-
-                let column = m.start() - line_start;
+                let (line_number, column) = lines.locate(m.start);
 
                 // Mark this range as processed
-                processed_ranges.push((m.start(), m.end()));
+                processed_ranges.push((m.start, m.end));
 
                 all_matches.push(Match {
                     file: file.to_string(),
                     line: line_number,
                     column,
-                    start: m.start(),
-                    end: m.end(),
-                    variant: variant.clone(),
-                    text: String::from_utf8_lossy(match_text).to_string(),
+                    start: m.start,
+                    end: m.end,
+                    variant: m.variant.to_string(),
+                    text: String::from_utf8_lossy(&content[m.start..m.end]).to_string(),
                 });
             }
         }
@@ -177,57 +169,30 @@ pub fn find_enhanced_matches(
     // Third, find all identifiers and check for compound matches
     {
         let identifiers = if processed_ranges.is_empty() {
-            identifier_extractor.find_all(content)
+            match candidate_lines {
+                Some(lines) if !lines.is_empty() => {
+                    find_identifiers_on_lines(content, lines, identifier_extractor)
+                },
+                _ => identifier_extractor.find_all(content),
+            }
         } else {
-            let mut candidate_lines = BTreeSet::new();
+            let mut nearby_lines = BTreeSet::new();
             for m in &all_matches {
-                candidate_lines.insert(m.line);
+                nearby_lines.insert(m.line);
                 if m.line > 1 {
-                    candidate_lines.insert(m.line - 1);
+                    nearby_lines.insert(m.line - 1);
                 }
-                candidate_lines.insert(m.line + 1);
+                nearby_lines.insert(m.line + 1);
             }
 
             if let Some(extra_lines) = additional_lines {
-                candidate_lines.extend(extra_lines.iter().copied());
+                nearby_lines.extend(extra_lines.iter().copied());
             }
 
-            if candidate_lines.is_empty() {
+            if nearby_lines.is_empty() {
                 identifier_extractor.find_all(content)
             } else {
-                let mut line_offsets = Vec::new();
-                let mut pos = 0;
-                for line in content.lines_with_terminator() {
-                    line_offsets.push(pos);
-                    pos += line.len();
-                }
-
-                let mut scoped_identifiers = Vec::new();
-                for line_idx in candidate_lines {
-                    let idx = line_idx.saturating_sub(1);
-                    if idx >= line_offsets.len() {
-                        continue;
-                    }
-
-                    let start = line_offsets[idx];
-                    let end = if idx + 1 < line_offsets.len() {
-                        line_offsets[idx + 1]
-                    } else {
-                        content.len()
-                    };
-                    let slice = &content[start..end];
-
-                    for (local_start, local_end, identifier) in identifier_extractor.find_all(slice)
-                    {
-                        scoped_identifiers.push((
-                            start + local_start,
-                            start + local_end,
-                            identifier,
-                        ));
-                    }
-                }
-
-                scoped_identifiers
+                find_identifiers_on_lines(content, &nearby_lines, identifier_extractor)
             }
         };
 
@@ -377,6 +342,42 @@ pub fn find_enhanced_matches(
     final_matches
 }
 
+/// Identifiers on the given (1-based) lines, with offsets into `content`
+fn find_identifiers_on_lines(
+    content: &[u8],
+    lines: &BTreeSet<usize>,
+    identifier_extractor: &IdentifierExtractor,
+) -> Vec<(usize, usize, String)> {
+    let mut line_offsets = Vec::new();
+    let mut pos = 0;
+    for line in content.lines_with_terminator() {
+        line_offsets.push(pos);
+        pos += line.len();
+    }
+
+    let mut scoped_identifiers = Vec::new();
+    for &line_idx in lines {
+        let idx = line_idx.saturating_sub(1);
+        if idx >= line_offsets.len() {
+            continue;
+        }
+
+        let start = line_offsets[idx];
+        let end = if idx + 1 < line_offsets.len() {
+            line_offsets[idx + 1]
+        } else {
+            content.len()
+        };
+        let slice = &content[start..end];
+
+        for (local_start, local_end, identifier) in identifier_extractor.find_all(slice) {
+            scoped_identifiers.push((start + local_start, start + local_end, identifier));
+        }
+    }
+
+    scoped_identifiers
+}
+
 /// Convert enhanced matches to `MatchHunks` with proper line context
 pub fn enhanced_matches_to_hunks(
     matches: &[Match],
@@ -437,14 +438,22 @@ pub fn enhanced_matches_to_hunks(
         let line_string = String::from_utf8_lossy(line).to_string();
 
         // Determine if this is a compound match or exact match
-        let (content, replace) = if variant_map.contains_key(&m.variant) {
-            // Exact match - use the variant map
-            let new_variant = variant_map.get(&m.variant).unwrap_or(&m.variant);
-            (m.variant.clone(), new_variant.clone())
-        } else {
-            // Compound match - the text field contains the replacement
-            (m.variant.clone(), m.text.clone())
-        };
+        let (content, replace, style, match_kind) =
+            if let Some((style, new_variant)) = variant_map.get_with_style(&m.variant) {
+                // Exact match - use the variant map
+                (
+                    m.variant.clone(),
+                    new_variant.clone(),
+                    style,
+                    MatchKind::Exact,
+                )
+            } else {
+                // Compound match - the text field contains the replacement
+                (m.variant.clone(), m.text.clone(), None, MatchKind::Compound)
+            };
+        let style = style.or_else(|| crate::case_model::detect_style(&content));
+        let (boundary_before, boundary_after) =
+            BoundaryClass::around(&line_string, m.column, m.column + content.len());
 
         // Generate the full line with replacement
         let line_before = Some(line_string.clone());
@@ -479,6 +488,10 @@ pub fn enhanced_matches_to_hunks(
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style,
+            match_kind: Some(match_kind),
+            boundary_before: Some(boundary_before),
+            boundary_after: Some(boundary_after),
         });
     }
 
@@ -566,6 +579,7 @@ mod tests {
             &styles,
             &extractor,
             None,
+            None,
         );
 
         // Should find both preview_format_arg and PreviewFormatArg
@@ -576,4 +590,36 @@ mod tests {
         assert!(variants.contains(&"preview_format_arg".to_string()));
         assert!(variants.contains(&"PreviewFormatArg".to_string()));
     }
+
+    #[test]
+    fn test_enhanced_matching_only_scans_candidate_lines() {
+        let content = b"let PreviewArg = 1;\nlet other = 2;\nlet PreviewOpt = 3;\n";
+
+        let mut variant_map = VariantMap::new();
+        variant_map.insert(
+            "Preview".to_string(),
+            Some(Style::Pascal),
+            "Display".to_string(),
+        );
+
+        let styles = vec![Style::Pascal];
+        let extractor = IdentifierExtractor::new(&styles);
+        let candidate_lines = BTreeSet::from([3]);
+
+        let matches = find_enhanced_matches(
+            content,
+            "test.rs",
+            "preview",
+            "display",
+            &variant_map,
+            &styles,
+            &extractor,
+            None,
+            Some(&candidate_lines),
+        );
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].variant, "PreviewOpt");
+        assert_eq!(matches[0].line, 3);
+    }
 }
@@ -1,9 +1,19 @@
-use anyhow::Result;
+use crate::acronym::AcronymRendering;
+use crate::case_model::CaseMapping;
+use crate::error::RenamifyError;
+use crate::profile::{Profile, BUILTIN_PROFILES};
+use crate::short_search::ShortSearchConfig;
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema, TS)]
+#[ts(export)]
 pub struct Config {
     #[serde(default)]
     pub defaults: DefaultsConfig,
@@ -11,9 +21,61 @@ pub struct Config {
     /// List of atomic identifiers (treated as indivisible units)
     #[serde(default)]
     pub atomic: Vec<String>,
+
+    /// How known acronyms are written in `camelCase` and `PascalCase`:
+    /// "preserve-upper", "pascalize" or "lower"
+    #[serde(default)]
+    pub acronym_rendering: AcronymRendering,
+
+    /// How letters change case in variants and replacements: "unicode",
+    /// "ascii" or "turkish"
+    #[serde(default)]
+    pub case_mapping: CaseMapping,
+
+    /// Policy settings for `renamify check`
+    #[serde(default)]
+    pub check: CheckConfig,
+
+    /// Single-word search terms that `plan` and `rename` refuse without
+    /// `--allow-short-search`
+    #[serde(default)]
+    pub short_search: ShortSearchConfig,
+
+    /// Settings for commits made with `--commit`
+    #[serde(default)]
+    pub commit: CommitConfig,
+
+    /// Named bundles of plan settings, selected with `--profile <name>`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Paths renames should not move into without confirmation, on top of
+    /// build output such as `target` and `node_modules`. Patterns follow
+    /// `.gitignore` rules.
+    #[serde(default)]
+    pub protected: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema, TS)]
+#[ts(export)]
+pub struct CheckConfig {
+    /// Identifiers that must not appear in any case variant
+    #[serde(default)]
+    pub forbid: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema, TS)]
+#[ts(export)]
+pub struct CommitConfig {
+    /// Commit message template. Placeholders: `{search}`, `{replace}`,
+    /// `{plan_id}`, `{matches}`, `{files}` and `{renames}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
 pub struct DefaultsConfig {
     /// Default preview format: "table", "diff", "json", or "summary"
     #[serde(default = "default_preview")]
@@ -34,8 +96,29 @@ pub struct DefaultsConfig {
     /// Whether to use color output by default (None = auto-detect)
     #[serde(default)]
     pub use_color: Option<bool>,
+
+    /// `rename` and `replace` need `--large` when more files than this change
+    #[serde(default = "default_large_files_threshold")]
+    pub large_files_threshold: usize,
+
+    /// `rename` and `replace` need `--large` when more paths than this are renamed
+    #[serde(default = "default_large_renames_threshold")]
+    pub large_renames_threshold: usize,
+
+    /// When apply syncs edited files to disk: "file", "batch" or "end"
+    #[serde(default)]
+    pub durability: crate::Durability,
+
+    /// What apply keeps so that undo can restore edits: "copy", "git-stash" or "none"
+    #[serde(default)]
+    pub backup: crate::BackupStrategy,
 }
 
+/// Default for `defaults.large_files_threshold`
+pub const DEFAULT_LARGE_FILES_THRESHOLD: usize = 500;
+/// Default for `defaults.large_renames_threshold`
+pub const DEFAULT_LARGE_RENAMES_THRESHOLD: usize = 100;
+
 impl Default for DefaultsConfig {
     fn default() -> Self {
         Self {
@@ -44,6 +127,10 @@ impl Default for DefaultsConfig {
             rename_dirs: true,
             unrestricted_level: 0,
             use_color: None,
+            large_files_threshold: DEFAULT_LARGE_FILES_THRESHOLD,
+            large_renames_threshold: DEFAULT_LARGE_RENAMES_THRESHOLD,
+            durability: crate::Durability::default(),
+            backup: crate::BackupStrategy::default(),
         }
     }
 }
@@ -56,18 +143,308 @@ fn default_true() -> bool {
     true
 }
 
+const fn default_large_files_threshold() -> usize {
+    DEFAULT_LARGE_FILES_THRESHOLD
+}
+
+const fn default_large_renames_threshold() -> usize {
+    DEFAULT_LARGE_RENAMES_THRESHOLD
+}
+
+/// Environment variables that override config values, with the key each one sets.
+/// List values are comma-separated.
+pub const CONFIG_ENV_VARS: &[(&str, &str)] = &[
+    ("RENAMIFY_PREVIEW_FORMAT", "defaults.preview_format"),
+    ("RENAMIFY_RENAME_FILES", "defaults.rename_files"),
+    ("RENAMIFY_RENAME_DIRS", "defaults.rename_dirs"),
+    ("RENAMIFY_UNRESTRICTED_LEVEL", "defaults.unrestricted_level"),
+    ("RENAMIFY_USE_COLOR", "defaults.use_color"),
+    (
+        "RENAMIFY_LARGE_FILES_THRESHOLD",
+        "defaults.large_files_threshold",
+    ),
+    (
+        "RENAMIFY_LARGE_RENAMES_THRESHOLD",
+        "defaults.large_renames_threshold",
+    ),
+    ("RENAMIFY_DURABILITY", "defaults.durability"),
+    ("RENAMIFY_BACKUP", "defaults.backup"),
+    ("RENAMIFY_ATOMIC", "atomic"),
+    ("RENAMIFY_ACRONYM_RENDERING", "acronym_rendering"),
+    ("RENAMIFY_CASE_MAPPING", "case_mapping"),
+    ("RENAMIFY_CHECK_FORBID", "check.forbid"),
+    (
+        "RENAMIFY_SHORT_SEARCH_MIN_LENGTH",
+        "short_search.min_length",
+    ),
+    ("RENAMIFY_SHORT_SEARCH_STOPWORDS", "short_search.stopwords"),
+    ("RENAMIFY_COMMIT_TEMPLATE", "commit.template"),
+    ("RENAMIFY_PROTECTED", "protected"),
+];
+
+/// Where an effective config value came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(tag = "origin", content = "source", rename_all = "snake_case")]
+pub enum ConfigOrigin {
+    /// Built-in default
+    Default,
+    /// User config file (`~/.config/renamify/config.toml`)
+    User(PathBuf),
+    /// Repository config file (`.renamify/config.toml`)
+    Repo(PathBuf),
+    /// Environment variable
+    Env(String),
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::User(path) => write!(f, "user ({})", path.display()),
+            Self::Repo(path) => write!(f, "repo ({})", path.display()),
+            Self::Env(var) => write!(f, "env ({var})"),
+        }
+    }
+}
+
+/// Which config file to read or edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigScope {
+    /// The per-user config (`~/.config/renamify/config.toml`)
+    User,
+    /// The repository config (`.renamify/config.toml`)
+    Repo,
+}
+
+/// Effective configuration together with the origin of every value
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    pub config: Config,
+    /// Origin of each value, keyed by dotted path (e.g. `defaults.preview_format`)
+    pub origins: BTreeMap<String, ConfigOrigin>,
+}
+
 impl Config {
-    /// Load config from .renamify/config.toml if it exists
+    /// Load the effective config: built-in defaults < user config < repo config
+    /// (`.renamify/config.toml`) < environment variables. CLI flags override the result.
     pub fn load() -> Result<Self> {
-        if let Ok(cwd) = std::env::current_dir() {
-            let config_path = cwd.join(".renamify").join("config.toml");
-            if config_path.exists() {
-                return Self::load_from_path(&config_path);
+        Ok(Self::load_layered()?.config)
+    }
+
+    /// Like `load`, with the repo config read from `working_dir` rather than
+    /// the current directory
+    pub fn load_in(working_dir: &Path) -> Result<Self> {
+        Ok(Self::load_layered_in(working_dir)?.config)
+    }
+
+    /// Like `load`, but also reports where each value came from
+    pub fn load_layered() -> Result<LayeredConfig> {
+        Self::load_layered_in(&std::env::current_dir()?)
+    }
+
+    /// Like `load_in`, but also reports where each value came from
+    pub fn load_layered_in(working_dir: &Path) -> Result<LayeredConfig> {
+        let repo_path = working_dir.join(".renamify").join("config.toml");
+        Self::load_layers(Self::user_config_path().as_deref(), &repo_path, |var| {
+            std::env::var(var).ok()
+        })
+    }
+
+    /// Path of the per-user config file: `$XDG_CONFIG_HOME/renamify/config.toml`,
+    /// falling back to `~/.config/renamify/config.toml`
+    pub fn user_config_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(|dir| dir.join("renamify").join("config.toml"))
+    }
+
+    /// Merge the config layers from explicit sources. Missing files are skipped.
+    pub fn load_layers(
+        user_path: Option<&Path>,
+        repo_path: &Path,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<LayeredConfig> {
+        let defaults = toml::Value::try_from(Self::default())?;
+        let mut merged = defaults.clone();
+        let mut origins = BTreeMap::new();
+        record_origins(&defaults, "", &ConfigOrigin::Default, &mut origins);
+
+        let files = user_path
+            .map(|path| (path, ConfigOrigin::User(path.to_path_buf())))
+            .into_iter()
+            .chain([(repo_path, ConfigOrigin::Repo(repo_path.to_path_buf()))]);
+        for (path, origin) in files {
+            if !path.exists() {
+                continue;
+            }
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config {}", path.display()))?;
+            let layer: toml::Value = toml::from_str(&content)
+                .with_context(|| format!("Invalid config file {}", path.display()))?;
+            merge_layer(&mut merged, &layer, "", &origin, &mut origins);
+        }
+
+        for (var, key) in CONFIG_ENV_VARS {
+            if let Some(raw) = env(var) {
+                let value = parse_raw_value(&raw, lookup_key(&defaults, key));
+                set_key(&mut merged, key, value);
+                origins.insert((*key).to_string(), ConfigOrigin::Env((*var).to_string()));
             }
         }
 
-        // Return default config if no config file exists
-        Ok(Self::default())
+        let config = merged
+            .try_into()
+            .context("Invalid configuration after merging config files and environment")?;
+        Ok(LayeredConfig { config, origins })
+    }
+
+    /// Look up a profile by name: profiles from the config first, then the
+    /// built-in ones
+    pub fn profile(&self, name: &str) -> Result<Profile> {
+        if let Some(profile) = self.profiles.get(name) {
+            return Ok(profile.clone());
+        }
+        Profile::builtin(name).ok_or_else(|| {
+            let mut available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            available.extend(BUILTIN_PROFILES);
+            available.sort_unstable();
+            available.dedup();
+            RenamifyError::InvalidInput(format!(
+                "Unknown profile '{name}'. Available profiles: {}",
+                available.join(", ")
+            ))
+            .into()
+        })
+    }
+
+    /// Every key a config file accepts, as dotted paths, read from the `Config` JSON schema
+    pub fn known_keys() -> Vec<String> {
+        let schema = schemars::schema_for!(Self);
+        let root = schema.as_value();
+        let mut keys = Vec::new();
+        collect_schema_keys(root, root, "", &mut keys);
+        keys.sort();
+        keys
+    }
+
+    /// Fail with `InvalidInput` unless `key` is a known config key
+    pub fn validate_key(key: &str) -> Result<()> {
+        let keys = Self::known_keys();
+        if keys.iter().any(|known| known == key) {
+            return Ok(());
+        }
+        Err(RenamifyError::InvalidInput(format!(
+            "Unknown config key '{key}'. Valid keys: {}",
+            keys.join(", ")
+        ))
+        .into())
+    }
+
+    /// Read `key` from a single config file, or None if the file or key is missing
+    pub fn get_in_file(path: &Path, key: &str) -> Result<Option<toml::Value>> {
+        Self::validate_key(key)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        let value: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        Ok(lookup_key(&value, key).cloned())
+    }
+
+    /// Set `key` in the config file at `path`, creating the file if needed.
+    ///
+    /// The value is parsed using the key's type. Comments and formatting in the
+    /// file are kept, and the file is only replaced if the result is still a
+    /// valid config. Returns the value that was written.
+    pub fn set_in_file(path: &Path, key: &str, raw: &str) -> Result<toml::Value> {
+        Self::validate_key(key)?;
+        let defaults = toml::Value::try_from(Self::default())?;
+        let value = parse_raw_value(raw, lookup_key(&defaults, key));
+
+        let content = if path.exists() {
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config {}", path.display()))?
+        } else {
+            String::new()
+        };
+        let mut document: toml_edit::DocumentMut = content
+            .parse()
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().unwrap_or(key);
+        let mut table = document.as_table_mut();
+        for part in parts {
+            table = table
+                .entry(part)
+                .or_insert_with(toml_edit::table)
+                .as_table_mut()
+                .ok_or_else(|| {
+                    RenamifyError::InvalidInput(format!(
+                        "Cannot set '{key}': '{part}' in {} is not a table",
+                        path.display()
+                    ))
+                })?;
+        }
+        let edit_value: toml_edit::Value = value
+            .to_string()
+            .parse()
+            .with_context(|| format!("Failed to encode value for '{key}'"))?;
+        match table.get_mut(last).and_then(toml_edit::Item::as_value_mut) {
+            // Replace in place so comments around the existing entry survive
+            Some(existing) => {
+                let decor = existing.decor().clone();
+                *existing = edit_value;
+                *existing.decor_mut() = decor;
+            },
+            None => {
+                table.insert(last, toml_edit::Item::Value(edit_value));
+            },
+        }
+
+        let updated = document.to_string();
+        let config = toml::from_str::<Self>(&updated).map_err(|err| {
+            RenamifyError::InvalidInput(format!(
+                "Invalid value '{raw}' for '{key}': {}",
+                err.message()
+            ))
+        })?;
+        if let Some(template) = &config.commit.template {
+            crate::vcs::validate_commit_template(template)?;
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        // Write next to the target and rename, so a failed write never leaves a truncated file
+        let temp_path = path.with_extension(format!("toml.{}.tmp", std::process::id()));
+        fs::write(&temp_path, updated)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+
+        Ok(value)
+    }
+
+    /// Value of `key` in this config, or None if it is unset
+    pub fn get(&self, key: &str) -> Result<Option<toml::Value>> {
+        Self::validate_key(key)?;
+        Ok(lookup_key(&toml::Value::try_from(self)?, key).cloned())
+    }
+
+    /// Every set value as a dotted key and its TOML representation, in key order
+    pub fn entries(&self) -> Result<Vec<(String, String)>> {
+        let mut entries = Vec::new();
+        flatten(&toml::Value::try_from(self)?, "", &mut entries);
+        Ok(entries)
     }
 
     /// Load config from a specific path
@@ -99,6 +476,140 @@ impl Config {
     }
 }
 
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Record `origin` for every leaf value under `prefix`
+fn record_origins(
+    value: &toml::Value,
+    prefix: &str,
+    origin: &ConfigOrigin,
+    origins: &mut BTreeMap<String, ConfigOrigin>,
+) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                record_origins(value, &join_key(prefix, key), origin, origins);
+            }
+        },
+        _ => {
+            origins.insert(prefix.to_string(), origin.clone());
+        },
+    }
+}
+
+/// Merge `layer` into `merged`: tables merge key by key, anything else replaces
+fn merge_layer(
+    merged: &mut toml::Value,
+    layer: &toml::Value,
+    prefix: &str,
+    origin: &ConfigOrigin,
+    origins: &mut BTreeMap<String, ConfigOrigin>,
+) {
+    let (toml::Value::Table(merged_table), toml::Value::Table(layer_table)) = (merged, layer)
+    else {
+        return;
+    };
+    for (key, value) in layer_table {
+        let path = join_key(prefix, key);
+        match merged_table.get_mut(key) {
+            Some(existing @ toml::Value::Table(_)) if value.is_table() => {
+                merge_layer(existing, value, &path, origin, origins);
+            },
+            _ => {
+                merged_table.insert(key.clone(), value.clone());
+                record_origins(value, &path, origin, origins);
+            },
+        }
+    }
+}
+
+fn lookup_key<'a>(value: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.')
+        .try_fold(value, |value, part| value.get(part))
+}
+
+fn set_key(value: &mut toml::Value, key: &str, new_value: toml::Value) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let Some(last) = parts.pop() else {
+        return;
+    };
+    let mut current = value;
+    for part in parts {
+        let Some(table) = current.as_table_mut() else {
+            return;
+        };
+        current = table
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    }
+    if let Some(table) = current.as_table_mut() {
+        table.insert(last.to_string(), new_value);
+    }
+}
+
+/// Parse a value from an environment variable or `config set` using the type of the built-in default:
+/// lists are comma-separated, booleans and numbers are parsed as TOML literals,
+/// and anything else is taken as a string
+fn parse_raw_value(raw: &str, default: Option<&toml::Value>) -> toml::Value {
+    match default {
+        Some(toml::Value::Array(_)) => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+        _ => toml::from_str::<toml::Table>(&format!("value = {raw}"))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string())),
+    }
+}
+
+/// Collect the dotted paths of all leaf properties in a JSON schema, following `$ref`s
+fn collect_schema_keys(
+    root: &serde_json::Value,
+    node: &serde_json::Value,
+    prefix: &str,
+    keys: &mut Vec<String>,
+) {
+    let node = node
+        .get("$ref")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/"))
+        .and_then(|pointer| root.pointer(&format!("/{pointer}")))
+        .unwrap_or(node);
+    match node
+        .get("properties")
+        .and_then(serde_json::Value::as_object)
+    {
+        Some(properties) => {
+            for (key, property) in properties {
+                collect_schema_keys(root, property, &join_key(prefix, key), keys);
+            }
+        },
+        None => keys.push(prefix.to_string()),
+    }
+}
+
+fn flatten(value: &toml::Value, prefix: &str, entries: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                flatten(value, &join_key(prefix, key), entries);
+            }
+        },
+        _ => entries.push((prefix.to_string(), value.to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,6 +658,20 @@ preview_format = "json"
         assert!(config.defaults.rename_files);
         assert!(config.defaults.rename_dirs);
         assert_eq!(config.defaults.unrestricted_level, 0);
+        assert_eq!(config.defaults.large_files_threshold, 500);
+        assert_eq!(config.defaults.large_renames_threshold, 100);
+    }
+
+    #[test]
+    fn test_large_change_thresholds_config() {
+        let toml_content = "
+[defaults]
+large_files_threshold = 2000
+";
+
+        let config: Config = toml::from_str(toml_content).unwrap();
+        assert_eq!(config.defaults.large_files_threshold, 2000);
+        assert_eq!(config.defaults.large_renames_threshold, 100);
     }
 
     #[test]
@@ -165,4 +690,160 @@ preview_format = "table"
         assert!(config.atomic.contains(&"FormAPI".to_string()));
         assert_eq!(config.defaults.preview_format, "table");
     }
+
+    #[test]
+    fn test_check_config() {
+        let toml_content = r#"
+[check]
+forbid = ["old_name", "legacy_api"]
+"#;
+
+        let config: Config = toml::from_str(toml_content).unwrap();
+        assert_eq!(config.check.forbid, vec!["old_name", "legacy_api"]);
+        assert!(Config::default().check.forbid.is_empty());
+    }
+
+    #[test]
+    fn test_config_layering() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_path = temp_dir.path().join("user.toml");
+        let repo_path = temp_dir.path().join("repo.toml");
+        fs::write(
+            &user_path,
+            "atomic = [\"GitHub\"]\n[defaults]\npreview_format = \"table\"\nunrestricted_level = 1\n",
+        )
+        .unwrap();
+        fs::write(&repo_path, "[defaults]\npreview_format = \"summary\"\n").unwrap();
+
+        let env = |var: &str| match var {
+            "RENAMIFY_UNRESTRICTED_LEVEL" => Some("2".to_string()),
+            "RENAMIFY_CHECK_FORBID" => Some("old_name, legacy_api".to_string()),
+            _ => None,
+        };
+        let layered = Config::load_layers(Some(&user_path), &repo_path, env).unwrap();
+
+        assert_eq!(layered.config.defaults.preview_format, "summary");
+        assert_eq!(layered.config.defaults.unrestricted_level, 2);
+        assert_eq!(layered.config.atomic, vec!["GitHub"]);
+        assert_eq!(layered.config.check.forbid, vec!["old_name", "legacy_api"]);
+        assert!(layered.config.defaults.rename_files);
+
+        assert_eq!(
+            layered.origins["defaults.preview_format"],
+            ConfigOrigin::Repo(repo_path)
+        );
+        assert_eq!(layered.origins["atomic"], ConfigOrigin::User(user_path));
+        assert_eq!(
+            layered.origins["defaults.unrestricted_level"],
+            ConfigOrigin::Env("RENAMIFY_UNRESTRICTED_LEVEL".to_string())
+        );
+        assert_eq!(
+            layered.origins["defaults.rename_files"],
+            ConfigOrigin::Default
+        );
+    }
+
+    #[test]
+    fn test_config_layering_missing_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let env = |var: &str| (var == "RENAMIFY_USE_COLOR").then(|| "false".to_string());
+        let layered =
+            Config::load_layers(None, &temp_dir.path().join("missing.toml"), env).unwrap();
+
+        assert_eq!(layered.config.defaults.preview_format, "diff");
+        assert_eq!(layered.config.defaults.use_color, Some(false));
+        assert!(layered
+            .config
+            .entries()
+            .unwrap()
+            .contains(&("defaults.use_color".to_string(), "false".to_string())));
+    }
+
+    #[test]
+    fn test_profiles_config() {
+        let toml_content = r#"
+[profiles.k8s]
+only_styles = ["kebab"]
+
+[profiles.docs]
+exclude = ["CHANGELOG.md"]
+"#;
+
+        let config: Config = toml::from_str(toml_content).unwrap();
+        assert_eq!(config.profile("k8s").unwrap().only_styles, vec!["kebab"]);
+        assert!(!config.profile("k8s").unwrap().label_warnings);
+        assert_eq!(
+            config.profile("docs").unwrap().exclude,
+            vec!["CHANGELOG.md"]
+        );
+        assert!(Config::default().profile("k8s").unwrap().label_warnings);
+
+        let err = config.profile("helm").unwrap_err().to_string();
+        assert!(err.contains("Available profiles: docs, k8s, sql"));
+    }
+
+    #[test]
+    fn test_known_keys_follow_schema() {
+        let keys = Config::known_keys();
+        assert!(keys.contains(&"defaults.preview_format".to_string()));
+        assert!(keys.contains(&"defaults.use_color".to_string()));
+        assert!(keys.contains(&"check.forbid".to_string()));
+        assert!(keys.contains(&"atomic".to_string()));
+        assert!(!keys.contains(&"defaults".to_string()));
+    }
+
+    #[test]
+    fn test_set_in_file_keeps_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "# My settings\n[defaults]\n# Preferred preview\npreview_format = \"diff\"\n",
+        )
+        .unwrap();
+
+        Config::set_in_file(&path, "defaults.preview_format", "table").unwrap();
+        Config::set_in_file(&path, "defaults.unrestricted_level", "2").unwrap();
+        Config::set_in_file(&path, "check.forbid", "old_name,legacy_api").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("# My settings"));
+        assert!(content.contains("# Preferred preview\npreview_format = \"table\""));
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.defaults.preview_format, "table");
+        assert_eq!(config.defaults.unrestricted_level, 2);
+        assert_eq!(config.check.forbid, vec!["old_name", "legacy_api"]);
+        assert_eq!(
+            Config::get_in_file(&path, "defaults.unrestricted_level").unwrap(),
+            Some(toml::Value::Integer(2))
+        );
+        assert_eq!(
+            Config::get_in_file(&path, "defaults.use_color").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_in_file_validates() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("config.toml");
+
+        let err = Config::set_in_file(&path, "defaults.preview", "table").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unknown config key 'defaults.preview'"));
+
+        let err = Config::set_in_file(&path, "defaults.rename_files", "sometimes").unwrap_err();
+        assert_eq!(
+            crate::error::error_kind(&err),
+            crate::error::ErrorKind::InvalidInput
+        );
+        assert!(!path.exists());
+
+        Config::set_in_file(&path, "defaults.use_color", "false").unwrap();
+        assert_eq!(
+            Config::load_from_path(&path).unwrap().defaults.use_color,
+            Some(false)
+        );
+    }
 }
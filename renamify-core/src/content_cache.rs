@@ -0,0 +1,145 @@
+//! Contents of matched files kept from the scan, so `rename` can apply its
+//! edits without reading every file a second time.
+//!
+//! A cached file is only used if its size and modification time are unchanged
+//! when apply asks for it. Files modified shortly before they were read are not
+//! trusted, since a second edit within the file system's timestamp resolution
+//! would leave the modification time unchanged (the same "racy clean" problem
+//! git has with its index).
+
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Most bytes kept in memory; matched files beyond this are read again at apply
+pub const CONTENT_CACHE_LIMIT: u64 = 256 * 1024 * 1024;
+
+/// How recently a file may have been modified before it was read and still be
+/// trusted to show a change through its modification time
+pub(crate) const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+struct CachedFile {
+    content: String,
+    len: u64,
+    modified: SystemTime,
+}
+
+#[derive(Default)]
+struct Entries {
+    files: HashMap<PathBuf, CachedFile>,
+    /// Total size of the cached contents
+    bytes: u64,
+}
+
+/// Matched file contents from a scan, keyed by path
+#[derive(Default)]
+pub struct ContentCache {
+    entries: Mutex<Entries>,
+}
+
+impl ContentCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep `content` for `path`, given the metadata taken before it was read.
+    /// Content that isn't UTF-8, or that doesn't fit, is not kept.
+    pub(crate) fn insert(
+        &self,
+        path: &Path,
+        content: Vec<u8>,
+        metadata: &Metadata,
+        read_at: SystemTime,
+    ) {
+        let Ok(modified) = metadata.modified() else {
+            return;
+        };
+        if read_at
+            .duration_since(modified)
+            .map_or(true, |age| age < RACY_WINDOW)
+        {
+            return;
+        }
+        let Ok(content) = String::from_utf8(content) else {
+            return;
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        let size = content.len() as u64;
+        if entries.files.contains_key(path) || entries.bytes + size > CONTENT_CACHE_LIMIT {
+            return;
+        }
+        entries.bytes += size;
+        entries.files.insert(
+            path.to_path_buf(),
+            CachedFile {
+                content,
+                len: metadata.len(),
+                modified,
+            },
+        );
+    }
+
+    /// Take the cached content of `path` if the file hasn't changed since it
+    /// was read
+    pub(crate) fn take(&self, path: &Path) -> Option<String> {
+        let cached = {
+            let mut entries = self.entries.lock().unwrap();
+            let cached = entries.files.remove(path)?;
+            entries.bytes -= cached.content.len() as u64;
+            cached
+        };
+        let metadata = std::fs::metadata(path).ok()?;
+        let unchanged =
+            metadata.len() == cached.len && metadata.modified().ok() == Some(cached.modified);
+        unchanged.then_some(cached.content)
+    }
+
+    /// Number of files held
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_content_cache_checks_for_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.rs");
+        fs::write(&path, "fn old_name() {}").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let later = metadata.modified().unwrap() + RACY_WINDOW;
+
+        let cache = ContentCache::new();
+        cache.insert(&path, b"fn old_name() {}".to_vec(), &metadata, later);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.take(&path).as_deref(), Some("fn old_name() {}"));
+        assert!(cache.is_empty());
+
+        // Changed since it was read
+        cache.insert(&path, b"fn old_name() {}".to_vec(), &metadata, later);
+        fs::write(&path, "fn old_name() { changed }").unwrap();
+        assert_eq!(cache.take(&path), None);
+
+        // Read too soon after it was modified to trust the timestamp
+        let metadata = fs::metadata(&path).unwrap();
+        cache.insert(
+            &path,
+            b"fn old_name() { changed }".to_vec(),
+            &metadata,
+            metadata.modified().unwrap(),
+        );
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+//! Debug tracing switches.
+//!
+//! Each `RENAMIFY_DEBUG_*` variable turns on tracing to stderr for one part
+//! of the scanner. The environment is read once, the first time any switch is
+//! checked, so scans never touch process-global state while they run and a
+//! long-lived embedder can change its environment without racing them.
+
+use std::sync::OnceLock;
+
+/// Which debug traces are enabled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugFlags {
+    /// `RENAMIFY_DEBUG_COMPOUND`: compound identifier matching
+    pub compound: bool,
+    /// `RENAMIFY_DEBUG_VARIANTS`: generated case variants
+    pub variants: bool,
+    /// `RENAMIFY_DEBUG_IDENTIFIERS`: identifiers found by the compound scanner
+    pub identifiers: bool,
+    /// `RENAMIFY_DEBUG_AMBIGUITY`: ambiguity resolution
+    pub ambiguity: bool,
+    /// `RENAMIFY_DEBUG_TRAIN_CASE`: Train-Case file renames
+    pub train_case: bool,
+    /// `DEBUG_TOKENIZE`: identifier tokenization
+    pub tokenize: bool,
+}
+
+static FLAGS: OnceLock<DebugFlags> = OnceLock::new();
+
+/// The debug switches, read from the environment on first use
+pub fn flags() -> DebugFlags {
+    *FLAGS.get_or_init(|| {
+        let set = |name: &str| std::env::var_os(name).is_some();
+        DebugFlags {
+            compound: set("RENAMIFY_DEBUG_COMPOUND"),
+            variants: set("RENAMIFY_DEBUG_VARIANTS"),
+            identifiers: set("RENAMIFY_DEBUG_IDENTIFIERS"),
+            ambiguity: set("RENAMIFY_DEBUG_AMBIGUITY"),
+            train_case: set("RENAMIFY_DEBUG_TRAIN_CASE"),
+            tokenize: set("DEBUG_TOKENIZE"),
+        }
+    })
+}
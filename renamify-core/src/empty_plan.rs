@@ -0,0 +1,266 @@
+//! Why a plan came out empty.
+//!
+//! When a scan finds nothing to change, renamify looks again to say where the
+//! files and matches went: hidden by ignore files, left out by `--include` or
+//! `--exclude`, binary, filtered out one match at a time, or written in case
+//! styles that were not searched. Only empty plans pay for the second look.
+
+use crate::case_model::Style;
+use crate::operations::plan::scan_plan;
+use crate::scanner::{Plan, PlanOptions, ScanCaches, SkipReason};
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write as _};
+use std::path::PathBuf;
+use ts_rs::TS;
+
+/// Where the files and matches of an empty plan were left out
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct EmptyPlanDiagnosis {
+    /// Text files that were searched
+    pub files_searched: usize,
+    /// Files hidden by ignore files or inside nested repositories, which `-uu`
+    /// and `--include-submodules` would search
+    pub files_ignored: usize,
+    /// Files left out by `--include` / `--exclude`
+    pub files_excluded: usize,
+    /// Binary files, searched only with `-uuu`
+    pub files_binary: usize,
+    /// Files that could not be read
+    pub files_unreadable: usize,
+    /// Files over `--max-filesize`, whose contents were not searched
+    #[serde(default)]
+    pub files_too_large: usize,
+    /// Matches that a filter left out, by reason
+    pub matches_filtered: Vec<FilteredMatches>,
+    /// Matches in case styles that were not searched
+    pub matches_in_other_styles: usize,
+}
+
+/// How many matches one filter left out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct FilteredMatches {
+    pub reason: SkipReason,
+    pub count: usize,
+}
+
+impl EmptyPlanDiagnosis {
+    /// Work out why scanning `roots` with `options` produced the empty `plan`
+    pub fn diagnose(
+        roots: &[PathBuf],
+        search: &str,
+        replace: &str,
+        options: &PlanOptions,
+        swap: bool,
+        plan: &Plan,
+    ) -> Result<Self> {
+        let skipped = &plan.stats.files_skipped;
+        let mut diagnosis = Self {
+            files_searched: plan.stats.files_scanned - skipped.binary,
+            files_ignored: 0,
+            files_excluded: skipped.excluded,
+            files_binary: skipped.binary,
+            files_unreadable: skipped.unreadable,
+            files_too_large: skipped.too_large.len(),
+            matches_filtered: Vec::new(),
+            matches_in_other_styles: 0,
+        };
+        if options.paths_only {
+            return Ok(diagnosis);
+        }
+
+        let visited = plan.stats.files_scanned
+            + skipped.unreadable
+            + skipped.excluded
+            + skipped.too_large.len();
+        diagnosis.files_ignored = count_all_files(roots, options).saturating_sub(visited);
+
+        // Scan again for the filtered matches, and with every default style
+        // when the searched styles were narrowed
+        let narrowed = options
+            .styles
+            .as_ref()
+            .is_some_and(|styles| Style::default_styles().iter().any(|s| !styles.contains(s)));
+        let mut rescan_options = PlanOptions {
+            report_skipped: true,
+            contents_only: true,
+            ..options.clone()
+        };
+        if narrowed {
+            let mut styles = Style::default_styles();
+            styles.extend(
+                options
+                    .styles
+                    .iter()
+                    .flatten()
+                    .filter(|style| !Style::default_styles().contains(style)),
+            );
+            rescan_options.styles = Some(styles);
+        }
+        let rescan = if narrowed || !options.report_skipped {
+            scan_plan(
+                roots,
+                search,
+                replace,
+                &rescan_options,
+                swap,
+                None,
+                ScanCaches::default(),
+            )?
+        } else {
+            plan.clone()
+        };
+
+        diagnosis.matches_in_other_styles = rescan.stats.total_matches;
+        for skipped in &rescan.skipped {
+            match diagnosis
+                .matches_filtered
+                .iter_mut()
+                .find(|filtered| filtered.reason == skipped.reason)
+            {
+                Some(filtered) => filtered.count += 1,
+                None => diagnosis.matches_filtered.push(FilteredMatches {
+                    reason: skipped.reason,
+                    count: 1,
+                }),
+            }
+        }
+        Ok(diagnosis)
+    }
+}
+
+/// Files under `roots` with every ignore file switched off and nested
+/// repositories included, leaving out renamify's own directory
+fn count_all_files(roots: &[PathBuf], options: &PlanOptions) -> usize {
+    let unrestricted = PlanOptions {
+        unrestricted_level: 2,
+        ..options.clone()
+    };
+    // Replaces the walker's own filter, which leaves out submodules
+    let mut builder = crate::configure_walker(roots, &unrestricted);
+    builder.filter_entry(|entry| {
+        entry.file_name() != ".renamify"
+            && !crate::vcs::is_metadata_dir(entry.file_name())
+            && (entry.depth() == 0
+                || !entry.file_type().is_some_and(|t| t.is_dir())
+                || crate::submodule::should_walk(entry.path(), true))
+    });
+    builder
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .count()
+}
+
+impl fmt::Display for EmptyPlanDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut output = String::new();
+        writeln!(output, "  Files searched: {}", self.files_searched)?;
+        writeln!(
+            output,
+            "  Hidden by ignore files or in nested repositories: {}{}",
+            self.files_ignored,
+            hint(
+                self.files_ignored,
+                "search them with -uu or --include-submodules"
+            )
+        )?;
+        writeln!(
+            output,
+            "  Left out by --include/--exclude: {}",
+            self.files_excluded
+        )?;
+        writeln!(
+            output,
+            "  Binary: {}{}",
+            self.files_binary,
+            hint(self.files_binary, "search them as text with -uuu")
+        )?;
+        writeln!(output, "  Unreadable: {}", self.files_unreadable)?;
+        if self.files_too_large > 0 {
+            writeln!(
+                output,
+                "  Too large: {} (search them with a higher --max-filesize)",
+                self.files_too_large
+            )?;
+        }
+        for filtered in &self.matches_filtered {
+            writeln!(
+                output,
+                "  Matches filtered out ({}): {} (list them with --show-skipped)",
+                filtered.reason, filtered.count
+            )?;
+        }
+        if self.matches_in_other_styles > 0 {
+            writeln!(
+                output,
+                "  Matches in case styles not searched: {} (check --only-styles and --exclude-styles)",
+                self.matches_in_other_styles
+            )?;
+        }
+        f.write_str(output.trim_end())
+    }
+}
+
+fn hint(count: usize, hint: &str) -> String {
+    if count == 0 {
+        String::new()
+    } else {
+        format!(" ({hint})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diagnose() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        std::fs::create_dir_all(root.join("build")).unwrap();
+        std::fs::write(root.join("build/out.rs"), "old_name();\n").unwrap();
+        std::fs::write(root.join("lib.rs"), "OldName::new(); // old_name\n").unwrap();
+        std::fs::write(root.join("other.rs"), "OldName::new();\n").unwrap();
+        std::fs::write(root.join("notes.md"), "old_name\n").unwrap();
+
+        let options = PlanOptions {
+            styles: Some(vec![Style::Snake]),
+            excludes: vec!["*.md".to_string()],
+            exclude_matching_lines: Some("//".to_string()),
+            ..PlanOptions::default()
+        };
+        let roots = [root];
+        let plan = scan_plan(
+            &roots,
+            "old_name",
+            "new_name",
+            &options,
+            false,
+            None,
+            ScanCaches::default(),
+        )
+        .unwrap();
+        assert_eq!(plan.stats.total_matches, 0);
+
+        let diagnosis =
+            EmptyPlanDiagnosis::diagnose(&roots, "old_name", "new_name", &options, false, &plan)
+                .unwrap();
+        assert_eq!(diagnosis.files_searched, 3);
+        assert_eq!(diagnosis.files_ignored, 1);
+        assert_eq!(diagnosis.files_excluded, 1);
+        assert_eq!(diagnosis.matches_in_other_styles, 1);
+        assert_eq!(
+            diagnosis.matches_filtered,
+            [FilteredMatches {
+                reason: SkipReason::ExcludeMatchingLines,
+                count: 2,
+            }]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Typed errors and the exit codes they map to
+//!
+//! Operations return `anyhow::Result`, but failures a caller may want to branch
+//! on are raised as `RenamifyError` (or `PlanGuardError`) so they can be
+//! classified with `error_kind` no matter how much context was added on top.
+
+use crate::operations::plan::PlanGuardError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Category of a failure, exposed as `error.kind` in JSON output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Files changed since planning, or renames collide
+    Conflict,
+    /// Bad arguments, flags, or patterns
+    InvalidInput,
+    /// A plan, history entry, or other referenced item does not exist
+    NotFound,
+    /// A size guard (`--max-matches`, `--large`, ...) stopped the operation
+    Guard,
+    /// `--fail-if-zero` found nothing to change
+    NoMatches,
+    /// Another renamify process holds the workspace lock
+    Locked,
+    /// `--expect-matches` or `--expect-renames` did not match the plan
+    UnexpectedCount,
+    /// An `OperationObserver` asked the operation to stop
+    Cancelled,
+    /// Anything else (I/O failures, bugs)
+    Internal,
+}
+
+impl ErrorKind {
+    /// Documented process exit code for this kind of error
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::Conflict => 1,
+            Self::InvalidInput | Self::NotFound => 2,
+            Self::Internal => 3,
+            Self::Guard => 4,
+            Self::Locked => 5,
+            Self::NoMatches => 6,
+            Self::UnexpectedCount => 7,
+            Self::Cancelled => 130,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Conflict => "conflict",
+            Self::InvalidInput => "invalid_input",
+            Self::NotFound => "not_found",
+            Self::Guard => "guard",
+            Self::Locked => "locked",
+            Self::NoMatches => "no_matches",
+            Self::UnexpectedCount => "unexpected_count",
+            Self::Cancelled => "cancelled",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+/// Errors raised by renamify operations that callers can branch on
+#[derive(Debug, thiserror::Error)]
+pub enum RenamifyError {
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Locked(String),
+    #[error("{0}")]
+    Cancelled(String),
+}
+
+impl RenamifyError {
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Conflict(_) => ErrorKind::Conflict,
+            Self::InvalidInput(_) => ErrorKind::InvalidInput,
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::Locked(_) => ErrorKind::Locked,
+            Self::Cancelled(_) => ErrorKind::Cancelled,
+        }
+    }
+}
+
+/// Classify an error by walking its chain for a typed renamify error
+pub fn error_kind(err: &anyhow::Error) -> ErrorKind {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<RenamifyError>() {
+            return e.kind();
+        }
+        match cause.downcast_ref::<PlanGuardError>() {
+            Some(PlanGuardError::NoMatches(_)) => return ErrorKind::NoMatches,
+            Some(PlanGuardError::UnexpectedCount { .. }) => return ErrorKind::UnexpectedCount,
+            Some(_) => return ErrorKind::Guard,
+            None => {},
+        }
+    }
+    ErrorKind::Internal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(ErrorKind::Conflict.exit_code(), 1);
+        assert_eq!(ErrorKind::InvalidInput.exit_code(), 2);
+        assert_eq!(ErrorKind::NotFound.exit_code(), 2);
+        assert_eq!(ErrorKind::Internal.exit_code(), 3);
+        assert_eq!(ErrorKind::Guard.exit_code(), 4);
+        assert_eq!(ErrorKind::Locked.exit_code(), 5);
+        assert_eq!(ErrorKind::NoMatches.exit_code(), 6);
+        assert_eq!(ErrorKind::UnexpectedCount.exit_code(), 7);
+        assert_eq!(ErrorKind::Cancelled.exit_code(), 130);
+    }
+
+    #[test]
+    fn test_error_kind_sees_through_context() {
+        let err: anyhow::Error = RenamifyError::NotFound("missing".to_string()).into();
+        let err = err.context("Failed to load plan");
+        assert_eq!(error_kind(&err), ErrorKind::NotFound);
+
+        let err = Err::<(), _>(PlanGuardError::TooManyFiles { found: 3, max: 2 })
+            .context("outer")
+            .unwrap_err();
+        assert_eq!(error_kind(&err), ErrorKind::Guard);
+
+        let err = Err::<(), _>(PlanGuardError::NoMatches(Box::default()))
+            .context("outer")
+            .unwrap_err();
+        assert_eq!(error_kind(&err), ErrorKind::NoMatches);
+    }
+
+    #[test]
+    fn test_untyped_errors_are_internal() {
+        let err = anyhow::anyhow!("invalid conflict not found");
+        assert_eq!(error_kind(&err), ErrorKind::Internal);
+    }
+
+    #[test]
+    fn test_error_kind_serializes_snake_case() {
+        assert_eq!(
+            serde_json::to_string(&ErrorKind::InvalidInput).unwrap(),
+            "\"invalid_input\""
+        );
+        assert_eq!(
+            serde_json::to_value(ErrorKind::Locked).unwrap(),
+            ErrorKind::Locked.as_str()
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Named filter sets, saved with `--save-filters` and reused with `--filters`.
+//!
+//! A filter set holds the path globs and match exclusions that a plan was
+//! refined with, stored as `.renamify/filters/<name>.toml`. Filters given on
+//! the command line are added to a loaded set, and saving writes the
+//! combination, so a set can be grown over several runs.
+
+use crate::error::RenamifyError;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSet {
+    /// Glob patterns to include, as in `--include`
+    pub include: Vec<String>,
+
+    /// Glob patterns to exclude, as in `--exclude`
+    pub exclude: Vec<String>,
+
+    /// Matches to leave unchanged, as in `--exclude-match`
+    pub exclude_match: Vec<String>,
+
+    /// Skip matches on lines matching this regex, as in
+    /// `--exclude-matching-lines`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_matching_lines: Option<String>,
+}
+
+impl FilterSet {
+    /// Load the filter set saved as `name` in `renamify_dir`
+    pub fn load(renamify_dir: &Path, name: &str) -> Result<Self> {
+        let path = filter_path(renamify_dir, name)?;
+        if !path.is_file() {
+            let saved = saved_names(renamify_dir);
+            let available = if saved.is_empty() {
+                "none are saved yet; create one with --save-filters".to_string()
+            } else {
+                format!("saved: {}", saved.join(", "))
+            };
+            return Err(RenamifyError::NotFound(format!(
+                "No filter set named '{name}' ({available})"
+            ))
+            .into());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).map_err(|e| {
+            RenamifyError::InvalidInput(format!("Invalid filter set {}: {e}", path.display()))
+                .into()
+        })
+    }
+
+    /// Save the filter set as `name` in `renamify_dir`, replacing any set
+    /// with that name
+    pub fn save(&self, renamify_dir: &Path, name: &str) -> Result<PathBuf> {
+        let path = filter_path(renamify_dir, name)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// This set with the filters of `other` added after its own
+    #[must_use]
+    pub fn merged(self, other: Self) -> Self {
+        let exclude_matching_lines =
+            match (self.exclude_matching_lines, other.exclude_matching_lines) {
+                (Some(a), Some(b)) if a != b => Some(format!("(?:{a})|(?:{b})")),
+                (a, b) => b.or(a),
+            };
+        Self {
+            include: combined(self.include, other.include),
+            exclude: combined(self.exclude, other.exclude),
+            exclude_match: combined(self.exclude_match, other.exclude_match),
+            exclude_matching_lines,
+        }
+    }
+}
+
+/// Where the filter set `name` is stored
+fn filter_path(renamify_dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    {
+        return Err(RenamifyError::InvalidInput(format!(
+            "Invalid filter set name '{name}': use letters, digits, '-' and '_'"
+        ))
+        .into());
+    }
+    Ok(renamify_dir.join("filters").join(format!("{name}.toml")))
+}
+
+/// The names of the saved filter sets, sorted
+fn saved_names(renamify_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(renamify_dir.join("filters"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "toml")
+                .then(|| path.file_stem()?.to_str().map(String::from))
+                .flatten()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// `first` followed by `second`, keeping the last copy of any pattern given
+/// twice, since the last matching pattern wins
+fn combined(first: Vec<String>, second: Vec<String>) -> Vec<String> {
+    let all: Vec<String> = first.into_iter().chain(second).collect();
+    all.iter()
+        .enumerate()
+        .filter(|(index, pattern)| !all[index + 1..].contains(pattern))
+        .map(|(_, pattern)| pattern.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_save_load_and_merge() {
+        let dir = TempDir::new().unwrap();
+        let saved = FilterSet {
+            exclude: strings(&["vendor/**", "!vendor/ours/**"]),
+            exclude_match: strings(&["old_name_legacy"]),
+            exclude_matching_lines: Some("^// keep".to_string()),
+            ..FilterSet::default()
+        };
+        saved.save(dir.path(), "legacy").unwrap();
+        assert_eq!(FilterSet::load(dir.path(), "legacy").unwrap(), saved);
+
+        let merged = saved.merged(FilterSet {
+            exclude: strings(&["vendor/**", "dist/**"]),
+            exclude_matching_lines: Some("TODO".to_string()),
+            ..FilterSet::default()
+        });
+        assert_eq!(
+            merged.exclude,
+            strings(&["!vendor/ours/**", "vendor/**", "dist/**"])
+        );
+        assert_eq!(merged.exclude_match, strings(&["old_name_legacy"]));
+        assert_eq!(
+            merged.exclude_matching_lines.as_deref(),
+            Some("(?:^// keep)|(?:TODO)")
+        );
+
+        let error = FilterSet::load(dir.path(), "missing").unwrap_err();
+        assert!(error.to_string().contains("saved: legacy"));
+        assert!(FilterSet::load(dir.path(), "../config").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Near-miss spellings of the search term, found with `--fuzzy`.
+//!
+//! An identifier is a near miss when its tokens line up with the search
+//! term's tokens and the misspelt ones are within a small edit distance, where
+//! swapping two neighbouring letters counts as one edit (`old_nmae`). Near
+//! misses are kept apart from the plan's matches until they are confirmed.
+
+use crate::case_model::{detect_style, parse_to_tokens, to_style, Style, TokenModel};
+use crate::compound_matcher::find_compound_variants;
+use crate::pattern::Match;
+use regex::bytes::Regex;
+use std::collections::HashMap;
+
+/// Search tokens shorter than this must be spelled exactly
+const MIN_FUZZY_TOKEN_LEN: usize = 3;
+
+#[derive(Debug)]
+pub struct FuzzyPattern {
+    search_tokens: Vec<String>,
+    replace: String,
+    replace_model: TokenModel,
+    styles: Vec<Style>,
+    max_distance: usize,
+    identifier: Regex,
+}
+
+impl FuzzyPattern {
+    /// None when fuzzy matching is off (`max_distance` 0) or the search term
+    /// has no tokens
+    pub fn new(search: &str, replace: &str, styles: &[Style], max_distance: u8) -> Option<Self> {
+        let search_tokens: Vec<String> = parse_to_tokens(search)
+            .tokens
+            .iter()
+            .map(|token| token.text.to_lowercase())
+            .collect();
+        if max_distance == 0 || search_tokens.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            search_tokens,
+            replace: replace.to_string(),
+            replace_model: parse_to_tokens(replace),
+            styles: styles.to_vec(),
+            max_distance: usize::from(max_distance),
+            identifier: Regex::new(r"[A-Za-z][A-Za-z0-9]*(?:[_-][A-Za-z0-9]+)*").ok()?,
+        })
+    }
+
+    /// Near misses in `content` that don't overlap the `exact` matches. The
+    /// match text is the replacement, as for compound matches.
+    pub fn find_matches(&self, content: &[u8], file: &str, exact: &[Match]) -> Vec<Match> {
+        let mut seen: HashMap<&[u8], Option<String>> = HashMap::new();
+        let mut found = Vec::new();
+
+        for candidate in self.identifier.find_iter(content) {
+            let (start, end) = (candidate.start(), candidate.end());
+            if exact.iter().any(|m| m.start < end && start < m.end) {
+                continue;
+            }
+            let replacement = seen
+                .entry(candidate.as_bytes())
+                .or_insert_with(|| {
+                    std::str::from_utf8(candidate.as_bytes())
+                        .ok()
+                        .and_then(|identifier| self.near_miss(identifier))
+                })
+                .clone();
+            let Some(replacement) = replacement else {
+                continue;
+            };
+
+            let line_start = content[..start]
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .map_or(0, |pos| pos + 1);
+            #[allow(clippy::naive_bytecount)]
+            let line = content[..start]
+                .iter()
+                .filter(|&&byte| byte == b'\n')
+                .count()
+                + 1;
+            found.push(Match {
+                file: file.to_string(),
+                line,
+                column: start - line_start,
+                start,
+                end,
+                variant: String::from_utf8_lossy(candidate.as_bytes()).to_string(),
+                text: replacement,
+            });
+        }
+        found
+    }
+
+    /// The replacement for `identifier` if it is a near miss of the search term
+    fn near_miss(&self, identifier: &str) -> Option<String> {
+        let tokens: Vec<String> = parse_to_tokens(identifier)
+            .tokens
+            .into_iter()
+            .map(|token| token.text)
+            .collect();
+        let width = self.search_tokens.len();
+        if tokens.len() < width {
+            return None;
+        }
+
+        let window = (0..=tokens.len() - width).find(|&start| {
+            let distance = self.distance(&tokens[start..start + width]);
+            distance.is_some_and(|d| d > 0 && d <= self.max_distance)
+        })?;
+
+        if tokens.len() == width {
+            let style = detect_style(identifier).filter(|style| self.styles.contains(style))?;
+            return Some(if self.replace.is_empty() {
+                String::new()
+            } else {
+                to_style(&self.replace_model, style)
+            });
+        }
+
+        // Rename the misspelt part like a compound match of its own spelling
+        let misspelt = tokens[window..window + width].join("_");
+        let probe = if self.replace.is_empty() {
+            misspelt.as_str()
+        } else {
+            self.replace.as_str()
+        };
+        let compound = find_compound_variants(identifier, &misspelt, probe, &self.styles);
+        let compound = compound.first()?;
+        Some(if self.replace.is_empty() {
+            String::new()
+        } else {
+            compound.replacement.clone()
+        })
+    }
+
+    /// Total edit distance of `window` from the search tokens, or None if a
+    /// short token is misspelt
+    fn distance(&self, window: &[String]) -> Option<usize> {
+        window
+            .iter()
+            .zip(&self.search_tokens)
+            .map(|(token, search)| {
+                let distance = edit_distance(&token.to_lowercase(), search);
+                (distance == 0 || search.chars().count() >= MIN_FUZZY_TOKEN_LEN).then_some(distance)
+            })
+            .sum()
+    }
+}
+
+/// Levenshtein distance that also counts swapping two neighbouring characters
+/// as a single edit (optimal string alignment)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("name", "name"), 0);
+        assert_eq!(edit_distance("nmae", "name"), 1);
+        assert_eq!(edit_distance("nam", "name"), 1);
+        assert_eq!(edit_distance("naem", "name"), 1);
+        assert_eq!(edit_distance("mane", "name"), 2);
+    }
+
+    #[test]
+    fn test_find_near_misses() {
+        let pattern =
+            FuzzyPattern::new("old_name", "new_name", &Style::default_styles(), 1).unwrap();
+        let source =
+            "old_name(); old_nmae(); OldNaem::x; get_old_nam(); od_name; old_game_x; old_mane;";
+        let exact_start = source.find("old_name").unwrap();
+        let exact = vec![Match {
+            file: "a.rs".to_string(),
+            line: 1,
+            column: exact_start,
+            start: exact_start,
+            end: exact_start + 8,
+            variant: "old_name".to_string(),
+            text: "new_name".to_string(),
+        }];
+
+        let found: Vec<(String, String)> = pattern
+            .find_matches(source.as_bytes(), "a.rs", &exact)
+            .into_iter()
+            .map(|m| (m.variant, m.text))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("old_nmae".to_string(), "new_name".to_string()),
+                ("OldNaem".to_string(), "NewName".to_string()),
+                ("get_old_nam".to_string(), "get_new_name".to_string()),
+                ("od_name".to_string(), "new_name".to_string()),
+                ("old_game_x".to_string(), "new_name_x".to_string()),
+            ]
+        );
+
+        assert!(FuzzyPattern::new("old_name", "new_name", &Style::default_styles(), 0).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
@@ -7,7 +8,7 @@ use std::path::{Path, PathBuf};
 use ts_rs::TS;
 
 /// Represents a single entry in the renaming history
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub struct HistoryEntry {
     /// Unique identifier for this plan/operation
@@ -623,8 +624,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -1,5 +1,6 @@
+use crate::error::RenamifyError;
 use crate::history::History;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use std::path::Path;
 
 /// Type of operation for ID resolution
@@ -17,7 +18,9 @@ pub fn resolve_id(id: &str, operation: OperationType, renamify_dir: &Path) -> Re
         // Verify the ID exists
         let history = History::load(renamify_dir)?;
         if history.find_entry(id).is_none() {
-            return Err(anyhow!("History entry '{}' not found", id));
+            return Err(
+                RenamifyError::NotFound(format!("History entry '{}' not found", id)).into(),
+            );
         }
         Ok(id.to_string())
     }
@@ -29,7 +32,7 @@ fn resolve_latest_id(operation: OperationType, renamify_dir: &Path) -> Result<St
     let entries = history.list_entries(None);
 
     if entries.is_empty() {
-        return Err(anyhow!("No renaming history found"));
+        return Err(RenamifyError::NotFound("No renaming history found".to_string()).into());
     }
 
     match operation {
@@ -40,7 +43,12 @@ fn resolve_latest_id(operation: OperationType, renamify_dir: &Path) -> Result<St
                 .iter()
                 .find(|entry| entry.revert_of.is_none())
                 .map(|entry| entry.id.clone())
-                .ok_or_else(|| anyhow!("No renaming entries found that can be undone"))
+                .ok_or_else(|| {
+                    RenamifyError::NotFound(
+                        "No renaming entries found that can be undone".to_string(),
+                    )
+                    .into()
+                })
         },
         OperationType::Redo => {
             // Find the most recent revert entry
@@ -52,7 +60,12 @@ fn resolve_latest_id(operation: OperationType, renamify_dir: &Path) -> Result<St
                     // Return the ID of the original operation that was undone
                     entry.revert_of.as_ref().unwrap().clone()
                 })
-                .ok_or_else(|| anyhow!("No undone renaming entries found that can be redone"))
+                .ok_or_else(|| {
+                    RenamifyError::NotFound(
+                        "No undone renaming entries found that can be redone".to_string(),
+                    )
+                    .into()
+                })
         },
     }
 }
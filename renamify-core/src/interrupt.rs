@@ -24,3 +24,28 @@ impl Drop for ConfirmationPromptGuard {
 pub fn confirmation_prompt_active() -> bool {
     CONFIRMATION_PROMPT_ACTIVE.load(Ordering::SeqCst)
 }
+
+/// Tracks whether a pager is showing output; it handles Ctrl+C itself.
+static PAGER_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// RAII helper that marks a pager as running while it is in scope.
+pub struct PagerGuard;
+
+impl PagerGuard {
+    /// Activate the pager state until the guard is dropped.
+    pub fn activate() -> Self {
+        PAGER_ACTIVE.store(true, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for PagerGuard {
+    fn drop(&mut self) {
+        PAGER_ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Returns true while a pager is showing output.
+pub fn pager_active() -> bool {
+    PAGER_ACTIVE.load(Ordering::SeqCst)
+}
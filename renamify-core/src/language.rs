@@ -0,0 +1,334 @@
+//! What language a file is written in, for the checks that depend on it.
+//!
+//! The style heuristics, string and comment scoping, and the SQL and
+//! structured-file handling all ask this module instead of matching on
+//! extensions themselves. A file's language comes from, in order:
+//!
+//! 1. A vim or emacs modeline in its first or last five lines
+//!    (`# vim: ft=ruby`, `-*- mode: python -*-`)
+//! 2. Its extension, including any registered with [`register_extension`]
+//! 3. The interpreter on its `#!` line, which also covers scripts without an
+//!    extension
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+/// Lines at each end of a file searched for a modeline, as in vim
+const MODELINE_LINES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Ruby,
+    Python,
+    /// JavaScript and TypeScript
+    JavaScript,
+    Go,
+    Rust,
+    /// Java and Kotlin
+    Java,
+    /// C and C++
+    C,
+    /// CSS and its preprocessors
+    Css,
+    /// HTML, XML and other markup
+    Html,
+    Shell,
+    Yaml,
+    Json,
+    Toml,
+    /// INI-style configuration and `.env` files
+    Ini,
+    Sql,
+}
+
+/// A named group of extensions, selected with `--type` and `--type-not` as in
+/// ripgrep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileType {
+    pub name: &'static str,
+    /// Extensions without the dot
+    pub extensions: &'static [&'static str],
+    /// The language of these files, for types the checks know about
+    pub language: Option<Language>,
+}
+
+impl FileType {
+    /// Glob patterns matching the files of this type, e.g. `*.rs`
+    pub fn globs(&self) -> impl Iterator<Item = String> {
+        self.extensions
+            .iter()
+            .map(|extension| format!("*.{extension}"))
+    }
+}
+
+const fn file_type(
+    name: &'static str,
+    extensions: &'static [&'static str],
+    language: Option<Language>,
+) -> FileType {
+    FileType {
+        name,
+        extensions,
+        language,
+    }
+}
+
+/// The built-in file types, which also give the language of each extension
+pub const FILE_TYPES: &[FileType] = &[
+    file_type("c", &["c", "h"], Some(Language::C)),
+    file_type(
+        "cpp",
+        &["cpp", "cc", "cxx", "h", "hpp", "hxx"],
+        Some(Language::C),
+    ),
+    file_type(
+        "css",
+        &["css", "scss", "sass", "less", "styl"],
+        Some(Language::Css),
+    ),
+    file_type("go", &["go"], Some(Language::Go)),
+    file_type("html", &["html", "htm"], Some(Language::Html)),
+    file_type("ini", &["ini", "cfg", "conf", "env"], Some(Language::Ini)),
+    file_type("java", &["java"], Some(Language::Java)),
+    file_type(
+        "js",
+        &["js", "jsx", "mjs", "cjs"],
+        Some(Language::JavaScript),
+    ),
+    file_type("json", &["json", "jsonc", "json5"], Some(Language::Json)),
+    file_type("kotlin", &["kt", "kts"], Some(Language::Java)),
+    file_type("md", &["md", "markdown", "mdx"], None),
+    file_type("py", &["py", "pyw", "pyi"], Some(Language::Python)),
+    file_type("ruby", &["rb", "rake", "gemspec"], Some(Language::Ruby)),
+    file_type("rust", &["rs"], Some(Language::Rust)),
+    file_type(
+        "sh",
+        &["sh", "bash", "zsh", "fish", "ksh"],
+        Some(Language::Shell),
+    ),
+    file_type("sql", &["sql"], Some(Language::Sql)),
+    file_type("svg", &["svg"], Some(Language::Html)),
+    file_type("toml", &["toml"], Some(Language::Toml)),
+    file_type(
+        "ts",
+        &["ts", "tsx", "mts", "cts"],
+        Some(Language::JavaScript),
+    ),
+    file_type("vue", &["vue"], Some(Language::Html)),
+    file_type("xml", &["xml"], Some(Language::Html)),
+    file_type("yaml", &["yml", "yaml"], Some(Language::Yaml)),
+];
+
+/// The built-in file type called `name`
+pub fn find_file_type(name: &str) -> Option<&'static FileType> {
+    FILE_TYPES.iter().find(|file_type| file_type.name == name)
+}
+
+/// Names used in shebangs and modelines that aren't also extensions
+const BUILTIN_NAMES: &[(&str, Language)] = &[
+    ("ruby", Language::Ruby),
+    ("python", Language::Python),
+    ("node", Language::JavaScript),
+    ("nodejs", Language::JavaScript),
+    ("deno", Language::JavaScript),
+    ("javascript", Language::JavaScript),
+    ("typescript", Language::JavaScript),
+    ("golang", Language::Go),
+    ("rust", Language::Rust),
+    ("kotlin", Language::Java),
+    ("cpp", Language::C),
+    ("c++", Language::C),
+    ("dash", Language::Shell),
+    ("shell", Language::Shell),
+    ("xhtml", Language::Html),
+    ("dosini", Language::Ini),
+    ("mysql", Language::Sql),
+    ("plsql", Language::Sql),
+];
+
+static EXTENSIONS: LazyLock<RwLock<HashMap<String, Language>>> = LazyLock::new(|| {
+    let extensions = FILE_TYPES
+        .iter()
+        .filter_map(|file_type| Some((file_type.extensions, file_type.language?)))
+        .flat_map(|(extensions, language)| {
+            extensions
+                .iter()
+                .map(move |extension| ((*extension).to_string(), language))
+        })
+        .collect();
+    RwLock::new(extensions)
+});
+
+/// Treat files ending in `.extension` as `language`, replacing any existing
+/// mapping. Extensions are case-insensitive and given without the dot.
+pub fn register_extension(extension: &str, language: Language) {
+    let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+    EXTENSIONS.write().unwrap().insert(extension, language);
+}
+
+impl Language {
+    /// Detect the language of `path`, looking at `content` for a modeline or
+    /// shebang when it is given
+    pub fn detect(path: &Path, content: Option<&str>) -> Option<Self> {
+        content
+            .and_then(from_modeline)
+            .or_else(|| Self::from_path(path))
+            .or_else(|| content.and_then(from_shebang))
+    }
+
+    /// The language of `path` from its extension alone
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        EXTENSIONS
+            .read()
+            .unwrap()
+            .get(&extension.to_ascii_lowercase())
+            .copied()
+    }
+
+    /// The language called `name` in a shebang or modeline, e.g. `python3`,
+    /// `sh` or `yaml`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        // Versioned interpreters: python3, python3.12, ruby2.7
+        let unversioned = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let language = [name.as_str(), unversioned]
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .find_map(|name| {
+                BUILTIN_NAMES
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map(|&(_, language)| language)
+                    .or_else(|| Self::from_extension(name))
+            });
+        language
+    }
+}
+
+/// The interpreter named on a `#!` first line, skipping `env` and its options
+fn from_shebang(content: &str) -> Option<Language> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    Language::from_name(program)
+}
+
+/// A vim or emacs modeline near the start or end of `content`
+fn from_modeline(content: &str) -> Option<Language> {
+    let lines: Vec<&str> = content.lines().collect();
+    let tail = lines
+        .len()
+        .saturating_sub(MODELINE_LINES)
+        .max(MODELINE_LINES);
+    lines
+        .iter()
+        .take(MODELINE_LINES)
+        .chain(lines.iter().skip(tail))
+        .find_map(|line| vim_modeline(line).or_else(|| emacs_modeline(line)))
+        .and_then(Language::from_name)
+}
+
+/// `ft`, `filetype` or `syntax` from a `vim:`, `vi:` or `ex:` modeline
+fn vim_modeline(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(pos, _)| *pos == 0 || line[..*pos].ends_with(char::is_whitespace))
+            .map(|(pos, marker)| pos + marker.len())
+    })?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|setting| {
+            let (key, value) = setting.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syntax").then_some(value)
+        })
+}
+
+/// The mode in an emacs `-*- mode: ruby -*-` or `-*- ruby -*-` line
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (settings, _) = rest.split_once("-*-")?;
+    if !settings.contains(':') {
+        return Some(settings.trim());
+    }
+    settings.split(';').find_map(|setting| {
+        let (key, value) = setting.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then_some(value.trim())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_from_extension_shebang_and_modeline() {
+        assert_eq!(
+            Language::detect(Path::new("app/models/user.RB"), None),
+            Some(Language::Ruby)
+        );
+        assert_eq!(Language::detect(Path::new("notes.txt"), None), None);
+
+        let script = "#!/usr/bin/env -S python3.12 -u\nprint('hi')\n";
+        assert_eq!(
+            Language::detect(Path::new("bin/tool"), Some(script)),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::detect(Path::new("bin/run"), Some("#!/bin/bash\n")),
+            Some(Language::Shell)
+        );
+
+        // A modeline outranks the extension
+        let template = "name: app\n# vim: set ft=yaml ts=2:\n";
+        assert_eq!(
+            Language::detect(Path::new("values.tpl"), Some(template)),
+            Some(Language::Yaml)
+        );
+        let emacs = "// -*- mode: javascript; indent-tabs-mode: nil -*-\n";
+        assert_eq!(
+            Language::detect(Path::new("build.txt"), Some(emacs)),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            Language::detect(Path::new("x.conf"), Some("# -*- ruby -*-\n")),
+            Some(Language::Ruby)
+        );
+
+        // Only the first and last lines hold modelines
+        let mut long = "line\n".repeat(20);
+        long.insert_str(30, "# vim: ft=ruby\n");
+        assert_eq!(Language::detect(Path::new("x"), Some(&long)), None);
+    }
+
+    #[test]
+    fn test_register_extension() {
+        assert_eq!(Language::from_extension("jbuilder"), None);
+        register_extension(".jbuilder", Language::Ruby);
+        assert_eq!(
+            Language::from_path(Path::new("show.json.jbuilder")),
+            Some(Language::Ruby)
+        );
+        assert_eq!(Language::from_name("jbuilder"), Some(Language::Ruby));
+    }
+
+    #[test]
+    fn test_file_types() {
+        let rust = find_file_type("rust").unwrap();
+        assert_eq!(rust.globs().collect::<Vec<_>>(), vec!["*.rs"]);
+        assert!(find_file_type("rs").is_none());
+        // Types share the language table, so `.h` is C either way
+        assert_eq!(Language::from_extension("tsx"), Some(Language::JavaScript));
+        assert_eq!(Language::from_extension("h"), Some(Language::C));
+        assert_eq!(Language::from_extension("md"), None);
+    }
+}
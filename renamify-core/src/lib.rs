@@ -13,53 +13,117 @@ pub mod acronym;
 pub mod ambiguity;
 pub mod apply;
 pub mod atomic;
+pub mod backup;
+pub mod bundle;
 pub mod case_constraints;
 pub mod case_model;
+pub mod codeowners;
 pub mod coercion;
 pub mod compound_matcher;
 pub mod compound_scanner;
 pub mod config;
+pub mod content_cache;
+pub mod debug;
+pub mod empty_plan;
+pub mod error;
+pub mod filters;
+pub mod fuzzy;
 pub mod history;
 pub mod id_resolver;
 pub mod interrupt;
+pub mod language;
 pub mod lock;
+pub mod lsp;
+pub mod mapping;
+pub mod noisy_dirs;
+pub mod observer;
 pub mod operations;
 pub mod output;
+pub mod pager;
 pub mod pattern;
+pub mod placeholders;
 pub mod preview;
+pub mod profile;
+pub mod remote;
 pub mod rename;
+pub mod scan_cache;
 pub mod scanner;
+pub mod short_search;
+pub mod signing;
+pub mod sparse;
+pub mod sql;
+pub mod stage;
+pub mod stale_paths;
+pub mod structured;
+pub mod submodule;
+pub mod suppress;
+pub mod suspicious;
+pub mod tracked;
 pub mod undo;
+pub mod vcs;
+pub mod warnings;
+pub mod workspace;
 
-pub use apply::{apply_plan, ApplyOptions};
+pub use apply::{
+    apply_plan, apply_plan_by_directory, apply_plan_cached, apply_plan_observed,
+    preview_history_entry, ApplyOptions, Durability, TransactionScope,
+};
+pub use backup::BackupStrategy;
 pub use case_model::{
     detect_style, generate_variant_map, parse_to_tokens, to_style, Style, Token, TokenModel,
 };
-pub use config::Config;
+pub use config::{Config, ConfigOrigin, ConfigScope, LayeredConfig};
+pub use content_cache::ContentCache;
+pub use error::{error_kind, ErrorKind, RenamifyError};
 pub use history::{
     create_history_entry, format_history, get_status, History, HistoryEntry, StatusInfo,
 };
 pub use id_resolver::{resolve_id, OperationType};
 pub use lock::LockFile;
+pub use mapping::RenameMapping;
+pub use observer::OperationObserver;
 pub use operations::{
-    apply_operation, history_operation, plan_operation, redo_operation, rename_operation,
-    status_operation, undo_operation,
+    apply_bundle_operation, apply_check_operation, apply_operation, check_operation,
+    check_staged_operation, config_get_operation, config_set_operation, config_show_operation,
+    doctor_operation, explain_operation, export_types_operation, gc_operation,
+    history_export_operation, history_mapping_operation, history_operation, plan_diff_operation,
+    plan_merge_operation, plan_operation, plan_split_operation, preview_file_operation,
+    redo_operation, rename_operation, replan_operation, report_operation, serve_operation,
+    status_operation, undo_operation, validate_plan_operation,
 };
 pub use output::{
-    ApplyResult, HistoryItem, HistoryResult, OutputFormat, OutputFormatter, PendingPlan,
-    PlanResult, RedoResult, RenameResult, StatusResult, UndoResult, VersionResult,
+    ApplyCheckResult, ApplyResult, BuildInfo, ChangeGroup, ChangedMatch, ChangedRename,
+    CheckResult, CheckViolation, ConfigShowResult, DoctorCheck, DoctorResult, DoctorStatus,
+    ErrorResult, ExplainResult, ExportTypesResult, GcArtifact, GcArtifactKind, GcResult,
+    GuardDetails, HistoryExportResult, HistoryItem, HistoryResult, LargeChangeCheck, OutputFormat,
+    OutputFormatter, OwnerPlan, PendingPlan, PlanDiffResult, PlanDrift, PlanIssue, PlanIssueKind,
+    PlanResult, PlanValidationResult, PorcelainFormatter, PreviewFileResult, RedoResult,
+    RenameCollision, RenameResult, RenameSafetyChecks, ReplanResult, StatusResult, UndoResult,
+    UsageByMonth, UsageReport, VersionResult,
+};
+pub use pattern::{build_pattern, find_matches, is_boundary, Match, MatchPattern, VariantMatch};
+pub use preview::{
+    register_renderer, render_plan, render_plan_in, render_plan_named, render_tree, write_preview,
+    GroupBy, Preview, PreviewRenderer, RenderOptions,
 };
-pub use pattern::{build_pattern, find_matches, is_boundary, Match, MatchPattern};
-pub use preview::{render_plan, write_preview, Preview};
+pub use profile::Profile;
 pub use rename::{
     detect_case_insensitive_fs, plan_renames_with_conflicts, ConflictKind, RenameConflict,
     RenamePlan,
 };
 pub use scanner::{
-    create_simple_plan, scan_repository, scan_repository_multi, write_plan, MatchHunk, Plan,
-    PlanOptions, Rename, RenameKind, Stats,
+    create_simple_plan, scan_content, scan_repository, scan_repository_cached,
+    scan_repository_multi, scan_repository_observed, write_plan, BoundaryClass, CoercionMode,
+    LargeFile, LiteralMode, MatchAnchor, MatchHunk, MatchKind, Plan, PlanOptions, Rename,
+    RenameKind, ScanCaches, ScanTimings, SkipReason, SkippedFiles, SkippedMatch, Stats,
+    UnreadableFile, WalkOptions, DEFAULT_MAX_FILESIZE,
 };
-pub use undo::{redo_renaming, undo_renaming};
+pub use signing::{PlanSignature, PlanTrust};
+pub use stage::StageMode;
+pub use structured::{StructuredFilter, StructuredTarget};
+pub use suspicious::SuspiciousRename;
+pub use undo::{redo_renaming, undo_conflicts, undo_renaming, LocalChange, UndoConflict};
+pub use warnings::{PlanWarning, WarningKind, WarningSeverity};
 
 use ignore::WalkBuilder;
 use std::path::PathBuf;
@@ -71,6 +135,10 @@ use std::path::PathBuf;
 /// - Level 1 (-u): Don't respect .gitignore, but respect .ignore and .rnignore; include hidden; exclude .git
 /// - Level 2 (-uu): Don't respect any ignore files; include hidden files; exclude .git
 /// - Level 3 (-uuu): Same as level 2, plus treat binary files as text (handled by caller)
+///
+/// Linked worktrees are never walked, and submodules only with `include_submodules`.
+/// `options.walk` bounds the depth, file systems and symlinks the walk follows,
+/// and limits the files it yields to the types selected with `--type`.
 pub fn configure_walker(roots: &[PathBuf], options: &scanner::PlanOptions) -> WalkBuilder {
     let mut builder = if roots.is_empty() {
         WalkBuilder::new(".")
@@ -101,11 +169,7 @@ pub fn configure_walker(roots: &[PathBuf], options: &scanner::PlanOptions) -> Wa
                 .parents(true)
                 .hidden(false)      // false = include hidden files like .goreleaser.yaml, .github/, etc.
                 .add_custom_ignore_filename(".gitignore")  // Treat .gitignore as custom ignore file for non-git directories
-                .add_custom_ignore_filename(".rnignore")  // Renamify-specific ignore file
-                .filter_entry(|e| {
-                    // Exclude .git directories from being scanned
-                    e.file_name() != ".git"
-                })
+                .add_custom_ignore_filename(".rnignore") // Renamify-specific ignore file
         },
         1 => {
             // -u: Don't respect .gitignore, but respect .ignore and .rnignore, include hidden, exclude .git
@@ -116,11 +180,7 @@ pub fn configure_walker(roots: &[PathBuf], options: &scanner::PlanOptions) -> Wa
                 .ignore(true)       // Still respect .ignore files
                 .parents(true)      // Still check parent dirs
                 .hidden(false)      // false = include hidden files
-                .add_custom_ignore_filename(".rnignore")  // Renamify-specific ignore file
-                .filter_entry(|e| {
-                    // Exclude .git directories from being scanned
-                    e.file_name() != ".git"
-                })
+                .add_custom_ignore_filename(".rnignore") // Renamify-specific ignore file
         },
         2 | 3 => {
             // -uu/-uuu: Don't respect any ignore files, show hidden, but still exclude .git
@@ -132,10 +192,6 @@ pub fn configure_walker(roots: &[PathBuf], options: &scanner::PlanOptions) -> Wa
                 .ignore(false)
                 .parents(false)
                 .hidden(false) // false = include hidden files
-                .filter_entry(|e| {
-                    // Always exclude .git directories - they should never be renamed
-                    e.file_name() != ".git"
-                })
         },
         _ => {
             // Treat any higher level as maximum unrestricted, but still exclude .git
@@ -146,12 +202,49 @@ pub fn configure_walker(roots: &[PathBuf], options: &scanner::PlanOptions) -> Wa
                 .ignore(false)
                 .parents(false)
                 .hidden(false)
-                .filter_entry(|e| {
-                    // Always exclude .git directories - they should never be renamed
-                    e.file_name() != ".git"
-                })
         },
     };
 
     builder
+        .max_depth(options.walk.max_depth)
+        .same_file_system(options.walk.one_file_system)
+        .follow_links(options.walk.follow_links);
+    if let Some(types) = file_types(&options.walk) {
+        builder.types(types);
+    }
+
+    // Never walk into repository metadata or linked worktrees, and only into
+    // submodules when asked
+    let include_submodules = options.include_submodules;
+    builder.filter_entry(move |e| {
+        !vcs::is_metadata_dir(e.file_name())
+            && (e.depth() == 0
+                || !e.file_type().is_some_and(|t| t.is_dir())
+                || submodule::should_walk(e.path(), include_submodules))
+    });
+
+    builder
+}
+
+/// The `--type` and `--type-not` selections as a matcher for the walk, or
+/// `None` when there are none. Names that aren't built-in types are skipped;
+/// the CLI rejects them before getting here.
+fn file_types(walk: &WalkOptions) -> Option<ignore::types::Types> {
+    if walk.types.is_empty() && walk.types_not.is_empty() {
+        return None;
+    }
+    let mut builder = ignore::types::TypesBuilder::new();
+    for file_type in language::FILE_TYPES {
+        for glob in file_type.globs() {
+            builder.add(file_type.name, &glob).ok()?;
+        }
+    }
+    let known = |name: &&String| language::find_file_type(name).is_some();
+    for name in walk.types.iter().filter(known) {
+        builder.select(name);
+    }
+    for name in walk.types_not.iter().filter(known) {
+        builder.negate(name);
+    }
+    builder.build().ok()
 }
@@ -1,4 +1,5 @@
-use anyhow::{anyhow, Context, Result};
+use crate::error::RenamifyError;
+use anyhow::{Context, Result};
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
@@ -46,12 +47,13 @@ impl LockFile {
                     fs::remove_file(&lock_path).context("Failed to remove stale lock file")?;
                 } else if is_process_running(pid) {
                     // Process is still running
-                    return Err(anyhow!(
+                    return Err(RenamifyError::Locked(format!(
                         "Another renamify process is already running (PID: {}). \
                         If this is incorrect, remove the lock file at: {}",
                         pid,
                         lock_path.display()
-                    ));
+                    ))
+                    .into());
                 } else {
                     // Process is not running, remove the lock
                     fs::remove_file(&lock_path).context("Failed to remove orphaned lock file")?;
@@ -118,9 +120,30 @@ impl Drop for LockFile {
     }
 }
 
+/// The lock file in `renamify_dir` when it was left behind by a process that
+/// is no longer running, or is older than the stale timeout
+pub fn stale_lock(renamify_dir: &Path) -> Option<PathBuf> {
+    let lock_path = renamify_dir.join(LOCK_FILE_NAME);
+    let content = fs::read_to_string(&lock_path).ok()?;
+    let Some((pid, timestamp)) = content.trim().split_once(':') else {
+        return Some(lock_path);
+    };
+    let (Ok(pid), Ok(timestamp)) = (pid.parse::<u32>(), timestamp.parse::<u64>()) else {
+        return Some(lock_path);
+    };
+
+    let current_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let stale = current_time.saturating_sub(timestamp) > STALE_LOCK_TIMEOUT_SECS
+        || !is_process_running(pid);
+    stale.then_some(lock_path)
+}
+
 /// Check if a process with the given PID is running
 #[cfg(unix)]
-fn is_process_running(pid: u32) -> bool {
+pub(crate) fn is_process_running(pid: u32) -> bool {
     // On Unix, we can check if a process exists by sending signal 0
     #[allow(clippy::cast_possible_wrap)]
     unsafe {
@@ -129,7 +152,7 @@ fn is_process_running(pid: u32) -> bool {
 }
 
 #[cfg(windows)]
-fn is_process_running(pid: u32) -> bool {
+pub(crate) fn is_process_running(pid: u32) -> bool {
     use winapi::um::handleapi::CloseHandle;
     use winapi::um::processthreadsapi::OpenProcess;
     use winapi::um::winnt::PROCESS_QUERY_INFORMATION;
@@ -146,7 +169,7 @@ fn is_process_running(pid: u32) -> bool {
 }
 
 #[cfg(not(any(unix, windows)))]
-fn is_process_running(_pid: u32) -> bool {
+pub(crate) fn is_process_running(_pid: u32) -> bool {
     // Fallback: assume process is not running if we can't check
     false
 }
@@ -0,0 +1,254 @@
+//! Plans as LSP `WorkspaceEdit` documents.
+//!
+//! Editor extensions can hand a `WorkspaceEdit` straight to the editor, which
+//! applies it through its own undo stack instead of renamify writing the
+//! files. Content edits come first, against the original paths, followed by
+//! the renames in the order `apply` performs them. Positions count UTF-16 code
+//! units, the default position encoding in LSP.
+
+use crate::apply::{adjust_rename, order_renames};
+use crate::error::RenamifyError;
+use crate::scanner::{MatchHunk, Plan};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceEdit {
+    pub document_changes: Vec<DocumentChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum DocumentChange {
+    Edit(TextDocumentEdit),
+    Rename(RenameFile),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentEdit {
+    pub text_document: TextDocumentIdentifier,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A document at whatever version the editor has open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Zero-based line and UTF-16 offset within the line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameFile {
+    /// Always `rename`
+    pub kind: &'static str,
+    pub old_uri: String,
+    pub new_uri: String,
+}
+
+/// The edits and renames of `plan`, with relative paths resolved against `base`.
+/// Fails if the plan's directory renames can't be ordered.
+pub fn workspace_edit(plan: &Plan, base: &Path) -> Result<WorkspaceEdit, RenamifyError> {
+    let resolve = |path: &Path| {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            base.join(path)
+        }
+    };
+
+    let mut by_file: BTreeMap<PathBuf, Vec<&MatchHunk>> = BTreeMap::new();
+    for hunk in &plan.matches {
+        by_file.entry(resolve(&hunk.file)).or_default().push(hunk);
+    }
+    let mut document_changes: Vec<DocumentChange> = by_file
+        .into_iter()
+        .map(|(path, hunks)| {
+            DocumentChange::Edit(TextDocumentEdit {
+                text_document: TextDocumentIdentifier {
+                    uri: file_uri(&path),
+                    version: None,
+                },
+                edits: hunks.into_iter().map(text_edit).collect(),
+            })
+        })
+        .collect();
+
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for rename in order_renames(&plan.paths)? {
+        let (from, to) = adjust_rename(&rename, &done);
+        document_changes.push(DocumentChange::Rename(RenameFile {
+            kind: "rename",
+            old_uri: file_uri(&resolve(&from)),
+            new_uri: file_uri(&resolve(&to)),
+        }));
+        done.push((rename.path, rename.new_path));
+    }
+
+    Ok(WorkspaceEdit { document_changes })
+}
+
+fn text_edit(hunk: &MatchHunk) -> TextEdit {
+    let line = u32::try_from(hunk.line.saturating_sub(1)).unwrap_or(u32::MAX);
+    let character = hunk
+        .line_before
+        .as_deref()
+        .map_or(hunk.char_offset, |text| {
+            let column = (hunk.byte_offset as usize).min(text.len());
+            text.get(..column).map_or(hunk.char_offset, utf16_len)
+        });
+    let start = Position { line, character };
+
+    // Title Case and sentence variants can run onto the next line
+    let mut end = start;
+    for (idx, segment) in hunk.content.split('\n').enumerate() {
+        if idx == 0 {
+            end.character += utf16_len(segment);
+        } else {
+            end.line += 1;
+            end.character = utf16_len(segment);
+        }
+    }
+
+    TextEdit {
+        range: Range { start, end },
+        new_text: hunk.replace.clone(),
+    }
+}
+
+fn utf16_len(text: &str) -> u32 {
+    u32::try_from(text.encode_utf16().count()).unwrap_or(u32::MAX)
+}
+
+/// `file://` URI of an absolute path, percent-encoding anything outside the
+/// unreserved characters and separators
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Rename, RenameKind};
+
+    fn hunk(file: &str, line: u64, line_before: &str, content: &str, replace: &str) -> MatchHunk {
+        let byte_offset = line_before.find(content).unwrap();
+        MatchHunk {
+            char_offset: u32::try_from(line_before[..byte_offset].chars().count()).unwrap(),
+            line_before: Some(line_before.to_string()),
+            ..MatchHunk::for_test(
+                file,
+                line,
+                u32::try_from(byte_offset).unwrap(),
+                content,
+                replace,
+            )
+        }
+    }
+
+    #[test]
+    fn test_workspace_edit() {
+        let plan = Plan {
+            id: "plan".to_string(),
+            created_at: String::new(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![
+                hunk(
+                    "src/old_name.rs",
+                    3,
+                    "let 😀 = old_name();\n",
+                    "old_name",
+                    "new_name",
+                ),
+                hunk("src/a b.rs", 1, "use old_name;\n", "old_name", "new_name"),
+            ],
+            paths: vec![
+                Rename {
+                    path: PathBuf::from("src/old_name.rs"),
+                    new_path: PathBuf::from("src/new_name.rs"),
+                    kind: RenameKind::File,
+                    coercion_applied: None,
+                },
+                Rename {
+                    path: PathBuf::from("src"),
+                    new_path: PathBuf::from("lib"),
+                    kind: RenameKind::Dir,
+                    coercion_applied: None,
+                },
+            ],
+            stats: crate::scanner::Stats::default(),
+            version: String::new(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            created_directories: None,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+        };
+
+        let edit = workspace_edit(&plan, Path::new("/repo")).unwrap();
+        let json = serde_json::to_value(&edit).unwrap();
+        let changes = json["documentChanges"].as_array().unwrap();
+        assert_eq!(changes.len(), 4);
+
+        assert_eq!(
+            changes[0]["textDocument"]["uri"],
+            "file:///repo/src/a%20b.rs"
+        );
+        assert!(changes[0]["textDocument"]["version"].is_null());
+        // The emoji is two UTF-16 code units
+        let range = &changes[1]["edits"][0]["range"];
+        assert_eq!(range["start"]["line"], 2);
+        assert_eq!(range["start"]["character"], 9);
+        assert_eq!(range["end"]["character"], 17);
+        assert_eq!(changes[1]["edits"][0]["newText"], "new_name");
+
+        // The directory goes first, and the file inside it follows it there
+        assert_eq!(changes[2]["kind"], "rename");
+        assert_eq!(changes[2]["oldUri"], "file:///repo/src");
+        assert_eq!(changes[2]["newUri"], "file:///repo/lib");
+        assert_eq!(changes[3]["oldUri"], "file:///repo/lib/old_name.rs");
+        assert_eq!(changes[3]["newUri"], "file:///repo/lib/new_name.rs");
+    }
+}
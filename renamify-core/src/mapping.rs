@@ -0,0 +1,193 @@
+//! Old-to-new mapping of an applied rename, for audits.
+//!
+//! Applying a plan writes `.renamify/mappings/<id>.json` next to the stored
+//! plan: each identifier variant that was replaced and each path that moved,
+//! with what it became. `history mapping <id>` prints it as JSON or CSV for
+//! tools outside renamify, such as documentation or compliance checks.
+
+use crate::error::RenamifyError;
+use crate::scanner::Plan;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenameMapping {
+    pub history_id: String,
+    pub search: String,
+    pub replace: String,
+    /// Each replaced variant, in order of the old text
+    pub identifiers: Vec<IdentifierMapping>,
+    /// Each moved file or directory, relative to the project root
+    pub paths: Vec<PathMapping>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentifierMapping {
+    pub old: String,
+    pub new: String,
+    /// How many times it was replaced
+    pub occurrences: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathMapping {
+    pub old: PathBuf,
+    pub new: PathBuf,
+}
+
+impl RenameMapping {
+    /// The mapping of `plan` once applied, where `renames` are the moves
+    /// made and `root` the canonical project root
+    pub fn from_plan(plan: &Plan, renames: &[(PathBuf, PathBuf)], root: &Path) -> Self {
+        let mut identifiers: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for hunk in &plan.matches {
+            *identifiers
+                .entry((&hunk.content, &hunk.replace))
+                .or_default() += 1;
+        }
+        let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        Self {
+            history_id: plan.id.clone(),
+            search: plan.search.clone(),
+            replace: plan.replace.clone(),
+            identifiers: identifiers
+                .into_iter()
+                .map(|((old, new), occurrences)| IdentifierMapping {
+                    old: old.to_string(),
+                    new: new.to_string(),
+                    occurrences,
+                })
+                .collect(),
+            paths: renames
+                .iter()
+                .map(|(old, new)| PathMapping {
+                    old: relative(old),
+                    new: relative(new),
+                })
+                .collect(),
+        }
+    }
+
+    fn path(renamify_dir: &Path, id: &str) -> PathBuf {
+        renamify_dir.join("mappings").join(format!("{id}.json"))
+    }
+
+    /// Store the mapping with its history entry
+    pub fn write(&self, renamify_dir: &Path) -> Result<()> {
+        let path = Self::path(renamify_dir, &self.history_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write mapping to {}", path.display()))
+    }
+
+    /// The stored mapping of history entry `id`, or `None` when the rename
+    /// was applied before mappings were kept
+    pub fn load(renamify_dir: &Path, id: &str) -> Result<Option<Self>> {
+        let path = Self::path(renamify_dir, id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mapping = serde_json::from_str(&content).map_err(|e| {
+            RenamifyError::InvalidInput(format!("Invalid mapping {}: {e}", path.display()))
+        })?;
+        Ok(Some(mapping))
+    }
+
+    /// One `kind,old,new,occurrences` row per identifier and path, after a
+    /// header row. Paths have no occurrence count.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("kind,old,new,occurrences\n");
+        for identifier in &self.identifiers {
+            csv.push_str(&csv_row(&[
+                "identifier",
+                &identifier.old,
+                &identifier.new,
+                &identifier.occurrences.to_string(),
+            ]));
+        }
+        for path in &self.paths {
+            csv.push_str(&csv_row(&[
+                "path",
+                &path.old.to_string_lossy(),
+                &path.new.to_string_lossy(),
+                "",
+            ]));
+        }
+        csv
+    }
+}
+
+/// A CSV line, quoting the fields that need it
+fn csv_row(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                (*field).to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_content, PlanOptions};
+
+    #[test]
+    fn test_mapping_from_plan() {
+        let root = Path::new("/repo");
+        let mut plan = scan_content(
+            &root.join("a.rs"),
+            b"old_name OldName old_name\n",
+            "old_name",
+            "new_name",
+            &PlanOptions::default(),
+        )
+        .unwrap();
+        plan.id = "abc".to_string();
+        let renames = vec![(root.join("src/old_name.rs"), root.join("src/new_name.rs"))];
+
+        let mapping = RenameMapping::from_plan(&plan, &renames, root);
+        assert_eq!(
+            mapping.identifiers,
+            vec![
+                IdentifierMapping {
+                    old: "OldName".to_string(),
+                    new: "NewName".to_string(),
+                    occurrences: 1,
+                },
+                IdentifierMapping {
+                    old: "old_name".to_string(),
+                    new: "new_name".to_string(),
+                    occurrences: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            mapping.to_csv(),
+            "kind,old,new,occurrences\n\
+             identifier,OldName,NewName,1\n\
+             identifier,old_name,new_name,2\n\
+             path,src/old_name.rs,src/new_name.rs,\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_row_quotes_fields() {
+        assert_eq!(
+            csv_row(&["a", "b,c", "say \"hi\""]),
+            "a,\"b,c\",\"say \"\"hi\"\"\"\n"
+        );
+    }
+}
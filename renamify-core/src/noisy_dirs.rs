@@ -0,0 +1,179 @@
+//! Suggesting `.rnignore` entries for directories that are rarely worth
+//! renaming in, such as dependencies and build output.
+//!
+//! `renamify init --suggest-ignores` walks the tree the way `-u` does, with
+//! `.gitignore` switched off, so a `node_modules` that git already ignores is
+//! still suggested: scans with `-u` would otherwise read all of it.
+
+use crate::scanner::PlanOptions;
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Directory names that almost always hold dependencies or generated files
+pub const NOISY_DIR_NAMES: &[&str] = &[
+    "node_modules",
+    "dist",
+    "target",
+    "coverage",
+    "build",
+    ".next",
+    ".nuxt",
+    "__pycache__",
+    ".venv",
+    ".tox",
+    ".gradle",
+];
+
+/// An `.rnignore` entry worth adding, with the directories it would hide
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreSuggestion {
+    /// The line to add, such as `node_modules/`
+    pub pattern: String,
+    /// Every directory under the root with this name, shallowest first
+    pub directories: Vec<PathBuf>,
+    /// Files inside those directories
+    pub files: usize,
+}
+
+/// Noisy directories under `root` that `.rnignore` and `.ignore` files don't
+/// already hide, one suggestion per directory name
+pub fn suggest_ignores(root: &Path) -> Vec<IgnoreSuggestion> {
+    let options = PlanOptions {
+        unrestricted_level: 1,
+        ..PlanOptions::default()
+    };
+    let found = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&found);
+
+    // Replaces the walker's own filter so noisy directories are recorded
+    // instead of descended into
+    let mut builder = crate::configure_walker(&[root.to_path_buf()], &options);
+    builder.filter_entry(move |entry| {
+        if entry.depth() == 0 || !entry.file_type().is_some_and(|t| t.is_dir()) {
+            return true;
+        }
+        let name = entry.file_name();
+        if crate::vcs::is_metadata_dir(name)
+            || name == ".renamify"
+            || !crate::submodule::should_walk(entry.path(), false)
+        {
+            return false;
+        }
+        if NOISY_DIR_NAMES.iter().any(|noisy| name == *noisy) {
+            recorder.lock().unwrap().push(entry.path().to_path_buf());
+            return false;
+        }
+        true
+    });
+    for _ in builder.build() {}
+
+    let mut directories = std::mem::take(&mut *found.lock().unwrap());
+    directories.sort_by_key(|dir| (dir.components().count(), dir.clone()));
+
+    let mut suggestions: Vec<IgnoreSuggestion> = Vec::new();
+    for name in NOISY_DIR_NAMES {
+        let matching: Vec<PathBuf> = directories
+            .iter()
+            .filter(|dir| dir.file_name().is_some_and(|n| n == *name))
+            .cloned()
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+        let files = matching.iter().map(|dir| count_files(dir)).sum();
+        suggestions.push(IgnoreSuggestion {
+            pattern: format!("{name}/"),
+            directories: matching,
+            files,
+        });
+    }
+    suggestions
+}
+
+fn count_files(dir: &Path) -> usize {
+    WalkBuilder::new(dir)
+        .standard_filters(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .count()
+}
+
+/// Append `patterns` to the `.rnignore` file in `root`, creating it if
+/// needed. Returns the patterns that were not already listed.
+pub fn add_to_rnignore(root: &Path, patterns: &[String]) -> Result<Vec<String>> {
+    let path = root.join(".rnignore");
+    let mut content = if path.exists() {
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let added: Vec<String> = patterns
+        .iter()
+        .filter(|pattern| !content.lines().any(|line| line.trim() == pattern.as_str()))
+        .cloned()
+        .collect();
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for pattern in &added {
+        content.push_str(pattern);
+        content.push('\n');
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_suggest_ignores() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+        fs::write(root.join("node_modules/left-pad/index.js"), "").unwrap();
+        fs::write(root.join("node_modules/left-pad/package.json"), "").unwrap();
+        fs::create_dir_all(root.join("web/node_modules/react")).unwrap();
+        fs::write(root.join("web/node_modules/react/index.js"), "").unwrap();
+        fs::create_dir_all(root.join("coverage")).unwrap();
+        fs::write(root.join("coverage/lcov.info"), "").unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        // Ignored by git, but scanned with -u, so still suggested
+        fs::write(root.join(".gitignore"), "coverage/\n").unwrap();
+
+        let suggestions = suggest_ignores(root);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].pattern, "node_modules/");
+        assert_eq!(
+            suggestions[0].directories,
+            [root.join("node_modules"), root.join("web/node_modules")]
+        );
+        assert_eq!(suggestions[0].files, 3);
+        assert_eq!(suggestions[1].pattern, "coverage/");
+
+        // Entries already in .rnignore are not suggested again
+        let added = add_to_rnignore(root, &["coverage/".to_string()]).unwrap();
+        assert_eq!(added, ["coverage/"]);
+        let suggestions = suggest_ignores(root);
+        assert_eq!(suggestions.len(), 1);
+
+        let added = add_to_rnignore(root, &["coverage/".to_string(), "dist/".to_string()]).unwrap();
+        assert_eq!(added, ["dist/"]);
+        assert_eq!(
+            fs::read_to_string(root.join(".rnignore")).unwrap(),
+            "coverage/\ndist/\n"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Progress reporting and cancellation for long-running operations.
+//!
+//! Embedders such as editors and GUIs pass an `OperationObserver` to
+//! `plan_operation` or `apply_operation` to show progress while files are
+//! scanned and edited, and to stop the operation without killing the process.
+//! A cancelled scan returns `RenamifyError::Cancelled` without writing a plan;
+//! a cancelled apply rolls back the changes made so far first.
+
+use crate::error::RenamifyError;
+use crate::scanner::MatchHunk;
+use std::path::Path;
+
+/// Receives progress from an operation and decides whether it should stop.
+///
+/// Scans run files in parallel, so the callbacks can be called from several
+/// threads at once. Every method has a default that does nothing.
+pub trait OperationObserver: Send + Sync {
+    /// A file was scanned; `scanned` of `total` files are done so far
+    fn on_file_scanned(&self, _path: &Path, _scanned: usize, _total: usize) {}
+
+    /// A hunk was written to disk; `applied` of `total` hunks are done so far
+    fn on_hunk_applied(&self, _hunk: &MatchHunk, _applied: usize, _total: usize) {}
+
+    /// Polled between files and renames; return true to stop the operation
+    fn should_cancel(&self) -> bool {
+        false
+    }
+}
+
+/// Fail with `RenamifyError::Cancelled` if the observer asked to stop
+pub(crate) fn check_cancelled(
+    observer: Option<&dyn OperationObserver>,
+    operation: &str,
+) -> Result<(), RenamifyError> {
+    if observer.is_some_and(OperationObserver::should_cancel) {
+        return Err(RenamifyError::Cancelled(format!("{operation} cancelled")));
+    }
+    Ok(())
+}
@@ -1,56 +1,106 @@
-use crate::{apply_plan, output::ApplyResult, scanner::Plan, ApplyOptions};
-use anyhow::{anyhow, Context, Result};
+use crate::bundle::Bundle;
+use crate::error::RenamifyError;
+use crate::operations::plan::check_readable;
+use crate::operations::validate_plan::plan_issues;
+use crate::output::{ApplyCheckResult, ChangeGroup};
+use crate::preview::render_tree;
+use crate::signing::{verify_plan, PlanTrust};
+use crate::stage::{apply_plan_to_index, StageMode};
+use crate::vcs::CommitOptions;
+use crate::{
+    apply_plan, apply_plan_by_directory, apply_plan_observed, output::ApplyResult, scanner::Plan,
+    ApplyOptions, BackupStrategy, Durability, History, OperationObserver, TransactionScope,
+};
+use anyhow::{Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 /// Apply operation - returns structured data
+///
+/// With `strict`, a plan that could not read some files fails instead of
+/// leaving any matches in them unchanged. With `report_stale_paths`, build
+/// metadata the plan could not cover is checked for renamed paths afterwards.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::fn_params_excessive_bools)]
 pub fn apply_operation(
     _plan_path: Option<&Path>,
     plan_id: Option<&str>,
-    commit: bool,
+    trust: &PlanTrust,
+    commit: Option<&CommitOptions>,
+    stage: StageMode,
     force: bool,
+    strict: bool,
+    report_stale_paths: bool,
+    transaction_scope: TransactionScope,
+    durability: Durability,
+    backup: BackupStrategy,
     working_dir: Option<&Path>,
+    observer: Option<&dyn OperationObserver>,
 ) -> Result<ApplyResult> {
+    crate::workspace::require("apply")?;
+
+    if stage == StageMode::IndexOnly && transaction_scope == TransactionScope::Dir {
+        return Err(RenamifyError::InvalidInput(
+            "--stage-only writes the whole plan to the git index at once, so it can't be combined with --transaction-scope dir".to_string(),
+        )
+        .into());
+    }
+
     let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
     let renamify_dir = current_dir.join(".renamify");
 
-    // Load the plan - check if plan_id looks like a path
-    let (plan_path, plan_id) = if let Some(id) = plan_id {
-        if id.contains('/')
-            || Path::new(id)
-                .extension()
-                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
-        {
-            // It's a path
-            (Some(PathBuf::from(id)), None)
-        } else {
-            // It's an ID
-            (None, Some(id))
-        }
-    } else {
-        (None, None)
-    };
-
+    let (plan_path, plan_id) = plan_source(plan_id, current_dir);
     let (mut plan, used_default_plan_file) =
-        load_plan_from_source_with_tracking(plan_path, plan_id, &renamify_dir)?;
+        load_plan_from_source_with_tracking(plan_path, plan_id, trust, current_dir)?;
+    if strict {
+        check_readable(&plan)?;
+    }
 
     // Save stats before applying
     let files_changed = plan.stats.files_with_matches;
     let replacements = plan.stats.total_matches;
     let renames = plan.paths.len();
     let plan_id = plan.id.clone();
+    let unreadable = plan.stats.files_skipped.unreadable_files.clone();
+    let base_dir = current_dir.canonicalize().ok();
+    let by_directory = ChangeGroup::by_directory(&plan.matches, base_dir.as_deref());
+    let by_extension = ChangeGroup::by_extension(&plan.matches);
 
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: renamify_dir.join("backups"),
-        create_backups: true,
-        commit,
+        commit: commit.is_some(),
+        commit_options: commit.cloned().unwrap_or_default(),
+        stage: stage == StageMode::Staged,
         force,
         skip_symlinks: false,
         log_file: Some(renamify_dir.join("logs").join(format!("{}.log", plan.id))),
+        working_dir: Some(current_dir.to_path_buf()),
+        durability,
+        backup,
     };
 
-    apply_plan(&mut plan, &apply_options)?;
+    let transactions = match transaction_scope {
+        _ if stage == StageMode::IndexOnly => {
+            apply_plan_to_index(&plan, commit, current_dir)?;
+            Vec::new()
+        },
+        TransactionScope::Plan => {
+            apply_plan_observed(&mut plan, &apply_options, observer)?;
+            Vec::new()
+        },
+        TransactionScope::Dir => apply_plan_by_directory(&plan, &apply_options, observer)?,
+    };
+
+    // Ignored build metadata kept the old paths; only say where
+    let stale_paths = match &base_dir {
+        Some(root) if report_stale_paths => crate::stale_paths::find_stale_paths(
+            &plan,
+            root,
+            &crate::config::Config::load_in(current_dir)?.protected,
+        ),
+        _ => Vec::new(),
+    };
 
     // Delete the plan.json file after successful apply (only if using default path)
     if let Some(default_plan_path) = used_default_plan_file {
@@ -68,49 +118,244 @@ pub fn apply_operation(
         files_changed,
         replacements,
         renames,
-        committed: commit,
+        committed: commit.is_some(),
+        staged: stage != StageMode::Unstaged,
+        index_only: stage == StageMode::IndexOnly,
+        transactions,
+        drifted: Vec::new(),
+        unreadable,
+        by_directory,
+        by_extension,
+        stale_paths,
+    })
+}
+
+/// Check what applying a plan would do, without changing anything.
+///
+/// The plan is found as `apply_operation` finds it, and every file edit and
+/// rename is checked against the working tree as `validate-plan` does. With
+/// `show_tree` the touched paths are laid out as they will be afterwards.
+pub fn apply_check_operation(
+    plan_id: Option<&str>,
+    trust: &PlanTrust,
+    strict: bool,
+    show_tree: bool,
+    use_color: bool,
+    working_dir: Option<&Path>,
+) -> Result<ApplyCheckResult> {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+
+    let (plan_path, plan_id) = plan_source(plan_id, current_dir);
+    let (plan, _) = load_plan_from_source_with_tracking(plan_path, plan_id, trust, current_dir)?;
+    if strict {
+        check_readable(&plan)?;
+    }
+
+    let base_dir = current_dir.canonicalize().ok();
+    Ok(ApplyCheckResult {
+        plan_id: plan.id.clone(),
+        files_changed: plan.stats.files_with_matches,
+        replacements: plan.stats.total_matches,
+        renames: plan.paths.len(),
+        issues: plan_issues(&plan, current_dir),
+        tree: show_tree.then(|| render_tree(&plan, use_color, base_dir.as_deref())),
+        base_dir,
+    })
+}
+
+/// Where the plan given to `apply` comes from: a URL or plan ID, or a path
+/// relative to the working directory. Neither means `.renamify/plan.json`.
+fn plan_source<'a>(
+    plan_id: Option<&'a str>,
+    current_dir: &Path,
+) -> (Option<PathBuf>, Option<&'a str>) {
+    match plan_id {
+        Some(id) if crate::remote::is_url(id) => (None, Some(id)),
+        Some(id)
+            if id.contains('/')
+                || Path::new(id)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("json")) =>
+        {
+            (Some(current_dir.join(id)), None)
+        },
+        Some(id) => (None, Some(id)),
+        None => (None, None),
+    }
+}
+
+/// Apply a bundle written by `history export --bundle`, without re-planning.
+///
+/// Files that have changed since the export are refused unless `force` is set,
+/// in which case their edits are moved to where their lines are now.
+pub fn apply_bundle_operation(
+    bundle_path: &Path,
+    commit: Option<&CommitOptions>,
+    stage: StageMode,
+    force: bool,
+    durability: Durability,
+    backup: BackupStrategy,
+    working_dir: Option<&Path>,
+) -> Result<ApplyResult> {
+    crate::workspace::require("apply")?;
+
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let renamify_dir = current_dir.join(".renamify");
+
+    let bundle_path = &current_dir.join(bundle_path);
+    let bundle = Bundle::read(bundle_path)?;
+    if History::load(&renamify_dir)?
+        .find_entry(&bundle.history_id)
+        .is_some()
+    {
+        return Err(RenamifyError::Conflict(format!(
+            "Rename {} from {} is already in this checkout's history",
+            bundle.history_id,
+            bundle_path.display()
+        ))
+        .into());
+    }
+
+    let root = current_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", current_dir.display()))?;
+    let drifted = bundle.drift(&root);
+    if !drifted.is_empty() && !force {
+        let files: Vec<String> = drifted
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        return Err(RenamifyError::Conflict(format!(
+            "Files have changed since the bundle was exported: {}. Use --force-with-conflicts to apply the edits where they now belong",
+            files.join(", ")
+        ))
+        .into());
+    }
+    let mut plan = bundle.plan_for(&root, &drifted)?;
+
+    let apply_options = ApplyOptions {
+        backup_dir: renamify_dir.join("backups"),
+        commit: commit.is_some(),
+        commit_options: commit.cloned().unwrap_or_default(),
+        stage: stage == StageMode::Staged,
+        force,
+        skip_symlinks: false,
+        log_file: Some(renamify_dir.join("logs").join(format!("{}.log", plan.id))),
+        working_dir: Some(current_dir.to_path_buf()),
+        durability,
+        backup,
+    };
+    if stage == StageMode::IndexOnly {
+        apply_plan_to_index(&plan, commit, current_dir)?;
+    } else {
+        apply_plan(&mut plan, &apply_options)?;
+    }
+
+    Ok(ApplyResult {
+        plan_id: plan.id.clone(),
+        files_changed: plan.stats.files_with_matches,
+        replacements: plan.stats.total_matches,
+        renames: plan.paths.len(),
+        committed: commit.is_some(),
+        staged: stage != StageMode::Unstaged,
+        index_only: stage == StageMode::IndexOnly,
+        transactions: Vec::new(),
+        drifted,
+        unreadable: Vec::new(),
+        by_directory: ChangeGroup::by_directory(&plan.matches, Some(&root)),
+        by_extension: ChangeGroup::by_extension(&plan.matches),
+        stale_paths: Vec::new(),
     })
 }
 
+/// Load the plan named by an ID, path or URL as `apply` does, or
+/// `.renamify/plan.json` when none is given
+pub(crate) fn load_plan(plan: Option<&str>, current_dir: &Path) -> Result<Plan> {
+    let (plan_path, plan_id) = plan_source(plan, current_dir);
+    let (plan, _) = load_plan_from_source_with_tracking(
+        plan_path,
+        plan_id,
+        &PlanTrust::default(),
+        current_dir,
+    )?;
+    Ok(plan)
+}
+
+/// Read and parse a plan JSON file written by `renamify plan`
+pub(crate) fn read_plan_file(path: &Path) -> Result<Plan> {
+    if !path.exists() {
+        return Err(RenamifyError::NotFound(format!(
+            "Failed to read plan file {}: file not found",
+            path.display()
+        ))
+        .into());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read plan file {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse plan file {}", path.display()))
+}
+
 fn load_plan_from_source_with_tracking(
     plan_path: Option<PathBuf>,
     plan_id: Option<&str>,
-    renamify_dir: &Path,
+    trust: &PlanTrust,
+    current_dir: &Path,
 ) -> Result<(Plan, Option<PathBuf>)> {
+    let renamify_dir = &current_dir.join(".renamify");
+    let root = current_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", current_dir.display()))?;
+    // Reads a path or URL, checking its checksum and signature, and moves a
+    // plan made in another checkout onto this one
+    let read = |source: &str| -> Result<Plan> {
+        let content = crate::remote::read_plan_source(source, trust.checksum.as_deref())?;
+        verify_plan(source, &content, trust)?;
+        let mut plan: Plan = serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse plan file {source}"))?;
+        crate::remote::rebase_plan(&mut plan, source, &root)?;
+        Ok(plan)
+    };
     match (plan_path, plan_id) {
         (Some(path), None) => {
-            // Load from specified path
-            let content = fs::read_to_string(&path)
-                .with_context(|| format!("Failed to read plan file {}", path.display()))?;
-            let plan = serde_json::from_str(&content)
-                .with_context(|| format!("Failed to parse plan file {}", path.display()))?;
-            Ok((plan, None))
+            if !path.exists() {
+                return Err(RenamifyError::NotFound(format!(
+                    "Failed to read plan file {}: file not found",
+                    path.display()
+                ))
+                .into());
+            }
+            Ok((read(&path.to_string_lossy())?, None))
         },
+        (None, Some(url)) if crate::remote::is_url(url) => Ok((read(url)?, None)),
         (None, Some(id)) => {
             // For plan IDs, we need to look for the plan file in .renamify/plans/
             // The history only stores metadata, not the full plan
             let plan_path = renamify_dir.join("plans").join(format!("{}.json", id));
             if !plan_path.exists() {
-                return Err(anyhow!("Plan with ID {} not found", id));
+                return Err(
+                    RenamifyError::NotFound(format!("Plan with ID {} not found", id)).into(),
+                );
             }
 
-            let content = fs::read_to_string(&plan_path)?;
-            let plan = serde_json::from_str(&content)?;
-            Ok((plan, None))
+            Ok((read(&plan_path.to_string_lossy())?, None))
         },
         (None, None) => {
             // Load from default plan.json
             let default_plan_path = renamify_dir.join("plan.json");
             if !default_plan_path.exists() {
-                return Err(anyhow!(
-                    "No plan file found. Create one with 'renamify plan' first."
-                ));
+                return Err(RenamifyError::NotFound(
+                    "No plan file found. Create one with 'renamify plan' first.".to_string(),
+                )
+                .into());
             }
 
-            let content = fs::read_to_string(&default_plan_path)?;
-            let plan = serde_json::from_str(&content)?;
+            let plan = read(&default_plan_path.to_string_lossy())?;
             Ok((plan, Some(default_plan_path)))
         },
-        (Some(_), Some(_)) => Err(anyhow!("Cannot specify both plan path and plan ID")),
+        (Some(_), Some(_)) => Err(RenamifyError::InvalidInput(
+            "Cannot specify both plan path and plan ID".to_string(),
+        )
+        .into()),
     }
 }
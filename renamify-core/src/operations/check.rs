@@ -0,0 +1,220 @@
+use crate::error::RenamifyError;
+use crate::output::{CheckResult, CheckViolation};
+use crate::scanner::{build_globset, build_include_globset, Plan};
+use crate::vcs::{Git, Vcs};
+use crate::{scan_content, scan_repository_multi, PlanOptions, WalkOptions};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Check operation - scans for every case variant of each forbidden identifier
+pub fn check_operation(
+    forbidden: &[String],
+    paths: Vec<PathBuf>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    unrestricted_level: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    working_dir: Option<&Path>,
+) -> Result<CheckResult> {
+    require_forbidden(forbidden)?;
+
+    let current_dir = working_dir.map_or_else(
+        || std::env::current_dir().expect("Failed to get current directory"),
+        Path::to_path_buf,
+    );
+
+    // Use provided paths or default to current directory
+    let search_paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths
+    };
+
+    let resolved_paths: Vec<PathBuf> = search_paths
+        .iter()
+        .map(|path| {
+            let absolute_path = if path.is_absolute() {
+                path.clone()
+            } else {
+                current_dir.join(path)
+            };
+            absolute_path.canonicalize().unwrap_or(absolute_path)
+        })
+        .collect();
+
+    let config = crate::config::Config::load_in(&current_dir)?;
+    let options = PlanOptions {
+        includes: include,
+        excludes: exclude,
+        unrestricted_level: unrestricted_level.min(3),
+        include_submodules,
+        walk,
+        acronym_rendering: config.acronym_rendering,
+        case_mapping: config.case_mapping,
+        ..PlanOptions::default()
+    };
+
+    let relative = |path: &Path| -> PathBuf {
+        path.strip_prefix(&current_dir)
+            .map_or_else(|_| path.to_path_buf(), Path::to_path_buf)
+    };
+
+    let mut files_scanned = 0;
+    let mut violations = Vec::new();
+
+    for term in forbidden {
+        let plan = scan_repository_multi(&resolved_paths, term, "", &options)
+            .with_context(|| format!("Failed to scan for forbidden identifier '{}'", term))?;
+        files_scanned = files_scanned.max(plan.stats.files_scanned);
+
+        violations.extend(content_violations(term, &plan, &relative));
+        for rename in &plan.paths {
+            let content = rename
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            violations.push(CheckViolation {
+                term: term.clone(),
+                file: relative(&rename.path),
+                line: None,
+                column: None,
+                content,
+            });
+        }
+    }
+
+    Ok(check_result(forbidden, files_scanned, violations))
+}
+
+/// Check only the files staged for the next commit, for a pre-commit hook.
+///
+/// Content comes from the git index, so a partly staged file is checked as
+/// it will be committed, and nothing else in the tree is read. The name of
+/// each staged file and its directories is checked too; deleted files are
+/// skipped. `include` and `exclude` are matched against paths relative to the
+/// repository root.
+pub fn check_staged_operation(
+    forbidden: &[String],
+    include: &[String],
+    exclude: &[String],
+    working_dir: Option<&Path>,
+) -> Result<CheckResult> {
+    require_forbidden(forbidden)?;
+
+    let current_dir = working_dir.map_or_else(
+        || std::env::current_dir().expect("Failed to get current directory"),
+        Path::to_path_buf,
+    );
+    let git = Git::containing(&current_dir)?;
+    let root = git.root().to_path_buf();
+    let relative = |path: &Path| -> PathBuf {
+        path.strip_prefix(&current_dir)
+            .map_or_else(|_| path.to_path_buf(), Path::to_path_buf)
+    };
+
+    let include_globs = build_include_globset(include)?;
+    let exclude_globs = build_globset(exclude)?;
+    let staged: Vec<String> = git
+        .staged_paths()?
+        .into_iter()
+        .filter(|path| {
+            include_globs
+                .as_ref()
+                .is_none_or(|globs| globs.is_match(path))
+                && !exclude_globs
+                    .as_ref()
+                    .is_some_and(|globs| globs.is_match(path))
+        })
+        .collect();
+    let contents = git.staged_contents(&staged)?;
+
+    // Each staged path and the directories above it, named once
+    let names: BTreeSet<&Path> = staged
+        .iter()
+        .flat_map(|path| Path::new(path).ancestors())
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect();
+
+    let options = PlanOptions::default();
+    let mut files_scanned = 0;
+    let mut violations = Vec::new();
+    for (path, content) in staged.iter().zip(&contents) {
+        let Some(content) = content else {
+            continue;
+        };
+        files_scanned += 1;
+        let file = root.join(path);
+        for term in forbidden {
+            let plan = scan_content(&file, content, term, "", &options)
+                .with_context(|| format!("Failed to scan for forbidden identifier '{}'", term))?;
+            violations.extend(content_violations(term, &plan, &relative));
+        }
+    }
+    for path in names {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        for term in forbidden {
+            // The name is scanned as text with the same matcher as contents
+            let plan = scan_content(Path::new(name), name.as_bytes(), term, "", &options)?;
+            if !plan.matches.is_empty() {
+                violations.push(CheckViolation {
+                    term: term.clone(),
+                    file: relative(&root.join(path)),
+                    line: None,
+                    column: None,
+                    content: name.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(check_result(forbidden, files_scanned, violations))
+}
+
+fn require_forbidden(forbidden: &[String]) -> Result<()> {
+    if forbidden.is_empty() {
+        return Err(RenamifyError::InvalidInput(
+            "No forbidden identifiers given (use --forbid or set `forbid` in the [check] section of .renamify/config.toml)"
+                .to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// A violation for each match of `term` in the contents `plan` scanned
+fn content_violations<'a>(
+    term: &'a str,
+    plan: &'a Plan,
+    relative: &'a dyn Fn(&Path) -> PathBuf,
+) -> impl Iterator<Item = CheckViolation> + 'a {
+    plan.matches.iter().map(move |hunk| CheckViolation {
+        term: term.to_string(),
+        file: relative(&hunk.file),
+        line: Some(hunk.line),
+        column: Some(hunk.char_offset + 1),
+        content: hunk.content.clone(),
+    })
+}
+
+fn check_result(
+    forbidden: &[String],
+    files_scanned: usize,
+    mut violations: Vec<CheckViolation>,
+) -> CheckResult {
+    violations.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    CheckResult {
+        forbidden: forbidden.to_vec(),
+        files_scanned,
+        violations,
+    }
+}
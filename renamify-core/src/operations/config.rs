@@ -0,0 +1,105 @@
+use crate::config::{Config, ConfigOrigin, ConfigScope};
+use crate::error::RenamifyError;
+use crate::output::{ConfigGetResult, ConfigSetResult, ConfigShowResult};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Config show operation - returns the effective configuration after layering
+/// built-in defaults, the user config, the repo config and environment variables
+pub fn config_show_operation(
+    show_origin: bool,
+    working_dir: Option<&Path>,
+) -> Result<ConfigShowResult> {
+    let layered = load_layered(working_dir)?;
+
+    Ok(ConfigShowResult {
+        config: layered.config,
+        origins: show_origin.then_some(layered.origins),
+    })
+}
+
+/// Config get operation - reads one key from the effective configuration, or
+/// from a single config file when a scope is given
+pub fn config_get_operation(
+    key: &str,
+    scope: Option<ConfigScope>,
+    working_dir: Option<&Path>,
+) -> Result<ConfigGetResult> {
+    let (value, origin) = if let Some(scope) = scope {
+        let path = scope_path(scope, working_dir)?;
+        (Config::get_in_file(&path, key)?, scope_origin(scope, path))
+    } else {
+        let layered = load_layered(working_dir)?;
+        let value = layered.config.get(key)?;
+        let origin = layered
+            .origins
+            .get(key)
+            .cloned()
+            .unwrap_or(ConfigOrigin::Default);
+        (value, origin)
+    };
+
+    let value = value.ok_or_else(|| match &origin {
+        ConfigOrigin::User(path) | ConfigOrigin::Repo(path) => RenamifyError::NotFound(format!(
+            "Config key '{key}' is not set in {}",
+            path.display()
+        )),
+        _ => RenamifyError::NotFound(format!("Config key '{key}' is not set")),
+    })?;
+
+    Ok(ConfigGetResult {
+        key: key.to_string(),
+        value,
+        origin,
+    })
+}
+
+/// Config set operation - writes one key to the user or repo config file
+pub fn config_set_operation(
+    key: &str,
+    value: &str,
+    scope: ConfigScope,
+    working_dir: Option<&Path>,
+) -> Result<ConfigSetResult> {
+    let path = scope_path(scope, working_dir)?;
+    let value = Config::set_in_file(&path, key, value)?;
+
+    Ok(ConfigSetResult {
+        key: key.to_string(),
+        value,
+        scope,
+        path,
+    })
+}
+
+fn current_dir(working_dir: Option<&Path>) -> Result<PathBuf> {
+    match working_dir {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => std::env::current_dir().context("Failed to get current directory"),
+    }
+}
+
+fn load_layered(working_dir: Option<&Path>) -> Result<crate::config::LayeredConfig> {
+    Config::load_layered_in(&current_dir(working_dir)?)
+}
+
+fn scope_path(scope: ConfigScope, working_dir: Option<&Path>) -> Result<PathBuf> {
+    match scope {
+        ConfigScope::User => Config::user_config_path().ok_or_else(|| {
+            RenamifyError::InvalidInput(
+                "Cannot locate the user config: no home directory".to_string(),
+            )
+            .into()
+        }),
+        ConfigScope::Repo => Ok(current_dir(working_dir)?
+            .join(".renamify")
+            .join("config.toml")),
+    }
+}
+
+const fn scope_origin(scope: ConfigScope, path: PathBuf) -> ConfigOrigin {
+    match scope {
+        ConfigScope::User => ConfigOrigin::User(path),
+        ConfigScope::Repo => ConfigOrigin::Repo(path),
+    }
+}
@@ -0,0 +1,257 @@
+use crate::output::{DoctorCheck, DoctorResult, DoctorStatus};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Doctor operation - checks the environment renamify runs in.
+///
+/// Looks at git, the file system's case sensitivity, long path support on
+/// Windows, whether `.renamify` can be written, the locale and color output,
+/// and says what to do about anything that will get in the way. `no_color` is
+/// whether color was turned off with `--no-color` or `NO_COLOR`.
+pub fn doctor_operation(no_color: bool, working_dir: Option<&Path>) -> DoctorResult {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    DoctorResult {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        checks: vec![
+            check_git(current_dir),
+            check_case_sensitivity(current_dir),
+            check_long_paths(current_dir),
+            check_workspace(&current_dir.join(".renamify")),
+            check_locale(|name| std::env::var(name).ok()),
+            check_color(no_color),
+        ],
+    }
+}
+
+fn check(name: &str, status: DoctorStatus, detail: impl Into<String>) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_string(),
+        status,
+        detail: detail.into(),
+        hint: None,
+    }
+}
+
+fn with_hint(mut check: DoctorCheck, hint: &str) -> DoctorCheck {
+    check.hint = Some(hint.to_string());
+    check
+}
+
+fn check_git(dir: &Path) -> DoctorCheck {
+    let version = Command::new("git")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    let Some(version) = version else {
+        return with_hint(
+            check("git", DoctorStatus::Warn, "git was not found on PATH"),
+            "Install git to use --stage, --commit and sparse checkout detection",
+        );
+    };
+    let in_repository = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(dir)
+        .output()
+        .is_ok_and(|output| output.status.success());
+    let version = String::from_utf8_lossy(&version.stdout).trim().to_string();
+    let detail = if in_repository {
+        format!("{version}, inside a repository")
+    } else {
+        format!("{version}, not inside a repository")
+    };
+    check("git", DoctorStatus::Pass, detail)
+}
+
+fn check_case_sensitivity(dir: &Path) -> DoctorCheck {
+    if crate::rename::detect_case_insensitive_fs(dir) {
+        check(
+            "filesystem",
+            DoctorStatus::Pass,
+            "case-insensitive; case-only renames go through a temporary name",
+        )
+    } else {
+        check("filesystem", DoctorStatus::Pass, "case-sensitive")
+    }
+}
+
+fn check_long_paths(dir: &Path) -> DoctorCheck {
+    if !cfg!(windows) {
+        return check("long-paths", DoctorStatus::Skip, "only applies on Windows");
+    }
+    let Ok(temp_dir) = TempDir::new_in(dir) else {
+        return check(
+            "long-paths",
+            DoctorStatus::Skip,
+            "could not create a test directory here",
+        );
+    };
+    let long_path = ["a", "b", "c"]
+        .iter()
+        .fold(temp_dir.path().to_path_buf(), |path, name| {
+            path.join(name.repeat(100))
+        });
+    if fs::create_dir_all(&long_path).is_ok() {
+        check(
+            "long-paths",
+            DoctorStatus::Pass,
+            "paths over 260 characters are supported",
+        )
+    } else {
+        with_hint(
+            check(
+                "long-paths",
+                DoctorStatus::Warn,
+                "paths over 260 characters can't be created",
+            ),
+            "Set LongPathsEnabled to 1 under HKLM\\SYSTEM\\CurrentControlSet\\Control\\FileSystem",
+        )
+    }
+}
+
+fn check_workspace(renamify_dir: &Path) -> DoctorCheck {
+    if crate::workspace::is_disabled() {
+        return check(
+            "workspace",
+            DoctorStatus::Skip,
+            "disabled with --no-workspace",
+        );
+    }
+    if crate::workspace::is_writable(renamify_dir) {
+        check("workspace", DoctorStatus::Pass, ".renamify is writable")
+    } else {
+        with_hint(
+            check("workspace", DoctorStatus::Fail, ".renamify is not writable"),
+            "Fix the permissions on .renamify and its parent, or pass --no-workspace to search and plan without it",
+        )
+    }
+}
+
+/// The locale from `LC_ALL`, `LC_CTYPE` or `LANG`, in that order, as looked
+/// up by `var`
+fn check_locale(var: impl Fn(&str) -> Option<String>) -> DoctorCheck {
+    const HINT: &str =
+        "Set LANG to a UTF-8 locale such as en_US.UTF-8 so non-ASCII names display correctly";
+    if cfg!(windows) {
+        return check("locale", DoctorStatus::Pass, "Windows paths are Unicode");
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].into_iter().find_map(|name| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(|value| format!("{name}={value}"))
+    });
+    match locale {
+        Some(locale) => {
+            let lower = locale.to_ascii_lowercase();
+            if lower.contains("utf-8") || lower.contains("utf8") {
+                check("locale", DoctorStatus::Pass, locale)
+            } else {
+                with_hint(
+                    check(
+                        "locale",
+                        DoctorStatus::Warn,
+                        format!("{locale} is not UTF-8"),
+                    ),
+                    HINT,
+                )
+            }
+        },
+        None => with_hint(
+            check(
+                "locale",
+                DoctorStatus::Warn,
+                "no locale set in LC_ALL, LC_CTYPE or LANG",
+            ),
+            HINT,
+        ),
+    }
+}
+
+fn check_color(no_color: bool) -> DoctorCheck {
+    if no_color {
+        return check(
+            "color",
+            DoctorStatus::Pass,
+            "disabled by --no-color or NO_COLOR",
+        );
+    }
+    if !io::stdout().is_terminal() {
+        return check(
+            "color",
+            DoctorStatus::Pass,
+            "disabled because output is not a terminal",
+        );
+    }
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return with_hint(
+            check(
+                "color",
+                DoctorStatus::Warn,
+                "enabled, but TERM=dumb may not show colors",
+            ),
+            "Pass --no-color or set NO_COLOR",
+        );
+    }
+    check("color", DoctorStatus::Pass, "enabled")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_check_locale() {
+        let env = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+                .collect();
+            move |name: &str| vars.get(name).cloned()
+        };
+        if cfg!(windows) {
+            return;
+        }
+
+        let found = check_locale(env(&[("LANG", "en_US.UTF-8")]));
+        assert_eq!(found.status, DoctorStatus::Pass);
+        assert_eq!(found.detail, "LANG=en_US.UTF-8");
+
+        // LC_ALL outranks LANG
+        let found = check_locale(env(&[("LC_ALL", "C"), ("LANG", "en_US.utf8")]));
+        assert_eq!(found.status, DoctorStatus::Warn);
+        assert_eq!(found.detail, "LC_ALL=C is not UTF-8");
+        assert!(found.hint.is_some());
+
+        let found = check_locale(env(&[("LC_ALL", "")]));
+        assert_eq!(found.status, DoctorStatus::Warn);
+    }
+
+    #[test]
+    fn test_doctor_operation() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = doctor_operation(true, Some(temp_dir.path()));
+
+        let names: Vec<&str> = result.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "git",
+                "filesystem",
+                "long-paths",
+                "workspace",
+                "locale",
+                "color"
+            ]
+        );
+        let workspace = &result.checks[3];
+        assert_eq!(workspace.status, DoctorStatus::Pass);
+        assert!(result.passed());
+        // Checking leaves nothing behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+}
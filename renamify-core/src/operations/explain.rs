@@ -0,0 +1,46 @@
+use crate::acronym::{with_rendering, AcronymRendering};
+use crate::case_model::{with_case_mapping, CaseMapping};
+use crate::compound_matcher::explain;
+use crate::error::RenamifyError;
+use crate::operations::plan::build_styles_list;
+use crate::output::ExplainResult;
+use crate::Style;
+use anyhow::Result;
+
+/// Explain operation - reports how a single identifier is tokenized and
+/// whether, and why, it matches the search term
+pub fn explain_operation(
+    search: &str,
+    identifier: &str,
+    replace: Option<&str>,
+    exclude_styles: &[Style],
+    include_styles: &[Style],
+    only_styles: &[Style],
+    acronym_rendering: AcronymRendering,
+    case_mapping: CaseMapping,
+) -> Result<ExplainResult> {
+    if search.trim().is_empty() || identifier.trim().is_empty() {
+        return Err(RenamifyError::InvalidInput(
+            "explain needs a search term and an identifier".to_string(),
+        )
+        .into());
+    }
+
+    let styles = build_styles_list(
+        exclude_styles.to_vec(),
+        include_styles.to_vec(),
+        only_styles.to_vec(),
+    )
+    .unwrap_or_else(Style::default_styles);
+    let replace = replace.unwrap_or_default();
+
+    Ok(ExplainResult {
+        search: search.to_string(),
+        replace: replace.to_string(),
+        explanation: with_rendering(acronym_rendering, || {
+            with_case_mapping(case_mapping, || {
+                explain(identifier, search, replace, &styles)
+            })
+        }),
+    })
+}
@@ -0,0 +1,275 @@
+use crate::atomic::AtomicConfig;
+use crate::history::HistoryEntry;
+use crate::output::{
+    ApplyCheckOutput, ApplyCheckSummary, ApplyOutput, ApplySummary, BuildInfo, ChangeGroup,
+    ChangeSummary, ChangedMatch, ChangedRename, CheckOutput, CheckSummary, CheckViolation,
+    ConfigGetOutput, ConfigSetOutput, ConfigShowOutput, DoctorCheck, DoctorOutput, DoctorStatus,
+    ErrorDetails, ErrorOutput, ExplainOutput, ExportTypesOutput, ExportTypesResult, GcArtifact,
+    GcArtifactKind, GcOutput, GuardDetails, HistoryExportOutput, HistoryItem, HistoryResult,
+    LargeChangeCheck, MatchChanges, OwnerPlan, PendingPlan, PlanDiffOutput, PlanDiffSummary,
+    PlanDrift, PlanIssue, PlanIssueKind, PlanMergeOutput, PlanMergeSummary, PlanOutput,
+    PlanSummary, PlanValidationOutput, PlanValidationSummary, PreviewFileOutput, RedoOutput,
+    RenameChanges, RenameCollision, RenameOutput, RenameSafetyChecks, ReplanOutput, ReplanSummary,
+    ReportOutput, StatusResult, UndoOutput, UndoSummary, UsageByMonth, UsageReport, VersionResult,
+};
+use crate::scanner::{
+    BoundaryClass, CoercionMode, LargeFile, LiteralMode, MatchAnchor, MatchHunk, MatchKind, Plan,
+    PlanOptions, Rename, RenameKind, ScanTimings, SkipReason, SkippedFiles, SkippedMatch, Stats,
+    UnreadableFile, WalkOptions,
+};
+use crate::structured::{StructuredFilter, StructuredTarget};
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde_json::json;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Name of the npm package written alongside the types
+const PACKAGE_NAME: &str = "renamify-types";
+
+/// Export types operation - writes the TypeScript declarations and JSON schemas
+/// for the plan, history and result types as a versioned `renamify-types` package.
+///
+/// Layout of `out_dir`:
+/// - `<Type>.d.ts` for every type, plus an `index.d.ts` re-exporting them all
+/// - `schema/<Type>.schema.json` with the JSON schema of every type
+/// - `package.json` carrying the renamify version
+pub fn export_types_operation(
+    out_dir: &Path,
+    working_dir: Option<&Path>,
+) -> Result<ExportTypesResult> {
+    let target_dir = match working_dir {
+        Some(dir) if out_dir.is_relative() => dir.join(out_dir),
+        _ => out_dir.to_path_buf(),
+    };
+    let schema_dir = target_dir.join("schema");
+    fs::create_dir_all(&schema_dir)
+        .with_context(|| format!("Failed to create directory: {}", schema_dir.display()))?;
+
+    let version = env!("CARGO_PKG_VERSION");
+    let types = vec![
+        export_type::<Plan>(&target_dir, version)?,
+        export_type::<MatchHunk>(&target_dir, version)?,
+        export_type::<Rename>(&target_dir, version)?,
+        export_type::<RenameKind>(&target_dir, version)?,
+        export_type::<Stats>(&target_dir, version)?,
+        export_type::<SkippedFiles>(&target_dir, version)?,
+        export_type::<SkippedMatch>(&target_dir, version)?,
+        export_type::<SkipReason>(&target_dir, version)?,
+        export_type::<ScanTimings>(&target_dir, version)?,
+        export_type::<crate::case_model::Style>(&target_dir, version)?,
+        export_type::<crate::coercion::Style>(&target_dir, version)?,
+        export_type::<PlanOptions>(&target_dir, version)?,
+        export_type::<crate::acronym::AcronymRendering>(&target_dir, version)?,
+        export_type::<crate::case_model::CaseMapping>(&target_dir, version)?,
+        export_type::<CoercionMode>(&target_dir, version)?,
+        export_type::<LiteralMode>(&target_dir, version)?,
+        export_type::<StructuredFilter>(&target_dir, version)?,
+        export_type::<StructuredTarget>(&target_dir, version)?,
+        export_type::<crate::suppress::FileLines>(&target_dir, version)?,
+        export_type::<crate::warnings::PlanWarning>(&target_dir, version)?,
+        export_type::<crate::warnings::WarningKind>(&target_dir, version)?,
+        export_type::<crate::warnings::WarningSeverity>(&target_dir, version)?,
+        export_type::<AtomicConfig>(&target_dir, version)?,
+        export_type::<HistoryEntry>(&target_dir, version)?,
+        export_type::<StatusResult>(&target_dir, version)?,
+        export_type::<PendingPlan>(&target_dir, version)?,
+        export_type::<PlanDrift>(&target_dir, version)?,
+        export_type::<VersionResult>(&target_dir, version)?,
+        export_type::<BuildInfo>(&target_dir, version)?,
+        export_type::<MatchKind>(&target_dir, version)?,
+        export_type::<BoundaryClass>(&target_dir, version)?,
+        export_type::<MatchAnchor>(&target_dir, version)?,
+        export_type::<WalkOptions>(&target_dir, version)?,
+        export_type::<LargeFile>(&target_dir, version)?,
+        export_type::<UnreadableFile>(&target_dir, version)?,
+        export_type::<crate::suspicious::SuspiciousRename>(&target_dir, version)?,
+        // What each command prints with `--output json`, and what those contain
+        export_type::<PlanOutput>(&target_dir, version)?,
+        export_type::<PlanSummary>(&target_dir, version)?,
+        export_type::<OwnerPlan>(&target_dir, version)?,
+        export_type::<crate::sparse::SparseReport>(&target_dir, version)?,
+        export_type::<crate::empty_plan::EmptyPlanDiagnosis>(&target_dir, version)?,
+        export_type::<crate::empty_plan::FilteredMatches>(&target_dir, version)?,
+        export_type::<ApplyOutput>(&target_dir, version)?,
+        export_type::<ApplySummary>(&target_dir, version)?,
+        export_type::<ChangeGroup>(&target_dir, version)?,
+        export_type::<crate::stale_paths::StalePath>(&target_dir, version)?,
+        export_type::<ApplyCheckOutput>(&target_dir, version)?,
+        export_type::<ApplyCheckSummary>(&target_dir, version)?,
+        export_type::<PlanIssue>(&target_dir, version)?,
+        export_type::<PlanIssueKind>(&target_dir, version)?,
+        export_type::<UndoOutput>(&target_dir, version)?,
+        export_type::<UndoSummary>(&target_dir, version)?,
+        export_type::<crate::undo::UndoConflict>(&target_dir, version)?,
+        export_type::<crate::undo::LocalChange>(&target_dir, version)?,
+        export_type::<RedoOutput>(&target_dir, version)?,
+        export_type::<ChangeSummary>(&target_dir, version)?,
+        export_type::<HistoryResult>(&target_dir, version)?,
+        export_type::<HistoryItem>(&target_dir, version)?,
+        export_type::<HistoryExportOutput>(&target_dir, version)?,
+        export_type::<RenameOutput>(&target_dir, version)?,
+        export_type::<RenameSafetyChecks>(&target_dir, version)?,
+        export_type::<LargeChangeCheck>(&target_dir, version)?,
+        export_type::<RenameCollision>(&target_dir, version)?,
+        export_type::<PlanValidationOutput>(&target_dir, version)?,
+        export_type::<PlanValidationSummary>(&target_dir, version)?,
+        export_type::<GcOutput>(&target_dir, version)?,
+        export_type::<GcArtifact>(&target_dir, version)?,
+        export_type::<GcArtifactKind>(&target_dir, version)?,
+        export_type::<DoctorOutput>(&target_dir, version)?,
+        export_type::<DoctorCheck>(&target_dir, version)?,
+        export_type::<DoctorStatus>(&target_dir, version)?,
+        export_type::<ReportOutput>(&target_dir, version)?,
+        export_type::<UsageReport>(&target_dir, version)?,
+        export_type::<UsageByMonth>(&target_dir, version)?,
+        export_type::<PlanDiffOutput>(&target_dir, version)?,
+        export_type::<PlanDiffSummary>(&target_dir, version)?,
+        export_type::<MatchChanges>(&target_dir, version)?,
+        export_type::<RenameChanges>(&target_dir, version)?,
+        export_type::<ChangedMatch>(&target_dir, version)?,
+        export_type::<ChangedRename>(&target_dir, version)?,
+        export_type::<ReplanOutput>(&target_dir, version)?,
+        export_type::<ReplanSummary>(&target_dir, version)?,
+        export_type::<ExplainOutput>(&target_dir, version)?,
+        export_type::<crate::compound_matcher::Explanation>(&target_dir, version)?,
+        export_type::<crate::compound_matcher::ExplainedMatch>(&target_dir, version)?,
+        export_type::<PreviewFileOutput>(&target_dir, version)?,
+        export_type::<PlanMergeOutput>(&target_dir, version)?,
+        export_type::<PlanMergeSummary>(&target_dir, version)?,
+        export_type::<ExportTypesOutput>(&target_dir, version)?,
+        export_type::<ConfigShowOutput>(&target_dir, version)?,
+        export_type::<ConfigGetOutput>(&target_dir, version)?,
+        export_type::<ConfigSetOutput>(&target_dir, version)?,
+        export_type::<crate::config::Config>(&target_dir, version)?,
+        export_type::<crate::config::DefaultsConfig>(&target_dir, version)?,
+        export_type::<crate::config::CheckConfig>(&target_dir, version)?,
+        export_type::<crate::config::CommitConfig>(&target_dir, version)?,
+        export_type::<crate::config::ConfigOrigin>(&target_dir, version)?,
+        export_type::<crate::config::ConfigScope>(&target_dir, version)?,
+        export_type::<crate::profile::Profile>(&target_dir, version)?,
+        export_type::<crate::short_search::ShortSearchConfig>(&target_dir, version)?,
+        export_type::<crate::backup::BackupStrategy>(&target_dir, version)?,
+        export_type::<crate::apply::Durability>(&target_dir, version)?,
+        export_type::<CheckOutput>(&target_dir, version)?,
+        export_type::<CheckSummary>(&target_dir, version)?,
+        export_type::<CheckViolation>(&target_dir, version)?,
+        export_type::<ErrorOutput>(&target_dir, version)?,
+        export_type::<ErrorDetails>(&target_dir, version)?,
+        export_type::<crate::error::ErrorKind>(&target_dir, version)?,
+        export_type::<GuardDetails>(&target_dir, version)?,
+        export_type::<crate::operations::plan::GuardThreshold>(&target_dir, version)?,
+    ];
+
+    let mut index = format!("// {PACKAGE_NAME} {version}, generated by `renamify export-types`\n");
+    for name in &types {
+        writeln!(index, "export type {{ {name} }} from \"./{name}\";").unwrap();
+    }
+    write_file(&target_dir.join("index.d.ts"), &index)?;
+
+    let package = json!({
+        "name": PACKAGE_NAME,
+        "version": version,
+        "description": "TypeScript types and JSON schemas for renamify plans, history and results",
+        "license": "MIT",
+        "types": "index.d.ts",
+        "files": ["*.d.ts", "schema/"],
+    });
+    write_file(
+        &target_dir.join("package.json"),
+        &format!("{}\n", serde_json::to_string_pretty(&package)?),
+    )?;
+
+    Ok(ExportTypesResult {
+        out_dir: out_dir.to_path_buf(),
+        version: version.to_string(),
+        types,
+    })
+}
+
+/// Write `<name>.d.ts` and `schema/<name>.schema.json` for one type, returning its name
+fn export_type<T: TS + JsonSchema + 'static>(out_dir: &Path, version: &str) -> Result<String> {
+    let name = T::name();
+
+    let declaration = T::export_to_string()
+        .with_context(|| format!("Failed to generate TypeScript for {name}"))?;
+    write_file(&out_dir.join(format!("{name}.d.ts")), &declaration)?;
+
+    let mut schema = schemars::schema_for!(T);
+    schema.insert("title".to_string(), json!(name));
+    schema.insert(
+        "$comment".to_string(),
+        json!(format!("{PACKAGE_NAME} {version}")),
+    );
+    let schema_path: PathBuf = out_dir.join("schema").join(format!("{name}.schema.json"));
+    write_file(
+        &schema_path,
+        &format!("{}\n", serde_json::to_string_pretty(&schema)?),
+    )?;
+
+    Ok(name)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_types_writes_package() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = export_types_operation(Path::new("types"), Some(temp_dir.path())).unwrap();
+
+        let out_dir = temp_dir.path().join("types");
+        assert!(result.types.contains(&"Plan".to_string()));
+        assert!(result.types.contains(&"CoercionStyle".to_string()));
+
+        let plan = fs::read_to_string(out_dir.join("Plan.d.ts")).unwrap();
+        assert!(plan.contains("export type Plan = {"));
+        assert!(plan.contains("import type { MatchHunk } from \"./MatchHunk\";"));
+
+        let index = fs::read_to_string(out_dir.join("index.d.ts")).unwrap();
+        assert!(index.contains("export type { HistoryEntry } from \"./HistoryEntry\";"));
+        for output in ["PlanOutput", "ApplyOutput", "CheckOutput", "ErrorOutput"] {
+            assert!(index.contains(&format!("export type {{ {output} }} from \"./{output}\";")));
+        }
+
+        // Every type a declaration imports is exported too
+        for name in &result.types {
+            let declaration = fs::read_to_string(out_dir.join(format!("{name}.d.ts"))).unwrap();
+            for line in declaration.lines() {
+                if let Some(import) = line.strip_prefix("import type { ") {
+                    let imported = import.split(' ').next().unwrap();
+                    assert!(
+                        result.types.iter().any(|name| name == imported),
+                        "{name} imports {imported}, which is not exported"
+                    );
+                }
+            }
+        }
+
+        let package: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(out_dir.join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(package["name"], "renamify-types");
+        assert_eq!(package["version"], env!("CARGO_PKG_VERSION"));
+
+        let schema: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(out_dir.join("schema").join("Plan.schema.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(schema["title"], "Plan");
+        assert!(schema["properties"]["matches"].is_object());
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("paths")));
+    }
+}
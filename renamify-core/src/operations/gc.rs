@@ -0,0 +1,296 @@
+use crate::backup::GitBlobs;
+use crate::lock::{is_process_running, stale_lock};
+use crate::output::{GcArtifact, GcArtifactKind, GcResult};
+use crate::{History, LockFile, PlanOptions};
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Garbage-collect operation - removes what crashed or interrupted runs left behind.
+///
+/// That is backups, saved plans and logs that no history entry refers to, a
+/// lock whose process is gone, and temporary files from atomic writes. With
+/// `dry_run` they are only listed.
+pub fn gc_operation(dry_run: bool, working_dir: Option<&Path>) -> Result<GcResult> {
+    if !dry_run {
+        crate::workspace::require("gc")?;
+    }
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let renamify_dir = current_dir.join(".renamify");
+
+    let mut artifacts = Vec::new();
+    if let Some(lock_path) = stale_lock(&renamify_dir) {
+        artifacts.push(artifact(lock_path, GcArtifactKind::Lock));
+    }
+
+    // Hold the lock while deleting so a running apply can't lose its backups;
+    // acquiring it clears a stale one
+    let lock = if dry_run || !renamify_dir.exists() {
+        None
+    } else {
+        Some(LockFile::acquire(&renamify_dir)?)
+    };
+
+    if renamify_dir.exists() {
+        let history = History::load(&renamify_dir)?;
+        let entries = history.list_entries(None);
+        let ids: HashSet<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+        // Matched by name, since older entries may hold relative paths
+        let backups: HashSet<&std::ffi::OsStr> = entries
+            .iter()
+            .filter_map(|entry| entry.backups_path.file_name())
+            .collect();
+
+        for path in list_dir(&renamify_dir.join("backups"))? {
+            let referenced = path.file_name().is_some_and(|name| {
+                backups.contains(name) || name.to_str().is_some_and(|id| ids.contains(id))
+            });
+            if path.is_dir() && !referenced {
+                artifacts.push(artifact(path, GcArtifactKind::Backup));
+            }
+        }
+        for (dir, extension, kind) in [
+            ("plans", "json", GcArtifactKind::Plan),
+            ("logs", "log", GcArtifactKind::Log),
+            ("mappings", "json", GcArtifactKind::Mapping),
+        ] {
+            for path in list_dir(&renamify_dir.join(dir))? {
+                let orphaned = path.extension().is_some_and(|ext| ext == extension)
+                    && path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .is_some_and(|id| !ids.contains(id));
+                if orphaned {
+                    artifacts.push(artifact(path, kind));
+                }
+            }
+        }
+    }
+    artifacts.extend(
+        orphaned_temp_files(current_dir)
+            .into_iter()
+            .map(|path| artifact(path, GcArtifactKind::TempFile)),
+    );
+
+    if !dry_run {
+        for artifact in &artifacts {
+            // The stale lock went when the lock was acquired
+            if artifact.kind == GcArtifactKind::Lock {
+                continue;
+            }
+            // Let git prune the blobs of a git-stash backup along with it
+            if artifact.kind == GcArtifactKind::Backup {
+                if let Ok(Some(blobs)) = GitBlobs::read(&artifact.path) {
+                    blobs.release()?;
+                }
+            }
+            let removed = if artifact.path.is_dir() {
+                fs::remove_dir_all(&artifact.path)
+            } else {
+                fs::remove_file(&artifact.path)
+            };
+            removed.with_context(|| format!("Failed to remove {}", artifact.path.display()))?;
+        }
+    }
+    if let Some(lock) = lock {
+        lock.release()?;
+    }
+
+    let bytes = artifacts.iter().map(|artifact| artifact.bytes).sum();
+    for artifact in &mut artifacts {
+        if let Ok(relative) = artifact.path.strip_prefix(current_dir) {
+            artifact.path = relative.to_path_buf();
+        }
+    }
+    Ok(GcResult {
+        dry_run,
+        artifacts,
+        bytes,
+    })
+}
+
+fn artifact(path: PathBuf, kind: GcArtifactKind) -> GcArtifact {
+    let bytes = size_of(&path);
+    GcArtifact { path, kind, bytes }
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn size_of(path: &Path) -> u64 {
+    if path.is_dir() {
+        walkdir_size(path)
+    } else {
+        fs::metadata(path).map_or(0, |metadata| metadata.len())
+    }
+}
+
+fn walkdir_size(dir: &Path) -> u64 {
+    ignore::WalkBuilder::new(dir)
+        .standard_filters(false)
+        .build()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Temporary files from atomic writes (`<name>.<pid>.renamify.tmp`, and
+/// `config.toml.<pid>.tmp` in `.renamify`) whose process is no longer running
+fn orphaned_temp_files(root: &Path) -> Vec<PathBuf> {
+    let options = PlanOptions {
+        unrestricted_level: 2,
+        ..PlanOptions::default()
+    };
+    let mut paths: Vec<PathBuf> = crate::configure_walker(&[root.to_path_buf()], &options)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| !t.is_dir()))
+        .map(ignore::DirEntry::into_path)
+        .filter(|path| temp_file_pid(path).is_some_and(|pid| !is_process_running(pid)))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// The process that wrote a renamify temporary file, parsed from its name
+fn temp_file_pid(path: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_str()?;
+    if let Some(rest) = name.strip_suffix(".renamify.tmp") {
+        return rest.rsplit_once('.')?.1.parse().ok();
+    }
+    let in_renamify_dir = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == ".renamify");
+    if !in_renamify_dir {
+        return None;
+    }
+    name.strip_prefix("config.toml.")?
+        .strip_suffix(".tmp")?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_temp_file_pid() {
+        assert_eq!(
+            temp_file_pid(Path::new("src/main.123.renamify.tmp")),
+            Some(123)
+        );
+        assert_eq!(temp_file_pid(Path::new("old.rs.77.renamify.tmp")), Some(77));
+        assert_eq!(
+            temp_file_pid(Path::new(".renamify/config.toml.9.tmp")),
+            Some(9)
+        );
+        assert_eq!(temp_file_pid(Path::new("notes.tmp")), None);
+        // Look-alikes outside .renamify belong to the user
+        assert_eq!(
+            temp_file_pid(Path::new("data/settings.toml.20240101.tmp")),
+            None
+        );
+        assert_eq!(temp_file_pid(Path::new("config.toml.9.tmp")), None);
+        assert_eq!(
+            temp_file_pid(Path::new(".renamify/settings.toml.9.tmp")),
+            None
+        );
+        assert_eq!(temp_file_pid(Path::new("main.renamify.tmp")), None);
+    }
+
+    #[test]
+    fn test_gc() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let renamify_dir = root.join(".renamify");
+        fs::create_dir_all(renamify_dir.join("backups/orphan")).unwrap();
+        fs::write(renamify_dir.join("backups/orphan/a.rs"), "backup").unwrap();
+        fs::create_dir_all(renamify_dir.join("plans")).unwrap();
+        fs::write(renamify_dir.join("plans/orphan.json"), "{}").unwrap();
+        fs::create_dir_all(renamify_dir.join("logs")).unwrap();
+        fs::write(renamify_dir.join("logs/orphan.log"), "").unwrap();
+        // Left by a process that is long gone
+        fs::write(renamify_dir.join("renamify.lock"), "999999999:0").unwrap();
+        fs::write(root.join("main.999999999.renamify.tmp"), "partial").unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::write(root.join("data/settings.toml.999999999.tmp"), "user").unwrap();
+
+        // Everything belonging to an entry in the history is kept
+        fs::create_dir_all(renamify_dir.join("backups/kept")).unwrap();
+        fs::write(renamify_dir.join("plans/kept.json"), "{}").unwrap();
+        fs::write(
+            renamify_dir.join("history.json"),
+            r#"[{"id": "kept", "created_at": "2024-01-01T00:00:00Z", "search": "a",
+                 "replace": "b", "styles": [], "includes": [], "excludes": [],
+                 "affected_files": {}, "renames": [],
+                 "backups_path": ".renamify/backups/kept"}]"#,
+        )
+        .unwrap();
+
+        let result = gc_operation(true, Some(&root)).unwrap();
+        let mut found: Vec<(PathBuf, GcArtifactKind)> = result
+            .artifacts
+            .iter()
+            .map(|artifact| (artifact.path.clone(), artifact.kind))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                (
+                    PathBuf::from(".renamify/backups/orphan"),
+                    GcArtifactKind::Backup
+                ),
+                (
+                    PathBuf::from(".renamify/logs/orphan.log"),
+                    GcArtifactKind::Log
+                ),
+                (
+                    PathBuf::from(".renamify/plans/orphan.json"),
+                    GcArtifactKind::Plan
+                ),
+                (
+                    PathBuf::from(".renamify/renamify.lock"),
+                    GcArtifactKind::Lock
+                ),
+                (
+                    PathBuf::from("main.999999999.renamify.tmp"),
+                    GcArtifactKind::TempFile
+                ),
+            ]
+        );
+        assert_eq!(result.bytes, 6 + 2 + 11 + 7);
+        assert!(renamify_dir.join("backups/orphan").exists());
+
+        let result = gc_operation(false, Some(&root)).unwrap();
+        assert_eq!(result.artifacts.len(), 5);
+        assert!(!renamify_dir.join("backups/orphan").exists());
+        assert!(!renamify_dir.join("renamify.lock").exists());
+        assert!(!root.join("main.999999999.renamify.tmp").exists());
+        assert!(root.join("main.rs").exists());
+        assert!(root.join("data/settings.toml.999999999.tmp").exists());
+        assert!(renamify_dir.join("backups/kept").exists());
+        assert!(renamify_dir.join("plans/kept.json").exists());
+
+        assert!(gc_operation(false, Some(&root))
+            .unwrap()
+            .artifacts
+            .is_empty());
+    }
+}
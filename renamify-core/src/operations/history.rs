@@ -1,6 +1,10 @@
-use crate::output::{HistoryItem, HistoryResult};
-use crate::History;
-use anyhow::Result;
+use crate::bundle::Bundle;
+use crate::error::RenamifyError;
+use crate::mapping::RenameMapping;
+use crate::operations::apply::read_plan_file;
+use crate::output::{HistoryExportResult, HistoryItem, HistoryResult};
+use crate::{resolve_id, History, OperationType};
+use anyhow::{Context, Result};
 use std::path::Path;
 
 /// History operation - returns structured data
@@ -39,3 +43,70 @@ pub fn history_operation(
 
     Ok(HistoryResult { entries: items })
 }
+
+/// Export an applied rename as a bundle that `apply --bundle` can replay on
+/// another checkout. `id` may be `latest`.
+pub fn history_export_operation(
+    id: &str,
+    bundle_path: &Path,
+    working_dir: Option<&Path>,
+) -> Result<HistoryExportResult> {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let renamify_dir = current_dir.join(".renamify");
+
+    let id = resolve_id(id, OperationType::Undo, &renamify_dir)?;
+    let history = History::load(&renamify_dir)?;
+    let entry = history
+        .find_entry(&id)
+        .ok_or_else(|| RenamifyError::NotFound(format!("History entry '{id}' not found")))?;
+    if entry.revert_of.is_some() {
+        return Err(RenamifyError::InvalidInput(format!(
+            "'{id}' is an undo; export the rename it reverted instead"
+        ))
+        .into());
+    }
+
+    let plan = read_plan_file(&renamify_dir.join("plans").join(format!("{id}.json")))?;
+    let root = current_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", current_dir.display()))?;
+    let bundle = Bundle::export(entry, &plan, &root)?;
+    bundle.write(&current_dir.join(bundle_path))?;
+
+    Ok(HistoryExportResult {
+        history_id: id,
+        bundle: bundle_path.to_path_buf(),
+        files: bundle.files.len(),
+        replacements: bundle.plan.matches.len(),
+        renames: bundle.plan.paths.len(),
+    })
+}
+
+/// The old-to-new mapping of an applied rename, as `history mapping` prints
+/// it. `id` may be `latest`. Renames applied before mappings were kept get
+/// one built from their stored plan.
+pub fn history_mapping_operation(id: &str, working_dir: Option<&Path>) -> Result<RenameMapping> {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let renamify_dir = current_dir.join(".renamify");
+
+    let id = resolve_id(id, OperationType::Undo, &renamify_dir)?;
+    if let Some(mapping) = RenameMapping::load(&renamify_dir, &id)? {
+        return Ok(mapping);
+    }
+
+    let history = History::load(&renamify_dir)?;
+    let entry = history
+        .find_entry(&id)
+        .ok_or_else(|| RenamifyError::NotFound(format!("History entry '{id}' not found")))?;
+    if let Some(reverted) = &entry.revert_of {
+        return Err(RenamifyError::InvalidInput(format!(
+            "'{id}' is an undo; its mapping is the reverse of '{reverted}'"
+        ))
+        .into());
+    }
+    let plan = read_plan_file(&renamify_dir.join("plans").join(format!("{id}.json")))?;
+    let root = current_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", current_dir.display()))?;
+    Ok(RenameMapping::from_plan(&plan, &entry.renames, &root))
+}
@@ -4,16 +4,44 @@
 //! separated from CLI concerns like argument parsing and output formatting.
 
 pub mod apply;
+pub mod check;
+pub mod config;
+pub mod doctor;
+pub mod explain;
+pub mod export_types;
+pub mod gc;
 pub mod history;
 pub mod plan;
+pub mod plan_diff;
+pub mod plan_merge;
+pub mod plan_split;
+pub mod preview_file;
 pub mod rename;
+pub mod replan;
+pub mod report;
+pub mod serve;
 pub mod status;
 pub mod undo;
+pub mod validate_plan;
 
 // Re-export the main operation functions for easy access
-pub use apply::apply_operation;
-pub use history::history_operation;
+pub use apply::{apply_bundle_operation, apply_check_operation, apply_operation};
+pub use check::{check_operation, check_staged_operation};
+pub use config::{config_get_operation, config_set_operation, config_show_operation};
+pub use doctor::doctor_operation;
+pub use explain::explain_operation;
+pub use export_types::export_types_operation;
+pub use gc::gc_operation;
+pub use history::{history_export_operation, history_mapping_operation, history_operation};
 pub use plan::plan_operation;
+pub use plan_diff::plan_diff_operation;
+pub use plan_merge::plan_merge_operation;
+pub use plan_split::plan_split_operation;
+pub use preview_file::preview_file_operation;
 pub use rename::rename_operation;
+pub use replan::replan_operation;
+pub use report::report_operation;
+pub use serve::serve_operation;
 pub use status::status_operation;
 pub use undo::{redo_operation, undo_operation};
+pub use validate_plan::validate_plan_operation;
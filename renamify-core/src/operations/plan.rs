@@ -1,16 +1,330 @@
+use crate::acronym::AcronymRendering;
+use crate::case_model::CaseMapping;
+use crate::empty_plan::EmptyPlanDiagnosis;
+use crate::error::RenamifyError;
+use crate::operations::plan_merge::merge_plans;
+use crate::placeholders::{has_placeholders, scan_with_placeholders};
+use crate::suppress::FileLines;
+use crate::warnings::{plan_warnings, PlanWarning, WarningKind, WarningSeverity};
 use crate::{
-    output::PlanResult, scan_repository_multi, write_plan, LockFile, PlanOptions, Preview, Style,
+    observer::OperationObserver, output::PlanResult, render_plan_named, scan_cache, scan_content,
+    scan_repository_cached, scanner::ScanCaches, sparse::SparseReport,
+    structured::StructuredFilter, write_plan, CoercionMode, GroupBy, LiteralMode, LockFile,
+    MatchAnchor, Plan, PlanOptions, Profile, RenderOptions, Stats, Style, UnreadableFile,
+    WalkOptions,
 };
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Thresholds that abort planning when a change looks suspiciously large or empty
+#[derive(Debug, Clone, Default)]
+pub struct PlanGuards {
+    /// Fail if the plan contains more than this many content matches
+    pub max_matches: Option<usize>,
+    /// Fail if more than this many files would have their contents changed
+    pub max_files: Option<usize>,
+    /// Fail if the plan contains no matches and no renames
+    pub fail_if_zero: bool,
+    /// Fail if any file could not be read
+    pub strict: bool,
+}
+
+/// Raised when a plan trips one of its `PlanGuards`
+#[derive(Debug, thiserror::Error)]
+pub enum PlanGuardError {
+    #[error("Plan guard tripped: {found} matches exceeds --max-matches {max}")]
+    TooManyMatches { found: usize, max: usize },
+    #[error("Plan guard tripped: {found} files with matches exceeds --max-files {max}")]
+    TooManyFiles { found: usize, max: usize },
+    #[error("No matches or renames found (--fail-if-zero)\n{0}")]
+    NoMatches(Box<EmptyPlanDiagnosis>),
+    #[error(
+        "Large change detected ({files} files, {renames} renames; thresholds are {max_files} files, {max_renames} renames). Use large=true to acknowledge."
+    )]
+    LargeChange {
+        files: usize,
+        renames: usize,
+        max_files: usize,
+        max_renames: usize,
+    },
+    #[error("{}", unreadable_message(.0))]
+    Unreadable(Vec<UnreadableFile>),
+    #[error("Expected {expected} {name}, found {found} (--expect-{name})")]
+    UnexpectedCount {
+        name: &'static str,
+        found: usize,
+        expected: ExpectedCount,
+    },
+}
+
+/// A threshold that a guard found exceeded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct GuardThreshold {
+    /// What was counted: `matches`, `files` or `renames`
+    pub name: String,
+    pub found: usize,
+    /// Lowest allowed count, for `--expect-*` guards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub min: Option<usize>,
+    pub max: usize,
+}
+
+impl PlanGuardError {
+    /// Stable name of the guard, exposed as `error.guard.name` in JSON output
+    pub fn guard_name(&self) -> &'static str {
+        match self {
+            Self::TooManyMatches { .. } => "max_matches",
+            Self::TooManyFiles { .. } => "max_files",
+            Self::NoMatches(_) => "fail_if_zero",
+            Self::LargeChange { .. } => "large_change",
+            Self::Unreadable(_) => "strict",
+            Self::UnexpectedCount {
+                name: "renames", ..
+            } => "expect_renames",
+            Self::UnexpectedCount { .. } => "expect_matches",
+        }
+    }
+
+    /// The thresholds that tripped the guard
+    pub fn exceeded_thresholds(&self) -> Vec<GuardThreshold> {
+        let threshold = |name: &str, found: usize, max: usize| GuardThreshold {
+            name: name.to_string(),
+            found,
+            min: None,
+            max,
+        };
+        match *self {
+            Self::TooManyMatches { found, max } => vec![threshold("matches", found, max)],
+            Self::TooManyFiles { found, max } => vec![threshold("files", found, max)],
+            Self::NoMatches(_) | Self::Unreadable(_) => vec![],
+            Self::LargeChange {
+                files,
+                renames,
+                max_files,
+                max_renames,
+            } => {
+                let mut exceeded = Vec::new();
+                if files > max_files {
+                    exceeded.push(threshold("files", files, max_files));
+                }
+                if renames > max_renames {
+                    exceeded.push(threshold("renames", renames, max_renames));
+                }
+                exceeded
+            },
+            Self::UnexpectedCount {
+                name,
+                found,
+                expected,
+            } => vec![GuardThreshold {
+                name: name.to_string(),
+                found,
+                min: Some(expected.count.saturating_sub(expected.tolerance)),
+                max: expected.count.saturating_add(expected.tolerance),
+            }],
+        }
+    }
+}
+
+fn unreadable_message(files: &[UnreadableFile]) -> String {
+    let mut message = match files.len() {
+        1 => "1 file could not be read (--strict):".to_string(),
+        count => format!("{count} files could not be read (--strict):"),
+    };
+    for file in files {
+        write!(message, "\n  {}: {}", file.path.display(), file.error).unwrap();
+    }
+    message
+}
+
+/// Fail with `PlanGuardError::Unreadable` if `plan` has files it could not read
+pub fn check_readable(plan: &Plan) -> Result<(), PlanGuardError> {
+    let unreadable = &plan.stats.files_skipped.unreadable_files;
+    if unreadable.is_empty() {
+        Ok(())
+    } else {
+        Err(PlanGuardError::Unreadable(unreadable.clone()))
+    }
+}
+
+/// A count given to `--expect-matches N[:tolerance]`, with how far the actual
+/// count may stray from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectedCount {
+    pub count: usize,
+    pub tolerance: usize,
+}
+
+impl ExpectedCount {
+    pub const fn allows(&self, found: usize) -> bool {
+        found.abs_diff(self.count) <= self.tolerance
+    }
+}
+
+impl std::fmt::Display for ExpectedCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.tolerance == 0 {
+            write!(f, "{}", self.count)
+        } else {
+            write!(f, "{}±{}", self.count, self.tolerance)
+        }
+    }
+}
+
+impl std::str::FromStr for ExpectedCount {
+    type Err = String;
+
+    /// Parse `N` or `N:tolerance`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (count, tolerance) = s.split_once(':').unwrap_or((s, "0"));
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid expected count '{s}', use N or N:TOLERANCE"))
+        };
+        Ok(Self {
+            count: parse(count)?,
+            tolerance: parse(tolerance)?,
+        })
+    }
+}
+
+/// Counts a `rename` must land on to go ahead without a confirmation prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountExpectations {
+    /// Expected number of content matches
+    pub matches: Option<ExpectedCount>,
+    /// Expected number of file and directory renames
+    pub renames: Option<ExpectedCount>,
+}
+
+impl CountExpectations {
+    pub const fn is_set(&self) -> bool {
+        self.matches.is_some() || self.renames.is_some()
+    }
+
+    /// Fail with `PlanGuardError::UnexpectedCount` if a count is out of tolerance
+    pub fn check(&self, matches: usize, renames: usize) -> Result<(), PlanGuardError> {
+        for (name, expected, found) in [
+            ("matches", self.matches, matches),
+            ("renames", self.renames, renames),
+        ] {
+            if let Some(expected) = expected.filter(|expected| !expected.allows(found)) {
+                return Err(PlanGuardError::UnexpectedCount {
+                    name,
+                    found,
+                    expected,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Size above which `rename` and `replace` refuse to run without `--large`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LargeChangeThresholds {
+    /// Maximum number of files whose contents change
+    pub max_files: usize,
+    /// Maximum number of file and directory renames
+    pub max_renames: usize,
+}
+
+impl Default for LargeChangeThresholds {
+    fn default() -> Self {
+        Self {
+            max_files: crate::config::DEFAULT_LARGE_FILES_THRESHOLD,
+            max_renames: crate::config::DEFAULT_LARGE_RENAMES_THRESHOLD,
+        }
+    }
+}
+
+impl LargeChangeThresholds {
+    /// Build thresholds from CLI flags, falling back to the config file defaults
+    pub fn from_flags_and_config(
+        max_files: Option<usize>,
+        max_renames: Option<usize>,
+        defaults: &crate::config::DefaultsConfig,
+    ) -> Self {
+        Self {
+            max_files: max_files.unwrap_or(defaults.large_files_threshold),
+            max_renames: max_renames.unwrap_or(defaults.large_renames_threshold),
+        }
+    }
+
+    pub const fn is_exceeded(&self, files: usize, renames: usize) -> bool {
+        files > self.max_files || renames > self.max_renames
+    }
+
+    /// Fail with `PlanGuardError::LargeChange` if the change is too large
+    pub fn check(&self, files: usize, renames: usize) -> Result<(), PlanGuardError> {
+        if self.is_exceeded(files, renames) {
+            return Err(PlanGuardError::LargeChange {
+                files,
+                renames,
+                max_files: self.max_files,
+                max_renames: self.max_renames,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl PlanGuards {
+    /// Check a freshly scanned plan against the configured thresholds, with
+    /// the diagnosis of an empty plan
+    pub fn check(
+        &self,
+        plan: &Plan,
+        empty_plan: Option<&EmptyPlanDiagnosis>,
+    ) -> Result<(), PlanGuardError> {
+        if self.fail_if_zero && plan.stats.total_matches == 0 && plan.paths.is_empty() {
+            return Err(PlanGuardError::NoMatches(Box::new(
+                empty_plan.cloned().unwrap_or_default(),
+            )));
+        }
+        if let Some(max) = self.max_matches {
+            if plan.stats.total_matches > max {
+                return Err(PlanGuardError::TooManyMatches {
+                    found: plan.stats.total_matches,
+                    max,
+                });
+            }
+        }
+        if let Some(max) = self.max_files {
+            if plan.stats.files_with_matches > max {
+                return Err(PlanGuardError::TooManyFiles {
+                    found: plan.stats.files_with_matches,
+                    max,
+                });
+            }
+        }
+        if self.strict {
+            check_readable(plan)?;
+        }
+        Ok(())
+    }
+}
 
 /// Plan operation - returns structured data
+///
+/// With `buffer`, only that content is scanned instead of the files under
+/// `paths`, and its hunks name the given path, relative to the working
+/// directory, whether or not it exists on disk.
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::fn_params_excessive_bools)]
 pub fn plan_operation(
     search: &str,
     replace: &str,
     paths: Vec<PathBuf>,
+    buffer: Option<(&Path, &[u8])>,
     include: Vec<String>,
     exclude: Vec<String>,
     respect_gitignore: bool,
@@ -22,19 +336,39 @@ pub fn plan_operation(
     only_styles: &[Style],
     exclude_match: Vec<String>,
     exclude_matching_lines: Option<String>,
+    exclude_lines_in_file: Vec<FileLines>,
     plan_out: Option<PathBuf>,
     preview_format: Option<&String>,
     dry_run: bool,
     fixed_table_width: bool,
+    group_by: GroupBy,
+    preview_sample: Option<usize>,
     use_color: bool,
     no_acronyms: bool,
     include_acronyms: Vec<String>,
     exclude_acronyms: Vec<String>,
     only_acronyms: Vec<String>,
+    acronym_rendering: Option<AcronymRendering>,
+    case_mapping: Option<CaseMapping>,
     enable_plural_variants: bool,
     ignore_ambiguous: bool,
+    allow_case_collisions: bool,
+    literal_mode: LiteralMode,
+    match_anchor: MatchAnchor,
+    coerce_separators: CoercionMode,
+    structured: Option<StructuredFilter>,
+    profile: Option<&Profile>,
+    report_skipped: bool,
+    swap: bool,
+    fuzzy: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    paths_only: bool,
+    contents_only: bool,
     working_dir: Option<&std::path::Path>,
     atomic_config: Option<&crate::atomic::AtomicConfig>,
+    guards: Option<&PlanGuards>,
+    observer: Option<&dyn OperationObserver>,
 ) -> Result<(PlanResult, Option<String>)> {
     let current_dir = working_dir.map_or_else(
         || std::env::current_dir().expect("Failed to get current directory"),
@@ -50,7 +384,7 @@ pub fn plan_operation(
 
     // Only acquire lock for non-dry-run operations (actual plan writes)
     let renamify_dir = current_dir.join(".renamify");
-    let _lock = if dry_run {
+    let _lock = if dry_run || crate::workspace::is_disabled() {
         None // No lock needed for dry-run/search operations
     } else {
         Some(
@@ -59,16 +393,18 @@ pub fn plan_operation(
         )
     };
 
-    // Build styles list
+    // Build styles list, with the profile's styles standing in for --only-styles
+    let only_styles = crate::profile::only_styles(profile, only_styles)?;
     let styles = build_styles_list(
         exclude_styles.to_vec(),
         include_styles.to_vec(),
-        only_styles.to_vec(),
+        only_styles,
     );
 
     let plan_out_path = plan_out.unwrap_or_else(|| PathBuf::from(".renamify/plan.json"));
+    let config = crate::config::Config::load_in(&current_dir)?;
 
-    let plan_options = PlanOptions {
+    let mut plan_options = PlanOptions {
         includes: include,
         excludes: exclude,
         respect_gitignore,
@@ -78,9 +414,10 @@ pub fn plan_operation(
         rename_dirs,
         rename_root: false,
         plan_out: plan_out_path.clone(),
-        coerce_separators: crate::scanner::CoercionMode::Auto,
+        coerce_separators,
         exclude_match,
         exclude_matching_lines,
+        exclude_lines_in_file,
         no_acronyms,
         include_acronyms,
         exclude_acronyms,
@@ -88,7 +425,24 @@ pub fn plan_operation(
         ignore_ambiguous,
         atomic_config: atomic_config.cloned(),
         enable_plural_variants,
+        allow_case_collisions,
+        literal_mode,
+        structured,
+        sql: false,
+        report_skipped,
+        fuzzy,
+        include_submodules,
+        paths_only,
+        contents_only,
+        match_anchor,
+        working_dir: Some(current_dir.clone()),
+        walk,
+        acronym_rendering: acronym_rendering.unwrap_or(config.acronym_rendering),
+        case_mapping: case_mapping.unwrap_or(config.case_mapping),
     };
+    if let Some(profile) = profile {
+        profile.apply(&mut plan_options);
+    }
 
     // Resolve all search paths to absolute paths and canonicalize them
     let resolved_paths: Vec<PathBuf> = search_paths
@@ -103,37 +457,132 @@ pub fn plan_operation(
         })
         .collect();
 
-    let plan = scan_repository_multi(&resolved_paths, search, replace, &plan_options)
-        .context("Failed to scan repository")?;
+    let buffer_path = buffer.map(|(path, _)| current_dir.join(path));
+    let mut plan = match buffer.zip(buffer_path.as_deref()) {
+        Some(((_, content), path)) => {
+            if swap {
+                return Err(RenamifyError::InvalidInput(
+                    "--swap can't be used with --stdin".to_string(),
+                )
+                .into());
+            }
+            scan_with_placeholders(replace, &renamify_dir, |replace| {
+                scan_content(path, content, search, replace, &plan_options)
+            })
+        },
+        None => scan_plan(
+            &resolved_paths,
+            search,
+            replace,
+            &plan_options,
+            swap,
+            observer,
+            ScanCaches {
+                files: scan_cache::cache_dir(&renamify_dir).as_deref(),
+                ..ScanCaches::default()
+            },
+        ),
+    }
+    .context("Failed to scan repository")?;
+    // From here on the replacement has its placeholders filled in
+    let replace = plan.replace.clone();
+    let replace = replace.as_str();
+
+    // The files on disk say nothing about why a buffer had no matches
+    let empty_plan = if buffer.is_none() && plan.stats.total_matches == 0 && plan.paths.is_empty() {
+        Some(EmptyPlanDiagnosis::diagnose(
+            &resolved_paths,
+            search,
+            replace,
+            &plan_options,
+            swap,
+            &plan,
+        )?)
+    } else {
+        None
+    };
+
+    // Renames into ignored or protected paths wait for confirmation
+    crate::suspicious::hold_back_suspicious_renames(
+        &mut plan,
+        &resolved_paths,
+        &plan_options,
+        &config.protected,
+    )?;
+
+    // Fail fast before rendering a preview or writing the plan
+    if let Some(guards) = guards {
+        guards.check(&plan, empty_plan.as_ref())?;
+    }
+
+    plan.warnings = plan_warnings(&plan, &resolved_paths, &plan_options, &config);
+    if let Some(profile) = profile {
+        let labels = profile.label_warnings(&plan, &resolved_paths, &plan_options);
+        plan.warnings.extend(labels);
+    }
+
+    // Tracked files missing from disk can't be renamed, so say which likely need it
+    let terms = plan_options
+        .with_casing(|| search_terms(search, replace, plan_options.styles.as_deref(), swap));
+    let terms: Vec<&str> = terms.iter().map(String::as_str).collect();
+    let sparse_checkout = buffer
+        .is_none()
+        .then(|| crate::sparse::sparse_report(&resolved_paths, &terms, &plan_options))
+        .flatten();
+    plan.warnings
+        .extend(sparse_checkout.as_ref().and_then(SparseReport::warning));
+    let mut warnings = plan.warnings.clone();
+
+    // Without a workspace, only a plan written outside .renamify is saved
+    let save_plan = !dry_run
+        && (!crate::workspace::is_disabled()
+            || !current_dir.join(&plan_out_path).starts_with(&renamify_dir));
+    if !dry_run && !save_plan {
+        warnings.push(
+            PlanWarning::new(
+                WarningKind::PlanNotSaved,
+                WarningSeverity::Warning,
+                format!(
+                    "The plan was not saved to {} because the .renamify workspace is disabled; pass --plan-out to save it elsewhere",
+                    plan_out_path.display()
+                ),
+            )
+            .with_suggested_flag("--plan-out"),
+        );
+    }
 
     // Generate preview content
     let preview_content = if let Some(format) = preview_format.as_ref() {
         if *format == "none" {
             None
         } else {
-            let preview = parse_preview_format(format)?;
             let use_color = if *format == "json" { false } else { use_color };
-            Some(crate::preview::render_plan_with_fixed_width(
-                &plan,
-                preview,
-                Some(use_color),
-                fixed_table_width,
-            ))
+            let options = RenderOptions {
+                use_color,
+                fixed_width: fixed_table_width,
+                group_by,
+                sample: preview_sample,
+                base_dir: Some(&current_dir),
+            };
+            Some(render_plan_named(&plan, format, &options)?)
         }
     } else {
         None
     };
 
     // Write the plan to disk unless dry-run
-    if !dry_run {
+    if save_plan {
+        // A relative --plan-out is relative to the working directory
+        let plan_out_file = current_dir.join(&plan_out_path);
+
         // Create the directory if it doesn't exist
-        if let Some(parent) = plan_out_path.parent() {
+        if let Some(parent) = plan_out_file.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create directory {}", parent.display()))?;
         }
 
         // Write the plan
-        write_plan(&plan, &plan_out_path)
+        write_plan(&plan, &plan_out_file)
             .with_context(|| format!("Failed to write plan to {}", plan_out_path.display()))?;
     }
 
@@ -147,13 +596,136 @@ pub fn plan_operation(
         renames: plan.paths.len(),
         dry_run,
         plan: Some(plan),
+        warnings,
+        sparse_checkout,
+        owner_plans: Vec::new(),
+        empty_plan,
+        search_only: false,
     };
 
     Ok((result, preview_content))
 }
 
+/// Every variant of `search` a plan looks for, and of `replace` as well with
+/// `--swap`
+fn search_terms(search: &str, replace: &str, styles: Option<&[Style]>, swap: bool) -> Vec<String> {
+    let mut terms: Vec<String> = crate::case_model::generate_variant_map(search, replace, styles)
+        .into_keys()
+        .collect();
+    if swap {
+        terms.extend(crate::case_model::generate_variant_map(replace, search, styles).into_keys());
+    }
+    terms.push(search.to_string());
+    terms.retain(|term| !term.is_empty());
+    terms
+}
+
+/// Move the near misses found with `--fuzzy` into the plan's matches once the
+/// user has confirmed them, and rewrite the plan file. Returns how many were added.
+pub fn accept_near_misses(result: &mut PlanResult, plan_path: &Path) -> Result<usize> {
+    let Some(plan) = result.plan.as_mut() else {
+        return Ok(0);
+    };
+    if plan.near_misses.is_empty() {
+        return Ok(0);
+    }
+
+    let _lock = LockFile::acquire(Path::new(".renamify"))
+        .context("Failed to acquire lock for renamify operation")?;
+
+    let added = plan.near_misses.len();
+    plan.matches.append(&mut plan.near_misses);
+    plan.matches.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.byte_offset.cmp(&b.byte_offset))
+    });
+    let counts = Stats::from_matches(plan.stats.files_scanned, &plan.matches);
+    plan.stats.total_matches = counts.total_matches;
+    plan.stats.files_with_matches = counts.files_with_matches;
+    plan.stats.matches_by_variant = counts.matches_by_variant;
+
+    write_plan(plan, plan_path)
+        .with_context(|| format!("Failed to write plan to {}", plan_path.display()))?;
+
+    result.total_matches = plan.stats.total_matches;
+    result.files_with_matches = plan.stats.files_with_matches;
+    Ok(added)
+}
+
+/// Move the renames into ignored or protected paths back into the plan's
+/// renames once the user has confirmed them, and rewrite the plan file.
+/// Returns how many were added.
+pub fn accept_suspicious_renames(result: &mut PlanResult, plan_path: &Path) -> Result<usize> {
+    let Some(plan) = result.plan.as_mut() else {
+        return Ok(0);
+    };
+    if plan.suspicious_renames.is_empty() {
+        return Ok(0);
+    }
+
+    let _lock = LockFile::acquire(Path::new(".renamify"))
+        .context("Failed to acquire lock for renamify operation")?;
+
+    let added = plan.suspicious_renames.len();
+    plan.paths.extend(
+        plan.suspicious_renames
+            .drain(..)
+            .map(|suspicious| suspicious.rename),
+    );
+
+    write_plan(plan, plan_path)
+        .with_context(|| format!("Failed to write plan to {}", plan_path.display()))?;
+
+    result.renames = plan.paths.len();
+    Ok(added)
+}
+
+/// Scan for `search` -> `replace`, or with `swap`, for both directions at once.
+/// A swap scans the unchanged tree for each term and merges the two plans, so
+/// existing occurrences of `replace` become `search` instead of colliding with
+/// the renamed ones. Both scans read from and fill `caches`.
+pub(crate) fn scan_plan(
+    roots: &[PathBuf],
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+    swap: bool,
+    observer: Option<&dyn OperationObserver>,
+    caches: ScanCaches<'_>,
+) -> Result<Plan> {
+    let scan = |search: &str, replace: &str| {
+        scan_repository_cached(roots, search, replace, options, observer, caches)
+    };
+    if !swap {
+        let renamify_dir = options
+            .working_dir
+            .clone()
+            .unwrap_or_default()
+            .join(".renamify");
+        return scan_with_placeholders(replace, &renamify_dir, |replace| scan(search, replace));
+    }
+    if replace.is_empty() || search == replace {
+        return Err(RenamifyError::InvalidInput(
+            "--swap needs two different terms to exchange".to_string(),
+        )
+        .into());
+    }
+    if has_placeholders(replace) {
+        return Err(RenamifyError::InvalidInput(
+            "--swap can't be used with placeholders in the replacement".to_string(),
+        )
+        .into());
+    }
+
+    let forward = scan(search, replace)?;
+    let backward = scan(replace, search)?;
+    Ok(merge_plans(&[forward, backward])?.plan)
+}
+
 #[allow(clippy::needless_pass_by_value)]
-fn build_styles_list(
+pub(crate) fn build_styles_list(
     exclude_styles: Vec<Style>,
     include_styles: Vec<Style>,
     only_styles: Vec<Style>,
@@ -186,13 +758,126 @@ fn build_styles_list(
     }
 }
 
-fn parse_preview_format(format: &str) -> Result<Preview> {
-    match format.to_lowercase().as_str() {
-        "table" => Ok(Preview::Table),
-        "diff" => Ok(Preview::Diff),
-        "matches" => Ok(Preview::Matches),
-        "summary" => Ok(Preview::Summary),
-        "none" => Ok(Preview::None),
-        _ => Err(anyhow::anyhow!("Invalid preview format: {}", format)),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Stats;
+    use std::collections::HashMap;
+
+    fn plan_with(total_matches: usize, files_with_matches: usize) -> Plan {
+        Plan {
+            id: "guard".to_string(),
+            created_at: "0".to_string(),
+            search: "old".to_string(),
+            replace: "new".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats {
+                files_scanned: files_with_matches,
+                total_matches,
+                matches_by_variant: HashMap::new(),
+                files_with_matches,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_guards_default_allows_anything() {
+        let guards = PlanGuards::default();
+        assert!(guards.check(&plan_with(0, 0), None).is_ok());
+        assert!(guards.check(&plan_with(10_000, 5_000), None).is_ok());
+    }
+
+    #[test]
+    fn test_plan_guards_thresholds() {
+        let guards = PlanGuards {
+            max_matches: Some(10),
+            max_files: Some(2),
+            fail_if_zero: true,
+            strict: false,
+        };
+        assert!(guards.check(&plan_with(10, 2), None).is_ok());
+        assert!(matches!(
+            guards.check(&plan_with(11, 1), None),
+            Err(PlanGuardError::TooManyMatches { found: 11, max: 10 })
+        ));
+        assert!(matches!(
+            guards.check(&plan_with(5, 3), None),
+            Err(PlanGuardError::TooManyFiles { found: 3, max: 2 })
+        ));
+        assert!(matches!(
+            guards.check(&plan_with(0, 0), None),
+            Err(PlanGuardError::NoMatches(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_guards_strict() {
+        let guards = PlanGuards {
+            strict: true,
+            ..PlanGuards::default()
+        };
+        let mut plan = plan_with(1, 1);
+        assert!(guards.check(&plan, None).is_ok());
+
+        plan.stats.files_skipped.unreadable_files = vec![UnreadableFile {
+            path: PathBuf::from("src/old.rs"),
+            error: "Permission denied (os error 13)".to_string(),
+        }];
+        let err = guards.check(&plan, None).unwrap_err();
+        assert_eq!(err.guard_name(), "strict");
+        assert_eq!(
+            err.to_string(),
+            "1 file could not be read (--strict):\n  src/old.rs: Permission denied (os error 13)"
+        );
+        assert!(PlanGuards::default().check(&plan, None).is_ok());
+    }
+
+    #[test]
+    fn test_count_expectations() {
+        assert_eq!(
+            "120:5".parse::<ExpectedCount>(),
+            Ok(ExpectedCount {
+                count: 120,
+                tolerance: 5
+            })
+        );
+        assert_eq!(
+            "3".parse::<ExpectedCount>(),
+            Ok(ExpectedCount {
+                count: 3,
+                tolerance: 0
+            })
+        );
+        assert!("3:".parse::<ExpectedCount>().is_err());
+        assert!("many".parse::<ExpectedCount>().is_err());
+
+        let expect = CountExpectations {
+            matches: Some("120:5".parse().unwrap()),
+            renames: Some("3".parse().unwrap()),
+        };
+        assert!(expect.check(115, 3).is_ok());
+        assert!(expect.check(125, 3).is_ok());
+        let err = expect.check(126, 3).unwrap_err();
+        assert_eq!(err.guard_name(), "expect_matches");
+        assert_eq!(
+            err.to_string(),
+            "Expected 120±5 matches, found 126 (--expect-matches)"
+        );
+        let err = expect.check(120, 4).unwrap_err();
+        assert_eq!(err.guard_name(), "expect_renames");
+        assert_eq!(err.exceeded_thresholds()[0].min, Some(3));
     }
 }
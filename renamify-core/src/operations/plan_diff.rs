@@ -0,0 +1,252 @@
+use crate::operations::apply::read_plan_file;
+use crate::output::{ChangedMatch, ChangedRename, PlanDiffResult};
+use crate::scanner::{MatchHunk, Plan, Rename};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Plan diff operation - compares two plan files hunk by hunk and rename by rename
+pub fn plan_diff_operation(
+    old_plan_path: &Path,
+    new_plan_path: &Path,
+    working_dir: Option<&Path>,
+) -> Result<PlanDiffResult> {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let resolve = |path: &Path| -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            current_dir.join(path)
+        }
+    };
+
+    let old_plan = read_plan_file(&resolve(old_plan_path))?;
+    let new_plan = read_plan_file(&resolve(new_plan_path))?;
+
+    let mut result = diff_plans(&old_plan, &new_plan);
+    result.old_plan = old_plan_path.to_path_buf();
+    result.new_plan = new_plan_path.to_path_buf();
+    result.base_dir = current_dir.canonicalize().ok();
+    Ok(result)
+}
+
+/// Hunks are identified by where they apply; two hunks at the same position
+/// with a different match or replacement count as a change.
+type HunkKey = (PathBuf, u64, u32);
+
+fn hunk_key(hunk: &MatchHunk) -> HunkKey {
+    (hunk.file.clone(), hunk.line, hunk.byte_offset)
+}
+
+pub fn diff_plans(old_plan: &Plan, new_plan: &Plan) -> PlanDiffResult {
+    let mut result = PlanDiffResult {
+        old_plan: PathBuf::new(),
+        new_plan: PathBuf::new(),
+        added_matches: Vec::new(),
+        removed_matches: Vec::new(),
+        changed_matches: Vec::new(),
+        unchanged_matches: 0,
+        added_renames: Vec::new(),
+        removed_renames: Vec::new(),
+        changed_renames: Vec::new(),
+        unchanged_renames: 0,
+        base_dir: None,
+    };
+
+    let old_hunks: BTreeMap<HunkKey, &MatchHunk> =
+        old_plan.matches.iter().map(|h| (hunk_key(h), h)).collect();
+    let new_hunks: BTreeMap<HunkKey, &MatchHunk> =
+        new_plan.matches.iter().map(|h| (hunk_key(h), h)).collect();
+
+    for (key, old_hunk) in &old_hunks {
+        match new_hunks.get(key) {
+            Some(new_hunk)
+                if new_hunk.content == old_hunk.content && new_hunk.replace == old_hunk.replace =>
+            {
+                result.unchanged_matches += 1;
+            },
+            Some(new_hunk) => result.changed_matches.push(ChangedMatch {
+                before: (*old_hunk).clone(),
+                after: (*new_hunk).clone(),
+            }),
+            None => result.removed_matches.push((*old_hunk).clone()),
+        }
+    }
+    result.added_matches = new_hunks
+        .iter()
+        .filter(|(key, _)| !old_hunks.contains_key(*key))
+        .map(|(_, hunk)| (*hunk).clone())
+        .collect();
+
+    let old_renames: BTreeMap<&Path, &Rename> = old_plan
+        .paths
+        .iter()
+        .map(|r| (r.path.as_path(), r))
+        .collect();
+    let new_renames: BTreeMap<&Path, &Rename> = new_plan
+        .paths
+        .iter()
+        .map(|r| (r.path.as_path(), r))
+        .collect();
+
+    for (path, old_rename) in &old_renames {
+        match new_renames.get(path) {
+            Some(new_rename) if new_rename.new_path == old_rename.new_path => {
+                result.unchanged_renames += 1;
+            },
+            Some(new_rename) => result.changed_renames.push(ChangedRename {
+                before: (*old_rename).clone(),
+                after: (*new_rename).clone(),
+            }),
+            None => result.removed_renames.push((*old_rename).clone()),
+        }
+    }
+    result.added_renames = new_renames
+        .iter()
+        .filter(|(path, _)| !old_renames.contains_key(*path))
+        .map(|(_, rename)| (*rename).clone())
+        .collect();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RenameKind, Stats};
+    use std::collections::HashMap;
+
+    fn rename(path: &str, new_path: &str) -> Rename {
+        Rename {
+            path: PathBuf::from(path),
+            new_path: PathBuf::from(new_path),
+            kind: RenameKind::File,
+            coercion_applied: None,
+        }
+    }
+
+    fn plan(matches: Vec<MatchHunk>, paths: Vec<Rename>) -> Plan {
+        Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches,
+            paths,
+            stats: Stats {
+                files_scanned: 0,
+                total_matches: 0,
+                matches_by_variant: HashMap::new(),
+                files_with_matches: 0,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_plans_classifies_hunks() {
+        let old = plan(
+            vec![
+                MatchHunk::for_test("a.rs", 1, 0, "old_name", "new_name"),
+                MatchHunk::for_test("a.rs", 2, 4, "OldName", "NewName"),
+                MatchHunk::for_test("b.rs", 3, 0, "old-name", "new-name"),
+            ],
+            vec![],
+        );
+        let new = plan(
+            vec![
+                MatchHunk::for_test("a.rs", 1, 0, "old_name", "new_name"),
+                MatchHunk::for_test("a.rs", 2, 4, "OldName", "BetterName"),
+                MatchHunk::for_test("c.rs", 5, 2, "OLD_NAME", "NEW_NAME"),
+            ],
+            vec![],
+        );
+
+        let diff = diff_plans(&old, &new);
+
+        assert_eq!(diff.unchanged_matches, 1);
+        assert_eq!(diff.changed_matches.len(), 1);
+        assert_eq!(diff.changed_matches[0].after.replace, "BetterName");
+        assert_eq!(diff.removed_matches.len(), 1);
+        assert_eq!(diff.removed_matches[0].file, PathBuf::from("b.rs"));
+        assert_eq!(diff.added_matches.len(), 1);
+        assert_eq!(diff.added_matches[0].file, PathBuf::from("c.rs"));
+        assert!(!diff.is_identical());
+    }
+
+    #[test]
+    fn test_diff_plans_classifies_renames() {
+        let old = plan(
+            vec![],
+            vec![
+                rename("old_name.rs", "new_name.rs"),
+                rename("old_name", "new_name"),
+            ],
+        );
+        let new = plan(
+            vec![],
+            vec![
+                rename("old_name.rs", "new_name.rs"),
+                rename("old_name", "better_name"),
+                rename("old_name.txt", "new_name.txt"),
+            ],
+        );
+
+        let diff = diff_plans(&old, &new);
+
+        assert_eq!(diff.unchanged_renames, 1);
+        assert_eq!(diff.changed_renames.len(), 1);
+        assert_eq!(
+            diff.changed_renames[0].after.new_path,
+            PathBuf::from("better_name")
+        );
+        assert_eq!(diff.added_renames.len(), 1);
+        assert!(diff.removed_renames.is_empty());
+    }
+
+    #[test]
+    fn test_diff_identical_plans() {
+        let old = plan(
+            vec![MatchHunk::for_test("a.rs", 1, 0, "old_name", "new_name")],
+            vec![rename("old_name.rs", "new_name.rs")],
+        );
+        let diff = diff_plans(&old, &old.clone());
+        assert!(diff.is_identical());
+        assert_eq!(diff.unchanged_matches, 1);
+        assert_eq!(diff.unchanged_renames, 1);
+    }
+
+    #[test]
+    fn test_diff_summary_paths_relative_to_base_dir() {
+        use crate::output::OutputFormatter;
+
+        let old = plan(vec![], vec![]);
+        let new = plan(
+            vec![MatchHunk::for_test(
+                "/repo/src/a.rs",
+                1,
+                0,
+                "old_name",
+                "new_name",
+            )],
+            vec![rename("/repo/old_name.rs", "/repo/new_name.rs")],
+        );
+        let mut diff = diff_plans(&old, &new);
+        diff.base_dir = Some(PathBuf::from("/repo"));
+
+        let summary = diff.format_summary();
+        assert!(summary.contains("  + src/a.rs:1:1 old_name -> new_name"));
+        assert!(summary.contains("  + old_name.rs -> new_name.rs"));
+    }
+}
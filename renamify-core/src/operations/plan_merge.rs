@@ -0,0 +1,390 @@
+use crate::error::RenamifyError;
+use crate::operations::apply::read_plan_file;
+use crate::output::PlanMergeResult;
+use crate::scanner::{write_plan, MatchHunk, Plan, Rename, Stats};
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Maximum number of conflicts listed in the error message
+const MAX_REPORTED_CONFLICTS: usize = 20;
+
+/// Plan merge operation - combines several plans into one plan file.
+///
+/// Identical hunks and renames are deduplicated. Edits that overlap the same
+/// byte range with different replacements, or paths renamed to different
+/// destinations, are reported as conflicts and nothing is written.
+pub fn plan_merge_operation(
+    plan_paths: &[PathBuf],
+    out: &Path,
+    working_dir: Option<&Path>,
+) -> Result<PlanMergeResult> {
+    if plan_paths.len() < 2 {
+        return Err(RenamifyError::InvalidInput(
+            "plan-merge needs at least two plan files".to_string(),
+        )
+        .into());
+    }
+
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let resolve = |path: &Path| -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            current_dir.join(path)
+        }
+    };
+
+    let plans = plan_paths
+        .iter()
+        .map(|path| read_plan_file(&resolve(path)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let merged = merge_plans(&plans)?;
+    write_plan(&merged.plan, &resolve(out))?;
+
+    Ok(PlanMergeResult {
+        plan_id: merged.plan.id.clone(),
+        plan_path: out.to_path_buf(),
+        sources: plan_paths.to_vec(),
+        total_matches: merged.plan.stats.total_matches,
+        files_with_matches: merged.plan.stats.files_with_matches,
+        renames: merged.plan.paths.len(),
+        duplicate_matches: merged.duplicate_matches,
+        duplicate_renames: merged.duplicate_renames,
+    })
+}
+
+#[derive(Debug)]
+pub struct MergedPlan {
+    pub plan: Plan,
+    pub duplicate_matches: usize,
+    pub duplicate_renames: usize,
+}
+
+/// Merge plans in memory, failing with a conflict error if any edits collide
+pub fn merge_plans(plans: &[Plan]) -> Result<MergedPlan> {
+    let mut conflicts = Vec::new();
+
+    // Content edits, deduplicated and then checked for overlaps per file
+    let mut duplicate_matches = 0;
+    let mut seen_hunks = BTreeSet::new();
+    let mut hunks_by_file: BTreeMap<PathBuf, Vec<MatchHunk>> = BTreeMap::new();
+    for hunk in plans.iter().flat_map(|plan| &plan.matches) {
+        let key = (
+            hunk.file.clone(),
+            hunk.start,
+            hunk.end,
+            hunk.replace.clone(),
+        );
+        if seen_hunks.insert(key) {
+            hunks_by_file
+                .entry(hunk.file.clone())
+                .or_default()
+                .push(hunk.clone());
+        } else {
+            duplicate_matches += 1;
+        }
+    }
+
+    let mut matches = Vec::new();
+    for (file, mut hunks) in hunks_by_file {
+        hunks.sort_by_key(|hunk| (hunk.start, hunk.end));
+        // Compare each hunk with the earlier hunk that reaches furthest
+        let mut furthest: Option<&MatchHunk> = None;
+        for hunk in &hunks {
+            if let Some(previous) = furthest {
+                if hunk.start < previous.end {
+                    conflicts.push(format!(
+                        "{}:{}: '{}' -> '{}' overlaps '{}' -> '{}'",
+                        file.display(),
+                        hunk.line,
+                        previous.content,
+                        previous.replace,
+                        hunk.content,
+                        hunk.replace
+                    ));
+                }
+            }
+            if furthest.is_none_or(|previous| hunk.end > previous.end) {
+                furthest = Some(hunk);
+            }
+        }
+        matches.extend(hunks);
+    }
+    matches.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.byte_offset.cmp(&b.byte_offset))
+    });
+
+    // Renames, keyed by source path; two sources may not share a destination
+    let mut duplicate_renames = 0;
+    let mut renames_by_source: BTreeMap<PathBuf, Rename> = BTreeMap::new();
+    for rename in plans.iter().flat_map(|plan| &plan.paths) {
+        match renames_by_source.get(&rename.path) {
+            Some(existing) if existing.new_path == rename.new_path => duplicate_renames += 1,
+            Some(existing) => conflicts.push(format!(
+                "{} is renamed to both {} and {}",
+                rename.path.display(),
+                existing.new_path.display(),
+                rename.new_path.display()
+            )),
+            None => {
+                renames_by_source.insert(rename.path.clone(), rename.clone());
+            },
+        }
+    }
+    let mut sources_by_destination: BTreeMap<&Path, &Path> = BTreeMap::new();
+    for rename in renames_by_source.values() {
+        if let Some(other) = sources_by_destination.insert(&rename.new_path, &rename.path) {
+            conflicts.push(format!(
+                "{} and {} are both renamed to {}",
+                other.display(),
+                rename.path.display(),
+                rename.new_path.display()
+            ));
+        }
+    }
+
+    if !conflicts.is_empty() {
+        let count = conflicts.len();
+        conflicts.truncate(MAX_REPORTED_CONFLICTS);
+        let mut message = format!("Cannot merge plans: {count} conflicting edit(s)");
+        for conflict in &conflicts {
+            message.push_str("\n  ");
+            message.push_str(conflict);
+        }
+        if count > MAX_REPORTED_CONFLICTS {
+            write!(
+                message,
+                "\n  ... and {} more",
+                count - MAX_REPORTED_CONFLICTS
+            )
+            .unwrap();
+        }
+        return Err(RenamifyError::Conflict(message).into());
+    }
+
+    // Source plans may have scanned overlapping trees, so the largest scan is
+    // the best available lower bound
+    let files_scanned = plans
+        .iter()
+        .map(|plan| plan.stats.files_scanned)
+        .max()
+        .unwrap_or(0);
+    let stats = Stats::from_matches(files_scanned, &matches);
+
+    let distinct_join = |values: Vec<&String>| -> String {
+        let mut unique: Vec<&String> = Vec::new();
+        for value in values {
+            if !unique.contains(&value) {
+                unique.push(value);
+            }
+        }
+        unique
+            .into_iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let search = distinct_join(plans.iter().map(|plan| &plan.search).collect());
+    let replace = distinct_join(plans.iter().map(|plan| &plan.replace).collect());
+
+    let mut styles = Vec::new();
+    for style in plans.iter().flat_map(|plan| &plan.styles) {
+        if !styles.contains(style) {
+            styles.push(*style);
+        }
+    }
+    // Plans built with different --coerce modes fall back to the default
+    let coercion = plans
+        .first()
+        .map(|first| first.coercion)
+        .filter(|mode| plans.iter().all(|plan| plan.coercion == *mode))
+        .unwrap_or_default();
+    let includes = union(plans.iter().map(|plan| &plan.includes));
+    let excludes = union(plans.iter().map(|plan| &plan.excludes));
+
+    let created_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        .to_string();
+
+    let mut hasher = Sha256::new();
+    for plan in plans {
+        hasher.update(plan.id.as_bytes());
+    }
+    hasher.update(created_at.as_bytes());
+    let id = format!("{:x}", hasher.finalize())[..16].to_string();
+
+    Ok(MergedPlan {
+        plan: Plan {
+            id,
+            created_at,
+            search,
+            replace,
+            styles,
+            includes,
+            excludes,
+            matches,
+            paths: renames_by_source.into_values().collect(),
+            stats,
+            version: "1.0.0".to_string(),
+            coercion,
+            created_directories: None,
+            skipped: plans.iter().flat_map(|plan| plan.skipped.clone()).collect(),
+            near_misses: plans
+                .iter()
+                .flat_map(|plan| plan.near_misses.clone())
+                .collect(),
+            suspicious_renames: plans
+                .iter()
+                .flat_map(|plan| plan.suspicious_renames.clone())
+                .collect(),
+            warnings: plans
+                .iter()
+                .flat_map(|plan| plan.warnings.clone())
+                .collect(),
+            options: None,
+        },
+        duplicate_matches,
+        duplicate_renames,
+    })
+}
+
+fn union<'a>(lists: impl Iterator<Item = &'a Vec<String>>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for value in lists.flatten() {
+        if !result.contains(value) {
+            result.push(value.clone());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{error_kind, ErrorKind};
+    use crate::scanner::RenameKind;
+    use std::collections::HashMap;
+
+    fn hunk(file: &str, start: usize, content: &str, replace: &str) -> MatchHunk {
+        MatchHunk::for_test(file, 1, u32::try_from(start).unwrap(), content, replace)
+    }
+
+    fn rename(path: &str, new_path: &str) -> Rename {
+        Rename {
+            path: PathBuf::from(path),
+            new_path: PathBuf::from(new_path),
+            kind: RenameKind::File,
+            coercion_applied: None,
+        }
+    }
+
+    fn plan(search: &str, matches: Vec<MatchHunk>, paths: Vec<Rename>) -> Plan {
+        Plan {
+            id: format!("plan-{search}"),
+            created_at: "0".to_string(),
+            search: search.to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches,
+            paths,
+            stats: Stats {
+                files_scanned: 3,
+                total_matches: 0,
+                matches_by_variant: HashMap::new(),
+                files_with_matches: 0,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_deduplicates_and_recomputes_stats() {
+        let one = plan(
+            "old_name",
+            vec![
+                hunk("a.rs", 0, "old_name", "new_name"),
+                hunk("b.rs", 10, "old_name", "new_name"),
+            ],
+            vec![rename("old_name.rs", "new_name.rs")],
+        );
+        let two = plan(
+            "other_name",
+            vec![
+                hunk("a.rs", 0, "old_name", "new_name"),
+                hunk("a.rs", 20, "other_name", "new_name"),
+            ],
+            vec![rename("old_name.rs", "new_name.rs")],
+        );
+
+        let merged = merge_plans(&[one, two]).unwrap();
+
+        assert_eq!(merged.duplicate_matches, 1);
+        assert_eq!(merged.duplicate_renames, 1);
+        assert_eq!(merged.plan.matches.len(), 3);
+        assert_eq!(merged.plan.paths.len(), 1);
+        assert_eq!(merged.plan.stats.total_matches, 3);
+        assert_eq!(merged.plan.stats.files_with_matches, 2);
+        assert_eq!(merged.plan.stats.matches_by_variant["old_name"], 2);
+        assert_eq!(merged.plan.search, "old_name, other_name");
+        assert_eq!(merged.plan.replace, "new_name");
+    }
+
+    #[test]
+    fn test_merge_rejects_overlapping_edits() {
+        let one = plan(
+            "old_name",
+            vec![hunk("a.rs", 0, "old_name", "new_name")],
+            vec![],
+        );
+        let two = plan("old_name", vec![hunk("a.rs", 4, "name", "title")], vec![]);
+
+        let err = merge_plans(&[one, two]).unwrap_err();
+        assert_eq!(error_kind(&err), ErrorKind::Conflict);
+        assert!(err.to_string().contains("a.rs:1"));
+    }
+
+    #[test]
+    fn test_merge_rejects_conflicting_renames() {
+        let one = plan("a", vec![], vec![rename("old.rs", "new.rs")]);
+        let two = plan("b", vec![], vec![rename("old.rs", "other.rs")]);
+        let err = merge_plans(&[one, two]).unwrap_err();
+        assert!(err.to_string().contains("renamed to both"));
+
+        let one = plan("a", vec![], vec![rename("one.rs", "same.rs")]);
+        let two = plan("b", vec![], vec![rename("two.rs", "same.rs")]);
+        let err = merge_plans(&[one, two]).unwrap_err();
+        assert!(err.to_string().contains("are both renamed to"));
+    }
+
+    #[test]
+    fn test_merge_requires_two_plans() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let err = plan_merge_operation(
+            &[PathBuf::from("one.json")],
+            Path::new("merged.json"),
+            Some(temp_dir.path()),
+        )
+        .unwrap_err();
+        assert_eq!(error_kind(&err), ErrorKind::InvalidInput);
+    }
+}
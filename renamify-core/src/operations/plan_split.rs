@@ -0,0 +1,135 @@
+use crate::codeowners::CodeOwners;
+use crate::output::OwnerPlan;
+use crate::scanner::{write_plan, Plan, RenameKind, Stats};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Plan split operation - writes one plan file per set of owners in
+/// CODEOWNERS, so each team can review and apply its part of a rename.
+///
+/// A directory rename goes to the directory's owners. Files inside it that
+/// belong to other teams keep their old path in those plans, so the plan that
+/// renames a directory should be applied after the plans that edit files in it.
+pub fn plan_split_operation(
+    plan: &Plan,
+    out_dir: &Path,
+    working_dir: Option<&Path>,
+) -> Result<Vec<OwnerPlan>> {
+    let current_dir = working_dir.map_or_else(
+        || std::env::current_dir().expect("Failed to get current directory"),
+        Path::to_path_buf,
+    );
+    let root = crate::submodule::repo_root(&current_dir).unwrap_or_else(|| current_dir.clone());
+    let root = root.canonicalize().unwrap_or(root);
+    let (_, codeowners) = CodeOwners::find(&root)?;
+
+    let resolved_out_dir = if out_dir.is_absolute() {
+        out_dir.to_path_buf()
+    } else {
+        current_dir.join(out_dir)
+    };
+    std::fs::create_dir_all(&resolved_out_dir)
+        .with_context(|| format!("Failed to create directory {}", out_dir.display()))?;
+
+    let owners_of = |path: &Path, is_dir: bool| -> Vec<String> {
+        let relative = path.strip_prefix(&root).unwrap_or(path);
+        codeowners.owners(relative, is_dir).to_vec()
+    };
+    let empty_plan = Plan {
+        matches: Vec::new(),
+        paths: Vec::new(),
+        skipped: Vec::new(),
+        near_misses: Vec::new(),
+        suspicious_renames: Vec::new(),
+        warnings: Vec::new(),
+        ..plan.clone()
+    };
+
+    let mut groups: BTreeMap<Vec<String>, Plan> = BTreeMap::new();
+    for hunk in &plan.matches {
+        groups
+            .entry(owners_of(&hunk.file, false))
+            .or_insert_with(|| empty_plan.clone())
+            .matches
+            .push(hunk.clone());
+    }
+    for hunk in &plan.near_misses {
+        groups
+            .entry(owners_of(&hunk.file, false))
+            .or_insert_with(|| empty_plan.clone())
+            .near_misses
+            .push(hunk.clone());
+    }
+    for skipped in &plan.skipped {
+        groups
+            .entry(owners_of(&skipped.file, false))
+            .or_insert_with(|| empty_plan.clone())
+            .skipped
+            .push(skipped.clone());
+    }
+    for rename in &plan.paths {
+        groups
+            .entry(owners_of(&rename.path, rename.kind == RenameKind::Dir))
+            .or_insert_with(|| empty_plan.clone())
+            .paths
+            .push(rename.clone());
+    }
+
+    let mut names = BTreeSet::new();
+    let mut owner_plans = Vec::with_capacity(groups.len());
+    for (index, (owners, mut sub_plan)) in groups.into_iter().enumerate() {
+        sub_plan.id = format!("{}-{}", plan.id, index + 1);
+        sub_plan.stats = Stats::from_matches(plan.stats.files_scanned, &sub_plan.matches);
+
+        let name = unique_name(&file_stem(&owners), &mut names);
+        let plan_path = out_dir.join(format!("{name}.json"));
+        write_plan(&sub_plan, &resolved_out_dir.join(format!("{name}.json")))
+            .with_context(|| format!("Failed to write plan to {}", plan_path.display()))?;
+
+        owner_plans.push(OwnerPlan {
+            owners,
+            plan_id: sub_plan.id.clone(),
+            plan_path,
+            files_with_matches: sub_plan.stats.files_with_matches,
+            total_matches: sub_plan.stats.total_matches,
+            renames: sub_plan.paths.len(),
+        });
+    }
+
+    Ok(owner_plans)
+}
+
+/// A file name for a set of owners: `@org/billing` becomes `org-billing`
+fn file_stem(owners: &[String]) -> String {
+    if owners.is_empty() {
+        return "unowned".to_string();
+    }
+    owners
+        .iter()
+        .map(|owner| {
+            owner
+                .trim_start_matches('@')
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn unique_name(stem: &str, taken: &mut BTreeSet<String>) -> String {
+    let mut name = stem.to_string();
+    let mut suffix = 2;
+    while !taken.insert(name.clone()) {
+        name = format!("{stem}-{suffix}");
+        suffix += 1;
+    }
+    name
+}
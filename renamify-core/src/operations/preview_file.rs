@@ -0,0 +1,132 @@
+use crate::acronym::AcronymRendering;
+use crate::apply::apply_replacements;
+use crate::case_model::CaseMapping;
+use crate::error::RenamifyError;
+use crate::operations::plan::build_styles_list;
+use crate::output::PreviewFileResult;
+use crate::scanner::{scan_content, CoercionMode, LiteralMode, PlanOptions};
+use crate::{render_plan, Preview, Style};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Name given to a buffer read from stdin without `--file`
+pub const STDIN_NAME: &str = "<stdin>";
+
+/// Preview file operation - transforms a single buffer such as an unsaved file.
+///
+/// Returns the new content, along with a unified diff when `diff` is set.
+/// Nothing in the workspace is touched: there is no lock, plan file or
+/// history entry, and the file itself is never renamed.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::needless_pass_by_value)]
+pub fn preview_file_operation(
+    search: &str,
+    replace: &str,
+    file: Option<&Path>,
+    content: &str,
+    exclude_styles: &[Style],
+    include_styles: &[Style],
+    only_styles: &[Style],
+    enable_plural_variants: bool,
+    ignore_ambiguous: bool,
+    exclude_match: Vec<String>,
+    exclude_matching_lines: Option<String>,
+    literal_mode: LiteralMode,
+    coerce_separators: CoercionMode,
+    acronym_rendering: AcronymRendering,
+    case_mapping: CaseMapping,
+    diff: bool,
+    use_color: bool,
+) -> Result<(PreviewFileResult, Option<String>)> {
+    if replace.is_empty() {
+        return Err(
+            RenamifyError::InvalidInput("preview-file needs a replacement".to_string()).into(),
+        );
+    }
+
+    let options = PlanOptions {
+        styles: build_styles_list(
+            exclude_styles.to_vec(),
+            include_styles.to_vec(),
+            only_styles.to_vec(),
+        ),
+        exclude_match,
+        exclude_matching_lines,
+        enable_plural_variants,
+        ignore_ambiguous,
+        literal_mode,
+        coerce_separators,
+        acronym_rendering,
+        case_mapping,
+        ..PlanOptions::default()
+    };
+
+    let path = file.map_or_else(|| PathBuf::from(STDIN_NAME), Path::to_path_buf);
+    let plan = scan_content(&path, content.as_bytes(), search, replace, &options)?;
+
+    let edits: Vec<(String, String, usize, usize)> = plan
+        .matches
+        .iter()
+        .map(|hunk| {
+            (
+                hunk.content.clone(),
+                hunk.replace.clone(),
+                hunk.start,
+                hunk.end,
+            )
+        })
+        .collect();
+    let transformed = apply_replacements(&path, content, &edits)?;
+
+    let diff = diff.then(|| render_plan(&plan, Preview::Diff, Some(use_color)));
+
+    Ok((
+        PreviewFileResult {
+            file: path,
+            search: search.to_string(),
+            replace: replace.to_string(),
+            content: transformed,
+            matches: plan.matches,
+        },
+        diff,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_file_transforms_buffer() {
+        let source = "let old_name = OldName::new(\"old-name\");\n";
+        let (result, diff) = preview_file_operation(
+            "old_name",
+            "new_name",
+            Some(Path::new("src/lib.rs")),
+            source,
+            &[],
+            &[],
+            &[],
+            true,
+            false,
+            vec![],
+            None,
+            LiteralMode::SkipStrings,
+            CoercionMode::Auto,
+            AcronymRendering::default(),
+            CaseMapping::default(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.content,
+            "let new_name = NewName::new(\"old-name\");\n"
+        );
+        assert_eq!(result.matches.len(), 2);
+        assert_eq!(result.file, PathBuf::from("src/lib.rs"));
+        assert!(diff.is_none());
+    }
+}
@@ -1,15 +1,30 @@
+use crate::acronym::AcronymRendering;
+use crate::case_model::CaseMapping;
+use crate::error::RenamifyError;
+use crate::operations::plan::scan_plan;
+use crate::operations::plan::{CountExpectations, LargeChangeThresholds};
+use crate::output::{LargeChangeCheck, RenameCollision, RenameResult, RenameSafetyChecks};
+use crate::scanner::{
+    CoercionMode, LiteralMode, MatchAnchor, Rename, RenameKind, ScanCaches, Stats, WalkOptions,
+};
+use crate::structured::StructuredFilter;
+use crate::suppress::FileLines;
+use crate::vcs::CommitOptions;
 use crate::{
-    apply_plan, atomic::AtomicConfig, output::RenameResult, scan_repository_multi, ApplyOptions,
-    LockFile, Plan, PlanOptions, Style,
+    apply::apply_plan_cached, atomic::AtomicConfig, content_cache::ContentCache, scan_cache,
+    ApplyOptions, BackupStrategy, Durability, LockFile, Plan, PlanOptions, Profile, Style,
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
 use std::fs;
 use std::io::{self, IsTerminal, Write as IoWrite};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Rename operation - returns structured data
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::too_many_lines)]
 pub fn rename_operation(
     search: &str,
     replace: &str,
@@ -17,6 +32,10 @@ pub fn rename_operation(
     include: &[String],
     exclude: &[String],
     unrestricted_level: u8,
+    include_submodules: bool,
+    walk: WalkOptions,
+    paths_only: bool,
+    contents_only: bool,
     rename_files: bool,
     rename_dirs: bool,
     exclude_styles: &[Style],
@@ -24,12 +43,24 @@ pub fn rename_operation(
     only_styles: &[Style],
     enable_plural_variants: bool,
     ignore_ambiguous: bool,
+    allow_case_collisions: bool,
+    literal_mode: LiteralMode,
+    match_anchor: MatchAnchor,
+    coerce_separators: CoercionMode,
+    structured: Option<StructuredFilter>,
+    profile: Option<&Profile>,
+    swap: bool,
     exclude_match: &[String],
     exclude_matching_lines: Option<&String>,
+    exclude_lines_in_file: &[FileLines],
     preview_format: Option<&String>,
-    commit: bool,
+    commit: Option<&CommitOptions>,
     large: bool,
+    large_thresholds: &LargeChangeThresholds,
+    expect: &CountExpectations,
     force_with_conflicts: bool,
+    durability: Durability,
+    backup: BackupStrategy,
     rename_root: bool,
     no_rename_root: bool,
     dry_run: bool,
@@ -37,11 +68,17 @@ pub fn rename_operation(
     include_acronyms: &[String],
     exclude_acronyms: &[String],
     only_acronyms: &[String],
+    acronym_rendering: Option<AcronymRendering>,
+    case_mapping: Option<CaseMapping>,
+    working_dir: Option<&Path>,
     atomic_config: Option<&AtomicConfig>,
     auto_approve: bool,
     use_color: bool,
 ) -> Result<(RenameResult, Option<String>)> {
-    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+    let current_dir = match working_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
 
     // Use provided paths or default to current directory
     let search_paths = if paths.is_empty() {
@@ -50,16 +87,28 @@ pub fn rename_operation(
         paths
     };
 
-    // Acquire lock
+    // Acquire lock; a dry run can go ahead without the workspace
+    if !dry_run {
+        crate::workspace::require("rename")?;
+    }
     let renamify_dir = current_dir.join(".renamify");
-    let _lock = LockFile::acquire(&renamify_dir)
-        .context("Failed to acquire lock for renamify operation")?;
+    let _lock = if crate::workspace::is_disabled() {
+        None
+    } else {
+        Some(
+            LockFile::acquire(&renamify_dir)
+                .context("Failed to acquire lock for renamify operation")?,
+        )
+    };
 
-    // Build the list of styles to use based on exclude, include, and only options
-    let styles = build_styles_list(exclude_styles, include_styles, only_styles);
+    // Build the list of styles to use based on exclude, include, and only options,
+    // with the profile's styles standing in for --only-styles
+    let only_styles = crate::profile::only_styles(profile, only_styles)?;
+    let styles = build_styles_list(exclude_styles, include_styles, &only_styles);
+    let config = crate::config::Config::load_in(&current_dir)?;
 
     // Generate the plan
-    let options = PlanOptions {
+    let mut options = PlanOptions {
         includes: include.to_owned(),
         excludes: exclude.to_owned(),
         respect_gitignore: true,
@@ -69,9 +118,10 @@ pub fn rename_operation(
         rename_dirs,
         rename_root: false,
         plan_out: PathBuf::from(".renamify/temp_plan.json"),
-        coerce_separators: crate::scanner::CoercionMode::Auto,
+        coerce_separators,
         exclude_match: exclude_match.to_owned(),
         exclude_matching_lines: exclude_matching_lines.map(std::string::ToString::to_string),
+        exclude_lines_in_file: exclude_lines_in_file.to_owned(),
         no_acronyms,
         include_acronyms: include_acronyms.to_owned(),
         exclude_acronyms: exclude_acronyms.to_owned(),
@@ -79,7 +129,24 @@ pub fn rename_operation(
         ignore_ambiguous,
         atomic_config: atomic_config.cloned(),
         enable_plural_variants,
+        allow_case_collisions,
+        literal_mode,
+        structured,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules,
+        paths_only,
+        contents_only,
+        match_anchor,
+        working_dir: Some(current_dir.clone()),
+        walk,
+        acronym_rendering: acronym_rendering.unwrap_or(config.acronym_rendering),
+        case_mapping: case_mapping.unwrap_or(config.case_mapping),
     };
+    if let Some(profile) = profile {
+        profile.apply(&mut options);
+    }
 
     // Resolve all search paths to absolute paths and canonicalize them
     let resolved_paths: Vec<PathBuf> = search_paths
@@ -94,8 +161,24 @@ pub fn rename_operation(
         })
         .collect();
 
-    let mut plan = scan_repository_multi(&resolved_paths, search, replace, &options)
-        .with_context(|| format!("Failed to scan repository for '{search}' -> '{replace}'"))?;
+    // Matched files are kept in memory so applying doesn't read them again
+    let contents = ContentCache::new();
+    let mut plan = scan_plan(
+        &resolved_paths,
+        search,
+        replace,
+        &options,
+        swap,
+        None,
+        ScanCaches {
+            contents: Some(&contents),
+            files: scan_cache::cache_dir(&renamify_dir).as_deref(),
+        },
+    )
+    .with_context(|| format!("Failed to scan repository for '{search}' -> '{replace}'"))?;
+    // From here on the replacement has its placeholders filled in
+    let replace = plan.replace.clone();
+    let replace = replace.as_str();
 
     // Separate root directory renames from other renames
     let (root_renames, other_renames) = separate_root_renames(&plan.paths, &resolved_paths);
@@ -107,6 +190,19 @@ pub fn rename_operation(
         rename_root,
         no_rename_root,
     );
+    // Renames into ignored or protected paths are only made from a plan
+    // where they were confirmed
+    crate::suspicious::hold_back_suspicious_renames(
+        &mut plan,
+        &resolved_paths,
+        &options,
+        &config.protected,
+    )?;
+
+    // Expected counts stand in for the confirmation prompt, so they are
+    // checked before anything else can end the run
+    expect.check(plan.stats.total_matches, plan.paths.len())?;
+    let auto_approve = auto_approve || expect.is_set();
 
     // Check if there's anything to do after filtering
     if plan.stats.total_matches == 0 && plan.paths.is_empty() {
@@ -126,10 +222,21 @@ pub fn rename_operation(
                     renames: 0,
                     committed: false,
                     plan: Some(plan),
+                    dry_run: false,
+                    safety_checks: None,
+                    history_entry: None,
                 },
                 preview,
             ));
         }
+        let diagnosis = crate::empty_plan::EmptyPlanDiagnosis::diagnose(
+            &resolved_paths,
+            search,
+            replace,
+            &options,
+            swap,
+            &plan,
+        )?;
         return Ok((
             RenameResult {
                 plan_id: plan.id.clone(),
@@ -140,27 +247,55 @@ pub fn rename_operation(
                 renames: 0,
                 committed: false,
                 plan: Some(plan),
+                dry_run: false,
+                safety_checks: None,
+                history_entry: None,
             },
-            Some(format!("No matches found for '{}'", search)),
+            Some(format!("No matches found for '{search}':\n{diagnosis}")),
         ));
     }
 
+    plan.warnings = crate::warnings::plan_warnings(&plan, &resolved_paths, &options, &config);
+    if let Some(profile) = profile {
+        let labels = profile.label_warnings(&plan, &resolved_paths, &options);
+        plan.warnings.extend(labels);
+    }
+
     // Generate preview if requested
     let mut preview_output = None;
     if let Some(format) = preview_format.as_ref() {
         if *format != "none" {
-            let preview = generate_preview_output(&plan, format, use_color)?;
+            let preview = generate_preview_output(&plan, format, use_color, &current_dir)?;
             preview_output = Some(preview.clone());
 
             // Print preview BEFORE asking for confirmation (but not in dry-run)
             if !dry_run && !auto_approve {
-                println!("{}", preview);
+                crate::pager::page(&preview);
             }
         }
     }
 
-    // If dry-run, stop here (no safety checks needed for dry-run)
+    // If dry-run, stop here and report what apply would check and record
     if dry_run {
+        let safety_checks = compute_safety_checks(
+            &plan,
+            root_renames,
+            rename_root,
+            large,
+            large_thresholds,
+            force_with_conflicts,
+        );
+        let history_entry = crate::apply::preview_history_entry(
+            &plan,
+            &rename_apply_options(
+                &plan.id,
+                commit,
+                force_with_conflicts,
+                durability,
+                backup,
+                &current_dir,
+            ),
+        )?;
         return Ok((
             RenameResult {
                 plan_id: plan.id.clone(),
@@ -171,16 +306,25 @@ pub fn rename_operation(
                 renames: plan.paths.len(),
                 committed: false,
                 plan: Some(plan),
+                dry_run: true,
+                safety_checks: Some(safety_checks),
+                history_entry: Some(history_entry),
             },
             preview_output,
         ));
     }
 
     // Safety checks (only for non-dry-run operations)
-    validate_operation_safety(&plan, auto_approve, large, force_with_conflicts)?;
+    validate_operation_safety(
+        &plan,
+        auto_approve,
+        large,
+        large_thresholds,
+        force_with_conflicts,
+    )?;
 
     // Get confirmation unless auto-approved
-    if !auto_approve && !get_user_confirmation()? {
+    if !auto_approve && !confirm_by_directory(&mut plan, &current_dir)? {
         return Ok((
             RenameResult {
                 plan_id: plan.id.clone(),
@@ -191,6 +335,9 @@ pub fn rename_operation(
                 renames: 0,
                 committed: false,
                 plan: Some(plan),
+                dry_run: false,
+                safety_checks: None,
+                history_entry: None,
             },
             Some("Aborted.".to_string()),
         ));
@@ -202,7 +349,15 @@ pub fn rename_operation(
     let replacements = plan.stats.total_matches;
     let renames = plan.paths.len();
 
-    apply_rename_changes(&mut plan, commit, force_with_conflicts)?;
+    apply_rename_changes(
+        &mut plan,
+        commit,
+        force_with_conflicts,
+        durability,
+        backup,
+        &current_dir,
+        &contents,
+    )?;
 
     // Add root rename snippet to preview if needed
     if !root_renames.is_empty() && !rename_root && !no_rename_root {
@@ -228,8 +383,11 @@ pub fn rename_operation(
             files_changed,
             replacements,
             renames,
-            committed: commit,
+            committed: commit.is_some(),
             plan: Some(plan),
+            dry_run: false,
+            safety_checks: None,
+            history_entry: None,
         },
         preview_output,
     ))
@@ -308,91 +466,354 @@ fn validate_operation_safety(
     plan: &Plan,
     auto_approve: bool,
     large: bool,
+    large_thresholds: &LargeChangeThresholds,
     force_with_conflicts: bool,
 ) -> Result<()> {
     // Safety check: Non-TTY without auto-approve should exit with error
     if !auto_approve && !io::stdout().is_terminal() {
-        return Err(anyhow!(
+        return Err(RenamifyError::InvalidInput(
             "Cannot prompt for confirmation in non-interactive mode. Use auto_approve=true."
-        ));
+                .to_string(),
+        )
+        .into());
     }
 
     // Safety check: Size guard for large changes
-    let file_count = plan.stats.files_with_matches;
-    let rename_count = plan.paths.len();
-    if (file_count > 500 || rename_count > 100) && !large {
-        return Err(anyhow!(
-            "Large change detected ({} files, {} renames). Use large=true to acknowledge.",
-            file_count,
-            rename_count
-        ));
+    if !large {
+        large_thresholds.check(plan.stats.files_with_matches, plan.paths.len())?;
     }
 
     // Safety check: Conflicts should abort unless forced
-    let has_conflicts = false; // TODO: implement conflict detection
-    if has_conflicts && !force_with_conflicts {
-        return Err(anyhow!(
-            "Conflicts detected. Use force_with_conflicts=true to override."
-        ));
+    let collisions = find_collisions(&plan.paths);
+    if !collisions.is_empty() && !force_with_conflicts {
+        let details: Vec<String> = collisions
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} -> {}: {}",
+                    c.path.display(),
+                    c.new_path.display(),
+                    c.reason
+                )
+            })
+            .collect();
+        return Err(RenamifyError::Conflict(format!(
+            "Conflicts detected. Use force_with_conflicts=true to override.\n  {}",
+            details.join("\n  ")
+        ))
+        .into());
     }
 
     Ok(())
 }
 
-fn generate_preview_output(plan: &Plan, format: &str, use_color: bool) -> Result<String> {
-    let preview_format = match format {
-        "table" => crate::preview::Preview::Table,
-        "diff" => crate::preview::Preview::Diff,
-        "matches" => crate::preview::Preview::Matches,
-        "summary" => crate::preview::Preview::Summary,
-        _ => return Err(anyhow!("Invalid preview format: {}", format)),
+/// Compute the checks `validate_operation_safety` enforces, for reporting in dry runs.
+/// The interactive-terminal check is left out since callers re-invoke with `-y`.
+fn compute_safety_checks(
+    plan: &Plan,
+    root_renames: Vec<Rename>,
+    rename_root: bool,
+    large: bool,
+    large_thresholds: &LargeChangeThresholds,
+    force_with_conflicts: bool,
+) -> RenameSafetyChecks {
+    let large_change = large_change_check(plan, large, large_thresholds);
+    let collisions = find_collisions(&plan.paths);
+    let passed = (!large_change.exceeded || large_change.acknowledged)
+        && (collisions.is_empty() || force_with_conflicts);
+    RenameSafetyChecks {
+        large_change,
+        collisions,
+        root_renames_applied: rename_root && !root_renames.is_empty(),
+        root_renames,
+        passed,
+    }
+}
+
+fn large_change_check(
+    plan: &Plan,
+    large: bool,
+    large_thresholds: &LargeChangeThresholds,
+) -> LargeChangeCheck {
+    let files = plan.stats.files_with_matches;
+    let renames = plan.paths.len();
+    LargeChangeCheck {
+        files,
+        renames,
+        max_files: large_thresholds.max_files,
+        max_renames: large_thresholds.max_renames,
+        exceeded: large_thresholds.is_exceeded(files, renames),
+        acknowledged: large,
+    }
+}
+
+/// Find renames whose destination already exists on disk or is shared with
+/// another rename. Destinations that are themselves renamed away, and case-only
+/// renames, don't count.
+pub(crate) fn find_collisions(renames: &[Rename]) -> Vec<RenameCollision> {
+    let sources: HashSet<&Path> = renames.iter().map(|r| r.path.as_path()).collect();
+    let mut destinations: HashMap<&Path, &Path> = HashMap::new();
+    let mut collisions = Vec::new();
+
+    for rename in renames {
+        let reason = if let Some(other) = destinations.insert(&rename.new_path, &rename.path) {
+            format!("destination is shared with {}", other.display())
+        } else if rename.new_path.exists()
+            && !sources.contains(rename.new_path.as_path())
+            && rename.path.to_string_lossy().to_lowercase()
+                != rename.new_path.to_string_lossy().to_lowercase()
+        {
+            "destination already exists".to_string()
+        } else {
+            continue;
+        };
+        collisions.push(RenameCollision {
+            path: rename.path.clone(),
+            new_path: rename.new_path.clone(),
+            reason,
+        });
+    }
+
+    collisions
+}
+
+fn generate_preview_output(
+    plan: &Plan,
+    format: &str,
+    use_color: bool,
+    current_dir: &Path,
+) -> Result<String> {
+    let options = crate::preview::RenderOptions {
+        use_color,
+        base_dir: Some(current_dir),
+        ..Default::default()
     };
+    crate::preview::render_plan_named(plan, format, &options)
+}
 
-    Ok(crate::preview::render_plan(
-        plan,
-        preview_format,
-        Some(use_color),
-    ))
+/// Answer to the confirmation prompt
+#[derive(Debug, PartialEq, Eq)]
+enum Confirmation {
+    Yes,
+    No,
+    /// Drop these top-level directories from the plan and ask again
+    Exclude(Vec<String>),
+}
+
+fn parse_confirmation(input: &str) -> Confirmation {
+    let input = input.trim();
+    if input.starts_with('!') {
+        let dirs = input
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('!'))
+            .map(|dir| dir.trim_end_matches('/').to_string())
+            .filter(|dir| !dir.is_empty())
+            .collect();
+        return Confirmation::Exclude(dirs);
+    }
+    match input.to_lowercase().as_str() {
+        "y" | "yes" => Confirmation::Yes,
+        _ => Confirmation::No,
+    }
+}
+
+/// Matches and renames under one top-level directory of the working directory
+#[derive(Debug, PartialEq, Eq)]
+struct DirectoryBreakdown {
+    /// Directory name, or `.` for files directly in the working directory
+    dir: String,
+    matches: usize,
+    renames: usize,
+}
+
+/// The top-level directory a planned path falls under. A directory that is
+/// itself being renamed counts under its own name.
+fn top_level_dir(path: &Path, root: &Path, is_dir: bool) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let mut components = relative.components();
+    match components.next() {
+        Some(first) if is_dir || components.next().is_some() => {
+            first.as_os_str().to_string_lossy().into_owned()
+        },
+        _ => ".".to_string(),
+    }
+}
+
+fn directory_breakdown(plan: &Plan, root: &Path) -> Vec<DirectoryBreakdown> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for hunk in &plan.matches {
+        counts
+            .entry(top_level_dir(&hunk.file, root, false))
+            .or_default()
+            .0 += 1;
+    }
+    for rename in &plan.paths {
+        let is_dir = rename.kind == RenameKind::Dir;
+        counts
+            .entry(top_level_dir(&rename.path, root, is_dir))
+            .or_default()
+            .1 += 1;
+    }
+    let mut breakdown: Vec<DirectoryBreakdown> = counts
+        .into_iter()
+        .map(|(dir, (matches, renames))| DirectoryBreakdown {
+            dir,
+            matches,
+            renames,
+        })
+        .collect();
+    // Files directly in the working directory go last
+    breakdown.sort_by_key(|entry| entry.dir == ".");
+    breakdown
+}
+
+fn render_breakdown(breakdown: &[DirectoryBreakdown]) -> String {
+    let width = breakdown
+        .iter()
+        .map(|entry| entry.dir.len() + 1)
+        .max()
+        .unwrap_or(0);
+    let mut output = String::from("Changes by directory:\n");
+    for (i, entry) in breakdown.iter().enumerate() {
+        let branch = if i + 1 == breakdown.len() {
+            "└──"
+        } else {
+            "├──"
+        };
+        let name = if entry.dir == "." {
+            "./".to_string()
+        } else {
+            format!("{}/", entry.dir)
+        };
+        let mut counts = Vec::new();
+        if entry.matches > 0 {
+            counts.push(pluralize(entry.matches, "match", "matches"));
+        }
+        if entry.renames > 0 {
+            counts.push(pluralize(entry.renames, "rename", "renames"));
+        }
+        writeln!(output, "{branch} {name:<width$}  {}", counts.join(", ")).unwrap();
+    }
+    output
 }
 
-fn get_user_confirmation() -> Result<bool> {
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    format!("{count} {}", if count == 1 { singular } else { plural })
+}
+
+/// Drop every match and rename under a top-level directory. Returns false if
+/// the plan had nothing there.
+fn exclude_directory(plan: &mut Plan, root: &Path, dir: &str) -> bool {
+    let before = plan.matches.len() + plan.paths.len();
+    plan.matches
+        .retain(|hunk| top_level_dir(&hunk.file, root, false) != dir);
+    plan.paths
+        .retain(|rename| top_level_dir(&rename.path, root, rename.kind == RenameKind::Dir) != dir);
+    let counts = Stats::from_matches(plan.stats.files_scanned, &plan.matches);
+    plan.stats.total_matches = counts.total_matches;
+    plan.stats.files_with_matches = counts.files_with_matches;
+    plan.stats.matches_by_variant = counts.matches_by_variant;
+    plan.matches.len() + plan.paths.len() < before
+}
+
+/// Show the per-directory summary and ask to apply, letting the user drop
+/// directories with `!dir` until they answer yes or no
+fn confirm_by_directory(plan: &mut Plan, current_dir: &Path) -> Result<bool> {
+    let root = current_dir
+        .canonicalize()
+        .unwrap_or_else(|_| current_dir.to_path_buf());
+    loop {
+        let breakdown = directory_breakdown(plan, &root);
+        print!("\n{}", render_breakdown(&breakdown));
+        let prompt = if breakdown.len() > 1 {
+            "Apply? [y/N, !dir to exclude a directory]: "
+        } else {
+            "Apply? [y/N]: "
+        };
+        match get_user_confirmation(prompt)? {
+            Confirmation::Yes => return Ok(true),
+            Confirmation::No => return Ok(false),
+            Confirmation::Exclude(dirs) => {
+                for dir in dirs {
+                    if !exclude_directory(plan, &root, &dir) {
+                        println!("No changes under {dir}/");
+                    }
+                }
+                if plan.matches.is_empty() && plan.paths.is_empty() {
+                    println!("Nothing left to apply");
+                    return Ok(false);
+                }
+            },
+        }
+    }
+}
+
+fn get_user_confirmation(prompt: &str) -> Result<Confirmation> {
     let _guard = crate::interrupt::ConfirmationPromptGuard::activate();
-    print!("Apply? [y/N]: ");
+    print!("{prompt}");
     IoWrite::flush(&mut io::stdout()).context("Failed to flush stdout")?;
 
     let mut input = String::new();
     io::stdin()
         .read_line(&mut input)
         .context("Failed to read user input")?;
-    let input = input.trim().to_lowercase();
 
-    Ok(input == "y" || input == "yes")
+    Ok(parse_confirmation(&input))
 }
 
-fn apply_rename_changes(plan: &mut Plan, commit: bool, force_with_conflicts: bool) -> Result<()> {
+fn apply_rename_changes(
+    plan: &mut Plan,
+    commit: Option<&CommitOptions>,
+    force_with_conflicts: bool,
+    durability: Durability,
+    backup: BackupStrategy,
+    current_dir: &Path,
+    contents: &ContentCache,
+) -> Result<()> {
     // Create the renamify directory if it doesn't exist
-    let renamify_dir = PathBuf::from(".renamify");
+    let renamify_dir = current_dir.join(".renamify");
     fs::create_dir_all(&renamify_dir)?;
 
     // Save the plan ID for the undo message
     let history_id = plan.id.clone();
-    let backup_dir = renamify_dir.join("backups");
 
     // Don't print to stdout when we're returning structured data
     eprintln!("Applying changes...");
 
-    // Apply the plan
-    let apply_options = ApplyOptions {
-        create_backups: true,
-        backup_dir,
+    let apply_options = rename_apply_options(
+        &history_id,
         commit,
+        force_with_conflicts,
+        durability,
+        backup,
+        current_dir,
+    );
+    apply_plan_cached(plan, &apply_options, None, Some(contents))
+        .context("Failed to apply renaming plan")?;
+    Ok(())
+}
+
+fn rename_apply_options(
+    history_id: &str,
+    commit: Option<&CommitOptions>,
+    force_with_conflicts: bool,
+    durability: Durability,
+    backup: BackupStrategy,
+    current_dir: &Path,
+) -> ApplyOptions {
+    let renamify_dir = current_dir.join(".renamify");
+    ApplyOptions {
+        backup_dir: renamify_dir.join("backups"),
+        commit: commit.is_some(),
+        commit_options: commit.cloned().unwrap_or_default(),
+        stage: false,
         force: force_with_conflicts,
         skip_symlinks: false,
         log_file: Some(renamify_dir.join("logs").join(format!("{history_id}.log"))),
-    };
-
-    apply_plan(plan, &apply_options).context("Failed to apply renaming plan")?;
-    Ok(())
+        working_dir: Some(current_dir.to_path_buf()),
+        durability,
+        backup,
+    }
 }
 
 fn generate_root_rename_snippet(root_renames: &[crate::scanner::Rename]) -> String {
@@ -556,18 +977,27 @@ mod tests {
                 total_matches: 1000,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 600, // > 500, should trigger large change check
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
         // Should error without large=true
-        let result = validate_operation_safety(&plan, true, false, false);
+        let result =
+            validate_operation_safety(&plan, true, false, &LargeChangeThresholds::default(), false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Large change"));
 
         // Should succeed with large=true
-        let result = validate_operation_safety(&plan, true, true, false);
+        let result =
+            validate_operation_safety(&plan, true, true, &LargeChangeThresholds::default(), false);
         assert!(result.is_ok());
     }
 
@@ -595,17 +1025,113 @@ mod tests {
                 total_matches: 50,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 50,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
         // Should error due to too many renames
-        let result = validate_operation_safety(&plan, true, false, false);
+        let result =
+            validate_operation_safety(&plan, true, false, &LargeChangeThresholds::default(), false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Large change"));
     }
 
+    #[test]
+    fn test_find_collisions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let taken = temp_dir.path().join("taken.txt");
+        fs::write(&taken, "").unwrap();
+        let path = |name: &str| temp_dir.path().join(name).to_string_lossy().to_string();
+
+        let renames = vec![
+            create_test_rename(&path("a.txt"), &path("taken.txt")),
+            create_test_rename(&path("b.txt"), &path("shared.txt")),
+            create_test_rename(&path("c.txt"), &path("shared.txt")),
+            // Destination is renamed away first, so it is free by the time we get there
+            create_test_rename(&path("d.txt"), &path("e.txt")),
+            create_test_rename(&path("e.txt"), &path("f.txt")),
+            // Case-only renames find their own source on case-insensitive filesystems
+            create_test_rename(&path("README.txt"), &path("readme.txt")),
+        ];
+        fs::write(temp_dir.path().join("e.txt"), "").unwrap();
+        fs::write(temp_dir.path().join("readme.txt"), "").unwrap();
+
+        let collisions = find_collisions(&renames);
+        assert_eq!(collisions.len(), 2);
+        assert_eq!(collisions[0].reason, "destination already exists");
+        assert_eq!(collisions[1].path, PathBuf::from(path("c.txt")));
+        assert!(collisions[1]
+            .reason
+            .starts_with("destination is shared with"));
+    }
+
+    #[test]
+    fn test_compute_safety_checks() {
+        use crate::scanner::Stats;
+        use std::collections::HashMap;
+
+        let plan = Plan {
+            id: "test".to_string(),
+            created_at: "2024-01-01".to_string(),
+            search: "old".to_string(),
+            replace: "new".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats {
+                files_scanned: 600,
+                total_matches: 600,
+                matches_by_variant: HashMap::new(),
+                files_with_matches: 600,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        let root = vec![create_test_rename("old_project", "new_project")];
+
+        let checks = compute_safety_checks(
+            &plan,
+            root.clone(),
+            false,
+            false,
+            &LargeChangeThresholds::default(),
+            false,
+        );
+        assert!(checks.large_change.exceeded);
+        assert!(!checks.passed);
+        assert_eq!(checks.root_renames.len(), 1);
+        assert!(!checks.root_renames_applied);
+
+        let checks = compute_safety_checks(
+            &plan,
+            root,
+            true,
+            true,
+            &LargeChangeThresholds::default(),
+            false,
+        );
+        assert!(checks.large_change.acknowledged);
+        assert!(checks.passed);
+        assert!(checks.root_renames_applied);
+    }
+
     #[test]
     fn test_generate_preview_output_table() {
         use crate::scanner::{Plan, Stats};
@@ -626,12 +1152,19 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
-        let result = generate_preview_output(&plan, "table", false);
+        let result = generate_preview_output(&plan, "table", false, Path::new("."));
         assert!(result.is_ok());
     }
 
@@ -655,12 +1188,19 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
-        let result = generate_preview_output(&plan, "invalid", false);
+        let result = generate_preview_output(&plan, "invalid", false, Path::new("."));
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -686,6 +1226,99 @@ mod tests {
         assert_eq!(snippet, "");
     }
 
+    #[test]
+    fn test_parse_confirmation() {
+        assert_eq!(parse_confirmation("y\n"), Confirmation::Yes);
+        assert_eq!(parse_confirmation("YES"), Confirmation::Yes);
+        assert_eq!(parse_confirmation("\n"), Confirmation::No);
+        assert_eq!(parse_confirmation("docs"), Confirmation::No);
+        assert_eq!(
+            parse_confirmation("!docs !tests/\n"),
+            Confirmation::Exclude(vec!["docs".to_string(), "tests".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_directory_breakdown_and_exclude() {
+        let root = Path::new("/repo");
+        let hunk = |file: &str| crate::scanner::MatchHunk {
+            file: root.join(file),
+            line: 1,
+            byte_offset: 0,
+            char_offset: 0,
+            variant: "old".to_string(),
+            content: "old".to_string(),
+            replace: "new".to_string(),
+            start: 0,
+            end: 3,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        };
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: String::new(),
+            search: "old".to_string(),
+            replace: "new".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![
+                hunk("src/a.rs"),
+                hunk("src/b/c.rs"),
+                hunk("docs/old.md"),
+                hunk("README.md"),
+            ],
+            paths: vec![
+                Rename {
+                    path: root.join("docs/old.md"),
+                    new_path: root.join("docs/new.md"),
+                    kind: RenameKind::File,
+                    coercion_applied: None,
+                },
+                Rename {
+                    path: root.join("old"),
+                    new_path: root.join("new"),
+                    kind: RenameKind::Dir,
+                    coercion_applied: None,
+                },
+            ],
+            stats: Stats::default(),
+            version: String::new(),
+            coercion: CoercionMode::default(),
+            created_directories: None,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+        };
+
+        let rendered = render_breakdown(&directory_breakdown(&plan, root));
+        assert_eq!(
+            rendered,
+            "Changes by directory:\n\
+             ├── docs/  1 match, 1 rename\n\
+             ├── old/   1 rename\n\
+             ├── src/   2 matches\n\
+             └── ./     1 match\n"
+        );
+
+        assert!(exclude_directory(&mut plan, root, "docs"));
+        assert!(!exclude_directory(&mut plan, root, "docs"));
+        assert_eq!(plan.matches.len(), 3);
+        assert_eq!(plan.paths.len(), 1);
+        assert_eq!(plan.stats.total_matches, 3);
+        assert_eq!(plan.stats.files_with_matches, 3);
+    }
+
     #[test]
     fn test_get_user_confirmation_needs_interactive_environment() {
         // This test can't be properly tested in CI, but we can at least test the function exists
@@ -0,0 +1,268 @@
+use crate::lock::LockFile;
+use crate::operations::apply::load_plan;
+use crate::operations::plan::scan_plan;
+use crate::operations::plan_diff::diff_plans;
+use crate::output::{ChangedMatch, PlanDiffResult, ReplanResult};
+use crate::scan_cache;
+use crate::scanner::{write_plan, MatchHunk, Plan, PlanOptions, ScanCaches};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Replan operation - regenerates an old plan against the current working
+/// tree with the options recorded in it.
+///
+/// `plan` is a plan ID, path or URL as accepted by `apply`, or
+/// `.renamify/plan.json` when `None`. The new plan is written to `plan_out`
+/// unless `dry_run` is set, and compared with the old one hunk by hunk.
+pub fn replan_operation(
+    plan: Option<&str>,
+    paths: Vec<PathBuf>,
+    plan_out: Option<&Path>,
+    dry_run: bool,
+    working_dir: Option<&Path>,
+) -> Result<ReplanResult> {
+    let current_dir = working_dir.map_or_else(
+        || std::env::current_dir().expect("Failed to get current directory"),
+        Path::to_path_buf,
+    );
+    let renamify_dir = current_dir.join(".renamify");
+    if !dry_run {
+        crate::workspace::require("replan")?;
+    }
+    let _lock = if dry_run {
+        None
+    } else {
+        Some(
+            LockFile::acquire(&renamify_dir)
+                .context("Failed to acquire lock for renamify operation")?,
+        )
+    };
+
+    let old_plan = load_plan(plan, &current_dir)?;
+    let plan_out_path =
+        plan_out.map_or_else(|| PathBuf::from(".renamify/plan.json"), Path::to_path_buf);
+    let options = recorded_options(&old_plan, &current_dir, &plan_out_path);
+
+    let search_paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths
+    };
+    let roots: Vec<PathBuf> = search_paths
+        .iter()
+        .map(|path| {
+            let absolute_path = current_dir.join(path);
+            absolute_path.canonicalize().unwrap_or(absolute_path)
+        })
+        .collect();
+
+    let mut new_plan = scan_plan(
+        &roots,
+        &old_plan.search,
+        &old_plan.replace,
+        &options,
+        false,
+        None,
+        ScanCaches {
+            files: scan_cache::cache_dir(&renamify_dir).as_deref(),
+            ..ScanCaches::default()
+        },
+    )
+    .context("Failed to scan repository")?;
+    let config = crate::config::Config::load_in(&current_dir)?;
+    crate::suspicious::hold_back_suspicious_renames(
+        &mut new_plan,
+        &roots,
+        &options,
+        &config.protected,
+    )?;
+    new_plan.warnings = crate::warnings::plan_warnings(&new_plan, &roots, &options, &config);
+
+    if !dry_run {
+        write_plan(&new_plan, &current_dir.join(&plan_out_path))
+            .with_context(|| format!("Failed to write plan to {}", plan_out_path.display()))?;
+    }
+
+    let mut diff = diff_plans(&old_plan, &new_plan);
+    diff.base_dir = current_dir.canonicalize().ok();
+    let moved_matches = take_moved_matches(&mut diff);
+    Ok(ReplanResult {
+        old_plan_id: old_plan.id,
+        plan_id: new_plan.id,
+        plan_path: (!dry_run).then_some(plan_out_path),
+        search: new_plan.search,
+        replace: new_plan.replace,
+        moved_matches,
+        diff,
+    })
+}
+
+/// The options an old plan was built with. Plans from before every option
+/// was recorded only keep their globs, styles and coercion mode.
+fn recorded_options(plan: &Plan, current_dir: &Path, plan_out: &Path) -> PlanOptions {
+    let recorded = plan.options.as_ref().map_or_else(
+        || PlanOptions {
+            includes: plan.includes.clone(),
+            excludes: plan.excludes.clone(),
+            styles: (!plan.styles.is_empty()).then(|| plan.styles.clone()),
+            coerce_separators: plan.coercion,
+            ..PlanOptions::default()
+        },
+        Clone::clone,
+    );
+    PlanOptions {
+        plan_out: plan_out.to_path_buf(),
+        working_dir: Some(current_dir.to_path_buf()),
+        ..recorded
+    }
+}
+
+/// Pair hunks that disappeared with new hunks for the same text in the same
+/// file, which moved rather than changed, in the order they appear
+fn take_moved_matches(diff: &mut PlanDiffResult) -> Vec<ChangedMatch> {
+    type TextKey = (PathBuf, String, String);
+    let text_key = |hunk: &MatchHunk| -> TextKey {
+        (
+            hunk.file.clone(),
+            hunk.content.clone(),
+            hunk.replace.clone(),
+        )
+    };
+
+    let mut added: BTreeMap<TextKey, VecDeque<MatchHunk>> = BTreeMap::new();
+    for hunk in diff.added_matches.drain(..) {
+        added.entry(text_key(&hunk)).or_default().push_back(hunk);
+    }
+
+    let mut moved = Vec::new();
+    let mut removed = Vec::new();
+    for hunk in diff.removed_matches.drain(..) {
+        match added
+            .get_mut(&text_key(&hunk))
+            .and_then(VecDeque::pop_front)
+        {
+            Some(after) => moved.push(ChangedMatch {
+                before: hunk,
+                after,
+            }),
+            None => removed.push(hunk),
+        }
+    }
+
+    diff.removed_matches = removed;
+    diff.added_matches = added.into_values().flatten().collect();
+    diff.added_matches
+        .sort_by(|a, b| (&a.file, a.line, a.byte_offset).cmp(&(&b.file, b.line, b.byte_offset)));
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn plan_in(dir: &Path) -> Plan {
+        // As after `renamify init`, the workspace itself is ignored
+        fs::write(dir.join(".gitignore"), ".renamify/\n").unwrap();
+        let options = PlanOptions {
+            working_dir: Some(dir.to_path_buf()),
+            ..PlanOptions::default()
+        };
+        let plan = crate::scanner::scan_repository(dir, "old_name", "new_name", &options).unwrap();
+        write_plan(&plan, &dir.join(".renamify/plan.json")).unwrap();
+        plan
+    }
+
+    #[test]
+    fn test_replan_reports_moved_and_removed_hunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.rs"), "let old_name = 1;\nOldName::new();\n").unwrap();
+        fs::write(root.join("b.rs"), "old_name();\n").unwrap();
+        let old_plan = plan_in(root);
+
+        // A line above shifts a.rs down, b.rs loses its match and c.rs gains one
+        fs::write(
+            root.join("a.rs"),
+            "// header\nlet old_name = 1;\nOldName::new();\n",
+        )
+        .unwrap();
+        fs::write(root.join("b.rs"), "other();\n").unwrap();
+        fs::write(root.join("c.rs"), "OLD_NAME\n").unwrap();
+
+        let result = replan_operation(None, vec![], None, false, Some(root)).unwrap();
+
+        assert_eq!(result.old_plan_id, old_plan.id);
+        assert_eq!(result.moved_matches.len(), 2);
+        assert!(result
+            .moved_matches
+            .iter()
+            .all(|moved| moved.after.line == moved.before.line + 1));
+        assert_eq!(result.diff.removed_matches.len(), 1);
+        assert!(result.diff.removed_matches[0].file.ends_with("b.rs"));
+        assert_eq!(result.diff.added_matches.len(), 1);
+        assert_eq!(result.diff.added_matches[0].content, "OLD_NAME");
+        assert_eq!(result.diff.unchanged_matches, 0);
+
+        // The refreshed plan replaces the pending one
+        let written =
+            crate::operations::apply::read_plan_file(&root.join(".renamify/plan.json")).unwrap();
+        assert_eq!(written.id, result.plan_id);
+        assert_eq!(written.stats.total_matches, 3);
+    }
+
+    #[test]
+    fn test_replan_keeps_recorded_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.rs"), "old_name OldName\nold_name // keep\n").unwrap();
+        fs::write(root.join("b.md"), "old_name\n").unwrap();
+        let options = PlanOptions {
+            includes: vec!["*.rs".to_string()],
+            styles: Some(vec![crate::Style::Snake]),
+            exclude_matching_lines: Some("// keep".to_string()),
+            working_dir: Some(root.to_path_buf()),
+            ..PlanOptions::default()
+        };
+        let plan = crate::scanner::scan_repository(root, "old_name", "new_name", &options).unwrap();
+        write_plan(&plan, &root.join("old.json")).unwrap();
+
+        let result = replan_operation(Some("old.json"), vec![], None, true, Some(root)).unwrap();
+
+        assert!(result.plan_path.is_none());
+        assert!(!root.join(".renamify/plan.json").exists());
+        assert!(result.diff.is_identical());
+        assert!(result.moved_matches.is_empty());
+        assert_eq!(result.diff.unchanged_matches, 1);
+    }
+
+    #[test]
+    fn test_replan_casing_does_not_outlive_it() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.rs"), "istanbul_city\nISTANBUL\n").unwrap();
+        let options = PlanOptions {
+            includes: vec!["*.rs".to_string()],
+            case_mapping: crate::case_model::CaseMapping::Turkish,
+            working_dir: Some(root.to_path_buf()),
+            ..PlanOptions::default()
+        };
+        // Turkish uppercases i to İ, so ISTANBUL is left alone
+        let plan = crate::scanner::scan_repository(root, "istanbul", "ankara", &options).unwrap();
+        assert_eq!(plan.stats.total_matches, 1);
+        write_plan(&plan, &root.join("old.json")).unwrap();
+
+        let result = replan_operation(Some("old.json"), vec![], None, true, Some(root)).unwrap();
+        assert!(result.diff.is_identical());
+
+        // Later plans on this thread use their own case mapping again
+        let options = PlanOptions {
+            includes: vec!["*.rs".to_string()],
+            ..PlanOptions::default()
+        };
+        let plan = crate::scanner::scan_repository(root, "istanbul", "ankara", &options).unwrap();
+        assert_eq!(plan.stats.total_matches, 2);
+    }
+}
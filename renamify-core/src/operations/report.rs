@@ -0,0 +1,146 @@
+use crate::operations::apply::read_plan_file;
+use crate::output::{UsageByMonth, UsageReport};
+use crate::History;
+use anyhow::Result;
+use std::path::Path;
+
+/// Report operation - summarizes how renamify has been used in this
+/// repository, from the history and saved plans in `.renamify`. Nothing is
+/// sent anywhere.
+#[allow(clippy::cast_precision_loss)]
+pub fn report_operation(working_dir: Option<&Path>) -> Result<UsageReport> {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let renamify_dir = current_dir.join(".renamify");
+
+    let history = History::load(&renamify_dir)?;
+    // Oldest first
+    let mut entries = history.list_entries(None);
+    entries.reverse();
+
+    let mut report = UsageReport {
+        entries: entries.len(),
+        first_used: entries.first().map(|entry| entry.created_at.clone()),
+        last_used: entries.last().map(|entry| entry.created_at.clone()),
+        ..UsageReport::default()
+    };
+    let mut apply_files = 0;
+    let mut apply_renames = 0;
+    let mut plan_matches = Vec::new();
+    for entry in &entries {
+        let month = entry.created_at.get(..7).unwrap_or(&entry.created_at);
+        if !report.by_month.iter().any(|usage| usage.month == month) {
+            report.by_month.push(UsageByMonth {
+                month: month.to_string(),
+                ..UsageByMonth::default()
+            });
+        }
+        let usage = report
+            .by_month
+            .iter_mut()
+            .find(|usage| usage.month == month)
+            .expect("added above");
+        usage.files_touched += entry.affected_files.len();
+        usage.renames += entry.renames.len();
+        report.files_touched += entry.affected_files.len();
+        report.renames += entry.renames.len();
+
+        if entry.revert_of.is_some() {
+            usage.undos += 1;
+            report.undos += 1;
+        } else if entry.redo_of.is_some() {
+            usage.redos += 1;
+            report.redos += 1;
+        } else {
+            usage.applies += 1;
+            report.applies += 1;
+            apply_files += entry.affected_files.len();
+            apply_renames += entry.renames.len();
+            // Saved plans are pruned with the history, so older ones may be gone
+            let plan_path = renamify_dir
+                .join("plans")
+                .join(format!("{}.json", entry.id));
+            if let Ok(plan) = read_plan_file(&plan_path) {
+                plan_matches.push(plan.stats.total_matches);
+            }
+        }
+    }
+
+    if report.applies > 0 {
+        let applies = report.applies as f64;
+        report.undo_rate = report.undos as f64 / applies;
+        report.average_files_per_apply = apply_files as f64 / applies;
+        report.average_renames_per_apply = apply_renames as f64 / applies;
+    }
+    report.plans_measured = plan_matches.len();
+    if !plan_matches.is_empty() {
+        report.average_matches_per_plan =
+            Some(plan_matches.iter().sum::<usize>() as f64 / plan_matches.len() as f64);
+    }
+    report.by_month.sort_by(|a, b| a.month.cmp(&b.month));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryEntry;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn entry(id: &str, created_at: &str, files: usize, revert_of: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            created_at: created_at.to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: Vec::new(),
+            includes: Vec::new(),
+            excludes: Vec::new(),
+            affected_files: (0..files)
+                .map(|index| (PathBuf::from(format!("{index}.rs")), String::new()))
+                .collect::<HashMap<_, _>>(),
+            renames: vec![(PathBuf::from("old.rs"), PathBuf::from("new.rs"))],
+            backups_path: PathBuf::new(),
+            revert_of: revert_of.map(String::from),
+            redo_of: None,
+        }
+    }
+
+    #[test]
+    fn test_report_operation() {
+        let dir = TempDir::new().unwrap();
+        let renamify_dir = dir.path().join(".renamify");
+        std::fs::create_dir_all(&renamify_dir).unwrap();
+
+        let report = report_operation(Some(dir.path())).unwrap();
+        assert_eq!(report.entries, 0);
+        assert!(report.average_matches_per_plan.is_none());
+
+        let mut history = History::load(&renamify_dir).unwrap();
+        history
+            .add_entry(entry("a", "2026-01-05T10:00:00Z", 4, None))
+            .unwrap();
+        history
+            .add_entry(entry("b", "2026-01-06T10:00:00Z", 4, Some("a")))
+            .unwrap();
+        history
+            .add_entry(entry("c", "2026-02-01T10:00:00Z", 2, None))
+            .unwrap();
+
+        let report = report_operation(Some(dir.path())).unwrap();
+        assert_eq!(report.entries, 3);
+        assert_eq!(report.applies, 2);
+        assert_eq!(report.undos, 1);
+        assert!((report.undo_rate - 0.5).abs() < f64::EPSILON);
+        assert!((report.average_files_per_apply - 3.0).abs() < f64::EPSILON);
+        assert_eq!(report.files_touched, 10);
+        assert_eq!(report.first_used.as_deref(), Some("2026-01-05T10:00:00Z"));
+        let months: Vec<(&str, usize, usize)> = report
+            .by_month
+            .iter()
+            .map(|usage| (usage.month.as_str(), usage.applies, usage.undos))
+            .collect();
+        assert_eq!(months, [("2026-01", 1, 1), ("2026-02", 1, 0)]);
+    }
+}
@@ -0,0 +1,599 @@
+//! A long-running JSON-RPC 2.0 service for GUIs and editors.
+//!
+//! Instead of starting the CLI for every command and parsing its output, a
+//! client keeps one `renamify serve --stdio` process open and sends it
+//! requests, one JSON object per line. Responses are written one per line in
+//! the order the requests arrived. Clients that subscribe to `progress` also
+//! get notifications while files are scanned and edited.
+//!
+//! Operation errors carry the CLI exit code as their error code and the
+//! `ErrorKind` name in `data.kind`, so clients can branch on them the same way
+//! scripts branch on exit codes.
+
+use crate::acronym::AcronymRendering;
+use crate::case_model::{CaseMapping, Style};
+use crate::error::error_kind;
+use crate::observer::OperationObserver;
+use crate::operations::{apply_operation, plan_operation, undo_operation};
+use crate::preview::{render_plan_named, renderer, GroupBy, Preview, RenderOptions};
+use crate::scanner::{CoercionMode, LiteralMode, MatchAnchor, MatchHunk, Plan, WalkOptions};
+use crate::{BackupStrategy, Durability, PlanTrust, StageMode, TransactionScope};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Version of the protocol spoken by `serve`. It only changes for breaking
+/// changes; new methods and new optional fields are added without a bump.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+const METHODS: &[&str] = &[
+    "initialize",
+    "plan",
+    "preview",
+    "apply",
+    "undo",
+    "subscribe",
+    "shutdown",
+];
+
+// Error codes defined by JSON-RPC 2.0
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(err: anyhow::Error) -> Self {
+        let kind = error_kind(&err);
+        Self {
+            code: i64::from(kind.exit_code()),
+            message: format!("{err:#}"),
+            data: Some(json!({ "kind": kind.as_str() })),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct InitializeParams {
+    protocol_version: Option<u32>,
+}
+
+impl Default for InitializeParams {
+    fn default() -> Self {
+        Self {
+            protocol_version: Some(PROTOCOL_VERSION),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+struct PlanParams {
+    search: String,
+    replace: String,
+    paths: Vec<PathBuf>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    exclude_styles: Vec<Style>,
+    include_styles: Vec<Style>,
+    only_styles: Vec<Style>,
+    exclude_match: Vec<String>,
+    unrestricted: u8,
+    rename_files: bool,
+    rename_dirs: bool,
+    ignore_ambiguous: bool,
+    swap: bool,
+    dry_run: bool,
+    /// Overrides the configured `acronym_rendering` for this plan
+    acronym_rendering: Option<AcronymRendering>,
+    /// Overrides the configured `case_mapping` for this plan
+    case_mapping: Option<CaseMapping>,
+    /// Preview format to render along with the result
+    preview: Option<String>,
+}
+
+impl Default for PlanParams {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            replace: String::new(),
+            paths: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            exclude_styles: Vec::new(),
+            include_styles: Vec::new(),
+            only_styles: Vec::new(),
+            exclude_match: Vec::new(),
+            unrestricted: 0,
+            rename_files: true,
+            rename_dirs: true,
+            ignore_ambiguous: false,
+            swap: false,
+            dry_run: false,
+            acronym_rendering: None,
+            case_mapping: None,
+            preview: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PreviewParams {
+    format: String,
+    /// Plan file to preview, instead of `.renamify/plan.json`
+    plan: Option<PathBuf>,
+}
+
+impl Default for PreviewParams {
+    fn default() -> Self {
+        Self {
+            format: "diff".to_string(),
+            plan: None,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ApplyParams {
+    plan_id: Option<String>,
+    force: bool,
+    strict: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct UndoParams {
+    id: String,
+    /// Undo even when files changed since the apply
+    force_undo: bool,
+}
+
+impl Default for UndoParams {
+    fn default() -> Self {
+        Self {
+            id: "latest".to_string(),
+            force_undo: false,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SubscribeParams {
+    events: Vec<String>,
+}
+
+/// One client connection: where responses go and what it subscribed to
+struct Session<W: Write + Send> {
+    output: Mutex<W>,
+    working_dir: PathBuf,
+    durability: Durability,
+    backup: BackupStrategy,
+    progress: AtomicBool,
+}
+
+impl<W: Write + Send> Session<W> {
+    fn send(&self, message: &Value) -> std::io::Result<()> {
+        let mut output = self.output.lock().unwrap();
+        serde_json::to_writer(&mut *output, message)?;
+        output.write_all(b"\n")?;
+        output.flush()
+    }
+
+    fn notify_progress(&self, operation: &str, path: &Path, done: usize, total: usize) {
+        // Around a hundred notifications per operation, however many files
+        let step = (total / 100).max(1);
+        if !self.progress.load(Ordering::Relaxed) || (!done.is_multiple_of(step) && done != total) {
+            return;
+        }
+        let _ = self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "progress",
+            "params": {
+                "operation": operation,
+                "path": path,
+                "done": done,
+                "total": total,
+            },
+        }));
+    }
+
+    fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => {
+                let params: InitializeParams = parse_params(params)?;
+                if params.protocol_version != Some(PROTOCOL_VERSION) {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!(
+                            "Unsupported protocol version, this server speaks {PROTOCOL_VERSION}"
+                        ),
+                    ));
+                }
+                Ok(json!({
+                    "protocol_version": PROTOCOL_VERSION,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "methods": METHODS,
+                }))
+            },
+            "plan" => self.plan(parse_params(params)?),
+            "preview" => self.preview(parse_params(params)?),
+            "apply" => {
+                let params: ApplyParams = parse_params(params)?;
+                let result = apply_operation(
+                    None,
+                    params.plan_id.as_deref(),
+                    &PlanTrust::default(),
+                    None,
+                    StageMode::default(),
+                    params.force,
+                    params.strict,
+                    false,
+                    TransactionScope::default(),
+                    self.durability,
+                    self.backup,
+                    Some(&self.working_dir),
+                    Some(self),
+                )?;
+                Ok(serde_json::to_value(result).map_err(anyhow::Error::from)?)
+            },
+            "undo" => {
+                let params: UndoParams = parse_params(params)?;
+                let result =
+                    undo_operation(&params.id, params.force_undo, Some(&self.working_dir))?;
+                Ok(serde_json::to_value(result).map_err(anyhow::Error::from)?)
+            },
+            "subscribe" => {
+                let params: SubscribeParams = parse_params(params)?;
+                if let Some(event) = params.events.iter().find(|event| *event != "progress") {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("Unknown event '{event}', expected progress"),
+                    ));
+                }
+                let progress = params.events.iter().any(|event| event == "progress");
+                self.progress.store(progress, Ordering::Relaxed);
+                Ok(json!({ "events": params.events }))
+            },
+            "shutdown" => Ok(Value::Null),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method '{method}'"),
+            )),
+        }
+    }
+
+    fn plan(&self, params: PlanParams) -> Result<Value, RpcError> {
+        if params.search.is_empty() {
+            return Err(RpcError::new(INVALID_PARAMS, "plan needs a search term"));
+        }
+        if let Some(format) = &params.preview {
+            parse_preview(format)?;
+        }
+
+        let (result, preview) = plan_operation(
+            &params.search,
+            &params.replace,
+            params.paths,
+            None,
+            params.include,
+            params.exclude,
+            true,
+            params.unrestricted,
+            params.rename_files,
+            params.rename_dirs,
+            &params.exclude_styles,
+            &params.include_styles,
+            &params.only_styles,
+            params.exclude_match,
+            None,
+            vec![],
+            None,
+            params.preview.as_ref(),
+            params.dry_run,
+            false,
+            GroupBy::default(),
+            None,
+            false,
+            false,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            params.acronym_rendering,
+            params.case_mapping,
+            true,
+            params.ignore_ambiguous,
+            false,
+            LiteralMode::default(),
+            MatchAnchor::default(),
+            CoercionMode::default(),
+            None,
+            None,
+            false,
+            params.swap,
+            0,
+            false,
+            WalkOptions::default(),
+            false,
+            false,
+            Some(&self.working_dir),
+            None,
+            None,
+            Some(self),
+        )?;
+
+        let mut value = serde_json::to_value(result).map_err(anyhow::Error::from)?;
+        if let Some(preview) = preview {
+            value["preview"] = Value::String(preview);
+        }
+        Ok(value)
+    }
+
+    fn preview(&self, params: PreviewParams) -> Result<Value, RpcError> {
+        parse_preview(&params.format)?;
+        let path = self.working_dir.join(
+            params
+                .plan
+                .unwrap_or_else(|| PathBuf::from(".renamify").join("plan.json")),
+        );
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            anyhow::Error::from(crate::error::RenamifyError::NotFound(format!(
+                "Failed to read plan {}: {e}",
+                path.display()
+            )))
+        })?;
+        let plan: Plan = serde_json::from_str(&content).map_err(anyhow::Error::from)?;
+        Ok(json!({
+            "plan_id": plan.id,
+            "preview": render_plan_named(&plan, &params.format, &RenderOptions {
+                base_dir: Some(&self.working_dir),
+                ..RenderOptions::default()
+            })?,
+        }))
+    }
+}
+
+impl<W: Write + Send> OperationObserver for Session<W> {
+    fn on_file_scanned(&self, path: &Path, scanned: usize, total: usize) {
+        self.notify_progress("scan", path, scanned, total);
+    }
+
+    fn on_hunk_applied(&self, hunk: &MatchHunk, applied: usize, total: usize) {
+        self.notify_progress("apply", &hunk.file, applied, total);
+    }
+}
+
+fn parse_params<T: DeserializeOwned + Default>(params: Value) -> Result<T, RpcError> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Check `format` names a registered preview renderer, or `none`
+fn parse_preview(format: &str) -> Result<(), RpcError> {
+    if format.eq_ignore_ascii_case(Preview::None.name()) || renderer(format).is_some() {
+        Ok(())
+    } else {
+        Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("Invalid preview format: {format}"),
+        ))
+    }
+}
+
+fn response(id: &Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => {
+            let mut body = json!({ "code": error.code, "message": error.message });
+            if let Some(data) = error.data {
+                body["data"] = data;
+            }
+            json!({ "jsonrpc": "2.0", "id": id, "error": body })
+        },
+    }
+}
+
+/// Answer requests read from `input` on `output` until `shutdown` or the end
+/// of the input.
+///
+/// Paths in requests are relative to `working_dir`. Applies sync files as
+/// `durability` says and keep backups as `backup` says.
+pub fn serve_operation<R: BufRead, W: Write + Send>(
+    input: R,
+    output: W,
+    durability: Durability,
+    backup: BackupStrategy,
+    working_dir: Option<&Path>,
+) -> Result<()> {
+    let working_dir = match working_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let session = Session {
+        output: Mutex::new(output),
+        working_dir,
+        durability,
+        backup,
+        progress: AtomicBool::new(false),
+    };
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = RpcError::new(PARSE_ERROR, e.to_string());
+                session.send(&response(&Value::Null, Err(error)))?;
+                continue;
+            },
+        };
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        let request = match serde_json::from_value::<Request>(message) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => {
+                let error = RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"");
+                session.send(&response(&id, Err(error)))?;
+                continue;
+            },
+            Err(e) => {
+                let error = RpcError::new(INVALID_REQUEST, e.to_string());
+                session.send(&response(&id, Err(error)))?;
+                continue;
+            },
+        };
+
+        let result = session.handle(&request.method, request.params);
+        // Requests without an id are notifications and get no response
+        if let Some(id) = request.id {
+            session.send(&response(&id, result))?;
+        }
+        if request.method == "shutdown" {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn run(dir: &Path, requests: &[Value]) -> Vec<Value> {
+        let input = requests
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut output = Vec::new();
+        serve_operation(
+            input.as_bytes(),
+            &mut output,
+            Durability::default(),
+            BackupStrategy::default(),
+            Some(dir),
+        )
+        .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_serve_session() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("main.rs"), "fn old_name() {}\n").unwrap();
+
+        let responses = run(
+            temp_dir.path(),
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocol_version": 1}}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "subscribe", "params": {"events": ["progress"]}}),
+                json!({"jsonrpc": "2.0", "id": 3, "method": "plan", "params": {"search": "old_name", "replace": "new_name"}}),
+                json!({"jsonrpc": "2.0", "id": 4, "method": "preview", "params": {"format": "summary"}}),
+                json!({"jsonrpc": "2.0", "id": 5, "method": "apply"}),
+                json!({"jsonrpc": "2.0", "id": 6, "method": "undo"}),
+                json!({"jsonrpc": "2.0", "id": 7, "method": "shutdown"}),
+                json!({"jsonrpc": "2.0", "id": 8, "method": "plan"}),
+            ],
+        );
+
+        let (notifications, responses): (Vec<Value>, Vec<Value>) =
+            responses.into_iter().partition(|r| r.get("id").is_none());
+        assert!(notifications
+            .iter()
+            .all(|n| n["method"] == "progress" && n["params"]["done"].is_number()));
+        assert!(!notifications.is_empty());
+
+        let ids: Vec<i64> = responses
+            .iter()
+            .map(|r| r["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(responses[0]["result"]["protocol_version"], PROTOCOL_VERSION);
+        assert_eq!(responses[2]["result"]["total_matches"], 1);
+        assert!(responses[3]["result"]["preview"].is_string());
+        assert_eq!(responses[4]["result"]["replacements"], 1);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("main.rs")).unwrap(),
+            "fn old_name() {}\n"
+        );
+        assert!(responses[5]["result"]["files_restored"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_serve_errors() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let responses = run(
+            temp_dir.path(),
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocol_version": 99}}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "rename"}),
+                json!({"jsonrpc": "2.0", "id": 3, "method": "plan", "params": {"serach": "x"}}),
+                json!({"jsonrpc": "2.0", "id": 4, "method": "undo", "params": {"id": "missing"}}),
+                json!({"jsonrpc": "2.0", "method": "subscribe", "params": {"events": []}}),
+                json!({"id": 5, "method": "shutdown"}),
+            ],
+        );
+
+        let codes: Vec<i64> = responses
+            .iter()
+            .map(|r| r["error"]["code"].as_i64().unwrap())
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                INVALID_PARAMS,
+                METHOD_NOT_FOUND,
+                INVALID_PARAMS,
+                2,
+                INVALID_REQUEST
+            ]
+        );
+        assert_eq!(responses[3]["error"]["data"]["kind"], "not_found");
+    }
+}
@@ -1,8 +1,10 @@
-use crate::output::{PendingPlan, StatusResult};
+use crate::output::{PendingPlan, PlanDrift, StatusResult};
+use crate::scanner::{MatchHunk, Plan, Rename, RenameKind};
 use crate::History;
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Status operation - returns structured data
 pub fn status_operation(working_dir: Option<&Path>) -> Result<StatusResult> {
@@ -13,11 +15,12 @@ pub fn status_operation(working_dir: Option<&Path>) -> Result<StatusResult> {
     let plan_path = renamify_dir.join("plan.json");
     let pending_plan = if plan_path.exists() {
         let content = fs::read_to_string(&plan_path)?;
-        let plan: crate::scanner::Plan = serde_json::from_str(&content)?;
+        let plan: Plan = serde_json::from_str(&content)?;
         Some(PendingPlan {
-            id: plan.id.clone(),
-            search: plan.search.clone(),
-            replace: plan.replace.clone(),
+            hunks: plan_drift(&plan, current_dir),
+            id: plan.id,
+            search: plan.search,
+            replace: plan.replace,
             created_at: plan.created_at,
         })
     } else {
@@ -40,3 +43,86 @@ pub fn status_operation(working_dir: Option<&Path>) -> Result<StatusResult> {
         last_operation,
     })
 }
+
+/// Compare the plan's hunks against the files as they are now
+#[allow(clippy::cast_possible_wrap)]
+fn plan_drift(plan: &Plan, current_dir: &Path) -> PlanDrift {
+    let mut hunks_by_file: BTreeMap<&Path, Vec<&MatchHunk>> = BTreeMap::new();
+    for hunk in &plan.matches {
+        hunks_by_file.entry(&hunk.file).or_default().push(hunk);
+    }
+
+    let root = current_dir
+        .canonicalize()
+        .unwrap_or_else(|_| current_dir.to_path_buf());
+    let display = |file: &Path| file.strip_prefix(&root).unwrap_or(file).to_path_buf();
+
+    let mut drift = PlanDrift::default();
+    for (file, mut hunks) in hunks_by_file {
+        let content = current_location(current_dir, file, &plan.paths)
+            .map(|path| resolve(current_dir, &path))
+            .and_then(|path| fs::read_to_string(path).ok());
+        let Some(content) = content else {
+            drift.drifted += hunks.len();
+            drift.drifted_files.push(display(file));
+            continue;
+        };
+
+        // Walk the hunks in order, tracking how far earlier applied hunks
+        // have moved the later ones
+        hunks.sort_by_key(|hunk| hunk.start);
+        let mut shift: isize = 0;
+        let mut drifted = false;
+        for hunk in hunks {
+            let start = hunk.start.checked_add_signed(shift);
+            let text_at = |len: usize| start.and_then(|start| content.get(start..start + len));
+            if text_at(hunk.content.len()) == Some(hunk.content.as_str()) {
+                drift.applicable += 1;
+            } else if !hunk.replace.is_empty()
+                && text_at(hunk.replace.len()) == Some(hunk.replace.as_str())
+            {
+                drift.applied += 1;
+                shift += hunk.replace.len() as isize - hunk.content.len() as isize;
+            } else {
+                drift.drifted += 1;
+                drifted = true;
+            }
+        }
+        if drifted {
+            drift.drifted_files.push(display(file));
+        }
+    }
+    drift
+}
+
+/// Where a planned file is now: its own path, or where the plan's renames
+/// moved it if they have already been applied
+fn current_location(current_dir: &Path, file: &Path, renames: &[Rename]) -> Option<PathBuf> {
+    if resolve(current_dir, file).is_file() {
+        return Some(file.to_path_buf());
+    }
+    let mut location = renames
+        .iter()
+        .find(|rename| rename.kind == RenameKind::File && rename.path == file)
+        .map_or_else(|| file.to_path_buf(), |rename| rename.new_path.clone());
+    // Directories are renamed deepest first, each move carrying the files below it
+    let mut dirs: Vec<&Rename> = renames
+        .iter()
+        .filter(|rename| rename.kind == RenameKind::Dir)
+        .collect();
+    dirs.sort_by_key(|rename| std::cmp::Reverse(rename.path.components().count()));
+    for dir in dirs {
+        if let Ok(rest) = location.strip_prefix(&dir.path) {
+            location = dir.new_path.join(rest);
+        }
+    }
+    (location != file).then_some(location)
+}
+
+fn resolve(current_dir: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        current_dir.join(path)
+    }
+}
@@ -1,11 +1,15 @@
+use crate::error::RenamifyError;
 use crate::id_resolver::{resolve_id, OperationType};
 use crate::output::{RedoResult, UndoResult};
 use crate::{redo_renaming, undo_renaming, History};
 use anyhow::Result;
 use std::path::Path;
 
-/// Undo operation - returns structured data
-pub fn undo_operation(id: &str, working_dir: Option<&Path>) -> Result<UndoResult> {
+/// Undo operation - returns structured data. Files changed since the apply
+/// are only overwritten with `force`.
+pub fn undo_operation(id: &str, force: bool, working_dir: Option<&Path>) -> Result<UndoResult> {
+    crate::workspace::require("undo")?;
+
     let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
     let renamify_dir = current_dir.join(".renamify");
 
@@ -14,25 +18,28 @@ pub fn undo_operation(id: &str, working_dir: Option<&Path>) -> Result<UndoResult
 
     // Load history to get entry details before undoing
     let history = History::load(&renamify_dir)?;
-    let entry = history
-        .find_entry(&actual_id)
-        .ok_or_else(|| anyhow::anyhow!("History entry '{}' not found", actual_id))?;
+    let entry = history.find_entry(&actual_id).ok_or_else(|| {
+        RenamifyError::NotFound(format!("History entry '{}' not found", actual_id))
+    })?;
 
     let files_restored = entry.affected_files.len();
     let renames_reverted = entry.renames.len();
 
     // Perform the undo
-    undo_renaming(&actual_id, &renamify_dir)?;
+    let conflicts = undo_renaming(&actual_id, &renamify_dir, force)?;
 
     Ok(UndoResult {
         history_id: actual_id,
         files_restored,
         renames_reverted,
+        conflicts,
     })
 }
 
 /// Redo operation - returns structured data
 pub fn redo_operation(id: &str, working_dir: Option<&Path>) -> Result<RedoResult> {
+    crate::workspace::require("redo")?;
+
     let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
     let renamify_dir = current_dir.join(".renamify");
 
@@ -41,9 +48,9 @@ pub fn redo_operation(id: &str, working_dir: Option<&Path>) -> Result<RedoResult
 
     // Load history to get entry details before redoing
     let history = History::load(&renamify_dir)?;
-    let entry = history
-        .find_entry(&actual_id)
-        .ok_or_else(|| anyhow::anyhow!("History entry '{}' not found", actual_id))?;
+    let entry = history.find_entry(&actual_id).ok_or_else(|| {
+        RenamifyError::NotFound(format!("History entry '{}' not found", actual_id))
+    })?;
 
     let files_changed = entry.affected_files.len();
     let renames = entry.renames.len();
@@ -0,0 +1,255 @@
+use crate::error::RenamifyError;
+use crate::operations::rename::find_collisions;
+use crate::output::{PlanIssue, PlanIssueKind, PlanValidationResult};
+use crate::scanner::{MatchHunk, Plan, Rename};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Validate plan operation - checks that a plan file parses and still applies
+/// cleanly to the working tree, without changing anything
+pub fn validate_plan_operation(
+    plan_path: &Path,
+    working_dir: Option<&Path>,
+) -> Result<PlanValidationResult> {
+    let current_dir = working_dir.unwrap_or_else(|| Path::new("."));
+    let resolve = |path: &Path| -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            current_dir.join(path)
+        }
+    };
+
+    let resolved_plan_path = resolve(plan_path);
+    if !resolved_plan_path.exists() {
+        return Err(RenamifyError::NotFound(format!(
+            "Plan file {} not found",
+            plan_path.display()
+        ))
+        .into());
+    }
+    let content = std::fs::read_to_string(&resolved_plan_path)
+        .with_context(|| format!("Failed to read plan file {}", plan_path.display()))?;
+
+    let mut result = PlanValidationResult {
+        plan_path: plan_path.to_path_buf(),
+        plan_id: None,
+        matches_checked: 0,
+        renames_checked: 0,
+        issues: Vec::new(),
+        base_dir: current_dir.canonicalize().ok(),
+    };
+    let plan: Plan = match serde_json::from_str(&content) {
+        Ok(plan) => plan,
+        Err(e) => {
+            result.issues.push(PlanIssue {
+                kind: PlanIssueKind::Schema,
+                path: None,
+                line: None,
+                message: format!("Not a valid plan file: {e}"),
+            });
+            return Ok(result);
+        },
+    };
+
+    result.plan_id = Some(plan.id.clone());
+    result.matches_checked = plan.matches.len();
+    result.renames_checked = plan.paths.len();
+    result.issues = plan_issues(&plan, current_dir);
+    Ok(result)
+}
+
+/// Everything that would stop `plan` applying cleanly to the files under
+/// `current_dir`, which relative paths in it are resolved against
+pub(crate) fn plan_issues(plan: &Plan, current_dir: &Path) -> Vec<PlanIssue> {
+    let resolve = |path: &Path| -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            current_dir.join(path)
+        }
+    };
+    let mut issues = check_matches(&plan.matches, &resolve);
+    issues.extend(check_renames(&plan.paths, &resolve));
+    issues
+}
+
+/// Check that every hunk's file exists and still holds the matched text at the
+/// hunk's position, and that no two hunks in a file overlap
+fn check_matches(matches: &[MatchHunk], resolve: &dyn Fn(&Path) -> PathBuf) -> Vec<PlanIssue> {
+    let mut hunks_by_file: BTreeMap<&Path, Vec<&MatchHunk>> = BTreeMap::new();
+    for hunk in matches {
+        hunks_by_file.entry(&hunk.file).or_default().push(hunk);
+    }
+
+    let mut issues = Vec::new();
+    for (file, mut hunks) in hunks_by_file {
+        let issue = |kind, line, message| PlanIssue {
+            kind,
+            path: Some(file.to_path_buf()),
+            line,
+            message,
+        };
+        let path = resolve(file);
+        if !path.is_file() {
+            issues.push(issue(
+                PlanIssueKind::MissingPath,
+                None,
+                "File to edit does not exist".to_string(),
+            ));
+            continue;
+        }
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                issues.push(issue(
+                    PlanIssueKind::StaleMatch,
+                    None,
+                    format!("Failed to read file to edit: {e}"),
+                ));
+                continue;
+            },
+        };
+
+        hunks.sort_by_key(|hunk| (hunk.start, hunk.end));
+        let mut previous: Option<&MatchHunk> = None;
+        for hunk in hunks {
+            if hunk.start > hunk.end {
+                issues.push(issue(
+                    PlanIssueKind::Schema,
+                    Some(hunk.line),
+                    format!(
+                        "Match ends (byte {}) before it starts (byte {})",
+                        hunk.end, hunk.start
+                    ),
+                ));
+                continue;
+            }
+            match content.get(hunk.start..hunk.end) {
+                Some(found) if found == hunk.content => {},
+                Some(found) => issues.push(issue(
+                    PlanIssueKind::StaleMatch,
+                    Some(hunk.line),
+                    format!("Expected '{}', found '{found}'", hunk.content),
+                )),
+                None => issues.push(issue(
+                    PlanIssueKind::StaleMatch,
+                    Some(hunk.line),
+                    format!(
+                        "Expected '{}' at bytes {}..{}, past the end of the file or inside a character",
+                        hunk.content, hunk.start, hunk.end
+                    ),
+                )),
+            }
+            if let Some(previous) = previous.filter(|previous| hunk.start < previous.end) {
+                issues.push(issue(
+                    PlanIssueKind::OverlappingMatch,
+                    Some(hunk.line),
+                    format!(
+                        "'{}' overlaps '{}' on line {}",
+                        hunk.content, previous.content, previous.line
+                    ),
+                ));
+            }
+            previous = Some(hunk);
+        }
+    }
+    issues
+}
+
+/// Check that every rename's source exists and that no destination collides
+/// with an existing path or with another rename
+fn check_renames(renames: &[Rename], resolve: &dyn Fn(&Path) -> PathBuf) -> Vec<PlanIssue> {
+    let mut issues = Vec::new();
+    for rename in renames {
+        if std::fs::symlink_metadata(resolve(&rename.path)).is_err() {
+            issues.push(PlanIssue {
+                kind: PlanIssueKind::MissingPath,
+                path: Some(rename.path.clone()),
+                line: None,
+                message: format!(
+                    "Path to rename to {} does not exist",
+                    rename.new_path.display()
+                ),
+            });
+        }
+    }
+
+    let resolved: Vec<Rename> = renames
+        .iter()
+        .map(|rename| Rename {
+            path: resolve(&rename.path),
+            new_path: resolve(&rename.new_path),
+            ..rename.clone()
+        })
+        .collect();
+    issues.extend(
+        find_collisions(&resolved)
+            .into_iter()
+            .map(|collision| PlanIssue {
+                kind: PlanIssueKind::RenameConflict,
+                path: Some(collision.path),
+                line: None,
+                message: format!(
+                    "Renaming to {}: {}",
+                    collision.new_path.display(),
+                    collision.reason
+                ),
+            }),
+    );
+    if let Err(e) = crate::apply::order_renames(renames) {
+        issues.push(PlanIssue {
+            kind: PlanIssueKind::RenameConflict,
+            path: None,
+            line: None,
+            message: e.to_string(),
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_repository, write_plan, PlanOptions};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_plan() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("old_name.rs"), "old_name();\nold_name();\n").unwrap();
+        std::fs::write(root.join("lib.rs"), "old_name();\n").unwrap();
+        let plan = scan_repository(&root, "old_name", "new_name", &PlanOptions::default()).unwrap();
+        write_plan(&plan, &root.join("plan.json")).unwrap();
+
+        let result = validate_plan_operation(Path::new("plan.json"), Some(&root)).unwrap();
+        assert!(result.is_valid(), "{:?}", result.issues);
+        assert_eq!(result.matches_checked, 3);
+        assert_eq!(result.renames_checked, 1);
+
+        // Edit a matched line, delete an edited file and occupy the rename target
+        std::fs::write(root.join("old_name.rs"), "renamed();\nold_name();\n").unwrap();
+        std::fs::remove_file(root.join("lib.rs")).unwrap();
+        std::fs::write(root.join("new_name.rs"), "").unwrap();
+        let result = validate_plan_operation(&root.join("plan.json"), None).unwrap();
+        let kinds: Vec<PlanIssueKind> = result.issues.iter().map(|issue| issue.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                PlanIssueKind::MissingPath,
+                PlanIssueKind::StaleMatch,
+                PlanIssueKind::StaleMatch,
+                PlanIssueKind::RenameConflict,
+            ]
+        );
+
+        std::fs::write(root.join("plan.json"), "{\"id\": 1}").unwrap();
+        let result = validate_plan_operation(&root.join("plan.json"), None).unwrap();
+        assert_eq!(result.issues[0].kind, PlanIssueKind::Schema);
+        assert!(result.plan_id.is_none());
+
+        assert!(validate_plan_operation(&root.join("missing.json"), None).is_err());
+    }
+}
@@ -1,6 +1,10 @@
+use crate::undo::{LocalChange, UndoConflict};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write;
+use ts_rs::TS;
 
 /// Output format for CLI commands
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,6 +25,35 @@ pub struct PlanResult {
     pub dry_run: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plan: Option<crate::scanner::Plan>,
+    /// The plan's warnings, plus any about the plan file itself
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<crate::warnings::PlanWarning>,
+    /// Tracked files that are not checked out, when any are under the scanned paths
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse_checkout: Option<crate::sparse::SparseReport>,
+    /// Plan files written with `--split-by-codeowners`, one per set of owners
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owner_plans: Vec<OwnerPlan>,
+    /// Where the files and matches went, when the plan is empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub empty_plan: Option<crate::empty_plan::EmptyPlanDiagnosis>,
+    /// Whether this is the result of `search`, where a non-empty `replace`
+    /// only shows what a rename would do
+    #[serde(default)]
+    pub search_only: bool,
+}
+
+/// The part of a plan owned by one set of CODEOWNERS owners
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct OwnerPlan {
+    /// Empty for files no rule assigns an owner
+    pub owners: Vec<String>,
+    pub plan_id: String,
+    pub plan_path: std::path::PathBuf,
+    pub files_with_matches: usize,
+    pub total_matches: usize,
+    pub renames: usize,
 }
 
 /// Result of an apply operation
@@ -31,6 +64,158 @@ pub struct ApplyResult {
     pub replacements: usize,
     pub renames: usize,
     pub committed: bool,
+    /// Whether the changes were staged in git
+    #[serde(default)]
+    pub staged: bool,
+    /// Whether the changes were written to the git index only, leaving the
+    /// working tree and history unchanged
+    #[serde(default)]
+    pub index_only: bool,
+    /// Per-directory transaction IDs, in apply order (empty for a single transaction)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<String>,
+    /// Files that had changed since the applied bundle was exported, whose
+    /// edits were moved to where their lines are now
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drifted: Vec<std::path::PathBuf>,
+    /// Files the plan could not read, so any matches in them were not changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unreadable: Vec<crate::scanner::UnreadableFile>,
+    /// Changed files and replacements by top-level directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_directory: Vec<ChangeGroup>,
+    /// Changed files and replacements by file extension
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_extension: Vec<ChangeGroup>,
+    /// Build metadata that still names renamed paths, with `--report-stale-paths`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale_paths: Vec<crate::stale_paths::StalePath>,
+}
+
+/// The changed files and replacements in one top-level directory, or with
+/// one file extension
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ChangeGroup {
+    /// `src/`, or `.` for files at the top level; `.rs`, or `(none)` for
+    /// files without an extension
+    pub name: String,
+    pub files: usize,
+    pub replacements: usize,
+}
+
+impl ChangeGroup {
+    /// Group `matches` by the directory under `base_dir` they are in, most
+    /// replacements first
+    pub fn by_directory(
+        matches: &[crate::scanner::MatchHunk],
+        base_dir: Option<&std::path::Path>,
+    ) -> Vec<Self> {
+        Self::group(matches, |file| {
+            let relative = base_dir
+                .and_then(|base_dir| file.strip_prefix(base_dir).ok())
+                .unwrap_or(file);
+            let mut components = relative.components();
+            match (components.next(), components.next()) {
+                (Some(first), Some(_)) => {
+                    format!("{}/", first.as_os_str().to_string_lossy())
+                },
+                _ => ".".to_string(),
+            }
+        })
+    }
+
+    /// Group `matches` by file extension, most replacements first
+    pub fn by_extension(matches: &[crate::scanner::MatchHunk]) -> Vec<Self> {
+        Self::group(matches, |file| {
+            file.extension().map_or_else(
+                || "(none)".to_string(),
+                |extension| format!(".{}", extension.to_string_lossy()),
+            )
+        })
+    }
+
+    fn group(
+        matches: &[crate::scanner::MatchHunk],
+        name_of: impl Fn(&std::path::Path) -> String,
+    ) -> Vec<Self> {
+        let mut groups: BTreeMap<String, (BTreeSet<&std::path::Path>, usize)> = BTreeMap::new();
+        for hunk in matches {
+            let (files, replacements) = groups.entry(name_of(&hunk.file)).or_default();
+            files.insert(&hunk.file);
+            *replacements += 1;
+        }
+        let mut groups: Vec<Self> = groups
+            .into_iter()
+            .map(|(name, (files, replacements))| Self {
+                name,
+                files: files.len(),
+                replacements,
+            })
+            .collect();
+        groups.sort_by(|a, b| b.replacements.cmp(&a.replacements));
+        groups
+    }
+}
+
+/// Write one breakdown of an apply summary, largest groups first
+fn write_change_groups(output: &mut String, heading: &str, groups: &[ChangeGroup]) {
+    const SHOWN: usize = 10;
+    if groups.len() < 2 {
+        return;
+    }
+    writeln!(output, "{heading}:").unwrap();
+    let width = groups
+        .iter()
+        .take(SHOWN)
+        .map(|group| group.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for group in groups.iter().take(SHOWN) {
+        writeln!(
+            output,
+            "  {:<width$}  {} in {}",
+            group.name,
+            count(group.replacements, "replacement"),
+            count(group.files, "file")
+        )
+        .unwrap();
+    }
+    if groups.len() > SHOWN {
+        writeln!(output, "  ... and {} more", groups.len() - SHOWN).unwrap();
+    }
+}
+
+/// `1 file`, `2 files`
+fn count(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Result of `apply --check`: what applying a plan would do, and anything that
+/// would stop it applying cleanly
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplyCheckResult {
+    pub plan_id: String,
+    pub files_changed: usize,
+    pub replacements: usize,
+    pub renames: usize,
+    pub issues: Vec<PlanIssue>,
+    /// The touched paths as they will be laid out, with `--show-tree`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree: Option<String>,
+    /// Directory the summary shows paths relative to
+    #[serde(skip)]
+    pub base_dir: Option<std::path::PathBuf>,
+}
+
+impl ApplyCheckResult {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
 }
 
 /// Result of an undo operation
@@ -39,6 +224,9 @@ pub struct UndoResult {
     pub history_id: String,
     pub files_restored: usize,
     pub renames_reverted: usize,
+    /// Files changed since the apply that `--force-undo` undid anyway
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<UndoConflict>,
 }
 
 /// Result of a redo operation
@@ -51,28 +239,62 @@ pub struct RedoResult {
 }
 
 /// Result of a status operation
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
 pub struct StatusResult {
     pub pending_plan: Option<PendingPlan>,
     pub history_count: usize,
     pub last_operation: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
 pub struct PendingPlan {
     pub id: String,
     pub search: String,
     pub replace: String,
     pub created_at: String,
+    /// How the plan's hunks compare to the working tree right now
+    #[serde(default)]
+    pub hunks: PlanDrift,
 }
 
-/// Result of a history operation
+/// Pending plan hunks counted by whether they still match the working tree
+#[derive(Debug, Default, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanDrift {
+    /// Hunks whose text is still where the plan expects it
+    pub applicable: usize,
+    /// Hunks whose text has changed, or whose file is gone
+    pub drifted: usize,
+    /// Hunks whose replacement is already in place
+    pub applied: usize,
+    /// Files with at least one drifted hunk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub drifted_files: Vec<std::path::PathBuf>,
+}
+
+/// Result of exporting a rename with `history export --bundle`
 #[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryExportResult {
+    pub history_id: String,
+    pub bundle: std::path::PathBuf,
+    /// Files with content edits
+    pub files: usize,
+    pub replacements: usize,
+    pub renames: usize,
+}
+
+/// Result of a history operation
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
 pub struct HistoryResult {
     pub entries: Vec<HistoryItem>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
 pub struct HistoryItem {
     pub id: String,
     pub operation: String,
@@ -97,126 +319,2741 @@ pub struct RenameResult {
     pub committed: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plan: Option<crate::scanner::Plan>,
+    /// True when nothing was applied (`--dry-run`)
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Safety checks apply would enforce; only computed for dry runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety_checks: Option<RenameSafetyChecks>,
+    /// History entry apply would record; only computed for dry runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_entry: Option<crate::history::HistoryEntry>,
+}
+
+/// Safety checks a rename runs before applying, with the outcome for the given flags
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct RenameSafetyChecks {
+    pub large_change: LargeChangeCheck,
+    /// Renames whose destination is already taken
+    pub collisions: Vec<RenameCollision>,
+    /// Renames of a search root itself, only applied with `--rename-root`
+    pub root_renames: Vec<crate::scanner::Rename>,
+    pub root_renames_applied: bool,
+    /// True when apply would go ahead with the given flags
+    pub passed: bool,
+}
+
+/// Size guard that requires `--large` for big changes
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct LargeChangeCheck {
+    pub files: usize,
+    pub renames: usize,
+    pub max_files: usize,
+    pub max_renames: usize,
+    pub exceeded: bool,
+    /// True when `--large` was passed
+    pub acknowledged: bool,
+}
+
+/// A rename whose destination already exists or is shared with another rename
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct RenameCollision {
+    pub path: std::path::PathBuf,
+    pub new_path: std::path::PathBuf,
+    pub reason: String,
 }
 
 /// Result of a version command
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
 pub struct VersionResult {
     pub name: String,
     pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub build: Option<BuildInfo>,
 }
 
-/// Trait for formatting output in different formats
-pub trait OutputFormatter {
-    fn format(&self, format: OutputFormat) -> String;
-    fn format_json(&self) -> String;
-    fn format_summary(&self) -> String;
+/// Build metadata embedded at compile time, for bug reports and package pinning
+#[derive(Debug, Default, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct BuildInfo {
+    /// Short git commit hash, or None when built outside a git checkout
+    pub git_commit: Option<String>,
+    /// Build date (YYYY-MM-DD), honoring `SOURCE_DATE_EPOCH`
+    pub build_date: Option<String>,
+    pub rustc_version: Option<String>,
+    /// Target triple, e.g. x86_64-unknown-linux-gnu
+    pub target: Option<String>,
+    pub features: Vec<String>,
 }
 
-impl OutputFormatter for PlanResult {
-    fn format(&self, format: OutputFormat) -> String {
-        match format {
-            OutputFormat::Json => self.format_json(),
-            OutputFormat::Summary => self.format_summary(),
+/// Result of a check operation (forbidden identifier policy)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub forbidden: Vec<String>,
+    pub files_scanned: usize,
+    pub violations: Vec<CheckViolation>,
+}
+
+/// A single occurrence of a forbidden identifier (content match or path name)
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct CheckViolation {
+    pub term: String,
+    pub file: std::path::PathBuf,
+    /// Line number, or None when the file or directory name itself matches
+    #[ts(type = "number | null")]
+    pub line: Option<u64>,
+    /// 1-based character column, or None for path matches
+    pub column: Option<u32>,
+    pub content: String,
+}
+
+/// Result of comparing two plans
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanDiffResult {
+    pub old_plan: std::path::PathBuf,
+    pub new_plan: std::path::PathBuf,
+    pub added_matches: Vec<crate::scanner::MatchHunk>,
+    pub removed_matches: Vec<crate::scanner::MatchHunk>,
+    pub changed_matches: Vec<ChangedMatch>,
+    pub unchanged_matches: usize,
+    pub added_renames: Vec<crate::scanner::Rename>,
+    pub removed_renames: Vec<crate::scanner::Rename>,
+    pub changed_renames: Vec<ChangedRename>,
+    pub unchanged_renames: usize,
+    /// Directory the summary shows paths relative to
+    #[serde(skip)]
+    pub base_dir: Option<std::path::PathBuf>,
+}
+
+/// A hunk at the same position in both plans with a different match or replacement
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ChangedMatch {
+    pub before: crate::scanner::MatchHunk,
+    pub after: crate::scanner::MatchHunk,
+}
+
+/// A path renamed by both plans, but to different destinations
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ChangedRename {
+    pub before: crate::scanner::Rename,
+    pub after: crate::scanner::Rename,
+}
+
+/// Result of regenerating a plan against the current working tree
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplanResult {
+    pub old_plan_id: String,
+    pub plan_id: String,
+    /// Where the new plan was written, unless this was a dry run
+    pub plan_path: Option<std::path::PathBuf>,
+    pub search: String,
+    pub replace: String,
+    /// Hunks with the same text in the same file, now at another position
+    pub moved_matches: Vec<ChangedMatch>,
+    /// Everything else that changed between the old and the new plan
+    pub diff: PlanDiffResult,
+}
+
+/// Result of checking a plan file against the working tree
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanValidationResult {
+    pub plan_path: std::path::PathBuf,
+    /// Absent when the file is not a valid plan
+    pub plan_id: Option<String>,
+    pub matches_checked: usize,
+    pub renames_checked: usize,
+    pub issues: Vec<PlanIssue>,
+    /// Directory the summary shows paths relative to
+    #[serde(skip)]
+    pub base_dir: Option<std::path::PathBuf>,
+}
+
+/// Something that would make applying a plan fail or do the wrong thing
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanIssue {
+    pub kind: PlanIssueKind,
+    /// File or path the issue is about, as written in the plan
+    pub path: Option<std::path::PathBuf>,
+    #[ts(type = "number | null")]
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanIssueKind {
+    /// The file is not a plan, or a hunk's positions make no sense
+    Schema,
+    /// A file to edit or a path to rename does not exist
+    MissingPath,
+    /// The file no longer has the matched text where the plan expects it
+    StaleMatch,
+    /// Two hunks edit overlapping text
+    OverlappingMatch,
+    /// A rename destination already exists or is shared with another rename
+    RenameConflict,
+}
+
+impl PlanIssueKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Schema => "schema",
+            Self::MissingPath => "missing_path",
+            Self::StaleMatch => "stale_match",
+            Self::OverlappingMatch => "overlapping_match",
+            Self::RenameConflict => "rename_conflict",
         }
     }
+}
 
-    fn format_json(&self) -> String {
-        serde_json::to_string(&json!({
-            "success": true,
-            "operation": if self.replace.is_empty() { "search" } else { "plan" },
-            "plan_id": self.plan_id,
-            "search": self.search,
-            "replace": self.replace,
-            "dry_run": self.dry_run,
-            "summary": {
-                "files_with_matches": self.files_with_matches,
-                "total_matches": self.total_matches,
-                "renames": self.renames,
-            },
-            "plan": self.plan,
-        }))
-        .unwrap_or_default()
+/// Local usage summary of one repository, built from its history
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct UsageReport {
+    /// History entries of every kind
+    pub entries: usize,
+    pub applies: usize,
+    pub undos: usize,
+    pub redos: usize,
+    /// Undos per apply
+    pub undo_rate: f64,
+    /// Files changed by every apply, undo and redo
+    pub files_touched: usize,
+    /// Paths renamed by every apply, undo and redo
+    pub renames: usize,
+    pub average_files_per_apply: f64,
+    pub average_renames_per_apply: f64,
+    /// Average matches of the applied plans still saved in `.renamify/plans`
+    pub average_matches_per_plan: Option<f64>,
+    /// How many saved plans the average was taken over
+    pub plans_measured: usize,
+    pub first_used: Option<String>,
+    pub last_used: Option<String>,
+    /// Oldest month first
+    pub by_month: Vec<UsageByMonth>,
+}
+
+/// What `gc` found, and removed unless it was a dry run
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GcResult {
+    pub dry_run: bool,
+    /// Paths are relative to the working directory
+    pub artifacts: Vec<GcArtifact>,
+    /// Total size of the artifacts
+    pub bytes: u64,
+}
+
+/// A file or directory left behind by a crashed or interrupted run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct GcArtifact {
+    pub path: std::path::PathBuf,
+    pub kind: GcArtifactKind,
+    #[ts(type = "number")]
+    pub bytes: u64,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS, JsonSchema,
+)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum GcArtifactKind {
+    /// A backup directory no history entry refers to
+    Backup,
+    /// A saved plan no history entry refers to
+    Plan,
+    /// An apply log no history entry refers to
+    Log,
+    /// A rename mapping no history entry refers to
+    Mapping,
+    /// A lock file whose process is no longer running
+    Lock,
+    /// A temporary file from an interrupted atomic write
+    TempFile,
+}
+
+impl std::fmt::Display for GcArtifactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Backup => "backup",
+            Self::Plan => "plan",
+            Self::Log => "log",
+            Self::Mapping => "mapping",
+            Self::Lock => "lock",
+            Self::TempFile => "temp file",
+        })
     }
+}
 
-    fn format_summary(&self) -> String {
-        let mut output = String::new();
+/// What `doctor` found out about the environment
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DoctorResult {
+    pub version: String,
+    /// Operating system and architecture, e.g. `linux x86_64`
+    pub platform: String,
+    pub checks: Vec<DoctorCheck>,
+}
 
-        if self.replace.is_empty() {
-            // Search mode
-            writeln!(output, "Search results for '{}'", self.search).unwrap();
-        } else {
-            // Plan mode
-            writeln!(output, "Renamify plan: {} -> {}", self.search, self.replace).unwrap();
-        }
+impl DoctorResult {
+    /// Whether no check failed; warnings don't count
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != DoctorStatus::Fail)
+    }
+}
 
-        writeln!(
-            output,
-            "Edits: {} files, {} replacements",
-            self.files_with_matches, self.total_matches
-        )
-        .unwrap();
+/// One environment check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct DoctorCheck {
+    /// Stable identifier, e.g. `git` or `long-paths`
+    pub name: String,
+    pub status: DoctorStatus,
+    /// What was found
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub hint: Option<String>,
+}
 
-        if self.renames > 0 {
-            writeln!(output, "Renames: {} items", self.renames).unwrap();
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorStatus {
+    Pass,
+    /// Renamify works, but some features won't
+    Warn,
+    /// Renamify can't work properly until this is fixed
+    Fail,
+    /// Not applicable here
+    Skip,
+}
 
-        if !self.dry_run {
-            writeln!(output, "Plan ID: {}", self.plan_id).unwrap();
+/// Usage in one calendar month
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct UsageByMonth {
+    /// As `YYYY-MM`
+    pub month: String,
+    pub applies: usize,
+    pub undos: usize,
+    pub redos: usize,
+    pub files_touched: usize,
+    pub renames: usize,
+}
+
+/// Result of merging several plans into one plan file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanMergeResult {
+    pub plan_id: String,
+    pub plan_path: std::path::PathBuf,
+    pub sources: Vec<std::path::PathBuf>,
+    pub total_matches: usize,
+    pub files_with_matches: usize,
+    pub renames: usize,
+    /// Hunks that appeared in more than one source plan
+    pub duplicate_matches: usize,
+    /// Renames that appeared in more than one source plan
+    pub duplicate_renames: usize,
+}
+
+/// Result of explaining how one identifier matches the search term
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExplainResult {
+    pub search: String,
+    /// Empty when explaining a search
+    pub replace: String,
+    pub explanation: crate::compound_matcher::Explanation,
+}
+
+/// Result of previewing a single buffer with `preview-file`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewFileResult {
+    /// Path the buffer was scanned as, or `<stdin>`
+    pub file: std::path::PathBuf,
+    pub search: String,
+    pub replace: String,
+    /// The buffer with every match replaced
+    pub content: String,
+    pub matches: Vec<crate::scanner::MatchHunk>,
+}
+
+/// Result of exporting the TypeScript types and JSON schemas
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTypesResult {
+    pub out_dir: std::path::PathBuf,
+    /// Renamify version the types were generated from
+    pub version: String,
+    /// Names of the exported types, each with a `.d.ts` and a JSON schema
+    pub types: Vec<String>,
+}
+
+/// Result of `config show`: the effective configuration after layering
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigShowResult {
+    pub config: crate::config::Config,
+    /// Origin of each value by dotted key, when `--origin` was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origins: Option<std::collections::BTreeMap<String, crate::config::ConfigOrigin>>,
+}
+
+/// Result of `config get`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigGetResult {
+    pub key: String,
+    pub value: toml::Value,
+    pub origin: crate::config::ConfigOrigin,
+}
+
+/// Result of `config set`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigSetResult {
+    pub key: String,
+    pub value: toml::Value,
+    pub scope: crate::config::ConfigScope,
+    /// Config file that was written
+    pub path: std::path::PathBuf,
+}
+
+impl PlanValidationResult {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl PlanDiffResult {
+    pub fn is_identical(&self) -> bool {
+        self.added_matches.is_empty()
+            && self.removed_matches.is_empty()
+            && self.changed_matches.is_empty()
+            && self.added_renames.is_empty()
+            && self.removed_renames.is_empty()
+            && self.changed_renames.is_empty()
+    }
+
+    fn rename_changes(&self) -> RenameChanges {
+        RenameChanges {
+            added: self.added_renames.clone(),
+            removed: self.removed_renames.clone(),
+            changed: self.changed_renames.clone(),
         }
+    }
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
 
+    /// Format violations as GitHub Actions error annotations
+    pub fn format_github(&self) -> String {
+        let mut output = String::new();
+        for violation in &self.violations {
+            let message = format!(
+                "Forbidden identifier '{}' (variant of '{}')",
+                violation.content, violation.term
+            );
+            let mut properties = format!(
+                "file={}",
+                escape_github_property(&violation.file.display().to_string())
+            );
+            if let (Some(line), Some(column)) = (violation.line, violation.column) {
+                write!(
+                    properties,
+                    ",line={},col={},endColumn={}",
+                    line,
+                    column,
+                    column as usize + violation.content.chars().count()
+                )
+                .unwrap();
+            }
+            writeln!(
+                output,
+                "::error {},title=renamify check::{}",
+                properties,
+                escape_github_data(&message)
+            )
+            .unwrap();
+        }
         output
     }
 }
 
-impl OutputFormatter for ApplyResult {
-    fn format(&self, format: OutputFormat) -> String {
-        match format {
-            OutputFormat::Json => self.format_json(),
-            OutputFormat::Summary => self.format_summary(),
+/// Result of a failed command, so `--output json` callers can branch on `kind`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResult {
+    pub kind: crate::error::ErrorKind,
+    pub message: String,
+    /// Which guard stopped the operation, for `guard` errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard: Option<GuardDetails>,
+    /// Where the files and matches went, for `no_matches` errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub empty_plan: Option<crate::empty_plan::EmptyPlanDiagnosis>,
+}
+
+/// The guard behind a `guard` error and the thresholds it found exceeded
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct GuardDetails {
+    pub name: String,
+    pub thresholds: Vec<crate::operations::plan::GuardThreshold>,
+}
+
+impl ErrorResult {
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        let guard_error = err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<crate::operations::plan::PlanGuardError>());
+        let guard = guard_error.map(|guard| GuardDetails {
+            name: guard.guard_name().to_string(),
+            thresholds: guard.exceeded_thresholds(),
+        });
+        let empty_plan = match guard_error {
+            Some(crate::operations::plan::PlanGuardError::NoMatches(diagnosis)) => {
+                Some((**diagnosis).clone())
+            },
+            _ => None,
+        };
+        Self {
+            kind: crate::error::error_kind(err),
+            message: format!("{err:#}"),
+            guard,
+            empty_plan,
         }
     }
 
-    fn format_json(&self) -> String {
-        serde_json::to_string(&json!({
-            "success": true,
-            "operation": "apply",
-            "plan_id": self.plan_id,
-            "summary": {
-                "files_changed": self.files_changed,
-                "replacements": self.replacements,
-                "renames": self.renames,
-            },
-            "committed": self.committed,
-        }))
-        .unwrap_or_default()
+    pub const fn exit_code(&self) -> i32 {
+        self.kind.exit_code()
     }
+}
 
-    fn format_summary(&self) -> String {
-        let mut output = format!("Changes applied successfully. Plan ID: {}\n", self.plan_id);
+/// What `plan` and `search` print with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanOutput {
+    pub success: bool,
+    /// `plan` or `search`
+    pub operation: String,
+    pub plan_id: String,
+    pub search: String,
+    /// Null when searching without a replacement
+    pub replace: Option<String>,
+    pub dry_run: bool,
+    pub summary: PlanSummary,
+    /// Without a replacement, matches have no `replace` or `line_after` and
+    /// paths no `new_path`
+    pub plan: Option<crate::scanner::Plan>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<crate::warnings::PlanWarning>>")]
+    pub warnings: Vec<crate::warnings::PlanWarning>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub sparse_checkout: Option<crate::sparse::SparseReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<OwnerPlan>>")]
+    pub owner_plans: Vec<OwnerPlan>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub empty_plan: Option<crate::empty_plan::EmptyPlanDiagnosis>,
+}
 
-        writeln!(
-            output,
-            "✓ Applied {} replacements across {} files",
-            self.replacements, self.files_changed
-        )
-        .unwrap();
+/// Plan counts, with the scan statistics whenever the plan is included
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanSummary {
+    pub files_with_matches: usize,
+    pub total_matches: usize,
+    pub renames: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub files_scanned: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub bytes_scanned: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub files_skipped: Option<crate::scanner::SkippedFiles>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub timings: Option<crate::scanner::ScanTimings>,
+}
 
-        if self.renames > 0 {
-            writeln!(output, "✓ Renamed {} items", self.renames).unwrap();
-        }
+/// What `apply` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ApplyOutput {
+    pub success: bool,
+    pub operation: String,
+    pub plan_id: String,
+    pub summary: ApplySummary,
+    pub committed: bool,
+    pub staged: bool,
+    pub index_only: bool,
+    pub transactions: Vec<String>,
+    pub drifted: Vec<std::path::PathBuf>,
+    pub unreadable: Vec<crate::scanner::UnreadableFile>,
+    pub stale_paths: Vec<crate::stale_paths::StalePath>,
+}
 
-        if self.committed {
-            output.push_str("✓ Changes committed to git\n");
-        }
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ApplySummary {
+    pub files_changed: usize,
+    pub replacements: usize,
+    pub renames: usize,
+    pub by_directory: Vec<ChangeGroup>,
+    pub by_extension: Vec<ChangeGroup>,
+}
 
-        writeln!(output, "Undo with: renamify undo {}", self.plan_id).unwrap();
+/// What `apply --check` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ApplyCheckOutput {
+    /// False when the plan has issues
+    pub success: bool,
+    pub operation: String,
+    pub check: bool,
+    pub plan_id: String,
+    pub summary: ApplyCheckSummary,
+    pub issues: Vec<PlanIssue>,
+    pub tree: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ApplyCheckSummary {
+    pub files_changed: usize,
+    pub replacements: usize,
+    pub renames: usize,
+    pub issues: usize,
+}
+
+/// What `undo` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct UndoOutput {
+    pub success: bool,
+    pub operation: String,
+    pub history_id: String,
+    pub summary: UndoSummary,
+    pub conflicts: Vec<UndoConflict>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct UndoSummary {
+    pub files_restored: usize,
+    pub renames_reverted: usize,
+}
+
+/// What `redo` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct RedoOutput {
+    pub success: bool,
+    pub operation: String,
+    pub history_id: String,
+    pub summary: ChangeSummary,
+}
+
+/// Files, replacements and renames changed by `redo`, `rename` or a bundle
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ChangeSummary {
+    pub files_changed: usize,
+    pub replacements: usize,
+    pub renames: usize,
+}
+
+/// What `history export --bundle` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct HistoryExportOutput {
+    pub success: bool,
+    pub operation: String,
+    pub history_id: String,
+    pub bundle: std::path::PathBuf,
+    pub summary: ChangeSummary,
+}
+
+/// What `rename` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct RenameOutput {
+    pub success: bool,
+    pub operation: String,
+    pub plan_id: String,
+    pub search: String,
+    pub replace: String,
+    pub summary: ChangeSummary,
+    pub committed: bool,
+    pub dry_run: bool,
+    pub plan: Option<crate::scanner::Plan>,
+    pub safety_checks: Option<RenameSafetyChecks>,
+    pub history_entry: Option<crate::history::HistoryEntry>,
+}
+
+/// What `validate-plan` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanValidationOutput {
+    /// Same as `valid`
+    pub success: bool,
+    pub operation: String,
+    pub plan_path: std::path::PathBuf,
+    pub plan_id: Option<String>,
+    pub valid: bool,
+    pub summary: PlanValidationSummary,
+    pub issues: Vec<PlanIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanValidationSummary {
+    pub matches_checked: usize,
+    pub renames_checked: usize,
+    pub issues: usize,
+}
+
+/// What `gc` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct GcOutput {
+    pub success: bool,
+    pub operation: String,
+    pub dry_run: bool,
+    pub artifacts: Vec<GcArtifact>,
+    #[ts(type = "number")]
+    pub bytes: u64,
+}
+
+/// What `doctor` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct DoctorOutput {
+    /// False when a check failed
+    pub success: bool,
+    pub operation: String,
+    pub version: String,
+    pub platform: String,
+    pub checks: Vec<DoctorCheck>,
+}
+
+/// What `report` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ReportOutput {
+    pub success: bool,
+    pub operation: String,
+    pub report: UsageReport,
+}
+
+/// What `plan diff` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanDiffOutput {
+    pub success: bool,
+    pub operation: String,
+    pub old_plan: std::path::PathBuf,
+    pub new_plan: std::path::PathBuf,
+    pub identical: bool,
+    pub summary: PlanDiffSummary,
+    pub matches: MatchChanges,
+    pub renames: RenameChanges,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanDiffSummary {
+    pub matches_added: usize,
+    pub matches_removed: usize,
+    pub matches_changed: usize,
+    pub matches_unchanged: usize,
+    pub renames_added: usize,
+    pub renames_removed: usize,
+    pub renames_changed: usize,
+    pub renames_unchanged: usize,
+}
+
+/// Hunks that differ between two plans
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct MatchChanges {
+    pub added: Vec<crate::scanner::MatchHunk>,
+    pub removed: Vec<crate::scanner::MatchHunk>,
+    /// Only reported by `replan`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub moved: Option<Vec<ChangedMatch>>,
+    pub changed: Vec<ChangedMatch>,
+}
+
+/// Renames that differ between two plans
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct RenameChanges {
+    pub added: Vec<crate::scanner::Rename>,
+    pub removed: Vec<crate::scanner::Rename>,
+    pub changed: Vec<ChangedRename>,
+}
+
+/// What `replan` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ReplanOutput {
+    pub success: bool,
+    pub operation: String,
+    pub old_plan_id: String,
+    pub plan_id: String,
+    pub plan_path: Option<std::path::PathBuf>,
+    pub search: String,
+    pub replace: String,
+    pub summary: ReplanSummary,
+    pub matches: MatchChanges,
+    pub renames: RenameChanges,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ReplanSummary {
+    pub matches_added: usize,
+    pub matches_removed: usize,
+    pub matches_moved: usize,
+    pub matches_changed: usize,
+    pub matches_unchanged: usize,
+    pub renames_added: usize,
+    pub renames_removed: usize,
+    pub renames_changed: usize,
+    pub renames_unchanged: usize,
+}
+
+/// What `explain` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ExplainOutput {
+    pub success: bool,
+    pub operation: String,
+    pub search: String,
+    pub replace: String,
+    pub explanation: crate::compound_matcher::Explanation,
+}
+
+/// What `preview-file` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PreviewFileOutput {
+    pub success: bool,
+    pub operation: String,
+    pub file: std::path::PathBuf,
+    pub search: String,
+    pub replace: String,
+    pub content: String,
+    pub matches: Vec<crate::scanner::MatchHunk>,
+}
+
+/// What `plan merge` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanMergeOutput {
+    pub success: bool,
+    pub operation: String,
+    pub plan_id: String,
+    pub plan_path: std::path::PathBuf,
+    pub sources: Vec<std::path::PathBuf>,
+    pub summary: PlanMergeSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanMergeSummary {
+    pub total_matches: usize,
+    pub files_with_matches: usize,
+    pub renames: usize,
+    pub duplicate_matches: usize,
+    pub duplicate_renames: usize,
+}
+
+/// What `export-types` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ExportTypesOutput {
+    pub success: bool,
+    pub operation: String,
+    pub out_dir: std::path::PathBuf,
+    pub version: String,
+    pub types: Vec<String>,
+}
+
+/// What `config show` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ConfigShowOutput {
+    pub success: bool,
+    pub operation: String,
+    pub config: crate::config::Config,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub origins: Option<BTreeMap<String, crate::config::ConfigOrigin>>,
+}
+
+/// What `config get` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ConfigGetOutput {
+    pub success: bool,
+    pub operation: String,
+    pub key: String,
+    #[ts(type = "unknown")]
+    #[schemars(with = "serde_json::Value")]
+    pub value: toml::Value,
+    pub origin: crate::config::ConfigOrigin,
+}
+
+/// What `config set` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ConfigSetOutput {
+    pub success: bool,
+    pub operation: String,
+    pub key: String,
+    #[ts(type = "unknown")]
+    #[schemars(with = "serde_json::Value")]
+    pub value: toml::Value,
+    pub scope: crate::config::ConfigScope,
+    pub path: std::path::PathBuf,
+}
+
+/// What `check` prints with `--output json`
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct CheckOutput {
+    /// False when a forbidden identifier was found
+    pub success: bool,
+    pub operation: String,
+    pub forbidden: Vec<String>,
+    pub summary: CheckSummary,
+    pub violations: Vec<CheckViolation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct CheckSummary {
+    pub files_scanned: usize,
+    pub violations: usize,
+}
+
+/// What any command prints with `--output json` when it fails
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ErrorOutput {
+    /// Always false
+    pub success: bool,
+    pub error: ErrorDetails,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct ErrorDetails {
+    pub kind: crate::error::ErrorKind,
+    pub message: String,
+    pub exit_code: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub guard: Option<GuardDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub empty_plan: Option<crate::empty_plan::EmptyPlanDiagnosis>,
+}
+
+/// Trait for formatting output in different formats
+pub trait OutputFormatter {
+    fn format(&self, format: OutputFormat) -> String;
+    fn format_json(&self) -> String;
+    fn format_summary(&self) -> String;
+}
+
+/// Stable, line-oriented output for scripts (`--output porcelain`).
+///
+/// Every line is a record: a key followed by tab-separated fields. Keys and
+/// the order of their fields never change between versions. New keys may be
+/// added and new fields may be appended to a record, so parsers must ignore
+/// keys and trailing fields they don't know. Records for absent values are
+/// omitted. Tabs, line breaks and backslashes inside fields are escaped as
+/// `\t`, `\n`, `\r` and `\\`.
+pub trait PorcelainFormatter {
+    fn format_porcelain(&self) -> String;
+}
+
+/// Append one porcelain record
+fn porcelain_record(output: &mut String, key: &str, fields: &[&dyn std::fmt::Display]) {
+    output.push_str(key);
+    for field in fields {
+        output.push('\t');
+        output.push_str(&escape_porcelain(&field.to_string()));
+    }
+    output.push('\n');
+}
+
+fn escape_porcelain(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+impl PorcelainFormatter for PlanResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        porcelain_record(&mut output, "plan_id", &[&self.plan_id]);
+        porcelain_record(&mut output, "search", &[&self.search]);
+        porcelain_record(&mut output, "replace", &[&self.replace]);
+        porcelain_record(&mut output, "dry_run", &[&self.dry_run]);
+        porcelain_record(
+            &mut output,
+            "files_with_matches",
+            &[&self.files_with_matches],
+        );
+        porcelain_record(&mut output, "total_matches", &[&self.total_matches]);
+        porcelain_record(&mut output, "renames", &[&self.renames]);
+        if let Some(sparse) = &self.sparse_checkout {
+            porcelain_record(
+                &mut output,
+                "sparse_missing",
+                &[&sparse.missing_files, &sparse.contents_searched],
+            );
+            for path in &sparse.likely_matches {
+                porcelain_record(&mut output, "sparse_match", &[&path.display()]);
+            }
+        }
+        for owner_plan in &self.owner_plans {
+            porcelain_record(
+                &mut output,
+                "owner_plan",
+                &[
+                    &owner_plan.plan_id,
+                    &owner_plan.plan_path.display(),
+                    &owner_plan.owners.join(" "),
+                ],
+            );
+        }
+        output
+    }
+}
+
+impl PorcelainFormatter for ApplyResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        porcelain_record(&mut output, "plan_id", &[&self.plan_id]);
+        porcelain_record(&mut output, "files_changed", &[&self.files_changed]);
+        porcelain_record(&mut output, "replacements", &[&self.replacements]);
+        porcelain_record(&mut output, "renames", &[&self.renames]);
+        porcelain_record(&mut output, "committed", &[&self.committed]);
+        porcelain_record(&mut output, "staged", &[&self.staged]);
+        porcelain_record(&mut output, "index_only", &[&self.index_only]);
+        for transaction in &self.transactions {
+            porcelain_record(&mut output, "transaction", &[transaction]);
+        }
+        for path in &self.drifted {
+            porcelain_record(&mut output, "drifted", &[&path.display()]);
+        }
+        for file in &self.unreadable {
+            porcelain_record(
+                &mut output,
+                "unreadable",
+                &[&file.path.display(), &file.error],
+            );
+        }
+        for (key, groups) in [
+            ("directory", &self.by_directory),
+            ("extension", &self.by_extension),
+        ] {
+            for group in groups {
+                porcelain_record(
+                    &mut output,
+                    key,
+                    &[&group.name, &group.files, &group.replacements],
+                );
+            }
+        }
+        for stale in &self.stale_paths {
+            porcelain_record(
+                &mut output,
+                "stale_path",
+                &[
+                    &stale.file.display(),
+                    &stale.line,
+                    &stale.old_path.display(),
+                    &stale.new_path.display(),
+                ],
+            );
+        }
+        output
+    }
+}
+
+impl PorcelainFormatter for UndoResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        porcelain_record(&mut output, "history_id", &[&self.history_id]);
+        porcelain_record(&mut output, "files_restored", &[&self.files_restored]);
+        porcelain_record(&mut output, "renames_reverted", &[&self.renames_reverted]);
+        for conflict in &self.conflicts {
+            porcelain_record(
+                &mut output,
+                "conflict",
+                &[&conflict.change, &conflict.path.display()],
+            );
+        }
+        output
+    }
+}
+
+impl PorcelainFormatter for RedoResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        porcelain_record(&mut output, "history_id", &[&self.history_id]);
+        porcelain_record(&mut output, "files_changed", &[&self.files_changed]);
+        porcelain_record(&mut output, "replacements", &[&self.replacements]);
+        porcelain_record(&mut output, "renames", &[&self.renames]);
+        output
+    }
+}
+
+impl PorcelainFormatter for StatusResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        if let Some(ref plan) = self.pending_plan {
+            porcelain_record(
+                &mut output,
+                "pending_plan",
+                &[&plan.id, &plan.created_at, &plan.search, &plan.replace],
+            );
+            porcelain_record(
+                &mut output,
+                "pending_hunks",
+                &[
+                    &plan.hunks.applicable,
+                    &plan.hunks.drifted,
+                    &plan.hunks.applied,
+                ],
+            );
+            for path in &plan.hunks.drifted_files {
+                porcelain_record(&mut output, "drifted", &[&path.display()]);
+            }
+        }
+        porcelain_record(&mut output, "history_count", &[&self.history_count]);
+        if let Some(ref operation) = self.last_operation {
+            porcelain_record(&mut output, "last_operation", &[operation]);
+        }
+        output
+    }
+}
+
+impl PorcelainFormatter for HistoryResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.entries {
+            porcelain_record(
+                &mut output,
+                "entry",
+                &[
+                    &entry.id,
+                    &entry.operation,
+                    &entry.timestamp,
+                    &entry.search,
+                    &entry.replace,
+                    &entry.files_changed,
+                    &entry.replacements,
+                    &entry.renames,
+                    &entry.reverted,
+                ],
+            );
+        }
+        output
+    }
+}
+
+impl PorcelainFormatter for ErrorResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        porcelain_record(
+            &mut output,
+            "error",
+            &[&self.kind.as_str(), &self.exit_code(), &self.message],
+        );
+        if let Some(guard) = &self.guard {
+            porcelain_record(&mut output, "guard", &[&guard.name]);
+            for threshold in &guard.thresholds {
+                porcelain_record(
+                    &mut output,
+                    "threshold",
+                    &[&threshold.name, &threshold.found, &threshold.max],
+                );
+            }
+        }
+        output
+    }
+}
+
+impl OutputFormatter for PlanResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        let summary = PlanSummary {
+            files_with_matches: self.files_with_matches,
+            total_matches: self.total_matches,
+            renames: self.renames,
+            files_scanned: self.plan.as_ref().map(|plan| plan.stats.files_scanned),
+            bytes_scanned: self.plan.as_ref().map(|plan| plan.stats.bytes_scanned),
+            files_skipped: self
+                .plan
+                .as_ref()
+                .map(|plan| plan.stats.files_skipped.clone()),
+            timings: self.plan.as_ref().map(|plan| plan.stats.timings.clone()),
+        };
+
+        let mut plan = self.plan.clone();
+        if self.replace.is_empty() {
+            // Without a replacement, matches leave out `replace` and paths
+            // `new_path`, and there is no line after the edit to show
+            for hunk in plan.iter_mut().flat_map(|plan| &mut plan.matches) {
+                hunk.line_after = None;
+            }
+        }
+
+        serde_json::to_string(&PlanOutput {
+            success: true,
+            operation: if self.is_search() { "search" } else { "plan" }.to_string(),
+            plan_id: self.plan_id.clone(),
+            search: self.search.clone(),
+            replace: (!self.replace.is_empty()).then(|| self.replace.clone()),
+            dry_run: self.dry_run,
+            summary,
+            plan,
+            warnings: self.warnings.clone(),
+            sparse_checkout: self.sparse_checkout.clone(),
+            owner_plans: self.owner_plans.clone(),
+            empty_plan: self.empty_plan.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+
+        if self.replace.is_empty() {
+            // Search mode
+            writeln!(output, "Search results for '{}'", self.search).unwrap();
+        } else if self.search_only {
+            writeln!(
+                output,
+                "Search results for '{}', with replacements for '{}'",
+                self.search, self.replace
+            )
+            .unwrap();
+        } else {
+            // Plan mode
+            writeln!(output, "Renamify plan: {} -> {}", self.search, self.replace).unwrap();
+        }
+
+        writeln!(
+            output,
+            "Edits: {} files, {} replacements",
+            self.files_with_matches, self.total_matches
+        )
+        .unwrap();
+
+        if self.renames > 0 {
+            writeln!(output, "Renames: {} items", self.renames).unwrap();
+        }
+
+        let too_large = self
+            .plan
+            .as_ref()
+            .map_or(0, |plan| plan.stats.files_skipped.too_large.len());
+        if too_large > 0 {
+            writeln!(
+                output,
+                "Not searched: {too_large} files over --max-filesize (names still searched)"
+            )
+            .unwrap();
+        }
+        let unreadable = self
+            .plan
+            .as_ref()
+            .map_or(0, |plan| plan.stats.files_skipped.unreadable_files.len());
+        if unreadable > 0 {
+            writeln!(
+                output,
+                "Not read: {unreadable} files could not be read (use --strict to fail instead)"
+            )
+            .unwrap();
+        }
+
+        if !self.dry_run {
+            writeln!(output, "Plan ID: {}", self.plan_id).unwrap();
+        }
+
+        if let Some(empty_plan) = &self.empty_plan {
+            writeln!(output, "\nNo matches or renames found:\n{empty_plan}").unwrap();
+        }
+
+        if let Some(sparse) = &self.sparse_checkout {
+            writeln!(
+                output,
+                "\nNot checked out: {} tracked files (sparse checkout)",
+                sparse.missing_files
+            )
+            .unwrap();
+            if !sparse.contents_searched {
+                writeln!(
+                    output,
+                    "  Contents not available locally; only paths were checked"
+                )
+                .unwrap();
+            }
+            if sparse.likely_matches.is_empty() {
+                writeln!(output, "  None of them look like they match").unwrap();
+            } else {
+                writeln!(output, "  Likely matches:").unwrap();
+                for path in &sparse.likely_matches {
+                    writeln!(output, "    {}", path.display()).unwrap();
+                }
+            }
+        }
+
+        if !self.owner_plans.is_empty() {
+            writeln!(
+                output,
+                "\nSplit by CODEOWNERS into {} plans:",
+                self.owner_plans.len()
+            )
+            .unwrap();
+            for owner_plan in &self.owner_plans {
+                let owners = if owner_plan.owners.is_empty() {
+                    "(unowned)".to_string()
+                } else {
+                    owner_plan.owners.join(" ")
+                };
+                writeln!(
+                    output,
+                    "  {}: {} ({} files, {} replacements, {} renames)",
+                    owners,
+                    owner_plan.plan_path.display(),
+                    owner_plan.files_with_matches,
+                    owner_plan.total_matches,
+                    owner_plan.renames
+                )
+                .unwrap();
+            }
+        }
+
+        output
+    }
+}
+
+impl PlanResult {
+    /// Whether this is the result of `search` rather than `plan`
+    pub fn is_search(&self) -> bool {
+        self.search_only || self.replace.is_empty()
+    }
+
+    /// Format matches as GitHub Actions workflow commands so CI jobs can
+    /// annotate every remaining occurrence of the search term, with paths
+    /// relative to `base_dir`
+    pub fn format_github(&self, base_dir: Option<&std::path::Path>) -> String {
+        let mut output = String::new();
+        let Some(plan) = &self.plan else {
+            return output;
+        };
+
+        for hunk in &plan.matches {
+            let start_col = hunk.char_offset as usize + 1;
+            let end_col = start_col + hunk.content.chars().count();
+            let message = if hunk.replace.is_empty() {
+                format!("Found '{}' (variant of '{}')", hunk.content, plan.search)
+            } else {
+                format!("Replace '{}' with '{}'", hunk.content, hunk.replace)
+            };
+            writeln!(
+                output,
+                "::warning file={},line={},col={},endColumn={},title=renamify::{}",
+                escape_github_property(&relative_display_in(&hunk.file, base_dir)),
+                hunk.line,
+                start_col,
+                end_col,
+                escape_github_data(&message)
+            )
+            .unwrap();
+        }
+
+        for rename in &plan.paths {
+            let kind = match rename.kind {
+                crate::scanner::RenameKind::File => "File",
+                crate::scanner::RenameKind::Dir => "Directory",
+            };
+            let message = format!("{} name matches '{}'", kind, plan.search);
+            writeln!(
+                output,
+                "::warning file={},title=renamify::{}",
+                escape_github_property(&relative_display_in(&rename.path, base_dir)),
+                escape_github_data(&message)
+            )
+            .unwrap();
+        }
+
+        output
+    }
+}
+
+impl PlanResult {
+    /// Format the number of content matches in each file as `path:count`,
+    /// like `rg --count`
+    pub fn format_count(&self, base_dir: Option<&std::path::Path>) -> String {
+        let mut counts: BTreeMap<&std::path::Path, usize> = BTreeMap::new();
+        for hunk in self.plan.iter().flat_map(|plan| &plan.matches) {
+            *counts.entry(&hunk.file).or_insert(0) += 1;
+        }
+
+        let mut output = String::new();
+        for (path, count) in counts {
+            writeln!(output, "{}:{count}", relative_display_in(path, base_dir)).unwrap();
+        }
+        output
+    }
+
+    /// Format the path of every file with a content match, one per line,
+    /// like `rg --files-with-matches`
+    pub fn format_files_with_matches(&self, base_dir: Option<&std::path::Path>) -> String {
+        let files: BTreeSet<&std::path::Path> = self
+            .plan
+            .iter()
+            .flat_map(|plan| &plan.matches)
+            .map(|hunk| hunk.file.as_path())
+            .collect();
+
+        let mut output = String::new();
+        for path in files {
+            writeln!(output, "{}", relative_display_in(path, base_dir)).unwrap();
+        }
+        output
+    }
+
+    /// Format the path of every scanned file without a content match, one
+    /// per line, like `rg --files-without-match`
+    pub fn format_files_without_match(
+        &self,
+        scanned: &[std::path::PathBuf],
+        base_dir: Option<&std::path::Path>,
+    ) -> String {
+        let with_matches: BTreeSet<&std::path::Path> = self
+            .plan
+            .iter()
+            .flat_map(|plan| &plan.matches)
+            .map(|hunk| hunk.file.as_path())
+            .collect();
+        let without_match: BTreeSet<&std::path::Path> = scanned
+            .iter()
+            .map(std::path::PathBuf::as_path)
+            .filter(|path| !with_matches.contains(path))
+            .collect();
+
+        let mut output = String::new();
+        for path in without_match {
+            writeln!(output, "{}", relative_display_in(path, base_dir)).unwrap();
+        }
+        output
+    }
+
+    /// Format every match and path rename as a JSON object on its own line,
+    /// followed by a summary line, like `rg --json`
+    pub fn format_json_events(&self, base_dir: Option<&std::path::Path>) -> String {
+        let mut output = String::new();
+        let Some(plan) = &self.plan else {
+            return output;
+        };
+
+        for hunk in &plan.matches {
+            let event = json!({
+                "type": "match",
+                "path": relative_display_in(&hunk.file, base_dir),
+                "line": hunk.line,
+                "column": hunk.char_offset + 1,
+                "text": hunk.content,
+                "variant": hunk.variant,
+                "style": hunk.style,
+                "replace": (!hunk.replace.is_empty()).then_some(&hunk.replace),
+                "line_text": hunk.line_before,
+            });
+            writeln!(output, "{event}").unwrap();
+        }
+
+        for rename in &plan.paths {
+            let kind = match rename.kind {
+                crate::scanner::RenameKind::File => "file",
+                crate::scanner::RenameKind::Dir => "dir",
+            };
+            let event = json!({
+                "type": "path",
+                "path": relative_display_in(&rename.path, base_dir),
+                "kind": kind,
+                "new_path": (!rename.new_path.as_os_str().is_empty())
+                    .then(|| relative_display_in(&rename.new_path, base_dir)),
+            });
+            writeln!(output, "{event}").unwrap();
+        }
+
+        let summary = json!({
+            "type": "summary",
+            "search": self.search,
+            "files_with_matches": self.files_with_matches,
+            "total_matches": self.total_matches,
+            "paths": plan.paths.len(),
+        });
+        writeln!(output, "{summary}").unwrap();
+        output
+    }
+}
+
+/// Display a path relative to `base_dir` when possible, or in full
+fn relative_display_in(path: &std::path::Path, base_dir: Option<&std::path::Path>) -> String {
+    base_dir
+        .and_then(|base_dir| path.strip_prefix(base_dir).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Escape the message part of a GitHub workflow command
+fn escape_github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value (e.g. `file=`) of a GitHub workflow command
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+impl OutputFormatter for ApplyResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&ApplyOutput {
+            success: true,
+            operation: "apply".to_string(),
+            plan_id: self.plan_id.clone(),
+            summary: ApplySummary {
+                files_changed: self.files_changed,
+                replacements: self.replacements,
+                renames: self.renames,
+                by_directory: self.by_directory.clone(),
+                by_extension: self.by_extension.clone(),
+            },
+            committed: self.committed,
+            staged: self.staged,
+            index_only: self.index_only,
+            transactions: self.transactions.clone(),
+            drifted: self.drifted.clone(),
+            unreadable: self.unreadable.clone(),
+            stale_paths: self.stale_paths.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = format!("Changes applied successfully. Plan ID: {}\n", self.plan_id);
+
+        writeln!(
+            output,
+            "✓ Applied {} replacements across {} files",
+            self.replacements, self.files_changed
+        )
+        .unwrap();
+
+        if self.renames > 0 {
+            writeln!(output, "✓ Renamed {} items", self.renames).unwrap();
+        }
+
+        write_change_groups(&mut output, "By directory", &self.by_directory);
+        write_change_groups(&mut output, "By extension", &self.by_extension);
+
+        if self.index_only {
+            output.push_str(
+                "✓ Changes written to the git index only; the working tree is unchanged\n",
+            );
+        } else if self.staged {
+            output.push_str("✓ Changes staged in git\n");
+        }
+
+        if self.committed {
+            output.push_str("✓ Changes committed to git\n");
+        }
+
+        for path in &self.drifted {
+            writeln!(
+                output,
+                "Moved edits in {}, which changed since the bundle was exported",
+                path.display()
+            )
+            .unwrap();
+        }
+
+        if self.index_only {
+            output.push_str("Not recorded in history, so undo it with git\n");
+        } else if self.transactions.is_empty() {
+            writeln!(output, "Undo with: renamify undo {}", self.plan_id).unwrap();
+        } else {
+            writeln!(
+                output,
+                "✓ Applied in {} directory transactions ({}-1 to {}-{})",
+                self.transactions.len(),
+                self.plan_id,
+                self.plan_id,
+                self.transactions.len()
+            )
+            .unwrap();
+            writeln!(
+                output,
+                "Undo with: renamify undo {}-{} (and so on, newest first)",
+                self.plan_id,
+                self.transactions.len()
+            )
+            .unwrap();
+        }
+
+        if !self.unreadable.is_empty() {
+            let files = match self.unreadable.len() {
+                1 => "1 file".to_string(),
+                count => format!("{count} files"),
+            };
+            writeln!(
+                output,
+                "Warning: {files} could not be read when planning, so any matches in them were not changed:"
+            )
+            .unwrap();
+            for file in &self.unreadable {
+                writeln!(output, "  {} ({})", file.path.display(), file.error).unwrap();
+            }
+        }
+
+        if !self.stale_paths.is_empty() {
+            output.push_str(
+                "Build metadata still names renamed paths; regenerate it or fix it by hand:\n",
+            );
+            for stale in &self.stale_paths {
+                writeln!(
+                    output,
+                    "  {}:{}  {} -> {}",
+                    stale.file.display(),
+                    stale.line,
+                    stale.old_path.display(),
+                    stale.new_path.display()
+                )
+                .unwrap();
+            }
+        }
+
+        output
+    }
+}
+
+impl OutputFormatter for UndoResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&UndoOutput {
+            success: true,
+            operation: "undo".to_string(),
+            history_id: self.history_id.clone(),
+            summary: UndoSummary {
+                files_restored: self.files_restored,
+                renames_reverted: self.renames_reverted,
+            },
+            conflicts: self.conflicts.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = format!("Successfully undid operation {}\n", self.history_id);
+
+        if self.files_restored > 0 {
+            writeln!(output, "✓ Restored {} files", self.files_restored).unwrap();
+        }
+
+        if self.renames_reverted > 0 {
+            writeln!(output, "✓ Reverted {} renames", self.renames_reverted).unwrap();
+        }
+
+        for conflict in &self.conflicts {
+            let path = conflict.path.display();
+            match conflict.change {
+                LocalChange::Modified => {
+                    writeln!(
+                        output,
+                        "Overwrote {path}, which was modified after the apply"
+                    )
+                },
+                LocalChange::Deleted => {
+                    writeln!(output, "Left {path} deleted, as it was after the apply")
+                },
+            }
+            .unwrap();
+        }
+
+        write!(output, "Redo with: renamify redo {}", self.history_id).unwrap();
+
+        output
+    }
+}
+
+impl OutputFormatter for RedoResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&RedoOutput {
+            success: true,
+            operation: "redo".to_string(),
+            history_id: self.history_id.clone(),
+            summary: ChangeSummary {
+                files_changed: self.files_changed,
+                replacements: self.replacements,
+                renames: self.renames,
+            },
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = format!("Successfully redid operation {}\n", self.history_id);
+
+        writeln!(
+            output,
+            "✓ Applied {} replacements across {} files",
+            self.replacements, self.files_changed
+        )
+        .unwrap();
+
+        if self.renames > 0 {
+            writeln!(output, "✓ Renamed {} items", self.renames).unwrap();
+        }
+
+        output
+    }
+}
+
+impl OutputFormatter for StatusResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&self).unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+
+        if let Some(ref plan) = self.pending_plan {
+            writeln!(
+                output,
+                "Pending plan: {} ({} -> {})",
+                plan.id, plan.search, plan.replace
+            )
+            .unwrap();
+            writeln!(output, "Created: {}", plan.created_at).unwrap();
+            let hunks = &plan.hunks;
+            writeln!(
+                output,
+                "Hunks: {} applicable, {} drifted, {} already applied",
+                hunks.applicable, hunks.drifted, hunks.applied
+            )
+            .unwrap();
+            if hunks.drifted > 0 {
+                output.push_str("Changed since the plan was made (re-run plan before applying):\n");
+                for path in &hunks.drifted_files {
+                    writeln!(output, "  {}", path.display()).unwrap();
+                }
+            } else if hunks.applicable == 0 && hunks.applied > 0 {
+                output.push_str("The plan is already applied\n");
+            }
+        } else {
+            output.push_str("No pending plan\n");
+        }
+
+        writeln!(output, "History entries: {}", self.history_count).unwrap();
+
+        if let Some(ref last_op) = self.last_operation {
+            writeln!(output, "Last operation: {}", last_op).unwrap();
+        }
+
+        output
+    }
+}
+
+impl OutputFormatter for HistoryResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        if self.entries.is_empty() {
+            return "No history entries found".to_string();
+        }
+
+        let mut output = String::new();
+        for entry in &self.entries {
+            write!(
+                output,
+                "{} [{}] {} -> {} ({} files, {} replacements",
+                entry.id,
+                entry.operation,
+                entry.search,
+                entry.replace,
+                entry.files_changed,
+                entry.replacements
+            )
+            .unwrap();
+
+            if entry.renames > 0 {
+                write!(output, ", {} renames", entry.renames).unwrap();
+            }
+
+            if entry.reverted {
+                output.push_str(" [REVERTED]");
+            }
+
+            writeln!(output, ") {}", entry.timestamp).unwrap();
+        }
+
+        output
+    }
+}
+
+impl OutputFormatter for HistoryExportResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&HistoryExportOutput {
+            success: true,
+            operation: "history_export".to_string(),
+            history_id: self.history_id.clone(),
+            bundle: self.bundle.clone(),
+            summary: ChangeSummary {
+                files_changed: self.files,
+                replacements: self.replacements,
+                renames: self.renames,
+            },
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "Exported {} to {}",
+            self.history_id,
+            self.bundle.display()
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "✓ {} replacements across {} files, {} renames",
+            self.replacements, self.files, self.renames
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "Apply with: renamify apply --bundle {}",
+            self.bundle.display()
+        )
+        .unwrap();
+        output
+    }
+}
+
+impl OutputFormatter for RenameResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&RenameOutput {
+            success: true,
+            operation: "rename".to_string(),
+            plan_id: self.plan_id.clone(),
+            search: self.search.clone(),
+            replace: self.replace.clone(),
+            summary: ChangeSummary {
+                files_changed: self.files_changed,
+                replacements: self.replacements,
+                renames: self.renames,
+            },
+            committed: self.committed,
+            dry_run: self.dry_run,
+            plan: self.plan.clone(),
+            safety_checks: self.safety_checks.clone(),
+            history_entry: self.history_entry.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        if self.dry_run {
+            let mut output = format!(
+                "Dry run: would apply {} replacements across {} files",
+                self.replacements, self.files_changed
+            );
+            if self.renames > 0 {
+                write!(output, " and rename {} items", self.renames).unwrap();
+            }
+            output.push('\n');
+            if let Some(checks) = &self.safety_checks {
+                if checks.large_change.exceeded && !checks.large_change.acknowledged {
+                    output.push_str("✗ Large change: re-run with --large to apply\n");
+                }
+                for collision in &checks.collisions {
+                    writeln!(
+                        output,
+                        "✗ {} -> {}: {}",
+                        collision.path.display(),
+                        collision.new_path.display(),
+                        collision.reason
+                    )
+                    .unwrap();
+                }
+            }
+            return output;
+        }
+
+        let mut output = format!(
+            "✓ Applied {} replacements across {} files\n",
+            self.replacements, self.files_changed
+        );
+
+        if self.renames > 0 {
+            writeln!(output, "✓ Renamed {} items", self.renames).unwrap();
+        }
+
+        if self.committed {
+            output.push_str("✓ Changes committed to git\n");
+        }
+
+        writeln!(output, "Undo with: renamify undo {}", self.plan_id).unwrap();
+
+        output
+    }
+}
+
+impl OutputFormatter for PlanValidationResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&PlanValidationOutput {
+            success: self.is_valid(),
+            operation: "validate-plan".to_string(),
+            plan_path: self.plan_path.clone(),
+            plan_id: self.plan_id.clone(),
+            valid: self.is_valid(),
+            summary: PlanValidationSummary {
+                matches_checked: self.matches_checked,
+                renames_checked: self.renames_checked,
+                issues: self.issues.len(),
+            },
+            issues: self.issues.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+        for issue in &self.issues {
+            writeln!(output, "{}", format_issue(issue, self.base_dir.as_deref())).unwrap();
+        }
+
+        let checked = format!(
+            "{} matches, {} renames checked",
+            self.matches_checked, self.renames_checked
+        );
+        if self.is_valid() {
+            writeln!(
+                output,
+                "✓ Plan {} is valid ({checked})",
+                self.plan_id.as_deref().unwrap_or_default()
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                output,
+                "✗ {} has {} issues ({checked})",
+                self.plan_path.display(),
+                self.issues.len()
+            )
+            .unwrap();
+        }
+        output
+    }
+}
+
+/// `path:line: kind: message`, with the location left out when there is none
+fn format_issue(issue: &PlanIssue, base_dir: Option<&std::path::Path>) -> String {
+    let location = match (&issue.path, issue.line) {
+        (Some(path), Some(line)) => format!("{}:{line}: ", relative_display_in(path, base_dir)),
+        (Some(path), None) => format!("{}: ", relative_display_in(path, base_dir)),
+        (None, _) => String::new(),
+    };
+    format!("{location}{}: {}", issue.kind.as_str(), issue.message)
+}
+
+impl OutputFormatter for ApplyCheckResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&ApplyCheckOutput {
+            success: self.is_clean(),
+            operation: "apply".to_string(),
+            check: true,
+            plan_id: self.plan_id.clone(),
+            summary: ApplyCheckSummary {
+                files_changed: self.files_changed,
+                replacements: self.replacements,
+                renames: self.renames,
+                issues: self.issues.len(),
+            },
+            issues: self.issues.clone(),
+            tree: self.tree.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+        for issue in &self.issues {
+            writeln!(output, "{}", format_issue(issue, self.base_dir.as_deref())).unwrap();
+        }
+
+        if self.is_clean() {
+            writeln!(
+                output,
+                "✓ Plan {} would apply cleanly (nothing was changed)",
+                self.plan_id
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                output,
+                "✗ Plan {} has {} issues (nothing was changed)",
+                self.plan_id,
+                self.issues.len()
+            )
+            .unwrap();
+        }
+        writeln!(
+            output,
+            "Would apply {} replacements across {} files",
+            self.replacements, self.files_changed
+        )
+        .unwrap();
+        if self.renames > 0 {
+            writeln!(output, "Would rename {} items", self.renames).unwrap();
+        }
+
+        if let Some(tree) = self.tree.as_deref().filter(|tree| !tree.is_empty()) {
+            write!(output, "\n{tree}").unwrap();
+        }
+        output
+    }
+}
+
+impl PorcelainFormatter for ApplyCheckResult {
+    fn format_porcelain(&self) -> String {
+        let mut output = String::new();
+        porcelain_record(&mut output, "plan_id", &[&self.plan_id]);
+        porcelain_record(&mut output, "files_changed", &[&self.files_changed]);
+        porcelain_record(&mut output, "replacements", &[&self.replacements]);
+        porcelain_record(&mut output, "renames", &[&self.renames]);
+        for issue in &self.issues {
+            porcelain_record(
+                &mut output,
+                "issue",
+                &[
+                    &issue.kind.as_str(),
+                    &issue
+                        .path
+                        .as_deref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_default(),
+                    &issue.line.map(|line| line.to_string()).unwrap_or_default(),
+                    &issue.message,
+                ],
+            );
+        }
+        output
+    }
+}
+
+impl OutputFormatter for GcResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&GcOutput {
+            success: true,
+            operation: "gc".to_string(),
+            dry_run: self.dry_run,
+            artifacts: self.artifacts.clone(),
+            bytes: self.bytes,
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        if self.artifacts.is_empty() {
+            return "No orphaned files found\n".to_string();
+        }
+
+        let mut output = String::new();
+        writeln!(
+            output,
+            "{} {} orphaned files ({}):",
+            if self.dry_run {
+                "Would remove"
+            } else {
+                "Removed"
+            },
+            self.artifacts.len(),
+            crate::preview::format_bytes(self.bytes)
+        )
+        .unwrap();
+        for artifact in &self.artifacts {
+            writeln!(
+                output,
+                "  {:<10} {}",
+                artifact.kind.to_string(),
+                artifact.path.display()
+            )
+            .unwrap();
+        }
+        if self.dry_run {
+            writeln!(output, "\nRun without --dry-run to remove them").unwrap();
+        }
+        output
+    }
+}
+
+impl OutputFormatter for DoctorResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&DoctorOutput {
+            success: self.passed(),
+            operation: "doctor".to_string(),
+            version: self.version.clone(),
+            platform: self.platform.clone(),
+            checks: self.checks.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+        writeln!(output, "renamify {} on {}\n", self.version, self.platform).unwrap();
+        for check in &self.checks {
+            let marker = match check.status {
+                DoctorStatus::Pass => "✓",
+                DoctorStatus::Warn => "!",
+                DoctorStatus::Fail => "✗",
+                DoctorStatus::Skip => "-",
+            };
+            writeln!(output, "{marker} {}: {}", check.name, check.detail).unwrap();
+            if let Some(hint) = &check.hint {
+                writeln!(output, "    {hint}").unwrap();
+            }
+        }
+
+        let count = |status| {
+            self.checks
+                .iter()
+                .filter(|check| check.status == status)
+                .count()
+        };
+        let (failed, warned) = (count(DoctorStatus::Fail), count(DoctorStatus::Warn));
+        let mut totals = Vec::new();
+        if failed > 0 {
+            totals.push(format!("{failed} failed"));
+        }
+        if warned > 0 {
+            let plural = if warned == 1 { "" } else { "s" };
+            totals.push(format!("{warned} warning{plural}"));
+        }
+        if totals.is_empty() {
+            output.push_str("\nAll checks passed\n");
+        } else {
+            writeln!(output, "\n{}", totals.join(", ")).unwrap();
+        }
+        output
+    }
+}
+
+impl OutputFormatter for UsageReport {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&ReportOutput {
+            success: true,
+            operation: "report".to_string(),
+            report: self.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        use comfy_table::{Cell, Color, Table};
+
+        if self.entries == 0 {
+            return "No renamify history in this repository yet\n".to_string();
+        }
+
+        let mut output = String::new();
+        let date = |timestamp: &Option<String>| {
+            timestamp
+                .as_deref()
+                .and_then(|timestamp| timestamp.split('T').next())
+                .unwrap_or_default()
+                .to_string()
+        };
+        writeln!(
+            output,
+            "Renamify usage from {} to {}\n",
+            date(&self.first_used),
+            date(&self.last_used)
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "Applies: {}  Undos: {} ({:.0}% of applies)  Redos: {}",
+            self.applies,
+            self.undos,
+            self.undo_rate * 100.0,
+            self.redos
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "Files touched: {}  Paths renamed: {}",
+            self.files_touched, self.renames
+        )
+        .unwrap();
+        write!(
+            output,
+            "Average apply: {:.1} files, {:.1} renames",
+            self.average_files_per_apply, self.average_renames_per_apply
+        )
+        .unwrap();
+        if let Some(matches) = self.average_matches_per_plan {
+            write!(
+                output,
+                ", {matches:.1} matches (over {} saved plans)",
+                self.plans_measured
+            )
+            .unwrap();
+        }
+        writeln!(output, "\n").unwrap();
+
+        let mut table = Table::new();
+        table.set_header(vec![
+            Cell::new("Month").fg(Color::Cyan),
+            Cell::new("Applies").fg(Color::Cyan),
+            Cell::new("Undos").fg(Color::Cyan),
+            Cell::new("Redos").fg(Color::Cyan),
+            Cell::new("Files").fg(Color::Cyan),
+            Cell::new("Renames").fg(Color::Cyan),
+        ]);
+        for usage in &self.by_month {
+            table.add_row(vec![
+                usage.month.clone(),
+                usage.applies.to_string(),
+                usage.undos.to_string(),
+                usage.redos.to_string(),
+                usage.files_touched.to_string(),
+                usage.renames.to_string(),
+            ]);
+        }
+        writeln!(output, "{table}").unwrap();
+        output
+    }
+}
+
+impl OutputFormatter for PlanDiffResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&PlanDiffOutput {
+            success: true,
+            operation: "plan-diff".to_string(),
+            old_plan: self.old_plan.clone(),
+            new_plan: self.new_plan.clone(),
+            identical: self.is_identical(),
+            summary: PlanDiffSummary {
+                matches_added: self.added_matches.len(),
+                matches_removed: self.removed_matches.len(),
+                matches_changed: self.changed_matches.len(),
+                matches_unchanged: self.unchanged_matches,
+                renames_added: self.added_renames.len(),
+                renames_removed: self.removed_renames.len(),
+                renames_changed: self.changed_renames.len(),
+                renames_unchanged: self.unchanged_renames,
+            },
+            matches: MatchChanges {
+                added: self.added_matches.clone(),
+                removed: self.removed_matches.clone(),
+                moved: None,
+                changed: self.changed_matches.clone(),
+            },
+            renames: self.rename_changes(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        if self.is_identical() {
+            return format!(
+                "Plans are identical ({} matches, {} renames)\n",
+                self.unchanged_matches, self.unchanged_renames
+            );
+        }
+
+        let base_dir = self.base_dir.as_deref();
+        let hunk_location = |hunk: &crate::scanner::MatchHunk| {
+            format!(
+                "{}:{}:{}",
+                relative_display_in(&hunk.file, base_dir),
+                hunk.line,
+                hunk.char_offset + 1
+            )
+        };
+
+        let mut output = String::new();
+        writeln!(
+            output,
+            "Matches: +{} -{} ~{} ({} unchanged)",
+            self.added_matches.len(),
+            self.removed_matches.len(),
+            self.changed_matches.len(),
+            self.unchanged_matches
+        )
+        .unwrap();
+        for hunk in &self.added_matches {
+            writeln!(
+                output,
+                "  + {} {} -> {}",
+                hunk_location(hunk),
+                hunk.content,
+                hunk.replace
+            )
+            .unwrap();
+        }
+        for hunk in &self.removed_matches {
+            writeln!(
+                output,
+                "  - {} {} -> {}",
+                hunk_location(hunk),
+                hunk.content,
+                hunk.replace
+            )
+            .unwrap();
+        }
+        for change in &self.changed_matches {
+            writeln!(
+                output,
+                "  ~ {} {} -> {} (was {} -> {})",
+                hunk_location(&change.after),
+                change.after.content,
+                change.after.replace,
+                change.before.content,
+                change.before.replace
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            output,
+            "Renames: +{} -{} ~{} ({} unchanged)",
+            self.added_renames.len(),
+            self.removed_renames.len(),
+            self.changed_renames.len(),
+            self.unchanged_renames
+        )
+        .unwrap();
+        for rename in &self.added_renames {
+            writeln!(
+                output,
+                "  + {} -> {}",
+                relative_display_in(&rename.path, base_dir),
+                relative_display_in(&rename.new_path, base_dir)
+            )
+            .unwrap();
+        }
+        for rename in &self.removed_renames {
+            writeln!(
+                output,
+                "  - {} -> {}",
+                relative_display_in(&rename.path, base_dir),
+                relative_display_in(&rename.new_path, base_dir)
+            )
+            .unwrap();
+        }
+        for change in &self.changed_renames {
+            writeln!(
+                output,
+                "  ~ {} -> {} (was {})",
+                relative_display_in(&change.after.path, base_dir),
+                relative_display_in(&change.after.new_path, base_dir),
+                relative_display_in(&change.before.new_path, base_dir)
+            )
+            .unwrap();
+        }
+
+        output
+    }
+}
+
+impl OutputFormatter for ReplanResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        let diff = &self.diff;
+        serde_json::to_string(&ReplanOutput {
+            success: true,
+            operation: "replan".to_string(),
+            old_plan_id: self.old_plan_id.clone(),
+            plan_id: self.plan_id.clone(),
+            plan_path: self.plan_path.clone(),
+            search: self.search.clone(),
+            replace: self.replace.clone(),
+            summary: ReplanSummary {
+                matches_added: diff.added_matches.len(),
+                matches_removed: diff.removed_matches.len(),
+                matches_moved: self.moved_matches.len(),
+                matches_changed: diff.changed_matches.len(),
+                matches_unchanged: diff.unchanged_matches,
+                renames_added: diff.added_renames.len(),
+                renames_removed: diff.removed_renames.len(),
+                renames_changed: diff.changed_renames.len(),
+                renames_unchanged: diff.unchanged_renames,
+            },
+            matches: MatchChanges {
+                added: diff.added_matches.clone(),
+                removed: diff.removed_matches.clone(),
+                moved: Some(self.moved_matches.clone()),
+                changed: diff.changed_matches.clone(),
+            },
+            renames: diff.rename_changes(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "Replanned {} -> {} (plan {} -> {})",
+            self.search, self.replace, self.old_plan_id, self.plan_id
+        )
+        .unwrap();
+
+        if self.moved_matches.is_empty() && self.diff.is_identical() {
+            writeln!(
+                output,
+                "Nothing changed since the plan was made ({} matches, {} renames)",
+                self.diff.unchanged_matches, self.diff.unchanged_renames
+            )
+            .unwrap();
+        } else {
+            if !self.moved_matches.is_empty() {
+                writeln!(output, "Moved: {} matches", self.moved_matches.len()).unwrap();
+                for moved in &self.moved_matches {
+                    writeln!(
+                        output,
+                        "  > {}:{}:{} {} -> {} (was line {}, column {})",
+                        relative_display_in(&moved.after.file, self.diff.base_dir.as_deref()),
+                        moved.after.line,
+                        moved.after.char_offset + 1,
+                        moved.after.content,
+                        moved.after.replace,
+                        moved.before.line,
+                        moved.before.char_offset + 1
+                    )
+                    .unwrap();
+                }
+            }
+            if self.diff.is_identical() {
+                writeln!(
+                    output,
+                    "Matches: +0 -0 ~0 ({} unchanged)\nRenames: +0 -0 ~0 ({} unchanged)",
+                    self.diff.unchanged_matches, self.diff.unchanged_renames
+                )
+                .unwrap();
+            } else {
+                output.push_str(&self.diff.format_summary());
+            }
+        }
+
+        match &self.plan_path {
+            Some(plan_path) => {
+                writeln!(output, "Plan written to {}", plan_path.display()).unwrap();
+            },
+            None => writeln!(output, "Dry run: no plan written").unwrap(),
+        }
+        output
+    }
+}
+
+impl OutputFormatter for ExplainResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&ExplainOutput {
+            success: true,
+            operation: "explain".to_string(),
+            search: self.search.clone(),
+            replace: self.replace.clone(),
+            explanation: self.explanation.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        use crate::compound_matcher::ExplainedMatch;
+
+        let explanation = &self.explanation;
+        let mut output = String::new();
+        writeln!(output, "Identifier: {}", explanation.identifier).unwrap();
+        if !explanation.prefix.is_empty() {
+            writeln!(output, "Prefix:     {}", explanation.prefix).unwrap();
+        }
+        writeln!(output, "Tokens:     {}", explanation.tokens.join(" | ")).unwrap();
+        writeln!(
+            output,
+            "Search:     {}",
+            explanation.search_tokens.join(" | ")
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "Style:      {}",
+            explanation.style.map_or("none", crate::Style::name)
+        )
+        .unwrap();
+        if explanation.segments.is_empty() {
+            writeln!(output, "Segments:   none").unwrap();
+        } else {
+            let segments: Vec<String> = explanation
+                .segments
+                .iter()
+                .map(|range| {
+                    format!(
+                        "{}..{} ({})",
+                        range.start,
+                        range.end,
+                        explanation.tokens[range.clone()].join(" ")
+                    )
+                })
+                .collect();
+            writeln!(output, "Segments:   {}", segments.join(", ")).unwrap();
+        }
+
+        let verdict = match explanation.matched {
+            ExplainedMatch::Exact => "exact match",
+            ExplainedMatch::Compound => "compound match",
+            ExplainedMatch::None => "no match",
+        };
+        match &explanation.replacement {
+            Some(replacement) => writeln!(
+                output,
+                "Result:     {verdict}: {} -> {replacement}",
+                explanation.identifier
+            )
+            .unwrap(),
+            None => writeln!(output, "Result:     {verdict}").unwrap(),
+        }
+        writeln!(output, "Reason:     {}", explanation.reason).unwrap();
+        output
+    }
+}
+
+impl OutputFormatter for PreviewFileResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&PreviewFileOutput {
+            success: true,
+            operation: "preview-file".to_string(),
+            file: self.file.clone(),
+            search: self.search.clone(),
+            replace: self.replace.clone(),
+            content: self.content.clone(),
+            matches: self.matches.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    /// The transformed content, exactly as it would be written
+    fn format_summary(&self) -> String {
+        self.content.clone()
+    }
+}
+
+impl OutputFormatter for PlanMergeResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
+        }
+    }
+
+    fn format_json(&self) -> String {
+        serde_json::to_string(&PlanMergeOutput {
+            success: true,
+            operation: "plan-merge".to_string(),
+            plan_id: self.plan_id.clone(),
+            plan_path: self.plan_path.clone(),
+            sources: self.sources.clone(),
+            summary: PlanMergeSummary {
+                total_matches: self.total_matches,
+                files_with_matches: self.files_with_matches,
+                renames: self.renames,
+                duplicate_matches: self.duplicate_matches,
+                duplicate_renames: self.duplicate_renames,
+            },
+        })
+        .unwrap_or_default()
+    }
 
+    fn format_summary(&self) -> String {
+        let mut output = format!(
+            "Merged {} plans into {}: {} matches in {} files, {} renames\n",
+            self.sources.len(),
+            self.plan_path.display(),
+            self.total_matches,
+            self.files_with_matches,
+            self.renames
+        );
+        if self.duplicate_matches > 0 || self.duplicate_renames > 0 {
+            writeln!(
+                output,
+                "Skipped {} duplicate matches and {} duplicate renames",
+                self.duplicate_matches, self.duplicate_renames
+            )
+            .unwrap();
+        }
+        writeln!(output, "Plan ID: {}", self.plan_id).unwrap();
         output
     }
 }
 
-impl OutputFormatter for UndoResult {
+impl OutputFormatter for ExportTypesResult {
     fn format(&self, format: OutputFormat) -> String {
         match format {
             OutputFormat::Json => self.format_json(),
@@ -225,36 +3062,27 @@ impl OutputFormatter for UndoResult {
     }
 
     fn format_json(&self) -> String {
-        serde_json::to_string(&json!({
-            "success": true,
-            "operation": "undo",
-            "history_id": self.history_id,
-            "summary": {
-                "files_restored": self.files_restored,
-                "renames_reverted": self.renames_reverted,
-            }
-        }))
+        serde_json::to_string(&ExportTypesOutput {
+            success: true,
+            operation: "export-types".to_string(),
+            out_dir: self.out_dir.clone(),
+            version: self.version.clone(),
+            types: self.types.clone(),
+        })
         .unwrap_or_default()
     }
 
     fn format_summary(&self) -> String {
-        let mut output = format!("Successfully undid operation {}\n", self.history_id);
-
-        if self.files_restored > 0 {
-            writeln!(output, "✓ Restored {} files", self.files_restored).unwrap();
-        }
-
-        if self.renames_reverted > 0 {
-            writeln!(output, "✓ Reverted {} renames", self.renames_reverted).unwrap();
-        }
-
-        write!(output, "Redo with: renamify redo {}", self.history_id).unwrap();
-
-        output
+        format!(
+            "Exported {} types (renamify-types {}) to {}\n",
+            self.types.len(),
+            self.version,
+            self.out_dir.display()
+        )
     }
 }
 
-impl OutputFormatter for RedoResult {
+impl OutputFormatter for ConfigShowResult {
     fn format(&self, format: OutputFormat) -> String {
         match format {
             OutputFormat::Json => self.format_json(),
@@ -263,38 +3091,38 @@ impl OutputFormatter for RedoResult {
     }
 
     fn format_json(&self) -> String {
-        serde_json::to_string(&json!({
-            "success": true,
-            "operation": "redo",
-            "history_id": self.history_id,
-            "summary": {
-                "files_changed": self.files_changed,
-                "replacements": self.replacements,
-                "renames": self.renames,
-            }
-        }))
+        serde_json::to_string(&ConfigShowOutput {
+            success: true,
+            operation: "config-show".to_string(),
+            config: self.config.clone(),
+            origins: self.origins.clone(),
+        })
         .unwrap_or_default()
     }
 
     fn format_summary(&self) -> String {
-        let mut output = format!("Successfully redid operation {}\n", self.history_id);
-
-        writeln!(
-            output,
-            "✓ Applied {} replacements across {} files",
-            self.replacements, self.files_changed
-        )
-        .unwrap();
+        let Some(origins) = &self.origins else {
+            return toml::to_string_pretty(&self.config).unwrap_or_default();
+        };
 
-        if self.renames > 0 {
-            writeln!(output, "✓ Renamed {} items", self.renames).unwrap();
+        let entries = self.config.entries().unwrap_or_default();
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|(key, value)| format!("{key} = {value}"))
+            .collect();
+        let width = lines.iter().map(String::len).max().unwrap_or(0);
+        let mut output = String::new();
+        for (line, (key, _)) in lines.iter().zip(&entries) {
+            match origins.get(key) {
+                Some(origin) => writeln!(output, "{line:width$}  # {origin}").unwrap(),
+                None => writeln!(output, "{line}").unwrap(),
+            }
         }
-
         output
     }
 }
 
-impl OutputFormatter for StatusResult {
+impl OutputFormatter for ConfigGetResult {
     fn format(&self, format: OutputFormat) -> String {
         match format {
             OutputFormat::Json => self.format_json(),
@@ -303,35 +3131,56 @@ impl OutputFormatter for StatusResult {
     }
 
     fn format_json(&self) -> String {
-        serde_json::to_string(&self).unwrap_or_default()
+        serde_json::to_string(&ConfigGetOutput {
+            success: true,
+            operation: "config-get".to_string(),
+            key: self.key.clone(),
+            value: self.value.clone(),
+            origin: self.origin.clone(),
+        })
+        .unwrap_or_default()
     }
 
     fn format_summary(&self) -> String {
-        let mut output = String::new();
-
-        if let Some(ref plan) = self.pending_plan {
-            writeln!(
-                output,
-                "Pending plan: {} ({} -> {})",
-                plan.id, plan.search, plan.replace
-            )
-            .unwrap();
-            writeln!(output, "Created: {}", plan.created_at).unwrap();
-        } else {
-            output.push_str("No pending plan\n");
+        // Strings are printed bare so scripts can use them directly
+        match &self.value {
+            toml::Value::String(value) => format!("{value}\n"),
+            value => format!("{value}\n"),
         }
+    }
+}
 
-        writeln!(output, "History entries: {}", self.history_count).unwrap();
-
-        if let Some(ref last_op) = self.last_operation {
-            writeln!(output, "Last operation: {}", last_op).unwrap();
+impl OutputFormatter for ConfigSetResult {
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.format_json(),
+            OutputFormat::Summary => self.format_summary(),
         }
+    }
 
-        output
+    fn format_json(&self) -> String {
+        serde_json::to_string(&ConfigSetOutput {
+            success: true,
+            operation: "config-set".to_string(),
+            key: self.key.clone(),
+            value: self.value.clone(),
+            scope: self.scope,
+            path: self.path.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn format_summary(&self) -> String {
+        format!(
+            "✓ Set {} = {} in {}\n",
+            self.key,
+            self.value,
+            self.path.display()
+        )
     }
 }
 
-impl OutputFormatter for HistoryResult {
+impl OutputFormatter for CheckResult {
     fn format(&self, format: OutputFormat) -> String {
         match format {
             OutputFormat::Json => self.format_json(),
@@ -340,47 +3189,61 @@ impl OutputFormatter for HistoryResult {
     }
 
     fn format_json(&self) -> String {
-        serde_json::to_string(&json!({
-            "entries": self.entries
-        }))
+        serde_json::to_string(&CheckOutput {
+            success: self.passed(),
+            operation: "check".to_string(),
+            forbidden: self.forbidden.clone(),
+            summary: CheckSummary {
+                files_scanned: self.files_scanned,
+                violations: self.violations.len(),
+            },
+            violations: self.violations.clone(),
+        })
         .unwrap_or_default()
     }
 
     fn format_summary(&self) -> String {
-        if self.entries.is_empty() {
-            return "No history entries found".to_string();
+        if self.passed() {
+            return format!(
+                "✓ No forbidden identifiers found ({} files scanned)\n",
+                self.files_scanned
+            );
         }
 
         let mut output = String::new();
-        for entry in &self.entries {
-            write!(
-                output,
-                "{} [{}] {} -> {} ({} files, {} replacements",
-                entry.id,
-                entry.operation,
-                entry.search,
-                entry.replace,
-                entry.files_changed,
-                entry.replacements
-            )
-            .unwrap();
-
-            if entry.renames > 0 {
-                write!(output, ", {} renames", entry.renames).unwrap();
-            }
-
-            if entry.reverted {
-                output.push_str(" [REVERTED]");
+        for violation in &self.violations {
+            match (violation.line, violation.column) {
+                (Some(line), Some(column)) => writeln!(
+                    output,
+                    "{}:{}:{}: {} (forbidden: {})",
+                    violation.file.display(),
+                    line,
+                    column,
+                    violation.content,
+                    violation.term
+                ),
+                _ => writeln!(
+                    output,
+                    "{}: path contains {} (forbidden: {})",
+                    violation.file.display(),
+                    violation.content,
+                    violation.term
+                ),
             }
-
-            writeln!(output, ") {}", entry.timestamp).unwrap();
+            .unwrap();
         }
+        writeln!(
+            output,
+            "✗ Found {} forbidden identifier occurrence(s)",
+            self.violations.len()
+        )
+        .unwrap();
 
         output
     }
 }
 
-impl OutputFormatter for RenameResult {
+impl OutputFormatter for ErrorResult {
     fn format(&self, format: OutputFormat) -> String {
         match format {
             OutputFormat::Json => self.format_json(),
@@ -389,40 +3252,21 @@ impl OutputFormatter for RenameResult {
     }
 
     fn format_json(&self) -> String {
-        serde_json::to_string(&json!({
-            "success": true,
-            "operation": "rename",
-            "plan_id": self.plan_id,
-            "search": self.search,
-            "replace": self.replace,
-            "summary": {
-                "files_changed": self.files_changed,
-                "replacements": self.replacements,
-                "renames": self.renames,
+        serde_json::to_string(&ErrorOutput {
+            success: false,
+            error: ErrorDetails {
+                kind: self.kind,
+                message: self.message.clone(),
+                exit_code: self.exit_code(),
+                guard: self.guard.clone(),
+                empty_plan: self.empty_plan.clone(),
             },
-            "committed": self.committed,
-            "plan": self.plan,
-        }))
+        })
         .unwrap_or_default()
     }
 
     fn format_summary(&self) -> String {
-        let mut output = format!(
-            "✓ Applied {} replacements across {} files\n",
-            self.replacements, self.files_changed
-        );
-
-        if self.renames > 0 {
-            writeln!(output, "✓ Renamed {} items", self.renames).unwrap();
-        }
-
-        if self.committed {
-            output.push_str("✓ Changes committed to git\n");
-        }
-
-        writeln!(output, "Undo with: renamify undo {}", self.plan_id).unwrap();
-
-        output
+        format!("Error: {}", self.message)
     }
 }
 
@@ -439,7 +3283,18 @@ impl OutputFormatter for VersionResult {
     }
 
     fn format_summary(&self) -> String {
-        format!("{} {}", self.name, self.version)
+        let details: Vec<&str> = self
+            .build
+            .iter()
+            .flat_map(|build| [build.git_commit.as_deref(), build.build_date.as_deref()])
+            .flatten()
+            .collect();
+
+        if details.is_empty() {
+            format!("{} {}", self.name, self.version)
+        } else {
+            format!("{} {} ({})", self.name, self.version, details.join(" "))
+        }
     }
 }
 
@@ -458,6 +3313,11 @@ mod tests {
             renames: 3,
             dry_run: false,
             plan: None,
+            warnings: vec![],
+            sparse_checkout: None,
+            owner_plans: vec![],
+            empty_plan: None,
+            search_only: false,
         };
 
         let json = result.format_json();
@@ -482,11 +3342,27 @@ mod tests {
             renames: 0,
             dry_run: true,
             plan: None,
+            warnings: vec![],
+            sparse_checkout: None,
+            owner_plans: vec![],
+            empty_plan: None,
+            search_only: false,
         };
 
         let json = result.format_json();
         assert!(json.contains("\"operation\":\"search\""));
+        assert!(json.contains("\"replace\":null"));
         assert!(json.contains("\"dry_run\":true"));
+
+        // A search with a hypothetical replacement is still a search
+        let result = PlanResult {
+            replace: "new_name".to_string(),
+            search_only: true,
+            ..result
+        };
+        let json = result.format_json();
+        assert!(json.contains("\"operation\":\"search\""));
+        assert!(json.contains("\"replace\":\"new_name\""));
     }
 
     #[test]
@@ -500,6 +3376,11 @@ mod tests {
             renames: 3,
             dry_run: false,
             plan: None,
+            warnings: vec![],
+            sparse_checkout: None,
+            owner_plans: vec![],
+            empty_plan: None,
+            search_only: false,
         };
 
         let summary = result.format_summary();
@@ -521,6 +3402,11 @@ mod tests {
             renames: 0,
             dry_run: true,
             plan: None,
+            warnings: vec![],
+            sparse_checkout: None,
+            owner_plans: vec![],
+            empty_plan: None,
+            search_only: false,
         };
 
         let summary = result.format_summary();
@@ -529,6 +3415,114 @@ mod tests {
         assert!(summary.contains('8'));
     }
 
+    #[test]
+    fn test_plan_result_github_format() {
+        use crate::scanner::{MatchHunk, Plan, Rename, RenameKind, Stats};
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let plan = Plan {
+            id: "search123".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: String::new(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![MatchHunk {
+                file: PathBuf::from("/repo/src/a,b.rs"),
+                line: 3,
+                byte_offset: 4,
+                char_offset: 4,
+                variant: "oldName".to_string(),
+                content: "oldName".to_string(),
+                replace: String::new(),
+                start: 10,
+                end: 17,
+                line_before: None,
+                line_after: None,
+                coercion_applied: None,
+                original_file: None,
+                renamed_file: None,
+                patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
+            }],
+            paths: vec![Rename {
+                path: PathBuf::from("src/old_name"),
+                new_path: PathBuf::new(),
+                kind: RenameKind::Dir,
+                coercion_applied: None,
+            }],
+            stats: Stats {
+                files_scanned: 1,
+                total_matches: 1,
+                matches_by_variant: HashMap::new(),
+                files_with_matches: 1,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+
+        let result = PlanResult {
+            plan_id: plan.id.clone(),
+            search: "old_name".to_string(),
+            replace: String::new(),
+            files_with_matches: 1,
+            total_matches: 1,
+            renames: 1,
+            dry_run: true,
+            plan: Some(plan),
+            warnings: vec![],
+            sparse_checkout: None,
+            owner_plans: vec![],
+            empty_plan: None,
+            search_only: false,
+        };
+
+        let output = result.format_github(Some(std::path::Path::new("/repo")));
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "::warning file=src/a%2Cb.rs,line=3,col=5,endColumn=12,title=renamify::Found 'oldName' (variant of 'old_name')"
+        );
+        assert_eq!(
+            lines[1],
+            "::warning file=src/old_name,title=renamify::Directory name matches 'old_name'"
+        );
+    }
+
+    #[test]
+    fn test_plan_result_github_format_without_plan() {
+        let result = PlanResult {
+            plan_id: "search123".to_string(),
+            search: "old_name".to_string(),
+            replace: String::new(),
+            files_with_matches: 0,
+            total_matches: 0,
+            renames: 0,
+            dry_run: true,
+            plan: None,
+            warnings: vec![],
+            sparse_checkout: None,
+            owner_plans: vec![],
+            empty_plan: None,
+            search_only: false,
+        };
+
+        assert!(result.format_github(None).is_empty());
+    }
+
     #[test]
     fn test_apply_result_json_format() {
         let result = ApplyResult {
@@ -537,6 +3531,14 @@ mod tests {
             replacements: 25,
             renames: 5,
             committed: true,
+            staged: false,
+            index_only: false,
+            transactions: vec![],
+            drifted: vec![],
+            unreadable: vec![],
+            by_directory: vec![],
+            by_extension: vec![],
+            stale_paths: vec![],
         };
 
         let json = result.format_json();
@@ -555,6 +3557,14 @@ mod tests {
             replacements: 25,
             renames: 5,
             committed: false,
+            staged: false,
+            index_only: false,
+            transactions: vec![],
+            drifted: vec![],
+            unreadable: vec![],
+            by_directory: vec![],
+            by_extension: vec![],
+            stale_paths: vec![],
         };
 
         let summary = result.format_summary();
@@ -573,18 +3583,118 @@ mod tests {
             replacements: 25,
             renames: 5,
             committed: true,
+            staged: false,
+            index_only: false,
+            transactions: vec![],
+            drifted: vec![],
+            unreadable: vec![],
+            by_directory: vec![],
+            by_extension: vec![],
+            stale_paths: vec![],
         };
 
         let summary = result.format_summary();
         assert!(summary.contains("committed") || summary.contains("git"));
     }
 
+    #[test]
+    fn test_apply_result_breakdowns() {
+        use crate::scanner::MatchHunk;
+        use std::path::{Path, PathBuf};
+
+        let hunk = |file: &str| MatchHunk {
+            file: PathBuf::from("/repo").join(file),
+            line: 1,
+            byte_offset: 0,
+            char_offset: 0,
+            variant: "old_name".to_string(),
+            content: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            start: 0,
+            end: 8,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        };
+        let matches = vec![
+            hunk("src/a.rs"),
+            hunk("src/a.rs"),
+            hunk("src/b.rs"),
+            hunk("vendor/lib/c.js"),
+            hunk("vendor/d.js"),
+            hunk("vendor/d.js"),
+            hunk("vendor/d.js"),
+            hunk("Makefile"),
+        ];
+        let by_directory = ChangeGroup::by_directory(&matches, Some(Path::new("/repo")));
+        let by_extension = ChangeGroup::by_extension(&matches);
+        let group = |name: &str, files, replacements| ChangeGroup {
+            name: name.to_string(),
+            files,
+            replacements,
+        };
+        assert_eq!(
+            by_directory,
+            vec![
+                group("vendor/", 2, 4),
+                group("src/", 2, 3),
+                group(".", 1, 1)
+            ]
+        );
+        assert_eq!(
+            by_extension,
+            vec![
+                group(".js", 2, 4),
+                group(".rs", 2, 3),
+                group("(none)", 1, 1)
+            ]
+        );
+
+        let result = ApplyResult {
+            plan_id: "apply123".to_string(),
+            files_changed: 5,
+            replacements: 8,
+            renames: 0,
+            committed: false,
+            staged: false,
+            index_only: false,
+            transactions: vec![],
+            drifted: vec![],
+            unreadable: vec![],
+            by_directory,
+            by_extension,
+            stale_paths: vec![],
+        };
+        assert!(result.format_summary().contains(concat!(
+            "By directory:\n",
+            "  vendor/  4 replacements in 2 files\n",
+            "  src/     3 replacements in 2 files\n",
+            "  .        1 replacement in 1 file\n",
+        )));
+        let json: serde_json::Value = serde_json::from_str(&result.format_json()).unwrap();
+        assert_eq!(
+            json["summary"]["by_extension"][0],
+            json!({"name": ".js", "files": 2, "replacements": 4})
+        );
+        assert!(result
+            .format_porcelain()
+            .contains("directory\tvendor/\t2\t4\n"));
+    }
+
     #[test]
     fn test_undo_result_json_format() {
         let result = UndoResult {
             history_id: "undo456".to_string(),
             files_restored: 8,
             renames_reverted: 3,
+            conflicts: vec![],
         };
 
         let json = result.format_json();
@@ -599,6 +3709,7 @@ mod tests {
             history_id: "undo456".to_string(),
             files_restored: 8,
             renames_reverted: 3,
+            conflicts: vec![],
         };
 
         let summary = result.format_summary();
@@ -607,6 +3718,31 @@ mod tests {
         assert!(summary.contains('3'));
     }
 
+    #[test]
+    fn test_undo_result_lists_conflicts() {
+        let result = UndoResult {
+            history_id: "undo456".to_string(),
+            files_restored: 1,
+            renames_reverted: 0,
+            conflicts: vec![UndoConflict {
+                path: "src/lib.rs".into(),
+                change: LocalChange::Modified,
+            }],
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&result.format_json()).unwrap();
+        assert_eq!(
+            json["conflicts"],
+            json!([{"change": "modified", "path": "src/lib.rs"}])
+        );
+        assert!(result
+            .format_porcelain()
+            .contains("conflict\tmodified\tsrc/lib.rs\n"));
+        assert!(result
+            .format_summary()
+            .contains("Overwrote src/lib.rs, which was modified after the apply"));
+    }
+
     #[test]
     fn test_redo_result_json_format() {
         let result = RedoResult {
@@ -646,6 +3782,7 @@ mod tests {
             search: "old".to_string(),
             replace: "new".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            hunks: PlanDrift::default(),
         });
 
         let result = StatusResult {
@@ -682,6 +3819,7 @@ mod tests {
             search: "old".to_string(),
             replace: "new".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            hunks: PlanDrift::default(),
         });
 
         let result = StatusResult {
@@ -786,6 +3924,9 @@ mod tests {
             renames: 3,
             committed: true,
             plan: None,
+            dry_run: false,
+            safety_checks: None,
+            history_entry: None,
         };
 
         let json = result.format_json();
@@ -806,6 +3947,9 @@ mod tests {
             renames: 3,
             committed: false,
             plan: None,
+            dry_run: false,
+            safety_checks: None,
+            history_entry: None,
         };
 
         let summary = result.format_summary();
@@ -814,11 +3958,129 @@ mod tests {
         assert!(summary.contains('3'));
     }
 
+    #[test]
+    fn test_check_result_json_format() {
+        let result = CheckResult {
+            forbidden: vec!["old_name".to_string()],
+            files_scanned: 4,
+            violations: vec![CheckViolation {
+                term: "old_name".to_string(),
+                file: std::path::PathBuf::from("src/lib.rs"),
+                line: Some(2),
+                column: Some(5),
+                content: "OldName".to_string(),
+            }],
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&result.format_json()).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["operation"], "check");
+        assert_eq!(json["summary"]["violations"], 1);
+        assert_eq!(json["violations"][0]["file"], "src/lib.rs");
+        assert_eq!(json["violations"][0]["content"], "OldName");
+    }
+
+    #[test]
+    fn test_check_result_summary_and_github_format() {
+        let result = CheckResult {
+            forbidden: vec!["old_name".to_string()],
+            files_scanned: 4,
+            violations: vec![
+                CheckViolation {
+                    term: "old_name".to_string(),
+                    file: std::path::PathBuf::from("src/lib.rs"),
+                    line: Some(2),
+                    column: Some(5),
+                    content: "OldName".to_string(),
+                },
+                CheckViolation {
+                    term: "old_name".to_string(),
+                    file: std::path::PathBuf::from("src/old_name.rs"),
+                    line: None,
+                    column: None,
+                    content: "old_name.rs".to_string(),
+                },
+            ],
+        };
+
+        let summary = result.format_summary();
+        assert!(summary.contains("src/lib.rs:2:5: OldName (forbidden: old_name)"));
+        assert!(summary.contains("src/old_name.rs: path contains old_name.rs"));
+        assert!(summary.contains("Found 2 forbidden"));
+
+        let github = result.format_github();
+        assert!(github
+            .contains("::error file=src/lib.rs,line=2,col=5,endColumn=12,title=renamify check::"));
+        assert!(github.contains("::error file=src/old_name.rs,title=renamify check::"));
+
+        let clean = CheckResult {
+            forbidden: vec!["old_name".to_string()],
+            files_scanned: 4,
+            violations: vec![],
+        };
+        assert!(clean.passed());
+        assert!(clean.format_summary().contains("No forbidden identifiers"));
+    }
+
+    #[test]
+    fn test_error_result_json_format() {
+        let err: anyhow::Error =
+            crate::error::RenamifyError::NotFound("Plan with ID abc not found".to_string()).into();
+        let result = ErrorResult::from_error(&err.context("Failed to load plan"));
+
+        let json: serde_json::Value = serde_json::from_str(&result.format_json()).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["error"]["kind"], "not_found");
+        assert_eq!(json["error"]["exit_code"], 2);
+        assert_eq!(
+            json["error"]["message"],
+            "Failed to load plan: Plan with ID abc not found"
+        );
+        assert_eq!(
+            result.format_summary(),
+            "Error: Failed to load plan: Plan with ID abc not found"
+        );
+    }
+
+    #[test]
+    fn test_error_result_guard_details() {
+        let err: anyhow::Error = crate::operations::plan::PlanGuardError::LargeChange {
+            files: 40,
+            renames: 3,
+            max_files: 25,
+            max_renames: 100,
+        }
+        .into();
+        let result = ErrorResult::from_error(&err);
+
+        let json: serde_json::Value = serde_json::from_str(&result.format_json()).unwrap();
+        assert_eq!(json["error"]["kind"], "guard");
+        assert_eq!(json["error"]["guard"]["name"], "large_change");
+        assert_eq!(
+            json["error"]["guard"]["thresholds"],
+            json!([{ "name": "files", "found": 40, "max": 25 }])
+        );
+        assert_eq!(
+            result.format_porcelain(),
+            format!(
+                "error\tguard\t4\t{}\nguard\tlarge_change\nthreshold\tfiles\t40\t25\n",
+                result.message
+            )
+        );
+
+        let err: anyhow::Error =
+            crate::error::RenamifyError::NotFound("missing".to_string()).into();
+        let json: serde_json::Value =
+            serde_json::from_str(&ErrorResult::from_error(&err).format_json()).unwrap();
+        assert!(json["error"].get("guard").is_none());
+    }
+
     #[test]
     fn test_version_result_json_format() {
         let result = VersionResult {
             name: "renamify".to_string(),
             version: "1.0.0".to_string(),
+            build: None,
         };
 
         let json = result.format_json();
@@ -831,17 +4093,44 @@ mod tests {
         let result = VersionResult {
             name: "renamify".to_string(),
             version: "1.0.0".to_string(),
+            build: None,
         };
 
         let summary = result.format_summary();
         assert_eq!(summary, "renamify 1.0.0");
     }
 
+    #[test]
+    fn test_version_result_with_build_info() {
+        let result = VersionResult {
+            name: "renamify".to_string(),
+            version: "1.0.0".to_string(),
+            build: Some(BuildInfo {
+                git_commit: Some("abc123def456".to_string()),
+                build_date: Some("2025-01-02".to_string()),
+                rustc_version: Some("rustc 1.89.0".to_string()),
+                target: Some("x86_64-unknown-linux-gnu".to_string()),
+                features: vec![],
+            }),
+        };
+
+        assert_eq!(
+            result.format_summary(),
+            "renamify 1.0.0 (abc123def456 2025-01-02)"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&result.format_json()).unwrap();
+        assert_eq!(json["build"]["git_commit"], "abc123def456");
+        assert_eq!(json["build"]["target"], "x86_64-unknown-linux-gnu");
+        assert_eq!(json["build"]["features"], serde_json::json!([]));
+    }
+
     #[test]
     fn test_output_format_trait() {
         let result = VersionResult {
             name: "test".to_string(),
             version: "0.1.0".to_string(),
+            build: None,
         };
 
         // Test that format() calls the right method
@@ -850,4 +4139,61 @@ mod tests {
             .format(OutputFormat::Json)
             .contains("\"name\":\"test\""));
     }
+
+    #[test]
+    fn test_apply_result_porcelain_format() {
+        let result = ApplyResult {
+            plan_id: "abc123".to_string(),
+            files_changed: 2,
+            replacements: 5,
+            renames: 1,
+            committed: false,
+            staged: true,
+            index_only: false,
+            transactions: vec!["abc123-1".to_string(), "abc123-2".to_string()],
+            drifted: vec![],
+            unreadable: vec![],
+            by_directory: vec![],
+            by_extension: vec![],
+            stale_paths: vec![],
+        };
+
+        assert_eq!(
+            result.format_porcelain(),
+            "plan_id\tabc123\nfiles_changed\t2\nreplacements\t5\nrenames\t1\ncommitted\tfalse\n\
+             staged\ttrue\nindex_only\tfalse\ntransaction\tabc123-1\ntransaction\tabc123-2\n"
+        );
+    }
+
+    #[test]
+    fn test_status_result_porcelain_omits_absent_values() {
+        let result = StatusResult {
+            pending_plan: None,
+            history_count: 0,
+            last_operation: None,
+        };
+        assert_eq!(result.format_porcelain(), "history_count\t0\n");
+    }
+
+    #[test]
+    fn test_porcelain_escapes_fields() {
+        let result = HistoryResult {
+            entries: vec![HistoryItem {
+                id: "abc".to_string(),
+                operation: "apply".to_string(),
+                timestamp: "2024-01-01".to_string(),
+                search: "old\tname".to_string(),
+                replace: "new\\name\n".to_string(),
+                files_changed: 1,
+                replacements: 2,
+                renames: 0,
+                reverted: true,
+            }],
+        };
+
+        assert_eq!(
+            result.format_porcelain(),
+            "entry\tabc\tapply\t2024-01-01\told\\tname\tnew\\\\name\\n\t1\t2\t0\ttrue\n"
+        );
+    }
 }
@@ -0,0 +1,95 @@
+//! Paging long previews, the way git does.
+//!
+//! When stdout is a terminal and a preview is taller than it, the preview is
+//! piped through `$RENAMIFY_PAGER`, `$PAGER` or `less`, in that order. `less`
+//! gets `LESS=FRX` unless `LESS` is set, so it keeps colors, quits at once when
+//! the output fits and leaves the output on screen. A pager of `cat` or an
+//! empty string turns paging off. Paging is off until the CLI turns it on, so
+//! embedding renamify never starts a pager.
+
+use crate::interrupt::PagerGuard;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Allow previews to be paged; the CLI does this unless `--no-pager` is given
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Print `content` and a newline, through the pager when it is taller than the
+/// terminal
+pub fn page(content: &str) {
+    let Some(pager) = pager_command(content) else {
+        println!("{content}");
+        return;
+    };
+    if run_pager(&pager, content).is_err() {
+        println!("{content}");
+    }
+}
+
+/// The pager to use for `content`, if it should be paged
+fn pager_command(content: &str) -> Option<String> {
+    if !ENABLED.load(Ordering::SeqCst) || !io::stdout().is_terminal() {
+        return None;
+    }
+    if terminal_height().is_some_and(|height| content.lines().count() < height) {
+        return None;
+    }
+    let pager = std::env::var("RENAMIFY_PAGER")
+        .or_else(|_| std::env::var("PAGER"))
+        .unwrap_or_else(|_| "less".to_string());
+    let pager = pager.trim();
+    (!pager.is_empty() && pager != "cat").then(|| pager.to_string())
+}
+
+fn run_pager(pager: &str, content: &str) -> io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", pager]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", pager]);
+        command
+    };
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    if std::env::var_os("LV").is_none() {
+        command.env("LV", "-c");
+    }
+
+    io::stdout().flush()?;
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+    let _guard = PagerGuard::activate();
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when the user quits before the end
+        let _ = writeln!(stdin, "{content}");
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Rows in the terminal, from `LINES` or the terminal itself
+fn terminal_height() -> Option<usize> {
+    if let Some(lines) = std::env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse().ok())
+    {
+        return Some(lines);
+    }
+    #[cfg(unix)]
+    {
+        // SAFETY: TIOCGWINSZ only writes a winsize into the struct given
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+        if result == 0 && size.ws_row > 0 {
+            return Some(usize::from(size.ws_row));
+        }
+    }
+    None
+}
@@ -1,55 +1,90 @@
-use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
-use regex::bytes::{Regex, RegexBuilder};
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, BuildError, MatchKind};
 use serde::{Deserialize, Serialize};
 
+/// Every generated variant compiled into a single Aho-Corasick automaton, so
+/// content is scanned once no matter how many variants there are
 #[derive(Debug, Clone)]
 pub struct MatchPattern {
-    pub regex: Regex,
     pub variants: Vec<String>,
     matcher: AhoCorasick,
 }
 
+/// A variant found in content, before any boundary checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantMatch<'p> {
+    pub start: usize,
+    pub end: usize,
+    pub variant: &'p str,
+}
+
 impl MatchPattern {
     pub fn identify_variant(&self, text: &[u8]) -> Option<&str> {
         self.matcher
             .find(text)
             .map(|m| self.variants[m.pattern().as_usize()].as_str())
     }
-}
 
-pub fn build_pattern(variants: &[String]) -> Result<MatchPattern, regex::Error> {
-    if variants.is_empty() {
-        return Ok(MatchPattern {
-            regex: Regex::new("$^")?,
-            variants: vec![],
-            matcher: AhoCorasick::new(Vec::<String>::new()).unwrap(),
-        });
+    pub fn is_match(&self, content: &[u8]) -> bool {
+        self.matcher.is_match(content)
     }
 
-    let escaped: Vec<String> = variants.iter().map(|v| regex::escape(v)).collect();
-
-    let longest_first = {
-        let mut sorted = escaped;
-        sorted.sort_by_key(|s| std::cmp::Reverse(s.len()));
-        sorted
-    };
-
-    let pattern = format!("(?:{})", longest_first.join("|"));
-
-    let regex = RegexBuilder::new(&pattern).unicode(false).build()?;
+    /// Non-overlapping variants in `content`, preferring the longest variant
+    /// at each position
+    pub fn find_iter<'a>(&'a self, content: &'a [u8]) -> impl Iterator<Item = VariantMatch<'a>> {
+        self.matcher.find_iter(content).map(|m| VariantMatch {
+            start: m.start(),
+            end: m.end(),
+            variant: self.variants[m.pattern().as_usize()].as_str(),
+        })
+    }
+}
 
+pub fn build_pattern(variants: &[String]) -> Result<MatchPattern, BuildError> {
     let matcher = AhoCorasickBuilder::new()
         .match_kind(MatchKind::LeftmostLongest)
-        .build(variants)
-        .unwrap();
+        .build(variants)?;
 
     Ok(MatchPattern {
-        regex,
         variants: variants.to_vec(),
         matcher,
     })
 }
 
+/// 1-based line number and 0-based column of each offset, for offsets given
+/// in ascending order. Counts newlines once across all of them rather than
+/// from the start of the content for every match.
+pub(crate) struct LineTracker<'a> {
+    content: &'a [u8],
+    offset: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> LineTracker<'a> {
+    pub(crate) const fn new(content: &'a [u8]) -> Self {
+        Self {
+            content,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+        }
+    }
+
+    pub(crate) fn locate(&mut self, offset: usize) -> (usize, usize) {
+        if offset < self.offset {
+            *self = Self::new(self.content);
+        }
+        for (idx, &byte) in self.content[self.offset..offset].iter().enumerate() {
+            if byte == b'\n' {
+                self.line += 1;
+                self.line_start = self.offset + idx + 1;
+            }
+        }
+        self.offset = offset;
+        (self.line, offset - self.line_start)
+    }
+}
+
 pub fn is_boundary(bytes: &[u8], start: usize, end: usize) -> bool {
     // Check if the matched text contains spaces (space-separated identifier)
     let match_bytes = &bytes[start..end];
@@ -107,35 +142,23 @@ pub struct Match {
 
 pub fn find_matches(pattern: &MatchPattern, content: &[u8], file: &str) -> Vec<Match> {
     let mut matches = Vec::new();
+    let mut lines = LineTracker::new(content);
 
-    for m in pattern.regex.find_iter(content) {
-        if !is_boundary(content, m.start(), m.end()) {
+    for m in pattern.find_iter(content) {
+        if !is_boundary(content, m.start, m.end) {
             continue;
         }
 
-        let match_text = m.as_bytes();
-        let variant = pattern
-            .identify_variant(match_text)
-            .unwrap_or_default()
-            .to_string();
-
-        #[allow(clippy::naive_bytecount)]
-        let line_number = content[..m.start()].iter().filter(|&&b| b == b'\n').count() + 1;
-
-        let line_start = content[..m.start()]
-            .iter()
-            .rposition(|&b| b == b'\n')
-            .map_or(0, |p| p + 1);
-        let column = m.start() - line_start;
+        let (line_number, column) = lines.locate(m.start);
 
         matches.push(Match {
             file: file.to_string(),
             line: line_number,
             column,
-            start: m.start(),
-            end: m.end(),
-            variant,
-            text: String::from_utf8_lossy(match_text).to_string(),
+            start: m.start,
+            end: m.end,
+            variant: m.variant.to_string(),
+            text: String::from_utf8_lossy(&content[m.start..m.end]).to_string(),
         });
     }
 
@@ -157,8 +180,8 @@ mod tests {
         let variants = vec!["hello_world".to_string()];
         let pattern = build_pattern(&variants).unwrap();
         assert_eq!(pattern.variants.len(), 1);
-        assert!(pattern.regex.is_match(b"hello_world"));
-        assert!(!pattern.regex.is_match(b"hello"));
+        assert!(pattern.is_match(b"hello_world"));
+        assert!(!pattern.is_match(b"hello"));
     }
 
     #[test]
@@ -170,9 +193,9 @@ mod tests {
         ];
         let pattern = build_pattern(&variants).unwrap();
         assert_eq!(pattern.variants.len(), 3);
-        assert!(pattern.regex.is_match(b"old_name"));
-        assert!(pattern.regex.is_match(b"oldName"));
-        assert!(pattern.regex.is_match(b"OldName"));
+        assert!(pattern.is_match(b"old_name"));
+        assert!(pattern.is_match(b"oldName"));
+        assert!(pattern.is_match(b"OldName"));
     }
 
     #[test]
@@ -185,8 +208,9 @@ mod tests {
         let pattern = build_pattern(&variants).unwrap();
 
         let text = b"foobarbaz";
-        let m = pattern.regex.find(text).unwrap();
-        assert_eq!(m.as_bytes(), b"foobarbaz");
+        let m = pattern.find_iter(text).next().unwrap();
+        assert_eq!(m.variant, "foobarbaz");
+        assert_eq!((m.start, m.end), (0, 9));
     }
 
     #[test]
@@ -294,11 +318,11 @@ mod tests {
         let variants = vec!["foo.bar".to_string(), "foo[bar]".to_string()];
         let pattern = build_pattern(&variants).unwrap();
 
-        assert!(pattern.regex.is_match(b"foo.bar"));
-        assert!(pattern.regex.is_match(b"foo[bar]"));
+        assert!(pattern.is_match(b"foo.bar"));
+        assert!(pattern.is_match(b"foo[bar]"));
 
-        assert!(!pattern.regex.is_match(b"fooXbar"));
-        assert!(!pattern.regex.is_match(b"foo_bar_"));
+        assert!(!pattern.is_match(b"fooXbar"));
+        assert!(!pattern.is_match(b"foo_bar_"));
     }
 
     #[test]
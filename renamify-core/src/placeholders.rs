@@ -0,0 +1,183 @@
+//! Placeholders in the replacement, filled in when the plan is made.
+//!
+//! `{date}`, `{year}`, `{user}` and `{counter}` stand for values only known
+//! at plan time, as in `old_api` -> `old_api_deprecated_{year}`. Each value is
+//! a literal segment: the rest of the replacement takes the case style of
+//! every match, but the value is written exactly as it is. Braces around any
+//! other word are kept as written.
+//!
+//! The scan sees each placeholder as a plain lowercase word, so it gets the
+//! separators of each style, and the word is swapped for the value in the
+//! finished plan.
+
+use crate::error::RenamifyError;
+use crate::history::History;
+use crate::scanner::Plan;
+use anyhow::Result;
+use std::path::Path;
+
+/// Each placeholder with the word the scan sees in its place
+const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("date", "renamifyplaceholderdate"),
+    ("year", "renamifyplaceholderyear"),
+    ("user", "renamifyplaceholderuser"),
+    ("counter", "renamifyplaceholdercounter"),
+];
+
+/// The placeholders in one replacement and their values
+#[derive(Debug)]
+pub struct Placeholders {
+    /// The replacement with each placeholder swapped for its stand-in word
+    replace: String,
+    /// Each stand-in word used with its value
+    values: Vec<(&'static str, String)>,
+}
+
+impl Placeholders {
+    /// Find the placeholders in `replace` and work out their values, or
+    /// `None` when it has none. `renamify_dir` holds the history `{counter}`
+    /// counts.
+    pub fn parse(replace: &str, renamify_dir: &Path) -> Result<Option<Self>> {
+        let mut stand_in = replace.to_string();
+        let mut values = Vec::new();
+        for (name, word) in PLACEHOLDERS {
+            let placeholder = format!("{{{name}}}");
+            if !replace.contains(&placeholder) {
+                continue;
+            }
+            stand_in = stand_in.replace(&placeholder, word);
+            values.push((*word, value(name, renamify_dir)?));
+        }
+        Ok((!values.is_empty()).then_some(Self {
+            replace: stand_in,
+            values,
+        }))
+    }
+
+    /// The replacement to scan with
+    pub fn replace(&self) -> &str {
+        &self.replace
+    }
+
+    /// `text` with each stand-in word, in whatever case, swapped for its value
+    pub fn fill_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (word, value) in &self.values {
+            // Lowercasing ASCII keeps byte offsets, so positions carry over
+            while let Some(start) = text.to_ascii_lowercase().find(word) {
+                text.replace_range(start..start + word.len(), value);
+            }
+        }
+        text
+    }
+
+    /// Swap the stand-in words in everything `plan` would write for the values
+    pub fn fill(&self, plan: &mut Plan) {
+        let fill_path = |path: &mut std::path::PathBuf| {
+            *path = self.fill_text(&path.to_string_lossy()).into();
+        };
+        plan.replace = self.fill_text(&plan.replace);
+        for hunk in plan.matches.iter_mut().chain(&mut plan.near_misses) {
+            hunk.replace = self.fill_text(&hunk.replace);
+            if let Some(line) = hunk.line_after.as_mut() {
+                *line = self.fill_text(line);
+            }
+            if let Some(file) = hunk.renamed_file.as_mut() {
+                fill_path(file);
+            }
+        }
+        for rename in plan
+            .paths
+            .iter_mut()
+            .chain(plan.suspicious_renames.iter_mut().map(|s| &mut s.rename))
+        {
+            fill_path(&mut rename.new_path);
+        }
+        for suspicious in &mut plan.suspicious_renames {
+            fill_path(&mut suspicious.destination);
+        }
+    }
+}
+
+/// Whether `replace` has any placeholders
+pub fn has_placeholders(replace: &str) -> bool {
+    PLACEHOLDERS
+        .iter()
+        .any(|(name, _)| replace.contains(&format!("{{{name}}}")))
+}
+
+/// The value of placeholder `name`
+fn value(name: &str, renamify_dir: &Path) -> Result<String> {
+    let now = chrono::Local::now();
+    Ok(match name {
+        "date" => now.format("%Y%m%d").to_string(),
+        "year" => now.format("%Y").to_string(),
+        "user" => std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .map_err(|_| {
+                RenamifyError::InvalidInput(
+                    "{user} in the replacement needs USER or USERNAME to be set".to_string(),
+                )
+            })?,
+        "counter" => (History::load(renamify_dir)?.list_entries(None).len() + 1).to_string(),
+        _ => unreachable!("unknown placeholder {name}"),
+    })
+}
+
+/// Run `scan` with the placeholders in `replace` as stand-in words, then
+/// fill their values into the plan it returns
+pub fn scan_with_placeholders(
+    replace: &str,
+    renamify_dir: &Path,
+    scan: impl FnOnce(&str) -> Result<Plan>,
+) -> Result<Plan> {
+    let Some(placeholders) = Placeholders::parse(replace, renamify_dir)? else {
+        return scan(replace);
+    };
+    let mut plan = scan(placeholders.replace())?;
+    placeholders.fill(&mut plan);
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_content, PlanOptions};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_placeholders_are_literal_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let renamify_dir = temp_dir.path().join(".renamify");
+        assert!(Placeholders::parse("new_name", &renamify_dir)
+            .unwrap()
+            .is_none());
+
+        let plan = scan_with_placeholders("old_name_v{counter}_{year}", &renamify_dir, |replace| {
+            scan_content(
+                &temp_dir.path().join("a.rs"),
+                b"old_name OldName OLD_NAME\n",
+                "old_name",
+                replace,
+                &PlanOptions::default(),
+            )
+        })
+        .unwrap();
+
+        let this_year = chrono::Local::now().format("%Y").to_string();
+        assert_eq!(plan.replace, format!("old_name_v1_{this_year}"));
+        let replacements: Vec<&str> = plan.matches.iter().map(|m| m.replace.as_str()).collect();
+        assert_eq!(
+            replacements,
+            vec![
+                format!("old_name_v1_{this_year}"),
+                format!("OldNameV1{this_year}"),
+                format!("OLD_NAME_V1_{this_year}"),
+            ]
+        );
+        assert_eq!(
+            plan.matches[0].line_after.as_deref(),
+            Some(format!("old_name_v1_{this_year} OldName OLD_NAME\n").as_str())
+        );
+    }
+}
@@ -1,12 +1,12 @@
+use super::spans::{after_spans, before_spans, marker_line};
 use crate::scanner::{MatchHunk, Plan, RenameKind};
 use nu_ansi_term::{Color as AnsiColor, Style};
 use similar::{ChangeTag, TextDiff};
 use std::collections::HashMap;
-use std::convert::TryFrom;
 use std::fmt::Write;
 use std::path::Path;
 
-/// Highlight specific ranges in a line with background color
+/// Highlight the replaced spans in a line with a brighter background
 fn highlight_line_with_hunks(
     line: &str,
     hunks: &[&MatchHunk],
@@ -17,14 +17,6 @@ fn highlight_line_with_hunks(
         return line.to_string();
     }
 
-    let mut result = String::new();
-    let mut last_end = 0;
-
-    // Sort hunks by column position and keep track of how much prior replacements shift offsets
-    let mut sorted_hunks = hunks.to_vec();
-    sorted_hunks.sort_by_key(|h| h.byte_offset);
-    let mut insertion_shift: isize = 0;
-
     // Base style for the whole line (Claude Code custom colors)
     let base_style = if is_delete {
         // Deleted line background: #852134
@@ -37,53 +29,32 @@ fn highlight_line_with_hunks(
             .on(AnsiColor::Rgb(0x00, 0x5E, 0x24))
             .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF))
     };
+    let highlight_style = if is_delete {
+        // Deleted match highlight: #c0526a
+        Style::new()
+            .on(AnsiColor::Rgb(0xC0, 0x52, 0x6A))
+            .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF))
+    } else {
+        // Added match highlight: #00a958
+        Style::new()
+            .on(AnsiColor::Rgb(0x00, 0xA9, 0x58))
+            .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF))
+    };
 
-    for hunk in sorted_hunks {
-        let (col, term, original_len) = if is_delete {
-            (
-                usize::try_from(hunk.byte_offset).unwrap(),
-                hunk.content.as_str(),
-                hunk.content.len(),
-            )
-        } else {
-            let original_len = hunk.content.len();
-            let new_len = hunk.replace.len();
-            let base_col = usize::try_from(hunk.byte_offset).unwrap();
-            let base_col_isize = isize::try_from(base_col).unwrap();
-            let max_col = isize::try_from(line.len()).unwrap();
-            let adjusted_isize = (base_col_isize + insertion_shift).clamp(0, max_col);
-            let adjusted = usize::try_from(adjusted_isize).unwrap();
-            insertion_shift +=
-                isize::try_from(new_len).unwrap() - isize::try_from(original_len).unwrap();
-            (adjusted, hunk.replace.as_str(), original_len)
-        };
-
-        // Add the part before the match with base style
-        if col > last_end && col <= line.len() {
-            result.push_str(&base_style.paint(&line[last_end..col]).to_string());
-        }
+    let spans = if is_delete {
+        before_spans(line, hunks)
+    } else {
+        after_spans(line, hunks)
+    };
 
-        // Add the highlighted match with brighter background (word-level highlight)
-        let end = if is_delete {
-            (col + original_len).min(line.len())
-        } else {
-            (col + term.len()).min(line.len())
-        };
-        if col < line.len() {
-            let highlight_style = if is_delete {
-                // Deleted match highlight: #c0526a
-                Style::new()
-                    .on(AnsiColor::Rgb(0xC0, 0x52, 0x6A))
-                    .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF))
-            } else {
-                // Added match highlight: #00a958
-                Style::new()
-                    .on(AnsiColor::Rgb(0x00, 0xA9, 0x58))
-                    .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF))
-            };
-            result.push_str(&highlight_style.paint(&line[col..end]).to_string());
-            last_end = end;
+    let mut result = String::new();
+    let mut last_end = 0;
+    for span in spans {
+        if span.start > last_end {
+            result.push_str(&base_style.paint(&line[last_end..span.start]).to_string());
         }
+        result.push_str(&highlight_style.paint(&line[span.clone()]).to_string());
+        last_end = span.end;
     }
 
     // Add any remaining part with base style
@@ -94,9 +65,35 @@ fn highlight_line_with_hunks(
     result
 }
 
+/// Write a `^` marker line under a changed line, aligned past the diff sign
+fn write_markers(output: &mut String, line: &str, hunks: &[&MatchHunk], is_delete: bool) {
+    let spans = if is_delete {
+        before_spans(line, hunks)
+    } else {
+        after_spans(line, hunks)
+    };
+    let markers = marker_line(line, &spans);
+    if !markers.is_empty() {
+        writeln!(output, " {}", markers).unwrap();
+    }
+}
+
 /// Render plan as unified diffs
 pub fn render_diff(plan: &Plan, use_color: bool) -> String {
+    render_diff_sampled(plan, use_color, None, None)
+}
+
+/// Render the diff with at most `sample` matches per file, rounded up to a
+/// whole line, followed by the complete totals
+#[allow(clippy::too_many_lines)]
+pub(super) fn render_diff_sampled(
+    plan: &Plan,
+    use_color: bool,
+    sample: Option<usize>,
+    base_dir: Option<&Path>,
+) -> String {
     let mut output = String::new();
+    let mut shown = 0;
     let _is_search = plan.replace.is_empty();
 
     // Group hunks by file
@@ -111,16 +108,20 @@ pub fn render_diff(plan: &Plan, use_color: bool) -> String {
 
     // Generate diffs for each file
     for file in sorted_files {
-        let hunks = &file_hunks[&file];
+        let mut hunks = file_hunks[&file].clone();
+        let mut omitted = 0;
+        if let Some(sample) = sample {
+            hunks.sort_by_key(|hunk| (hunk.line, hunk.byte_offset));
+            if let Some(last_line) = hunks.get(sample.saturating_sub(1)).map(|hunk| hunk.line) {
+                let keep = hunks.partition_point(|hunk| hunk.line <= last_line);
+                omitted = hunks.len() - keep;
+                hunks.truncate(keep);
+            }
+        }
+        shown += hunks.len();
 
         // Make path relative to current directory for cleaner display
-        let relative_path = match std::env::current_dir()
-            .ok()
-            .and_then(|cwd| file.strip_prefix(cwd).ok())
-        {
-            Some(rel) => rel,
-            None => file,
-        };
+        let relative_path = super::relative_to(file, base_dir);
 
         // Use forward slashes for consistent cross-platform output
         let file_str = if cfg!(windows) {
@@ -247,10 +248,44 @@ pub fn render_diff(plan: &Plan, use_color: bool) -> String {
                     output.push_str(&highlighted);
                 } else {
                     writeln!(output, "{}{}", sign, change_text).unwrap();
+                    if change.tag() != ChangeTag::Equal {
+                        write_markers(
+                            &mut output,
+                            change_text,
+                            line_hunk_group,
+                            change.tag() == ChangeTag::Delete,
+                        );
+                    }
                 }
             }
             output.push('\n');
         }
+
+        if omitted > 0 {
+            let note = format!("... {omitted} more matches in this file not shown\n\n");
+            if use_color {
+                output.push_str(&AnsiColor::DarkGray.paint(note).to_string());
+            } else {
+                output.push_str(&note);
+            }
+        }
+    }
+
+    if let Some(sample) = sample {
+        let note = format!(
+            "Showing {} of {} matches (up to {} per file). Totals: {} matches in {} files, {} paths to rename\n",
+            shown,
+            plan.matches.len(),
+            sample,
+            plan.stats.total_matches,
+            plan.stats.files_with_matches,
+            plan.paths.len()
+        );
+        if use_color {
+            output.push_str(&Style::new().bold().paint(note).to_string());
+        } else {
+            output.push_str(&note);
+        }
     }
 
     // Add rename section
@@ -329,6 +364,10 @@ mod tests {
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
         };
 
         let hunks = vec![&hunk];
@@ -384,6 +423,10 @@ mod tests {
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
         }
     }
 
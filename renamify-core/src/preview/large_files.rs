@@ -0,0 +1,109 @@
+use crate::scanner::Plan;
+use nu_ansi_term::Color as AnsiColor;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The files over `--max-filesize` whose contents were not searched, so a
+/// match inside one is never missed silently. Their names were still
+/// searched, and the ones that will be renamed are marked.
+pub(super) fn render_large_files(plan: &Plan, use_color: bool, base_dir: Option<&Path>) -> String {
+    let mut output = String::new();
+    let too_large = &plan.stats.files_skipped.too_large;
+    if too_large.is_empty() {
+        return output;
+    }
+
+    let heading = format!(
+        "Large Files Not Searched ({}, raise --max-filesize to search them):",
+        too_large.len()
+    );
+    if use_color {
+        writeln!(output, "\n{}", AnsiColor::Yellow.bold().paint(heading)).unwrap();
+    } else {
+        writeln!(output, "\n{heading}").unwrap();
+    }
+
+    for file in too_large {
+        let path = super::relative_to(&file.path, base_dir)
+            .display()
+            .to_string();
+        let name_matches = plan.paths.iter().any(|rename| rename.path == file.path);
+        let note = if name_matches {
+            format!(
+                "({}, name matches)",
+                super::summary::format_bytes(file.bytes)
+            )
+        } else {
+            format!("({})", super::summary::format_bytes(file.bytes))
+        };
+        if use_color {
+            writeln!(
+                output,
+                "  {} {}",
+                AnsiColor::Green.paint(path),
+                AnsiColor::DarkGray.paint(note)
+            )
+            .unwrap();
+        } else {
+            writeln!(output, "  {path} {note}").unwrap();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{LargeFile, Rename, RenameKind, Stats};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_large_files() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_large_files(&plan, false, None), "");
+
+        plan.stats.files_skipped.too_large = vec![
+            LargeFile {
+                path: PathBuf::from("data/dump.sql"),
+                bytes: 54 * 1024 * 1024,
+            },
+            LargeFile {
+                path: PathBuf::from("old_name.log"),
+                bytes: 12 * 1024 * 1024,
+            },
+        ];
+        plan.paths = vec![Rename {
+            path: PathBuf::from("old_name.log"),
+            new_path: PathBuf::from("new_name.log"),
+            kind: RenameKind::File,
+            coercion_applied: None,
+        }];
+        assert_eq!(
+            render_large_files(&plan, false, None),
+            concat!(
+                "\nLarge Files Not Searched (2, raise --max-filesize to search them):\n",
+                "  data/dump.sql (54.0 MB)\n",
+                "  old_name.log (12.0 MB, name matches)\n",
+            )
+        );
+    }
+}
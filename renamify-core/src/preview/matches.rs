@@ -1,12 +1,173 @@
-use crate::scanner::{Plan, RenameKind};
+use super::spans::{before_spans, marker_line};
+use super::variants::{exclude_hint, variant_groups};
+use crate::scanner::{MatchHunk, Plan, RenameKind};
 use nu_ansi_term::{Color as AnsiColor, Style};
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::path::Path;
 
+/// Matched lines shown per file before the rest are summarized
+const MAX_LINES_PER_FILE: usize = 5;
+
+/// Write one matched line with all of its matches highlighted, or underlined
+/// with `^` markers when color is off
+fn write_match_line(output: &mut String, hunks: &[&MatchHunk], use_color: bool) {
+    let first = hunks[0];
+    let Some(line_before) = first.line_before.as_deref() else {
+        // Fallback without line context
+        for hunk in hunks {
+            if use_color {
+                writeln!(
+                    output,
+                    "    {}:{}: {} ({})",
+                    hunk.line,
+                    hunk.byte_offset,
+                    Style::new()
+                        .on(AnsiColor::Yellow)
+                        .fg(AnsiColor::Black)
+                        .bold()
+                        .paint(&hunk.content),
+                    hunk.variant
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    output,
+                    "    {}:{}: {} ({})",
+                    hunk.line, hunk.byte_offset, hunk.content, hunk.variant
+                )
+                .unwrap();
+            }
+        }
+        return;
+    };
+
+    let spans = before_spans(line_before, hunks);
+
+    if use_color {
+        let prefix = format!("    {}:{}: ", first.line, first.byte_offset + 1);
+        // Same green as diff highlights
+        let highlight_style = Style::new()
+            .on(AnsiColor::Rgb(0x00, 0xA9, 0x58))
+            .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF));
+        output.push_str(&prefix);
+        let mut last_end = 0;
+        for span in spans {
+            output.push_str(&line_before[last_end..span.start]);
+            write!(
+                output,
+                "{}",
+                highlight_style.paint(&line_before[span.clone()])
+            )
+            .unwrap();
+            last_end = span.end;
+        }
+        output.push_str(&line_before[last_end..]);
+        writeln!(output).unwrap();
+    } else {
+        let prefix = format!("    {}:{}: ", first.line, first.byte_offset);
+        // Trim the line for display and shift the spans to match
+        let indent = line_before.len() - line_before.trim_start().len();
+        let line = line_before.trim();
+        let spans: Vec<_> = spans
+            .into_iter()
+            .filter(|span| span.start >= indent && span.end <= indent + line.len())
+            .map(|span| span.start - indent..span.end - indent)
+            .collect();
+        writeln!(output, "{}{}", prefix, line).unwrap();
+        let markers = marker_line(line, &spans);
+        if !markers.is_empty() {
+            writeln!(output, "{}{}", " ".repeat(prefix.len()), markers).unwrap();
+        }
+    }
+}
+
+/// Write each file's matched lines, up to `MAX_LINES_PER_FILE` per file, or
+/// whole lines until `sample` matches are shown
+fn write_file_blocks(
+    output: &mut String,
+    hunks: &[&MatchHunk],
+    use_color: bool,
+    sample: Option<usize>,
+    base_dir: Option<&Path>,
+) {
+    // Group matches by file
+    let mut file_matches: HashMap<&Path, Vec<&MatchHunk>> = HashMap::new();
+    for hunk in hunks {
+        file_matches.entry(&hunk.file).or_default().push(hunk);
+    }
+
+    // Sort files for deterministic output
+    let mut sorted_files: Vec<_> = file_matches.keys().copied().collect();
+    sorted_files.sort();
+
+    for file in sorted_files {
+        let hunks = &file_matches[&file];
+
+        // Make path relative for cleaner display
+        let file_str = super::relative_to(file, base_dir).display().to_string();
+
+        if use_color {
+            writeln!(output, "\n  {}", AnsiColor::Green.paint(&file_str)).unwrap();
+        } else {
+            writeln!(output, "\n  {}", file_str).unwrap();
+        }
+
+        // Sort hunks by line number
+        let mut sorted_hunks = hunks.clone();
+        sorted_hunks.sort_by_key(|h| (h.line, h.byte_offset));
+
+        // Show up to the first 5 matched lines per file, with every match
+        // on a line highlighted in place
+        let mut display_count = 0;
+        for (index, line_group) in sorted_hunks.chunk_by(|a, b| a.line == b.line).enumerate() {
+            let shown_enough = sample.map_or(index >= MAX_LINES_PER_FILE, |sample| {
+                display_count >= sample
+            });
+            if shown_enough {
+                break;
+            }
+            display_count += line_group.len();
+            write_match_line(output, line_group, use_color);
+        }
+
+        if hunks.len() > display_count {
+            let remaining = hunks.len() - display_count;
+            if use_color {
+                writeln!(
+                    output,
+                    "    {}",
+                    AnsiColor::DarkGray.paint(format!("... and {} more matches", remaining))
+                )
+                .unwrap();
+            } else {
+                writeln!(output, "    ... and {} more matches", remaining).unwrap();
+            }
+        }
+    }
+}
+
 /// Render search results as a focused matches view
-#[allow(clippy::too_many_lines)]
 pub fn render_matches(plan: &Plan, use_color: bool) -> String {
+    render_matches_grouped(plan, use_color, false, None, None)
+}
+
+/// Render the matches view with one section per variant, ending with a
+/// suggested command for excluding a variant
+pub fn render_matches_by_variant(plan: &Plan, use_color: bool) -> String {
+    render_matches_grouped(plan, use_color, true, None, None)
+}
+
+/// Render the matches view; `sample` replaces the per-file limit of
+/// `MAX_LINES_PER_FILE` lines with a number of matches
+#[allow(clippy::too_many_lines)]
+pub(super) fn render_matches_grouped(
+    plan: &Plan,
+    use_color: bool,
+    by_variant: bool,
+    sample: Option<usize>,
+    base_dir: Option<&Path>,
+) -> String {
     let mut output = String::new();
 
     // Header
@@ -37,124 +198,25 @@ pub fn render_matches(plan: &Plan, use_color: bool) -> String {
             writeln!(output, "Content Matches:").unwrap();
         }
 
-        // Group matches by file
-        let mut file_matches: HashMap<&Path, Vec<_>> = HashMap::new();
-        for hunk in &plan.matches {
-            file_matches.entry(&hunk.file).or_default().push(hunk);
-        }
-
-        // Sort files for deterministic output
-        let mut sorted_files: Vec<_> = file_matches.keys().copied().collect();
-        sorted_files.sort();
-
-        for file in sorted_files {
-            let hunks = &file_matches[&file];
-
-            // Make path relative for cleaner display
-            let file_str = match std::env::current_dir()
-                .ok()
-                .and_then(|cwd| file.strip_prefix(cwd).ok())
-            {
-                Some(relative_path) => relative_path.display().to_string(),
-                None => file.display().to_string(),
-            };
-
-            if use_color {
-                writeln!(output, "\n  {}", AnsiColor::Green.paint(&file_str)).unwrap();
-            } else {
-                writeln!(output, "\n  {}", file_str).unwrap();
-            }
-
-            // Sort hunks by line number
-            let mut sorted_hunks = hunks.clone();
-            sorted_hunks.sort_by_key(|h| (h.line, h.byte_offset));
-
-            // Show up to first 5 matches per file with context
-            let display_count = sorted_hunks.len().min(5);
-            for hunk in sorted_hunks.iter().take(display_count) {
-                if let Some(ref line_before) = hunk.line_before {
-                    // Highlight the match in the line
-                    let col = hunk.byte_offset as usize;
-                    let end = col + hunk.content.len();
-
-                    if use_color {
-                        write!(output, "    {}:{}: ", hunk.line, hunk.byte_offset + 1).unwrap();
-
-                        // Print the line with the match highlighted with green background
-                        // Print text before the match
-                        if col > 0 {
-                            write!(output, "{}", &line_before[..col]).unwrap();
-                        }
-
-                        // Print the highlighted match
-                        if col < line_before.len() {
-                            let actual_end = end.min(line_before.len());
-                            write!(
-                                output,
-                                "{}",
-                                Style::new()
-                                    .on(AnsiColor::Rgb(0x00, 0xA9, 0x58))  // Same green as diff highlights
-                                    .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF))
-                                    .paint(&line_before[col..actual_end])
-                            )
-                            .unwrap();
-
-                            // Print text after the match
-                            if actual_end < line_before.len() {
-                                write!(output, "{}", &line_before[actual_end..]).unwrap();
-                            }
-                        }
-                        writeln!(output).unwrap();
-                    } else {
-                        writeln!(
-                            output,
-                            "    {}:{}: {}",
-                            hunk.line,
-                            hunk.byte_offset,
-                            line_before.trim()
-                        )
-                        .unwrap();
-                    }
-                } else {
-                    // Fallback without line context
-                    if use_color {
-                        writeln!(
-                            output,
-                            "    {}:{}: {} ({})",
-                            hunk.line,
-                            hunk.byte_offset,
-                            Style::new()
-                                .on(AnsiColor::Yellow)
-                                .fg(AnsiColor::Black)
-                                .bold()
-                                .paint(&hunk.content),
-                            hunk.variant
-                        )
-                        .unwrap();
-                    } else {
-                        writeln!(
-                            output,
-                            "    {}:{}: {} ({})",
-                            hunk.line, hunk.byte_offset, hunk.content, hunk.variant
-                        )
-                        .unwrap();
-                    }
-                }
-            }
-
-            if hunks.len() > display_count {
-                let remaining = hunks.len() - display_count;
+        if by_variant {
+            for group in variant_groups(plan) {
+                let heading = format!(
+                    "{} ({}): {} matches in {} files",
+                    group.variant,
+                    group.style(),
+                    group.hunks.len(),
+                    group.files
+                );
                 if use_color {
-                    writeln!(
-                        output,
-                        "    {}",
-                        AnsiColor::DarkGray.paint(format!("... and {} more matches", remaining))
-                    )
-                    .unwrap();
+                    writeln!(output, "\n{}", AnsiColor::Magenta.bold().paint(heading)).unwrap();
                 } else {
-                    writeln!(output, "    ... and {} more matches", remaining).unwrap();
+                    writeln!(output, "\n{}", heading).unwrap();
                 }
+                write_file_blocks(&mut output, &group.hunks, use_color, sample, base_dir);
             }
+        } else {
+            let hunks: Vec<&MatchHunk> = plan.matches.iter().collect();
+            write_file_blocks(&mut output, &hunks, use_color, sample, base_dir);
         }
     }
 
@@ -192,13 +254,9 @@ pub fn render_matches(plan: &Plan, use_color: bool) -> String {
             }
 
             for rename in dirs {
-                let path_str = match std::env::current_dir()
-                    .ok()
-                    .and_then(|cwd| rename.path.strip_prefix(cwd).ok())
-                {
-                    Some(relative_path) => relative_path.display().to_string(),
-                    None => rename.path.display().to_string(),
-                };
+                let path_str = super::relative_to(&rename.path, base_dir)
+                    .display()
+                    .to_string();
 
                 if use_color {
                     writeln!(output, "    {}", AnsiColor::Green.paint(&path_str)).unwrap();
@@ -217,13 +275,9 @@ pub fn render_matches(plan: &Plan, use_color: bool) -> String {
             }
 
             for rename in files {
-                let path_str = match std::env::current_dir()
-                    .ok()
-                    .and_then(|cwd| rename.path.strip_prefix(cwd).ok())
-                {
-                    Some(relative_path) => relative_path.display().to_string(),
-                    None => rename.path.display().to_string(),
-                };
+                let path_str = super::relative_to(&rename.path, base_dir)
+                    .display()
+                    .to_string();
 
                 if use_color {
                     writeln!(output, "    📄 {}", AnsiColor::Green.paint(&path_str)).unwrap();
@@ -280,6 +334,14 @@ pub fn render_matches(plan: &Plan, use_color: bool) -> String {
         writeln!(output).unwrap();
     }
 
+    if by_variant {
+        let hint = exclude_hint(plan, &variant_groups(plan), use_color);
+        if !hint.is_empty() {
+            writeln!(output).unwrap();
+            output.push_str(&hint);
+        }
+    }
+
     output
 }
 
@@ -296,6 +358,7 @@ mod tests {
             total_matches: 3,
             matches_by_variant: HashMap::new(),
             files_with_matches: 2,
+            ..Default::default()
         };
         stats.matches_by_variant.insert("old_name".to_string(), 2);
         stats.matches_by_variant.insert("OldName".to_string(), 1);
@@ -325,6 +388,10 @@ mod tests {
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
                 MatchHunk {
                     file: PathBuf::from("/project/src/main.rs"),
@@ -342,6 +409,10 @@ mod tests {
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
                 // A match without line context
                 MatchHunk {
@@ -360,6 +431,10 @@ mod tests {
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
             ],
             paths: vec![
@@ -378,6 +453,12 @@ mod tests {
             ],
             stats,
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         }
     }
@@ -448,8 +529,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -487,6 +575,10 @@ mod tests {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             })
             .collect();
 
@@ -520,6 +612,10 @@ mod tests {
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
         });
 
         // Test match at beginning of line
@@ -539,12 +635,65 @@ mod tests {
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
         });
 
         let output = render_matches(&plan, true);
         assert!(output.contains("/project/edge.rs"));
     }
 
+    #[test]
+    fn test_render_matches_marks_every_match_on_a_line() {
+        let mut plan = create_test_plan();
+        let line = "    let old_name = old_name_fn(OldName);";
+        plan.matches = [(8, "old_name"), (19, "old_name"), (31, "OldName")]
+            .into_iter()
+            .map(|(col, content)| MatchHunk {
+                file: PathBuf::from("/project/dense.rs"),
+                line: 3,
+                byte_offset: col,
+                char_offset: col,
+                variant: content.to_string(),
+                content: content.to_string(),
+                replace: "new_name".to_string(),
+                start: col as usize,
+                end: col as usize + content.len(),
+                line_before: Some(line.to_string()),
+                line_after: None,
+                coercion_applied: None,
+                original_file: None,
+                renamed_file: None,
+                patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
+            })
+            .collect();
+
+        let output = render_matches(&plan, false);
+        assert!(output.contains(concat!(
+            "    3:8: let old_name = old_name_fn(OldName);\n",
+            "             ^^^^^^^^   ^^^^^^^^    ^^^^^^^\n",
+        )));
+        assert_eq!(output.matches("let old_name").count(), 1);
+
+        let output = render_matches(&plan, true);
+        let highlight = Style::new()
+            .on(AnsiColor::Rgb(0x00, 0xA9, 0x58))
+            .fg(AnsiColor::Rgb(0xFF, 0xFF, 0xFF));
+        assert_eq!(
+            output
+                .matches(&highlight.paint("old_name").to_string())
+                .count(),
+            2
+        );
+        assert!(output.contains(&highlight.paint("OldName").to_string()));
+    }
+
     #[test]
     fn test_render_matches_only_files() {
         let plan = Plan {
@@ -567,8 +716,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -600,8 +756,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -1,16 +1,31 @@
 mod diff;
+mod large_files;
 mod matches;
+mod near_misses;
+mod renderer;
+mod skipped;
+mod spans;
 mod summary;
+mod suspicious;
 mod table;
+mod tree;
+mod unreadable;
+mod variants;
+mod warnings;
 
 pub use diff::render_diff;
-pub use matches::render_matches;
+pub use matches::{render_matches, render_matches_by_variant};
+pub use renderer::{register_renderer, renderer, renderer_names, PreviewRenderer, RenderOptions};
+pub(crate) use summary::format_bytes;
 pub use summary::render_summary;
-pub use table::render_table;
+pub use table::{render_table, render_table_by_variant};
+pub use tree::render_tree;
 
+use crate::error::RenamifyError;
 use crate::scanner::Plan;
 use anyhow::Result;
 use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Preview {
@@ -21,6 +36,19 @@ pub enum Preview {
     None,
 }
 
+impl Preview {
+    /// Name of the format, as registered with the renderers
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::Diff => "diff",
+            Self::Matches => "matches",
+            Self::Summary => "summary",
+            Self::None => "none",
+        }
+    }
+}
+
 impl std::str::FromStr for Preview {
     type Err = String;
 
@@ -36,6 +64,27 @@ impl std::str::FromStr for Preview {
     }
 }
 
+/// How the table and matches previews group their rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    File,
+    /// One section per variant, e.g. all `OldName` matches apart from `old_name`
+    Variant,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(Self::File),
+            "variant" => Ok(Self::Variant),
+            _ => Err(format!("Invalid group-by value: {}", s)),
+        }
+    }
+}
+
 /// Determine whether to use colors based on explicit preference or terminal detection
 pub fn should_use_color_with_detector<F>(use_color: Option<bool>, is_terminal: F) -> bool
 where
@@ -64,15 +113,75 @@ pub fn render_plan_with_fixed_width(
     use_color: Option<bool>,
     fixed_width: bool,
 ) -> String {
-    let use_color = should_use_color(use_color);
-
-    match format {
-        Preview::Table => render_table(plan, use_color, fixed_width),
-        Preview::Diff => render_diff(plan, use_color),
-        Preview::Matches => render_matches(plan, use_color),
-        Preview::Summary => render_summary(plan),
-        Preview::None => String::new(), // Return empty string for no preview
+    render_plan_grouped(plan, format, use_color, fixed_width, GroupBy::File)
+}
+
+/// Render the plan, grouping the table and matches previews as requested.
+/// Other formats ignore `group_by`.
+pub fn render_plan_grouped(
+    plan: &Plan,
+    format: Preview,
+    use_color: Option<bool>,
+    fixed_width: bool,
+    group_by: GroupBy,
+) -> String {
+    render_plan_sampled(plan, format, use_color, fixed_width, group_by, None)
+}
+
+/// Render the plan, showing at most `sample` matches per file in the diff
+/// and matches previews. Totals always cover the whole plan.
+pub fn render_plan_sampled(
+    plan: &Plan,
+    format: Preview,
+    use_color: Option<bool>,
+    fixed_width: bool,
+    group_by: GroupBy,
+    sample: Option<usize>,
+) -> String {
+    render_plan_in(plan, format, use_color, fixed_width, group_by, sample, None)
+}
+
+/// Render the plan with paths shown relative to `base_dir`.
+///
+/// No renderer reads the process's current directory, so a server can render
+/// plans for several repositories at once. Paths outside `base_dir` are shown
+/// in full, as are all paths when it is `None`, which the other renderers pass.
+pub fn render_plan_in(
+    plan: &Plan,
+    format: Preview,
+    use_color: Option<bool>,
+    fixed_width: bool,
+    group_by: GroupBy,
+    sample: Option<usize>,
+    base_dir: Option<&Path>,
+) -> String {
+    let options = RenderOptions {
+        use_color: should_use_color(use_color),
+        fixed_width,
+        group_by,
+        sample,
+        base_dir,
+    };
+    // The built-in formats are always registered
+    render_plan_named(plan, format.name(), &options).unwrap_or_default()
+}
+
+/// Render the plan with the renderer registered as `name`, or nothing for
+/// `none`
+pub fn render_plan_named(plan: &Plan, name: &str, options: &RenderOptions<'_>) -> Result<String> {
+    if name.eq_ignore_ascii_case(Preview::None.name()) {
+        return Ok(String::new());
     }
+    let renderer = renderer(name)
+        .ok_or_else(|| RenamifyError::InvalidInput(format!("Invalid preview format: {}", name)))?;
+    Ok(renderer.render(plan, options))
+}
+
+/// `path` relative to `base_dir` for display, or unchanged if it lies outside
+fn relative_to<'a>(path: &'a Path, base_dir: Option<&Path>) -> &'a Path {
+    base_dir
+        .and_then(|base_dir| path.strip_prefix(base_dir).ok())
+        .unwrap_or(path)
 }
 
 /// Write plan preview to stdout
@@ -122,6 +231,10 @@ mod tests {
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
                 MatchHunk {
                     file: PathBuf::from("src/main.rs"),
@@ -139,6 +252,10 @@ mod tests {
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
             ],
             paths: vec![Rename {
@@ -152,8 +269,15 @@ mod tests {
                 total_matches: 2,
                 matches_by_variant,
                 files_with_matches: 1,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         }
     }
@@ -174,6 +298,45 @@ mod tests {
         assert!(Preview::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_render_plan_named_uses_registered_renderers() {
+        struct CountRenderer;
+
+        impl PreviewRenderer for CountRenderer {
+            fn name(&self) -> &'static str {
+                "Count"
+            }
+
+            fn render(&self, plan: &Plan, _options: &RenderOptions<'_>) -> String {
+                format!("{} matches\n", plan.matches.len())
+            }
+        }
+
+        let plan = create_test_plan();
+        let options = RenderOptions::default();
+        assert!(render_plan_named(&plan, "count", &options).is_err());
+
+        register_renderer(CountRenderer);
+        assert!(renderer_names().contains(&"count".to_string()));
+        assert_eq!(
+            render_plan_named(&plan, "COUNT", &options).unwrap(),
+            "2 matches\n"
+        );
+        assert_eq!(
+            render_plan_named(&plan, "diff", &options).unwrap(),
+            render_plan_in(
+                &plan,
+                Preview::Diff,
+                Some(false),
+                false,
+                GroupBy::File,
+                None,
+                None
+            )
+        );
+        assert_eq!(render_plan_named(&plan, "none", &options).unwrap(), "");
+    }
+
     #[test]
     fn test_render_table_no_color() {
         let plan = create_test_plan();
@@ -249,6 +412,10 @@ mod tests {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             }],
             paths: vec![],
             stats: Stats {
@@ -256,8 +423,15 @@ mod tests {
                 total_matches: 1,
                 matches_by_variant,
                 files_with_matches: 1,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -294,6 +468,26 @@ mod tests {
         assert!(result.contains("file: old_name.txt -> new_name.txt"));
     }
 
+    #[test]
+    fn test_render_summary_scan_stats() {
+        let mut plan = create_test_plan();
+        plan.stats.files_scanned = 12;
+        plan.stats.bytes_scanned = 3 * 1024 * 1024 / 2;
+        plan.stats.files_skipped.binary = 2;
+        plan.stats.files_skipped.excluded = 5;
+        plan.stats.timings.walk_ms = 1.25;
+        plan.stats.timings.match_ms = 4.0;
+
+        let result = render_summary(&plan);
+
+        assert!(result.contains("Scanned: 12 files, 1.5 MB (skipped: 2 binary, 5 excluded)"));
+        assert!(result.contains("Timing: walk 1.2ms, read 0.0ms, match 4.0ms, rename-plan 0.0ms"));
+
+        // Plans without scan statistics keep the short header
+        plan.stats.files_scanned = 0;
+        assert!(!render_summary(&plan).contains("Scanned:"));
+    }
+
     #[test]
     fn test_empty_plan() {
         let plan = Plan {
@@ -311,8 +505,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -0,0 +1,102 @@
+use crate::scanner::Plan;
+use nu_ansi_term::Color as AnsiColor;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The near-miss spellings found with `--fuzzy`, which are not part of the
+/// plan until confirmed. Empty for plans built without `--fuzzy`.
+pub(super) fn render_near_misses(plan: &Plan, use_color: bool, base_dir: Option<&Path>) -> String {
+    let mut output = String::new();
+    if plan.near_misses.is_empty() {
+        return output;
+    }
+
+    let heading = format!(
+        "Near Misses ({}, not in the plan until confirmed):",
+        plan.near_misses.len()
+    );
+    if use_color {
+        writeln!(output, "\n{}", AnsiColor::Magenta.bold().paint(heading)).unwrap();
+    } else {
+        writeln!(output, "\n{heading}").unwrap();
+    }
+
+    for hunk in &plan.near_misses {
+        let file = super::relative_to(&hunk.file, base_dir)
+            .display()
+            .to_string();
+        let location = format!("{file}:{}", hunk.line);
+        let change = if hunk.replace.is_empty() {
+            hunk.content.clone()
+        } else {
+            format!("{} -> {}", hunk.content, hunk.replace)
+        };
+        if use_color {
+            writeln!(output, "  {} {change}", AnsiColor::Green.paint(location)).unwrap();
+        } else {
+            writeln!(output, "  {location} {change}").unwrap();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{MatchHunk, Stats};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_near_misses() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_near_misses(&plan, false, None), "");
+
+        plan.near_misses = vec![MatchHunk {
+            file: PathBuf::from("src/lib.rs"),
+            line: 4,
+            byte_offset: 0,
+            char_offset: 0,
+            variant: "old_nmae".to_string(),
+            content: "old_nmae".to_string(),
+            replace: "new_name".to_string(),
+            start: 0,
+            end: 8,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        }];
+        assert_eq!(
+            render_near_misses(&plan, false, None),
+            concat!(
+                "\nNear Misses (1, not in the plan until confirmed):\n",
+                "  src/lib.rs:4 old_nmae -> new_name\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Preview formats looked up by name, so other crates can add their own.
+//!
+//! The built-in formats (`table`, `diff`, `matches` and `summary`) are
+//! registered up front. Registering a renderer under an existing name replaces
+//! it, for built-in formats too.
+
+use super::{
+    diff, large_files, matches, near_misses, skipped, summary, suspicious, table, unreadable,
+    warnings, GroupBy,
+};
+use crate::scanner::Plan;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Settings shared by every preview format. Renderers ignore the ones that
+/// don't apply to them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions<'a> {
+    pub use_color: bool,
+    /// Keep table columns at a fixed width instead of fitting the terminal
+    pub fixed_width: bool,
+    pub group_by: GroupBy,
+    /// Most matches shown per file
+    pub sample: Option<usize>,
+    /// Paths are shown relative to this directory
+    pub base_dir: Option<&'a Path>,
+}
+
+/// A preview format
+pub trait PreviewRenderer: Send + Sync {
+    /// Name the format is selected by, e.g. `diff`
+    fn name(&self) -> &str;
+
+    fn render(&self, plan: &Plan, options: &RenderOptions<'_>) -> String;
+}
+
+static RENDERERS: LazyLock<RwLock<BTreeMap<String, Arc<dyn PreviewRenderer>>>> =
+    LazyLock::new(|| {
+        let builtin: [Arc<dyn PreviewRenderer>; 4] = [
+            Arc::new(TableRenderer),
+            Arc::new(DiffRenderer),
+            Arc::new(MatchesRenderer),
+            Arc::new(SummaryRenderer),
+        ];
+        let renderers = builtin
+            .into_iter()
+            .map(|renderer| (renderer.name().to_lowercase(), renderer))
+            .collect();
+        RwLock::new(renderers)
+    });
+
+/// Make `renderer` available under its name, replacing any renderer already
+/// registered under it. Names are case-insensitive.
+pub fn register_renderer<R: PreviewRenderer + 'static>(renderer: R) {
+    let name = renderer.name().to_lowercase();
+    RENDERERS.write().unwrap().insert(name, Arc::new(renderer));
+}
+
+/// The renderer registered under `name`
+pub fn renderer(name: &str) -> Option<Arc<dyn PreviewRenderer>> {
+    RENDERERS.read().unwrap().get(&name.to_lowercase()).cloned()
+}
+
+/// Names of all registered renderers, sorted
+pub fn renderer_names() -> Vec<String> {
+    RENDERERS.read().unwrap().keys().cloned().collect()
+}
+
+/// Warnings, near misses, suspicious renames, skipped matches, large files and
+/// unreadable files, which the built-in formats list after the preview itself
+fn with_notes(preview: String, plan: &Plan, options: &RenderOptions<'_>) -> String {
+    preview
+        + &warnings::render_warnings(plan, options.use_color)
+        + &near_misses::render_near_misses(plan, options.use_color, options.base_dir)
+        + &suspicious::render_suspicious(plan, options.use_color, options.base_dir)
+        + &skipped::render_skipped(plan, options.use_color, options.base_dir)
+        + &large_files::render_large_files(plan, options.use_color, options.base_dir)
+        + &unreadable::render_unreadable(plan, options.use_color, options.base_dir)
+}
+
+struct TableRenderer;
+
+impl PreviewRenderer for TableRenderer {
+    fn name(&self) -> &'static str {
+        "table"
+    }
+
+    fn render(&self, plan: &Plan, options: &RenderOptions<'_>) -> String {
+        let preview = match options.group_by {
+            GroupBy::Variant => {
+                table::render_table_by_variant(plan, options.use_color, options.fixed_width)
+            },
+            GroupBy::File => table::render_table_in(
+                plan,
+                options.use_color,
+                options.fixed_width,
+                options.base_dir,
+            ),
+        };
+        with_notes(preview, plan, options)
+    }
+}
+
+struct DiffRenderer;
+
+impl PreviewRenderer for DiffRenderer {
+    fn name(&self) -> &'static str {
+        "diff"
+    }
+
+    fn render(&self, plan: &Plan, options: &RenderOptions<'_>) -> String {
+        let preview =
+            diff::render_diff_sampled(plan, options.use_color, options.sample, options.base_dir);
+        with_notes(preview, plan, options)
+    }
+}
+
+struct MatchesRenderer;
+
+impl PreviewRenderer for MatchesRenderer {
+    fn name(&self) -> &'static str {
+        "matches"
+    }
+
+    fn render(&self, plan: &Plan, options: &RenderOptions<'_>) -> String {
+        let preview = matches::render_matches_grouped(
+            plan,
+            options.use_color,
+            options.group_by == GroupBy::Variant,
+            options.sample,
+            options.base_dir,
+        );
+        with_notes(preview, plan, options)
+    }
+}
+
+struct SummaryRenderer;
+
+impl PreviewRenderer for SummaryRenderer {
+    fn name(&self) -> &'static str {
+        "summary"
+    }
+
+    fn render(&self, plan: &Plan, options: &RenderOptions<'_>) -> String {
+        with_notes(
+            summary::render_summary_in(plan, options.base_dir),
+            plan,
+            options,
+        )
+    }
+}
@@ -0,0 +1,99 @@
+use crate::scanner::Plan;
+use nu_ansi_term::Color as AnsiColor;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The matches that filters left out of the plan, each with its reason.
+/// Empty unless the plan was built with `--show-skipped`.
+pub(super) fn render_skipped(plan: &Plan, use_color: bool, base_dir: Option<&Path>) -> String {
+    let mut output = String::new();
+    if plan.skipped.is_empty() {
+        return output;
+    }
+
+    let heading = format!("Skipped Matches ({}):", plan.skipped.len());
+    if use_color {
+        writeln!(output, "\n{}", AnsiColor::Yellow.bold().paint(heading)).unwrap();
+    } else {
+        writeln!(output, "\n{heading}").unwrap();
+    }
+
+    for skipped in &plan.skipped {
+        let file = super::relative_to(&skipped.file, base_dir)
+            .display()
+            .to_string();
+        let location = match skipped.line {
+            Some(line) => format!("{file}:{line}"),
+            None => file,
+        };
+        let reason = format!("({})", skipped.reason);
+        if use_color {
+            writeln!(
+                output,
+                "  {} {} {}",
+                AnsiColor::Green.paint(location),
+                skipped.content,
+                AnsiColor::DarkGray.paint(reason)
+            )
+            .unwrap();
+        } else {
+            writeln!(output, "  {location} {} {reason}", skipped.content).unwrap();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{SkipReason, SkippedMatch, Stats};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_skipped() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_skipped(&plan, false, None), "");
+
+        plan.skipped = vec![
+            SkippedMatch {
+                file: PathBuf::from("src/lib.rs"),
+                line: Some(3),
+                content: "OldName".to_string(),
+                reason: SkipReason::ExcludeMatch,
+            },
+            SkippedMatch {
+                file: PathBuf::from("logo.png"),
+                line: None,
+                content: "old_name".to_string(),
+                reason: SkipReason::Binary,
+            },
+        ];
+        assert_eq!(
+            render_skipped(&plan, false, None),
+            concat!(
+                "\nSkipped Matches (2):\n",
+                "  src/lib.rs:3 OldName (excluded by --exclude-match)\n",
+                "  logo.png old_name (binary file)\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::scanner::MatchHunk;
+use std::ops::Range;
+
+/// Byte ranges of the matched text in the original line, one per hunk that
+/// actually sits at its recorded column
+pub(super) fn before_spans(line: &str, hunks: &[&MatchHunk]) -> Vec<Range<usize>> {
+    let mut sorted = hunks.to_vec();
+    sorted.sort_by_key(|h| h.byte_offset);
+
+    sorted
+        .iter()
+        .filter_map(|hunk| span_at(line, hunk.byte_offset as usize, &hunk.content))
+        .collect()
+}
+
+/// Byte ranges of the replacement text in the rewritten line. Each earlier
+/// replacement shifts the columns of the ones after it.
+pub(super) fn after_spans(line: &str, hunks: &[&MatchHunk]) -> Vec<Range<usize>> {
+    let mut sorted = hunks.to_vec();
+    sorted.sort_by_key(|h| h.byte_offset);
+
+    let mut shift: isize = 0;
+    let mut spans = Vec::new();
+    for hunk in sorted {
+        let col = (hunk.byte_offset as usize).checked_add_signed(shift);
+        shift += isize::try_from(hunk.replace.len()).unwrap()
+            - isize::try_from(hunk.content.len()).unwrap();
+        if let Some(span) = col.and_then(|col| span_at(line, col, &hunk.replace)) {
+            spans.push(span);
+        }
+    }
+    spans
+}
+
+fn span_at(line: &str, col: usize, text: &str) -> Option<Range<usize>> {
+    (!text.is_empty() && line.get(col..)?.starts_with(text)).then(|| col..col + text.len())
+}
+
+/// A line of `^` markers under the given spans, for previews without color.
+/// Tabs are kept so the markers stay aligned with tab-indented lines.
+pub(super) fn marker_line(line: &str, spans: &[Range<usize>]) -> String {
+    let Some(last_end) = spans.iter().map(|span| span.end).max() else {
+        return String::new();
+    };
+
+    line.char_indices()
+        .take_while(|(offset, _)| *offset < last_end)
+        .map(|(offset, ch)| {
+            if spans.iter().any(|span| span.contains(&offset)) {
+                '^'
+            } else if ch == '\t' {
+                '\t'
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(byte_offset: u32, content: &str, replace: &str) -> MatchHunk {
+        MatchHunk::for_test("test.rs", 1, byte_offset, content, replace)
+    }
+
+    #[test]
+    fn test_spans_and_markers_for_dense_line() {
+        let before = "\tlet old_name = old_name_fn(OldName);";
+        let after = "\tlet new_title = new_title_fn(NewTitle);";
+        let h1 = hunk(5, "old_name", "new_title");
+        let h2 = hunk(16, "old_name", "new_title");
+        let h3 = hunk(28, "OldName", "NewTitle");
+        let hunks = vec![&h3, &h1, &h2];
+
+        let spans = before_spans(before, &hunks);
+        assert_eq!(spans, vec![5..13, 16..24, 28..35]);
+        assert_eq!(
+            marker_line(before, &spans),
+            "\t    ^^^^^^^^   ^^^^^^^^    ^^^^^^^"
+        );
+
+        let spans = after_spans(after, &hunks);
+        assert_eq!(spans, vec![5..14, 17..26, 30..38]);
+        assert_eq!(
+            marker_line(after, &spans),
+            "\t    ^^^^^^^^^   ^^^^^^^^^    ^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn test_spans_skip_hunks_not_at_their_column() {
+        let h1 = hunk(0, "old_name", "new_name");
+        let h2 = hunk(40, "old_name", "new_name");
+        assert_eq!(before_spans("old_name", &[&h1, &h2]), vec![0..8]);
+        assert_eq!(before_spans("other", &[&h1]), Vec::<Range<usize>>::new());
+        assert_eq!(marker_line("other", &[]), "");
+    }
+
+    #[test]
+    fn test_marker_line_counts_characters() {
+        let line = "é = old_name";
+        let h = hunk(5, "old_name", "new_name");
+        let spans = before_spans(line, &[&h]);
+        assert_eq!(marker_line(line, &spans), "    ^^^^^^^^");
+    }
+}
@@ -1,10 +1,70 @@
-use crate::scanner::{Plan, RenameKind};
+use crate::scanner::{Plan, RenameKind, Stats};
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::path::Path;
 
+/// Scan statistics, omitted for plans that carry none (older plan files)
+fn write_scan_stats(output: &mut String, stats: &Stats) {
+    if stats.files_scanned == 0 {
+        return;
+    }
+
+    write!(
+        output,
+        "Scanned: {} files, {}",
+        stats.files_scanned,
+        format_bytes(stats.bytes_scanned)
+    )
+    .unwrap();
+    let skipped = &stats.files_skipped;
+    let reasons: Vec<String> = [
+        (skipped.binary, "binary"),
+        (skipped.excluded, "excluded"),
+        (skipped.unreadable, "unreadable"),
+        (skipped.too_large.len(), "too large"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, reason)| format!("{count} {reason}"))
+    .collect();
+    if !reasons.is_empty() {
+        write!(output, " (skipped: {})", reasons.join(", ")).unwrap();
+    }
+    writeln!(output).unwrap();
+
+    let timings = &stats.timings;
+    if timings.walk_ms + timings.read_ms + timings.match_ms + timings.rename_plan_ms > 0.0 {
+        writeln!(
+            output,
+            "Timing: walk {:.1}ms, read {:.1}ms, match {:.1}ms, rename-plan {:.1}ms",
+            timings.walk_ms, timings.read_ms, timings.match_ms, timings.rename_plan_ms
+        )
+        .unwrap();
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
 /// Render plan as AI-friendly summary format
 pub fn render_summary(plan: &Plan) -> String {
+    render_summary_in(plan, None)
+}
+
+/// Render the summary with paths shown relative to `base_dir`
+pub(super) fn render_summary_in(plan: &Plan, base_dir: Option<&Path>) -> String {
     let mut output = String::new();
 
     // Header with basic info
@@ -20,6 +80,7 @@ pub fn render_summary(plan: &Plan) -> String {
     writeln!(output, "Matches: {}", plan.stats.total_matches).unwrap();
     writeln!(output, "Files: {}", plan.stats.files_with_matches).unwrap();
     writeln!(output, "Paths: {}", plan.paths.len()).unwrap();
+    write_scan_stats(&mut output, &plan.stats);
     writeln!(output).unwrap();
 
     // Content changes grouped by file
@@ -45,13 +106,7 @@ pub fn render_summary(plan: &Plan) -> String {
             let total_matches: usize = variant_counts.values().sum();
 
             // Make path relative for cleaner display
-            let file_str = match std::env::current_dir()
-                .ok()
-                .and_then(|cwd| file.strip_prefix(cwd).ok())
-            {
-                Some(relative_path) => relative_path.display().to_string(),
-                None => file.display().to_string(),
-            };
+            let file_str = super::relative_to(file, base_dir).display().to_string();
 
             write!(output, "{}: {} matches", file_str, total_matches).unwrap();
 
@@ -81,26 +136,18 @@ pub fn render_summary(plan: &Plan) -> String {
             };
 
             // Make paths relative for cleaner display
-            let from_str = match std::env::current_dir()
-                .ok()
-                .and_then(|cwd| rename.path.strip_prefix(cwd).ok())
-            {
-                Some(relative_path) => relative_path.display().to_string(),
-                None => rename.path.display().to_string(),
-            };
+            let from_str = super::relative_to(&rename.path, base_dir)
+                .display()
+                .to_string();
 
             if is_search {
                 // For search, just show the matching file/dir
                 writeln!(output, "{}: {}", kind, from_str).unwrap();
             } else {
                 // For plan, show the rename
-                let to_str = match std::env::current_dir()
-                    .ok()
-                    .and_then(|cwd| rename.new_path.strip_prefix(cwd).ok())
-                {
-                    Some(relative_path) => relative_path.display().to_string(),
-                    None => rename.new_path.display().to_string(),
-                };
+                let to_str = super::relative_to(&rename.new_path, base_dir)
+                    .display()
+                    .to_string();
                 writeln!(output, "{}: {} -> {}", kind, from_str, to_str).unwrap();
             }
         }
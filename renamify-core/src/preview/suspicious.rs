@@ -0,0 +1,94 @@
+use crate::scanner::Plan;
+use nu_ansi_term::Color as AnsiColor;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Renames that would move a path into an ignored or protected one, which are
+/// not part of the plan until confirmed, with where each ends up and why
+pub(super) fn render_suspicious(plan: &Plan, use_color: bool, base_dir: Option<&Path>) -> String {
+    let mut output = String::new();
+    if plan.suspicious_renames.is_empty() {
+        return output;
+    }
+
+    let heading = format!(
+        "Suspicious Renames ({}, into ignored or protected paths, not in the plan until confirmed):",
+        plan.suspicious_renames.len()
+    );
+    if use_color {
+        writeln!(output, "\n{}", AnsiColor::Yellow.bold().paint(heading)).unwrap();
+    } else {
+        writeln!(output, "\n{heading}").unwrap();
+    }
+
+    for suspicious in &plan.suspicious_renames {
+        let change = format!(
+            "{} -> {}",
+            super::relative_to(&suspicious.rename.path, base_dir).display(),
+            super::relative_to(&suspicious.destination, base_dir).display()
+        );
+        if use_color {
+            writeln!(
+                output,
+                "  {} {}",
+                AnsiColor::Green.paint(change),
+                AnsiColor::DarkGray.paint(format!("({})", suspicious.reason))
+            )
+            .unwrap();
+        } else {
+            writeln!(output, "  {change} ({})", suspicious.reason).unwrap();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Rename, RenameKind, Stats};
+    use crate::suspicious::SuspiciousRename;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_suspicious() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "src".to_string(),
+            replace: "target".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_suspicious(&plan, false, None), "");
+
+        plan.suspicious_renames = vec![SuspiciousRename {
+            rename: Rename {
+                path: PathBuf::from("/repo/src"),
+                new_path: PathBuf::from("/repo/target"),
+                kind: RenameKind::Dir,
+                coercion_applied: None,
+            },
+            destination: PathBuf::from("/repo/target"),
+            reason: "matches the protected pattern `target`".to_string(),
+        }];
+        assert_eq!(
+            render_suspicious(&plan, false, Some(Path::new("/repo"))),
+            concat!(
+                "\nSuspicious Renames (1, into ignored or protected paths, not in the plan until confirmed):\n",
+                "  src -> target (matches the protected pattern `target`)\n",
+            )
+        );
+    }
+}
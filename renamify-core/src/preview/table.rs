@@ -1,3 +1,4 @@
+use super::variants::{exclude_hint, variant_groups};
 use crate::scanner::{Plan, RenameKind};
 use comfy_table::{Cell, Color, ColumnConstraint, ContentArrangement, Table, Width};
 use std::collections::HashMap;
@@ -6,6 +7,16 @@ use std::path::Path;
 
 /// Render plan as a table with optional fixed column widths
 pub fn render_table(plan: &Plan, use_color: bool, fixed_table_width: bool) -> String {
+    render_table_in(plan, use_color, fixed_table_width, None)
+}
+
+/// Render the table with paths shown relative to `base_dir`
+pub(super) fn render_table_in(
+    plan: &Plan,
+    use_color: bool,
+    fixed_table_width: bool,
+    base_dir: Option<&Path>,
+) -> String {
     let mut table = Table::new();
 
     // Set content arrangement and constraints based on fixed width parameter
@@ -69,13 +80,7 @@ pub fn render_table(plan: &Plan, use_color: bool, fixed_table_width: bool) -> St
     for file in sorted_files {
         let (count, variant_counts) = &file_stats[&file];
         // Make path relative to current directory for cleaner display
-        let file_str = match std::env::current_dir()
-            .ok()
-            .and_then(|cwd| file.strip_prefix(cwd).ok())
-        {
-            Some(relative_path) => relative_path.display().to_string(),
-            None => file.display().to_string(),
-        };
+        let file_str = super::relative_to(file, base_dir).display().to_string();
         // Show variants with their per-file counts in parentheses
         let mut variants_with_counts: Vec<String> = variant_counts
             .iter()
@@ -105,13 +110,9 @@ pub fn render_table(plan: &Plan, use_color: bool, fixed_table_width: bool) -> St
     // Add rename rows (root directory renames should not be in plans unless explicitly requested)
     for rename in &plan.paths {
         // Make paths relative to current directory for cleaner display
-        let from_str = match std::env::current_dir()
-            .ok()
-            .and_then(|cwd| rename.path.strip_prefix(cwd).ok())
-        {
-            Some(relative_path) => relative_path.display().to_string(),
-            None => rename.path.display().to_string(),
-        };
+        let from_str = super::relative_to(&rename.path, base_dir)
+            .display()
+            .to_string();
 
         let kind_str = match rename.kind {
             RenameKind::File => "File",
@@ -132,13 +133,9 @@ pub fn render_table(plan: &Plan, use_color: bool, fixed_table_width: bool) -> St
             }
         } else {
             // For plan, show the rename with arrow
-            let to_str = match std::env::current_dir()
-                .ok()
-                .and_then(|cwd| rename.new_path.strip_prefix(cwd).ok())
-            {
-                Some(relative_path) => relative_path.display().to_string(),
-                None => rename.new_path.display().to_string(),
-            };
+            let to_str = super::relative_to(&rename.new_path, base_dir)
+                .display()
+                .to_string();
 
             if use_color {
                 table.add_row(vec![
@@ -183,3 +180,73 @@ pub fn render_table(plan: &Plan, use_color: bool, fixed_table_width: bool) -> St
 
     table.to_string()
 }
+
+/// Render one row per variant with its style, match count and file count,
+/// followed by a suggested command for excluding a variant
+pub fn render_table_by_variant(plan: &Plan, use_color: bool, fixed_table_width: bool) -> String {
+    let mut table = Table::new();
+    if fixed_table_width || !io::stdout().is_terminal() {
+        table.set_content_arrangement(ContentArrangement::Disabled);
+    } else {
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+    }
+    if use_color {
+        table.enforce_styling();
+        table.set_header(vec![
+            Cell::new("Variant").fg(Color::Cyan),
+            Cell::new("Style").fg(Color::Cyan),
+            Cell::new("Matches").fg(Color::Cyan),
+            Cell::new("Files").fg(Color::Cyan),
+        ]);
+    } else {
+        table.set_header(vec!["Variant", "Style", "Matches", "Files"]);
+    }
+
+    let groups = variant_groups(plan);
+    for group in &groups {
+        if use_color {
+            table.add_row(vec![
+                Cell::new(group.variant),
+                Cell::new(group.style()).fg(Color::Green),
+                Cell::new(group.hunks.len().to_string()).fg(Color::Yellow),
+                Cell::new(group.files.to_string()),
+            ]);
+        } else {
+            table.add_row(vec![
+                group.variant,
+                group.style(),
+                &group.hunks.len().to_string(),
+                &group.files.to_string(),
+            ]);
+        }
+    }
+
+    let totals = vec![
+        "TOTALS".to_string(),
+        format!("{} paths", plan.paths.len()),
+        plan.stats.total_matches.to_string(),
+        plan.stats.files_with_matches.to_string(),
+    ];
+    if use_color {
+        table.add_row(vec![
+            Cell::new("─────────").fg(Color::DarkGrey);
+            4
+        ]);
+        table.add_row(vec![
+            Cell::new(&totals[0]).fg(Color::Cyan),
+            Cell::new(&totals[1]).fg(Color::White),
+            Cell::new(&totals[2]).fg(Color::Yellow),
+            Cell::new(&totals[3]).fg(Color::White),
+        ]);
+    } else {
+        table.add_row(vec!["─────────"; 4]);
+        table.add_row(totals);
+    }
+
+    let hint = exclude_hint(plan, &groups, use_color);
+    if hint.is_empty() {
+        table.to_string()
+    } else {
+        format!("{table}\n\n{hint}")
+    }
+}
@@ -0,0 +1,180 @@
+use crate::scanner::{Plan, RenameKind};
+use nu_ansi_term::Color as AnsiColor;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+struct TreeNode {
+    children: BTreeMap<String, Self>,
+    /// Name before the rename, for renamed entries
+    was: Option<String>,
+    edits: usize,
+    is_dir: bool,
+}
+
+/// The paths `plan` touches, laid out like `tree` as they will be after it is
+/// applied.
+///
+/// Renamed entries show their old name, and edited files how many edits they
+/// get; everything else in the repository is left out.
+pub fn render_tree(plan: &Plan, use_color: bool, base_dir: Option<&Path>) -> String {
+    let renames: HashMap<&Path, &Path> = plan
+        .paths
+        .iter()
+        .map(|rename| (rename.path.as_path(), rename.new_path.as_path()))
+        .collect();
+
+    let mut root = TreeNode::default();
+    for rename in &plan.paths {
+        let node = insert(&mut root, &projected(&rename.path, &renames), base_dir);
+        node.was = rename
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        node.is_dir = rename.kind == RenameKind::Dir;
+    }
+    for hunk in &plan.matches {
+        insert(&mut root, &projected(&hunk.file, &renames), base_dir).edits += 1;
+    }
+
+    let mut output = String::new();
+    if root.children.is_empty() {
+        return output;
+    }
+    writeln!(output, ".").unwrap();
+    render_children(&root, "", use_color, &mut output);
+    output
+}
+
+/// Where `path` ends up once every rename in the plan, including those of
+/// its parent directories, has been applied
+fn projected(path: &Path, renames: &HashMap<&Path, &Path>) -> PathBuf {
+    let target = renames.get(path).copied().unwrap_or(path);
+    match (target.parent(), target.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            projected(parent, renames).join(name)
+        },
+        _ => target.to_path_buf(),
+    }
+}
+
+/// The node for `path`, creating it and the directories above it
+fn insert<'a>(root: &'a mut TreeNode, path: &Path, base_dir: Option<&Path>) -> &'a mut TreeNode {
+    let path = super::relative_to(path, base_dir);
+    let mut node = root;
+    for component in path.components() {
+        node.is_dir = true;
+        node = node
+            .children
+            .entry(component.as_os_str().to_string_lossy().into_owned())
+            .or_default();
+    }
+    node
+}
+
+fn render_children(node: &TreeNode, prefix: &str, use_color: bool, output: &mut String) {
+    let count = node.children.len();
+    for (index, (name, child)) in node.children.iter().enumerate() {
+        let last = index + 1 == count;
+        let branch = if last { "└── " } else { "├── " };
+        writeln!(output, "{prefix}{branch}{}", label(name, child, use_color)).unwrap();
+        let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+        render_children(child, &prefix, use_color, output);
+    }
+}
+
+fn label(name: &str, node: &TreeNode, use_color: bool) -> String {
+    let suffix = if node.is_dir { "/" } else { "" };
+    let mut label = match &node.was {
+        Some(was) if use_color => format!(
+            "{} → {}",
+            AnsiColor::Red.paint(format!("{was}{suffix}")),
+            AnsiColor::Green.paint(format!("{name}{suffix}"))
+        ),
+        Some(was) => format!("{was}{suffix} → {name}{suffix}"),
+        None => format!("{name}{suffix}"),
+    };
+    if node.edits > 0 {
+        let edits = match node.edits {
+            1 => " (1 edit)".to_string(),
+            count => format!(" ({count} edits)"),
+        };
+        if use_color {
+            write!(label, "{}", AnsiColor::DarkGray.paint(edits)).unwrap();
+        } else {
+            label.push_str(&edits);
+        }
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{MatchHunk, Rename, Stats};
+
+    fn hunk(file: &str) -> MatchHunk {
+        MatchHunk::for_test(file, 1, 0, "old_name", "new_name")
+    }
+
+    fn rename(path: &str, new_path: &str, kind: RenameKind) -> Rename {
+        Rename {
+            path: PathBuf::from(path),
+            new_path: PathBuf::from(new_path),
+            kind,
+            coercion_applied: None,
+        }
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_tree(&plan, false, None), "");
+
+        plan.matches = vec![
+            hunk("/repo/src/old_name/old_name.rs"),
+            hunk("/repo/src/old_name/old_name.rs"),
+            hunk("/repo/src/old_name/util.rs"),
+            hunk("/repo/README.md"),
+        ];
+        plan.paths = vec![
+            rename("/repo/src/old_name", "/repo/src/new_name", RenameKind::Dir),
+            rename(
+                "/repo/src/old_name/old_name.rs",
+                "/repo/src/old_name/new_name.rs",
+                RenameKind::File,
+            ),
+        ];
+        assert_eq!(
+            render_tree(&plan, false, Some(Path::new("/repo"))),
+            concat!(
+                ".\n",
+                "├── README.md (1 edit)\n",
+                "└── src/\n",
+                "    └── old_name/ → new_name/\n",
+                "        ├── old_name.rs → new_name.rs (2 edits)\n",
+                "        └── util.rs (1 edit)\n",
+            )
+        );
+    }
+}
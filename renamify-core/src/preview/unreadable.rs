@@ -0,0 +1,86 @@
+use crate::scanner::Plan;
+use nu_ansi_term::Color as AnsiColor;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The files that could not be read, so a match inside one is never missed
+/// silently, with the error reading each
+pub(super) fn render_unreadable(plan: &Plan, use_color: bool, base_dir: Option<&Path>) -> String {
+    let mut output = String::new();
+    let unreadable = &plan.stats.files_skipped.unreadable_files;
+    if unreadable.is_empty() {
+        return output;
+    }
+
+    let heading = format!(
+        "Unreadable Files ({}, any matches in them are not in the plan):",
+        unreadable.len()
+    );
+    if use_color {
+        writeln!(output, "\n{}", AnsiColor::Red.bold().paint(heading)).unwrap();
+    } else {
+        writeln!(output, "\n{heading}").unwrap();
+    }
+
+    for file in unreadable {
+        let path = super::relative_to(&file.path, base_dir)
+            .display()
+            .to_string();
+        if use_color {
+            writeln!(
+                output,
+                "  {} {}",
+                AnsiColor::Green.paint(path),
+                AnsiColor::DarkGray.paint(format!("({})", file.error))
+            )
+            .unwrap();
+        } else {
+            writeln!(output, "  {path} ({})", file.error).unwrap();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Stats, UnreadableFile};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_unreadable() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_unreadable(&plan, false, None), "");
+
+        plan.stats.files_skipped.unreadable_files = vec![UnreadableFile {
+            path: PathBuf::from("/repo/secrets/old_name.env"),
+            error: "Permission denied (os error 13)".to_string(),
+        }];
+        assert_eq!(
+            render_unreadable(&plan, false, Some(Path::new("/repo"))),
+            concat!(
+                "\nUnreadable Files (1, any matches in them are not in the plan):\n",
+                "  secrets/old_name.env (Permission denied (os error 13))\n",
+            )
+        );
+    }
+}
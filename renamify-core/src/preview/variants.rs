@@ -0,0 +1,145 @@
+use crate::case_model::{detect_style, Style};
+use crate::scanner::{MatchHunk, Plan};
+use nu_ansi_term::Color as AnsiColor;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// All matches of one variant, e.g. every `OldName` in the plan
+pub(super) struct VariantGroup<'a> {
+    pub variant: &'a str,
+    pub hunks: Vec<&'a MatchHunk>,
+    pub files: usize,
+}
+
+impl VariantGroup<'_> {
+    /// Case style of the variant, named as in `--exclude-styles`
+    pub fn style(&self) -> &'static str {
+        detect_style(self.variant).map_or("mixed", Style::name)
+    }
+}
+
+/// Group the plan's matches by variant, most frequent first
+pub(super) fn variant_groups(plan: &Plan) -> Vec<VariantGroup<'_>> {
+    let mut by_variant: HashMap<&str, Vec<&MatchHunk>> = HashMap::new();
+    for hunk in &plan.matches {
+        by_variant.entry(&hunk.variant).or_default().push(hunk);
+    }
+
+    let mut groups: Vec<VariantGroup> = by_variant
+        .into_iter()
+        .map(|(variant, hunks)| {
+            let files = hunks
+                .iter()
+                .map(|hunk| hunk.file.as_path())
+                .collect::<BTreeSet<&Path>>()
+                .len();
+            VariantGroup {
+                variant,
+                hunks,
+                files,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.hunks
+            .len()
+            .cmp(&a.hunks.len())
+            .then_with(|| a.variant.cmp(b.variant))
+    });
+    groups
+}
+
+/// A follow-up command that leaves one variant unchanged, suggested for the
+/// least frequent variant since that is usually the one worth a second look
+pub(super) fn exclude_hint(plan: &Plan, groups: &[VariantGroup], use_color: bool) -> String {
+    let Some(rarest) = groups.last().filter(|_| groups.len() > 1) else {
+        return String::new();
+    };
+
+    let replace = if plan.replace.is_empty() {
+        "<NEW>".to_string()
+    } else {
+        shell_quote(&plan.replace)
+    };
+    let command = format!(
+        "renamify plan {} {} --exclude-match {}",
+        shell_quote(&plan.search),
+        replace,
+        shell_quote(rarest.variant)
+    );
+    let command = if use_color {
+        AnsiColor::Cyan.paint(command).to_string()
+    } else {
+        command
+    };
+    format!("To leave a variant unchanged, exclude it with --exclude-match:\n  {command}\n")
+}
+
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.' | '/'))
+    {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Stats;
+
+    fn hunk(file: &str, variant: &str) -> MatchHunk {
+        MatchHunk::for_test(file, 1, 0, variant, "")
+    }
+
+    #[test]
+    fn test_variant_groups_and_exclude_hint() {
+        let plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "old name".to_string(),
+            replace: String::new(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![
+                hunk("a.rs", "old_name"),
+                hunk("b.rs", "old_name"),
+                hunk("a.rs", "Old Name"),
+            ],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+
+        let groups = variant_groups(&plan);
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|group| (group.variant, group.style(), group.hunks.len(), group.files))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("old_name", "snake", 2, 2), ("Old Name", "title", 1, 1)]
+        );
+
+        assert_eq!(
+            exclude_hint(&plan, &groups, false),
+            concat!(
+                "To leave a variant unchanged, exclude it with --exclude-match:\n",
+                "  renamify plan 'old name' <NEW> --exclude-match 'Old Name'\n",
+            )
+        );
+        assert_eq!(exclude_hint(&plan, &groups[..1], false), "");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::scanner::Plan;
+use crate::warnings::WarningSeverity;
+use nu_ansi_term::Color as AnsiColor;
+use std::fmt::Write;
+
+/// The plan's warnings, most severe first, with the flag that deals with each
+pub(super) fn render_warnings(plan: &Plan, use_color: bool) -> String {
+    let mut output = String::new();
+    if plan.warnings.is_empty() {
+        return output;
+    }
+
+    let heading = format!("Warnings ({}):", plan.warnings.len());
+    if use_color {
+        writeln!(output, "\n{}", AnsiColor::Yellow.bold().paint(heading)).unwrap();
+    } else {
+        writeln!(output, "\n{heading}").unwrap();
+    }
+
+    for warning in &plan.warnings {
+        let severity = format!("[{}]", warning.severity);
+        let flag = warning
+            .suggested_flag
+            .as_ref()
+            .map(|flag| format!(" (see {flag})"))
+            .unwrap_or_default();
+        if use_color {
+            let color = match warning.severity {
+                WarningSeverity::Critical => AnsiColor::Red,
+                WarningSeverity::Warning => AnsiColor::Yellow,
+                WarningSeverity::Info => AnsiColor::Blue,
+            };
+            writeln!(
+                output,
+                "  {} {}{}",
+                color.paint(severity),
+                warning.message,
+                AnsiColor::DarkGray.paint(flag)
+            )
+            .unwrap();
+        } else {
+            writeln!(output, "  {severity} {}{flag}", warning.message).unwrap();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Stats;
+    use crate::warnings::{PlanWarning, WarningKind};
+
+    #[test]
+    fn test_render_warnings() {
+        let mut plan = Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "id".to_string(),
+            replace: "key".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        assert_eq!(render_warnings(&plan, false), "");
+
+        plan.warnings = vec![
+            PlanWarning::new(
+                WarningKind::CaseCollision,
+                WarningSeverity::Critical,
+                "Key.rs differs only by case from key.rs",
+            ),
+            PlanWarning::new(
+                WarningKind::GeneratedFile,
+                WarningSeverity::Warning,
+                "1 match in Cargo.lock, which is a lockfile",
+            )
+            .with_suggested_flag("--exclude"),
+        ];
+        assert_eq!(
+            render_warnings(&plan, false),
+            concat!(
+                "\nWarnings (2):\n",
+                "  [critical] Key.rs differs only by case from key.rs\n",
+                "  [warning] 1 match in Cargo.lock, which is a lockfile (see --exclude)\n",
+            )
+        );
+    }
+}
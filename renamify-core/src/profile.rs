@@ -0,0 +1,316 @@
+//! Named bundles of plan settings, selected with `--profile`.
+//!
+//! Profiles are defined under `[profiles.<name>]` in `.renamify/config.toml`.
+//! Renamify also ships built-in profiles; a config profile with the same name
+//! replaces the built-in one.
+
+use crate::case_model::Style;
+use crate::error::RenamifyError;
+use crate::scanner::{MatchHunk, Plan, PlanOptions};
+use crate::structured::StructuredRegions;
+use crate::warnings::{PlanWarning, WarningKind, WarningSeverity};
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Names of the profiles that are available without any configuration
+pub const BUILTIN_PROFILES: &[&str] = &["k8s", "sql"];
+
+/// Mapping keys whose entries must stay in sync across Kubernetes manifests
+const LABEL_PARENTS: &[&str] = &["labels", "matchLabels", "selector"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct Profile {
+    /// One-line summary of what the profile is for
+    pub description: String,
+
+    /// Case styles to use, as in `--only-styles`. An explicit `--only-styles`
+    /// takes precedence.
+    pub only_styles: Vec<String>,
+
+    /// Glob patterns to exclude, added to `--exclude`
+    pub exclude: Vec<String>,
+
+    /// Matches to leave unchanged, added to `--exclude-match`
+    pub exclude_match: Vec<String>,
+
+    /// Skip matches on lines matching this regex, combined with
+    /// `--exclude-matching-lines`
+    pub exclude_matching_lines: Option<String>,
+
+    /// Warn when a Kubernetes label or selector entry is renamed in some
+    /// manifests but not in others
+    pub label_warnings: bool,
+
+    /// Treat `.sql` files as SQL: unquoted identifiers match in any letter
+    /// case and unquoted keywords are left alone
+    pub sql: bool,
+}
+
+impl Profile {
+    /// A profile that ships with renamify, or None for unknown names
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "k8s" => Some(Self {
+                description: "Kubernetes and Helm manifests".to_string(),
+                only_styles: vec!["kebab".to_string(), "snake".to_string()],
+                exclude: vec![],
+                exclude_match: vec![],
+                // Helm-managed annotations and labels such as `helm.sh/chart`
+                exclude_matching_lines: Some(
+                    r#"^\s*["']?([\w.-]+\.)?helm\.sh/[\w.-]+["']?\s*:"#.to_string(),
+                ),
+                label_warnings: true,
+                sql: false,
+            }),
+            "sql" => Some(Self {
+                description: "SQL migrations and the ORM code that uses them".to_string(),
+                only_styles: ["snake", "screaming-snake", "pascal", "camel"]
+                    .map(String::from)
+                    .to_vec(),
+                sql: true,
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// The profile's case styles
+    pub fn styles(&self) -> Result<Vec<Style>> {
+        self.only_styles
+            .iter()
+            .map(|name| {
+                name.parse::<Style>()
+                    .map_err(|e| RenamifyError::InvalidInput(e).into())
+            })
+            .collect()
+    }
+
+    /// Add the profile's exclusions and file handling to the plan options
+    pub fn apply(&self, options: &mut PlanOptions) {
+        options.sql |= self.sql;
+        options.excludes.extend(self.exclude.iter().cloned());
+        options
+            .exclude_match
+            .extend(self.exclude_match.iter().cloned());
+        if let Some(pattern) = &self.exclude_matching_lines {
+            options.exclude_matching_lines = Some(match options.exclude_matching_lines.take() {
+                Some(existing) => format!("(?:{existing})|(?:{pattern})"),
+                None => pattern.clone(),
+            });
+        }
+    }
+
+    /// Warnings about label and selector entries that the plan renames in some
+    /// YAML files but not in others, or renames differently. Files excluded from
+    /// the plan are still checked, since a selector that no longer matches its
+    /// labels breaks a deployment just the same.
+    pub fn label_warnings(
+        &self,
+        plan: &Plan,
+        roots: &[PathBuf],
+        options: &PlanOptions,
+    ) -> Vec<PlanWarning> {
+        if !self.label_warnings || plan.matches.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hunks_by_file: HashMap<&Path, Vec<&MatchHunk>> = HashMap::new();
+        for hunk in &plan.matches {
+            hunks_by_file.entry(&hunk.file).or_default().push(hunk);
+        }
+
+        // Old label -> every place it appears, with what it becomes there
+        let mut labels: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        for entry in crate::configure_walker(roots, options).build().flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|t| t.is_file())
+                || path.components().any(|c| c.as_os_str() == ".renamify")
+            {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let Some(regions) = StructuredRegions::scan(path, content.as_bytes()) else {
+                continue;
+            };
+            let hunks = hunks_by_file.get(path).map_or(&[][..], Vec::as_slice);
+            for entry in regions.entries() {
+                let is_label = entry
+                    .path
+                    .iter()
+                    .rev()
+                    .nth(1)
+                    .is_some_and(|parent| LABEL_PARENTS.contains(&parent.as_str()));
+                if !is_label {
+                    continue;
+                }
+                let before = label(&content, &entry.key, &entry.value, &[]);
+                let after = label(&content, &entry.key, &entry.value, hunks);
+                let line = content[..entry.key.start].matches('\n').count() + 1;
+                let location = format!("{}:{line}", display_path(path, roots).display());
+                labels.entry(before).or_default().push((location, after));
+            }
+        }
+
+        labels
+            .into_iter()
+            .filter_map(|(before, places)| label_warning(&before, &places))
+            .map(|message| PlanWarning::new(WarningKind::Label, WarningSeverity::Warning, message))
+            .collect()
+    }
+}
+
+/// `--only-styles` if given, otherwise the profile's styles
+pub fn only_styles(profile: Option<&Profile>, only_styles: &[Style]) -> Result<Vec<Style>> {
+    match profile {
+        Some(profile) if only_styles.is_empty() => profile.styles(),
+        _ => Ok(only_styles.to_vec()),
+    }
+}
+
+/// `key: value` with the hunks inside the key and value applied
+fn label(content: &str, key: &Range<usize>, value: &Range<usize>, hunks: &[&MatchHunk]) -> String {
+    format!(
+        "{}: {}",
+        unquote(&replace_in(content, key, hunks)),
+        unquote(&replace_in(content, value, hunks))
+    )
+}
+
+fn replace_in(content: &str, span: &Range<usize>, hunks: &[&MatchHunk]) -> String {
+    let mut inside: Vec<&&MatchHunk> = hunks
+        .iter()
+        .filter(|hunk| span.start <= hunk.start && hunk.end <= span.end)
+        .collect();
+    inside.sort_by_key(|hunk| hunk.start);
+
+    let mut result = String::new();
+    let mut pos = span.start;
+    for hunk in inside {
+        if hunk.start < pos {
+            continue;
+        }
+        result.push_str(&content[pos..hunk.start]);
+        result.push_str(&hunk.replace);
+        pos = hunk.end;
+    }
+    result.push_str(&content[pos..span.end]);
+    result
+}
+
+fn unquote(text: &str) -> &str {
+    text.trim_matches(|c| c == '"' || c == '\'')
+}
+
+fn display_path<'a>(path: &'a Path, roots: &[PathBuf]) -> &'a Path {
+    roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .find(|relative| !relative.as_os_str().is_empty())
+        .unwrap_or(path)
+}
+
+fn label_warning(before: &str, places: &[(String, String)]) -> Option<String> {
+    let changed: Vec<&(String, String)> =
+        places.iter().filter(|(_, after)| after != before).collect();
+    let first = changed.first()?;
+    if changed.iter().all(|(_, after)| after == &first.1) && changed.len() == places.len() {
+        return None;
+    }
+
+    let locations = |places: &[&(String, String)]| {
+        places
+            .iter()
+            .map(|(location, _)| location.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let unchanged: Vec<&(String, String)> =
+        places.iter().filter(|(_, after)| after == before).collect();
+    if unchanged.is_empty() {
+        let renames: Vec<String> = changed
+            .iter()
+            .map(|(location, after)| format!("'{after}' in {location}"))
+            .collect();
+        Some(format!(
+            "Label '{before}' is renamed differently: {}",
+            renames.join(", ")
+        ))
+    } else {
+        Some(format!(
+            "Label '{before}' is renamed in {} but not in {}",
+            locations(&changed),
+            locations(&unchanged)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn places(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(location, after)| ((*location).to_string(), (*after).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_k8s_profile() {
+        let profile = Profile::builtin("k8s").unwrap();
+        assert_eq!(profile.styles().unwrap(), vec![Style::Kebab, Style::Snake]);
+        assert!(Profile::builtin("helm").is_none());
+
+        let lines = regex::Regex::new(profile.exclude_matching_lines.as_deref().unwrap()).unwrap();
+        assert!(lines.is_match("    helm.sh/chart: old-app-1.2.0"));
+        assert!(lines.is_match("  \"meta.helm.sh/release-name\": old-app"));
+        assert!(!lines.is_match("    app.kubernetes.io/name: old-app"));
+
+        let mut options = PlanOptions {
+            exclude_matching_lines: Some("TODO".to_string()),
+            ..PlanOptions::default()
+        };
+        profile.apply(&mut options);
+        assert_eq!(
+            options.exclude_matching_lines.unwrap(),
+            format!("(?:TODO)|(?:{})", lines.as_str())
+        );
+    }
+
+    #[test]
+    fn test_label_warning() {
+        let before = "app: old-app";
+        assert_eq!(
+            label_warning(
+                before,
+                &places(&[("a.yaml:3", "app: new-app"), ("b.yaml:7", "app: new-app")])
+            ),
+            None
+        );
+        assert_eq!(
+            label_warning(before, &places(&[("a.yaml:3", before)])),
+            None
+        );
+        assert_eq!(
+            label_warning(
+                before,
+                &places(&[("a.yaml:3", "app: new-app"), ("b.yaml:7", before)])
+            )
+            .unwrap(),
+            "Label 'app: old-app' is renamed in a.yaml:3 but not in b.yaml:7"
+        );
+        assert_eq!(
+            label_warning(before, &places(&[("a.yaml:3", "app: new-app"), ("b.yaml:7", "app: new_app")])).unwrap(),
+            "Label 'app: old-app' is renamed differently: 'app: new-app' in a.yaml:3, 'app: new_app' in b.yaml:7"
+        );
+    }
+}
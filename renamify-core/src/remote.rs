@@ -0,0 +1,262 @@
+//! Plans read from a URL.
+//!
+//! `apply --plan` accepts an `https://` URL as well as a path, so a plan
+//! generated once can be applied by many checkouts without copying it around.
+//! With the `cloud-storage` feature, `s3://` and `gs://` URLs are read with
+//! the `aws` and `gcloud` command line tools, using their credentials.
+//! `--checksum sha256:<hex>` checks the plan's bytes before anything is
+//! applied; plain `http://` URLs are only read when a checksum is given.
+//!
+//! Plans record absolute paths from the checkout they were made in, so a
+//! plan made elsewhere is moved onto the checkout applying it, and any path
+//! that would land outside that checkout is refused.
+
+use crate::bundle::is_inside_root;
+use crate::error::RenamifyError;
+use crate::scanner::Plan;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "cloud-storage")]
+use std::process::Command;
+
+/// Plans larger than this are refused rather than read into memory
+const MAX_PLAN_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Whether `source` names a plan by URL rather than by path or ID
+pub fn is_url(source: &str) -> bool {
+    ["https://", "http://", "s3://", "gs://"]
+        .iter()
+        .any(|scheme| source.starts_with(scheme))
+}
+
+/// The bytes of the plan at `source`, a path or a URL, checked against
+/// `checksum` when one is given
+pub fn read_plan_source(source: &str, checksum: Option<&str>) -> Result<Vec<u8>> {
+    let checksum = checksum.map(Checksum::parse).transpose()?;
+    let content = if is_url(source) {
+        if source.starts_with("http://") && checksum.is_none() {
+            return Err(RenamifyError::InvalidInput(format!(
+                "Refusing to read {source} over plain http without --checksum"
+            ))
+            .into());
+        }
+        fetch(source)?
+    } else {
+        std::fs::read(source).with_context(|| format!("Failed to read plan file {source}"))?
+    };
+    if let Some(checksum) = checksum {
+        checksum.verify(source, &content)?;
+    }
+    Ok(content)
+}
+
+/// An expected SHA-256 digest, given as `sha256:<hex>` or bare hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum(String);
+
+impl Checksum {
+    pub fn parse(value: &str) -> Result<Self> {
+        let hex = value.strip_prefix("sha256:").unwrap_or(value);
+        if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(RenamifyError::InvalidInput(format!(
+                "Invalid checksum '{value}': expected sha256:<64 hex digits>"
+            ))
+            .into());
+        }
+        Ok(Self(hex.to_ascii_lowercase()))
+    }
+
+    pub fn verify(&self, source: &str, content: &[u8]) -> Result<()> {
+        let actual = format!("{:x}", Sha256::digest(content));
+        if actual != self.0 {
+            return Err(RenamifyError::InvalidInput(format!(
+                "Checksum mismatch for {source}: expected sha256:{}, got sha256:{actual}",
+                self.0
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    if url.starts_with("s3://") || url.starts_with("gs://") {
+        return fetch_cloud_storage(url);
+    }
+    let response = ureq::get(url).call().map_err(|e| match e {
+        ureq::Error::Status(404, _) => {
+            RenamifyError::NotFound(format!("Plan not found at {url}")).into()
+        },
+        e => anyhow::Error::new(e).context(format!("Failed to download plan from {url}")),
+    })?;
+    let mut content = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_PLAN_BYTES + 1)
+        .read_to_end(&mut content)
+        .with_context(|| format!("Failed to download plan from {url}"))?;
+    check_size(url, &content)?;
+    Ok(content)
+}
+
+#[cfg(feature = "cloud-storage")]
+fn fetch_cloud_storage(url: &str) -> Result<Vec<u8>> {
+    let (program, args): (&str, &[&str]) = if url.starts_with("s3://") {
+        ("aws", &["s3", "cp", url, "-"])
+    } else {
+        ("gcloud", &["storage", "cat", url])
+    };
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {program} to download {url}"))?;
+    if !output.status.success() {
+        return Err(RenamifyError::NotFound(format!(
+            "Failed to download plan from {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    check_size(url, &output.stdout)?;
+    Ok(output.stdout)
+}
+
+#[cfg(not(feature = "cloud-storage"))]
+fn fetch_cloud_storage(url: &str) -> Result<Vec<u8>> {
+    Err(RenamifyError::InvalidInput(format!(
+        "Can't read {url}: renamify was built without the cloud-storage feature"
+    ))
+    .into())
+}
+
+fn check_size(url: &str, content: &[u8]) -> Result<()> {
+    if content.len() as u64 > MAX_PLAN_BYTES {
+        return Err(RenamifyError::InvalidInput(format!(
+            "Plan at {url} is larger than {} MiB",
+            MAX_PLAN_BYTES / 1024 / 1024
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Move the paths of `plan`, read from `source`, under `root`, the canonical
+/// root of the checkout applying it.
+///
+/// Paths under `root` are kept; paths under the directory the plan was made in
+/// are moved to the same place under `root`. Any other path, or one that climbs
+/// out with `..`, is refused.
+pub fn rebase_plan(plan: &mut Plan, source: &str, root: &Path) -> Result<()> {
+    let made_in = plan
+        .options
+        .as_ref()
+        .and_then(|options| options.working_dir.clone());
+    let rebase = |path: &mut PathBuf| -> Result<()> {
+        let relative = if path.is_relative() {
+            Some(path.as_path())
+        } else {
+            path.strip_prefix(root).ok().or_else(|| {
+                made_in
+                    .as_deref()
+                    .and_then(|dir| path.strip_prefix(dir).ok())
+            })
+        };
+        match relative.filter(|relative| is_inside_root(relative)) {
+            Some(relative) => {
+                *path = root.join(relative);
+                Ok(())
+            },
+            None => Err(RenamifyError::InvalidInput(format!(
+                "Plan {source} has path {}, which is outside {}",
+                path.display(),
+                root.display()
+            ))
+            .into()),
+        }
+    };
+
+    for hunk in &mut plan.matches {
+        rebase(&mut hunk.file)?;
+        if let Some(path) = &mut hunk.original_file {
+            rebase(path)?;
+        }
+        if let Some(path) = &mut hunk.renamed_file {
+            rebase(path)?;
+        }
+    }
+    for rename in &mut plan.paths {
+        rebase(&mut rename.path)?;
+        rebase(&mut rename.new_path)?;
+    }
+    for dir in plan.created_directories.iter_mut().flatten() {
+        rebase(dir)?;
+    }
+    if let Some(options) = &mut plan.options {
+        options.working_dir = Some(root.to_path_buf());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_content, PlanOptions};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_plan_source_checks_checksum() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("plan.json");
+        std::fs::write(&path, "{}").unwrap();
+        let path = path.to_str().unwrap();
+        let digest = format!("{:x}", Sha256::digest(b"{}"));
+
+        assert_eq!(read_plan_source(path, None).unwrap(), b"{}");
+        let checksum = format!("sha256:{}", digest.to_ascii_uppercase());
+        assert_eq!(read_plan_source(path, Some(&checksum)).unwrap(), b"{}");
+        let error = read_plan_source(path, Some(&"0".repeat(64))).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+        assert!(read_plan_source(path, Some("md5:abc")).is_err());
+
+        let error = read_plan_source("http://example.com/plan.json", None).unwrap_err();
+        assert!(error.to_string().contains("without --checksum"));
+    }
+
+    #[test]
+    fn test_rebase_plan_onto_another_checkout() {
+        let made_in = Path::new("/ci/checkout");
+        let root = Path::new("/home/me/repo");
+        let plan_in = |dir: &Path, file: &Path| {
+            let mut plan = scan_content(
+                file,
+                b"old_name\n",
+                "old_name",
+                "new_name",
+                &PlanOptions::default(),
+            )
+            .unwrap();
+            plan.options = Some(PlanOptions {
+                working_dir: Some(dir.to_path_buf()),
+                ..PlanOptions::default()
+            });
+            plan
+        };
+
+        let mut plan = plan_in(made_in, &made_in.join("src/a.rs"));
+        rebase_plan(&mut plan, "plan.json", root).unwrap();
+        assert_eq!(plan.matches[0].file, root.join("src/a.rs"));
+
+        // Already in this checkout
+        let mut plan = plan_in(root, &root.join("src/a.rs"));
+        rebase_plan(&mut plan, "plan.json", root).unwrap();
+        assert_eq!(plan.matches[0].file, root.join("src/a.rs"));
+
+        for outside in ["/etc/passwd", "/ci/checkout/../../etc/passwd", "../a.rs"] {
+            let mut plan = plan_in(made_in, Path::new(outside));
+            let error = rebase_plan(&mut plan, "plan.json", root).unwrap_err();
+            assert!(error.to_string().contains("outside"), "{error}");
+        }
+    }
+}
@@ -1,14 +1,17 @@
-use crate::scanner::build_globset;
-use anyhow::{anyhow, Result};
+use crate::error::RenamifyError;
+use crate::scanner::{build_globset, build_include_globset};
+use anyhow::Result;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use tempfile::TempDir;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
 
 use crate::ambiguity::{AmbiguityContext, AmbiguityResolver};
 use crate::case_constraints::filter_compatible_styles;
 use crate::case_model::{parse_to_tokens, to_style, Style};
-use crate::scanner::{PlanOptions, Rename, RenameKind};
+use crate::scanner::{coerce_name, CoercionMode, MatchAnchor, PlanOptions, Rename, RenameKind};
 
 /// Normalize a path by removing Windows long path prefix if present
 fn normalize_path(path: &Path) -> PathBuf {
@@ -33,19 +36,26 @@ const WINDOWS_RESERVED: &[&str] = &[
     "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
 ];
 
+/// Longest file path Windows accepts without the `\\?\` prefix (`MAX_PATH` minus the NUL)
+const WINDOWS_MAX_FILE_PATH: usize = 259;
+
+/// Longest directory path Windows accepts without the prefix (room is kept for an 8.3 file name)
+const WINDOWS_MAX_DIR_PATH: usize = 247;
+
 /// Determine the best replacement for a filename using the ambiguity resolver
 fn determine_filename_replacement(
     filename: &str,
-    _search: &str,
+    search: &str,
     replace: &str,
     mapping: &BTreeMap<String, String>,
     ambiguity_resolver: &AmbiguityResolver,
+    anchor: MatchAnchor,
 ) -> Option<String> {
     // Check if the filename contains the search pattern
     // We need to find which variant from the mapping matches this filename
     let mut matching_variant = None;
     for old_variant in mapping.keys() {
-        if filename.contains(old_variant) {
+        if anchored_in(filename, old_variant, search, anchor) {
             matching_variant = Some(old_variant.clone());
             break;
         }
@@ -57,7 +67,13 @@ fn determine_filename_replacement(
     if !crate::ambiguity::is_ambiguous(&matching_variant, &Style::all_styles()) {
         // Not ambiguous - just use the variant map directly
         let new_variant = mapping.get(&matching_variant)?;
-        return Some(filename.replace(&matching_variant, new_variant));
+        return Some(replace_anchored(
+            filename,
+            &matching_variant,
+            new_variant,
+            search,
+            anchor,
+        ));
     }
 
     // For ambiguous identifiers, use the ambiguity resolver
@@ -86,7 +102,13 @@ fn determine_filename_replacement(
     let styled_replacement = to_style(&replacement_tokens, resolved.style);
 
     // Replace the variant in the filename with the styled replacement
-    Some(filename.replace(&matching_variant, &styled_replacement))
+    Some(replace_anchored(
+        filename,
+        &matching_variant,
+        &styled_replacement,
+        search,
+        anchor,
+    ))
 }
 
 #[derive(Debug, Clone)]
@@ -94,6 +116,8 @@ pub struct RenameConflict {
     pub sources: Vec<PathBuf>,
     pub target: PathBuf,
     pub kind: ConflictKind,
+    /// How the user could avoid the conflict, when there is an obvious fix
+    pub suggestion: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -104,6 +128,13 @@ pub enum ConflictKind {
     CaseInsensitive,
     /// Target is a Windows reserved name
     WindowsReserved,
+    /// Target name ends with a dot or space, which Windows silently strips
+    WindowsTrailingDotOrSpace,
+    /// Target path exceeds `MAX_PATH` and needs the `\\?\` prefix on Windows
+    WindowsPathTooLong,
+    /// Target differs only by case from another path, which breaks checkouts
+    /// on case-insensitive filesystems (Windows, default macOS)
+    CaseCollision,
 }
 
 #[derive(Debug, Clone)]
@@ -134,6 +165,27 @@ pub fn detect_case_insensitive_fs(path: &Path) -> bool {
     fs::metadata(&test_file_upper).is_ok()
 }
 
+/// Whether git in the repository at `path` is set to keep decomposed (NFD)
+/// file names as they are, with `core.precomposeUnicode` set to false
+fn keeps_decomposed_names(path: &Path) -> bool {
+    Command::new("git")
+        .current_dir(path)
+        .args(["config", "--type=bool", "--get", "core.precomposeUnicode"])
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| output.status.success() && output.stdout.trim_ascii() == b"false")
+}
+
+/// A file name written in the same Unicode form as `original`: decomposed if
+/// it was and git keeps names that way, composed (NFC) otherwise
+fn normalized_name(name: &str, original: &str, keep_decomposed: impl FnOnce() -> bool) -> String {
+    if !is_nfc(original) && keep_decomposed() {
+        name.nfd().collect()
+    } else {
+        name.nfc().collect()
+    }
+}
+
 /// Check if a filename is a Windows reserved name
 /// Always checks regardless of platform to ensure cross-platform compatibility
 pub fn is_windows_reserved(name: &str) -> bool {
@@ -143,6 +195,75 @@ pub fn is_windows_reserved(name: &str) -> bool {
     WINDOWS_RESERVED.contains(&base.as_str())
 }
 
+/// Check if a filename ends with a dot or space (Windows strips these, so the
+/// file would silently get a different name)
+pub fn has_windows_trailing_dot_or_space(name: &str) -> bool {
+    name != "." && name != ".." && (name.ends_with('.') || name.ends_with(' '))
+}
+
+/// Length of a path in UTF-16 code units, as Windows measures it
+fn windows_path_len(path: &Path) -> usize {
+    normalize_path(path)
+        .to_string_lossy()
+        .encode_utf16()
+        .count()
+}
+
+/// Check a rename target for names or lengths that would fail on Windows.
+/// Problems already present in the source path are not reported.
+fn windows_path_problem(rename: &Rename) -> Option<(ConflictKind, String)> {
+    let new_name = rename.new_path.file_name()?.to_string_lossy();
+    let old_name = rename
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    if is_windows_reserved(&new_name) && !is_windows_reserved(&old_name) {
+        let base_len = new_name.split('.').next().unwrap_or(&new_name).len();
+        let alternative = format!("{}_{}", &new_name[..base_len], &new_name[base_len..]);
+        return Some((
+            ConflictKind::WindowsReserved,
+            format!(
+                "'{}' is reserved on Windows, use '{}' instead",
+                new_name, alternative
+            ),
+        ));
+    }
+
+    if has_windows_trailing_dot_or_space(&new_name) && !has_windows_trailing_dot_or_space(&old_name)
+    {
+        // A name of only dots and spaces has nothing left to suggest
+        let alternative = new_name.trim_end_matches(['.', ' ']);
+        let suggestion = if alternative.is_empty() {
+            "Windows strips trailing dots and spaces, which leaves an empty name".to_string()
+        } else {
+            format!(
+                "Windows strips trailing dots and spaces, use '{}' instead",
+                alternative
+            )
+        };
+        return Some((ConflictKind::WindowsTrailingDotOrSpace, suggestion));
+    }
+
+    let limit = match rename.kind {
+        RenameKind::File => WINDOWS_MAX_FILE_PATH,
+        RenameKind::Dir => WINDOWS_MAX_DIR_PATH,
+    };
+    let new_len = windows_path_len(&rename.new_path);
+    if new_len > limit && windows_path_len(&rename.path) <= limit {
+        return Some((
+            ConflictKind::WindowsPathTooLong,
+            format!(
+                "path is {} characters (Windows limit is {}), shorten the new name or enable long paths (git config core.longpaths true)",
+                new_len, limit
+            ),
+        ));
+    }
+
+    None
+}
+
 /// Check if a filename is a Windows reserved name (only on Windows)
 #[cfg(test)]
 fn is_windows_reserved_on_windows(name: &str) -> bool {
@@ -152,18 +273,50 @@ fn is_windows_reserved_on_windows(name: &str) -> bool {
     is_windows_reserved(name)
 }
 
+/// A file or directory found by the walker, kept so the content scan and
+/// rename planning can share a single walk of the tree
+#[derive(Debug, Clone)]
+pub struct WalkedPath {
+    pub path: PathBuf,
+    /// Depth below the walk root, which is 0
+    pub depth: usize,
+    pub file_type: Option<fs::FileType>,
+}
+
+impl From<&ignore::DirEntry> for WalkedPath {
+    fn from(entry: &ignore::DirEntry) -> Self {
+        Self {
+            path: entry.path().to_path_buf(),
+            depth: entry.depth(),
+            file_type: entry.file_type(),
+        }
+    }
+}
+
+/// Walk `root` as the scanner does, for planning renames on their own
+fn walk_root(root: &Path, options: &PlanOptions) -> Vec<WalkedPath> {
+    crate::configure_walker(&[root.to_path_buf()], options)
+        .build()
+        .flatten()
+        .map(|entry| WalkedPath::from(&entry))
+        .collect()
+}
+
 /// Plan renames for files and directories based on variant mapping
 pub fn plan_renames_with_conflicts(
     root: &Path,
     mapping: &BTreeMap<String, String>,
     options: &PlanOptions,
 ) -> Result<RenamePlan> {
-    plan_renames_with_conflicts_and_params(root, mapping, options, "", "")
+    plan_walked_renames(root, &walk_root(root, options), mapping, options, "", "")
 }
 
-/// Plan renames for files and directories based on variant mapping, with search/replace params
-fn plan_renames_with_conflicts_and_params(
+/// Plan renames for the paths found by walking `root`, with search/replace
+/// params for ambiguity resolution
+#[allow(clippy::too_many_lines)]
+pub(crate) fn plan_walked_renames(
     root: &Path,
+    walked: &[WalkedPath],
     mapping: &BTreeMap<String, String>,
     options: &PlanOptions,
     search: &str,
@@ -172,23 +325,45 @@ fn plan_renames_with_conflicts_and_params(
     let mut collected_renames = Vec::new();
     let case_insensitive_fs = detect_case_insensitive_fs(root);
 
+    // macOS file systems may hand back names decomposed (NFD) while search
+    // terms are typed composed (NFC), so names are matched in NFC
+    let search: String = search.nfc().collect();
+    let replace: String = replace.nfc().collect();
+    let (search, replace) = (search.as_str(), replace.as_str());
+    let mapping: &BTreeMap<String, String> = &mapping
+        .iter()
+        .map(|(old, new)| (old.nfc().collect(), new.nfc().collect()))
+        .collect();
+    let mut keep_decomposed = None;
+    let mut keep_decomposed =
+        || *keep_decomposed.get_or_insert_with(|| keeps_decomposed_names(root));
+
     // Create ambiguity resolver for intelligent style selection
     let ambiguity_resolver = AmbiguityResolver::new();
 
     // Build globsets for include/exclude filtering using shared logic
-    let include_globs = build_globset(&options.includes)?;
+    let include_globs = build_include_globset(&options.includes)?;
     let exclude_globs = build_globset(&options.excludes)?;
 
-    // Use shared walker configuration
-    let walker = crate::configure_walker(&[root.to_path_buf()], options).build();
+    // Every walked path, including ones filtered out below, so renames can be
+    // checked against files that stay where they are
+    let existing_paths: Vec<PathBuf> = walked
+        .iter()
+        .map(|entry| normalize_path(&entry.path))
+        .collect();
 
-    // Collect all potential renames
-    for entry in walker {
-        let Ok(entry) = entry else {
-            continue;
-        };
+    // Automatic coercion rewrites every occurrence in a name, anchored or not
+    let coerce_separators = if options.match_anchor != MatchAnchor::Any
+        && options.coerce_separators == CoercionMode::Auto
+    {
+        CoercionMode::Off
+    } else {
+        options.coerce_separators
+    };
 
-        let path = entry.path();
+    // Collect all potential renames
+    for entry in walked {
+        let path = entry.path.as_path();
 
         // Apply include/exclude filters (use relative path for matching)
         let relative_path = path.strip_prefix(root).unwrap_or(path);
@@ -206,10 +381,15 @@ fn plan_renames_with_conflicts_and_params(
         }
 
         // Get file type from entry metadata
-        let Some(file_type) = entry.file_type() else {
+        let Some(file_type) = entry.file_type else {
             continue;
         };
 
+        // A submodule's directory is moved with `git mv`, which updates .gitmodules
+        if file_type.is_dir() && entry.depth > 0 && crate::submodule::nested_repo(path).is_some() {
+            continue;
+        }
+
         // Skip if not renaming files/dirs based on options
         if file_type.is_dir() && !options.rename_dirs {
             continue;
@@ -219,15 +399,16 @@ fn plan_renames_with_conflicts_and_params(
         }
 
         if let Some(file_name) = path.file_name() {
-            let file_name_str = file_name.to_string_lossy();
+            let original_name = file_name.to_string_lossy();
+            let file_name_str: String = original_name.nfc().collect();
 
             // Use the ambiguity resolver to determine the best replacement
             // If search/replace are provided, use them; otherwise fall back to simple replacement
             if !search.is_empty() && !replace.is_empty() {
                 // First, find which variant from the mapping matches this filename
-                let matching_variant = mapping
-                    .keys()
-                    .find(|old_variant| file_name_str.contains(*old_variant));
+                let matching_variant = mapping.keys().find(|old_variant| {
+                    anchored_in(&file_name_str, old_variant, search, options.match_anchor)
+                });
 
                 if let Some(old_variant) = matching_variant {
                     if let Some(new_variant) = mapping.get(old_variant) {
@@ -237,24 +418,26 @@ fn plan_renames_with_conflicts_and_params(
                             replace,
                             mapping,
                             &ambiguity_resolver,
+                            options.match_anchor,
                         ) {
-                            let mut coercion_applied = None;
-
                             // Apply coercion if enabled for contextual separator coercion
                             // (e.g., preserving Pascal case inside camel case containers)
                             // Use the specific variant that matched, not the original search/replace
-                            if options.coerce_separators == crate::scanner::CoercionMode::Auto {
-                                if let Some((coerced, reason)) = crate::coercion::apply_coercion(
-                                    &file_name_str,
-                                    old_variant,
-                                    new_variant,
-                                ) {
-                                    new_name = coerced;
-                                    coercion_applied = Some(reason);
-                                }
-                            }
-
-                            if new_name != file_name_str {
+                            let coercion_applied = coerce_name(
+                                coerce_separators,
+                                &file_name_str,
+                                &mut new_name,
+                                old_variant,
+                                new_variant,
+                            );
+
+                            // A name that only changes its Unicode form isn't renamed
+                            if new_name.nfc().ne(file_name_str.chars()) {
+                                let new_name = normalized_name(
+                                    &new_name,
+                                    &original_name,
+                                    &mut keep_decomposed,
+                                );
                                 let new_path = path.with_file_name(&new_name);
 
                                 let kind = if file_type.is_dir() {
@@ -277,20 +460,16 @@ fn plan_renames_with_conflicts_and_params(
                 // Fallback to simple replacement for backward compatibility
                 // (when search/replace are not provided)
                 for (old, new) in mapping {
-                    if file_name_str.contains(old) {
-                        let mut new_name = file_name_str.replace(old, new);
-                        let mut coercion_applied = None;
+                    if anchored_in(&file_name_str, old, old, options.match_anchor) {
+                        let mut new_name =
+                            replace_anchored(&file_name_str, old, new, old, options.match_anchor);
 
                         // Apply coercion if enabled
-                        if options.coerce_separators == crate::scanner::CoercionMode::Auto {
-                            if let Some((coerced, reason)) =
-                                crate::coercion::apply_coercion(&file_name_str, old, new)
-                            {
-                                new_name = coerced;
-                                coercion_applied = Some(reason);
-                            }
-                        }
+                        let coercion_applied =
+                            coerce_name(coerce_separators, &file_name_str, &mut new_name, old, new);
 
+                        let new_name =
+                            normalized_name(&new_name, &original_name, &mut keep_decomposed);
                         let new_path = path.with_file_name(&new_name);
 
                         let kind = if file_type.is_dir() {
@@ -315,12 +494,15 @@ fn plan_renames_with_conflicts_and_params(
     // Filter out root directory renames unless explicitly allowed
     if !options.rename_root {
         collected_renames.retain(|rename| {
-            // Check if this rename is for the root directory (current working directory)
-            if let Ok(current_dir) = std::env::current_dir() {
-                rename.path != current_dir
-            } else {
+            // Check if this rename is for the root directory (the working directory)
+            match options
+                .working_dir
+                .clone()
+                .map_or_else(std::env::current_dir, Ok)
+            {
+                Ok(working_dir) => rename.path != working_dir,
                 // If we can't get current dir, keep the rename (safe default)
-                true
+                Err(_) => true,
             }
         });
     }
@@ -346,16 +528,15 @@ fn plan_renames_with_conflicts_and_params(
     let mut requires_staging = false;
 
     for rename in &collected_renames {
-        // Check for Windows reserved names
-        if let Some(file_name) = rename.new_path.file_name() {
-            if is_windows_reserved(&file_name.to_string_lossy()) {
-                conflicts.push(RenameConflict {
-                    sources: vec![rename.path.clone()],
-                    target: rename.new_path.clone(),
-                    kind: ConflictKind::WindowsReserved,
-                });
-                continue;
-            }
+        // Check for names and lengths that would fail on Windows
+        if let Some((kind, suggestion)) = windows_path_problem(rename) {
+            conflicts.push(RenameConflict {
+                sources: vec![rename.path.clone()],
+                target: rename.new_path.clone(),
+                kind,
+                suggestion: Some(suggestion),
+            });
+            continue;
         }
 
         // Check for case-only changes on case-insensitive filesystem
@@ -372,6 +553,7 @@ fn plan_renames_with_conflicts_and_params(
                     sources: vec![rename.path.clone()],
                     target: rename.new_path.clone(),
                     kind: ConflictKind::CaseInsensitive,
+                    suggestion: None,
                 });
             }
         }
@@ -383,6 +565,10 @@ fn plan_renames_with_conflicts_and_params(
             .push(rename.path.clone());
     }
 
+    if !options.allow_case_collisions {
+        conflicts.extend(detect_case_collisions(&collected_renames, &existing_paths));
+    }
+
     // Find multiple-to-one conflicts
     for (target, sources) in target_map {
         if sources.len() > 1 {
@@ -390,6 +576,7 @@ fn plan_renames_with_conflicts_and_params(
                 sources,
                 target,
                 kind: ConflictKind::MultipleToOne,
+                suggestion: None,
             });
         }
     }
@@ -414,6 +601,121 @@ fn plan_renames_with_conflicts_and_params(
     })
 }
 
+fn lowercase_path(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// Find rename targets that only differ by case from a path that will still
+/// exist after the renames, or from another rename target. Such trees work on
+/// case-sensitive filesystems but cannot be checked out on Windows or macOS.
+fn detect_case_collisions(renames: &[Rename], existing_paths: &[PathBuf]) -> Vec<RenameConflict> {
+    case_collisions(renames, existing_paths)
+        .into_iter()
+        .map(|(rename, colliding)| {
+            let names = colliding
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            RenameConflict {
+                sources: vec![rename.path.clone()],
+                target: rename.new_path.clone(),
+                kind: ConflictKind::CaseCollision,
+                suggestion: Some(format!(
+                    "differs only by case from {names}; pass --allow-case-collisions if this repo is never checked out on a case-insensitive filesystem"
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Each rename whose target differs only by case from other final paths,
+/// with those paths, sorted
+pub(crate) fn case_collisions<'a>(
+    renames: &'a [Rename],
+    existing_paths: &[PathBuf],
+) -> Vec<(&'a Rename, Vec<PathBuf>)> {
+    let renamed_away: HashSet<&Path> = renames.iter().map(|r| r.path.as_path()).collect();
+
+    // Paths that stay in place, plus every planned target
+    let mut final_paths: HashMap<String, Vec<&Path>> = HashMap::new();
+    for path in existing_paths {
+        if !renamed_away.contains(path.as_path()) {
+            final_paths
+                .entry(lowercase_path(path))
+                .or_default()
+                .push(path.as_path());
+        }
+    }
+    for rename in renames {
+        final_paths
+            .entry(lowercase_path(&rename.new_path))
+            .or_default()
+            .push(rename.new_path.as_path());
+    }
+
+    let mut collisions = Vec::new();
+    for rename in renames {
+        let Some(others) = final_paths.get(&lowercase_path(&rename.new_path)) else {
+            continue;
+        };
+        // Identical targets are reported as `MultipleToOne` instead
+        let mut colliding: Vec<&Path> = others
+            .iter()
+            .copied()
+            .filter(|other| *other != rename.new_path)
+            .collect();
+        colliding.sort();
+        colliding.dedup();
+        if colliding.is_empty() {
+            continue;
+        }
+        collisions.push((
+            rename,
+            colliding.into_iter().map(Path::to_path_buf).collect(),
+        ));
+    }
+    collisions
+}
+
+/// Fail with a conflict error if any rename collides by case with another path
+pub(crate) fn check_case_collisions(renames: &[Rename], existing_paths: &[PathBuf]) -> Result<()> {
+    let conflicts = detect_case_collisions(renames, existing_paths);
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    Err(conflicts_error(&conflicts))
+}
+
+fn conflicts_error(conflicts: &[RenameConflict]) -> anyhow::Error {
+    let conflict_msg = conflicts
+        .iter()
+        .map(format_conflict)
+        .collect::<Vec<_>>()
+        .join("\n");
+    RenamifyError::Conflict(format!(
+        "Found {} rename conflicts:\n{}",
+        conflicts.len(),
+        conflict_msg
+    ))
+    .into()
+}
+
+fn format_conflict(conflict: &RenameConflict) -> String {
+    let mut line = format!(
+        "{:?}: {:?} -> {}",
+        conflict.kind,
+        conflict.sources,
+        conflict.target.display()
+    );
+    if let Some(ref suggestion) = conflict.suggestion {
+        line.push_str(" (");
+        line.push_str(suggestion);
+        line.push(')');
+    }
+    line
+}
+
 /// Plan renames with search and replace for ambiguity resolution
 pub fn plan_renames_with_search(
     root: &Path,
@@ -422,22 +724,56 @@ pub fn plan_renames_with_search(
     search: &str,
     replace: &str,
 ) -> Result<Vec<Rename>> {
-    let plan = plan_renames_with_conflicts_and_params(root, mapping, options, search, replace)?;
+    plan_walked_renames_with_search(
+        root,
+        &walk_root(root, options),
+        mapping,
+        options,
+        search,
+        replace,
+    )
+}
+
+/// [`plan_renames_with_search`] for paths that were already walked
+/// Whether `variant` occurs in `name` where `--match-anchor` allows it
+fn anchored_in(name: &str, variant: &str, search: &str, anchor: MatchAnchor) -> bool {
+    name.match_indices(variant)
+        .any(|(i, _)| anchor.keeps(name, i, i + variant.len(), search))
+}
+
+/// Replace the occurrences of `variant` in `name` that `--match-anchor` allows
+fn replace_anchored(
+    name: &str,
+    variant: &str,
+    replacement: &str,
+    search: &str,
+    anchor: MatchAnchor,
+) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut last = 0;
+    for (i, _) in name.match_indices(variant) {
+        if anchor.keeps(name, i, i + variant.len(), search) {
+            result.push_str(&name[last..i]);
+            result.push_str(replacement);
+            last = i + variant.len();
+        }
+    }
+    result.push_str(&name[last..]);
+    result
+}
+
+pub(crate) fn plan_walked_renames_with_search(
+    root: &Path,
+    walked: &[WalkedPath],
+    mapping: &BTreeMap<String, String>,
+    options: &PlanOptions,
+    search: &str,
+    replace: &str,
+) -> Result<Vec<Rename>> {
+    let plan = plan_walked_renames(root, walked, mapping, options, search, replace)?;
 
     if !plan.conflicts.is_empty() {
-        let conflict_count = plan.conflicts.len();
-        let conflict_msg = plan
-            .conflicts
-            .iter()
-            .map(|c| format!("{:?}: {:?} -> {}", c.kind, c.sources, c.target.display()))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        return Err(anyhow!(
-            "Found {} rename conflicts:\n{}",
-            conflict_count,
-            conflict_msg
-        ));
+        return Err(conflicts_error(&plan.conflicts));
     }
 
     Ok(plan.renames)
@@ -452,19 +788,7 @@ pub fn plan_renames(
     let plan = plan_renames_with_conflicts(root, mapping, options)?;
 
     if !plan.conflicts.is_empty() {
-        let conflict_count = plan.conflicts.len();
-        let conflict_msg = plan
-            .conflicts
-            .iter()
-            .map(|c| format!("{:?}: {:?} -> {}", c.kind, c.sources, c.target.display()))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        return Err(anyhow!(
-            "Found {} rename conflicts:\n{}",
-            conflict_count,
-            conflict_msg
-        ));
+        return Err(conflicts_error(&plan.conflicts));
     }
 
     Ok(plan.renames)
@@ -493,6 +817,146 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_windows_trailing_dot_or_space() {
+        assert!(has_windows_trailing_dot_or_space("name."));
+        assert!(has_windows_trailing_dot_or_space("name "));
+        assert!(!has_windows_trailing_dot_or_space("name.rs"));
+        assert!(!has_windows_trailing_dot_or_space("."));
+        assert!(!has_windows_trailing_dot_or_space(".."));
+    }
+
+    #[test]
+    fn test_windows_path_problem_suggestions() {
+        let rename = |from: &str, to: &str| Rename {
+            path: PathBuf::from(from),
+            new_path: PathBuf::from(to),
+            kind: RenameKind::File,
+            coercion_applied: None,
+        };
+
+        let (kind, suggestion) =
+            windows_path_problem(&rename("/repo/old.config.rs", "/repo/aux.config.rs")).unwrap();
+        assert_eq!(kind, ConflictKind::WindowsReserved);
+        assert!(suggestion.contains("'aux_.config.rs'"));
+
+        let (kind, suggestion) =
+            windows_path_problem(&rename("/repo/old_name", "/repo/new_name.")).unwrap();
+        assert_eq!(kind, ConflictKind::WindowsTrailingDotOrSpace);
+        assert!(suggestion.contains("'new_name'"));
+
+        let (kind, suggestion) =
+            windows_path_problem(&rename("/repo/old_name", "/repo/. .")).unwrap();
+        assert_eq!(kind, ConflictKind::WindowsTrailingDotOrSpace);
+        assert!(!suggestion.contains("''"), "{suggestion}");
+
+        // Names that were already a problem are not blamed on the rename
+        assert!(windows_path_problem(&rename("/repo/aux.old.rs", "/repo/aux.new.rs")).is_none());
+        assert!(windows_path_problem(&rename("/repo/old_name.", "/repo/other_name.")).is_none());
+
+        let short_dir = "/repo/".to_string() + &"d".repeat(200);
+        let (kind, _) = windows_path_problem(&rename(
+            &format!("{short_dir}/old.rs"),
+            &format!("{short_dir}/{}.rs", "n".repeat(60)),
+        ))
+        .unwrap();
+        assert_eq!(kind, ConflictKind::WindowsPathTooLong);
+
+        // Paths that were already too long are not blamed on the rename
+        let long_dir = "/repo/".to_string() + &"d".repeat(300);
+        assert!(windows_path_problem(&rename(
+            &format!("{long_dir}/old.rs"),
+            &format!("{long_dir}/new.rs"),
+        ))
+        .is_none());
+
+        assert!(windows_path_problem(&rename("/repo/old.rs", "/repo/new.rs")).is_none());
+    }
+
+    #[test]
+    fn test_windows_reserved_target_is_a_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("old_name.txt"), "test").unwrap();
+
+        let mut mapping = BTreeMap::new();
+        mapping.insert("old_name".to_string(), "nul".to_string());
+
+        let plan = plan_renames_with_conflicts(temp_dir.path(), &mapping, &PlanOptions::default())
+            .unwrap();
+
+        assert!(plan.renames.is_empty());
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].kind, ConflictKind::WindowsReserved);
+        assert!(format_conflict(&plan.conflicts[0]).contains("use 'nul_.txt' instead"));
+    }
+
+    #[test]
+    fn test_case_collision_with_existing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        // Skip on case-insensitive filesystems where both files cannot coexist
+        if detect_case_insensitive_fs(temp_dir.path()) {
+            return;
+        }
+        std::fs::write(temp_dir.path().join("old_name.rs"), "test").unwrap();
+        std::fs::write(temp_dir.path().join("New_Name.rs"), "test").unwrap();
+
+        let mut mapping = BTreeMap::new();
+        mapping.insert("old_name".to_string(), "new_name".to_string());
+
+        let plan = plan_renames_with_conflicts(temp_dir.path(), &mapping, &PlanOptions::default())
+            .unwrap();
+        assert!(plan.renames.is_empty());
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].kind, ConflictKind::CaseCollision);
+        assert!(format_conflict(&plan.conflicts[0]).contains("New_Name.rs"));
+
+        let options = PlanOptions {
+            allow_case_collisions: true,
+            ..Default::default()
+        };
+        let plan = plan_renames_with_conflicts(temp_dir.path(), &mapping, &options).unwrap();
+        assert_eq!(plan.renames.len(), 1);
+        assert!(plan.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_case_collision_between_targets() {
+        let renames = vec![
+            Rename {
+                path: PathBuf::from("old_name.rs"),
+                new_path: PathBuf::from("new_name.rs"),
+                kind: RenameKind::File,
+                coercion_applied: None,
+            },
+            Rename {
+                path: PathBuf::from("OldName.rs"),
+                new_path: PathBuf::from("NEW_NAME.rs"),
+                kind: RenameKind::File,
+                coercion_applied: None,
+            },
+        ];
+        let existing = vec![PathBuf::from("old_name.rs"), PathBuf::from("OldName.rs")];
+
+        let conflicts = detect_case_collisions(&renames, &existing);
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts
+            .iter()
+            .all(|c| c.kind == ConflictKind::CaseCollision));
+    }
+
+    #[test]
+    fn test_case_only_rename_is_not_a_collision() {
+        let renames = vec![Rename {
+            path: PathBuf::from("oldname.rs"),
+            new_path: PathBuf::from("OldName.rs"),
+            kind: RenameKind::File,
+            coercion_applied: None,
+        }];
+        let existing = vec![PathBuf::from("oldname.rs"), PathBuf::from("other.rs")];
+
+        assert!(detect_case_collisions(&renames, &existing).is_empty());
+    }
+
     #[test]
     fn test_case_insensitive_fs_detection() {
         let temp_dir = TempDir::new().unwrap();
@@ -595,6 +1059,60 @@ mod tests {
         assert!(plan.conflicts.is_empty());
     }
 
+    #[test]
+    fn test_decomposed_file_names() {
+        let decomposed = "cafe\u{301}_old.rs";
+        let mut mapping = BTreeMap::new();
+        mapping.insert("café_old".to_string(), "café_new".to_string());
+        let plan = |root: &Path| {
+            plan_renames_with_search(
+                root,
+                &mapping,
+                &PlanOptions::default(),
+                "café_old",
+                "café_new",
+            )
+            .unwrap()
+        };
+
+        // A decomposed name matches the composed search and is renamed to NFC
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(decomposed), "test").unwrap();
+        let renames = plan(temp_dir.path());
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames[0].path.file_name().unwrap(), decomposed);
+        assert_eq!(renames[0].new_path.file_name().unwrap(), "caf\u{e9}_new.rs");
+
+        // Unless git keeps decomposed names as they are
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .current_dir(temp_dir.path())
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if git(&["init", "-q"]) && git(&["config", "core.precomposeUnicode", "false"]) {
+            let renames = plan(temp_dir.path());
+            assert_eq!(
+                renames[0].new_path.file_name().unwrap(),
+                "cafe\u{301}_new.rs"
+            );
+        }
+
+        // Names that only differ in their Unicode form aren't renamed
+        let mut same = BTreeMap::new();
+        same.insert("café_old".to_string(), "cafe\u{301}_old".to_string());
+        let renames = plan_renames_with_search(
+            temp_dir.path(),
+            &same,
+            &PlanOptions::default(),
+            "café_old",
+            "cafe\u{301}_old",
+        )
+        .unwrap();
+        assert!(renames.is_empty());
+    }
+
     #[test]
     fn test_file_only_rename_option() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,286 @@
+//! Per-file scan results kept in `.renamify/cache` between plans.
+//!
+//! Each search term and set of matching options gets its own cache file,
+//! keyed by a hash of both, so changing anything that affects matching starts
+//! from an empty cache. A file is only skipped when the last scan found
+//! nothing in it and it is unchanged: same size and modification time, or,
+//! when only the modification time moved, the same content hash. Files with
+//! matches are always searched again, since their hunks aren't stored.
+
+use crate::content_cache::RACY_WINDOW;
+use crate::scanner::PlanOptions;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory under `.renamify` holding the cache files
+pub const SCAN_CACHE_DIR: &str = "cache";
+
+/// Cache files kept for other search terms before the oldest are removed
+const SCAN_CACHES_KEPT: usize = 8;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Stop plans from reading or writing `.renamify/cache` (`--no-cache`)
+pub fn set_disabled(disabled: bool) {
+    DISABLED.store(disabled, Ordering::SeqCst);
+}
+
+pub fn is_disabled() -> bool {
+    DISABLED.load(Ordering::SeqCst)
+}
+
+/// The cache directory to scan with from `renamify_dir`, unless caching or
+/// the workspace is turned off
+pub fn cache_dir(renamify_dir: &Path) -> Option<PathBuf> {
+    (!is_disabled() && !crate::workspace::is_disabled()).then(|| renamify_dir.join(SCAN_CACHE_DIR))
+}
+
+/// What the last scan found in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CachedScan {
+    size: u64,
+    modified_ns: u64,
+    hash: String,
+    /// Hunks, skipped matches and near misses found in the file
+    matches: usize,
+    binary: bool,
+}
+
+impl CachedScan {
+    pub(crate) fn new(metadata: &Metadata, content: &[u8]) -> Self {
+        Self {
+            size: metadata.len(),
+            modified_ns: modified_ns(metadata).unwrap_or_default(),
+            hash: content_hash(content),
+            matches: 0,
+            binary: false,
+        }
+    }
+
+    /// Set what the scan found in the file
+    pub(crate) const fn found(mut self, matches: usize, binary: bool) -> Self {
+        self.matches = matches;
+        self.binary = binary;
+        self
+    }
+
+    pub(crate) const fn binary(&self) -> bool {
+        self.binary
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: String,
+    files: HashMap<PathBuf, CachedScan>,
+}
+
+/// The cache for one search, loaded at the start of a scan and saved at the end
+pub(crate) struct ScanCache {
+    path: PathBuf,
+    previous: HashMap<PathBuf, CachedScan>,
+    current: Mutex<HashMap<PathBuf, CachedScan>>,
+}
+
+impl ScanCache {
+    /// Load the cache in `dir` for `search` -> `replace` with `options`. A
+    /// missing or unreadable cache, or one from another version, is empty.
+    pub(crate) fn load(dir: &Path, search: &str, replace: &str, options: &PlanOptions) -> Self {
+        let path = dir.join(format!("{}.json", cache_key(search, replace, options)));
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheFile>(&bytes).ok())
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION"))
+            .map(|cache| cache.files)
+            .unwrap_or_default();
+        Self {
+            path,
+            previous,
+            current: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The previous scan of `path` if it found nothing and the file still has
+    /// the size and modification time in `metadata`
+    pub(crate) fn unchanged(&self, path: &Path, metadata: &Metadata) -> Option<&CachedScan> {
+        self.previous.get(path).filter(|cached| {
+            cached.matches == 0
+                && cached.size == metadata.len()
+                && Some(cached.modified_ns) == modified_ns(metadata)
+        })
+    }
+
+    /// The previous scan of `path` if it found nothing and `content` is the
+    /// same as it was then, for files whose modification time has moved
+    pub(crate) fn same_content(&self, path: &Path, content: &[u8]) -> Option<&CachedScan> {
+        self.previous.get(path).filter(|cached| {
+            cached.matches == 0
+                && cached.size == content.len() as u64
+                && cached.hash == content_hash(content)
+        })
+    }
+
+    /// Record the scan of `path`, read at `read_at`. Files modified just
+    /// before they were read aren't recorded, since a second change within
+    /// the timestamp resolution wouldn't show.
+    pub(crate) fn record(&self, path: &Path, scan: CachedScan, read_at: SystemTime) {
+        let Ok(read_ns) = read_at.duration_since(UNIX_EPOCH) else {
+            return;
+        };
+        let racy = u128::from(scan.modified_ns) + RACY_WINDOW.as_nanos() > read_ns.as_nanos();
+        if racy {
+            return;
+        }
+        self.current
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), scan);
+    }
+
+    /// Write the recorded scans, together with earlier ones for files this
+    /// scan didn't visit, and remove the oldest caches for other searches
+    pub(crate) fn save(self) -> Result<()> {
+        let current = self.current.into_inner().unwrap();
+        if current.is_empty() {
+            return Ok(());
+        }
+        let mut files = self.previous;
+        files.extend(current);
+
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(dir)?;
+        let cache = CacheFile {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            files,
+        };
+        let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+        serde_json::to_writer(&mut temp, &cache)?;
+        temp.persist(&self.path)?;
+
+        prune(dir, &self.path);
+        Ok(())
+    }
+}
+
+/// Remove all but the most recently written cache files
+fn prune(dir: &Path, keep: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut caches: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path != keep && path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path))
+        })
+        .collect();
+    caches.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in caches.into_iter().skip(SCAN_CACHES_KEPT - 1) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Hash of everything that decides what a file matches
+fn cache_key(search: &str, replace: &str, options: &PlanOptions) -> String {
+    let mut options = options.clone();
+    options.plan_out = PathBuf::new();
+    let mut hasher = Sha256::new();
+    hasher.update(search.as_bytes());
+    hasher.update([0]);
+    hasher.update(replace.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&options).unwrap_or_default());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+fn content_hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn modified_ns(metadata: &Metadata) -> Option<u64> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(since_epoch.as_nanos()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_cache_reuses_unchanged_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let path = temp_dir.path().join("file.rs");
+        fs::write(&path, "fn other() {}").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let later = metadata.modified().unwrap() + RACY_WINDOW;
+        let options = PlanOptions::default();
+
+        let cache = ScanCache::load(&cache_dir, "old_name", "new_name", &options);
+        cache.record(&path, CachedScan::new(&metadata, b"fn other() {}"), later);
+        cache.save().unwrap();
+
+        let cache = ScanCache::load(&cache_dir, "old_name", "new_name", &options);
+        assert!(cache.unchanged(&path, &metadata).is_some());
+        assert!(cache.same_content(&path, b"fn other() {}").is_some());
+        assert!(cache.same_content(&path, b"fn old_name() {}").is_none());
+
+        // Another search, or other options, start from an empty cache
+        let other = ScanCache::load(&cache_dir, "old_name", "renamed", &options);
+        assert!(other.unchanged(&path, &metadata).is_none());
+        let options = PlanOptions {
+            ignore_ambiguous: true,
+            ..PlanOptions::default()
+        };
+        let other = ScanCache::load(&cache_dir, "old_name", "new_name", &options);
+        assert!(other.unchanged(&path, &metadata).is_none());
+
+        // A file without matches under one case mapping can match under another
+        let options = PlanOptions {
+            case_mapping: crate::case_model::CaseMapping::Turkish,
+            ..PlanOptions::default()
+        };
+        let other = ScanCache::load(&cache_dir, "old_name", "new_name", &options);
+        assert!(other.unchanged(&path, &metadata).is_none());
+    }
+
+    #[test]
+    fn test_scan_cache_skips_files_with_matches_and_racy_reads() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let matched = temp_dir.path().join("matched.rs");
+        let racy = temp_dir.path().join("racy.rs");
+        fs::write(&matched, "fn old_name() {}").unwrap();
+        fs::write(&racy, "fn other() {}").unwrap();
+        let matched_metadata = fs::metadata(&matched).unwrap();
+        let racy_metadata = fs::metadata(&racy).unwrap();
+        let options = PlanOptions::default();
+
+        let cache = ScanCache::load(&cache_dir, "old_name", "new_name", &options);
+        cache.record(
+            &matched,
+            CachedScan::new(&matched_metadata, b"fn old_name() {}").found(1, false),
+            matched_metadata.modified().unwrap() + RACY_WINDOW,
+        );
+        cache.record(
+            &racy,
+            CachedScan::new(&racy_metadata, b"fn other() {}"),
+            racy_metadata.modified().unwrap(),
+        );
+        cache.save().unwrap();
+
+        let cache = ScanCache::load(&cache_dir, "old_name", "new_name", &options);
+        assert!(cache.unchanged(&matched, &matched_metadata).is_none());
+        assert!(cache.unchanged(&racy, &racy_metadata).is_none());
+    }
+}
@@ -1,9 +1,22 @@
-use crate::acronym::AcronymSet;
+use crate::acronym::{AcronymRendering, AcronymSet};
+use crate::ambiguity::languages::literals::{LiteralRegions, RegionKind};
 use crate::ambiguity::{AmbiguityContext, AmbiguityResolver};
 use crate::case_constraints::filter_compatible_styles;
-use crate::case_model::{parse_to_tokens, singularize_token_case, to_style, Style, TokenModel};
+use crate::case_model::{
+    case_mapping, parse_to_tokens, parse_to_tokens_with_acronyms, singularize_token_case, to_style,
+    CaseMapping, Style, TokenModel,
+};
+use crate::content_cache::ContentCache;
+use crate::error::RenamifyError;
+use crate::fuzzy::FuzzyPattern;
+use crate::observer::{check_cancelled, OperationObserver};
 use crate::pattern::{build_pattern, Match};
-use aho_corasick::{AhoCorasick, MatchKind};
+use crate::rename::WalkedPath;
+use crate::scan_cache::{CachedScan, ScanCache};
+use crate::sql::{SqlPattern, SqlRegions};
+use crate::structured::{StructuredFilter, StructuredRegions};
+use crate::suppress::{FileLines, SuppressedLines};
+use aho_corasick::{AhoCorasick, MatchKind as AhoMatchKind};
 use anyhow::Result;
 use bstr::ByteSlice;
 use content_inspector::ContentType;
@@ -11,13 +24,15 @@ use globset::{Glob, GlobSet, GlobSetBuilder};
 use memmap2::Mmap;
 use rayon::prelude::*;
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 use ts_rs::TS;
 
 /// Convert byte offset to character offset in a UTF-8 string
@@ -37,16 +52,7 @@ fn byte_offset_to_char_offset(text: &str, byte_offset: usize) -> usize {
 }
 
 fn capitalize_token(text: &str) -> String {
-    if text.is_empty() {
-        return String::new();
-    }
-
-    let mut chars = text.chars();
-    let first = chars.next().unwrap();
-    let mut result = String::new();
-    result.extend(first.to_uppercase());
-    result.push_str(&chars.as_str().to_lowercase());
-    result
+    case_mapping().capitalize(text)
 }
 
 /// Normalize a path by removing Windows long path prefix if present
@@ -70,7 +76,7 @@ fn default_enable_plural_variants() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub struct PlanOptions {
     pub includes: Vec<String>,
@@ -89,6 +95,8 @@ pub struct PlanOptions {
     pub exclude_match: Vec<String>, // Specific matches to exclude
     #[ts(optional)]
     pub exclude_matching_lines: Option<String>, // Regex to exclude lines matching this pattern
+    #[serde(default)]
+    pub exclude_lines_in_file: Vec<FileLines>, // Line ranges of specific files to leave alone
     pub no_acronyms: bool,          // Disable acronym detection
     pub include_acronyms: Vec<String>, // Additional acronyms to recognize
     pub exclude_acronyms: Vec<String>, // Default acronyms to exclude
@@ -98,21 +106,275 @@ pub struct PlanOptions {
     pub atomic_config: Option<crate::atomic::AtomicConfig>, // Atomic identifier configuration
     #[serde(default = "default_enable_plural_variants")]
     pub enable_plural_variants: bool,
+    #[serde(default)]
+    pub allow_case_collisions: bool, // Allow renames that only differ by case from another path
+    #[serde(default)]
+    pub literal_mode: LiteralMode,
+    #[serde(default)]
+    #[ts(optional)]
+    pub structured: Option<StructuredFilter>, // Restrict matches in YAML/JSON/TOML files
+    #[serde(default)]
+    pub sql: bool, // Treat .sql files as SQL: case-insensitive identifiers, keywords left alone
+    #[serde(default)]
+    pub report_skipped: bool, // Record filtered-out matches in Plan.skipped
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub fuzzy: u8, // Edit distance for near misses in Plan.near_misses, 0 to disable
+    #[serde(default)]
+    pub include_submodules: bool, // Walk into submodules and other nested repositories
+    #[serde(default)]
+    pub paths_only: bool, // Only plan file and directory renames, without reading file contents
+    #[serde(default)]
+    pub contents_only: bool, // Only plan content edits, without walking the tree for renames
+    #[serde(default)]
+    pub match_anchor: MatchAnchor, // Where the search term must sit within an identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "string")]
+    pub working_dir: Option<PathBuf>, // Directory the plan is made in, instead of the current directory
+    #[serde(default)]
+    pub walk: WalkOptions, // Depth, file system and symlink bounds for the directory walk
+    #[serde(default)]
+    pub acronym_rendering: AcronymRendering, // How acronyms are written in camelCase and PascalCase
+    #[serde(default)]
+    pub case_mapping: CaseMapping, // How letters change case in variants and replacements
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub enum CoercionMode {
-    Auto,                          // Default: automatically detect and apply
+    #[default]
+    Auto, // Default: automatically detect and apply
     Off,                           // Disable coercion
     Force(crate::coercion::Style), // Force a specific style
 }
 
+impl std::str::FromStr for CoercionMode {
+    type Err = String;
+
+    /// Parse `auto`, `off` or `force:<style>` as accepted by `--coerce`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "off" => Ok(Self::Off),
+            other => other
+                .strip_prefix("force:")
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid coercion mode '{}', expected auto, off or force:<style>",
+                        s
+                    )
+                })?
+                .parse()
+                .map(Self::Force),
+        }
+    }
+}
+
+impl std::fmt::Display for CoercionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Off => write!(f, "off"),
+            Self::Force(style) => write!(f, "force:{}", style.name()),
+        }
+    }
+}
+
+/// Which matches to keep based on where they occur in code files.
+///
+/// Files whose language is not recognised keep all their matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum LiteralMode {
+    /// Keep every match
+    #[default]
+    All,
+    /// Drop matches inside string literals (`--skip-strings`)
+    SkipStrings,
+    /// Keep only matches inside string literals and comments, and plan no
+    /// path renames (`--only-strings`)
+    OnlyStrings,
+}
+
+impl LiteralMode {
+    /// Whether a match in a region of this kind (None for code) is kept
+    pub fn keeps(self, region: Option<RegionKind>) -> bool {
+        match self {
+            Self::All => true,
+            Self::SkipStrings => region != Some(RegionKind::String),
+            Self::OnlyStrings => region.is_some(),
+        }
+    }
+}
+
+/// Files larger than this are left out of content scanning by default
+pub const DEFAULT_MAX_FILESIZE: u64 = 10 * 1024 * 1024;
+
+#[allow(clippy::unnecessary_wraps)]
+const fn default_max_filesize() -> Option<u64> {
+    Some(DEFAULT_MAX_FILESIZE)
+}
+
+const fn default_include_tracked() -> bool {
+    true
+}
+
+/// Bounds on the directory walk and the files it searches, mirroring
+/// ripgrep's flags
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct WalkOptions {
+    /// Descend at most this many directories below each search path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub max_depth: Option<usize>,
+    /// Don't cross into other file systems, such as NFS mounts
+    #[serde(default)]
+    pub one_file_system: bool,
+    /// Follow symbolic links to files and directories
+    #[serde(default)]
+    pub follow_links: bool,
+    /// Contents of files larger than this many bytes are not searched, though
+    /// their names still are; None searches files of any size
+    #[serde(default = "default_max_filesize")]
+    #[ts(type = "number | null")]
+    pub max_filesize: Option<u64>,
+    /// Also search files git tracks that a `.gitignore` rule matches
+    #[serde(default = "default_include_tracked")]
+    pub include_tracked: bool,
+    /// Only search files of these types from [`crate::language::FILE_TYPES`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub types: Vec<String>,
+    /// Skip files of these types
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub types_not: Vec<String>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            one_file_system: false,
+            follow_links: false,
+            max_filesize: default_max_filesize(),
+            include_tracked: true,
+            types: Vec::new(),
+            types_not: Vec::new(),
+        }
+    }
+}
+
+/// Where the search term has to sit within the identifier around a match.
+///
+/// Identifiers are split with the token model, so `old` is a leading token of
+/// both `old_config` and `OldConfig` but a trailing one of `threshold_old`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum MatchAnchor {
+    /// Match the search term anywhere in an identifier
+    #[default]
+    Any,
+    /// Only match when the search term leads the identifier
+    Prefix,
+    /// Only match when the search term ends the identifier
+    Suffix,
+    /// Only match when the search term is the whole identifier
+    Whole,
+}
+
+impl MatchAnchor {
+    /// Whether the match at `start..end` of `text` sits where this anchor allows.
+    ///
+    /// The search term's tokens are looked up among the tokens of the enclosing
+    /// identifier that the match covers, falling back to the matched text's own
+    /// tokens (for plural variants). A match inside a single token, like `old`
+    /// in `threshold`, is never anchored.
+    pub fn keeps(self, text: &str, start: usize, end: usize, search: &str) -> bool {
+        if self == Self::Any
+            || start > end
+            || !text.is_char_boundary(start)
+            || !text.is_char_boundary(end)
+        {
+            return true;
+        }
+
+        let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        let identifier_start = text[..start]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_identifier_char(*c))
+            .last()
+            .map_or(start, |(i, _)| i);
+        let identifier_end = text[end..]
+            .char_indices()
+            .find(|(_, c)| !is_identifier_char(*c))
+            .map_or(text.len(), |(i, _)| end + i);
+
+        let identifier_text = &text[identifier_start..identifier_end];
+        let identifier = parse_to_tokens(identifier_text).tokens;
+
+        // Byte range of each token, to tell tokens apart from substrings of them
+        let lower = identifier_text.to_ascii_lowercase();
+        let mut spans = Vec::with_capacity(identifier.len());
+        let mut cursor = 0;
+        for token in &identifier {
+            let Some(offset) = lower[cursor..].find(&token.text.to_ascii_lowercase()) else {
+                return true;
+            };
+            cursor += offset + token.text.len();
+            spans.push((cursor - token.text.len(), cursor));
+        }
+        let (start, end) = (start - identifier_start, end - identifier_start);
+
+        let windows = |needle: &str| {
+            let needle = parse_to_tokens(needle).tokens;
+            if needle.is_empty() || needle.len() > identifier.len() {
+                return Vec::new();
+            }
+            (0..=identifier.len() - needle.len())
+                .filter(|&i| {
+                    spans[i].0 >= start
+                        && spans[i + needle.len() - 1].1 <= end
+                        && needle
+                            .iter()
+                            .zip(&identifier[i..])
+                            .all(|(a, b)| a.text.eq_ignore_ascii_case(&b.text))
+                })
+                .map(|i| (i, i + needle.len()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut found = windows(search);
+        if found.is_empty() {
+            found = windows(&text[identifier_start + start..identifier_start + end]);
+        }
+
+        found.into_iter().any(|(first, last)| match self {
+            Self::Any => true,
+            Self::Prefix => first == 0,
+            Self::Suffix => last == identifier.len(),
+            Self::Whole => first == 0 && last == identifier.len(),
+        })
+    }
+}
+
 impl PlanOptions {
     /// Returns true if binary files should be treated as text (level 3/-uuu)
     pub fn binary_as_text(&self) -> bool {
         self.unrestricted_level >= 3
     }
+
+    /// Run `f` with this plan's acronym rendering and case mapping in effect
+    /// on the current thread
+    pub fn with_casing<R>(&self, f: impl FnOnce() -> R) -> R {
+        crate::acronym::with_rendering(self.acronym_rendering, || {
+            crate::case_model::with_case_mapping(self.case_mapping, f)
+        })
+    }
 }
 
 impl Default for PlanOptions {
@@ -130,6 +392,7 @@ impl Default for PlanOptions {
             coerce_separators: CoercionMode::Auto,
             exclude_match: vec![],
             exclude_matching_lines: None,
+            exclude_lines_in_file: vec![],
             no_acronyms: false, // Default: enable acronym detection
             include_acronyms: vec![],
             exclude_acronyms: vec![],
@@ -137,11 +400,25 @@ impl Default for PlanOptions {
             ignore_ambiguous: false, // Default: process ambiguous identifiers
             atomic_config: None,     // Default: no atomic configuration
             enable_plural_variants: true,
+            allow_case_collisions: false,
+            literal_mode: LiteralMode::All,
+            structured: None,
+            sql: false,
+            report_skipped: false,
+            fuzzy: 0,
+            include_submodules: false,
+            paths_only: false,
+            contents_only: false,
+            match_anchor: MatchAnchor::Any,
+            working_dir: None,
+            walk: WalkOptions::default(),
+            acronym_rendering: AcronymRendering::default(),
+            case_mapping: CaseMapping::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub struct MatchHunk {
     #[ts(type = "string")]
@@ -154,8 +431,9 @@ pub struct MatchHunk {
     pub char_offset: u32, // Character offset from start of line (for JS/frontend)
     pub variant: String,
     pub content: String, // The word/variant being replaced
-    #[serde(skip_serializing_if = "String::is_empty")]
-    pub replace: String, // The replacement word/variant
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[ts(optional, as = "Option<String>")]
+    pub replace: String, // The replacement word/variant, left out by `search` without `--replace`
     #[ts(type = "number")]
     pub start: usize,
     #[ts(type = "number")]
@@ -178,6 +456,101 @@ pub struct MatchHunk {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub patch_hash: Option<String>, // SHA256 hash of the patch file for this change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub style: Option<Style>, // Case style of the variant that matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub match_kind: Option<MatchKind>, // Whole identifier or part of a longer one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub boundary_before: Option<BoundaryClass>, // Character just before the match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub boundary_after: Option<BoundaryClass>, // Character just after the match
+}
+
+#[cfg(test)]
+impl MatchHunk {
+    /// A hunk replacing ASCII `content` at `byte_offset` on `line` of `file`,
+    /// with every optional field unset
+    pub(crate) fn for_test(
+        file: &str,
+        line: u64,
+        byte_offset: u32,
+        content: &str,
+        replace: &str,
+    ) -> Self {
+        Self {
+            file: PathBuf::from(file),
+            line,
+            byte_offset,
+            char_offset: byte_offset,
+            variant: content.to_string(),
+            content: content.to_string(),
+            replace: replace.to_string(),
+            start: byte_offset as usize,
+            end: byte_offset as usize + content.len(),
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        }
+    }
+}
+
+/// How a hunk's content was matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// A generated variant, matched as a whole identifier
+    Exact,
+    /// A variant inside a longer identifier, such as `OldName` in `getOldName`
+    Compound,
+}
+
+/// The kind of character on one side of a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryClass {
+    /// The start or end of the line
+    LineEdge,
+    Whitespace,
+    /// `_`, `-`, `.`, `/` or `:`, which join the words of an identifier or path
+    Separator,
+    /// Any other ASCII punctuation, such as quotes and brackets
+    Punctuation,
+    /// A letter or digit, so the match is part of a longer word
+    Alphanumeric,
+    Other,
+}
+
+impl BoundaryClass {
+    pub fn of(c: Option<char>) -> Self {
+        match c {
+            None | Some('\n' | '\r') => Self::LineEdge,
+            Some(c) if c.is_whitespace() => Self::Whitespace,
+            Some('_' | '-' | '.' | '/' | ':') => Self::Separator,
+            Some(c) if c.is_ascii_punctuation() => Self::Punctuation,
+            Some(c) if c.is_alphanumeric() => Self::Alphanumeric,
+            Some(_) => Self::Other,
+        }
+    }
+
+    /// The classes on either side of `line[start..end]`
+    pub fn around(line: &str, start: usize, end: usize) -> (Self, Self) {
+        let before = line.get(..start).and_then(|text| text.chars().next_back());
+        let after = line.get(end..).and_then(|text| text.chars().next());
+        (Self::of(before), Self::of(after))
+    }
 }
 
 /// Helper function to check if a `PathBuf` is empty (for serde skip)
@@ -185,21 +558,21 @@ fn is_empty_path(p: &Path) -> bool {
     p.as_os_str().is_empty()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub struct Rename {
     #[ts(type = "string")]
     pub path: PathBuf,
-    #[serde(skip_serializing_if = "is_empty_path")]
-    #[ts(type = "string")]
-    pub new_path: PathBuf,
+    #[serde(default, skip_serializing_if = "is_empty_path")]
+    #[ts(optional, as = "Option<String>")]
+    pub new_path: PathBuf, // Left out by `search` without `--replace`
     pub kind: RenameKind,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub coercion_applied: Option<String>, // Details about coercion if applied
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 #[serde(rename_all = "lowercase")]
 #[ts(rename_all = "lowercase")]
@@ -208,7 +581,7 @@ pub enum RenameKind {
     Dir,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub struct Stats {
     #[ts(type = "number")]
@@ -219,16 +592,177 @@ pub struct Stats {
     pub matches_by_variant: HashMap<String, usize>,
     #[ts(type = "number")]
     pub files_with_matches: usize,
+    /// Total size of the files that were read
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub bytes_scanned: u64,
+    #[serde(default)]
+    pub files_skipped: SkippedFiles,
+    #[serde(default)]
+    pub timings: ScanTimings,
+}
+
+/// Files the scan visited but did not search, by reason.
+///
+/// Files hidden by ignore files are never visited, so they are not counted here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct SkippedFiles {
+    /// Binary files (searched anyway with `-uuu`)
+    #[ts(type = "number")]
+    pub binary: usize,
+    /// Files filtered out by `--include` / `--exclude`
+    #[ts(type = "number")]
+    pub excluded: usize,
+    /// Files that could not be read
+    #[ts(type = "number")]
+    pub unreadable: usize,
+    /// The files that could not be read, with why
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<UnreadableFile>>")]
+    pub unreadable_files: Vec<UnreadableFile>,
+    /// Files over `--max-filesize`, whose names were still searched
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<LargeFile>>")]
+    pub too_large: Vec<LargeFile>,
+}
+
+/// A file left out of content scanning for its size
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct LargeFile {
+    #[ts(type = "string")]
+    pub path: PathBuf,
+    #[ts(type = "number")]
+    pub bytes: u64,
+}
+
+/// A file whose contents could not be read, so any matches in it are missing
+/// from the plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct UnreadableFile {
+    #[ts(type = "string")]
+    pub path: PathBuf,
+    /// The error reading it, e.g. `Permission denied (os error 13)`
+    pub error: String,
+}
+
+/// A match that a filter left out of the plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct SkippedMatch {
+    pub file: PathBuf,
+    /// Line of the match; absent for binary files, which are not read by line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub line: Option<u64>,
+    pub content: String,
+    pub reason: SkipReason,
+}
+
+/// Why a match was left out of the plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SkipReason {
+    /// `--exclude-match`
+    ExcludeMatch,
+    /// `--exclude-matching-lines`
+    ExcludeMatchingLines,
+    /// `--exclude-lines-in-file`
+    ExcludeLinesInFile,
+    /// A `renamify:ignore-*` comment
+    InlineIgnore,
+    /// `--ignore-ambiguous`
+    Ambiguous,
+    /// `--skip-strings` or `--only-strings`
+    Literal,
+    /// `--only-keys`, `--only-values` or `--yaml-path`
+    Structured,
+    /// An unquoted SQL keyword (`sql` profile)
+    SqlKeyword,
+    /// The file is binary (searched anyway with `-uuu`)
+    Binary,
+    /// `--match-anchor`
+    Anchor,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::ExcludeMatch => "excluded by --exclude-match",
+            Self::ExcludeMatchingLines => "line matches --exclude-matching-lines",
+            Self::ExcludeLinesInFile => "line excluded by --exclude-lines-in-file",
+            Self::InlineIgnore => "line marked with a renamify:ignore comment",
+            Self::Ambiguous => "ambiguous identifier (--ignore-ambiguous)",
+            Self::Literal => "filtered by --skip-strings or --only-strings",
+            Self::Structured => "filtered by --only-keys, --only-values or --yaml-path",
+            Self::SqlKeyword => "unquoted SQL keyword",
+            Self::Binary => "binary file",
+            Self::Anchor => "search term not at the --match-anchor position",
+        };
+        f.write_str(description)
+    }
+}
+
+/// Time spent in each scan phase, in milliseconds.
+///
+/// Files are read and matched in parallel, so `read_ms` and `match_ms` are
+/// summed across threads and can exceed the wall-clock time of the scan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[allow(clippy::struct_field_names)]
+pub struct ScanTimings {
+    pub walk_ms: f64,
+    pub read_ms: f64,
+    pub match_ms: f64,
+    pub rename_plan_ms: f64,
+}
+
+fn duration_ms(duration: Duration) -> f64 {
+    // Round to microseconds to keep plan files readable
+    (duration.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+impl Stats {
+    /// Recompute match statistics for a set of hunks
+    pub fn from_matches(files_scanned: usize, matches: &[MatchHunk]) -> Self {
+        let mut matches_by_variant = HashMap::new();
+        for hunk in matches {
+            *matches_by_variant.entry(hunk.variant.clone()).or_insert(0) += 1;
+        }
+        let files_with_matches = matches
+            .iter()
+            .map(|hunk| &hunk.file)
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+
+        Self {
+            files_scanned,
+            total_matches: matches.len(),
+            matches_by_variant,
+            files_with_matches,
+            ..Self::default()
+        }
+    }
 }
 
 #[derive(Default)]
 struct FileOutcome {
     scanned: bool,
+    binary: bool,
+    bytes: u64,
+    read_time: Duration,
+    elapsed: Duration,
     hunks: Vec<MatchHunk>,
     matches_by_variant: HashMap<String, usize>,
+    skipped: Vec<SkippedMatch>,
+    near_misses: Vec<MatchHunk>,
+    unreadable: Option<UnreadableFile>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[ts(export)]
 pub struct Plan {
     pub id: String,
@@ -242,9 +776,33 @@ pub struct Plan {
     pub paths: Vec<Rename>,
     pub stats: Stats,
     pub version: String,
+    /// The `--coerce` mode the plan was built with, so it can be reproduced
+    #[serde(default)]
+    pub coercion: CoercionMode,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[ts(optional, type = "Array<string>")]
     pub created_directories: Option<Vec<PathBuf>>, // Directories created during apply that should be removed on undo
+    /// Matches left out by a filter, recorded with `--show-skipped`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<SkippedMatch>>")]
+    pub skipped: Vec<SkippedMatch>,
+    /// Near-miss spellings found with `--fuzzy`, left out of `matches` until confirmed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<MatchHunk>>")]
+    pub near_misses: Vec<MatchHunk>,
+    /// Renames into ignored or protected paths, left out of `paths` until confirmed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<crate::suspicious::SuspiciousRename>>")]
+    pub suspicious_renames: Vec<crate::suspicious::SuspiciousRename>,
+    /// Problems worth a look before applying, most severe first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<crate::warnings::PlanWarning>>")]
+    pub warnings: Vec<crate::warnings::PlanWarning>,
+    /// Every option the plan was built with; missing from merged plans and
+    /// plans made before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub options: Option<PlanOptions>,
 }
 
 /// Backward-compatible single-path scan (for tests)
@@ -253,17 +811,121 @@ pub fn scan_repository(root: &Path, old: &str, new: &str, options: &PlanOptions)
 }
 
 /// Multi-path repository scan
-#[allow(clippy::too_many_lines)]
 pub fn scan_repository_multi(
     roots: &[PathBuf],
     search: &str,
     replace: &str,
     options: &PlanOptions,
+) -> Result<Plan> {
+    scan(
+        roots,
+        None,
+        search,
+        replace,
+        options,
+        None,
+        ScanCaches::default(),
+    )
+}
+
+/// Multi-path repository scan that reports each scanned file to `observer`
+/// and stops with `RenamifyError::Cancelled` when it asks to
+pub fn scan_repository_observed(
+    roots: &[PathBuf],
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+    observer: Option<&dyn OperationObserver>,
+) -> Result<Plan> {
+    scan(
+        roots,
+        None,
+        search,
+        replace,
+        options,
+        observer,
+        ScanCaches::default(),
+    )
+}
+
+/// Caches a scan reads from and fills
+#[derive(Clone, Copy, Default)]
+pub struct ScanCaches<'a> {
+    /// Keeps the contents of files with matches, for applying the plan
+    /// without reading them again
+    pub contents: Option<&'a ContentCache>,
+    /// Directory of per-file results from earlier scans (`.renamify/cache`),
+    /// so unchanged files without matches aren't searched again
+    pub files: Option<&'a Path>,
+}
+
+/// [`scan_repository_observed`] that reads from and fills `caches`
+pub fn scan_repository_cached(
+    roots: &[PathBuf],
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+    observer: Option<&dyn OperationObserver>,
+    caches: ScanCaches<'_>,
+) -> Result<Plan> {
+    scan(roots, None, search, replace, options, observer, caches)
+}
+
+/// Plan `search` -> `replace` for a single in-memory buffer.
+///
+/// `path` names the buffer in the hunks and decides how it is parsed (string
+/// literals, YAML keys, SQL); nothing is read from disk and no paths are
+/// renamed.
+pub fn scan_content(
+    path: &Path,
+    content: &[u8],
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+) -> Result<Plan> {
+    scan(
+        &[],
+        Some((path, content)),
+        search,
+        replace,
+        options,
+        None,
+        ScanCaches::default(),
+    )
+}
+
+/// Scan the files under `roots`, or only `buffer` when given
+fn scan(
+    roots: &[PathBuf],
+    buffer: Option<(&Path, &[u8])>,
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+    observer: Option<&dyn OperationObserver>,
+    caches: ScanCaches<'_>,
+) -> Result<Plan> {
+    options
+        .with_casing(|| scan_with_casing(roots, buffer, search, replace, options, observer, caches))
+}
+
+/// [`scan`] once the plan's acronym rendering and case mapping are in effect
+#[allow(clippy::too_many_lines)]
+fn scan_with_casing(
+    roots: &[PathBuf],
+    buffer: Option<(&Path, &[u8])>,
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+    observer: Option<&dyn OperationObserver>,
+    caches: ScanCaches<'_>,
 ) -> Result<Plan> {
     // Validate the exclude pattern if provided
     if let Some(ref pattern) = options.exclude_matching_lines {
         Regex::new(pattern).map_err(|e| {
-            anyhow::anyhow!("Invalid regex pattern for --exclude-matching-lines: {}", e)
+            RenamifyError::InvalidInput(format!(
+                "Invalid regex pattern for --exclude-matching-lines: {}",
+                e
+            ))
         })?;
     }
 
@@ -283,8 +945,6 @@ pub fn scan_repository_multi(
     let variants: Vec<String> = variant_map.keys().cloned().collect();
     let _pattern = build_pattern(&variants)?;
 
-    let search_tokens = crate::case_model::parse_to_tokens_with_acronyms(search, &acronym_set);
-    let replace_tokens = crate::case_model::parse_to_tokens_with_acronyms(replace, &acronym_set);
     let search_is_atomic = options
         .atomic_config
         .as_ref()
@@ -293,6 +953,16 @@ pub fn scan_repository_multi(
         .atomic_config
         .as_ref()
         .is_some_and(|c| c.should_treat_replace_atomic(replace));
+    let search_tokens = if search_is_atomic {
+        crate::atomic::parse_atomic(search)
+    } else {
+        crate::case_model::parse_to_tokens_with_acronyms(search, &acronym_set)
+    };
+    let replace_tokens = if replace_is_atomic {
+        crate::atomic::parse_atomic(replace)
+    } else {
+        crate::case_model::parse_to_tokens_with_acronyms(replace, &acronym_set)
+    };
 
     let mut matcher_patterns = variants;
     let mut token_variant_groups: Vec<Vec<String>> = Vec::new();
@@ -313,27 +983,23 @@ pub fn scan_repository_multi(
         matcher_patterns.push(original.clone());
         variants_for_token.push(original.clone());
 
-        let lower = text.to_lowercase();
+        let mapping = case_mapping();
+        let lower = mapping.lower(text);
         if !variants_for_token.iter().any(|v| v == &lower) {
             matcher_patterns.push(lower.clone());
             variants_for_token.push(lower);
         }
 
-        let upper = text.to_uppercase();
+        let upper = mapping.upper(text);
         if !variants_for_token.iter().any(|v| v == &upper) {
             matcher_patterns.push(upper.clone());
             variants_for_token.push(upper);
         }
 
-        if let Some(first_char) = text.chars().next() {
-            let rest = &text[first_char.len_utf8()..];
-            let mut title = String::new();
-            title.extend(first_char.to_uppercase());
-            title.push_str(&rest.to_lowercase());
-            if !variants_for_token.iter().any(|v| v == &title) {
-                matcher_patterns.push(title.clone());
-                variants_for_token.push(title);
-            }
+        let title = mapping.capitalize(text);
+        if !title.is_empty() && !variants_for_token.iter().any(|v| v == &title) {
+            matcher_patterns.push(title.clone());
+            variants_for_token.push(title);
         }
 
         if enable_singular_variants && idx == search_tokens.tokens.len() - 1 {
@@ -341,8 +1007,8 @@ pub fn scan_repository_multi(
                 let mut singular_forms = Vec::new();
                 for form in [
                     singular_base.clone(),
-                    singular_base.to_lowercase(),
-                    singular_base.to_uppercase(),
+                    mapping.lower(&singular_base),
+                    mapping.upper(&singular_base),
                     capitalize_token(&singular_base),
                 ] {
                     if form.is_empty() {
@@ -393,13 +1059,23 @@ pub fn scan_repository_multi(
         .as_deref()
         .unwrap_or(default_styles.as_slice());
     let identifier_extractor = crate::compound_scanner::IdentifierExtractor::new(styles_slice);
+    // Binary files are only searched to report them as skipped
+    let binary_pattern = options
+        .report_skipped
+        .then(|| build_pattern(&variant_map.keys().cloned().collect::<Vec<_>>()).ok())
+        .flatten();
+    let sql_pattern = options
+        .sql
+        .then(|| SqlPattern::new(search, replace))
+        .flatten();
+    let fuzzy_pattern = FuzzyPattern::new(search, replace, styles_slice, options.fuzzy);
 
     let variant_matcher = if matcher_patterns.is_empty() {
         None
     } else {
         Some(
             AhoCorasick::builder()
-                .match_kind(MatchKind::LeftmostFirst)
+                .match_kind(AhoMatchKind::LeftmostFirst)
                 .build(&matcher_patterns)?,
         )
     };
@@ -409,202 +1085,468 @@ pub fn scan_repository_multi(
     } else {
         Some(
             AhoCorasick::builder()
-                .match_kind(MatchKind::LeftmostFirst)
+                .match_kind(AhoMatchKind::LeftmostFirst)
                 .build(&token_patterns)?,
         )
     };
 
-    let include_globs = build_globset(&options.includes)?;
+    let include_globs = build_include_globset(&options.includes)?;
     let exclude_globs = build_globset(&options.excludes)?;
 
     let mut matches = Vec::new();
-    let mut stats = Stats {
-        files_scanned: 0,
-        total_matches: 0,
-        matches_by_variant: HashMap::new(),
-        files_with_matches: 0,
-    };
+    let mut skipped: Vec<SkippedMatch> = Vec::new();
+    let mut near_misses: Vec<MatchHunk> = Vec::new();
+    let mut stats = Stats::default();
 
-    // Use shared walker configuration
-    let walker = crate::configure_walker(roots, options).build();
+    // Paths are identifiers too (module and import names), so only-strings leaves them alone
+    let plan_paths = buffer.is_none()
+        && !options.contents_only
+        && (options.rename_files || options.rename_dirs)
+        && options.literal_mode != LiteralMode::OnlyStrings;
 
+    let walk_start = Instant::now();
     let mut file_entries = Vec::new();
-    for entry in walker {
-        let Ok(entry) = entry else {
-            continue;
-        };
-
-        if !entry.file_type().is_some_and(|t| t.is_file()) {
-            continue;
-        }
-
-        let path = entry.path().to_path_buf();
-        let relative = roots
-            .iter()
-            .find_map(|root| path.strip_prefix(root).ok())
-            .map_or_else(|| path.clone(), std::borrow::ToOwned::to_owned);
-
-        if let Some(ref includes) = include_globs {
-            if !includes.is_match(&relative) {
-                continue;
+    // Every walked path under each root, for rename planning after the scan
+    let mut root_paths: Vec<Vec<WalkedPath>> = vec![Vec::new(); roots.len()];
+    if let Some((path, _)) = buffer {
+        file_entries.push(path.to_path_buf());
+    } else if plan_paths || !options.paths_only {
+        // Use shared walker configuration
+        let walker = crate::configure_walker(roots, options).build();
+        let mut seen_targets = HashSet::new();
+
+        // Tracked files a .gitignore rule kept the walker out of come after it,
+        // with the root they belong to
+        let tracked = roots.iter().enumerate().flat_map(|(index, root)| {
+            crate::tracked::ignored_tracked_files(root, options)
+                .into_iter()
+                .map(move |(path, depth)| {
+                    let file_type = fs::symlink_metadata(&path)
+                        .ok()
+                        .map(|metadata| metadata.file_type());
+                    let entry = WalkedPath {
+                        path,
+                        depth,
+                        file_type,
+                    };
+                    (Some(index), entry)
+                })
+        });
+        let entries = walker
+            .flatten()
+            .map(|entry| (None, WalkedPath::from(&entry)))
+            .chain(tracked);
+
+        // The walker visits the roots one after another, each starting at depth 0
+        let mut root_index = 0;
+        let mut next_root = 0;
+        for (tracked_root, entry) in entries {
+            let is_file = entry.file_type.is_some_and(|t| t.is_file());
+            let path = entry.path.clone();
+
+            if plan_paths {
+                if let Some(index) = tracked_root {
+                    root_index = index;
+                } else if entry.depth == 0 {
+                    if let Some(offset) = roots[next_root..]
+                        .iter()
+                        .position(|root| *root == entry.path)
+                    {
+                        root_index = next_root + offset;
+                        next_root = root_index + 1;
+                    }
+                }
+                if let Some(paths) = root_paths.get_mut(root_index) {
+                    paths.push(entry);
+                }
             }
-        }
 
-        if let Some(ref excludes) = exclude_globs {
-            if excludes.is_match(&relative) {
+            if options.paths_only || !is_file {
                 continue;
             }
-        }
-
-        file_entries.push(path);
-    }
-
-    let outcomes: Vec<FileOutcome> = file_entries
-        .par_iter()
-        .map(|path| {
-            let mut outcome = FileOutcome::default();
 
-            let content = match read_file_content(path) {
-                Ok(content) => {
-                    outcome.scanned = true;
-                    content
-                },
-                Err(_) => return outcome,
-            };
+            let relative = roots
+                .iter()
+                .find_map(|root| path.strip_prefix(root).ok())
+                .map_or_else(|| path.clone(), std::borrow::ToOwned::to_owned);
 
-            if !options.binary_as_text() && is_binary(&content) {
-                return outcome;
+            if let Some(ref includes) = include_globs {
+                if !includes.is_match(&relative) {
+                    stats.files_skipped.excluded += 1;
+                    continue;
+                }
             }
 
-            let mut token_line_hits: BTreeSet<usize> = BTreeSet::new();
-            let mut line_offsets = Vec::new();
-            let mut pos = 0;
-            for line in content.lines_with_terminator() {
-                line_offsets.push(pos);
-                pos += line.len();
-            }
-            if line_offsets.is_empty() {
-                line_offsets.push(0);
+            if let Some(ref excludes) = exclude_globs {
+                if excludes.is_match(&relative) {
+                    stats.files_skipped.excluded += 1;
+                    continue;
+                }
             }
 
-            let tokens_satisfied = if let Some(token_matcher) = token_matcher.as_ref() {
-                if token_variant_groups.is_empty() {
-                    false
+            if let Some(limit) = options.walk.max_filesize {
+                let metadata = if options.walk.follow_links {
+                    fs::metadata(&path)
                 } else {
-                    let mut present = vec![false; token_variant_groups.len()];
-                    for mat in token_matcher.find_iter(&content) {
-                        let token_idx = token_pattern_to_group[mat.pattern()];
-                        present[token_idx] = true;
-                        let offset = mat.start();
-                        let line = match line_offsets.binary_search(&offset) {
-                            Ok(idx) => idx + 1,
-                            Err(idx) => {
-                                if idx == 0 {
-                                    1
-                                } else {
-                                    idx
-                                }
-                            },
-                        };
-                        token_line_hits.insert(line);
-                    }
-                    present.iter().all(|&p| p)
+                    fs::symlink_metadata(&path)
+                };
+                let bytes = metadata.map_or(0, |metadata| metadata.len());
+                if bytes > limit {
+                    stats
+                        .files_skipped
+                        .too_large
+                        .push(LargeFile { path, bytes });
+                    continue;
                 }
-            } else {
-                false
-            };
-
-            let variant_found = variant_matcher
-                .as_ref()
-                .is_some_and(|matcher| matcher.find(&content).is_some());
+            }
 
-            if !variant_found && !tokens_satisfied {
-                return outcome;
+            // With symlinks followed, a file can be reached by several paths;
+            // scan it once so its matches aren't edited twice
+            if options.walk.follow_links
+                && !seen_targets.insert(path.canonicalize().unwrap_or_else(|_| path.clone()))
+            {
+                continue;
             }
 
-            if std::env::var("RENAMIFY_DEBUG_COMPOUND").is_ok() {
-                eprintln!("SCANNER: Using styles: {:?}", styles_slice);
-                eprintln!("SCANNER: options.styles = {:?}", options.styles);
+            file_entries.push(path);
+        }
+    }
+    stats.timings.walk_ms = duration_ms(walk_start.elapsed());
+
+    let file_cache = caches
+        .files
+        .filter(|_| buffer.is_none())
+        .map(|dir| ScanCache::load(dir, search, replace, options));
+
+    let search_file = |path: &PathBuf,
+                       content: Vec<u8>,
+                       metadata: Option<fs::Metadata>,
+                       read_at: SystemTime,
+                       mut outcome: FileOutcome|
+     -> FileOutcome {
+        if !options.binary_as_text() && is_binary(&content) {
+            outcome.binary = true;
+            if let Some(pattern) = &binary_pattern {
+                if let Some(found) = crate::pattern::find_matches(pattern, &content, "").first() {
+                    outcome.skipped.push(SkippedMatch {
+                        file: normalize_path(path),
+                        line: None,
+                        content: found.text.clone(),
+                        reason: SkipReason::Binary,
+                    });
+                }
             }
+            return outcome;
+        }
 
-            let mut file_matches = if replace.is_empty() {
-                let variants: Vec<String> = variant_map.keys().cloned().collect();
-                if let Ok(pattern) = build_pattern(&variants) {
-                    crate::pattern::find_matches(&pattern, &content, path.to_str().unwrap_or(""))
-                } else {
-                    Vec::new()
+        let mut token_line_hits: BTreeSet<usize> = BTreeSet::new();
+        let mut line_offsets = Vec::new();
+        let mut pos = 0;
+        for line in content.lines_with_terminator() {
+            line_offsets.push(pos);
+            pos += line.len();
+        }
+        if line_offsets.is_empty() {
+            line_offsets.push(0);
+        }
+
+        let line_of = |offset: usize| match line_offsets.binary_search(&offset) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx.max(1),
+        };
+
+        let tokens_satisfied = if let Some(token_matcher) = token_matcher.as_ref() {
+            if token_variant_groups.is_empty() {
+                false
+            } else {
+                let mut present = vec![false; token_variant_groups.len()];
+                for mat in token_matcher.find_iter(&content) {
+                    let token_idx = token_pattern_to_group[mat.pattern()];
+                    present[token_idx] = true;
+                    token_line_hits.insert(line_of(mat.start()));
                 }
+                present.iter().all(|&p| p)
+            }
+        } else {
+            false
+        };
+
+        // Prefilter: lines holding any variant or token literal. Compound
+        // matching only has to look for identifiers on these lines.
+        let candidate_lines: BTreeSet<usize> = variant_matcher
+            .as_ref()
+            .map(|matcher| {
+                matcher
+                    .find_iter(&content)
+                    .map(|mat| line_of(mat.start()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let variant_found = !candidate_lines.is_empty();
+
+        let sql_regions = sql_pattern
+            .as_ref()
+            .filter(|_| crate::sql::is_sql_file(path))
+            .map(|_| SqlRegions::scan(&content));
+
+        // Near misses can be anywhere, so fuzzy scans look at every file
+        let candidate = variant_found || tokens_satisfied || sql_regions.is_some();
+        if !candidate && fuzzy_pattern.is_none() {
+            return outcome;
+        }
+
+        if crate::debug::flags().compound {
+            eprintln!("SCANNER: Using styles: {:?}", styles_slice);
+            eprintln!("SCANNER: options.styles = {:?}", options.styles);
+        }
+
+        let mut file_matches = if !candidate {
+            Vec::new()
+        } else if replace.is_empty() {
+            let variants: Vec<String> = variant_map.keys().cloned().collect();
+            if let Ok(pattern) = build_pattern(&variants) {
+                crate::pattern::find_matches(&pattern, &content, path.to_str().unwrap_or(""))
             } else {
-                crate::compound_scanner::find_enhanced_matches(
-                    &content,
-                    path.to_str().unwrap_or(""),
-                    search,
-                    replace,
-                    &variant_map,
-                    styles_slice,
-                    &identifier_extractor,
-                    if token_line_hits.is_empty() {
-                        None
-                    } else {
-                        Some(&token_line_hits)
-                    },
-                )
-            };
+                Vec::new()
+            }
+        } else {
+            crate::compound_scanner::find_enhanced_matches(
+                &content,
+                path.to_str().unwrap_or(""),
+                search,
+                replace,
+                &variant_map,
+                styles_slice,
+                &identifier_extractor,
+                if token_line_hits.is_empty() {
+                    None
+                } else {
+                    Some(&token_line_hits)
+                },
+                Some(&candidate_lines),
+            )
+        };
 
-            if options.ignore_ambiguous {
-                file_matches.retain(|m| !crate::ambiguity::is_ambiguous(&m.variant, styles_slice));
+        if options.ignore_ambiguous {
+            let (ambiguous, kept): (Vec<Match>, Vec<Match>) = file_matches
+                .into_iter()
+                .partition(|m| crate::ambiguity::is_ambiguous(&m.variant, styles_slice));
+            file_matches = kept;
+            if options.report_skipped {
+                outcome.skipped.extend(
+                    ambiguous
+                        .iter()
+                        .map(|m| skipped_match(path, &content, m, SkipReason::Ambiguous)),
+                );
             }
+        }
 
-            if file_matches.is_empty() {
-                return outcome;
+        if let (Some(pattern), Some(regions)) = (&sql_pattern, &sql_regions) {
+            let keywords = pattern.adjust_matches(
+                &mut file_matches,
+                &content,
+                path.to_str().unwrap_or(""),
+                regions,
+            );
+            if options.report_skipped {
+                outcome.skipped.extend(
+                    keywords
+                        .iter()
+                        .map(|m| skipped_match(path, &content, m, SkipReason::SqlKeyword)),
+                );
             }
+        }
 
-            file_matches.sort_by_key(|m| (m.line, m.column));
+        if options.match_anchor != MatchAnchor::Any && !file_matches.is_empty() {
+            let lines: Vec<&[u8]> = content.lines_with_terminator().collect();
+            let (kept, unanchored): (Vec<Match>, Vec<Match>) =
+                file_matches.into_iter().partition(|m| {
+                    let Some(line) = lines.get(m.line.saturating_sub(1)) else {
+                        return true;
+                    };
+                    let line = String::from_utf8_lossy(line);
+                    let end = m.column + (m.end - m.start);
+                    options.match_anchor.keeps(&line, m.column, end, search)
+                });
+            file_matches = kept;
+            if options.report_skipped {
+                outcome.skipped.extend(
+                    unanchored
+                        .iter()
+                        .map(|m| skipped_match(path, &content, m, SkipReason::Anchor)),
+                );
+            }
+        }
 
-            let hunks = generate_hunks(
-                &file_matches,
+        if let Some(fuzzy) = &fuzzy_pattern {
+            let near_misses =
+                fuzzy.find_matches(&content, path.to_str().unwrap_or(""), &file_matches);
+            outcome.near_misses = generate_hunks(
+                &near_misses,
                 &content,
                 &variant_map,
                 path,
                 options,
                 replace,
+                &mut Vec::new(),
             );
+        }
+
+        if file_matches.is_empty() {
+            return outcome;
+        }
+
+        file_matches.sort_by_key(|m| (m.line, m.column));
+
+        let hunks = generate_hunks(
+            &file_matches,
+            &content,
+            &variant_map,
+            path,
+            options,
+            replace,
+            &mut outcome.skipped,
+        );
+
+        let mut matches_by_variant = HashMap::new();
+        for hunk in &hunks {
+            *matches_by_variant.entry(hunk.variant.clone()).or_insert(0) += 1;
+        }
+
+        outcome.matches_by_variant = matches_by_variant;
+        outcome.hunks = hunks;
+        if let (Some(cache), Some(metadata)) = (caches.contents, metadata) {
+            cache.insert(path, content, &metadata, read_at);
+        }
+        outcome
+    };
+
+    let scan_file = |path: &PathBuf| -> FileOutcome {
+        let mut outcome = FileOutcome::default();
+
+        // Unchanged files the last scan found nothing in aren't read at all
+        if let Some(cache) = &file_cache {
+            let metadata = fs::metadata(path).ok();
+            if let Some(cached) = metadata.and_then(|metadata| cache.unchanged(path, &metadata)) {
+                outcome.scanned = true;
+                outcome.binary = cached.binary();
+                return outcome;
+            }
+        }
+
+        let read_start = Instant::now();
+        let read_at = SystemTime::now();
+        let (content, metadata) = match buffer.map_or_else(
+            || read_file_content(path).map(|(content, metadata)| (content, Some(metadata))),
+            |(_, content)| Ok((content.to_vec(), None)),
+        ) {
+            Ok(read) => {
+                outcome.scanned = true;
+                read
+            },
+            Err(e) => {
+                outcome.unreadable = Some(UnreadableFile {
+                    path: path.clone(),
+                    error: e.to_string(),
+                });
+                return outcome;
+            },
+        };
+        outcome.read_time = read_start.elapsed();
+        outcome.bytes = content.len() as u64;
+
+        let Some((cache, metadata_ref)) = file_cache.as_ref().zip(metadata.as_ref()) else {
+            return search_file(path, content, metadata, read_at, outcome);
+        };
+        // Touched but not changed, as after switching branches and back
+        if let Some(cached) = cache.same_content(path, &content) {
+            outcome.binary = cached.binary();
+            let scan = CachedScan::new(metadata_ref, &content).found(0, outcome.binary);
+            cache.record(path, scan, read_at);
+            return outcome;
+        }
+        let scan = CachedScan::new(metadata_ref, &content);
+        let outcome = search_file(path, content, metadata, read_at, outcome);
+        let found = outcome.hunks.len() + outcome.skipped.len() + outcome.near_misses.len();
+        cache.record(path, scan.found(found, outcome.binary), read_at);
+        outcome
+    };
 
-            let mut matches_by_variant = HashMap::new();
-            for hunk in &hunks {
-                *matches_by_variant.entry(hunk.variant.clone()).or_insert(0) += 1;
+    let files_done = AtomicUsize::new(0);
+    let outcomes: Vec<FileOutcome> = file_entries
+        .par_iter()
+        .map(|path| {
+            // Files still queued after a cancel are skipped rather than scanned
+            if observer.is_some_and(OperationObserver::should_cancel) {
+                return FileOutcome::default();
+            }
+            let start = Instant::now();
+            // Worker threads don't share the casing set for this scan
+            let mut outcome = options.with_casing(|| scan_file(path));
+            outcome.elapsed = start.elapsed();
+            if let Some(observer) = observer {
+                let scanned = files_done.fetch_add(1, Ordering::Relaxed) + 1;
+                observer.on_file_scanned(path, scanned, file_entries.len());
             }
-
-            outcome.matches_by_variant = matches_by_variant;
-            outcome.hunks = hunks;
             outcome
         })
         .collect();
+    check_cancelled(observer, "Scan")?;
+    if let Some(cache) = file_cache {
+        // The cache only saves work, so a plan doesn't fail over it
+        let _ = cache.save();
+    }
 
     stats.files_scanned = outcomes.iter().filter(|o| o.scanned).count();
     stats.files_with_matches = outcomes.iter().filter(|o| !o.hunks.is_empty()).count();
 
+    let mut read_time = Duration::ZERO;
+    let mut match_time = Duration::ZERO;
     for outcome in outcomes {
+        if outcome.scanned {
+            stats.bytes_scanned += outcome.bytes;
+            stats.files_skipped.binary += usize::from(outcome.binary);
+        } else {
+            stats.files_skipped.unreadable += 1;
+        }
+        stats
+            .files_skipped
+            .unreadable_files
+            .extend(outcome.unreadable);
+        read_time += outcome.read_time;
+        match_time += outcome.elapsed.saturating_sub(outcome.read_time);
         stats.total_matches += outcome.hunks.len();
         for (variant, count) in outcome.matches_by_variant {
             *stats.matches_by_variant.entry(variant).or_insert(0) += count;
         }
         matches.extend(outcome.hunks);
+        skipped.extend(outcome.skipped);
+        near_misses.extend(outcome.near_misses);
+    }
+    stats.timings.read_ms = duration_ms(read_time);
+    stats.timings.match_ms = duration_ms(match_time);
+
+    for hunks in [&mut matches, &mut near_misses] {
+        hunks.sort_by(|a, b| {
+            a.file
+                .cmp(&b.file)
+                .then_with(|| a.line.cmp(&b.line))
+                .then_with(|| a.byte_offset.cmp(&b.byte_offset))
+        });
     }
+    skipped.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
+    stats
+        .files_skipped
+        .unreadable_files
+        .sort_by(|a, b| a.path.cmp(&b.path));
 
-    matches.sort_by(|a, b| {
-        a.file
-            .cmp(&b.file)
-            .then_with(|| a.line.cmp(&b.line))
-            .then_with(|| a.byte_offset.cmp(&b.byte_offset))
-    });
-
-    let paths = if options.rename_files || options.rename_dirs {
+    let rename_start = Instant::now();
+    let paths = if plan_paths {
         let mut all_renames = Vec::new();
         let btree_map = variant_map.to_btree_map();
-        for root in roots {
-            let mut root_renames = crate::rename::plan_renames_with_search(
-                root, &btree_map, options, search, replace,
+        for (root, walked) in roots.iter().zip(&root_paths) {
+            let mut root_renames = crate::rename::plan_walked_renames_with_search(
+                root, walked, &btree_map, options, search, replace,
             )?;
             // For search mode (when new is empty), clear the new_path to empty PathBuf
             if replace.is_empty() {
@@ -618,6 +1560,7 @@ pub fn scan_repository_multi(
     } else {
         vec![]
     };
+    stats.timings.rename_plan_ms = duration_ms(rename_start.elapsed());
 
     let id = generate_plan_id(search, replace, options);
     let created_at = SystemTime::now()
@@ -646,19 +1589,58 @@ pub fn scan_repository_multi(
         paths,
         stats,
         version: "1.0.0".to_string(),
+        coercion: options.coerce_separators,
+        skipped,
+        near_misses,
+        suspicious_renames: Vec::new(),
+        warnings: Vec::new(),
         created_directories: None,
+        options: Some(options.clone()),
     })
 }
 
-pub fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>> {
+/// Glob patterns from `--include` or `--exclude`, with gitignore semantics
+///
+/// The last pattern that matches a path decides, and a pattern starting with
+/// `!` re-includes what an earlier one matched. `\!` matches a literal `!`.
+#[derive(Debug, Clone)]
+pub struct GlobList {
+    set: GlobSet,
+    /// The pattern each glob in `set` came from
+    pattern_of: Vec<usize>,
+    negated: Vec<bool>,
+}
+
+impl GlobList {
+    /// Whether the last pattern matching `path` is not negated
+    pub fn is_match(&self, path: impl AsRef<Path>) -> bool {
+        self.set
+            .matches(path)
+            .into_iter()
+            .map(|glob| self.pattern_of[glob])
+            .max()
+            .is_some_and(|pattern| !self.negated[pattern])
+    }
+}
+
+pub fn build_globset(patterns: &[String]) -> Result<Option<GlobList>> {
     if patterns.is_empty() {
         return Ok(None);
     }
 
     let mut builder = GlobSetBuilder::new();
-    for pattern in patterns {
+    let mut pattern_of = Vec::new();
+    let mut negated = Vec::with_capacity(patterns.len());
+    for (index, pattern) in patterns.iter().enumerate() {
+        let (pattern, is_negated) = match pattern.strip_prefix('!') {
+            Some(rest) => (rest, true),
+            None => (pattern.as_str(), false),
+        };
+        negated.push(is_negated);
+
         // Add the pattern as-is
         builder.add(Glob::new(pattern)?);
+        pattern_of.push(index);
 
         // If pattern looks like a directory (ends with / or no wildcards and no extension),
         // also add a pattern that matches everything under it
@@ -671,12 +1653,28 @@ pub fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>> {
                 format!("{}/**", pattern)
             };
             builder.add(Glob::new(&recursive_pattern)?);
+            pattern_of.push(index);
         }
     }
-    Ok(Some(builder.build()?))
+    Ok(Some(GlobList {
+        set: builder.build()?,
+        pattern_of,
+        negated,
+    }))
+}
+
+/// [`build_globset`] for `--include`, where a list of only `!` patterns
+/// includes everything they don't match, as in ripgrep
+pub fn build_include_globset(patterns: &[String]) -> Result<Option<GlobList>> {
+    if !patterns.is_empty() && patterns.iter().all(|pattern| pattern.starts_with('!')) {
+        let everything = std::iter::once("**".to_string()).chain(patterns.iter().cloned());
+        return build_globset(&everything.collect::<Vec<_>>());
+    }
+    build_globset(patterns)
 }
 
-fn read_file_content(path: &Path) -> Result<Vec<u8>> {
+/// Read a file, with its metadata from before the read
+fn read_file_content(path: &Path) -> Result<(Vec<u8>, std::fs::Metadata)> {
     use std::io::Read;
 
     let file = File::open(path)?;
@@ -685,10 +1683,10 @@ fn read_file_content(path: &Path) -> Result<Vec<u8>> {
     if metadata.len() > 50 * 1024 * 1024 {
         let mut content = Vec::new();
         std::fs::File::open(path)?.read_to_end(&mut content)?;
-        Ok(content)
+        Ok((content, metadata))
     } else {
         let mmap = unsafe { Mmap::map(&file)? };
-        Ok(mmap.to_vec())
+        Ok((mmap.to_vec(), metadata))
     }
 }
 
@@ -696,6 +1694,7 @@ fn is_binary(content: &[u8]) -> bool {
     matches!(content_inspector::inspect(content), ContentType::BINARY)
 }
 
+#[allow(clippy::too_many_lines)]
 fn generate_hunks(
     matches: &[Match],
     content: &[u8],
@@ -703,9 +1702,15 @@ fn generate_hunks(
     path: &Path,
     options: &PlanOptions,
     original_replacement: &str,
+    skipped: &mut Vec<SkippedMatch>,
 ) -> Vec<MatchHunk> {
     let lines: Vec<&[u8]> = content.lines_with_terminator().collect();
     let mut hunks = Vec::new();
+    let mut skip = |m: &Match, reason: SkipReason| {
+        if options.report_skipped {
+            skipped.push(skipped_match(path, content, m, reason));
+        }
+    };
 
     // Create ambiguity resolver for handling ambiguous identifiers
     let ambiguity_resolver = AmbiguityResolver::new();
@@ -720,30 +1725,65 @@ fn generate_hunks(
     } else {
         None
     };
+    let suppressed_lines = SuppressedLines::new(content, path, options);
+
+    // Only lex the file when matches are filtered by string literals
+    let literal_regions = if options.literal_mode == LiteralMode::All {
+        None
+    } else {
+        LiteralRegions::scan_file(path, content)
+    };
+
+    let structured_regions = options
+        .structured
+        .as_ref()
+        .and_then(|_| StructuredRegions::scan(path, content));
 
     for m in matches {
         if options.ignore_ambiguous
             && crate::ambiguity::is_ambiguous(&m.variant, &Style::all_styles())
         {
+            skip(m, SkipReason::Ambiguous);
             continue;
         }
 
         // Check if this match should be excluded
         if options.exclude_match.contains(&m.variant) || options.exclude_match.contains(&m.text) {
+            skip(m, SkipReason::ExcludeMatch);
             continue;
         }
 
+        if let Some(regions) = &literal_regions {
+            if !options.literal_mode.keeps(regions.kind_at(m.start)) {
+                skip(m, SkipReason::Literal);
+                continue;
+            }
+        }
+
+        if let (Some(filter), Some(regions)) = (&options.structured, &structured_regions) {
+            if !regions.keeps(filter, m.start) {
+                skip(m, SkipReason::Structured);
+                continue;
+            }
+        }
+
         let line_idx = m.line.saturating_sub(1);
         if line_idx >= lines.len() {
             continue;
         }
 
+        if let Some(reason) = suppressed_lines.reason(m.line) {
+            skip(m, reason);
+            continue;
+        }
+
         let line = lines[line_idx];
         let line_string = String::from_utf8_lossy(line).to_string();
 
         // Check if this line should be excluded based on regex pattern
         if let Some(ref regex) = exclude_line_regex {
             if regex.is_match(&line_string) {
+                skip(m, SkipReason::ExcludeMatchingLines);
                 continue;
             }
         }
@@ -751,6 +1791,7 @@ fn generate_hunks(
         // Check if this is a compound match (text field contains replacement)
         // or an exact match (use variant map)
         let is_compound_match = !variant_map.contains_key(&m.variant);
+        let mut style = None;
         let (content, mut replace) = if is_compound_match {
             // Compound match - text field has the replacement
             (m.variant.clone(), m.text.clone())
@@ -777,19 +1818,38 @@ fn generate_hunks(
 
             // Generate the replacement in the resolved style
             // We need to tokenize the original replacement to apply the new style
-            let replacement_tokens = parse_to_tokens(original_replacement);
-            let styled_replacement = to_style(&replacement_tokens, resolved.style);
+            let replace_is_atomic = options
+                .atomic_config
+                .as_ref()
+                .is_some_and(|c| c.should_treat_replace_atomic(original_replacement));
+            let styled_replacement = if replace_is_atomic {
+                crate::atomic::to_atomic_style(original_replacement, resolved.style)
+            } else {
+                let replacement_tokens = parse_to_tokens_with_acronyms(
+                    original_replacement,
+                    &build_acronym_set(options),
+                );
+                to_style(&replacement_tokens, resolved.style)
+            };
 
+            style = Some(resolved.style);
             (m.variant.clone(), styled_replacement)
         } else {
             // Exact match - use variant map
-            let new_variant = variant_map.get(&m.variant).unwrap();
+            let (variant_style, new_variant) = variant_map.get_with_style(&m.variant).unwrap();
+            style = variant_style;
             (m.variant.clone(), new_variant.clone())
         };
+        let style = style.or_else(|| crate::case_model::detect_style(&content));
         let mut coercion_applied = None;
 
         // Apply coercion if enabled
-        if options.coerce_separators == CoercionMode::Auto {
+        if let CoercionMode::Force(style) = options.coerce_separators {
+            if let Some(forced) = force_style(&replace, style) {
+                replace = forced;
+                coercion_applied = Some(forced_reason(style));
+            }
+        } else if options.coerce_separators == CoercionMode::Auto {
             // Find the match position within the line and extract context
             if let Some(match_pos) = line_string.find(&content) {
                 let identifier_context =
@@ -822,10 +1882,12 @@ fn generate_hunks(
             }
         }
 
-        if content
-            .chars()
-            .next()
-            .is_some_and(|c| c.is_ascii_uppercase())
+        // A forced style decides the case of the first letter too
+        if !matches!(options.coerce_separators, CoercionMode::Force(_))
+            && content
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_uppercase())
             && replace
                 .chars()
                 .next()
@@ -869,6 +1931,8 @@ fn generate_hunks(
 
         // Calculate character offset from byte offset
         let char_offset = byte_offset_to_char_offset(&line_before, m.column);
+        let (boundary_before, boundary_after) =
+            BoundaryClass::around(&line_before, m.column, m.column + content.len());
 
         hunks.push(MatchHunk {
             file: normalize_path(path),
@@ -886,12 +1950,29 @@ fn generate_hunks(
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style,
+            match_kind: Some(if is_compound_match {
+                MatchKind::Compound
+            } else {
+                MatchKind::Exact
+            }),
+            boundary_before: Some(boundary_before),
+            boundary_after: Some(boundary_after),
         });
     }
 
     hunks
 }
 
+fn skipped_match(path: &Path, content: &[u8], m: &Match, reason: SkipReason) -> SkippedMatch {
+    SkippedMatch {
+        file: normalize_path(path),
+        line: Some(m.line as u64),
+        content: String::from_utf8_lossy(&content[m.start..m.end]).to_string(),
+        reason,
+    }
+}
+
 /// Extract the immediate context around a match to make better coercion decisions
 fn extract_immediate_context(line: &str, match_start: usize, match_end: usize) -> String {
     let chars: Vec<char> = line.chars().collect();
@@ -993,6 +2074,75 @@ fn detect_compound_coercion(
     }
 }
 
+/// Render a replacement in a forced style, or None if it already has that style
+pub(crate) fn force_style(replacement: &str, style: crate::coercion::Style) -> Option<String> {
+    let forced = crate::coercion::render_tokens(&crate::coercion::tokenize(replacement), style);
+    (!forced.is_empty() && forced != replacement).then_some(forced)
+}
+
+pub(crate) fn forced_reason(style: crate::coercion::Style) -> String {
+    format!("Forced {} style", style_name(style))
+}
+
+/// Apply the `--coerce` mode to a renamed file or directory name. Forcing a
+/// style only restyles the replaced part, leaving the extension alone.
+/// Returns the reason if the name changed.
+pub(crate) fn coerce_name(
+    mode: CoercionMode,
+    file_name: &str,
+    new_name: &mut String,
+    old: &str,
+    new: &str,
+) -> Option<String> {
+    match mode {
+        CoercionMode::Auto => {
+            let (coerced, reason) = crate::coercion::apply_coercion(file_name, old, new)?;
+            *new_name = coerced;
+            Some(reason)
+        },
+        CoercionMode::Force(style) => {
+            let forced = force_style(new, style)?;
+            if !new_name.contains(new) {
+                return None;
+            }
+            *new_name = new_name.replace(new, &forced);
+            Some(forced_reason(style))
+        },
+        CoercionMode::Off => None,
+    }
+}
+
+/// Coerce a `replace` replacement to the identifier around the match, or
+/// force its style, returning the replacement and the reason if it changed
+fn coerce_simple_replacement(
+    mode: CoercionMode,
+    line: &str,
+    start: usize,
+    end: usize,
+    matched: &str,
+    replacement: &str,
+) -> (String, Option<String>) {
+    let coerced = match mode {
+        CoercionMode::Off => None,
+        CoercionMode::Force(style) => {
+            force_style(replacement, style).map(|forced| (forced, forced_reason(style)))
+        },
+        CoercionMode::Auto => {
+            let context = extract_immediate_context(line, start, end);
+            crate::coercion::apply_coercion(&context, matched, replacement).and_then(
+                |(_, reason)| {
+                    apply_coercion_to_variant(&context, matched, replacement)
+                        .map(|coerced| (coerced, reason))
+                },
+            )
+        },
+    };
+    match coerced {
+        Some((text, reason)) => (text, Some(reason)),
+        None => (replacement.to_string(), None),
+    }
+}
+
 /// Convert a coercion style to a human-readable name
 fn style_name(style: crate::coercion::Style) -> &'static str {
     match style {
@@ -1093,21 +2243,27 @@ impl VariantMap {
     /// Get the best replacement for a search pattern
     /// Prefers `snake_case` replacement when ambiguous (most common style)
     pub fn get(&self, key: &str) -> Option<&String> {
+        self.get_with_style(key).map(|(_, replacement)| replacement)
+    }
+
+    /// Get the best replacement for a search pattern, with the style of the
+    /// variant it was generated for
+    pub fn get_with_style(&self, key: &str) -> Option<(Option<Style>, &String)> {
         self.map.get(key).and_then(|replacements| {
             // If there's only one replacement, use it
             if replacements.len() == 1 {
-                return Some(&replacements[0].1);
+                return Some((replacements[0].0, &replacements[0].1));
             }
 
             // Otherwise, prefer snake_case if available
             for (style, replacement) in replacements {
                 if matches!(style, Some(Style::Snake)) {
-                    return Some(replacement);
+                    return Some((*style, replacement));
                 }
             }
 
             // Fall back to the first one
-            replacements.first().map(|(_, r)| r)
+            replacements.first().map(|(style, r)| (*style, r))
         })
     }
 
@@ -1144,30 +2300,22 @@ fn generate_variant_map_with_acronyms(
     atomic_config: Option<&crate::atomic::AtomicConfig>,
     enable_plural_variants: bool,
 ) -> VariantMap {
-    // If we have atomic config, use atomic-aware variant generation
-    if let Some(atomic_cfg) = atomic_config {
-        // Convert to BTreeMap first
-        let btree_map = crate::case_model::generate_variant_map_with_atomic_and_plurals(
-            search,
-            replace,
-            styles,
-            Some(atomic_cfg),
-            enable_plural_variants,
-        );
-
-        // Convert BTreeMap to VariantMap
-        let mut variant_map = VariantMap::new();
-        for (key, value) in btree_map {
-            variant_map.insert(key, None, value);
-        }
-        return variant_map;
-    }
-
-    // Otherwise use the acronym-aware tokenization (existing logic)
-    let old_tokens = crate::case_model::parse_to_tokens_with_acronyms(search, acronym_set);
-    let new_tokens = crate::case_model::parse_to_tokens_with_acronyms(replace, acronym_set);
+    // Atomic terms are kept as a single word; everything else is split with
+    // the acronym-aware tokenizer
+    let search_is_atomic = atomic_config.is_some_and(|c| c.should_treat_search_atomic(search));
+    let replace_is_atomic = atomic_config.is_some_and(|c| c.should_treat_replace_atomic(replace));
+    let old_tokens = if search_is_atomic {
+        crate::atomic::parse_atomic(search)
+    } else {
+        crate::case_model::parse_to_tokens_with_acronyms(search, acronym_set)
+    };
+    let new_tokens = if replace_is_atomic {
+        crate::atomic::parse_atomic(replace)
+    } else {
+        crate::case_model::parse_to_tokens_with_acronyms(replace, acronym_set)
+    };
 
-    if std::env::var("RENAMIFY_DEBUG_VARIANTS").is_ok() {
+    if crate::debug::flags().variants {
         eprintln!(
             "DEBUG VARIANTS (with acronyms): Generating variants for '{}' -> '{}'",
             search, replace
@@ -1200,7 +2348,7 @@ fn generate_variant_map_with_acronyms(
     let mut variant_models: Vec<(TokenModel, TokenModel)> =
         vec![(old_tokens.clone(), new_tokens.clone())];
 
-    if enable_plural_variants {
+    if enable_plural_variants && !search_is_atomic && !replace_is_atomic {
         if let Some(singular_old) = crate::case_model::singularize_token_model(&old_tokens) {
             let singular_new = crate::case_model::singularize_token_model(&new_tokens)
                 .unwrap_or_else(|| new_tokens.clone());
@@ -1217,10 +2365,18 @@ fn generate_variant_map_with_acronyms(
     // Generate variants for each requested style
     for style in styles {
         for (search_model, replace_model) in &variant_models {
-            let search_variant = crate::case_model::to_style(search_model, *style);
-            let replace_variant = crate::case_model::to_style(replace_model, *style);
+            let search_variant = if search_is_atomic {
+                crate::atomic::to_atomic_style(search, *style)
+            } else {
+                crate::case_model::to_style(search_model, *style)
+            };
+            let replace_variant = if replace_is_atomic {
+                crate::atomic::to_atomic_style(replace, *style)
+            } else {
+                crate::case_model::to_style(replace_model, *style)
+            };
 
-            if std::env::var("RENAMIFY_DEBUG_VARIANTS").is_ok() {
+            if crate::debug::flags().variants {
                 eprintln!(
                     "DEBUG VARIANTS (with acronyms): Style {:?} -> '{}' => '{}'",
                     style, search_variant, replace_variant
@@ -1246,20 +2402,14 @@ fn process_file_content(
     is_regex: bool,
     search_regex: Option<&regex::Regex>,
     exclude_lines_regex: Option<&regex::Regex>,
-    options: &PlanOptions,
-) -> Result<(Vec<MatchHunk>, bool)> {
+    suppressed_lines: &SuppressedLines,
+    coerce: CoercionMode,
+    content_bytes: &[u8],
+) -> (Vec<MatchHunk>, bool) {
     let mut file_matches = Vec::new();
 
-    // Read file content as bytes first to check if binary
-    let content_bytes = std::fs::read(path)?;
-
-    // Check if binary
-    if !options.binary_as_text() && is_binary(&content_bytes) {
-        return Ok((file_matches, false));
-    }
-
     // Convert to string
-    let content = String::from_utf8_lossy(&content_bytes);
+    let content = String::from_utf8_lossy(content_bytes);
     let lines: Vec<&str> = content.lines().collect();
     let relative_path = path.strip_prefix(root).unwrap_or(path);
     let mut has_matches = false;
@@ -1267,6 +2417,9 @@ fn process_file_content(
     // Find matches
     for (line_num, line) in lines.iter().enumerate() {
         // Skip excluded lines
+        if suppressed_lines.reason(line_num + 1).is_some() {
+            continue;
+        }
         if let Some(regex) = exclude_lines_regex {
             if regex.is_match(line) {
                 continue;
@@ -1294,11 +2447,21 @@ fn process_file_content(
                     }
                 }
 
+                let (replacement_text, coercion_applied) = coerce_simple_replacement(
+                    coerce,
+                    line,
+                    start,
+                    end,
+                    matched_text,
+                    &replacement_text,
+                );
+
                 has_matches = true;
                 let line_after = format!("{}{}{}", &line[..start], &replacement_text, &line[end..]);
 
                 // Calculate character offset from byte offset
                 let char_offset = byte_offset_to_char_offset(line, start);
+                let (boundary_before, boundary_after) = BoundaryClass::around(line, start, end);
 
                 file_matches.push(MatchHunk {
                     file: relative_path.to_path_buf(),
@@ -1314,10 +2477,14 @@ fn process_file_content(
                     end,
                     line_before: Some((*line).to_string()),
                     line_after: Some(line_after),
-                    coercion_applied: None,
+                    coercion_applied,
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: Some(boundary_before),
+                    boundary_after: Some(boundary_after),
                 });
             }
         } else {
@@ -1327,11 +2494,15 @@ fn process_file_content(
                 let start = search_start + pos;
                 let end = start + pattern.len();
 
+                let (replacement_text, coercion_applied) =
+                    coerce_simple_replacement(coerce, line, start, end, pattern, replacement);
+
                 has_matches = true;
-                let line_after = format!("{}{}{}", &line[..start], replacement, &line[end..]);
+                let line_after = format!("{}{}{}", &line[..start], &replacement_text, &line[end..]);
 
                 // Calculate character offset from byte offset
                 let char_offset = byte_offset_to_char_offset(line, start);
+                let (boundary_before, boundary_after) = BoundaryClass::around(line, start, end);
 
                 file_matches.push(MatchHunk {
                     file: relative_path.to_path_buf(),
@@ -1342,15 +2513,19 @@ fn process_file_content(
                     char_offset: char_offset as u32,
                     variant: pattern.to_string(),
                     content: pattern.to_string(),
-                    replace: replacement.to_string(),
+                    replace: replacement_text,
                     start,
                     end,
                     line_before: Some((*line).to_string()),
                     line_after: Some(line_after),
-                    coercion_applied: None,
+                    coercion_applied,
                     original_file: None,
                     renamed_file: None,
                     patch_hash: None,
+                    style: None,
+                    match_kind: None,
+                    boundary_before: Some(boundary_before),
+                    boundary_after: Some(boundary_after),
                 });
 
                 search_start = end;
@@ -1358,36 +2533,29 @@ fn process_file_content(
         }
     }
 
-    Ok((file_matches, has_matches))
+    (file_matches, has_matches)
 }
 
-/// Process path renames for simple pattern matching
+/// Process path renames for simple pattern matching, over the paths found by
+/// the content walk
 fn process_path_renames(
-    paths: &[PathBuf],
+    walked: &[WalkedPath],
     root: &Path,
     pattern: &str,
     replacement: &str,
     is_regex: bool,
     search_regex: Option<&regex::Regex>,
     options: &PlanOptions,
-    include_globs: Option<&GlobSet>,
-    exclude_globs: Option<&GlobSet>,
+    include_globs: Option<&GlobList>,
+    exclude_globs: Option<&GlobList>,
 ) -> Result<Vec<Rename>> {
-    use crate::configure_walker;
-
     let mut renames = Vec::new();
+    let mut existing_paths = Vec::new();
 
-    if !options.rename_files && !options.rename_dirs {
-        return Ok(renames);
-    }
-
-    // Walk again for renames
-    let builder = configure_walker(paths, options);
-
-    for entry in builder.build() {
-        let entry = entry?;
-        let path = entry.path();
+    for entry in walked {
+        let path = entry.path.as_path();
         let relative_path = path.strip_prefix(root).unwrap_or(path);
+        existing_paths.push(relative_path.to_path_buf());
 
         // Skip if doesn't match includes or matches excludes
         if let Some(globs) = include_globs {
@@ -1418,6 +2586,21 @@ fn process_path_renames(
                 None
             };
 
+            // Regex renames are taken as written; literal ones follow --coerce
+            let mut coercion_applied = None;
+            let new_name = new_name.map(|mut new_name| {
+                if !is_regex {
+                    coercion_applied = coerce_name(
+                        options.coerce_separators,
+                        &file_name_str,
+                        &mut new_name,
+                        pattern,
+                        replacement,
+                    );
+                }
+                new_name
+            });
+
             if let Some(new_name) = new_name {
                 if new_name != file_name_str {
                     let new_path = path.with_file_name(new_name);
@@ -1439,13 +2622,17 @@ fn process_path_renames(
                         path: relative_path.to_path_buf(),
                         new_path: new_relative.to_path_buf(),
                         kind,
-                        coercion_applied: None,
+                        coercion_applied,
                     });
                 }
             }
         }
     }
 
+    if !options.allow_case_collisions {
+        crate::rename::check_case_collisions(&renames, &existing_paths)?;
+    }
+
     // Sort renames by depth for proper ordering
     renames.sort_by_key(|r| std::cmp::Reverse(r.path.components().count()));
 
@@ -1472,7 +2659,7 @@ pub fn create_simple_plan(
     };
 
     // Build glob patterns for include/exclude
-    let include_globs = build_globset(&options.includes)?;
+    let include_globs = build_include_globset(&options.includes)?;
     let exclude_globs = build_globset(&options.excludes)?;
 
     // Build regex for line exclusion if provided
@@ -1492,35 +2679,70 @@ pub fn create_simple_plan(
     let mut all_matches = Vec::new();
     let mut files_scanned = 0;
     let mut files_with_matches = std::collections::HashSet::new();
-
-    // Walk the directory
+    let mut bytes_scanned = 0;
+    let mut files_skipped = SkippedFiles::default();
+    let mut read_time = Duration::ZERO;
+    let mut match_time = Duration::ZERO;
+
+    // Walk the directory, keeping every path for rename planning
+    let plan_paths = !options.contents_only && (options.rename_files || options.rename_dirs);
+    let mut walked = Vec::new();
+    let walk_start = Instant::now();
     let builder = configure_walker(&paths, options);
 
     for entry in builder.build() {
         let entry = entry?;
+        if plan_paths {
+            walked.push(WalkedPath::from(&entry));
+        }
         let path = entry.path();
         let relative_path = path.strip_prefix(&root).unwrap_or(path);
 
+        // Only process files
+        if !path.is_file() {
+            continue;
+        }
+
         // Skip if doesn't match includes or matches excludes
         if let Some(globs) = &include_globs {
             if !globs.is_match(relative_path) {
+                files_skipped.excluded += 1;
                 continue;
             }
         }
         if let Some(globs) = &exclude_globs {
             if globs.is_match(relative_path) {
+                files_skipped.excluded += 1;
                 continue;
             }
         }
 
-        // Only process files
-        if !path.is_file() {
-            continue;
+        if let Some(limit) = options.walk.max_filesize {
+            let bytes = entry.metadata().map_or(0, |metadata| metadata.len());
+            if bytes > limit {
+                files_skipped.too_large.push(LargeFile {
+                    path: path.to_path_buf(),
+                    bytes,
+                });
+                continue;
+            }
         }
 
         files_scanned += 1;
 
+        // Read file content as bytes first to check if binary
+        let read_start = Instant::now();
+        let content_bytes = std::fs::read(path)?;
+        read_time += read_start.elapsed();
+        bytes_scanned += content_bytes.len() as u64;
+
+        if !options.binary_as_text() && is_binary(&content_bytes) {
+            files_skipped.binary += 1;
+            continue;
+        }
+
         // Process the file content
+        let match_start = Instant::now();
         let (file_matches, has_matches) = process_file_content(
             path,
             &root,
@@ -1529,18 +2751,24 @@ pub fn create_simple_plan(
             is_regex,
             search_regex.as_ref(),
             exclude_lines_regex.as_ref(),
-            options,
-        )?;
+            &SuppressedLines::new(&content_bytes, path, options),
+            options.coerce_separators,
+            &content_bytes,
+        );
+        match_time += match_start.elapsed();
 
         if has_matches {
             files_with_matches.insert(relative_path.to_path_buf());
         }
         all_matches.extend(file_matches);
     }
+    // Reading and matching happen inside the walk loop
+    let walk_time = walk_start.elapsed().saturating_sub(read_time + match_time);
 
     // Handle file/directory renames if enabled
+    let rename_start = Instant::now();
     let renames = process_path_renames(
-        &paths,
+        &walked,
         &root,
         pattern,
         replacement,
@@ -1550,6 +2778,7 @@ pub fn create_simple_plan(
         include_globs.as_ref(),
         exclude_globs.as_ref(),
     )?;
+    let rename_time = rename_start.elapsed();
 
     // Create stats
     let mut matches_by_variant = HashMap::new();
@@ -1560,6 +2789,14 @@ pub fn create_simple_plan(
         total_matches: all_matches.len(),
         matches_by_variant,
         files_with_matches: files_with_matches.len(),
+        bytes_scanned,
+        files_skipped,
+        timings: ScanTimings {
+            walk_ms: duration_ms(walk_time),
+            read_ms: duration_ms(read_time),
+            match_ms: duration_ms(match_time),
+            rename_plan_ms: duration_ms(rename_time),
+        },
     };
 
     // Generate plan
@@ -1578,7 +2815,13 @@ pub fn create_simple_plan(
         paths: renames,
         stats,
         version: env!("CARGO_PKG_VERSION").to_string(),
+        coercion: options.coerce_separators,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
         created_directories: None,
+        options: Some(options.clone()),
     };
 
     Ok(plan)
@@ -1598,6 +2841,54 @@ mod tests {
         assert_eq!(opts.plan_out, PathBuf::from(".renamify/plan.json"));
     }
 
+    #[test]
+    fn test_coercion_mode_from_str_and_display() {
+        use crate::coercion::Style as CoercionStyle;
+
+        assert_eq!("auto".parse(), Ok(CoercionMode::Auto));
+        assert_eq!("OFF".parse(), Ok(CoercionMode::Off));
+        assert_eq!(
+            "force:screaming_snake".parse(),
+            Ok(CoercionMode::Force(CoercionStyle::ScreamingSnake))
+        );
+        assert!("force:mixed".parse::<CoercionMode>().is_err());
+        assert!("sometimes".parse::<CoercionMode>().is_err());
+
+        for mode in ["auto", "off", "force:kebab", "force:upper-sentence"] {
+            assert_eq!(mode.parse::<CoercionMode>().unwrap().to_string(), mode);
+        }
+    }
+
+    #[test]
+    fn test_renames_from_shared_walk_across_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        for dir in ["app/old_name_dir", "lib/old_name_dir"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("old_name.rs"), "old_name\n").unwrap();
+        }
+        let roots = [root.join("app"), root.join("missing"), root.join("lib")];
+
+        let plan =
+            scan_repository_multi(&roots, "old_name", "new_name", &PlanOptions::default()).unwrap();
+        let mut renamed: Vec<_> = plan
+            .paths
+            .iter()
+            .map(|rename| rename.new_path.strip_prefix(&root).unwrap().to_path_buf())
+            .collect();
+        renamed.sort();
+        assert_eq!(
+            renamed,
+            [
+                PathBuf::from("app/new_name_dir"),
+                PathBuf::from("app/old_name_dir/new_name.rs"),
+                PathBuf::from("lib/new_name_dir"),
+                PathBuf::from("lib/old_name_dir/new_name.rs"),
+            ]
+        );
+        assert_eq!(plan.stats.total_matches, 2);
+    }
+
     #[test]
     fn test_is_binary() {
         assert!(!is_binary(b"hello world"));
@@ -1666,6 +2957,38 @@ mod tests {
         assert!(globset.is_match("docs/src/assets/file.png"));
     }
 
+    #[test]
+    fn test_build_globset_negation() {
+        let patterns = |list: &[&str]| list.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        let globs = build_globset(&patterns(&["tests/**", "!tests/fixtures/**"]))
+            .unwrap()
+            .unwrap();
+        assert!(globs.is_match("tests/unit.rs"));
+        assert!(!globs.is_match("tests/fixtures/data.json"));
+        assert!(!globs.is_match("src/main.rs"));
+
+        // The last matching pattern wins
+        let globs = build_globset(&patterns(&["!src/gen/**", "src"]))
+            .unwrap()
+            .unwrap();
+        assert!(globs.is_match("src/gen/api.rs"));
+
+        let globs = build_globset(&patterns(&["\\!important.md"]))
+            .unwrap()
+            .unwrap();
+        assert!(globs.is_match("!important.md"));
+
+        // An include list of only exceptions starts from everything
+        let includes = build_include_globset(&patterns(&["!**/*.md"]))
+            .unwrap()
+            .unwrap();
+        assert!(includes.is_match("src/main.rs"));
+        assert!(!includes.is_match("docs/README.md"));
+        let excludes = build_globset(&patterns(&["!**/*.md"])).unwrap().unwrap();
+        assert!(!excludes.is_match("src/main.rs"));
+    }
+
     #[test]
     fn test_scan_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -1726,6 +3049,7 @@ mod tests {
             Path::new("test.txt"),
             &opts,
             "new_name",
+            &mut Vec::new(),
         );
 
         assert_eq!(hunks.len(), 2);
@@ -1811,6 +3135,7 @@ mod tests {
             Path::new("test.rs"),
             &opts,
             "new_name",
+            &mut Vec::new(),
         );
 
         assert_eq!(hunks.len(), 3);
@@ -1902,6 +3227,113 @@ mod tests {
         assert_eq!(plan.stats.files_with_matches, 1);
     }
 
+    #[test]
+    fn test_max_filesize_skips_contents_but_not_names() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("small.rs"), "old_name\n").unwrap();
+        std::fs::write(temp_dir.path().join("old_name.log"), "old_name\n".repeat(8)).unwrap();
+
+        let opts = PlanOptions {
+            walk: WalkOptions {
+                max_filesize: Some(32),
+                ..WalkOptions::default()
+            },
+            ..Default::default()
+        };
+        let plan = scan_repository(temp_dir.path(), "old_name", "new_name", &opts).unwrap();
+
+        assert_eq!(plan.stats.files_scanned, 1);
+        assert_eq!(plan.stats.total_matches, 1);
+        assert_eq!(
+            plan.stats.files_skipped.too_large,
+            [LargeFile {
+                path: temp_dir.path().join("old_name.log"),
+                bytes: 72,
+            }]
+        );
+        assert_eq!(plan.paths.len(), 1);
+
+        let unlimited = PlanOptions {
+            walk: WalkOptions {
+                max_filesize: None,
+                ..WalkOptions::default()
+            },
+            ..Default::default()
+        };
+        let plan = scan_repository(temp_dir.path(), "old_name", "new_name", &unlimited).unwrap();
+        assert_eq!(plan.stats.total_matches, 9);
+        assert!(plan.stats.files_skipped.too_large.is_empty());
+    }
+
+    #[test]
+    fn test_scan_stats_bytes_and_skipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("main.rs"), "old_name\n").unwrap();
+        std::fs::write(temp_dir.path().join("notes.md"), "old_name\n").unwrap();
+        std::fs::write(temp_dir.path().join("image.bin"), [0u8, 1, 2, 3, 0, 0]).unwrap();
+
+        let opts = PlanOptions {
+            excludes: vec!["*.md".to_string()],
+            ..Default::default()
+        };
+        let plan = scan_repository(temp_dir.path(), "old_name", "new_name", &opts).unwrap();
+
+        assert_eq!(plan.stats.files_scanned, 2);
+        assert_eq!(plan.stats.bytes_scanned, 15);
+        assert_eq!(
+            plan.stats.files_skipped,
+            SkippedFiles {
+                binary: 1,
+                excluded: 1,
+                unreadable: 0,
+                unreadable_files: vec![],
+                too_large: vec![],
+            }
+        );
+
+        let plan = create_simple_plan(
+            "old_name",
+            "new_name",
+            vec![temp_dir.path().to_path_buf()],
+            &opts,
+            false,
+        )
+        .unwrap();
+        assert_eq!(plan.stats.bytes_scanned, 15);
+        assert_eq!(plan.stats.files_skipped.binary, 1);
+        assert_eq!(plan.stats.files_skipped.excluded, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_records_unreadable_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked.rs");
+        std::fs::write(temp_dir.path().join("main.rs"), "old_name\n").unwrap();
+        std::fs::write(&locked, "old_name\n").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        // Permissions don't stop root
+        if std::fs::read(&locked).is_ok() {
+            return;
+        }
+
+        let plan = scan_repository(
+            temp_dir.path(),
+            "old_name",
+            "new_name",
+            &PlanOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(plan.stats.total_matches, 1);
+        assert_eq!(plan.stats.files_skipped.unreadable, 1);
+        let unreadable = &plan.stats.files_skipped.unreadable_files;
+        assert_eq!(unreadable.len(), 1);
+        assert_eq!(unreadable[0].path, locked);
+        assert!(unreadable[0].error.contains("Permission denied"));
+    }
+
     #[test]
     fn test_rename_sorting() {
         let mut renames = vec![
@@ -1963,8 +3395,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -1975,6 +3414,63 @@ mod tests {
         assert!(content.contains("\"id\": \"test123\""));
     }
 
+    #[test]
+    fn test_plan_records_options() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "old_name\n").unwrap();
+        let options = PlanOptions {
+            unrestricted_level: 2,
+            exclude_acronyms: vec!["ID".to_string()],
+            match_anchor: MatchAnchor::Prefix,
+            ..PlanOptions::default()
+        };
+        let plan = scan_repository(temp_dir.path(), "old_name", "new_name", &options).unwrap();
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["options"]["unrestricted_level"], 2);
+        assert_eq!(
+            json["options"]["exclude_acronyms"],
+            serde_json::json!(["ID"])
+        );
+        assert_eq!(json["options"]["acronym_rendering"], "preserve-upper");
+        assert_eq!(json["options"]["case_mapping"], "unicode");
+
+        let parsed: Plan = serde_json::from_value(json).unwrap();
+        let recorded = parsed.options.unwrap();
+        assert_eq!(recorded.match_anchor, MatchAnchor::Prefix);
+        assert_eq!(recorded.unrestricted_level, 2);
+
+        // Plans written before options were recorded still load
+        let mut legacy = serde_json::to_value(&plan).unwrap();
+        legacy.as_object_mut().unwrap().remove("options");
+        let parsed: Plan = serde_json::from_value(legacy).unwrap();
+        assert!(parsed.options.is_none());
+    }
+
+    #[test]
+    fn test_scan_uses_case_mapping_from_options() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "istanbul_city\n").unwrap();
+        let turkish = PlanOptions {
+            case_mapping: CaseMapping::Turkish,
+            ..PlanOptions::default()
+        };
+
+        // Turkish lowercases I to a dotless ı, so istanbul is a different word
+        let plan = scan_repository(temp_dir.path(), "ISTANBUL", "ANKARA", &turkish).unwrap();
+        assert_eq!(plan.stats.total_matches, 0);
+        assert_eq!(case_mapping(), CaseMapping::Unicode);
+
+        let plan = scan_repository(
+            temp_dir.path(),
+            "ISTANBUL",
+            "ANKARA",
+            &PlanOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(plan.stats.total_matches, 1);
+    }
+
     // ===================================================================
     // Tests for apply_coercion_to_variant
     // ===================================================================
@@ -2240,4 +3736,136 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_match_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("lib.ts"),
+            "const x = old_name;\ngetOldName();\nOldName\n",
+        )
+        .unwrap();
+        let options = PlanOptions {
+            rename_files: false,
+            rename_dirs: false,
+            ..PlanOptions::default()
+        };
+        let plan = scan_repository(&root, "old_name", "new_name", &options).unwrap();
+        let mut metadata: Vec<_> = plan
+            .matches
+            .iter()
+            .map(|hunk| {
+                (
+                    hunk.line,
+                    hunk.style,
+                    hunk.match_kind,
+                    hunk.boundary_before,
+                    hunk.boundary_after,
+                )
+            })
+            .collect();
+        metadata.sort_by_key(|(line, ..)| *line);
+        assert_eq!(
+            metadata,
+            [
+                (
+                    1,
+                    Some(Style::Snake),
+                    Some(MatchKind::Exact),
+                    Some(BoundaryClass::Whitespace),
+                    Some(BoundaryClass::Punctuation),
+                ),
+                (
+                    2,
+                    Some(Style::Camel),
+                    Some(MatchKind::Compound),
+                    Some(BoundaryClass::LineEdge),
+                    Some(BoundaryClass::Punctuation),
+                ),
+                (
+                    3,
+                    Some(Style::Pascal),
+                    Some(MatchKind::Exact),
+                    Some(BoundaryClass::LineEdge),
+                    Some(BoundaryClass::LineEdge),
+                ),
+            ]
+        );
+
+        let json = serde_json::to_value(&plan.matches[0]).unwrap();
+        assert_eq!(json["match_kind"], "exact");
+        assert_eq!(json["boundary_after"], "punctuation");
+    }
+
+    #[test]
+    fn test_match_anchor() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("lib.rs"),
+            "let old_config = OldConfig::new();\nlet threshold_old = old;\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("old_config.rs"), "").unwrap();
+        std::fs::write(root.join("threshold_old.rs"), "").unwrap();
+
+        let scan = |match_anchor| {
+            let options = PlanOptions {
+                match_anchor,
+                report_skipped: true,
+                ..PlanOptions::default()
+            };
+            scan_repository(&root, "old", "new", &options).unwrap()
+        };
+        let matched = |plan: &Plan| {
+            let mut matched: Vec<_> = plan
+                .matches
+                .iter()
+                .map(|hunk| (hunk.line, hunk.content.clone()))
+                .collect();
+            matched.sort();
+            matched
+        };
+        let renamed = |plan: &Plan| {
+            let mut names: Vec<_> = plan
+                .paths
+                .iter()
+                .map(|rename| rename.new_path.file_name().unwrap().to_owned())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let prefix = scan(MatchAnchor::Prefix);
+        assert_eq!(
+            matched(&prefix),
+            [
+                (1, "OldConfig".to_string()),
+                (1, "old_config".to_string()),
+                (2, "old".to_string())
+            ]
+        );
+        assert_eq!(renamed(&prefix), ["new_config.rs"]);
+        assert!(prefix
+            .skipped
+            .iter()
+            .any(|skipped| skipped.reason == SkipReason::Anchor));
+
+        let suffix = scan(MatchAnchor::Suffix);
+        assert_eq!(
+            matched(&suffix),
+            [(2, "old".to_string()), (2, "threshold_old".to_string())]
+        );
+        assert_eq!(renamed(&suffix), ["threshold_new.rs"]);
+
+        let whole = scan(MatchAnchor::Whole);
+        assert_eq!(matched(&whole), [(2, "old".to_string())]);
+        assert!(renamed(&whole).is_empty());
+
+        assert!(MatchAnchor::Prefix.keeps("oldConfig", 0, 3, "old"));
+        assert!(!MatchAnchor::Prefix.keeps("getOldConfig", 3, 6, "old"));
+        assert!(MatchAnchor::Suffix.keeps("get-old", 4, 7, "old"));
+        assert!(!MatchAnchor::Suffix.keeps("threshold_old.rs", 6, 9, "old"));
+    }
 }
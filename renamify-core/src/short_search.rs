@@ -0,0 +1,102 @@
+//! Guarding against search terms too short or common to rename safely.
+//!
+//! A search for `id` or `data` matches inside countless unrelated
+//! identifiers. `plan` and `rename` refuse single-word search terms that are
+//! shorter than `[short_search] min_length` or on the stopword list unless
+//! `--allow-short-search` is given; `search` only warns.
+
+use crate::case_model::parse_to_tokens;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Common words that match far more than the identifier being renamed
+pub const DEFAULT_STOPWORDS: &[&str] = &[
+    "and", "add", "all", "data", "for", "get", "info", "item", "key", "list", "not", "set", "the",
+    "tmp", "type", "val", "value",
+];
+
+pub const DEFAULT_MIN_LENGTH: usize = 3;
+
+const fn default_min_length() -> usize {
+    DEFAULT_MIN_LENGTH
+}
+
+/// Protection against search terms that are a single short or common word,
+/// which `plan` and `rename` refuse without `--allow-short-search`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+pub struct ShortSearchConfig {
+    /// Single-word search terms shorter than this many characters are refused
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+
+    /// Single-word search terms refused on top of the built-in stopwords,
+    /// compared case-insensitively
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+}
+
+impl Default for ShortSearchConfig {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_LENGTH,
+            stopwords: Vec::new(),
+        }
+    }
+}
+
+impl ShortSearchConfig {
+    /// Why `search` is likely to cause mass false positives, or None if it
+    /// is long or specific enough. Only single-word terms are checked, so
+    /// `id` is refused but `user_id` is not.
+    pub fn check(&self, search: &str) -> Option<String> {
+        let tokens = parse_to_tokens(search).tokens;
+        let [token] = tokens.as_slice() else {
+            return None;
+        };
+
+        let length = token.text.chars().count();
+        if length < self.min_length {
+            return Some(format!(
+                "'{search}' is a single {length}-character word (minimum {})",
+                self.min_length
+            ));
+        }
+
+        let is_stopword = DEFAULT_STOPWORDS
+            .iter()
+            .copied()
+            .chain(self.stopwords.iter().map(String::as_str))
+            .any(|stopword| stopword.eq_ignore_ascii_case(&token.text));
+        is_stopword.then(|| format!("'{search}' is a common word on the stopword list"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let config = ShortSearchConfig {
+            stopwords: vec!["Widget".to_string()],
+            ..ShortSearchConfig::default()
+        };
+
+        assert!(config.check("id").unwrap().contains("2-character"));
+        assert!(config.check("DB").is_some());
+        assert!(config.check("data").unwrap().contains("stopword"));
+        assert!(config.check("widget").is_some());
+
+        assert_eq!(config.check("user_id"), None);
+        assert_eq!(config.check("dataSource"), None);
+        assert_eq!(config.check("old"), None);
+
+        let relaxed = ShortSearchConfig {
+            min_length: 1,
+            ..ShortSearchConfig::default()
+        };
+        assert_eq!(relaxed.check("id"), None);
+    }
+}
@@ -0,0 +1,323 @@
+//! Signed plans.
+//!
+//! `plan --sign <KEY>` signs the written plan with an ed25519 key made by
+//! `renamify keygen`, storing the public key, the time and the signature under
+//! the plan's `signature` key. The signature covers every other field of the
+//! plan, read as JSON with sorted keys, so any edit to the plan breaks it.
+//!
+//! `apply` always checks a signed plan and refuses one that was changed after
+//! signing. `--trusted-key` limits the keys a signature is accepted from, and
+//! `--require-signed` refuses plans without a signature.
+
+use crate::error::RenamifyError;
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Kept apart from any other use of the same key
+const SIGNING_CONTEXT: &str = "renamify plan signature v1\n";
+
+/// Who signed a plan and when, as stored in the plan file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanSignature {
+    /// Hex-encoded ed25519 public key
+    pub public_key: String,
+    pub signed_at: String,
+    /// Hex-encoded ed25519 signature
+    pub signature: String,
+}
+
+/// What a plan must satisfy before it is applied
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlanTrust {
+    /// Expected SHA-256 digest of the plan file, as `sha256:<hex>`
+    pub checksum: Option<String>,
+    /// Public keys, or paths to public key files, that signatures are
+    /// accepted from; any key when empty
+    pub trusted_keys: Vec<String>,
+    /// Refuse plans without a signature
+    pub require_signed: bool,
+}
+
+/// Generate a key pair, writing the secret key to `path` and the public key
+/// to `path` with `.pub` appended. Returns the public key.
+pub fn generate_key(path: &Path, force: bool) -> Result<String> {
+    let public_path = public_key_path(path);
+    for path in [path, public_path.as_path()] {
+        if path.exists() && !force {
+            return Err(RenamifyError::Conflict(format!(
+                "{} already exists (pass --force to replace it)",
+                path.display()
+            ))
+            .into());
+        }
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+
+    let key = SigningKey::generate(&mut rand_core::OsRng);
+    let public_key = to_hex(key.verifying_key().as_bytes());
+    write_secret(path, &format!("{}\n", to_hex(key.as_bytes())))?;
+    std::fs::write(&public_path, format!("{public_key}\n"))
+        .with_context(|| format!("Failed to write {}", public_path.display()))?;
+    Ok(public_key)
+}
+
+/// Where the public key of the secret key at `path` is written
+pub fn public_key_path(path: &Path) -> PathBuf {
+    let mut public_path = path.as_os_str().to_owned();
+    public_path.push(".pub");
+    PathBuf::from(public_path)
+}
+
+/// Sign the plan file at `plan_path` with the secret key at `key_path`,
+/// replacing any earlier signature
+pub fn sign_plan_file(plan_path: &Path, key_path: &Path) -> Result<PlanSignature> {
+    let secret = std::fs::read_to_string(key_path)
+        .with_context(|| format!("Failed to read signing key {}", key_path.display()))?;
+    let seed: [u8; 32] = from_hex(secret.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            RenamifyError::InvalidInput(format!(
+                "{} is not a signing key made by `renamify keygen`",
+                key_path.display()
+            ))
+        })?;
+    let key = SigningKey::from_bytes(&seed);
+
+    let content = std::fs::read(plan_path)
+        .with_context(|| format!("Failed to read plan file {}", plan_path.display()))?;
+    let mut plan: Value = serde_json::from_slice(&content)
+        .with_context(|| format!("Failed to parse plan file {}", plan_path.display()))?;
+    let Some(fields) = plan.as_object_mut() else {
+        return Err(RenamifyError::InvalidInput(format!(
+            "{} is not a plan file",
+            plan_path.display()
+        ))
+        .into());
+    };
+    fields.insert(
+        "signature".to_string(),
+        serde_json::json!({
+            "public_key": to_hex(key.verifying_key().as_bytes()),
+            "signed_at": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+    let signature = key.sign(signed_message(&plan).as_bytes());
+    plan["signature"]["signature"] = Value::String(to_hex(&signature.to_bytes()));
+
+    std::fs::write(plan_path, serde_json::to_string_pretty(&plan)?)
+        .with_context(|| format!("Failed to write plan to {}", plan_path.display()))?;
+    Ok(serde_json::from_value(plan["signature"].take())?)
+}
+
+/// Check the signature of the plan file `content` against `trust`, returning
+/// the signature when the plan is signed
+pub fn verify_plan(
+    source: &str,
+    content: &[u8],
+    trust: &PlanTrust,
+) -> Result<Option<PlanSignature>> {
+    let plan: Value = serde_json::from_slice(content)
+        .with_context(|| format!("Failed to parse plan file {source}"))?;
+    let Some(stored) = plan.get("signature") else {
+        if trust.require_signed {
+            return Err(untrusted(format!("{source} is not signed")));
+        }
+        return Ok(None);
+    };
+    let stored: PlanSignature = serde_json::from_value(stored.clone())
+        .map_err(|e| untrusted(format!("{source} has an invalid signature: {e}")))?;
+
+    if !trust.trusted_keys.is_empty() {
+        let trusted = trust
+            .trusted_keys
+            .iter()
+            .map(|key| read_public_key(key))
+            .collect::<Result<Vec<_>>>()?;
+        if !trusted.contains(&stored.public_key) {
+            return Err(untrusted(format!(
+                "{source} is signed by {}, which is not a trusted key",
+                stored.public_key
+            )));
+        }
+    }
+
+    let public_key = from_hex(&stored.public_key)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok());
+    let signature = from_hex(&stored.signature)
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+        .map(|bytes| Signature::from_bytes(&bytes));
+    let (Some(public_key), Some(signature)) = (public_key, signature) else {
+        return Err(untrusted(format!("{source} has a malformed signature")));
+    };
+
+    let mut unsigned = plan;
+    if let Some(fields) = unsigned["signature"].as_object_mut() {
+        fields.remove("signature");
+    }
+    public_key
+        .verify(signed_message(&unsigned).as_bytes(), &signature)
+        .map_err(|_| {
+            untrusted(format!(
+                "{source} was changed after it was signed; refusing to apply it"
+            ))
+        })?;
+    Ok(Some(stored))
+}
+
+/// A public key given inline as hex or as the path of a `.pub` file, as hex
+fn read_public_key(key: &str) -> Result<String> {
+    let hex = if from_hex(key).is_some_and(|bytes| bytes.len() == 32) {
+        key.to_string()
+    } else {
+        std::fs::read_to_string(key)
+            .with_context(|| format!("Failed to read trusted key {key}"))?
+            .trim()
+            .to_string()
+    };
+    match from_hex(&hex) {
+        Some(bytes) if bytes.len() == 32 => Ok(hex.to_ascii_lowercase()),
+        _ => Err(RenamifyError::InvalidInput(format!(
+            "Invalid trusted key {key}: expected a public key made by `renamify keygen`"
+        ))
+        .into()),
+    }
+}
+
+/// The bytes a signature covers: the plan as JSON with object keys sorted
+fn signed_message(plan: &Value) -> String {
+    let mut message = SIGNING_CONTEXT.to_string();
+    write_canonical(plan, &mut message);
+    message
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        },
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&fields[key], out);
+            }
+            out.push('}');
+        },
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(unix)]
+fn write_secret(path: &Path, content: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn write_secret(path: &Path, content: &str) -> Result<()> {
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn untrusted(message: String) -> anyhow::Error {
+    RenamifyError::Conflict(message).into()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sign_and_verify_plan() {
+        let dir = TempDir::new().unwrap();
+        let key_path = dir.path().join("keys/renamify.key");
+        let public_key = generate_key(&key_path, false).unwrap();
+        assert!(generate_key(&key_path, false).is_err());
+        let plan_path = dir.path().join("plan.json");
+        std::fs::write(&plan_path, r#"{"id": "abc", "matches": [{"line": 1}]}"#).unwrap();
+
+        let unsigned = std::fs::read(&plan_path).unwrap();
+        assert!(verify_plan("plan.json", &unsigned, &PlanTrust::default())
+            .unwrap()
+            .is_none());
+        let require_signed = PlanTrust {
+            require_signed: true,
+            ..PlanTrust::default()
+        };
+        assert!(verify_plan("plan.json", &unsigned, &require_signed).is_err());
+
+        let signature = sign_plan_file(&plan_path, &key_path).unwrap();
+        assert_eq!(signature.public_key, public_key);
+        let signed = std::fs::read(&plan_path).unwrap();
+        let trust = PlanTrust {
+            trusted_keys: vec![public_key_path(&key_path).to_string_lossy().to_string()],
+            require_signed: true,
+            ..PlanTrust::default()
+        };
+        assert_eq!(
+            verify_plan("plan.json", &signed, &trust).unwrap(),
+            Some(signature)
+        );
+
+        let tampered = String::from_utf8(signed.clone())
+            .unwrap()
+            .replace("\"line\": 1", "\"line\": 2");
+        let error = verify_plan("plan.json", tampered.as_bytes(), &trust).unwrap_err();
+        assert!(error.to_string().contains("changed after it was signed"));
+
+        let other_key = generate_key(&dir.path().join("other.key"), false).unwrap();
+        let other = PlanTrust {
+            trusted_keys: vec![other_key],
+            ..PlanTrust::default()
+        };
+        let error = verify_plan("plan.json", &signed, &other).unwrap_err();
+        assert!(error.to_string().contains("not a trusted key"));
+    }
+}
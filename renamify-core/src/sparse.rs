@@ -0,0 +1,157 @@
+//! Sparse checkout and partial clone awareness.
+//!
+//! Files that git tracks but that are not on disk were left out by a sparse
+//! checkout, so a plan can't reach them. The report lists the ones that likely
+//! need the rename: their path contains the search term, or their content does
+//! when git has it locally. Contents are never fetched, so in a partial clone
+//! only paths may be checked.
+
+use crate::scanner::{build_globset, build_include_globset, PlanOptions};
+use crate::warnings::{PlanWarning, WarningKind, WarningSeverity};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use ts_rs::TS;
+
+/// Paths passed to a single `git grep`
+const GREP_BATCH: usize = 500;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct SparseReport {
+    /// Tracked files under the scanned paths that are not checked out
+    pub missing_files: usize,
+    /// Files that are not checked out whose path or content contains the
+    /// search term, relative to the repository root
+    pub likely_matches: Vec<PathBuf>,
+    /// False when the contents of missing files are not available locally, as
+    /// in a partial clone, so only their paths were checked
+    pub contents_searched: bool,
+}
+
+impl SparseReport {
+    /// The warning shown with the plan, or None when nothing likely matches
+    pub fn warning(&self) -> Option<PlanWarning> {
+        if self.likely_matches.is_empty() {
+            return None;
+        }
+        Some(PlanWarning::new(
+            WarningKind::SparseCheckout,
+            WarningSeverity::Warning,
+            format!(
+                "{} files outside the sparse checkout likely need this rename too; check them out and plan again before treating the rename as complete",
+                self.likely_matches.len()
+            ),
+        ))
+    }
+}
+
+/// Compare the files git tracks under `roots` with the files on disk. None
+/// when the roots are not in a git repository or every tracked file is there.
+pub fn sparse_report(
+    roots: &[PathBuf],
+    variants: &[&str],
+    options: &PlanOptions,
+) -> Option<SparseReport> {
+    let first = roots.first()?;
+    let dir = if first.is_dir() {
+        first.as_path()
+    } else {
+        first.parent()?
+    };
+    let top = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let top = PathBuf::from(top.trim_end()).canonicalize().ok()?;
+
+    let includes = build_include_globset(&options.includes).ok()?;
+    let excludes = build_globset(&options.excludes).ok()?;
+    let tracked = git(&top, &["ls-files", "-z"])?;
+    let missing: Vec<&str> = tracked
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .filter(|path| {
+            // Globs match relative to the scan root, as in the scanner
+            let full = top.join(path);
+            let Some(relative) = roots.iter().find_map(|root| full.strip_prefix(root).ok()) else {
+                return false;
+            };
+            includes
+                .as_ref()
+                .is_none_or(|globs| globs.is_match(relative))
+                && !excludes
+                    .as_ref()
+                    .is_some_and(|globs| globs.is_match(relative))
+                && std::fs::symlink_metadata(&full).is_err()
+        })
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let mut likely: BTreeSet<PathBuf> = missing
+        .iter()
+        .filter(|path| variants.iter().any(|variant| path.contains(variant)))
+        .map(PathBuf::from)
+        .collect();
+
+    let mut contents_searched = true;
+    for batch in missing.chunks(GREP_BATCH) {
+        let Some(found) = grep_cached(&top, variants, batch) else {
+            contents_searched = false;
+            break;
+        };
+        likely.extend(found);
+    }
+
+    Some(SparseReport {
+        missing_files: missing.len(),
+        likely_matches: likely.into_iter().collect(),
+        contents_searched,
+    })
+}
+
+/// Files among `paths` whose indexed content contains any of `variants`, or
+/// None if git can't read them without fetching
+fn grep_cached(top: &Path, variants: &[&str], paths: &[&str]) -> Option<Vec<PathBuf>> {
+    let mut command = Command::new("git");
+    command
+        .current_dir(top)
+        .env("GIT_NO_LAZY_FETCH", "1")
+        .args(["grep", "--cached", "-l", "-z", "-F"]);
+    for variant in variants {
+        command.arg("-e").arg(variant);
+    }
+    let output = command
+        .arg("--")
+        .args(paths)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    // Exit status 1 means nothing matched
+    match output.status.code() {
+        Some(0) => Some(
+            String::from_utf8_lossy(&output.stdout)
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+        ),
+        Some(1) => Some(Vec::new()),
+        _ => None,
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
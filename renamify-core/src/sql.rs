@@ -0,0 +1,393 @@
+//! SQL identifier awareness for `.sql` files.
+//!
+//! Unquoted SQL identifiers are case-insensitive, so `Old_Name`, `old_name`
+//! and `OLD_NAME` all name the same table, while quoted identifiers
+//! (`"OldName"`, `` `old_name` ``, `[old_name]`) keep their exact case.
+//! Keywords are case-insensitive too, and an unquoted `ORDER` or `user` is a
+//! keyword rather than the table being renamed.
+
+use crate::case_model::{parse_to_tokens, to_style, Style};
+use crate::language::Language;
+use crate::pattern::Match;
+use regex::bytes::Regex;
+use std::path::Path;
+
+/// Reserved words that are never renamed when unquoted, sorted for lookup
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASCADE",
+    "CASE",
+    "CHECK",
+    "COLUMN",
+    "COMMIT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "CURRENT",
+    "DATABASE",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXISTS",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GRANT",
+    "GROUP",
+    "HAVING",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "REFERENCES",
+    "REVOKE",
+    "RIGHT",
+    "ROLLBACK",
+    "ROW",
+    "ROWS",
+    "SCHEMA",
+    "SELECT",
+    "SESSION",
+    "SET",
+    "TABLE",
+    "THEN",
+    "TO",
+    "TRANSACTION",
+    "TRIGGER",
+    "TRUNCATE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USER",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+/// Whether `path` is a SQL file
+pub fn is_sql_file(path: &Path) -> bool {
+    Language::from_path(path) == Some(Language::Sql)
+}
+
+/// Whether `word` is a reserved SQL keyword, in any letter case
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .binary_search(&word.to_ascii_uppercase().as_str())
+        .is_ok()
+}
+
+/// What a byte offset in a SQL file is inside of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlRegion {
+    /// A quoted identifier, whose case is significant
+    QuotedIdentifier,
+    /// A string literal or comment
+    Literal,
+}
+
+/// Byte ranges of a SQL file that are quoted identifiers, strings or comments
+#[derive(Debug, Default)]
+pub struct SqlRegions {
+    regions: Vec<(usize, usize, SqlRegion)>,
+}
+
+impl SqlRegions {
+    pub fn scan(content: &[u8]) -> Self {
+        let mut regions = Vec::new();
+        let mut pos = 0;
+
+        while pos < content.len() {
+            let rest = &content[pos..];
+            let (end, kind) = if rest.starts_with(b"--") {
+                (
+                    find(content, pos, b"\n").unwrap_or(content.len()),
+                    SqlRegion::Literal,
+                )
+            } else if rest.starts_with(b"/*") {
+                let end = find(content, pos + 2, b"*/").map_or(content.len(), |found| found + 2);
+                (end, SqlRegion::Literal)
+            } else if rest[0] == b'\'' {
+                (quoted_end(content, pos, b'\''), SqlRegion::Literal)
+            } else if rest[0] == b'"' || rest[0] == b'`' {
+                (
+                    quoted_end(content, pos, rest[0]),
+                    SqlRegion::QuotedIdentifier,
+                )
+            } else if rest[0] == b'[' && (pos == 0 || !is_ident_byte(content[pos - 1])) {
+                // `[name]` quotes an identifier in SQL Server; `a[1]` is a subscript
+                (quoted_end(content, pos, b']'), SqlRegion::QuotedIdentifier)
+            } else {
+                pos += 1;
+                continue;
+            };
+            regions.push((pos, end, kind));
+            pos = end;
+        }
+
+        Self { regions }
+    }
+
+    /// The region containing `offset`, or None if it is in unquoted SQL
+    pub fn kind_at(&self, offset: usize) -> Option<SqlRegion> {
+        let idx = self
+            .regions
+            .partition_point(|(start, _, _)| *start <= offset);
+        let (start, end, kind) = *self.regions.get(idx.checked_sub(1)?)?;
+        (start <= offset && offset < end).then_some(kind)
+    }
+}
+
+/// Matches the search term's snake case form in any letter case
+#[derive(Debug)]
+pub struct SqlPattern {
+    regex: Regex,
+    replacement: String,
+}
+
+impl SqlPattern {
+    /// None when the search term has no snake case form
+    pub fn new(search: &str, replace: &str) -> Option<Self> {
+        let snake = to_style(&parse_to_tokens(search), Style::Snake);
+        if snake.is_empty() {
+            return None;
+        }
+        let regex = Regex::new(&format!("(?i){}", regex::escape(&snake))).ok()?;
+        let replacement = if replace.is_empty() {
+            String::new()
+        } else {
+            to_style(&parse_to_tokens(replace), Style::Snake)
+        };
+        Some(Self { regex, replacement })
+    }
+
+    /// Drop matches that are unquoted keywords, returning them, and add unquoted identifiers
+    /// that only differ from the search term by letter case (`Old_Name`).
+    /// These are replaced with the snake case replacement in the same letter
+    /// case: `OLD_NAME`, `Old_Name` or lower case.
+    pub fn adjust_matches(
+        &self,
+        matches: &mut Vec<Match>,
+        content: &[u8],
+        file: &str,
+        regions: &SqlRegions,
+    ) -> Vec<Match> {
+        let (keywords, kept): (Vec<Match>, Vec<Match>) =
+            std::mem::take(matches).into_iter().partition(|m| {
+                regions.kind_at(m.start).is_none()
+                    && std::str::from_utf8(&content[m.start..m.end]).is_ok_and(is_keyword)
+            });
+        *matches = kept;
+
+        let mut extra = Vec::new();
+        for found in self.regex.find_iter(content) {
+            let (start, end) = (found.start(), found.end());
+            let bounded = (start == 0 || !is_ident_byte(content[start - 1]))
+                && content.get(end).is_none_or(|&byte| !is_ident_byte(byte));
+            let text = String::from_utf8_lossy(found.as_bytes()).to_string();
+            if !bounded
+                || regions.kind_at(start).is_some()
+                || is_keyword(&text)
+                || matches.iter().any(|m| m.start < end && start < m.end)
+            {
+                continue;
+            }
+
+            let replacement = match_case(&text, &self.replacement);
+            let line_start = content[..start]
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .map_or(0, |p| p + 1);
+            #[allow(clippy::naive_bytecount)]
+            let line = content[..start]
+                .iter()
+                .filter(|&&byte| byte == b'\n')
+                .count()
+                + 1;
+            extra.push(Match {
+                file: file.to_string(),
+                line,
+                column: start - line_start,
+                start,
+                end,
+                variant: text,
+                text: replacement,
+            });
+        }
+        matches.extend(extra);
+        keywords
+    }
+}
+
+/// `replacement` upper-cased if `text` is, with each word capitalized if each
+/// word of `text` is, otherwise lower case
+fn match_case(text: &str, replacement: &str) -> String {
+    if !text.chars().any(|c| c.is_ascii_lowercase()) {
+        return replacement.to_ascii_uppercase();
+    }
+    let capitalized = text
+        .split('_')
+        .all(|word| word.starts_with(|c: char| c.is_ascii_uppercase()));
+    if !capitalized {
+        return replacement.to_string();
+    }
+    replacement
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+fn find(content: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    content
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|found| from + found)
+}
+
+/// End of a quoted string or identifier starting at `pos`. A doubled closing
+/// quote (`''`, `""`) is an escaped quote, not the end.
+fn quoted_end(content: &[u8], pos: usize, close: u8) -> usize {
+    let mut i = pos + 1;
+    while i < content.len() {
+        if content[i] == close {
+            if content.get(i + 1) == Some(&close) && close != b']' {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    content.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords_are_sorted() {
+        assert!(KEYWORDS.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(is_keyword("order"));
+        assert!(is_keyword("User"));
+        assert!(!is_keyword("orders"));
+    }
+
+    #[test]
+    fn test_regions() {
+        let source = "SELECT \"OldName\", `old_name`, [old name] FROM t -- old_name\nWHERE a = 'it''s old_name' AND b[1] = old_name;";
+        let regions = SqlRegions::scan(source.as_bytes());
+        let kinds: Vec<Option<SqlRegion>> = [
+            "OldName",
+            "`old_name",
+            "old name",
+            "-- old",
+            "s old",
+            "1]",
+            "old_name;",
+        ]
+        .iter()
+        .map(|needle| regions.kind_at(source.find(needle).unwrap()))
+        .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Some(SqlRegion::QuotedIdentifier),
+                Some(SqlRegion::QuotedIdentifier),
+                Some(SqlRegion::QuotedIdentifier),
+                Some(SqlRegion::Literal),
+                Some(SqlRegion::Literal),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_adjust_matches() {
+        let source = "SELECT Old_Name.id FROM old_name JOIN \"Old_Name\" ON OLD_NAME.x;";
+        let regions = SqlRegions::scan(source.as_bytes());
+        let start = source.find("old_name").unwrap();
+        let mut matches = vec![Match {
+            file: "q.sql".to_string(),
+            line: 1,
+            column: start,
+            start,
+            end: start + 8,
+            variant: "old_name".to_string(),
+            text: "old_name".to_string(),
+        }];
+
+        let pattern = SqlPattern::new("OldName", "NewTable").unwrap();
+        pattern.adjust_matches(&mut matches, source.as_bytes(), "q.sql", &regions);
+        let found: Vec<(&str, &str)> = matches
+            .iter()
+            .map(|m| (m.variant.as_str(), m.text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("old_name", "old_name"),
+                ("Old_Name", "New_Table"),
+                ("OLD_NAME", "NEW_TABLE")
+            ]
+        );
+
+        let source = "SELECT * FROM orders ORDER BY id";
+        let mut matches = vec![Match {
+            file: "q.sql".to_string(),
+            line: 1,
+            column: 21,
+            start: 21,
+            end: 26,
+            variant: "ORDER".to_string(),
+            text: "PURCHASE".to_string(),
+        }];
+        let regions = SqlRegions::scan(source.as_bytes());
+        SqlPattern::new("order", "purchase")
+            .unwrap()
+            .adjust_matches(&mut matches, source.as_bytes(), "q.sql", &regions);
+        assert!(matches.is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+//! Staging an applied plan in git.
+//!
+//! `--stage` applies a plan as usual and then stages the files it changed.
+//! `--stage-only` writes the plan into the git index without touching the
+//! working tree, so a rename can be committed while local edits stay where
+//! they are. Edits are applied to the indexed content of each file; a line
+//! that differs between the working tree and the index can't be matched up,
+//! so an edit on such a line is a conflict.
+
+use crate::apply::{apply_replacements, DEFAULT_COMMIT_TEMPLATE};
+use crate::error::RenamifyError;
+use crate::scanner::{Plan, RenameKind};
+use crate::vcs::{CommitOptions, Git, IndexEntry};
+use anyhow::{Context, Result};
+use similar::{DiffOp, TextDiff};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Where `apply` leaves its changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StageMode {
+    /// Written to the working tree and left unstaged
+    #[default]
+    Unstaged,
+    /// Written to the working tree and staged
+    Staged,
+    /// Written to the git index only; the working tree is left as it is
+    IndexOnly,
+}
+
+/// Stage the files an apply changed: `edited` at their original paths, and
+/// `renames` as performed, in order
+pub fn stage_applied(
+    current_dir: &Path,
+    edited: &[PathBuf],
+    renames: &[(PathBuf, PathBuf)],
+) -> Result<()> {
+    let root = git_root(current_dir)?;
+    let git = Git::new(&root);
+    let tracked: HashSet<String> = git
+        .index_entries()?
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect();
+
+    let is_tracked = |path: &Path| {
+        index_path(&root, current_dir, path).is_ok_and(|path| {
+            tracked.contains(&path)
+                || tracked
+                    .iter()
+                    .any(|entry| entry.starts_with(&format!("{path}/")))
+        })
+    };
+    let mut paths: Vec<PathBuf> = edited
+        .iter()
+        .map(|path| renamed_path(path, renames))
+        .chain(renames.iter().map(|(_, to)| to.clone()))
+        // Skips the temporary names of swapped paths
+        .filter(|path| path.exists())
+        // `git add` fails on a missing path it doesn't track, such as an
+        // untracked file that was renamed
+        .chain(
+            renames
+                .iter()
+                .map(|(from, _)| from.clone())
+                .filter(|from| is_tracked(from)),
+        )
+        // git runs in the repository root
+        .map(|path| current_dir.join(path))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    git.stage_paths(&paths)
+}
+
+/// Write `plan` into the git index of the repository containing
+/// `current_dir`, then commit the index if `commit` is given
+pub fn apply_plan_to_index(
+    plan: &Plan,
+    commit: Option<&CommitOptions>,
+    current_dir: &Path,
+) -> Result<()> {
+    let root = git_root(current_dir)?;
+    let git = Git::new(&root);
+
+    let message = commit
+        .map(|options| options.message(plan, DEFAULT_COMMIT_TEMPLATE))
+        .transpose()?;
+    if let Some(submodule) = crate::submodule::nested_repos(
+        current_dir,
+        plan.matches
+            .iter()
+            .map(|hunk| hunk.file.as_path())
+            .chain(plan.paths.iter().map(|rename| rename.path.as_path())),
+    )
+    .first()
+    {
+        return Err(RenamifyError::InvalidInput(format!(
+            "--stage-only can't change files in {}, which is another repository",
+            submodule.display()
+        ))
+        .into());
+    }
+
+    let entries = git.index_entries()?;
+    if let Some(entry) = entries.iter().find(|entry| entry.stage != 0) {
+        return Err(RenamifyError::Conflict(format!(
+            "{} has an unresolved merge conflict; resolve it before using --stage-only",
+            entry.path
+        ))
+        .into());
+    }
+    let mut index: BTreeMap<String, IndexEntry> = entries
+        .into_iter()
+        .map(|entry| (entry.path.clone(), entry))
+        .collect();
+
+    // Content edits, applied to the indexed content of each file
+    let mut edits_by_file: BTreeMap<&Path, Vec<(String, String, usize, usize)>> = BTreeMap::new();
+    for hunk in &plan.matches {
+        edits_by_file.entry(&hunk.file).or_default().push((
+            hunk.content.clone(),
+            hunk.replace.clone(),
+            hunk.start,
+            hunk.end,
+        ));
+    }
+    let mut changed: HashSet<String> = HashSet::new();
+    for (file, edits) in edits_by_file {
+        let path = index_path(&root, current_dir, file)?;
+        let entry = index.get_mut(&path).ok_or_else(|| untracked(file))?;
+        let indexed = String::from_utf8(git.read_blob(&entry.object)?)
+            .with_context(|| format!("{} is not UTF-8 in the git index", file.display()))?;
+        let working = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let edits = map_edits(file, &working, &indexed, &edits)?;
+        let modified = apply_replacements(file, &indexed, &edits)?;
+        entry.object = git.write_blob(modified.as_bytes())?;
+        changed.insert(path);
+    }
+
+    // Renames, as the new name of each renamed path
+    let mut new_names: HashMap<String, String> = HashMap::new();
+    for rename in &plan.paths {
+        let path = index_path(&root, current_dir, &rename.path)?;
+        let tracked = match rename.kind {
+            RenameKind::File => index.contains_key(&path),
+            RenameKind::Dir => index
+                .keys()
+                .any(|entry| entry.starts_with(&format!("{path}/"))),
+        };
+        if !tracked {
+            return Err(untracked(&rename.path));
+        }
+        let new_name = rename
+            .new_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| {
+                RenamifyError::InvalidInput(format!(
+                    "Invalid rename target {}",
+                    rename.new_path.display()
+                ))
+            })?;
+        new_names.insert(path, new_name);
+    }
+
+    let mut removed = Vec::new();
+    let mut moved = Vec::new();
+    let mut added = Vec::new();
+    for (path, entry) in &index {
+        let new_path = renamed_index_path(path, &new_names);
+        if new_path != *path {
+            removed.push(entry.clone());
+            moved.push(IndexEntry {
+                path: new_path,
+                ..entry.clone()
+            });
+        } else if changed.contains(path) {
+            added.push(entry.clone());
+        }
+    }
+    // A path can only be renamed onto another that is moving away too
+    let removed_paths: HashSet<&str> = removed.iter().map(|entry| entry.path.as_str()).collect();
+    if let Some(entry) = moved.iter().find(|entry| {
+        index.contains_key(&entry.path) && !removed_paths.contains(entry.path.as_str())
+    }) {
+        return Err(
+            RenamifyError::Conflict(format!("{} is already in the git index", entry.path)).into(),
+        );
+    }
+    added.extend(moved);
+    git.update_index(&removed, &added)?;
+
+    if let (Some(options), Some(message)) = (commit, message) {
+        git.commit_staged(&message, options)?;
+    }
+    Ok(())
+}
+
+/// Move edits made against the working tree content of `file` to the same
+/// text in its indexed content
+fn map_edits(
+    file: &Path,
+    working: &str,
+    indexed: &str,
+    edits: &[(String, String, usize, usize)],
+) -> Result<Vec<(String, String, usize, usize)>> {
+    if working == indexed {
+        return Ok(edits.to_vec());
+    }
+
+    let working_starts = line_starts(working);
+    let indexed_starts = line_starts(indexed);
+    let line_of = |offset: usize| working_starts.partition_point(|&start| start <= offset) - 1;
+    let diff = TextDiff::from_lines(indexed, working);
+    edits
+        .iter()
+        .map(|(before, after, start, end)| {
+            let first = line_of(*start);
+            let last = line_of(end.saturating_sub(1).max(*start));
+            let unchanged = diff.ops().iter().find_map(|op| match *op {
+                DiffOp::Equal {
+                    old_index,
+                    new_index,
+                    len,
+                } if (new_index..new_index + len).contains(&first)
+                    && (new_index..new_index + len).contains(&last) =>
+                {
+                    Some(old_index + first - new_index)
+                },
+                _ => None,
+            });
+            let Some(indexed_line) = unchanged else {
+                return Err(RenamifyError::Conflict(format!(
+                    "{}:{} has unstaged changes on the line to edit; stage or stash them, or apply without --stage-only",
+                    file.display(),
+                    first + 1
+                ))
+                .into());
+            };
+            let offset = indexed_starts[indexed_line];
+            let moved = |position: usize| position - working_starts[first] + offset;
+            Ok((before.clone(), after.clone(), moved(*start), moved(*end)))
+        })
+        .collect()
+}
+
+fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+/// `path` with every renamed component, at any depth, given its new name
+fn renamed_index_path(path: &str, new_names: &HashMap<String, String>) -> String {
+    let mut original = String::new();
+    let mut renamed = String::new();
+    for component in path.split('/') {
+        if !original.is_empty() {
+            original.push('/');
+            renamed.push('/');
+        }
+        original.push_str(component);
+        renamed.push_str(new_names.get(&original).map_or(component, String::as_str));
+    }
+    renamed
+}
+
+/// Where `path` ended up after `renames`, performed in order
+fn renamed_path(path: &Path, renames: &[(PathBuf, PathBuf)]) -> PathBuf {
+    renames.iter().fold(path.to_path_buf(), |path, (from, to)| {
+        path.strip_prefix(from)
+            .map_or_else(|_| path.clone(), |rest| to.join(rest))
+    })
+}
+
+/// `path` relative to the repository root, with `/` separators, as git lists
+/// it in the index
+fn index_path(root: &Path, current_dir: &Path, path: &Path) -> Result<String> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        current_dir.join(path)
+    };
+    let absolute = absolute
+        .canonicalize()
+        .unwrap_or_else(|_| normalize(&absolute));
+    let relative = absolute.strip_prefix(root).map_err(|_| {
+        RenamifyError::InvalidInput(format!(
+            "{} is outside the git repository at {}",
+            path.display(),
+            root.display()
+        ))
+    })?;
+    Ok(relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn git_root(dir: &Path) -> Result<PathBuf> {
+    match crate::vcs::detect(dir) {
+        Some(vcs) if vcs.name() == "git" => Ok(vcs.root().to_path_buf()),
+        _ => Err(RenamifyError::InvalidInput("Staging needs a git repository".to_string()).into()),
+    }
+}
+
+fn untracked(path: &Path) -> anyhow::Error {
+    RenamifyError::InvalidInput(format!(
+        "{} is not tracked by git, so --stage-only can't change it",
+        path.display()
+    ))
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_edits() {
+        let edit = |start: usize| ("old".to_string(), "new".to_string(), start, start + 3);
+        let indexed = "fn a() { old(); }\nfn b() { old(); }\n";
+        let working = "// wip\nfn a() { old(); }\nfn b() { old(x); }\n";
+
+        let mapped = map_edits(Path::new("lib.rs"), working, indexed, &[edit(16)]).unwrap();
+        assert_eq!(mapped, [edit(9)]);
+        let error = map_edits(Path::new("lib.rs"), working, indexed, &[edit(34)]).unwrap_err();
+        assert!(error.to_string().contains("lib.rs:3 has unstaged changes"));
+    }
+
+    #[test]
+    fn test_renamed_index_path() {
+        let new_names = HashMap::from([
+            ("src/old_dir".to_string(), "new_dir".to_string()),
+            ("src/old_dir/old.rs".to_string(), "new.rs".to_string()),
+        ]);
+        assert_eq!(
+            renamed_index_path("src/old_dir/old.rs", &new_names),
+            "src/new_dir/new.rs"
+        );
+        assert_eq!(
+            renamed_index_path("src/old_dir/other.rs", &new_names),
+            "src/new_dir/other.rs"
+        );
+        assert_eq!(renamed_index_path("src/lib.rs", &new_names), "src/lib.rs");
+    }
+}
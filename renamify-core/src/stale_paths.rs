@@ -0,0 +1,236 @@
+//! Build metadata that still names renamed paths once a plan is applied.
+//!
+//! Plans skip ignored files, so generated metadata such as
+//! `compile_commands.json`, IDE settings under `.idea/` and Makefiles in build
+//! directories keep pointing at the old paths. `apply --report-stale-paths`
+//! lists those references so they can be regenerated or fixed by hand;
+//! nothing is changed.
+
+use crate::apply::{adjust_rename, order_renames};
+use crate::scanner::Plan;
+use crate::suspicious::Rules;
+use ignore::WalkBuilder;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Metadata files looked for wherever they are
+const METADATA_FILES: &[&str] = &["compile_commands.json", "CMakeCache.txt"];
+
+/// Editor settings under `.vscode/` that name paths
+const VSCODE_FILES: &[&str] = &[
+    "settings.json",
+    "launch.json",
+    "tasks.json",
+    "c_cpp_properties.json",
+];
+
+/// Build files only looked at in ignored or protected directories, since the
+/// plan already covered the others
+const BUILD_FILES: &[&str] = &["Makefile", "makefile", "GNUmakefile", "build.ninja"];
+
+/// Directories never worth walking into for metadata
+const SKIPPED_DIRS: &[&str] = &[".git", ".hg", ".jj", ".renamify", "node_modules"];
+
+/// How deep under the root metadata is looked for
+const MAX_DEPTH: usize = 4;
+
+/// A line of build metadata that names a path renamed by the plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct StalePath {
+    pub file: PathBuf,
+    /// Line of the reference, from 1
+    #[ts(type = "number")]
+    pub line: u64,
+    /// The renamed path as named in the file, relative to the root
+    pub old_path: PathBuf,
+    /// Where it is now, relative to the root
+    pub new_path: PathBuf,
+}
+
+/// Find build metadata under `root` that still names a path renamed by `plan`.
+///
+/// `protected` are the protected patterns from the config, used with the
+/// ignore files to tell which Makefiles were left out of the plan.
+pub fn find_stale_paths(plan: &Plan, root: &Path, protected: &[String]) -> Vec<StalePath> {
+    let Ok(ordered) = order_renames(&plan.paths) else {
+        return Vec::new();
+    };
+    // Each renamed path with where it ends up, most specific first
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for rename in &ordered {
+        let (_, to) = adjust_rename(rename, &done);
+        done.push((rename.path.clone(), to.clone()));
+        if let (Ok(from), Ok(to)) = (rename.path.strip_prefix(root), to.strip_prefix(root)) {
+            moved.push((from.to_path_buf(), to.to_path_buf()));
+        }
+    }
+    if moved.is_empty() {
+        return Vec::new();
+    }
+    moved.sort_by_key(|(from, _)| std::cmp::Reverse(from.as_os_str().len()));
+
+    let mut rules = Rules::new(root, protected, 0).ok();
+    let mut stale = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .max_depth(Some(MAX_DEPTH))
+        .filter_entry(|entry| !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+        .build();
+    for entry in walker.flatten() {
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        if !is_metadata(relative, || {
+            rules
+                .as_mut()
+                .is_some_and(|rules| rules.reason(path, false).is_some())
+        }) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        for (index, line) in content.lines().enumerate() {
+            let found = moved
+                .iter()
+                .find(|(from, _)| names_path(line, &root.join(from)) || names_path(line, from));
+            if let Some((from, to)) = found {
+                stale.push(StalePath {
+                    file: path.to_path_buf(),
+                    line: index as u64 + 1,
+                    old_path: from.clone(),
+                    new_path: to.clone(),
+                });
+            }
+        }
+    }
+    stale.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    stale
+}
+
+/// Whether the file at `relative` is build metadata worth checking;
+/// `is_ignored` is only asked about build files
+fn is_metadata(relative: &Path, is_ignored: impl FnOnce() -> bool) -> bool {
+    let name = relative
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let extension = relative
+        .extension()
+        .map(|extension| extension.to_string_lossy())
+        .unwrap_or_default();
+    let under = |dir: &str| relative.components().any(|c| c.as_os_str() == dir);
+
+    METADATA_FILES.contains(&name.as_ref())
+        || extension == "iml"
+        || (under(".idea") && extension == "xml")
+        || (relative.starts_with(".vscode") && VSCODE_FILES.contains(&name.as_ref()))
+        || ((BUILD_FILES.contains(&name.as_ref()) || extension == "mk") && is_ignored())
+}
+
+/// Whether `line` names `path` itself, rather than a longer name starting
+/// with it
+fn names_path(line: &str, path: &Path) -> bool {
+    let needle = path.to_string_lossy().replace('\\', "/");
+    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+    line.match_indices(needle.as_str()).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + needle.len()..].chars().next();
+        // A relative path may follow a directory, but not be the end of a name
+        let starts_cleanly = path.is_absolute() || !before.is_some_and(is_name_char);
+        starts_cleanly && !after.is_some_and(is_name_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_repository, PlanOptions};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_names_path() {
+        let relative = Path::new("src/old_name");
+        assert!(names_path("  \"file\": \"src/old_name/a.c\",", relative));
+        assert!(names_path("include src/old_name", relative));
+        assert!(names_path(
+            "-I$PROJECT_DIR$/../src/old_name",
+            Path::new("old_name")
+        ));
+        assert!(!names_path("src/old_name_v2/a.c", relative));
+        assert!(!names_path("src/old_name.c", relative));
+        assert!(!names_path("mysrc/old_name", relative));
+        assert!(names_path(
+            "cd /repo/src/old_name",
+            Path::new("/repo/src/old_name")
+        ));
+    }
+
+    #[test]
+    fn test_find_stale_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::write(root.join(".gitignore"), "build/\n.idea/\n").unwrap();
+        fs::create_dir_all(root.join("src/old_name")).unwrap();
+        fs::write(root.join("src/old_name/main.c"), "int main() {}\n").unwrap();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(
+            root.join("build/compile_commands.json"),
+            format!(
+                "[{{\n  \"directory\": \"{}\",\n  \"file\": \"{}/src/old_name/main.c\"\n}}]\n",
+                root.display(),
+                root.display()
+            ),
+        )
+        .unwrap();
+        fs::write(
+            root.join("build/Makefile"),
+            "SRC = ../src/old_name/main.c\nOTHER = ../src/old_name_v2\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join(".idea")).unwrap();
+        fs::write(
+            root.join(".idea/workspace.xml"),
+            "<option value=\"$PROJECT_DIR$/src/old_name\" />\n",
+        )
+        .unwrap();
+        // Not ignored, so the plan covers it
+        fs::write(root.join("Makefile"), "SRC = src/old_name/main.c\n").unwrap();
+
+        let options = PlanOptions {
+            working_dir: Some(root.clone()),
+            ..PlanOptions::default()
+        };
+        let plan = scan_repository(&root, "old_name", "new_name", &options).unwrap();
+        let stale = find_stale_paths(&plan, &root, &[]);
+
+        let found: Vec<(String, u64)> = stale
+            .iter()
+            .map(|stale| {
+                let file = stale.file.strip_prefix(&root).unwrap();
+                (file.display().to_string(), stale.line)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (".idea/workspace.xml".to_string(), 1),
+                ("build/Makefile".to_string(), 1),
+                ("build/compile_commands.json".to_string(), 3),
+            ]
+        );
+        assert!(stale.iter().all(|stale| {
+            stale.old_path == Path::new("src/old_name")
+                && stale.new_path == Path::new("src/new_name")
+        }));
+    }
+}
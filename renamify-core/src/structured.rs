@@ -0,0 +1,438 @@
+//! Key and value awareness for YAML, JSON and TOML files.
+//!
+//! Structured files are parsed with span-aware parsers so that quoting,
+//! escaping and block scalars are handled by the parser rather than by
+//! guessing. Every key and scalar value is recorded with its byte range and
+//! its dotted path (`spec.template.metadata.name`), which lets a plan keep
+//! only matches in keys, in values, or under specific paths.
+
+use crate::language::Language;
+use saphyr_parser::{Event, Parser, Span};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::Path;
+use ts_rs::TS;
+
+/// Which side of a key/value pair a match is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum StructuredTarget {
+    Keys,
+    Values,
+}
+
+/// Restricts matches in YAML, JSON and TOML files to keys, values or paths.
+///
+/// Other files, and structured files that fail to parse, keep all their
+/// matches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct StructuredFilter {
+    /// Keep only matches in keys (`--only-keys`) or values (`--only-values`)
+    #[ts(optional)]
+    pub target: Option<StructuredTarget>,
+    /// Dotted paths the match must be at or under (`--yaml-path`). `*` matches
+    /// any single key, and sequence items are addressed by index.
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+impl StructuredFilter {
+    /// Whether a match in a key or value at `path` is kept
+    fn keeps(&self, target: StructuredTarget, path: &[String]) -> bool {
+        self.target.is_none_or(|wanted| wanted == target)
+            && (self.paths.is_empty()
+                || self.paths.iter().any(|pattern| path_matches(pattern, path)))
+    }
+}
+
+/// A path pattern matches its own path and everything below it
+fn path_matches(pattern: &str, path: &[String]) -> bool {
+    let segments: Vec<&str> = pattern.split('.').collect();
+    segments.len() <= path.len()
+        && segments
+            .iter()
+            .zip(path)
+            .all(|(segment, key)| *segment == "*" || segment == key)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        match Language::from_path(path)? {
+            // JSON is a subset of YAML, so one parser handles both
+            Language::Yaml | Language::Json => Some(Self::Yaml),
+            Language::Toml => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Region {
+    span: Range<usize>,
+    target: StructuredTarget,
+    path: Vec<String>,
+}
+
+/// A `key: value` pair whose value is a scalar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Dotted path of the entry, ending in its key
+    pub path: Vec<String>,
+    pub key: Range<usize>,
+    pub value: Range<usize>,
+}
+
+/// Byte ranges of the keys and scalar values of a structured file
+#[derive(Debug, Default)]
+pub struct StructuredRegions {
+    regions: Vec<Region>,
+}
+
+impl StructuredRegions {
+    /// Parse a YAML, JSON or TOML file. Returns None for other files and for
+    /// files that do not parse.
+    pub fn scan(path: &Path, content: &[u8]) -> Option<Self> {
+        let format = Format::from_path(path)?;
+        let text = std::str::from_utf8(content).ok()?;
+        let mut regions = match format {
+            Format::Yaml => scan_yaml(text)?,
+            Format::Toml => scan_toml(text)?,
+        };
+        regions.sort_by_key(|region| region.span.start);
+        Some(Self { regions })
+    }
+
+    /// Whether a match starting at `offset` is kept by the filter. Matches
+    /// outside every key and value, such as in comments, are dropped.
+    pub fn keeps(&self, filter: &StructuredFilter, offset: usize) -> bool {
+        let idx = self
+            .regions
+            .partition_point(|region| region.span.start <= offset);
+        idx.checked_sub(1)
+            .and_then(|idx| self.regions.get(idx))
+            .filter(|region| region.span.contains(&offset))
+            .is_some_and(|region| filter.keeps(region.target, &region.path))
+    }
+
+    /// Every key followed directly by a scalar value, in file order
+    pub fn entries(&self) -> Vec<Entry> {
+        self.regions
+            .windows(2)
+            .filter(|pair| {
+                pair[0].target == StructuredTarget::Keys
+                    && pair[1].target == StructuredTarget::Values
+                    && pair[0].path == pair[1].path
+            })
+            .map(|pair| Entry {
+                path: pair[0].path.clone(),
+                key: pair[0].span.clone(),
+                value: pair[1].span.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Where the YAML event walker is inside the document
+enum Frame {
+    Mapping {
+        path: Vec<String>,
+        /// The key whose value comes next, or None when a key comes next
+        key: Option<String>,
+    },
+    Sequence {
+        path: Vec<String>,
+        index: usize,
+    },
+}
+
+impl Frame {
+    /// What the next node is, and the path it lives at
+    fn next_node(&self) -> (StructuredTarget, Vec<String>) {
+        match self {
+            Self::Mapping { path, key: None } => (StructuredTarget::Keys, path.clone()),
+            Self::Mapping {
+                path,
+                key: Some(key),
+            } => (StructuredTarget::Values, child(path, key)),
+            Self::Sequence { path, index } => {
+                (StructuredTarget::Values, child(path, &index.to_string()))
+            },
+        }
+    }
+
+    /// Move past the current node, remembering `key` if it was a mapping key
+    fn advance(&mut self, key: Option<String>) {
+        match self {
+            Self::Mapping { key: current, .. } => {
+                *current = if current.is_some() {
+                    None
+                } else {
+                    Some(key.unwrap_or_default())
+                };
+            },
+            Self::Sequence { index, .. } => *index += 1,
+        }
+    }
+}
+
+fn child(path: &[String], key: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    path
+}
+
+fn scan_yaml(text: &str) -> Option<Vec<Region>> {
+    // Parser positions are character indices
+    let byte_offsets: Option<Vec<usize>> = (!text.is_ascii()).then(|| {
+        text.char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(text.len()))
+            .collect()
+    });
+    let to_bytes = |span: Span| -> Range<usize> {
+        let (start, end) = (span.start.index(), span.end.index());
+        match &byte_offsets {
+            Some(offsets) => {
+                offsets[start.min(offsets.len() - 1)]..offsets[end.min(offsets.len() - 1)]
+            },
+            None => start..end,
+        }
+    };
+
+    let mut regions = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    for event in Parser::new_from_str(text) {
+        let (event, span) = event.ok()?;
+        match event {
+            Event::DocumentStart(_) => stack.clear(),
+            Event::Scalar(value, ..) => {
+                let (target, path) = stack
+                    .last()
+                    .map_or_else(|| (StructuredTarget::Values, Vec::new()), Frame::next_node);
+                let path = match target {
+                    StructuredTarget::Keys => child(&path, &value),
+                    StructuredTarget::Values => path,
+                };
+                regions.push(Region {
+                    span: to_bytes(span),
+                    target,
+                    path,
+                });
+                if let Some(frame) = stack.last_mut() {
+                    frame.advance(Some(value.into_owned()));
+                }
+            },
+            Event::Alias(_) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.advance(None);
+                }
+            },
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                let path = stack
+                    .last()
+                    .map(|frame| frame.next_node().1)
+                    .unwrap_or_default();
+                stack.push(if matches!(event, Event::MappingStart(..)) {
+                    Frame::Mapping { path, key: None }
+                } else {
+                    Frame::Sequence { path, index: 0 }
+                });
+            },
+            Event::MappingEnd | Event::SequenceEnd => {
+                stack.pop();
+                if let Some(frame) = stack.last_mut() {
+                    frame.advance(None);
+                }
+            },
+            _ => {},
+        }
+    }
+    Some(regions)
+}
+
+fn scan_toml(text: &str) -> Option<Vec<Region>> {
+    let document = toml_edit::Document::parse(text).ok()?;
+    let mut regions = Vec::new();
+    walk_toml_table(document.as_table(), &[], &mut regions);
+    Some(regions)
+}
+
+fn walk_toml_table(table: &toml_edit::Table, path: &[String], regions: &mut Vec<Region>) {
+    for (name, _) in table {
+        let Some((key, item)) = table.get_key_value(name) else {
+            continue;
+        };
+        let path = child(path, name);
+        if let Some(span) = key.span() {
+            regions.push(Region {
+                span,
+                target: StructuredTarget::Keys,
+                path: path.clone(),
+            });
+        }
+        match item {
+            toml_edit::Item::Value(value) => walk_toml_value(value, &path, regions),
+            toml_edit::Item::Table(table) => walk_toml_table(table, &path, regions),
+            toml_edit::Item::ArrayOfTables(tables) => {
+                for (index, table) in tables.iter().enumerate() {
+                    walk_toml_table(table, &child(&path, &index.to_string()), regions);
+                }
+            },
+            toml_edit::Item::None => {},
+        }
+    }
+}
+
+fn walk_toml_value(value: &toml_edit::Value, path: &[String], regions: &mut Vec<Region>) {
+    match value {
+        toml_edit::Value::Array(array) => {
+            for (index, value) in array.iter().enumerate() {
+                walk_toml_value(value, &child(path, &index.to_string()), regions);
+            }
+        },
+        toml_edit::Value::InlineTable(table) => {
+            for (name, _) in table {
+                let Some((key, item)) = table.get_key_value(name) else {
+                    continue;
+                };
+                let path = child(path, name);
+                if let Some(span) = key.span() {
+                    regions.push(Region {
+                        span,
+                        target: StructuredTarget::Keys,
+                        path: path.clone(),
+                    });
+                }
+                if let Some(value) = item.as_value() {
+                    walk_toml_value(value, &path, regions);
+                }
+            }
+        },
+        scalar => {
+            if let Some(span) = scalar.span() {
+                regions.push(Region {
+                    span,
+                    target: StructuredTarget::Values,
+                    path: path.to_vec(),
+                });
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The target and path of every occurrence of `word`, None if outside
+    /// every key and value
+    fn lookup(file: &str, source: &str, word: &str) -> Vec<Option<(StructuredTarget, String)>> {
+        let regions = StructuredRegions::scan(Path::new(file), source.as_bytes()).unwrap();
+        source
+            .match_indices(word)
+            .map(|(offset, _)| {
+                let idx = regions
+                    .regions
+                    .partition_point(|region| region.span.start <= offset);
+                regions.regions[..idx]
+                    .last()
+                    .filter(|region| region.span.contains(&offset))
+                    .map(|region| (region.target, region.path.join(".")))
+            })
+            .collect()
+    }
+
+    fn key(path: &str) -> (StructuredTarget, String) {
+        (StructuredTarget::Keys, path.to_string())
+    }
+
+    fn value(path: &str) -> (StructuredTarget, String) {
+        (StructuredTarget::Values, path.to_string())
+    }
+
+    #[test]
+    fn test_yaml_keys_values_and_paths() {
+        let source = concat!(
+            "# old_app manifest\n",
+            "metadata:\n",
+            "  name: old_app\n",
+            "  labels: {old_app: \"old_app\"}\n",
+            "spec:\n",
+            "  containers:\n",
+            "    - name: 'old_app'\n",
+            "      args: [\"--old_app\"]\n",
+            "  old_app: |\n",
+            "    run old_app\n",
+        );
+        assert_eq!(
+            lookup("deploy.yaml", source, "old_app"),
+            vec![
+                None,
+                Some(value("metadata.name")),
+                Some(key("metadata.labels.old_app")),
+                Some(value("metadata.labels.old_app")),
+                Some(value("spec.containers.0.name")),
+                Some(value("spec.containers.0.args.0")),
+                Some(key("spec.old_app")),
+                Some(value("spec.old_app")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_and_toml() {
+        let json = "{\"old_app\": {\"image\": \"old_app:1\"}, \"tags\": [\"old_app\"]}";
+        assert_eq!(
+            lookup("package.json", json, "old_app"),
+            vec![
+                Some(key("old_app")),
+                Some(value("old_app.image")),
+                Some(value("tags.0"))
+            ]
+        );
+
+        let toml = concat!(
+            "[old_app]\n",
+            "name = \"old_app\" # old_app\n",
+            "deps = { old_app = [\"old_app\"] }\n",
+        );
+        assert_eq!(
+            lookup("Cargo.toml", toml, "old_app"),
+            vec![
+                Some(key("old_app")),
+                Some(value("old_app.name")),
+                None,
+                Some(key("old_app.deps.old_app")),
+                Some(value("old_app.deps.old_app.0")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_paths_and_targets() {
+        let path: Vec<String> = ["spec", "containers", "0", "name"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let filter = |target, paths: &[&str]| StructuredFilter {
+            target,
+            paths: paths.iter().map(ToString::to_string).collect(),
+        };
+
+        assert!(filter(None, &["spec.containers"]).keeps(StructuredTarget::Values, &path));
+        assert!(filter(None, &["spec.*.*.name"]).keeps(StructuredTarget::Keys, &path));
+        assert!(!filter(None, &["spec.template"]).keeps(StructuredTarget::Values, &path));
+        assert!(!filter(Some(StructuredTarget::Keys), &[]).keeps(StructuredTarget::Values, &path));
+        assert!(StructuredRegions::scan(Path::new("broken.json"), b"{\"a\": ").is_none());
+        assert!(StructuredRegions::scan(Path::new("main.rs"), b"fn main() {}").is_none());
+    }
+}
@@ -0,0 +1,136 @@
+//! Submodules and linked worktrees inside the scanned tree.
+//!
+//! Both are directories with their own `.git` entry. A linked worktree is
+//! another checkout of the same repository and is never scanned. Submodules,
+//! and any other repository nested in the tree, are skipped unless
+//! `--include-submodules` is given; their changes are then committed in their
+//! own repository.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory below a scan root that is the top of another checkout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NestedRepo {
+    /// A submodule, or any other repository nested in the tree
+    Submodule,
+    /// A linked worktree created with `git worktree add`
+    Worktree,
+}
+
+/// What kind of nested checkout `dir` is the top of, if any
+pub fn nested_repo(dir: &Path) -> Option<NestedRepo> {
+    let git = dir.join(".git");
+    let metadata = fs::symlink_metadata(&git).ok()?;
+    if metadata.is_dir() {
+        return Some(NestedRepo::Submodule);
+    }
+
+    // A `.git` file points at the real git directory: `.git/modules/<name>`
+    // for a submodule, `.git/worktrees/<name>` for a linked worktree
+    let content = fs::read_to_string(&git).ok()?;
+    let gitdir = content
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))?
+        .trim();
+    let in_worktrees = Path::new(gitdir)
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == "worktrees");
+    Some(if in_worktrees {
+        NestedRepo::Worktree
+    } else {
+        NestedRepo::Submodule
+    })
+}
+
+/// Whether the walker should descend into `dir`, a directory below a scan root
+pub fn should_walk(dir: &Path, include_submodules: bool) -> bool {
+    match nested_repo(dir) {
+        None => true,
+        Some(NestedRepo::Submodule) => include_submodules,
+        Some(NestedRepo::Worktree) => false,
+    }
+}
+
+/// The top of the checkout that contains `path`: its nearest ancestor with a
+/// `.git` entry
+pub fn repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            dir.join(".git").exists()
+        })
+        .map(Path::to_path_buf)
+}
+
+/// The nested checkouts that contain any of `paths`, deepest first, leaving
+/// out the one `working_dir` is in
+pub fn nested_repos<'a>(
+    working_dir: &Path,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<PathBuf> {
+    let top = repo_root(working_dir).and_then(|root| root.canonicalize().ok());
+    let mut repos: Vec<PathBuf> = paths
+        .into_iter()
+        .filter_map(repo_root)
+        .filter_map(|root| root.canonicalize().ok())
+        .filter(|root| Some(root) != top.as_ref())
+        .collect();
+    repos.sort();
+    repos.dedup();
+    repos.sort_by_key(|root| std::cmp::Reverse(root.components().count()));
+    repos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nested_repo() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git/modules/lib")).unwrap();
+        for name in ["lib", "vendor", "feature", "src"] {
+            fs::create_dir_all(root.join(name)).unwrap();
+        }
+        fs::write(root.join("lib/.git"), "gitdir: ../.git/modules/lib\n").unwrap();
+        fs::create_dir_all(root.join("vendor/.git")).unwrap();
+        fs::write(
+            root.join("feature/.git"),
+            "gitdir: /work/repo/.git/worktrees/feature\n",
+        )
+        .unwrap();
+
+        assert_eq!(nested_repo(&root.join("lib")), Some(NestedRepo::Submodule));
+        assert_eq!(
+            nested_repo(&root.join("vendor")),
+            Some(NestedRepo::Submodule)
+        );
+        assert_eq!(
+            nested_repo(&root.join("feature")),
+            Some(NestedRepo::Worktree)
+        );
+        assert_eq!(nested_repo(&root.join("src")), None);
+
+        assert!(!should_walk(&root.join("lib"), false));
+        assert!(should_walk(&root.join("lib"), true));
+        assert!(!should_walk(&root.join("feature"), true));
+        assert!(should_walk(&root.join("src"), false));
+
+        assert_eq!(
+            repo_root(&root.join("lib/src/main.rs")),
+            Some(root.join("lib"))
+        );
+        assert_eq!(
+            repo_root(&root.join("src/main.rs")),
+            Some(root.to_path_buf())
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Lines a plan leaves alone, marked in the code or named on the command line.
+//!
+//! Inline markers live in comments, so the opt-out stays with the code across
+//! plans:
+//!
+//! - `renamify:ignore-line` skips the line it is on
+//! - `renamify:ignore-next-line` skips the line after it
+//! - `renamify:ignore-start` and `renamify:ignore-end` skip every line from
+//!   one marker to the other. A start without an end runs to the end of the
+//!   file.
+//!
+//! `--exclude-lines-in-file PATH:N` (or `PATH:N-M`) skips lines of one file
+//! without editing it.
+
+use crate::scanner::{PlanOptions, SkipReason};
+use bstr::ByteSlice;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+const MARKER: &[u8] = b"renamify:ignore-";
+
+/// Lines of one file skipped by inline markers, numbered from 1
+#[derive(Debug, Default)]
+pub struct InlineIgnores {
+    ranges: Vec<RangeInclusive<usize>>,
+}
+
+impl InlineIgnores {
+    /// Find the markers in `content`, or `None` when it has none
+    pub fn scan(content: &[u8]) -> Option<Self> {
+        content.find(MARKER)?;
+
+        let mut ranges = Vec::new();
+        let mut block_start = None;
+        let mut last_line = 0;
+        for (index, line) in content.lines().enumerate() {
+            let number = index + 1;
+            last_line = number;
+            for marker in markers(line) {
+                match marker {
+                    b"line" => ranges.push(number..=number),
+                    b"next-line" => ranges.push(number + 1..=number + 1),
+                    b"start" => {
+                        block_start.get_or_insert(number);
+                    },
+                    b"end" => {
+                        if let Some(start) = block_start.take() {
+                            ranges.push(start..=number);
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        }
+        if let Some(start) = block_start {
+            ranges.push(start..=last_line);
+        }
+
+        Some(Self { ranges })
+    }
+
+    /// Whether line `line` (from 1) is skipped
+    pub fn contains(&self, line: usize) -> bool {
+        self.ranges.iter().any(|range| range.contains(&line))
+    }
+}
+
+/// The words after each `renamify:ignore-` on a line
+fn markers(line: &[u8]) -> impl Iterator<Item = &[u8]> {
+    line.find_iter(MARKER).map(move |position| {
+        let rest = &line[position + MARKER.len()..];
+        let len = rest
+            .iter()
+            .position(|byte| !(byte.is_ascii_lowercase() || *byte == b'-'))
+            .unwrap_or(rest.len());
+        &rest[..len]
+    })
+}
+
+/// Lines of one file left alone with `--exclude-lines-in-file PATH:N[-M]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct FileLines {
+    /// The file, relative to the directory the plan is made in
+    #[ts(type = "string")]
+    pub file: PathBuf,
+    /// First line skipped, from 1
+    #[ts(type = "number")]
+    pub start: u64,
+    /// Last line skipped
+    #[ts(type = "number")]
+    pub end: u64,
+}
+
+impl FileLines {
+    /// Whether line `line` (from 1) is skipped
+    pub fn contains(&self, line: u64) -> bool {
+        (self.start..=self.end).contains(&line)
+    }
+
+    /// The ranges in `ranges` that name `path`, with relative paths resolved
+    /// against `working_dir` or the current directory
+    pub fn for_file<'a>(
+        ranges: &'a [Self],
+        path: &Path,
+        working_dir: Option<&Path>,
+    ) -> Vec<&'a Self> {
+        if ranges.is_empty() {
+            return Vec::new();
+        }
+        let base = working_dir.map_or_else(
+            || std::env::current_dir().unwrap_or_default(),
+            Path::to_path_buf,
+        );
+        let canonical = path.canonicalize().ok();
+        ranges
+            .iter()
+            .filter(|range| {
+                let target = base.join(&range.file);
+                target == path
+                    || canonical.as_ref().is_some_and(|canonical| {
+                        target.canonicalize().ok().as_ref() == Some(canonical)
+                    })
+            })
+            .collect()
+    }
+}
+
+impl std::str::FromStr for FileLines {
+    type Err = String;
+
+    /// Parse `PATH:N` or `PATH:N-M` as accepted by `--exclude-lines-in-file`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid line range '{}', expected PATH:N or PATH:N-M", s);
+        let (file, lines) = s.rsplit_once(':').ok_or_else(invalid)?;
+        let (start, end) = lines.split_once('-').unwrap_or((lines, lines));
+        let start: u64 = start.parse().map_err(|_| invalid())?;
+        let end: u64 = end.parse().map_err(|_| invalid())?;
+        if file.is_empty() || start == 0 || end < start {
+            return Err(invalid());
+        }
+        Ok(Self {
+            file: PathBuf::from(file),
+            start,
+            end,
+        })
+    }
+}
+
+impl std::fmt::Display for FileLines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}:{}", self.file.display(), self.start)
+        } else {
+            write!(f, "{}:{}-{}", self.file.display(), self.start, self.end)
+        }
+    }
+}
+
+/// The lines of one file skipped by inline markers or `--exclude-lines-in-file`
+pub struct SuppressedLines<'a> {
+    inline: Option<InlineIgnores>,
+    excluded: Vec<&'a FileLines>,
+}
+
+impl<'a> SuppressedLines<'a> {
+    pub fn new(content: &[u8], path: &Path, options: &'a PlanOptions) -> Self {
+        Self {
+            inline: InlineIgnores::scan(content),
+            excluded: FileLines::for_file(
+                &options.exclude_lines_in_file,
+                path,
+                options.working_dir.as_deref(),
+            ),
+        }
+    }
+
+    /// Why line `line` (from 1) is skipped, with markers in the code first
+    pub fn reason(&self, line: usize) -> Option<SkipReason> {
+        if self
+            .inline
+            .as_ref()
+            .is_some_and(|ignores| ignores.contains(line))
+        {
+            Some(SkipReason::InlineIgnore)
+        } else if self
+            .excluded
+            .iter()
+            .any(|range| range.contains(line as u64))
+        {
+            Some(SkipReason::ExcludeLinesInFile)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_markers() {
+        let content = b"a // renamify:ignore-line\n\
+            b # renamify:ignore-next-line\n\
+            c\n\
+            d\n\
+            /* renamify:ignore-start */\n\
+            e\n\
+            /* renamify:ignore-end */\n\
+            f\n\
+            -- renamify:ignore-start\n\
+            g\n";
+        let ignores = InlineIgnores::scan(content).unwrap();
+        let skipped: Vec<usize> = (1..=10).filter(|line| ignores.contains(*line)).collect();
+        assert_eq!(skipped, vec![1, 3, 5, 6, 7, 9, 10]);
+
+        assert!(InlineIgnores::scan(b"no markers here\n").is_none());
+        // Unknown markers are not suppressions
+        let ignores = InlineIgnores::scan(b"x renamify:ignore-lines\ny\n").unwrap();
+        assert!(!ignores.contains(1));
+    }
+
+    #[test]
+    fn test_parse_file_lines() {
+        let range: FileLines = "src/a.rs:10-20".parse().unwrap();
+        assert_eq!(range.file, PathBuf::from("src/a.rs"));
+        assert!(range.contains(10) && range.contains(20) && !range.contains(21));
+        assert_eq!(range.to_string(), "src/a.rs:10-20");
+
+        let single: FileLines = "C:/repo/a.rs:7".parse().unwrap();
+        assert_eq!(single.file, PathBuf::from("C:/repo/a.rs"));
+        assert_eq!(single.to_string(), "C:/repo/a.rs:7");
+
+        for invalid in ["a.rs", "a.rs:0", "a.rs:5-2", ":3", "a.rs:x"] {
+            assert!(invalid.parse::<FileLines>().is_err(), "{invalid}");
+        }
+    }
+}
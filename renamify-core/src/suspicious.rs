@@ -0,0 +1,311 @@
+//! Renames that would move a path into an ignored or protected one.
+//!
+//! The walk never goes into ignored directories such as build output, so a
+//! rename ending up inside one, like `src/old_name` becoming `target/new_name`
+//! because `src` matched the search term too, is almost always a mistake.
+//! Plans hold such renames back in [`Plan::suspicious_renames`] until they are
+//! confirmed, as with near misses.
+
+use crate::apply::{adjust_rename, order_renames};
+use crate::scanner::{Plan, PlanOptions, Rename, RenameKind};
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Paths renames never move into unless confirmed, on top of the `protected`
+/// patterns in the config. Patterns follow `.gitignore` rules.
+pub const BUILTIN_PROTECTED: &[&str] = &[
+    ".git",
+    ".hg",
+    ".jj",
+    ".renamify",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "vendor",
+    "__pycache__",
+    ".venv",
+];
+
+/// A rename held back from a plan because it moves a path into an ignored or
+/// protected one
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct SuspiciousRename {
+    pub rename: Rename,
+    /// Where the path ends up once the directories above it are renamed too
+    #[ts(type = "string")]
+    pub destination: PathBuf,
+    /// Why the destination is suspicious, e.g. `ignored by .gitignore`
+    pub reason: String,
+}
+
+/// Ignore files looked at for an unrestricted level, as in the walk
+const fn ignore_files(level: u8) -> &'static [&'static str] {
+    match level {
+        0 => &[".gitignore", ".ignore", ".rnignore"],
+        1 => &[".ignore", ".rnignore"],
+        _ => &[],
+    }
+}
+
+/// The built-in protected paths plus the `protected` patterns from the
+/// config, relative to `root`
+pub(crate) fn protected_matcher(root: &Path, protected: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in BUILTIN_PROTECTED
+        .iter()
+        .copied()
+        .chain(protected.iter().map(String::as_str))
+    {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid protected pattern '{pattern}'"))?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Whether paths under one root are ignored or protected
+pub(crate) struct Rules {
+    root: PathBuf,
+    protected: Gitignore,
+    ignore_files: &'static [&'static str],
+    /// Parsed ignore files by path, `None` for ones that don't exist
+    parsed: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl Rules {
+    pub(crate) fn new(root: &Path, protected: &[String], level: u8) -> Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            protected: protected_matcher(root, protected)?,
+            ignore_files: ignore_files(level),
+            parsed: HashMap::new(),
+        })
+    }
+
+    /// Why `path` is ignored or protected, if it is
+    pub(crate) fn reason(&mut self, path: &Path, is_dir: bool) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        if let Match::Ignore(glob) = self.protected.matched_path_or_any_parents(relative, is_dir) {
+            return Some(format!(
+                "matches the protected pattern `{}`",
+                glob.original()
+            ));
+        }
+
+        // The deepest ignore file with a rule for the path decides
+        let dirs: Vec<PathBuf> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in dirs {
+            for name in self.ignore_files {
+                let file = dir.join(name);
+                let parsed = self.parsed.entry(file.clone()).or_insert_with(|| {
+                    file.is_file().then(|| {
+                        let mut builder = GitignoreBuilder::new(&dir);
+                        builder.add(&file);
+                        builder.build().unwrap_or_else(|_| Gitignore::empty())
+                    })
+                });
+                match parsed
+                    .as_ref()
+                    .map(|rules| rules.matched_path_or_any_parents(path, is_dir))
+                {
+                    Some(Match::Ignore(_)) => {
+                        let file = file.strip_prefix(&self.root).unwrap_or(&file);
+                        return Some(format!("ignored by {}", file.display()));
+                    },
+                    Some(Match::Whitelist(_)) => return None,
+                    _ => {},
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Move the renames of `plan` that would put a path inside an ignored or
+/// protected one, but don't start there, from `paths` to `suspicious_renames`.
+///
+/// `protected` are the patterns from the config. Renames that can't be
+/// ordered are left for apply to report.
+pub fn hold_back_suspicious_renames(
+    plan: &mut Plan,
+    roots: &[PathBuf],
+    options: &PlanOptions,
+    protected: &[String],
+) -> Result<()> {
+    let Ok(ordered) = order_renames(&plan.paths) else {
+        return Ok(());
+    };
+    let mut destinations: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for rename in &ordered {
+        let (_, to) = adjust_rename(rename, &done);
+        destinations.insert(rename.path.clone(), to.clone());
+        done.push((rename.path.clone(), to));
+    }
+
+    let level = if options.respect_gitignore {
+        options.unrestricted_level
+    } else {
+        options.unrestricted_level.max(1)
+    };
+    let mut rules = roots
+        .iter()
+        .map(|root| Rules::new(root, protected, level))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut kept = Vec::with_capacity(plan.paths.len());
+    for rename in std::mem::take(&mut plan.paths) {
+        let is_dir = rename.kind == RenameKind::Dir;
+        let destination = destinations
+            .get(&rename.path)
+            .cloned()
+            .unwrap_or_else(|| rename.new_path.clone());
+        let reason = rules
+            .iter_mut()
+            .find(|rules| rename.path.starts_with(&rules.root))
+            .and_then(|rules| match rules.reason(&rename.path, is_dir) {
+                // Already inside, so nothing is moving in
+                Some(_) => None,
+                None => rules.reason(&destination, is_dir),
+            });
+        match reason {
+            Some(reason) => plan.suspicious_renames.push(SuspiciousRename {
+                rename,
+                destination,
+                reason,
+            }),
+            None => kept.push(rename),
+        }
+    }
+    plan.paths = kept;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn rename(root: &Path, from: &str, to: &str, kind: RenameKind) -> Rename {
+        Rename {
+            path: root.join(from),
+            new_path: root.join(to),
+            kind,
+            coercion_applied: None,
+        }
+    }
+
+    #[test]
+    fn test_hold_back_suspicious_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join(".gitignore"), "/generated/\n*.log\n!keep.log\n").unwrap();
+
+        let mut plan = crate::scanner::Plan {
+            id: "test".to_string(),
+            created_at: "0".to_string(),
+            search: "src".to_string(),
+            replace: "target".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![
+                // Moved into target/ by its parent's rename
+                rename(root, "src/old_name", "src/new_name", RenameKind::Dir),
+                rename(root, "src", "target", RenameKind::Dir),
+                rename(root, "lib/old.rs", "lib/new.rs", RenameKind::File),
+                rename(root, "docs/old.md", "docs/old.log", RenameKind::File),
+                rename(root, "docs/old.txt", "docs/keep.log", RenameKind::File),
+                rename(root, "gen", "generated", RenameKind::Dir),
+            ],
+            stats: crate::scanner::Stats::default(),
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        hold_back_suspicious_renames(
+            &mut plan,
+            &[root.to_path_buf()],
+            &PlanOptions::default(),
+            &[],
+        )
+        .unwrap();
+
+        let kept: Vec<&Path> = plan.paths.iter().map(|r| r.path.as_path()).collect();
+        assert_eq!(
+            kept,
+            [
+                root.join("lib/old.rs").as_path(),
+                &root.join("docs/old.txt")
+            ]
+        );
+        let held: Vec<(PathBuf, &str)> = plan
+            .suspicious_renames
+            .iter()
+            .map(|s| (s.destination.clone(), s.reason.as_str()))
+            .collect();
+        assert_eq!(
+            held,
+            [
+                (
+                    root.join("target/new_name"),
+                    "matches the protected pattern `target`"
+                ),
+                (
+                    root.join("target"),
+                    "matches the protected pattern `target`"
+                ),
+                (root.join("docs/old.log"), "ignored by .gitignore"),
+                (root.join("generated"), "ignored by .gitignore"),
+            ]
+        );
+
+        // Config patterns add to the built-in ones, and -uu ignores .gitignore
+        let mut plan = crate::scanner::Plan {
+            paths: vec![
+                rename(root, "old.log", "new.log", RenameKind::File),
+                rename(root, "old", "fixtures/new", RenameKind::Dir),
+            ],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            ..plan
+        };
+        let options = PlanOptions {
+            unrestricted_level: 2,
+            ..PlanOptions::default()
+        };
+        hold_back_suspicious_renames(
+            &mut plan,
+            &[root.to_path_buf()],
+            &options,
+            &["fixtures/".to_string()],
+        )
+        .unwrap();
+        assert_eq!(plan.paths.len(), 1);
+        assert_eq!(
+            plan.suspicious_renames[0].reason,
+            "matches the protected pattern `fixtures/`"
+        );
+    }
+}
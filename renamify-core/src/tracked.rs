@@ -0,0 +1,137 @@
+//! Files git tracks even though a `.gitignore` rule matches them.
+//!
+//! The walk respects `.gitignore`, which skips files that were added before
+//! the rule or with `git add -f`, such as a vendored `dist/` bundle. They are
+//! part of the repository all the same, so the scanner adds them back from
+//! `git ls-files` unless [`WalkOptions::include_tracked`] is turned off.
+//! `.ignore` and `.rnignore` still exclude them, as does `-u`, which already
+//! walks past `.gitignore`.
+//!
+//! [`WalkOptions::include_tracked`]: crate::scanner::WalkOptions::include_tracked
+
+use crate::scanner::PlanOptions;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::Match;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Ignore files that keep applying to tracked files
+const IGNORE_FILES: &[&str] = &[".ignore", ".rnignore"];
+
+/// Tracked files under `root` that git's ignore rules keep the walk out of,
+/// with their depth below it.
+///
+/// The rules are `.gitignore`, `.git/info/exclude` and the global excludes
+/// file. Empty outside a git repository or when `options` don't call for them.
+pub fn ignored_tracked_files(root: &Path, options: &PlanOptions) -> Vec<(PathBuf, usize)> {
+    if !options.walk.include_tracked
+        || !options.respect_gitignore
+        || options.unrestricted_level > 0
+        || !root.is_dir()
+    {
+        return Vec::new();
+    }
+    let output = Command::new("git")
+        .current_dir(root)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--ignored",
+            "--exclude-standard",
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    let Some(output) = output else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| (root.join(path), Path::new(path).components().count()))
+        .filter(|(path, depth)| {
+            options.walk.max_depth.is_none_or(|max| *depth <= max)
+                && std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_file())
+                && !excluded(root, path)
+        })
+        .collect()
+}
+
+/// Whether an `.ignore` or `.rnignore` file between `root` and `path` excludes
+/// it, the deepest one deciding
+fn excluded(root: &Path, path: &Path) -> bool {
+    for dir in path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+    {
+        for name in IGNORE_FILES {
+            let file = dir.join(name);
+            if !file.is_file() {
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new(dir);
+            builder.add(&file);
+            let Ok(rules) = builder.build() else {
+                continue;
+            };
+            match rules.matched_path_or_any_parents(path, false) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {},
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::WalkOptions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn test_ignored_tracked_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        if !git(root, &["init", "-q"]) {
+            return;
+        }
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::create_dir_all(root.join("generated")).unwrap();
+        fs::write(root.join(".gitignore"), "dist/\ngenerated/\n").unwrap();
+        fs::write(root.join(".rnignore"), "generated/\n").unwrap();
+        fs::write(root.join("dist/old_name.js"), "old_name").unwrap();
+        fs::write(root.join("dist/untracked.js"), "old_name").unwrap();
+        fs::write(root.join("generated/old_name.rs"), "old_name").unwrap();
+        assert!(git(
+            root,
+            &["add", "-f", "dist/old_name.js", "generated/old_name.rs"]
+        ));
+
+        let found = ignored_tracked_files(root, &PlanOptions::default());
+        assert_eq!(found, [(root.join("dist/old_name.js"), 2)]);
+
+        let off = PlanOptions {
+            walk: WalkOptions {
+                include_tracked: false,
+                ..WalkOptions::default()
+            },
+            ..PlanOptions::default()
+        };
+        assert!(ignored_tracked_files(root, &off).is_empty());
+    }
+}
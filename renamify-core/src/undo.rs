@@ -1,12 +1,88 @@
-use crate::apply::{apply_plan, calculate_checksum, ApplyOptions};
-use crate::history::History;
+use crate::apply::{apply_plan, calculate_checksum, renamed_location, staging_path, ApplyOptions};
+use crate::backup::GitBlobs;
+use crate::error::RenamifyError;
+use crate::history::{History, HistoryEntry};
 use crate::scanner::Plan;
 use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write as _};
 use std::fs;
 use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// How a file changed after the apply being undone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalChange {
+    /// Its content no longer matches the checksum taken after the apply
+    Modified,
+    /// It no longer exists
+    Deleted,
+}
+
+impl fmt::Display for LocalChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+        })
+    }
+}
+
+/// A file that changed after the apply, whose newer edits undo would overwrite
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct UndoConflict {
+    pub path: PathBuf,
+    pub change: LocalChange,
+}
+
+/// The files of `entry` that changed since it was applied, found by comparing
+/// them with the checksums recorded in the history entry
+pub fn undo_conflicts(entry: &HistoryEntry) -> Result<Vec<UndoConflict>> {
+    let mut conflicts = Vec::new();
+    for (path, checksum) in &entry.affected_files {
+        let change = if !path.is_file() {
+            LocalChange::Deleted
+        } else if calculate_checksum(path)? != *checksum {
+            LocalChange::Modified
+        } else {
+            continue;
+        };
+        conflicts.push(UndoConflict {
+            path: path.clone(),
+            change,
+        });
+    }
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(conflicts)
+}
+
+fn undo_conflict_error(id: &str, conflicts: &[UndoConflict]) -> anyhow::Error {
+    let count = match conflicts.len() {
+        1 => "1 file".to_string(),
+        count => format!("{count} files"),
+    };
+    let mut message = format!(
+        "{count} changed since '{id}' was applied, so undo would overwrite newer edits. \
+         Nothing was changed; use --force-undo to undo anyway:"
+    );
+    for conflict in conflicts {
+        write!(
+            message,
+            "\n  {}: {}",
+            conflict.change,
+            conflict.path.display()
+        )
+        .unwrap();
+    }
+    RenamifyError::Conflict(message).into()
+}
 
 /// Apply a single patch to a file
 fn apply_single_patch(file_path: &Path, patch_content: &str) -> Result<()> {
@@ -112,19 +188,62 @@ fn apply_single_patch(file_path: &Path, patch_content: &str) -> Result<()> {
     Ok(())
 }
 
-/// Undo a previously applied renaming
-pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
+/// Move each renamed path (`to`) that still exists back to its original path
+/// (`from`). When the original path is still taken by another renamed path, as
+/// when undoing a swap, the move goes through a temporary name and finishes
+/// once that path has moved back too.
+fn reverse_renames(
+    mappings: &[(PathBuf, PathBuf)],
+    mut move_back: impl FnMut(&Path, &Path) -> Result<()>,
+) -> Result<()> {
+    let mut occupied: HashSet<&Path> = mappings.iter().map(|(_, to)| to.as_path()).collect();
+    let mut waiting: Vec<(PathBuf, &Path)> = Vec::new();
+    for (from, to) in mappings {
+        occupied.remove(to.as_path());
+        if to.exists() {
+            if occupied.contains(from.as_path()) {
+                let temp = staging_path(to);
+                fs::rename(to, &temp)?;
+                waiting.push((temp, from));
+            } else {
+                move_back(to, from)?;
+            }
+        }
+
+        let (ready, still_waiting): (Vec<_>, Vec<_>) = waiting
+            .into_iter()
+            .partition(|(_, original)| *original == to.as_path());
+        waiting = still_waiting;
+        for (temp, original) in ready {
+            fs::rename(&temp, original)?;
+        }
+    }
+    for (temp, original) in waiting {
+        fs::rename(&temp, original)?;
+    }
+    Ok(())
+}
+
+/// Undo a previously applied renaming.
+///
+/// Files changed since the apply are only overwritten with `force`; those it
+/// overwrote are returned.
+pub fn undo_renaming(id: &str, renamify_dir: &Path, force: bool) -> Result<Vec<UndoConflict>> {
     let mut history = History::load(renamify_dir)?;
 
     // Find the entry to undo
     let entry = history
         .find_entry(id)
-        .ok_or_else(|| anyhow!("History entry '{}' not found", id))?
+        .ok_or_else(|| RenamifyError::NotFound(format!("History entry '{}' not found", id)))?
         .clone();
 
     // Check if this was already reverted
     if entry.revert_of.is_some() {
-        return Err(anyhow!("Entry '{}' is already a revert operation", id));
+        return Err(RenamifyError::InvalidInput(format!(
+            "Entry '{}' is already a revert operation",
+            id
+        ))
+        .into());
     }
 
     // Check if any later entry was already reverted
@@ -134,28 +253,46 @@ pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
         .any(|e| e.revert_of.as_ref() == Some(&entry.id));
 
     if has_later_revert {
-        return Err(anyhow!("Entry '{}' has already been reverted", id));
+        return Err(RenamifyError::InvalidInput(format!(
+            "Entry '{}' has already been reverted",
+            id
+        ))
+        .into());
+    }
+
+    // Refuse to clobber edits made since the apply
+    let conflicts = undo_conflicts(&entry)?;
+    if !conflicts.is_empty() && !force {
+        return Err(undo_conflict_error(id, &conflicts));
     }
 
     // Load the plan to get patch information
     let plan_path = renamify_dir.join("plans").join(format!("{}.json", id));
     if !plan_path.exists() {
-        return Err(anyhow!(
+        return Err(RenamifyError::NotFound(format!(
             "Plan file not found for entry '{}'. Cannot undo without plan.",
             id
-        ));
+        ))
+        .into());
     }
     let plan_json = fs::read_to_string(&plan_path)?;
     let plan: Plan = serde_json::from_str(&plan_json)?;
 
-    // Check for individual reverse patches directory
+    // Content edits are restored from reverse patches (--backup copy) or git
+    // blobs (--backup git-stash). With --backup none only renames can be undone.
     let reverse_patches_dir = entry.backups_path.join("reverse_patches");
-    if !reverse_patches_dir.exists() {
+    let git_blobs = GitBlobs::read(&entry.backups_path)?;
+    if !reverse_patches_dir.exists() && git_blobs.is_none() && !plan.matches.is_empty() {
         return Err(anyhow!(
-            "No reverse patches found for entry '{}'. Cannot undo.",
+            "No backups found for entry '{}', so its content edits can't be undone. \
+             If it was applied with --backup none, restore them with git.",
             id
         ));
     }
+    // Refuse before touching anything when git has pruned a blob
+    if let Some(git_blobs) = &git_blobs {
+        git_blobs.check_available()?;
+    }
     // STEP 1: Reverse renames first (new locations back to old)
     // Process renames in reverse order, deepest paths first
     // Use the renames from the plan, not from history
@@ -175,11 +312,7 @@ pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
         b_depth.cmp(&a_depth)
     });
 
-    for (from, to) in &dir_mappings {
-        if to.exists() {
-            fs::rename(to, from)?;
-        }
-    }
+    reverse_renames(&dir_mappings, |to, from| Ok(fs::rename(to, from)?))?;
 
     // Now handle file renames, adjusting paths if they were inside renamed directories
     let mut file_renames = Vec::new();
@@ -215,29 +348,31 @@ pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
         b_depth.cmp(&a_depth)
     });
 
-    for (from, to) in &file_renames {
-        if to.exists() {
-            // Handle case-only renames on case-insensitive filesystems
-            let case_only = from.to_string_lossy().to_lowercase()
-                == to.to_string_lossy().to_lowercase()
-                && from != to;
-
-            if case_only
-                && crate::rename::detect_case_insensitive_fs(
-                    to.parent().unwrap_or_else(|| Path::new(".")),
-                )
-            {
-                // Two-step rename for case-only changes
-                let temp_name = to.with_extension(format!("{}.renamify.tmp", std::process::id()));
-                fs::rename(to, &temp_name)?;
-                fs::rename(&temp_name, from)?;
-            } else {
-                fs::rename(to, from)?;
-            }
+    reverse_renames(&file_renames, |to, from| {
+        // Handle case-only renames on case-insensitive filesystems
+        let case_only = from.to_string_lossy().to_lowercase()
+            == to.to_string_lossy().to_lowercase()
+            && from != to;
+
+        if case_only
+            && crate::rename::detect_case_insensitive_fs(
+                to.parent().unwrap_or_else(|| Path::new(".")),
+            )
+        {
+            // Two-step rename for case-only changes
+            let temp_name = to.with_extension(format!("{}.renamify.tmp", std::process::id()));
+            fs::rename(to, &temp_name)?;
+            fs::rename(&temp_name, from)?;
+        } else {
+            fs::rename(to, from)?;
         }
-    }
+        Ok(())
+    })?;
 
-    // STEP 2: Apply individual reverse patches
+    // STEP 2: Restore the original content, from git blobs or reverse patches
+    if let Some(git_blobs) = &git_blobs {
+        git_blobs.restore()?;
+    }
     // Group matches by file to apply patches
     let mut patches_by_file: HashMap<PathBuf, String> = HashMap::new();
     for hunk in &plan.matches {
@@ -255,6 +390,10 @@ pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
     // Apply all patches
     let mut failed_patches = Vec::new();
     for (file_path, patch_content) in patches_by_file {
+        // Files deleted since the apply stay deleted with --force-undo
+        if force && !file_path.exists() {
+            continue;
+        }
         if let Err(e) = apply_single_patch(&file_path, &patch_content) {
             eprintln!(
                 "  ERROR: Failed to apply patch to {}: {}",
@@ -309,31 +448,23 @@ pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
         return Err(anyhow!("Failed to apply {} patches", failed_patches.len()));
     }
 
-    // Calculate checksums of affected files and collect reversed renames
-    let mut affected_files = HashMap::new();
-    let mut reversed_renames = Vec::new();
+    // Collect reversed renames from the original entry
+    let reversed_renames: Vec<(PathBuf, PathBuf)> = entry
+        .renames
+        .iter()
+        .map(|(from, to)| (to.clone(), from.clone()))
+        .collect();
 
-    // Process all original affected files to calculate new checksums
+    // Calculate checksums of affected files, now back at their original location
+    let mut affected_files = HashMap::new();
     for path in entry.affected_files.keys() {
-        // The file should now be at its original location (before the renaming)
-        // Check if it was renamed and is now back at original location
-        let original_path = entry
-            .renames
-            .iter()
-            .find(|(_, to)| to == path)
-            .map_or(path, |(from, _)| from);
-
-        if original_path.exists() && original_path.is_file() {
-            let checksum = calculate_checksum(original_path)?;
-            affected_files.insert(original_path.clone(), checksum);
+        let original_path = renamed_location(path, &reversed_renames);
+        if original_path.is_file() {
+            let checksum = calculate_checksum(&original_path)?;
+            affected_files.insert(original_path, checksum);
         }
     }
 
-    // Collect reversed renames from the original entry
-    for (from, to) in &entry.renames {
-        reversed_renames.push((to.clone(), from.clone()));
-    }
-
     // Create a revert history entry
     let revert_entry = crate::history::HistoryEntry {
         id: format!("revert-{}-{}", entry.id, chrono::Local::now().timestamp()),
@@ -351,8 +482,11 @@ pub fn undo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
     };
 
     history.add_entry(revert_entry)?;
+    if let Some(git_blobs) = &git_blobs {
+        git_blobs.release()?;
+    }
 
-    Ok(())
+    Ok(conflicts)
 }
 
 /// Redo a previously undone renaming
@@ -362,7 +496,7 @@ pub fn redo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
     // Find the original entry
     let entry = history
         .find_entry(id)
-        .ok_or_else(|| anyhow!("History entry '{}' not found", id))?;
+        .ok_or_else(|| RenamifyError::NotFound(format!("History entry '{}' not found", id)))?;
 
     // Check if this entry was reverted
     let entries = history.list_entries(None);
@@ -371,7 +505,9 @@ pub fn redo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
         .find(|e| e.revert_of.as_ref() == Some(&entry.id));
 
     if revert_entry.is_none() {
-        return Err(anyhow!("Entry '{}' has not been reverted", id));
+        return Err(
+            RenamifyError::InvalidInput(format!("Entry '{}' has not been reverted", id)).into(),
+        );
     }
 
     eprintln!("Redoing renaming '{}'...", id);
@@ -379,7 +515,7 @@ pub fn redo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
     // Load the original plan from disk
     let plan_path = renamify_dir.join("plans").join(format!("{}.json", id));
     if !plan_path.exists() {
-        return Err(anyhow!("Plan file not found for entry '{}'. This may be an old renaming before plans were stored.", id));
+        return Err(RenamifyError::NotFound(format!("Plan file not found for entry '{}'. This may be an old renaming before plans were stored.", id)).into());
     }
 
     let plan_json = fs::read_to_string(&plan_path)?;
@@ -391,7 +527,7 @@ pub fn redo_renaming(id: &str, renamify_dir: &Path) -> Result<()> {
     // Apply the plan again
     let options = ApplyOptions {
         backup_dir: renamify_dir.join("backups"),
-        create_backups: true,
+        backup: crate::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -470,6 +606,10 @@ mod tests {
                 original_file: Some(temp_dir.path().join("old_name.txt")),
                 renamed_file: None,
                 patch_hash: Some(hash),
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             }],
             paths: vec![crate::scanner::Rename {
                 path: temp_dir.path().join("old_name.txt"),
@@ -482,8 +622,15 @@ mod tests {
                 total_matches: 1,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 1,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
         let plan_path = plans_dir.join("test_apply_123.json");
@@ -491,7 +638,7 @@ mod tests {
 
         // Create history entry representing the applied renaming
         let mut affected_files = HashMap::new();
-        affected_files.insert(new_file.clone(), "checksum123".to_string());
+        affected_files.insert(new_file.clone(), calculate_checksum(&new_file).unwrap());
 
         let entry = crate::history::HistoryEntry {
             id: "test_apply_123".to_string(),
@@ -516,7 +663,7 @@ mod tests {
         history.save().unwrap();
 
         // Perform undo
-        undo_renaming("test_apply_123", &renamify_dir).unwrap();
+        undo_renaming("test_apply_123", &renamify_dir, false).unwrap();
 
         // Verify file was renamed back
         assert!(!new_file.exists(), "Renamed file should not exist");
@@ -589,7 +736,7 @@ mod tests {
         history.save().unwrap();
 
         // Try to undo again - should fail
-        let result = undo_renaming("original", &renamify_dir);
+        let result = undo_renaming("original", &renamify_dir, false);
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -626,7 +773,7 @@ mod tests {
         history.save().unwrap();
 
         // Try to undo a revert entry - should fail
-        let result = undo_renaming("revert-123", &renamify_dir);
+        let result = undo_renaming("revert-123", &renamify_dir, false);
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -647,7 +794,7 @@ mod tests {
         history.save().unwrap();
 
         // Try to undo nonexistent entry
-        let result = undo_renaming("nonexistent", &renamify_dir);
+        let result = undo_renaming("nonexistent", &renamify_dir, false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
@@ -927,6 +1074,10 @@ mod tests {
                 original_file: Some(test_file.clone()),
                 renamed_file: None,
                 patch_hash: Some(hash),
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             }],
             paths: vec![],
             stats: crate::scanner::Stats {
@@ -934,8 +1085,15 @@ mod tests {
                 total_matches: 1,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 1,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
         let plan_path = plans_dir.join("test_perms.json");
@@ -943,7 +1101,7 @@ mod tests {
 
         // Create history entry
         let mut affected_files = HashMap::new();
-        affected_files.insert(test_file.clone(), "checksum123".to_string());
+        affected_files.insert(test_file.clone(), calculate_checksum(&test_file).unwrap());
 
         let entry = crate::history::HistoryEntry {
             id: "test_perms".to_string(),
@@ -967,7 +1125,7 @@ mod tests {
         history.save().unwrap();
 
         // Perform undo
-        undo_renaming("test_perms", &renamify_dir).unwrap();
+        undo_renaming("test_perms", &renamify_dir, false).unwrap();
 
         // Verify content was restored
         let content = fs::read_to_string(&test_file).unwrap();
@@ -1079,6 +1237,10 @@ mod tests {
                     original_file: Some(old_file.clone()),
                     renamed_file: Some(renamed_file.clone()),
                     patch_hash: Some(hash1),
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
                 crate::scanner::MatchHunk {
                     file: modified_file.clone(),
@@ -1096,6 +1258,10 @@ mod tests {
                     original_file: Some(modified_file.clone()),
                     renamed_file: None,
                     patch_hash: Some(hash2),
+                    style: None,
+                    match_kind: None,
+                    boundary_before: None,
+                    boundary_after: None,
                 },
             ],
             paths: vec![
@@ -1117,8 +1283,15 @@ mod tests {
                 total_matches: 2,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 2,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
 
@@ -1127,8 +1300,14 @@ mod tests {
 
         // Create history entry
         let mut affected_files = HashMap::new();
-        affected_files.insert(renamed_file.clone(), "checksum1".to_string());
-        affected_files.insert(modified_file.clone(), "checksum2".to_string());
+        affected_files.insert(
+            renamed_file.clone(),
+            calculate_checksum(&renamed_file).unwrap(),
+        );
+        affected_files.insert(
+            modified_file.clone(),
+            calculate_checksum(&modified_file).unwrap(),
+        );
 
         let entry = crate::history::HistoryEntry {
             id: "test_complex".to_string(),
@@ -1155,7 +1334,7 @@ mod tests {
         history.save().unwrap();
 
         // Perform undo
-        undo_renaming("test_complex", &renamify_dir).unwrap();
+        undo_renaming("test_complex", &renamify_dir, false).unwrap();
 
         // Verify renames were undone
         assert!(!new_dir.exists(), "New directory should not exist");
@@ -1219,7 +1398,7 @@ mod tests {
 
         // Create history entry for case-only rename
         let mut affected_files = HashMap::new();
-        affected_files.insert(new_file.clone(), "checksum".to_string());
+        affected_files.insert(new_file.clone(), calculate_checksum(&new_file).unwrap());
 
         let entry = crate::history::HistoryEntry {
             id: "test_case".to_string(),
@@ -1257,8 +1436,15 @@ mod tests {
                 total_matches: 0,
                 matches_by_variant: HashMap::new(),
                 files_with_matches: 0,
+                ..Default::default()
             },
             version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
             created_directories: None,
         };
         let plan_path = plans_dir.join("test_case.json");
@@ -1271,7 +1457,7 @@ mod tests {
         history.save().unwrap();
 
         // Perform undo
-        let result = undo_renaming("test_case", &renamify_dir);
+        let result = undo_renaming("test_case", &renamify_dir, false);
 
         // On case-insensitive filesystems, this should handle the temp rename
         // On case-sensitive filesystems, it should just rename directly
@@ -0,0 +1,867 @@
+//! Version control systems renamify works with: git, Jujutsu (jj) and
+//! Mercurial (hg).
+//!
+//! Each one answers the few questions renamify asks of a repository: where
+//! `.renamify/` should be ignored, whether it is tracked, and how to commit a
+//! rename. A jj repository colocated with git is handled as jj, since jj
+//! commits are made with `jj`, and uses git's exclude files, which jj reads.
+
+use crate::error::RenamifyError;
+use crate::scanner::Plan;
+use anyhow::{anyhow, Context, Result};
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Directories holding repository metadata, which are never scanned
+pub const METADATA_DIRS: [&str; 3] = [".git", ".hg", ".jj"];
+
+/// Placeholders a `commit.template` can use
+pub const COMMIT_PLACEHOLDERS: [&str; 6] = [
+    "search", "replace", "plan_id", "matches", "files", "renames",
+];
+
+/// How `--commit` commits a rename
+#[derive(Debug, Clone, Default)]
+pub struct CommitOptions {
+    /// Message template from `commit.template`, or None for the command's own
+    pub template: Option<String>,
+    /// Add a Signed-off-by trailer (`--signoff`)
+    pub signoff: bool,
+    /// Skip pre-commit and commit-msg hooks (`--no-verify`)
+    pub no_verify: bool,
+}
+
+impl CommitOptions {
+    /// The commit message for `plan`, from the configured template or else
+    /// `default_template`
+    pub fn message(&self, plan: &Plan, default_template: &str) -> Result<String> {
+        let template = self.template.as_deref().unwrap_or(default_template);
+        expand_template(template, |name| {
+            Some(match name {
+                "search" => plan.search.clone(),
+                "replace" => plan.replace.clone(),
+                "plan_id" => plan.id.clone(),
+                "matches" => plan.stats.total_matches.to_string(),
+                "files" => plan.stats.files_with_matches.to_string(),
+                "renames" => plan.paths.len().to_string(),
+                _ => return None,
+            })
+        })
+    }
+}
+
+/// Check that `template` only uses known placeholders
+pub fn validate_commit_template(template: &str) -> Result<()> {
+    expand_template(template, |name| {
+        COMMIT_PLACEHOLDERS.contains(&name).then(String::new)
+    })
+    .map(drop)
+}
+
+/// Replace each `{name}` in `template`; `{{` and `}}` stand for braces
+fn expand_template(template: &str, value: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        output.push_str(&rest[..index]);
+        let tail = &rest[index..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let name = tail
+            .strip_prefix('{')
+            .and_then(|tail| tail.split_once('}'))
+            .map(|(name, _)| name)
+            .ok_or_else(|| {
+                RenamifyError::InvalidInput(format!(
+                    "Unmatched brace in commit template '{template}'; write {{{{ or }}}} for a literal brace"
+                ))
+            })?;
+        let expanded = value(name).ok_or_else(|| {
+            RenamifyError::InvalidInput(format!(
+                "Unknown placeholder {{{name}}} in commit template. Available: {}",
+                COMMIT_PLACEHOLDERS
+                    .iter()
+                    .map(|name| format!("{{{name}}}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?;
+        output.push_str(&expanded);
+        rest = &tail[name.len() + 2..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+pub trait Vcs {
+    /// Name of the command line tool, used in messages
+    fn name(&self) -> &'static str;
+
+    /// Top of the working copy
+    fn root(&self) -> &Path;
+
+    /// The ignore file `.renamify/` is added to by default
+    fn ignore_file(&self) -> PathBuf;
+
+    /// Lines to append to the ignore file so it ignores `.renamify/`
+    fn ignore_lines(&self) -> &'static [&'static str] {
+        &[".renamify/"]
+    }
+
+    /// The ignore file that is private to this clone, like `.git/info/exclude`
+    fn local_exclude_file(&self) -> Result<PathBuf>;
+
+    /// Whether `path`, or anything below it, is tracked
+    fn is_tracked(&self, path: &Path) -> Result<bool>;
+
+    /// The command that stops tracking `path` without deleting it
+    fn untrack_command(&self, path: &str) -> String;
+
+    /// Fail if `options` asks for something this system can't do, so it is
+    /// caught before any file changes
+    fn check_commit_options(&self, options: &CommitOptions) -> Result<()>;
+
+    /// Commit every change in the working copy
+    fn commit_all(&self, message: &str, options: &CommitOptions) -> Result<()>;
+
+    /// Commit the changes to `paths` only
+    fn commit_paths(&self, paths: &[PathBuf], message: &str, options: &CommitOptions)
+        -> Result<()>;
+}
+
+/// The repository that contains `dir`, found from its nearest ancestor with a
+/// `.jj`, `.hg` or `.git` entry
+pub fn detect(dir: &Path) -> Option<Box<dyn Vcs>> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    dir.ancestors().find_map(|root| -> Option<Box<dyn Vcs>> {
+        let root = root.to_path_buf();
+        if root.join(".jj").is_dir() {
+            Some(Box::new(Jujutsu { root }))
+        } else if root.join(".hg").is_dir() {
+            Some(Box::new(Mercurial { root }))
+        } else if root.join(".git").exists() {
+            Some(Box::new(Git { root }))
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether a directory entry named `name` holds repository metadata
+pub fn is_metadata_dir(name: &OsStr) -> bool {
+    METADATA_DIRS.iter().any(|dir| name == *dir)
+}
+
+#[derive(Debug)]
+pub struct Git {
+    root: PathBuf,
+}
+
+impl Git {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl Vcs for Git {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn ignore_file(&self) -> PathBuf {
+        PathBuf::from(".gitignore")
+    }
+
+    fn local_exclude_file(&self) -> Result<PathBuf> {
+        let output = run(&self.root, "git", &["rev-parse", "--git-dir"])?;
+        if !output.status.success() {
+            return Err(anyhow!("Not in a git repository"));
+        }
+        let git_dir = String::from_utf8(output.stdout).context("Invalid UTF-8 in git output")?;
+        Ok(self.root.join(git_dir.trim()).join("info").join("exclude"))
+    }
+
+    fn is_tracked(&self, path: &Path) -> Result<bool> {
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args(["ls-files", "--error-unmatch"])
+            .arg(path)
+            .output()
+            .context("Failed to run git ls-files")?;
+        Ok(output.status.success())
+    }
+
+    fn untrack_command(&self, path: &str) -> String {
+        format!("git rm -r --cached {path}")
+    }
+
+    fn check_commit_options(&self, _options: &CommitOptions) -> Result<()> {
+        Ok(())
+    }
+
+    fn commit_all(&self, message: &str, options: &CommitOptions) -> Result<()> {
+        let output = run(&self.root, "git", &["add", "-A"])?;
+        check(&output, "Failed to stage changes")?;
+        self.commit_staged(message, options)
+    }
+
+    fn commit_paths(
+        &self,
+        paths: &[PathBuf],
+        message: &str,
+        options: &CommitOptions,
+    ) -> Result<()> {
+        if !paths.is_empty() {
+            let output = Command::new("git")
+                .current_dir(&self.root)
+                .args(["add", "--"])
+                .args(paths)
+                .output()
+                .context("Failed to run git add")?;
+            check(&output, "Failed to stage changes")?;
+        }
+        self.commit_staged(message, options)
+    }
+}
+
+/// An entry in the git index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub mode: String,
+    pub object: String,
+    /// Merge stage, non-zero while a conflict is unresolved
+    pub stage: u8,
+    /// Relative to the repository root, with `/` separators
+    pub path: String,
+}
+
+impl Git {
+    /// Stage the current state of `paths`, including deletions
+    pub fn stage_paths(&self, paths: &[PathBuf]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args(["add", "--all", "--"])
+            .args(paths)
+            .output()
+            .context("Failed to run git add")?;
+        check(&output, "Failed to stage changes")
+    }
+
+    /// The git repository containing `dir`, found with `git rev-parse`
+    pub fn containing(dir: &Path) -> Result<Self> {
+        let output = run(dir, "git", &["rev-parse", "--show-toplevel"])?;
+        if !output.status.success() {
+            return Err(RenamifyError::InvalidInput("Not in a git repository".to_string()).into());
+        }
+        let root = String::from_utf8(output.stdout).context("Invalid UTF-8 in git output")?;
+        Ok(Self::new(Path::new(root.trim_end_matches('\n'))))
+    }
+
+    /// Paths staged for the next commit, relative to the repository root.
+    /// Deletions are left out, since there is nothing left to read.
+    pub fn staged_paths(&self) -> Result<Vec<String>> {
+        let output = run(
+            &self.root,
+            "git",
+            &["diff", "--cached", "--name-only", "--diff-filter=d", "-z"],
+        )?;
+        check(&output, "Failed to list staged files")?;
+        let listing = String::from_utf8(output.stdout).context("Invalid UTF-8 in git output")?;
+        Ok(listing
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// The staged content of each of `paths`, from one `git cat-file`
+    /// process. `None` for a path that isn't a blob in the index, such as a
+    /// submodule.
+    pub fn staged_contents(&self, paths: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut child = Command::new("git")
+            .current_dir(&self.root)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run git cat-file")?;
+        let mut input = String::new();
+        for path in paths {
+            writeln!(input, ":{path}")?;
+        }
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Failed to open stdin for git"))?;
+        // git answers while it reads, so write from another thread
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child
+            .wait_with_output()
+            .context("Failed to run git cat-file")?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("Failed to write to git cat-file"))??;
+        check(&output, "Failed to read from the git index")?;
+        parse_batch(&output.stdout, paths.len())
+    }
+
+    /// Every entry in the index
+    pub fn index_entries(&self) -> Result<Vec<IndexEntry>> {
+        let output = run(&self.root, "git", &["ls-files", "--stage", "-z"])?;
+        check(&output, "Failed to read the git index")?;
+        let listing = String::from_utf8(output.stdout).context("Invalid UTF-8 in git output")?;
+        listing
+            .split('\0')
+            .filter(|record| !record.is_empty())
+            .map(|record| {
+                // <mode> SP <object> SP <stage> TAB <path>
+                let (info, path) = record
+                    .split_once('\t')
+                    .ok_or_else(|| anyhow!("Unexpected git ls-files output: {record}"))?;
+                let mut fields = info.split(' ');
+                let (Some(mode), Some(object), Some(stage)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    return Err(anyhow!("Unexpected git ls-files output: {record}"));
+                };
+                Ok(IndexEntry {
+                    mode: mode.to_string(),
+                    object: object.to_string(),
+                    stage: stage
+                        .parse()
+                        .context("Invalid stage in git ls-files output")?,
+                    path: path.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// The content of a blob
+    pub fn read_blob(&self, object: &str) -> Result<Vec<u8>> {
+        let output = run(&self.root, "git", &["cat-file", "blob", object])?;
+        check(&output, "Failed to read from the git object store")?;
+        Ok(output.stdout)
+    }
+
+    /// Store `content` as a blob, without clean filters, and return its id
+    pub fn write_blob(&self, content: &[u8]) -> Result<String> {
+        let output = run_with_input(
+            &self.root,
+            &["hash-object", "-w", "--no-filters", "--stdin"],
+            content,
+        )?;
+        check(&output, "Failed to write to the git object store")?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Store the content of each of `paths` as a blob, without clean
+    /// filters, from one `git hash-object` process, and return their ids
+    pub fn write_blobs(&self, paths: &[PathBuf]) -> Result<Vec<String>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut child = Command::new("git")
+            .current_dir(&self.root)
+            .args(["hash-object", "-w", "--no-filters", "--stdin-paths"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run git hash-object")?;
+        let mut input = String::new();
+        for path in paths {
+            writeln!(input, "{}", path.display())?;
+        }
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Failed to open stdin for git"))?;
+        // git answers while it reads, so write from another thread
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child
+            .wait_with_output()
+            .context("Failed to run git hash-object")?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("Failed to write to git hash-object"))??;
+        check(&output, "Failed to write to the git object store")?;
+        let ids: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if ids.len() != paths.len() {
+            return Err(anyhow!(
+                "git hash-object stored {} of {} files",
+                ids.len(),
+                paths.len()
+            ));
+        }
+        Ok(ids)
+    }
+
+    /// Keep `blobs` reachable from `reference`, so `git gc` doesn't prune them,
+    /// through a commit whose tree holds them as numbered files
+    pub fn keep_blobs(&self, reference: &str, blobs: &[String], message: &str) -> Result<()> {
+        let mut entries = String::new();
+        for (index, id) in blobs.iter().enumerate() {
+            writeln!(entries, "100644 blob {id}\t{index}")?;
+        }
+        let output = run_with_input(&self.root, &["mktree"], entries.as_bytes())?;
+        check(&output, "Failed to write a git tree")?;
+        let tree = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        // The commit is never checked out, so who made it doesn't matter
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args(["commit-tree", "--no-gpg-sign", &tree, "-m", message])
+            .envs([
+                ("GIT_AUTHOR_NAME", "renamify"),
+                ("GIT_AUTHOR_EMAIL", "renamify@localhost"),
+                ("GIT_COMMITTER_NAME", "renamify"),
+                ("GIT_COMMITTER_EMAIL", "renamify@localhost"),
+            ])
+            .output()
+            .context("Failed to run git commit-tree")?;
+        check(&output, "Failed to write a git commit")?;
+        let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        let output = run(&self.root, "git", &["update-ref", reference, &commit])?;
+        check(&output, &format!("Failed to update {reference}"))
+    }
+
+    /// Delete `reference`; deleting one that doesn't exist is not an error
+    pub fn delete_ref(&self, reference: &str) -> Result<()> {
+        let output = run(&self.root, "git", &["update-ref", "-d", reference])?;
+        check(&output, &format!("Failed to delete {reference}"))
+    }
+
+    /// The objects among `ids` that are not in the object store
+    pub fn missing_objects(&self, ids: &[&str]) -> Result<Vec<String>> {
+        let mut input = String::new();
+        for id in ids {
+            writeln!(input, "{id}")?;
+        }
+        let output = run_with_input(&self.root, &["cat-file", "--batch-check"], input.as_bytes())?;
+        check(&output, "Failed to read from the git object store")?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_suffix(" missing"))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Remove `removed` from the index, then add or replace `added`, leaving
+    /// the working tree alone
+    pub fn update_index(&self, removed: &[IndexEntry], added: &[IndexEntry]) -> Result<()> {
+        let mut input = String::new();
+        for entry in removed {
+            // Mode 0 removes the path
+            write!(
+                input,
+                "0 {}\t{}\0",
+                "0".repeat(entry.object.len()),
+                entry.path
+            )?;
+        }
+        for entry in added {
+            write!(input, "{} {}\t{}\0", entry.mode, entry.object, entry.path)?;
+        }
+        let output = run_with_input(
+            &self.root,
+            &["update-index", "-z", "--index-info"],
+            input.as_bytes(),
+        )?;
+        check(&output, "Failed to update the git index")
+    }
+
+    /// Commit what is staged in the index
+    pub fn commit_staged(&self, message: &str, options: &CommitOptions) -> Result<()> {
+        let mut command = Command::new("git");
+        command
+            .current_dir(&self.root)
+            .args(["commit", "-m", message]);
+        if options.signoff {
+            command.arg("--signoff");
+        }
+        if options.no_verify {
+            command.arg("--no-verify");
+        }
+        let output = command.output().context("Failed to run git commit")?;
+        check(&output, "Failed to commit changes")
+    }
+}
+
+#[derive(Debug)]
+pub struct Jujutsu {
+    root: PathBuf,
+}
+
+impl Jujutsu {
+    /// A fileset that matches `path` exactly, or everything below it
+    fn fileset(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        format!("root:\"{}\"", relative.replace('"', "\\\""))
+    }
+}
+
+impl Vcs for Jujutsu {
+    fn name(&self) -> &'static str {
+        "jj"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn ignore_file(&self) -> PathBuf {
+        PathBuf::from(".gitignore")
+    }
+
+    fn local_exclude_file(&self) -> Result<PathBuf> {
+        // jj only reads git's exclude file when colocated with git
+        if self.root.join(".git").is_dir() {
+            return Ok(self.root.join(".git").join("info").join("exclude"));
+        }
+        Err(RenamifyError::InvalidInput(
+            "jj repositories only have a local exclude file when colocated with git; add .renamify/ to .gitignore instead".to_string(),
+        )
+        .into())
+    }
+
+    fn is_tracked(&self, path: &Path) -> Result<bool> {
+        let output = Command::new("jj")
+            .current_dir(&self.root)
+            .args(["file", "list", "--"])
+            .arg(self.fileset(path))
+            .output()
+            .context("Failed to run jj file list")?;
+        Ok(output.status.success() && !output.stdout.is_empty())
+    }
+
+    fn untrack_command(&self, path: &str) -> String {
+        format!("jj file untrack {path}")
+    }
+
+    fn check_commit_options(&self, options: &CommitOptions) -> Result<()> {
+        // jj runs no commit hooks, so --no-verify has nothing to skip
+        if options.signoff {
+            return Err(unsupported("jj", "--signoff"));
+        }
+        Ok(())
+    }
+
+    fn commit_all(&self, message: &str, options: &CommitOptions) -> Result<()> {
+        self.check_commit_options(options)?;
+        // jj snapshots the working copy itself, so there is nothing to stage
+        let output = run(&self.root, "jj", &["commit", "-m", message])?;
+        check(&output, "Failed to commit changes")
+    }
+
+    fn commit_paths(
+        &self,
+        paths: &[PathBuf],
+        message: &str,
+        options: &CommitOptions,
+    ) -> Result<()> {
+        self.check_commit_options(options)?;
+        let output = Command::new("jj")
+            .current_dir(&self.root)
+            .args(["commit", "-m", message, "--"])
+            .args(paths.iter().map(|path| self.fileset(path)))
+            .output()
+            .context("Failed to run jj commit")?;
+        check(&output, "Failed to commit changes")
+    }
+}
+
+#[derive(Debug)]
+pub struct Mercurial {
+    root: PathBuf,
+}
+
+impl Mercurial {
+    /// A pattern that matches `path` literally, relative to the root
+    fn pattern(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        format!("path:{}", relative.to_string_lossy())
+    }
+}
+
+impl Vcs for Mercurial {
+    fn name(&self) -> &'static str {
+        "hg"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn ignore_file(&self) -> PathBuf {
+        // Mercurial only reads the .hgignore at the top of the repository
+        self.root.join(".hgignore")
+    }
+
+    fn ignore_lines(&self) -> &'static [&'static str] {
+        // .hgignore defaults to regular expressions
+        &["syntax: glob", ".renamify"]
+    }
+
+    fn local_exclude_file(&self) -> Result<PathBuf> {
+        Err(RenamifyError::InvalidInput(
+            "hg repositories have no local exclude file; add .renamify to .hgignore instead"
+                .to_string(),
+        )
+        .into())
+    }
+
+    fn is_tracked(&self, path: &Path) -> Result<bool> {
+        let output = Command::new("hg")
+            .current_dir(&self.root)
+            .arg("files")
+            .arg(self.pattern(path))
+            .output()
+            .context("Failed to run hg files")?;
+        Ok(output.status.success())
+    }
+
+    fn untrack_command(&self, path: &str) -> String {
+        format!("hg forget {path}")
+    }
+
+    fn check_commit_options(&self, options: &CommitOptions) -> Result<()> {
+        if options.signoff {
+            return Err(unsupported("hg", "--signoff"));
+        }
+        if options.no_verify {
+            return Err(unsupported("hg", "--no-verify"));
+        }
+        Ok(())
+    }
+
+    fn commit_all(&self, message: &str, options: &CommitOptions) -> Result<()> {
+        self.check_commit_options(options)?;
+        let output = run(&self.root, "hg", &["addremove"])?;
+        check(&output, "Failed to stage changes")?;
+        let output = run(&self.root, "hg", &["commit", "-m", message])?;
+        check(&output, "Failed to commit changes")
+    }
+
+    fn commit_paths(
+        &self,
+        paths: &[PathBuf],
+        message: &str,
+        options: &CommitOptions,
+    ) -> Result<()> {
+        self.check_commit_options(options)?;
+        let patterns: Vec<String> = paths.iter().map(|path| self.pattern(path)).collect();
+        let output = Command::new("hg")
+            .current_dir(&self.root)
+            .arg("addremove")
+            .args(&patterns)
+            .output()
+            .context("Failed to run hg addremove")?;
+        check(&output, "Failed to stage changes")?;
+        let output = Command::new("hg")
+            .current_dir(&self.root)
+            .args(["commit", "-m", message])
+            .args(&patterns)
+            .output()
+            .context("Failed to run hg commit")?;
+        check(&output, "Failed to commit changes")
+    }
+}
+
+/// Split `git cat-file --batch` output into the content of `count` objects:
+/// `<object> <type> <size>` then the content and a newline for each, or
+/// `<name> missing`
+fn parse_batch(mut output: &[u8], count: usize) -> Result<Vec<Option<Vec<u8>>>> {
+    let mut contents = Vec::with_capacity(count);
+    for _ in 0..count {
+        let newline = output
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| anyhow!("Unexpected end of git cat-file output"))?;
+        let header = String::from_utf8_lossy(&output[..newline]).into_owned();
+        output = &output[newline + 1..];
+        let mut fields = header.rsplitn(3, ' ');
+        let (Some(size), Some(kind)) = (fields.next(), fields.next()) else {
+            contents.push(None);
+            continue;
+        };
+        let Ok(size) = size.parse::<usize>() else {
+            // `<name> missing` or `<name> ambiguous`
+            contents.push(None);
+            continue;
+        };
+        if output.len() < size + 1 {
+            return Err(anyhow!("Unexpected end of git cat-file output"));
+        }
+        contents.push((kind == "blob").then(|| output[..size].to_vec()));
+        output = &output[size + 1..];
+    }
+    Ok(contents)
+}
+
+fn run(root: &Path, program: &str, args: &[&str]) -> Result<Output> {
+    Command::new(program)
+        .current_dir(root)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {program} {}", args.join(" ")))
+}
+
+fn run_with_input(root: &Path, args: &[&str], input: &[u8]) -> Result<Output> {
+    let mut child = Command::new("git")
+        .current_dir(root)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Failed to open stdin for git"))?;
+    // Batch commands answer while they read, so write from another thread
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    writer
+        .join()
+        .map_err(|_| anyhow!("Failed to write to git {}", args.join(" ")))??;
+    Ok(output)
+}
+
+fn unsupported(vcs: &str, flag: &str) -> anyhow::Error {
+    RenamifyError::InvalidInput(format!(
+        "{flag} is not supported when committing with {vcs}"
+    ))
+    .into()
+}
+
+fn check(output: &Output, failure: &str) -> Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let error = String::from_utf8_lossy(&output.stderr);
+    Err(anyhow!("{failure}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_batch() {
+        let output = b"aaa blob 3\nabc\n:sub missing\nbbb tree 0\n\nccc blob 0\n\n";
+        let contents = parse_batch(output, 4).unwrap();
+        assert_eq!(
+            contents,
+            vec![Some(b"abc".to_vec()), None, None, Some(Vec::new())]
+        );
+        assert!(parse_batch(b"aaa blob 10\nabc\n", 1).is_err());
+    }
+
+    #[test]
+    fn test_detect() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for repo in ["git/.git", "hg/.hg", "jj/.jj", "jj/.git", "git/sub/.hg"] {
+            fs::create_dir_all(root.join(repo)).unwrap();
+        }
+        fs::create_dir_all(root.join("git/src")).unwrap();
+        fs::create_dir_all(root.join("plain")).unwrap();
+
+        let found =
+            |path: &str| detect(&root.join(path)).map(|vcs| (vcs.name(), vcs.root().to_path_buf()));
+        assert_eq!(found("git/src"), Some(("git", root.join("git"))));
+        assert_eq!(found("git/sub"), Some(("hg", root.join("git/sub"))));
+        assert_eq!(found("hg"), Some(("hg", root.join("hg"))));
+        assert_eq!(found("jj"), Some(("jj", root.join("jj"))));
+        assert!(detect(&root.join("plain")).is_none_or(|vcs| !vcs.root().starts_with(&root)));
+
+        let hg = detect(&root.join("hg")).unwrap();
+        assert_eq!(hg.ignore_file(), root.join("hg/.hgignore"));
+        assert!(hg.local_exclude_file().is_err());
+        let jj = detect(&root.join("jj")).unwrap();
+        assert_eq!(
+            jj.local_exclude_file().unwrap(),
+            root.join("jj/.git/info/exclude")
+        );
+        assert!(is_metadata_dir(OsStr::new(".jj")));
+        assert!(!is_metadata_dir(OsStr::new(".github")));
+    }
+
+    #[test]
+    fn test_commit_message_template() {
+        let plan = Plan {
+            id: "abc123".to_string(),
+            created_at: "0".to_string(),
+            search: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            styles: vec![],
+            includes: vec![],
+            excludes: vec![],
+            matches: vec![],
+            paths: vec![],
+            stats: crate::scanner::Stats {
+                total_matches: 12,
+                files_with_matches: 3,
+                ..Default::default()
+            },
+            version: "1.0.0".to_string(),
+            coercion: crate::scanner::CoercionMode::Auto,
+            skipped: vec![],
+            near_misses: vec![],
+            suspicious_renames: vec![],
+            warnings: vec![],
+            options: None,
+            created_directories: None,
+        };
+        let options = CommitOptions {
+            template: Some(
+                "rename: {search} -> {replace} ({matches} edits in {files} files, {renames} renames)\n\nPlan-Id: {plan_id} {{literal}}".to_string(),
+            ),
+            ..CommitOptions::default()
+        };
+        assert_eq!(
+            options.message(&plan, "unused").unwrap(),
+            "rename: old_name -> new_name (12 edits in 3 files, 0 renames)\n\nPlan-Id: abc123 {literal}"
+        );
+        assert_eq!(
+            CommitOptions::default()
+                .message(&plan, "Replace '{search}'")
+                .unwrap(),
+            "Replace 'old_name'"
+        );
+
+        assert!(validate_commit_template("{search} {renames}").is_ok());
+        let error = validate_commit_template("{serach}")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Unknown placeholder {serach}"), "{error}");
+        assert!(validate_commit_template("{search").is_err());
+    }
+}
@@ -0,0 +1,488 @@
+//! Problems worth a look before applying a plan.
+//!
+//! Plans carry their warnings in [`Plan::warnings`], each with a kind, a
+//! severity and, where one helps, the flag that deals with it, so JSON
+//! consumers see the same warnings people do on the terminal.
+
+use crate::config::Config;
+use crate::scanner::{Plan, PlanOptions, RenameKind};
+use ignore::Match;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// How much a warning matters, from least to most
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum WarningSeverity {
+    /// Worth knowing, rarely a problem
+    Info,
+    /// Likely needs a look before applying
+    Warning,
+    /// Likely breaks something once applied
+    Critical,
+}
+
+/// What a warning is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum WarningKind {
+    /// The search term is a single short or common word, so some matches are
+    /// probably false positives
+    ShortSearch,
+    /// Matches or a rename inside a protected path such as `vendor`
+    ProtectedPath,
+    /// A binary file renamed because its name matches; its contents are not searched
+    BinaryFilename,
+    /// A rename target that differs only by case from another path
+    CaseCollision,
+    /// Matches in a generated file or lockfile, which regenerating overwrites
+    GeneratedFile,
+    /// A label renamed in some places but not others (profiles with `label_warnings`)
+    Label,
+    /// Files outside the sparse checkout likely need the rename too
+    SparseCheckout,
+    /// The plan was not written to disk
+    PlanNotSaved,
+}
+
+/// A problem found while planning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[ts(export)]
+pub struct PlanWarning {
+    pub kind: WarningKind,
+    pub severity: WarningSeverity,
+    pub message: String,
+    /// The file or directory the warning is about, if there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "string")]
+    pub path: Option<PathBuf>,
+    /// A flag that deals with the problem, e.g. `--exclude`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub suggested_flag: Option<String>,
+}
+
+impl PlanWarning {
+    pub fn new(kind: WarningKind, severity: WarningSeverity, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity,
+            message: message.into(),
+            path: None,
+            suggested_flag: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_suggested_flag(mut self, flag: impl Into<String>) -> Self {
+        self.suggested_flag = Some(flag.into());
+        self
+    }
+}
+
+impl std::fmt::Display for PlanWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::fmt::Display for WarningSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// Lockfiles, rewritten by their package manager rather than by hand
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Gemfile.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+    "mix.lock",
+    "pubspec.lock",
+    "Podfile.lock",
+    "packages.lock.json",
+];
+
+/// File name endings of minified and generated code
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js", ".min.css", ".js.map", ".css.map", ".pb.go", "_pb2.py", ".g.dart",
+];
+
+/// Header comments generators leave at the top of their output
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "DO NOT EDIT",
+    "Code generated by",
+    "<auto-generated",
+];
+
+/// How much of a file is looked at for a generated marker or binary content
+const HEADER_BYTES: u64 = 8192;
+
+/// The warnings about a freshly scanned plan, most severe first.
+///
+/// These cover a short search term, matches and renames inside protected
+/// paths, renamed binary files, case collisions let through with
+/// `--allow-case-collisions` and matches in generated files. Label and sparse
+/// checkout warnings come from the profile and the sparse report instead.
+pub fn plan_warnings(
+    plan: &Plan,
+    roots: &[PathBuf],
+    options: &PlanOptions,
+    config: &Config,
+) -> Vec<PlanWarning> {
+    let base_dir = options.working_dir.as_deref();
+    let mut warnings = Vec::new();
+
+    if let Some(reason) = config.short_search.check(&plan.search) {
+        warnings.push(
+            PlanWarning::new(
+                WarningKind::ShortSearch,
+                WarningSeverity::Warning,
+                format!(
+                    "{reason}, so some matches are probably false positives; plan and rename refuse it without --allow-short-search"
+                ),
+            )
+            .with_suggested_flag("--match-anchor"),
+        );
+    }
+
+    let mut matches_by_file: BTreeMap<&Path, usize> = BTreeMap::new();
+    for hunk in &plan.matches {
+        *matches_by_file.entry(&hunk.file).or_default() += 1;
+    }
+
+    warnings.extend(protected_path_warnings(
+        plan,
+        &matches_by_file,
+        roots,
+        &config.protected,
+        base_dir,
+    ));
+
+    if !options.binary_as_text() {
+        for rename in &plan.paths {
+            if rename.kind == RenameKind::File
+                && read_header(&rename.path).is_some_and(|header| is_binary(&header))
+            {
+                warnings.push(
+                    PlanWarning::new(
+                        WarningKind::BinaryFilename,
+                        WarningSeverity::Info,
+                        format!(
+                            "{} is binary, so it is renamed but its contents are not searched",
+                            display(&rename.path, base_dir)
+                        ),
+                    )
+                    .with_path(&rename.path)
+                    .with_suggested_flag("-uuu"),
+                );
+            }
+        }
+    }
+
+    if options.allow_case_collisions {
+        warnings.extend(case_collision_warnings(plan, base_dir));
+    }
+
+    for (file, count) in &matches_by_file {
+        if let Some(reason) = generated_reason(file) {
+            warnings.push(
+                PlanWarning::new(
+                    WarningKind::GeneratedFile,
+                    WarningSeverity::Warning,
+                    format!(
+                        "{} {} in {}, which is {reason}; regenerating it overwrites the edits",
+                        count,
+                        if *count == 1 { "match" } else { "matches" },
+                        display(file, base_dir)
+                    ),
+                )
+                .with_path(*file)
+                .with_suggested_flag("--exclude"),
+            );
+        }
+    }
+
+    // Stable, so warnings of the same severity keep their order
+    warnings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    warnings
+}
+
+/// Matches and renames that are already inside a protected path, which the
+/// walk only enters with `-uu` or an explicit path
+fn protected_path_warnings(
+    plan: &Plan,
+    matches_by_file: &BTreeMap<&Path, usize>,
+    roots: &[PathBuf],
+    protected: &[String],
+    base_dir: Option<&Path>,
+) -> Vec<PlanWarning> {
+    let matchers: Vec<_> = roots
+        .iter()
+        .filter_map(|root| {
+            crate::suspicious::protected_matcher(root, protected)
+                .ok()
+                .map(|matcher| (root, matcher))
+        })
+        .collect();
+    let pattern_for = |path: &Path, is_dir: bool| {
+        matchers.iter().find_map(|(root, matcher)| {
+            let relative = path.strip_prefix(root).ok()?;
+            match matcher.matched_path_or_any_parents(relative, is_dir) {
+                Match::Ignore(glob) => Some(glob.original().to_string()),
+                _ => None,
+            }
+        })
+    };
+
+    let mut warnings = Vec::new();
+    for (file, count) in matches_by_file {
+        if let Some(pattern) = pattern_for(file, false) {
+            warnings.push(
+                PlanWarning::new(
+                    WarningKind::ProtectedPath,
+                    WarningSeverity::Warning,
+                    format!(
+                        "{} {} in {}, inside the protected path `{pattern}`",
+                        count,
+                        if *count == 1 { "match" } else { "matches" },
+                        display(file, base_dir)
+                    ),
+                )
+                .with_path(*file)
+                .with_suggested_flag("--exclude"),
+            );
+        }
+    }
+    for rename in &plan.paths {
+        if let Some(pattern) = pattern_for(&rename.path, rename.kind == RenameKind::Dir) {
+            warnings.push(
+                PlanWarning::new(
+                    WarningKind::ProtectedPath,
+                    WarningSeverity::Warning,
+                    format!(
+                        "{} is renamed inside the protected path `{pattern}`",
+                        display(&rename.path, base_dir)
+                    ),
+                )
+                .with_path(&rename.path)
+                .with_suggested_flag("--exclude"),
+            );
+        }
+    }
+    warnings
+}
+
+/// Renames let through by `--allow-case-collisions`, checked against the
+/// other entries of the directories they end up in
+fn case_collision_warnings(plan: &Plan, base_dir: Option<&Path>) -> Vec<PlanWarning> {
+    let dirs: BTreeSet<&Path> = plan
+        .paths
+        .iter()
+        .flat_map(|rename| [rename.path.parent(), rename.new_path.parent()])
+        .flatten()
+        .collect();
+    let existing: Vec<PathBuf> = dirs
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+
+    crate::rename::case_collisions(&plan.paths, &existing)
+        .into_iter()
+        .map(|(rename, colliding)| {
+            let names = colliding
+                .iter()
+                .map(|path| display(path, base_dir).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            PlanWarning::new(
+                WarningKind::CaseCollision,
+                WarningSeverity::Critical,
+                format!(
+                    "{} differs only by case from {names}, so the tree can't be checked out on a case-insensitive filesystem",
+                    display(&rename.new_path, base_dir)
+                ),
+            )
+            .with_path(&rename.new_path)
+        })
+        .collect()
+}
+
+/// Why `path` looks generated, if it does
+fn generated_reason(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy();
+    if LOCKFILES.contains(&name.as_ref()) {
+        return Some("a lockfile".to_string());
+    }
+    if let Some(suffix) = GENERATED_SUFFIXES
+        .iter()
+        .find(|suffix| name.ends_with(*suffix))
+    {
+        return Some(format!("generated (`*{suffix}`)"));
+    }
+    let header = read_header(path)?;
+    let header = String::from_utf8_lossy(&header);
+    GENERATED_MARKERS
+        .iter()
+        .find(|marker| header.contains(*marker))
+        .map(|marker| format!("marked `{marker}`"))
+}
+
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut header = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(HEADER_BYTES)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(header)
+}
+
+fn is_binary(content: &[u8]) -> bool {
+    matches!(
+        content_inspector::inspect(content),
+        content_inspector::ContentType::BINARY
+    )
+}
+
+fn display<'a>(path: &'a Path, base_dir: Option<&Path>) -> std::path::Display<'a> {
+    base_dir
+        .and_then(|base_dir| path.strip_prefix(base_dir).ok())
+        .unwrap_or(path)
+        .display()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn scan(root: &Path, search: &str, options: &PlanOptions) -> (Plan, Vec<PlanWarning>) {
+        let plan = crate::scanner::scan_repository(root, search, "new_name", options).unwrap();
+        let warnings = plan_warnings(&plan, &[root.to_path_buf()], options, &Config::default());
+        (plan, warnings)
+    }
+
+    #[test]
+    fn test_plan_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::write(root.join("lib.rs"), "fn old_name() {}\n").unwrap();
+        fs::write(
+            root.join("api.rs"),
+            "// @generated by protoc\nold_name();\nold_name();\n",
+        )
+        .unwrap();
+        fs::write(root.join("Cargo.lock"), "name = \"old_name\"\n").unwrap();
+        fs::write(root.join("old_name.png"), [0x89, b'P', b'N', b'G', 0, 0, 1]).unwrap();
+        fs::create_dir(root.join("vendor")).unwrap();
+        fs::write(root.join("vendor/dep.rs"), "old_name();\n").unwrap();
+
+        let options = PlanOptions {
+            working_dir: Some(root.clone()),
+            ..PlanOptions::default()
+        };
+        let (_, warnings) = scan(&root, "old_name", &options);
+        let summary: Vec<(WarningKind, WarningSeverity, &str, Option<&str>)> = warnings
+            .iter()
+            .map(|warning| {
+                (
+                    warning.kind,
+                    warning.severity,
+                    warning.message.as_str(),
+                    warning.suggested_flag.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    WarningKind::ProtectedPath,
+                    WarningSeverity::Warning,
+                    "1 match in vendor/dep.rs, inside the protected path `vendor`",
+                    Some("--exclude"),
+                ),
+                (
+                    WarningKind::GeneratedFile,
+                    WarningSeverity::Warning,
+                    "1 match in Cargo.lock, which is a lockfile; regenerating it overwrites the edits",
+                    Some("--exclude"),
+                ),
+                (
+                    WarningKind::GeneratedFile,
+                    WarningSeverity::Warning,
+                    "2 matches in api.rs, which is marked `@generated`; regenerating it overwrites the edits",
+                    Some("--exclude"),
+                ),
+                (
+                    WarningKind::BinaryFilename,
+                    WarningSeverity::Info,
+                    "old_name.png is binary, so it is renamed but its contents are not searched",
+                    Some("-uuu"),
+                ),
+            ]
+        );
+        assert_eq!(warnings[0].path, Some(root.join("vendor/dep.rs")));
+    }
+
+    #[test]
+    fn test_short_search_and_case_collision_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::write(root.join("id.rs"), "").unwrap();
+        fs::write(root.join("New_name.rs"), "").unwrap();
+
+        let options = PlanOptions {
+            allow_case_collisions: true,
+            working_dir: Some(root.clone()),
+            ..PlanOptions::default()
+        };
+        let (plan, warnings) = scan(&root, "id", &options);
+        assert_eq!(plan.paths.len(), 1);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].kind, WarningKind::CaseCollision);
+        assert_eq!(warnings[0].severity, WarningSeverity::Critical);
+        assert_eq!(
+            warnings[0].message,
+            "new_name.rs differs only by case from New_name.rs, so the tree can't be checked out on a case-insensitive filesystem"
+        );
+        assert_eq!(warnings[1].kind, WarningKind::ShortSearch);
+        assert_eq!(
+            warnings[1].suggested_flag.as_deref(),
+            Some("--match-anchor")
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Running without a writable `.renamify` directory.
+//!
+//! Plans, history, backups and the lock all live in `.renamify`. In a
+//! read-only checkout, such as some CI jobs, renamify can still search and
+//! preview: with the workspace disabled, nothing is locked or written to
+//! `.renamify`, and a plan is only saved when `--plan-out` points elsewhere.
+//! Commands that change files still need the workspace, since a change
+//! without history and backups can't be undone. The workspace is enabled
+//! until the CLI turns it off with `--no-workspace`, or because `.renamify`
+//! can't be written.
+
+use crate::error::RenamifyError;
+use anyhow::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Stop renamify from locking or writing anything in `.renamify`
+pub fn set_disabled(disabled: bool) {
+    DISABLED.store(disabled, Ordering::SeqCst);
+}
+
+pub fn is_disabled() -> bool {
+    DISABLED.load(Ordering::SeqCst)
+}
+
+/// Fail when the workspace is disabled, for commands that change files
+pub fn require(command: &str) -> Result<()> {
+    if is_disabled() {
+        return Err(RenamifyError::InvalidInput(format!(
+            "{command} needs a writable .renamify directory for its history and backups, \
+             but the workspace is disabled (--no-workspace, or .renamify is read-only)"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Whether files can be created in `renamify_dir`, or, when it doesn't exist
+/// yet, in the directory it would be created in
+pub fn is_writable(renamify_dir: &Path) -> bool {
+    let dir = if renamify_dir.exists() {
+        renamify_dir
+    } else {
+        match renamify_dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        }
+    };
+    tempfile::tempfile_in(dir).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_writable() {
+        let dir = TempDir::new().unwrap();
+        let renamify_dir = dir.path().join(".renamify");
+        assert!(is_writable(&renamify_dir));
+        assert!(!renamify_dir.exists());
+        assert!(!is_writable(&dir.path().join("missing/.renamify")));
+    }
+}
@@ -20,6 +20,7 @@ fn test_no_acronyms_flag() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: true, // Disable acronym detection
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -37,6 +38,20 @@ fn test_no_acronyms_flag() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "XMLHttpRequest", "NewRequest", &options).unwrap();
@@ -78,6 +93,7 @@ fn test_include_acronyms_flag() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec!["K8S".to_string(), "GCP".to_string()], // Add custom acronyms
         exclude_acronyms: vec![],
@@ -95,6 +111,20 @@ fn test_include_acronyms_flag() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "K8SCluster", "KubeCluster", &options).unwrap();
@@ -128,6 +158,7 @@ fn test_exclude_acronyms_flag() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec!["ID".to_string()], // Exclude ID from acronyms
@@ -145,6 +176,20 @@ fn test_exclude_acronyms_flag() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "getUserID", "getUserIdentifier", &options).unwrap();
@@ -178,6 +223,7 @@ fn test_only_acronyms_flag() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -195,6 +241,20 @@ fn test_only_acronyms_flag() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     // AWSProvider should match
@@ -235,6 +295,7 @@ fn test_acronym_case_insensitive() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec!["api".to_string()], // lowercase
         exclude_acronyms: vec![],
@@ -252,6 +313,20 @@ fn test_acronym_case_insensitive() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     // Should still work with ApiClient (Api is matched as API)
@@ -25,6 +25,7 @@ fn test_acronym_affects_case_variants() {
     let options_with = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec!["B2B".to_string()], // Add B2B as acronym
         exclude_acronyms: vec![],
@@ -42,6 +43,20 @@ fn test_acronym_affects_case_variants() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan_with = scan_repository(&root, "b2b_sales", "business_sales", &options_with).unwrap();
@@ -64,6 +79,7 @@ fn test_acronym_affects_case_variants() {
     let options_without = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![], // Don't add B2B
         exclude_acronyms: vec![],
@@ -81,6 +97,20 @@ fn test_acronym_affects_case_variants() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan_without =
@@ -126,6 +156,7 @@ fn test_custom_acronym_generation() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec!["K8S".to_string()], // Add K8S as acronym
         exclude_acronyms: vec![],
@@ -143,6 +174,20 @@ fn test_custom_acronym_generation() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "k8s_cluster", "kube_cluster", &options).unwrap();
@@ -193,6 +238,7 @@ fn test_excluded_acronym_variants() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec!["ID".to_string()], // Exclude ID
@@ -210,6 +256,20 @@ fn test_excluded_acronym_variants() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "user_id", "user_identifier", &options).unwrap();
@@ -251,6 +311,7 @@ fn test_only_acronyms_list() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -268,6 +329,20 @@ fn test_only_acronyms_list() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     // Search for XML patterns - should work
@@ -1,4 +1,5 @@
 use renamify_core::operations::plan::plan_operation;
+use renamify_core::{CoercionMode, LiteralMode, MatchAnchor};
 use std::fs;
 use tempfile::TempDir;
 
@@ -41,31 +42,52 @@ APIClass = API
     let (plan_result, _) = plan_operation(
         "api",
         "ServiceHandler",
-        vec![],          // empty = current dir
-        vec![],          // includes
-        vec![],          // excludes
-        true,            // respect_gitignore
-        0,               // unrestricted_level
-        true,            // rename_files
-        true,            // rename_dirs
-        &[],             // exclude_styles
-        &[],             // include_styles
-        &[],             // only_styles
-        vec![],          // exclude_match
-        None,            // exclude_matching_lines
-        None,            // plan_out
-        None,            // preview_format
-        true,            // dry_run
-        false,           // fixed_table_width
-        false,           // use_color
-        false,           // no_acronyms
-        vec![],          // include_acronyms
-        vec![],          // exclude_acronyms
-        vec![],          // only_acronyms
-        true,            // enable_plural_variants
-        false,           // ignore_ambiguous
-        Some(temp_path), // working_dir
-        None,            // atomic_config
+        vec![],                                // empty = current dir
+        None,                                  // buffer
+        vec![],                                // includes
+        vec![],                                // excludes
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        true,                                  // rename_files
+        true,                                  // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        None,                                  // preview_format
+        true,                                  // dry_run
+        false,                                 // fixed_table_width
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_path),                       // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
     )
     .unwrap();
 
@@ -170,6 +192,7 @@ const API_TIMEOUT = 5000;
         "api",
         "DataService",
         vec![],
+        None,
         vec![],
         vec![],
         true,
@@ -181,19 +204,39 @@ const API_TIMEOUT = 5000;
         &[],
         vec![],
         None,
+        vec![],
         None,
         None,
         true,
         false,
+        renamify_core::GroupBy::File,
+        None, // preview_sample
         false,
         false,
         vec![],
         vec![],
         vec![],
+        None, // acronym_rendering
+        None, // case_mapping
         true,
         false,
+        false,
+        LiteralMode::All,
+        MatchAnchor::Any,
+        CoercionMode::Auto,
+        None,
+        None,
+        false,
+        false,
+        0,
+        false,
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
         Some(temp_path),
         None,
+        None,
+        None,
     )
     .unwrap();
 
@@ -248,6 +291,7 @@ def get_api():
         "api",
         "backend",
         vec![],
+        None,
         vec![],
         vec![],
         true,
@@ -259,19 +303,39 @@ def get_api():
         &[],
         vec![],
         None,
+        vec![],
         None,
         None,
         true,
         false,
+        renamify_core::GroupBy::File,
+        None, // preview_sample
         false,
         false,
         vec![],
         vec![],
         vec![],
+        None, // acronym_rendering
+        None, // case_mapping
         true,
         false,
+        false,
+        LiteralMode::All,
+        MatchAnchor::Any,
+        CoercionMode::Auto,
+        None,
+        None,
+        false,
+        false,
+        0,
+        false,
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
         Some(temp_path),
         None,
+        None,
+        None,
     )
     .unwrap();
 
@@ -19,8 +19,15 @@ fn create_test_plan(id: &str, old: &str, new: &str) -> Plan {
             total_matches: 0,
             matches_by_variant: HashMap::new(),
             files_with_matches: 0,
+            ..Default::default()
         },
         version: "1.0.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     }
 }
@@ -55,6 +62,10 @@ fn test_apply_content_edits() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
     plan.matches.push(MatchHunk {
         file: test_file.clone(),
@@ -72,6 +83,10 @@ fn test_apply_content_edits() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply the plan
@@ -172,6 +187,10 @@ fn test_rollback_on_error() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply should fail
@@ -248,6 +267,10 @@ fn test_atomic_operations() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Second edit will fail (wrong content)
@@ -267,6 +290,10 @@ fn test_atomic_operations() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     let options = ApplyOptions {
@@ -381,6 +408,10 @@ fn test_apply_with_git_commit() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply with commit
@@ -509,6 +540,10 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     plan.matches.push(MatchHunk {
@@ -527,6 +562,10 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Add content changes for the stable file (not renamed)
@@ -549,6 +588,10 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     plan.matches.push(MatchHunk {
@@ -567,6 +610,10 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Add content changes for the file inside the directory that will be renamed
@@ -586,6 +633,10 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     plan.matches.push(MatchHunk {
@@ -604,6 +655,10 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Case 5: Add content changes for the file that gets renamed inside a renamed directory
@@ -624,12 +679,16 @@ fn test_apply_with_both_renames_and_content_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply the plan
     let options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
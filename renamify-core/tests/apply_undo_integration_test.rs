@@ -1,5 +1,6 @@
 use renamify_core::{
-    apply_plan, undo_renaming, ApplyOptions, MatchHunk, Plan, Rename, RenameKind, Stats,
+    apply_plan, undo_renaming, ApplyOptions, BackupStrategy, LocalChange, MatchHunk, Plan, Rename,
+    RenameKind, Stats, UndoConflict,
 };
 use std::collections::HashMap;
 use std::fs;
@@ -21,8 +22,15 @@ fn create_test_plan(id: &str, old: &str, new: &str) -> Plan {
             total_matches: 0,
             matches_by_variant: HashMap::new(),
             files_with_matches: 0,
+            ..Default::default()
         },
         version: "1.0.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     }
 }
@@ -54,6 +62,10 @@ fn test_apply_undo_content_changes_only() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
     plan.matches.push(MatchHunk {
         file: test_file.clone(),
@@ -71,12 +83,16 @@ fn test_apply_undo_content_changes_only() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -90,7 +106,7 @@ fn test_apply_undo_content_changes_only() {
 
     // Undo the changes
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify undo worked
     let undone_content = fs::read_to_string(&test_file).unwrap();
@@ -123,7 +139,7 @@ fn test_apply_undo_file_rename_only() {
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -137,7 +153,7 @@ fn test_apply_undo_file_rename_only() {
 
     // Undo the rename
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify undo worked
     assert!(old_file.exists());
@@ -168,7 +184,7 @@ fn test_apply_undo_dir_rename_only() {
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -186,7 +202,7 @@ fn test_apply_undo_dir_rename_only() {
 
     // Undo the rename
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify undo worked
     assert!(old_dir.exists());
@@ -232,6 +248,10 @@ fn test_apply_undo_content_and_file_rename() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
     plan.matches.push(MatchHunk {
         file: old_file.clone(),
@@ -249,12 +269,16 @@ fn test_apply_undo_content_and_file_rename() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -270,7 +294,7 @@ fn test_apply_undo_content_and_file_rename() {
 
     // Undo the changes
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify undo worked
     assert!(old_file.exists());
@@ -322,6 +346,10 @@ fn test_apply_undo_content_and_dir_rename() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
     plan.matches.push(MatchHunk {
         file: test_file.clone(),
@@ -339,12 +367,16 @@ fn test_apply_undo_content_and_dir_rename() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -362,7 +394,7 @@ fn test_apply_undo_content_and_dir_rename() {
 
     // Undo the changes
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify undo worked
     assert!(old_dir.exists());
@@ -409,7 +441,7 @@ fn test_apply_undo_file_and_dir_rename() {
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -425,7 +457,7 @@ fn test_apply_undo_file_and_dir_rename() {
 
     // Undo the changes
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify undo worked
     assert!(old_dir.exists());
@@ -485,6 +517,10 @@ fn test_apply_undo_all_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
     plan.matches.push(MatchHunk {
         file: old_file.clone(),
@@ -502,6 +538,10 @@ fn test_apply_undo_all_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Content changes in stable file
@@ -521,6 +561,10 @@ fn test_apply_undo_all_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
     plan.matches.push(MatchHunk {
         file: stable_file.clone(),
@@ -538,12 +582,16 @@ fn test_apply_undo_all_changes() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -566,7 +614,7 @@ fn test_apply_undo_all_changes() {
 
     // Undo ALL the changes
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify complete undo
     assert!(old_dir.exists());
@@ -633,6 +681,10 @@ fn test_apply_undo_multiple_files_in_renamed_dir() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Content changes in file2
@@ -652,12 +704,16 @@ fn test_apply_undo_multiple_files_in_renamed_dir() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     // Apply
     let apply_options = ApplyOptions {
         backup_dir: temp_dir.path().join(".renamify/backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         ..Default::default()
     };
 
@@ -670,7 +726,7 @@ fn test_apply_undo_multiple_files_in_renamed_dir() {
 
     // Undo
     let renamify_dir = temp_dir.path().join(".renamify");
-    undo_renaming(&plan.id, &renamify_dir).unwrap();
+    undo_renaming(&plan.id, &renamify_dir, false).unwrap();
 
     // Verify complete restoration
     assert!(old_dir.exists());
@@ -678,3 +734,186 @@ fn test_apply_undo_multiple_files_in_renamed_dir() {
     assert_eq!(fs::read_to_string(&file1).unwrap(), file1_initial);
     assert_eq!(fs::read_to_string(&file2).unwrap(), file2_initial);
 }
+
+#[test]
+fn test_apply_undo_with_git_stash_and_no_backups() {
+    for backup in [BackupStrategy::GitStash, BackupStrategy::None] {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&root)
+            .status()
+            .unwrap();
+        let old_file = root.join("old_name.rs");
+        let new_file = root.join("new_name.rs");
+        let initial_content = "fn old_name() {}\n";
+        fs::write(&old_file, initial_content).unwrap();
+
+        let mut plan = create_test_plan("backup_strategy", "old_name", "new_name");
+        plan.paths.push(Rename {
+            path: old_file.clone(),
+            new_path: new_file.clone(),
+            kind: RenameKind::File,
+            coercion_applied: None,
+        });
+        plan.matches.push(MatchHunk {
+            file: old_file.clone(),
+            line: 1,
+            byte_offset: 3,
+            char_offset: 3,
+            variant: "old_name".to_string(),
+            content: "old_name".to_string(),
+            replace: "new_name".to_string(),
+            start: 3,
+            end: 11,
+            line_before: None,
+            line_after: None,
+            coercion_applied: None,
+            original_file: None,
+            renamed_file: None,
+            patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
+        });
+
+        let apply_options = ApplyOptions {
+            backup_dir: root.join(".renamify/backups"),
+            backup,
+            working_dir: Some(root.clone()),
+            ..Default::default()
+        };
+        apply_plan(&mut plan, &apply_options).unwrap();
+        assert_eq!(fs::read_to_string(&new_file).unwrap(), "fn new_name() {}\n");
+        assert!(!root
+            .join(".renamify/backups/backup_strategy/reverse_patches")
+            .exists());
+
+        let result = undo_renaming(&plan.id, &root.join(".renamify"), false);
+        if backup == BackupStrategy::GitStash {
+            result.unwrap();
+            assert!(!new_file.exists());
+            assert_eq!(fs::read_to_string(&old_file).unwrap(), initial_content);
+        } else {
+            // Without backups nothing is touched, not even the rename
+            assert!(result.unwrap_err().to_string().contains("No backups found"));
+            assert!(new_file.exists());
+        }
+    }
+}
+
+#[test]
+fn test_undo_refuses_files_modified_since_apply() {
+    let temp_dir = TempDir::new().unwrap();
+    let old_file = temp_dir.path().join("old_name.txt");
+    let new_file = temp_dir.path().join("new_name.txt");
+    fs::write(&old_file, "original").unwrap();
+
+    let mut plan = create_test_plan("modified_since_apply", "old_name", "new_name");
+    plan.paths.push(Rename {
+        path: old_file.clone(),
+        new_path: new_file.clone(),
+        kind: RenameKind::File,
+        coercion_applied: None,
+    });
+    let apply_options = ApplyOptions {
+        backup_dir: temp_dir.path().join(".renamify/backups"),
+        ..Default::default()
+    };
+    apply_plan(&mut plan, &apply_options).unwrap();
+
+    // A newer edit to the renamed file
+    fs::write(&new_file, "edited after the apply").unwrap();
+
+    let renamify_dir = temp_dir.path().join(".renamify");
+    let err = undo_renaming(&plan.id, &renamify_dir, false).unwrap_err();
+    assert_eq!(
+        renamify_core::error_kind(&err),
+        renamify_core::ErrorKind::Conflict
+    );
+    let message = err.to_string();
+    assert!(message.contains("--force-undo"), "{message}");
+    assert!(
+        message.contains(&format!("modified: {}", new_file.display())),
+        "{message}"
+    );
+    assert!(new_file.exists());
+
+    let conflicts = undo_renaming(&plan.id, &renamify_dir, true).unwrap();
+    assert_eq!(
+        conflicts,
+        vec![UndoConflict {
+            path: new_file.clone(),
+            change: LocalChange::Modified,
+        }]
+    );
+    assert!(!new_file.exists());
+    assert_eq!(
+        fs::read_to_string(&old_file).unwrap(),
+        "edited after the apply"
+    );
+}
+
+#[test]
+fn test_undo_refuses_edits_inside_renamed_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let old_dir = temp_dir.path().join("old_name");
+    let new_dir = temp_dir.path().join("new_name");
+    fs::create_dir(&old_dir).unwrap();
+    let old_file = old_dir.join("lib.rs");
+    fs::write(&old_file, "fn old_name() {}\n").unwrap();
+
+    let mut plan = create_test_plan("edit_in_renamed_dir", "old_name", "new_name");
+    plan.paths.push(Rename {
+        path: old_dir.clone(),
+        new_path: new_dir.clone(),
+        kind: RenameKind::Dir,
+        coercion_applied: None,
+    });
+    plan.matches.push(MatchHunk {
+        file: old_file.clone(),
+        line: 1,
+        byte_offset: 3,
+        char_offset: 3,
+        variant: "old_name".to_string(),
+        content: "old_name".to_string(),
+        replace: "new_name".to_string(),
+        start: 3,
+        end: 11,
+        line_before: None,
+        line_after: None,
+        coercion_applied: None,
+        original_file: None,
+        renamed_file: None,
+        patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
+    });
+    let apply_options = ApplyOptions {
+        backup_dir: temp_dir.path().join(".renamify/backups"),
+        ..Default::default()
+    };
+    apply_plan(&mut plan, &apply_options).unwrap();
+
+    // The edited file is recorded where the directory rename moved it
+    let new_file = new_dir.join("lib.rs");
+    fs::write(&new_file, "fn edited_after_the_apply() {}\n").unwrap();
+
+    let renamify_dir = temp_dir.path().join(".renamify");
+    let err = undo_renaming(&plan.id, &renamify_dir, false).unwrap_err();
+    assert_eq!(
+        renamify_core::error_kind(&err),
+        renamify_core::ErrorKind::Conflict
+    );
+    assert!(
+        err.to_string()
+            .contains(&format!("modified: {}", new_file.display())),
+        "{err}"
+    );
+    assert!(new_file.exists());
+    assert!(!old_dir.exists());
+}
@@ -0,0 +1,77 @@
+use renamify_core::atomic::AtomicConfig;
+use renamify_core::{scan_repository, PlanOptions, Style};
+use tempfile::TempDir;
+
+fn matches(
+    root: &std::path::Path,
+    search: &str,
+    replace: &str,
+    options: &PlanOptions,
+) -> Vec<(String, String)> {
+    let plan = scan_repository(root, search, replace, options).unwrap();
+    plan.matches
+        .into_iter()
+        .map(|m| (m.content, m.replace))
+        .collect()
+}
+
+#[test]
+fn test_atomic_config_keeps_acronym_settings() {
+    // An atomic config that doesn't cover the terms must not change how they
+    // are split: with acronyms disabled, HTTPSAPI is one word
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("client.rs"),
+        "let a = https_api_client();\nlet b = httpsapi_client();\n",
+    )
+    .unwrap();
+
+    let options = PlanOptions {
+        no_acronyms: true,
+        atomic_config: Some(AtomicConfig::from_flags_and_config(
+            false,
+            false,
+            false,
+            vec!["DocSpring".to_string()],
+        )),
+        ..PlanOptions::default()
+    };
+    assert_eq!(
+        matches(temp_dir.path(), "HTTPSAPIClient", "WebClient", &options),
+        [("httpsapi_client".to_string(), "web_client".to_string())]
+    );
+}
+
+#[test]
+fn test_atomic_identifiers_from_config() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("lib.rs"),
+        "docspring();\ndoc_spring();\nDOCSPRING;\n",
+    )
+    .unwrap();
+
+    let mut options = PlanOptions {
+        styles: Some(vec![Style::Snake, Style::ScreamingSnake]),
+        ..PlanOptions::default()
+    };
+    assert_eq!(
+        matches(temp_dir.path(), "DocSpring", "FormAPI", &options),
+        [("doc_spring".to_string(), "form_api".to_string())]
+    );
+
+    // Only the search term is listed, so FormAPI is still split into words
+    options.atomic_config = Some(AtomicConfig::from_flags_and_config(
+        false,
+        false,
+        false,
+        vec!["docspring".to_string()],
+    ));
+    assert_eq!(
+        matches(temp_dir.path(), "DocSpring", "FormAPI", &options),
+        [
+            ("docspring".to_string(), "form_api".to_string()),
+            ("DOCSPRING".to_string(), "FORM_API".to_string()),
+        ]
+    );
+}
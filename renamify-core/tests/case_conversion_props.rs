@@ -0,0 +1,130 @@
+//! Property tests for case conversion: random identifiers are written in every
+//! style, parsed back and converted again. Run with
+//! `cargo test -p renamify-core --features property-tests`.
+#![cfg(feature = "property-tests")]
+
+use proptest::prelude::*;
+use renamify_core::case_model::{detect_style, parse_to_tokens, to_style, Style, TokenModel};
+
+/// Styles that keep the word boundaries, so the words can be read back
+const SEPARATED_STYLES: &[Style] = &[
+    Style::Snake,
+    Style::Kebab,
+    Style::Camel,
+    Style::Pascal,
+    Style::ScreamingSnake,
+    Style::Title,
+    Style::Train,
+    Style::ScreamingTrain,
+    Style::Dot,
+    Style::Sentence,
+    Style::LowerSentence,
+    Style::UpperSentence,
+];
+
+/// Separated styles that keep each word's letter case. Uppercase styles lose
+/// it, and short uppercase words read back as acronyms (`IO`, `ID`).
+const CASE_PRESERVING_STYLES: &[Style] = &[
+    Style::Snake,
+    Style::Kebab,
+    Style::Camel,
+    Style::Pascal,
+    Style::Title,
+    Style::Train,
+    Style::Dot,
+    Style::Sentence,
+    Style::LowerSentence,
+];
+
+fn words(words: &[&str]) -> TokenModel {
+    TokenModel::new(
+        words
+            .iter()
+            .map(|word| renamify_core::case_model::Token::new(*word))
+            .collect(),
+    )
+}
+
+fn lowercase_words(model: &TokenModel) -> Vec<String> {
+    model
+        .tokens
+        .iter()
+        .map(|token| token.text.to_lowercase())
+        .collect()
+}
+
+/// Lowercase words starting with two letters, which may contain digits. A
+/// single letter is ambiguous in `camelCase` (`aBC`), as is one before digits
+/// in `PascalCase` (`A0A0` is also `a0a0` uppercased).
+fn word() -> impl Strategy<Value = String> {
+    "[a-z]{2}[a-z0-9]{0,6}"
+}
+
+fn identifier() -> impl Strategy<Value = Vec<String>> {
+    prop::collection::vec(word(), 1..5)
+}
+
+fn style() -> impl Strategy<Value = Style> {
+    prop::sample::select(Style::all_styles())
+}
+
+fn separated_style() -> impl Strategy<Value = Style> {
+    prop::sample::select(SEPARATED_STYLES)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    /// Parsing an identifier written in a separated style gives back its words
+    #[test]
+    fn no_token_loss(parts in identifier(), style in separated_style()) {
+        let model = words(&parts.iter().map(String::as_str).collect::<Vec<_>>());
+        let written = to_style(&model, style);
+        prop_assert_eq!(lowercase_words(&parse_to_tokens(&written)), parts, "{}", written);
+    }
+
+    /// Converting to a style twice is the same as converting once
+    #[test]
+    fn idempotent(parts in identifier(), style in style()) {
+        let model = words(&parts.iter().map(String::as_str).collect::<Vec<_>>());
+        let written = to_style(&model, style);
+        prop_assert_eq!(to_style(&parse_to_tokens(&written), style), written);
+    }
+
+    /// Going through another style first doesn't change the result
+    #[test]
+    fn round_trip_stable(
+        parts in identifier(),
+        via in prop::sample::select(CASE_PRESERVING_STYLES),
+        to in style(),
+    ) {
+        let model = words(&parts.iter().map(String::as_str).collect::<Vec<_>>());
+        let direct = to_style(&model, to);
+        let through = to_style(&parse_to_tokens(&to_style(&model, via)), to);
+        prop_assert_eq!(through, direct, "via {:?}", via);
+    }
+
+    /// Identifiers of several words written in a style are detected as it
+    #[test]
+    fn detects_written_style(parts in prop::collection::vec(word(), 2..5), style in prop::sample::select(&[
+        Style::Snake,
+        Style::Kebab,
+        Style::Camel,
+        Style::Pascal,
+        Style::ScreamingSnake,
+        Style::Dot,
+    ][..])) {
+        let model = words(&parts.iter().map(String::as_str).collect::<Vec<_>>());
+        let written = to_style(&model, style);
+        prop_assert_eq!(detect_style(&written), Some(style), "{}", written);
+    }
+
+    /// Arbitrary input never panics, and tokens are never empty
+    #[test]
+    fn arbitrary_input(input in any::<String>(), style in style()) {
+        let model = parse_to_tokens(&input);
+        prop_assert!(model.tokens.iter().all(|token| !token.text.is_empty()));
+        let _ = to_style(&model, style);
+        let _ = detect_style(&input);
+    }
+}
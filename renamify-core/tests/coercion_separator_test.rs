@@ -1,4 +1,5 @@
-use renamify_core::{scan_repository, PlanOptions};
+use renamify_core::coercion::Style as CoercionStyle;
+use renamify_core::{scan_repository, CoercionMode, PlanOptions};
 use std::path::PathBuf;
 use tempfile::TempDir;
 
@@ -20,6 +21,7 @@ github.com/DocSpring/oldproject
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -37,6 +39,20 @@ github.com/DocSpring/oldproject
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -71,6 +87,7 @@ MACRO(oldproject)
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -88,6 +105,20 @@ MACRO(oldproject)
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -122,6 +153,7 @@ SETTINGS[oldproject]
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -139,6 +171,20 @@ SETTINGS[oldproject]
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -173,6 +219,7 @@ Module:oldproject
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -190,6 +237,20 @@ Module:oldproject
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -226,6 +287,7 @@ fn test_coercion_preserves_case_after_at_sign() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -243,6 +305,20 @@ fn test_coercion_preserves_case_after_at_sign() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -277,6 +353,7 @@ System.oldproject.init();
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -294,6 +371,20 @@ System.oldproject.init();
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -328,6 +419,7 @@ project=oldproject
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -345,6 +437,20 @@ project=oldproject
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -379,6 +485,7 @@ fn test_coercion_preserves_case_after_space() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -396,6 +503,20 @@ fn test_coercion_preserves_case_after_space() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -433,6 +554,7 @@ fn test_coercion_preserves_case_with_quotes() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -450,6 +572,20 @@ fn test_coercion_preserves_case_with_quotes() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -485,6 +621,7 @@ MyClass{oldproject}
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -502,6 +639,20 @@ MyClass{oldproject}
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -536,6 +687,7 @@ const OLDPROJECT_VERSION = "1.0";
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -553,6 +705,20 @@ const OLDPROJECT_VERSION = "1.0";
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "oldproject", "newproject", &options).unwrap();
@@ -567,3 +733,38 @@ const OLDPROJECT_VERSION = "1.0";
         }
     }
 }
+
+#[test]
+fn test_coercion_modes_off_and_force() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    std::fs::write(root.join("test.rs"), "use tool_core::Engine;\n").unwrap();
+
+    let scan = |coerce_separators: CoercionMode| {
+        let options = PlanOptions {
+            rename_files: false,
+            rename_dirs: false,
+            coerce_separators,
+            ..PlanOptions::default()
+        };
+        let plan = scan_repository(&root, "tool", "new-tool", &options).unwrap();
+        assert_eq!(plan.coercion, coerce_separators);
+        assert_eq!(plan.matches.len(), 1);
+        plan.matches.into_iter().next().unwrap()
+    };
+
+    let auto = scan(CoercionMode::Auto);
+    assert_eq!(auto.replace, "new_tool_core");
+    assert!(auto.coercion_applied.is_some());
+
+    let off = scan(CoercionMode::Off);
+    assert_eq!(off.replace, "new_tool_core");
+    assert_eq!(off.coercion_applied, None);
+
+    let forced = scan(CoercionMode::Force(CoercionStyle::Kebab));
+    assert_eq!(forced.replace, "new-tool-core");
+    assert_eq!(
+        forced.coercion_applied.as_deref(),
+        Some("Forced Kebab style")
+    );
+}
@@ -259,6 +259,7 @@ fn test_end_to_end_coercion_with_files() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -276,6 +277,20 @@ fn test_end_to_end_coercion_with_files() {
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Auto, // Enable coercion
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -360,6 +375,7 @@ fn test_coercion_disabled() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -377,6 +393,20 @@ fn test_coercion_disabled() {
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Off, // Disable coercion
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -427,6 +457,7 @@ let config = oldtool.config.load();
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -444,6 +475,20 @@ let config = oldtool.config.load();
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -527,6 +572,7 @@ let regex_pattern = r"oldtool[_-](\w+)";
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -544,6 +590,20 @@ let regex_pattern = r"oldtool[_-](\w+)";
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -629,6 +689,7 @@ let nested = oldtool::core::pattern::Match;
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -646,6 +707,20 @@ let nested = oldtool::core::pattern::Match;
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -691,6 +766,7 @@ let snake_case_var = oldtool_core; let camelVar = oldtoolService;
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -708,6 +784,20 @@ let snake_case_var = oldtool_core; let camelVar = oldtoolService;
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -768,6 +858,7 @@ oldtool = { path = "../oldtool" }
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -785,6 +876,20 @@ oldtool = { path = "../oldtool" }
         plan_out: temp_dir.path().join("plan.json"),
         coerce_separators: CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(temp_dir.path(), "oldtool", "newtool", &options).unwrap();
@@ -28,6 +28,7 @@ type FooBarOption = i32;",
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -49,6 +50,20 @@ type FooBarOption = i32;",
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "foo_bar", "foo", &options).unwrap();
@@ -119,6 +134,7 @@ type GetFooBarOption = i32;",
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -140,6 +156,20 @@ type GetFooBarOption = i32;",
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "foo_bar", "foo", &options).unwrap();
@@ -210,6 +240,7 @@ type LoadFooBar = i32;",
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -231,6 +262,20 @@ type LoadFooBar = i32;",
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "foo_bar", "foo", &options).unwrap();
@@ -293,6 +338,7 @@ let fooBar = getFooBar();",
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -314,6 +360,20 @@ let fooBar = getFooBar();",
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     let plan = scan_repository(&root, "foo_bar", "foo", &options).unwrap();
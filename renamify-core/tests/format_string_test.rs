@@ -1,4 +1,5 @@
 use renamify_core::operations::plan::plan_operation;
+use renamify_core::{CoercionMode, LiteralMode, MatchAnchor};
 use std::fs;
 use tempfile::TempDir;
 
@@ -30,33 +31,54 @@ fn main() {
 
     // Scan and replace with all the required parameters
     let (result, _preview) = plan_operation(
-        "greattool",
-        "awesome_tool_name",
-        vec![temp_path.to_path_buf()],
-        vec![], // include
-        vec![], // exclude
-        true,   // respect_gitignore
-        0,      // unrestricted_level
-        true,   // rename_files
-        true,   // rename_dirs
-        &[],    // exclude_styles
-        &[],    // include_styles
-        &[],    // only_styles
-        vec![], // exclude_match
-        None,   // exclude_matching_lines
-        None,   // plan_out
-        None,   // preview_format
-        true,   // dry_run
-        false,  // fixed_table_width
-        false,  // use_color
-        false,  // no_acronyms
-        vec![], // include_acronyms
-        vec![], // exclude_acronyms
-        vec![], // only_acronyms
-        true,   // enable_plural_variants
-        false,  // ignore_ambiguous
-        None,   // cwd
-        None,   // atomic_config
+        "greattool",                           // search
+        "awesome_tool_name",                   // replace
+        vec![temp_path.to_path_buf()],         // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        true,                                  // rename_files
+        true,                                  // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        None,                                  // preview_format
+        true,                                  // dry_run
+        false,                                 // fixed_table_width
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        None,                                  // cwd
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
     )
     .unwrap();
 
@@ -120,33 +142,54 @@ template = "mytool_{}.tmp"
     .unwrap();
 
     let (result, _preview) = plan_operation(
-        "mytool",
-        "super_awesome_tool",
-        vec![temp_path.to_path_buf()],
-        vec![],          // include
-        vec![],          // exclude
-        true,            // respect_gitignore
-        0,               // unrestricted_level
-        true,            // rename_files
-        true,            // rename_dirs
-        &[],             // exclude_styles
-        &[],             // include_styles
-        &[],             // only_styles
-        vec![],          // exclude_match
-        None,            // exclude_matching_lines
-        None,            // plan_out
-        None,            // preview_format
-        true,            // dry_run
-        false,           // fixed_table_width
-        false,           // use_color
-        false,           // no_acronyms
-        vec![],          // include_acronyms
-        vec![],          // exclude_acronyms
-        vec![],          // only_acronyms
-        true,            // enable_plural_variants
-        false,           // ignore_ambiguous
-        Some(temp_path), // cwd - set to temp dir (expects &Path)
-        None,            // atomic_config
+        "mytool",                              // search
+        "super_awesome_tool",                  // replace
+        vec![temp_path.to_path_buf()],         // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        true,                                  // rename_files
+        true,                                  // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        None,                                  // preview_format
+        true,                                  // dry_run
+        false,                                 // fixed_table_width
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(temp_path),                       // cwd - set to temp dir (expects &Path)
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
     )
     .unwrap();
 
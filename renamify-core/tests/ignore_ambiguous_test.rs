@@ -1,4 +1,5 @@
 use renamify_core::operations::plan::plan_operation;
+use renamify_core::{CoercionMode, LiteralMode, MatchAnchor};
 use std::fs;
 use tempfile::TempDir;
 
@@ -12,33 +13,54 @@ fn ignore_ambiguous_flag_skips_plain_words() {
 
     // Baseline without ignore flag should capture both occurrences.
     let (baseline, _) = plan_operation(
-        "config",
-        "setting",
-        vec![root.to_path_buf()],
-        vec![],
-        vec![],
-        true,
-        0,
-        false,
-        false,
-        &[],
-        &[],
-        &[],
-        vec![],
-        None,
-        None,
-        None,
-        true,
-        false,
-        false,
-        false,
-        vec![],
-        vec![],
-        vec![],
-        true,
-        false,
-        Some(root),
-        None,
+        "config",                              // search
+        "setting",                             // replace
+        vec![root.to_path_buf()],              // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        false,                                 // rename_files
+        false,                                 // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        None,                                  // preview_format
+        true,                                  // dry_run
+        false,                                 // fixed_table_width
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        false,                                 // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(root),                            // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
     )
     .expect("baseline plan");
 
@@ -51,33 +73,54 @@ fn ignore_ambiguous_flag_skips_plain_words() {
 
     // Now enable ignore_ambiguous and ensure we skip ambiguous identifiers like `config`.
     let (filtered, _) = plan_operation(
-        "config",
-        "setting",
-        vec![root.to_path_buf()],
-        vec![],
-        vec![],
-        true,
-        0,
-        false,
-        false,
-        &[],
-        &[],
-        &[],
-        vec![],
-        None,
-        None,
-        None,
-        true,
-        false,
-        false,
-        false,
-        vec![],
-        vec![],
-        vec![],
-        true,
-        true,
-        Some(root),
-        None,
+        "config",                              // search
+        "setting",                             // replace
+        vec![root.to_path_buf()],              // paths
+        None,                                  // buffer
+        vec![],                                // include
+        vec![],                                // exclude
+        true,                                  // respect_gitignore
+        0,                                     // unrestricted_level
+        false,                                 // rename_files
+        false,                                 // rename_dirs
+        &[],                                   // exclude_styles
+        &[],                                   // include_styles
+        &[],                                   // only_styles
+        vec![],                                // exclude_match
+        None,                                  // exclude_matching_lines
+        vec![],                                // exclude_lines_in_file
+        None,                                  // plan_out
+        None,                                  // preview_format
+        true,                                  // dry_run
+        false,                                 // fixed_table_width
+        renamify_core::GroupBy::File,          // group_by
+        None,                                  // preview_sample
+        false,                                 // use_color
+        false,                                 // no_acronyms
+        vec![],                                // include_acronyms
+        vec![],                                // exclude_acronyms
+        vec![],                                // only_acronyms
+        None,                                  // acronym_rendering
+        None,                                  // case_mapping
+        true,                                  // enable_plural_variants
+        true,                                  // ignore_ambiguous
+        false,                                 // allow_case_collisions
+        LiteralMode::All,                      // literal_mode
+        MatchAnchor::Any,                      // match_anchor
+        CoercionMode::Auto,                    // coerce_separators
+        None,                                  // structured
+        None,                                  // profile
+        false,                                 // report_skipped
+        false,                                 // swap
+        0,                                     // fuzzy
+        false,                                 // include_submodules
+        renamify_core::WalkOptions::default(), // walk
+        false,                                 // paths_only
+        false,                                 // contents_only
+        Some(root),                            // working_dir
+        None,                                  // atomic_config
+        None,                                  // guards
+        None,                                  // observer
     )
     .expect("filtered plan");
 
@@ -0,0 +1,146 @@
+use renamify_core::{
+    apply_plan_observed, error_kind, scan_repository, scan_repository_observed, ApplyOptions,
+    ErrorKind, MatchHunk, OperationObserver, PlanOptions,
+};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tempfile::TempDir;
+
+/// Records progress and cancels once `cancel_after` events have been seen
+struct Recorder {
+    files: Mutex<Vec<(usize, usize)>>,
+    hunks: Mutex<Vec<(String, usize, usize)>>,
+    events: AtomicUsize,
+    cancel_after: usize,
+}
+
+impl Recorder {
+    fn new(cancel_after: usize) -> Self {
+        Self {
+            files: Mutex::new(Vec::new()),
+            hunks: Mutex::new(Vec::new()),
+            events: AtomicUsize::new(0),
+            cancel_after,
+        }
+    }
+}
+
+impl OperationObserver for Recorder {
+    fn on_file_scanned(&self, _path: &Path, scanned: usize, total: usize) {
+        self.files.lock().unwrap().push((scanned, total));
+        self.events.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_hunk_applied(&self, hunk: &MatchHunk, applied: usize, total: usize) {
+        self.hunks
+            .lock()
+            .unwrap()
+            .push((hunk.content.clone(), applied, total));
+        self.events.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn should_cancel(&self) -> bool {
+        self.events.load(Ordering::SeqCst) >= self.cancel_after
+    }
+}
+
+fn setup() -> (TempDir, PlanOptions) {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("a.rs"), "fn old_name() {}\n").unwrap();
+    std::fs::write(root.join("b.rs"), "old_name();\nOldName::new();\n").unwrap();
+    std::fs::write(root.join("c.txt"), "nothing here\n").unwrap();
+    let options = PlanOptions {
+        rename_files: false,
+        rename_dirs: false,
+        ..PlanOptions::default()
+    };
+    (temp_dir, options)
+}
+
+fn apply_options(root: &Path) -> ApplyOptions {
+    ApplyOptions {
+        backup_dir: root.join(".renamify/backups"),
+        log_file: None,
+        ..ApplyOptions::default()
+    }
+}
+
+#[test]
+fn test_scan_reports_each_file() {
+    let (temp_dir, options) = setup();
+    let recorder = Recorder::new(usize::MAX);
+
+    let plan = scan_repository_observed(
+        &[temp_dir.path().to_path_buf()],
+        "old_name",
+        "new_name",
+        &options,
+        Some(&recorder),
+    )
+    .unwrap();
+
+    assert_eq!(plan.stats.total_matches, 3);
+    let mut files = recorder.files.lock().unwrap().clone();
+    files.sort_unstable();
+    assert_eq!(files, [(1, 3), (2, 3), (3, 3)]);
+}
+
+#[test]
+fn test_scan_cancelled() {
+    let (temp_dir, options) = setup();
+    let recorder = Recorder::new(0);
+
+    let err = scan_repository_observed(
+        &[temp_dir.path().to_path_buf()],
+        "old_name",
+        "new_name",
+        &options,
+        Some(&recorder),
+    )
+    .unwrap_err();
+
+    assert_eq!(error_kind(&err), ErrorKind::Cancelled);
+    assert!(recorder.files.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_apply_reports_each_hunk() {
+    let (temp_dir, options) = setup();
+    let root = temp_dir.path();
+    let mut plan = scan_repository(root, "old_name", "new_name", &options).unwrap();
+    let recorder = Recorder::new(usize::MAX);
+
+    apply_plan_observed(&mut plan, &apply_options(root), Some(&recorder)).unwrap();
+
+    let hunks = recorder.hunks.lock().unwrap().clone();
+    let progress: Vec<(usize, usize)> = hunks.iter().map(|(_, n, total)| (*n, *total)).collect();
+    assert_eq!(progress, [(1, 3), (2, 3), (3, 3)]);
+    assert_eq!(
+        std::fs::read_to_string(root.join("b.rs")).unwrap(),
+        "new_name();\nNewName::new();\n"
+    );
+}
+
+#[test]
+fn test_apply_cancelled_rolls_back() {
+    let (temp_dir, options) = setup();
+    let root = temp_dir.path();
+    let mut plan = scan_repository(root, "old_name", "new_name", &options).unwrap();
+
+    // Cancel once the first file's hunk has been written
+    let recorder = Recorder::new(1);
+    let err = apply_plan_observed(&mut plan, &apply_options(root), Some(&recorder)).unwrap_err();
+
+    assert_eq!(error_kind(&err), ErrorKind::Cancelled);
+    assert_eq!(recorder.hunks.lock().unwrap().len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(root.join("a.rs")).unwrap(),
+        "fn old_name() {}\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("b.rs")).unwrap(),
+        "old_name();\nOldName::new();\n"
+    );
+}
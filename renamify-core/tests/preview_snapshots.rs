@@ -29,8 +29,8 @@ fn create_sample_plan() -> Plan {
             MatchHunk {
                 file: PathBuf::from("src/models/user.rs"),
                 line: 15,
-                byte_offset: 12,
-                char_offset: 12,
+                byte_offset: 8,
+                char_offset: 8,
                 variant: "user_name".to_string(),
                 content: "user_name".to_string(),
                 replace: "customer_name".to_string(),
@@ -42,6 +42,10 @@ fn create_sample_plan() -> Plan {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             },
             MatchHunk {
                 file: PathBuf::from("src/models/user.rs"),
@@ -59,12 +63,16 @@ fn create_sample_plan() -> Plan {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             },
             MatchHunk {
                 file: PathBuf::from("src/api/handlers.rs"),
                 line: 42,
-                byte_offset: 16,
-                char_offset: 16,
+                byte_offset: 7,
+                char_offset: 7,
                 variant: "UserName".to_string(),
                 content: "UserName".to_string(),
                 replace: "CustomerName".to_string(),
@@ -76,12 +84,16 @@ fn create_sample_plan() -> Plan {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             },
             MatchHunk {
                 file: PathBuf::from("src/api/handlers.rs"),
                 line: 50,
-                byte_offset: 20,
-                char_offset: 20,
+                byte_offset: 11,
+                char_offset: 11,
                 variant: "user_name".to_string(),
                 content: "user_name".to_string(),
                 replace: "customer_name".to_string(),
@@ -93,6 +105,10 @@ fn create_sample_plan() -> Plan {
                 original_file: None,
                 renamed_file: None,
                 patch_hash: None,
+                style: None,
+                match_kind: None,
+                boundary_before: None,
+                boundary_after: None,
             },
         ],
         paths: vec![
@@ -114,8 +130,15 @@ fn create_sample_plan() -> Plan {
             total_matches: 6,
             matches_by_variant,
             files_with_matches: 2,
+            ..Default::default()
         },
         version: "1.0.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     }
 }
@@ -176,8 +199,15 @@ fn test_empty_plan_table_snapshot() {
             total_matches: 0,
             matches_by_variant: HashMap::new(),
             files_with_matches: 0,
+            ..Default::default()
         },
         version: "1.0.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     };
 
@@ -202,8 +232,15 @@ fn test_empty_plan_diff_snapshot() {
             total_matches: 0,
             matches_by_variant: HashMap::new(),
             files_with_matches: 0,
+            ..Default::default()
         },
         version: "1.0.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     };
 
@@ -244,6 +281,10 @@ fn test_root_directory_rename_handling() {
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
         }],
         paths: vec![
             // Only regular directory rename - root directory rename is filtered out
@@ -260,8 +301,15 @@ fn test_root_directory_rename_handling() {
             total_matches: 1,
             matches_by_variant,
             files_with_matches: 1,
+            ..Default::default()
         },
         version: "1.0.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     };
 
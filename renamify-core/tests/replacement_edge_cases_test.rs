@@ -36,12 +36,16 @@ let mixed = renamify_someCAMEL-case;
     // Apply the plan
     let backup_dir = temp_dir.path().join("backups");
     let apply_opts = ApplyOptions {
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         backup_dir,
         commit: false,
+        commit_options: Default::default(),
+        stage: false,
         force: false,
         skip_symlinks: false,
         log_file: None,
+        working_dir: None,
+        durability: renamify_core::Durability::default(),
     };
 
     apply_plan(&mut plan, &apply_opts).unwrap();
@@ -134,12 +138,16 @@ let env = std::env::var("RENAMIFY_ENABLED").unwrap_or_default();
 
     let backup_dir = temp_dir.path().join("backups");
     let apply_opts = ApplyOptions {
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         backup_dir,
         commit: false,
+        commit_options: Default::default(),
+        stage: false,
         force: false,
         skip_symlinks: false,
         log_file: None,
+        working_dir: None,
+        durability: renamify_core::Durability::default(),
     };
 
     apply_plan(&mut plan, &apply_opts).unwrap();
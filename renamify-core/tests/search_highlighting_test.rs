@@ -31,6 +31,10 @@ fn test_search_highlighting_positions() {
         original_file: None,
         renamed_file: None,
         patch_hash: None,
+        style: None,
+        match_kind: None,
+        boundary_before: None,
+        boundary_after: None,
     });
 
     let plan = Plan {
@@ -48,8 +52,15 @@ fn test_search_highlighting_positions() {
             total_matches: 1,
             matches_by_variant: HashMap::new(),
             files_with_matches: 1,
+            ..Default::default()
         },
         version: "0.1.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     };
 
@@ -134,6 +145,10 @@ fn test_search_highlighting_multiple_matches() {
             original_file: None,
             renamed_file: None,
             patch_hash: None,
+            style: None,
+            match_kind: None,
+            boundary_before: None,
+            boundary_after: None,
         });
     }
 
@@ -152,8 +167,15 @@ fn test_search_highlighting_multiple_matches() {
             total_matches: 3,
             matches_by_variant: HashMap::new(),
             files_with_matches: 1,
+            ..Default::default()
         },
         version: "0.1.0".to_string(),
+        coercion: renamify_core::scanner::CoercionMode::Auto,
+        skipped: vec![],
+        near_misses: vec![],
+        suspicious_renames: vec![],
+        warnings: vec![],
+        options: None,
         created_directories: None,
     };
 
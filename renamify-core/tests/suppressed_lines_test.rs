@@ -0,0 +1,105 @@
+use renamify_core::suppress::FileLines;
+use renamify_core::{scan_repository_multi, PlanOptions, SkipReason};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_inline_ignore_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        r#"fn old_name() {}
+const LEGACY: &str = "old_name"; // renamify:ignore-line
+// renamify:ignore-next-line
+pub use old_name as compat;
+/* renamify:ignore-start */
+// old_name is kept for the v1 API
+pub fn old_name_v1() {}
+/* renamify:ignore-end */
+let x = old_name();
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("config.yml"),
+        "name: old_name # renamify:ignore-line\nother: old_name\n",
+    )
+    .unwrap();
+
+    let options = PlanOptions {
+        report_skipped: true,
+        ..Default::default()
+    };
+    let plan = scan_repository_multi(
+        &[temp_dir.path().to_path_buf()],
+        "old_name",
+        "new_name",
+        &options,
+    )
+    .unwrap();
+
+    let mut kept: Vec<(String, u64)> = plan
+        .matches
+        .iter()
+        .map(|hunk| {
+            let name = hunk.file.file_name().unwrap().to_string_lossy();
+            (name.to_string(), hunk.line)
+        })
+        .collect();
+    kept.sort();
+    assert_eq!(
+        kept,
+        vec![
+            ("config.yml".to_string(), 2),
+            ("lib.rs".to_string(), 1),
+            ("lib.rs".to_string(), 9),
+        ]
+    );
+    assert_eq!(plan.skipped.len(), 5);
+    assert!(plan
+        .skipped
+        .iter()
+        .all(|skipped| skipped.reason == SkipReason::InlineIgnore));
+}
+
+#[test]
+fn test_exclude_lines_in_file() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("a.rs"),
+        "old_name\nold_name\nold_name\n// old_name\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("b.rs"), "old_name\nold_name\n").unwrap();
+
+    let options = PlanOptions {
+        exclude_lines_in_file: vec!["a.rs:1-2".parse::<FileLines>().unwrap()],
+        exclude_matching_lines: Some(r"^\s*//".to_string()),
+        working_dir: Some(temp_dir.path().to_path_buf()),
+        report_skipped: true,
+        ..Default::default()
+    };
+    let plan = scan_repository_multi(
+        &[temp_dir.path().to_path_buf()],
+        "old_name",
+        "new_name",
+        &options,
+    )
+    .unwrap();
+
+    // a.rs keeps line 3 only; b.rs is not named, so both lines stay
+    assert_eq!(plan.stats.total_matches, 3);
+    let reasons: Vec<(Option<u64>, SkipReason)> = plan
+        .skipped
+        .iter()
+        .map(|skipped| (skipped.line, skipped.reason))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            (Some(1), SkipReason::ExcludeLinesInFile),
+            (Some(2), SkipReason::ExcludeLinesInFile),
+            (Some(4), SkipReason::ExcludeMatchingLines),
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+use renamify_core::{
+    render_plan_in, scan_repository, ApplyOptions, Config, GroupBy, Plan, PlanOptions, Preview,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_core_types_are_send_and_sync() {
+    assert_send_sync::<Plan>();
+    assert_send_sync::<PlanOptions>();
+    assert_send_sync::<ApplyOptions>();
+    assert_send_sync::<Config>();
+}
+
+fn render(root: &Path, format: Preview) -> String {
+    let options = PlanOptions::default();
+    let plan = scan_repository(root, "old_name", "new_name", &options).unwrap();
+    render_plan_in(
+        &plan,
+        format,
+        Some(false),
+        true,
+        GroupBy::File,
+        None,
+        Some(root),
+    )
+}
+
+#[test]
+fn test_concurrent_plans_render_relative_to_their_root() {
+    let repos: Vec<TempDir> = (0..4)
+        .map(|i| {
+            let temp_dir = TempDir::new().unwrap();
+            let dir = temp_dir.path().join(format!("src{i}"));
+            std::fs::create_dir(&dir).unwrap();
+            std::fs::write(dir.join("lib.rs"), "fn old_name() {}\n").unwrap();
+            temp_dir
+        })
+        .collect();
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = repos
+            .iter()
+            .map(|repo| {
+                scope.spawn(|| {
+                    [
+                        Preview::Table,
+                        Preview::Diff,
+                        Preview::Matches,
+                        Preview::Summary,
+                    ]
+                    .map(|format| render(repo.path(), format))
+                })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let expected = format!("src{i}/lib.rs");
+            for output in handle.join().unwrap() {
+                assert!(output.contains(&expected), "{output}");
+                assert!(
+                    !output.contains(&*repos[i].path().to_string_lossy()),
+                    "{output}"
+                );
+            }
+        }
+    });
+}
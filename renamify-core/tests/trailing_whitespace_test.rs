@@ -1,4 +1,7 @@
-use renamify_core::{apply_operation, scan_repository, undo_operation, PlanOptions};
+use renamify_core::{
+    apply_operation, scan_repository, undo_operation, PlanOptions, PlanTrust, StageMode,
+    TransactionScope,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -60,7 +63,22 @@ fn test_preserve_trailing_whitespace_in_patches() {
     std::fs::write(&plan_path, plan_json).unwrap();
 
     // Apply the plan - pass the working directory instead of changing directory
-    apply_operation(None, None, false, false, Some(root)).unwrap();
+    apply_operation(
+        None,
+        None,
+        &PlanTrust::default(),
+        None,
+        StageMode::Unstaged,
+        false,
+        false,
+        false,
+        TransactionScope::Plan,
+        renamify_core::Durability::default(),
+        renamify_core::BackupStrategy::default(),
+        Some(root),
+        None,
+    )
+    .unwrap();
 
     // Read the modified file and verify replacements were made
     let content = std::fs::read_to_string(&test_file).unwrap();
@@ -80,7 +98,7 @@ fn test_preserve_trailing_whitespace_in_patches() {
     );
 
     // Now test undo - this is where the bug manifests
-    let undo_result = undo_operation("latest", Some(root));
+    let undo_result = undo_operation("latest", false, Some(root));
 
     if let Err(e) = undo_result {
         // If undo fails, it's likely due to the trailing whitespace bug
@@ -171,10 +189,26 @@ fn test_patches_preserve_exact_line_content() {
     }
 
     // Apply the plan - pass the working directory instead of changing directory
-    apply_operation(None, None, false, false, Some(root)).unwrap();
+    apply_operation(
+        None,
+        None,
+        &PlanTrust::default(),
+        None,
+        StageMode::Unstaged,
+        false,
+        false,
+        false,
+        TransactionScope::Plan,
+        renamify_core::Durability::default(),
+        renamify_core::BackupStrategy::default(),
+        Some(root),
+        None,
+    )
+    .unwrap();
 
     // Undo should work without issues
-    undo_operation("latest", Some(root)).expect("Undo should work with preserved whitespace");
+    undo_operation("latest", false, Some(root))
+        .expect("Undo should work with preserved whitespace");
 
     // Verify exact restoration
     let restored_content = std::fs::read_to_string(&test_file).unwrap();
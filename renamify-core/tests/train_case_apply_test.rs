@@ -30,6 +30,7 @@ fn test_train_case_patterns_are_replaced_in_apply() {
     let options = PlanOptions {
         exclude_match: vec![],
         exclude_matching_lines: None,
+        exclude_lines_in_file: vec![],
         no_acronyms: false,
         include_acronyms: vec![],
         exclude_acronyms: vec![],
@@ -47,6 +48,20 @@ fn test_train_case_patterns_are_replaced_in_apply() {
         plan_out: PathBuf::from("plan.json"),
         coerce_separators: renamify_core::scanner::CoercionMode::Auto,
         enable_plural_variants: true,
+        allow_case_collisions: false,
+        literal_mode: renamify_core::LiteralMode::All,
+        structured: None,
+        sql: false,
+        report_skipped: false,
+        fuzzy: 0,
+        include_submodules: false,
+        paths_only: false,
+        contents_only: false,
+        match_anchor: renamify_core::MatchAnchor::Any,
+        working_dir: None,
+        walk: renamify_core::WalkOptions::default(),
+        acronym_rendering: renamify_core::acronym::AcronymRendering::default(),
+        case_mapping: renamify_core::case_model::CaseMapping::default(),
     };
 
     // Create the plan
@@ -63,11 +78,15 @@ fn test_train_case_patterns_are_replaced_in_apply() {
     // Apply the plan
     let apply_options = ApplyOptions {
         backup_dir: root.join(".backups"),
-        create_backups: true,
+        backup: renamify_core::BackupStrategy::Copy,
         commit: false,
+        commit_options: Default::default(),
+        stage: false,
         force: false,
         skip_symlinks: false,
         log_file: None,
+        working_dir: None,
+        durability: renamify_core::Durability::default(),
     };
 
     apply_plan(&mut plan, &apply_options).unwrap();
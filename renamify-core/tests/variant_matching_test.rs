@@ -34,11 +34,11 @@ fn test_variant_matching_preserves_case_in_paths() {
         let content = input.as_bytes();
 
         // Find the match
-        let matches: Vec<_> = pattern.regex.find_iter(content).collect();
+        let matches: Vec<_> = pattern.find_iter(content).collect();
         assert!(!matches.is_empty(), "Should find a match in '{}'", input);
 
         let m = &matches[0];
-        let match_text = m.as_bytes();
+        let match_text = &content[m.start..m.end];
         let match_str = std::str::from_utf8(match_text).unwrap();
 
         // Verify we matched the right text
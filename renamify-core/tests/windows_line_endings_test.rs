@@ -1,5 +1,7 @@
 #[cfg(windows)]
-use renamify_core::{apply_operation, scan_repository, PlanOptions};
+use renamify_core::{
+    apply_operation, scan_repository, PlanOptions, PlanTrust, StageMode, TransactionScope,
+};
 #[cfg(windows)]
 use std::fs;
 #[cfg(windows)]
@@ -37,7 +39,22 @@ fn test_patches_have_crlf_line_endings_on_windows() {
     fs::write(&plan_path, plan_json).unwrap();
 
     // Apply the plan - this should create patches
-    apply_operation(None, None, false, false, Some(root)).unwrap();
+    apply_operation(
+        None,
+        None,
+        &PlanTrust::default(),
+        None,
+        StageMode::Unstaged,
+        false,
+        false,
+        false,
+        TransactionScope::Plan,
+        renamify_core::Durability::default(),
+        renamify_core::BackupStrategy::default(),
+        Some(root),
+        None,
+    )
+    .unwrap();
 
     // Find all patch files
     let backups_dir = renamify_dir.join("backups");
@@ -125,8 +142,22 @@ fn test_patch_application_with_crlf_files() {
     fs::write(&plan_path, serde_json::to_string_pretty(&plan).unwrap()).unwrap();
 
     // Apply should succeed
-    apply_operation(None, None, false, false, Some(root))
-        .expect("Apply should succeed with CRLF files");
+    apply_operation(
+        None,
+        None,
+        &PlanTrust::default(),
+        None,
+        StageMode::Unstaged,
+        false,
+        false,
+        false,
+        TransactionScope::Plan,
+        renamify_core::Durability::default(),
+        renamify_core::BackupStrategy::default(),
+        Some(root),
+        None,
+    )
+    .expect("Apply should succeed with CRLF files");
 
     // Verify files were modified
     let content1 = fs::read_to_string(&file1).unwrap();
@@ -137,7 +168,8 @@ fn test_patch_application_with_crlf_files() {
 
     // Most importantly: undo should also work
     use renamify_core::undo_operation;
-    undo_operation("latest", Some(root)).expect("Undo should succeed with CRLF patches and files");
+    undo_operation("latest", false, Some(root))
+        .expect("Undo should succeed with CRLF patches and files");
 
     // Verify files were restored
     let restored1 = fs::read_to_string(&file1).unwrap();
@@ -152,7 +184,10 @@ fn test_patch_application_with_crlf_files() {
 // This test runs on all platforms to ensure we don't break non-Windows systems
 #[test]
 fn test_line_endings_preserved_per_platform() {
-    use renamify_core::{apply_operation, scan_repository, undo_operation, PlanOptions};
+    use renamify_core::{
+        apply_operation, scan_repository, undo_operation, PlanOptions, PlanTrust, StageMode,
+        TransactionScope,
+    };
     use std::fs;
     use tempfile::TempDir;
 
@@ -178,10 +213,24 @@ fn test_line_endings_preserved_per_platform() {
     fs::write(&plan_path, serde_json::to_string_pretty(&plan).unwrap()).unwrap();
 
     // Apply and undo should both work
-    apply_operation(None, None, false, false, Some(root))
-        .expect("Apply should work on all platforms");
-
-    undo_operation("latest", Some(root)).expect("Undo should work on all platforms");
+    apply_operation(
+        None,
+        None,
+        &PlanTrust::default(),
+        None,
+        StageMode::Unstaged,
+        false,
+        false,
+        false,
+        TransactionScope::Plan,
+        renamify_core::Durability::default(),
+        renamify_core::BackupStrategy::default(),
+        Some(root),
+        None,
+    )
+    .expect("Apply should work on all platforms");
+
+    undo_operation("latest", false, Some(root)).expect("Undo should work on all platforms");
 
     // Verify content is restored exactly
     let restored = fs::read_to_string(&test_file).unwrap();
@@ -170,7 +170,7 @@ export class RenamifyService {
     this.addDryRunArg(args, options.dryRun);
     this.addRenameArgs(args, options.renameFiles, options.renameDirs);
     this.addIgnoreAmbiguousArg(args, options.ignoreAmbiguous);
-    this.addAtomicSearchArg(args, options.atomicSearch);
+    this.addAtomicArg(args, options.atomicSearch);
     this.addPluralArg(args, options.enablePluralVariants);
 
     return args;
@@ -191,8 +191,7 @@ export class RenamifyService {
     this.addDryRunArg(args, options.dryRun);
     this.addRenameArgs(args, options.renameFiles, options.renameDirs);
     this.addIgnoreAmbiguousArg(args, options.ignoreAmbiguous);
-    this.addAtomicSearchArg(args, options.atomicSearch);
-    this.addAtomicReplaceArg(args, options.atomicReplace);
+    this.addAtomicArg(args, options.atomicSearch, options.atomicReplace);
     this.addPluralArg(args, options.enablePluralVariants);
 
     return args;
@@ -254,15 +253,17 @@ export class RenamifyService {
     }
   }
 
-  private addAtomicSearchArg(args: string[], atomicSearch?: boolean): void {
-    if (atomicSearch) {
-      args.push('--atomic-search');
-    }
-  }
-
-  private addAtomicReplaceArg(args: string[], atomicReplace?: boolean): void {
-    if (atomicReplace) {
-      args.push('--atomic-replace');
+  private addAtomicArg(
+    args: string[],
+    atomicSearch?: boolean,
+    atomicReplace?: boolean
+  ): void {
+    if (atomicSearch && atomicReplace) {
+      args.push('--atomic');
+    } else if (atomicSearch) {
+      args.push('--atomic=search');
+    } else if (atomicReplace) {
+      args.push('--atomic=replace');
     }
   }
 
@@ -488,12 +489,7 @@ export class RenamifyService {
     }
 
     // Add atomic flags
-    if (options.atomicSearch) {
-      args.push('--atomic-search');
-    }
-    if (options.atomicReplace) {
-      args.push('--atomic-replace');
-    }
+    this.addAtomicArg(args, options.atomicSearch, options.atomicReplace);
 
     // Add dry-run flag
     if (options.dryRun) {
@@ -11,6 +11,22 @@ type VersionInfo = {
   version: string;
 };
 
+function atomicFlag(
+  atomicSearch?: boolean,
+  atomicReplace?: boolean
+): string | undefined {
+  if (atomicSearch && atomicReplace) {
+    return '--atomic';
+  }
+  if (atomicSearch) {
+    return '--atomic=search';
+  }
+  if (atomicReplace) {
+    return '--atomic=replace';
+  }
+  return;
+}
+
 export class RenamifyCliService {
   private readonly extensionVersion: string;
   private readonly processCoordinator: ProcessCoordinator;
@@ -215,8 +231,9 @@ export class RenamifyCliService {
       args.push('--no-rename-paths');
     }
 
-    if (options.atomicSearch) {
-      args.push('--atomic-search');
+    const atomic = atomicFlag(options.atomicSearch);
+    if (atomic) {
+      args.push(atomic);
     }
 
     if (options.enablePluralVariants === false) {
@@ -276,12 +293,9 @@ export class RenamifyCliService {
       args.push('--no-rename-paths');
     }
 
-    if (options.atomicSearch) {
-      args.push('--atomic-search');
-    }
-
-    if (options.atomicReplace) {
-      args.push('--atomic-replace');
+    const atomic = atomicFlag(options.atomicSearch, options.atomicReplace);
+    if (atomic) {
+      args.push(atomic);
     }
 
     if (options.enablePluralVariants === false) {
@@ -337,12 +351,9 @@ export class RenamifyCliService {
       args.push('--no-rename-paths');
     }
 
-    if (options.atomicSearch) {
-      args.push('--atomic-search');
-    }
-
-    if (options.atomicReplace) {
-      args.push('--atomic-replace');
+    const atomic = atomicFlag(options.atomicSearch, options.atomicReplace);
+    if (atomic) {
+      args.push(atomic);
     }
 
     if (options.enablePluralVariants === false) {